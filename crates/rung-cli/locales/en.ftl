@@ -0,0 +1,189 @@
+# English messages for rung's human-readable output.
+#
+# To translate, copy this file to ~/.config/rung/locales/<lang>.ftl (for
+# example `de.ftl` or `pt-BR.ftl`) and translate the text after each `=`.
+# Keep the ids and the `{ $name }` placeables as they are; messages left out
+# of a translation are shown in English.
+
+## Shared
+
+noun-branch = branch
+noun-branches = branches
+ready-marked = PR #{ $pr } ({ $branch }) passed its checks - marked it ready for review
+ready-failed = Could not mark PR #{ $pr } ready for review: { $error }
+ready-check-failed = Could not check PR #{ $pr }: { $error }
+github-anonymous = No GitHub token - reading without one, which works for public repositories (60 requests an hour)
+github-budget-spent = GitHub's rate limit for requests without a token is used up - skipping the rest (set GITHUB_TOKEN to lift it)
+
+## Next-step hints (`general.hints`)
+
+hint-prefix = hint:
+hint-stage-resolved = Mark each file resolved with `git add { $files }` before continuing
+
+## Accessible mode, spelling out what glyphs and colors show
+
+a11y-error = Error:
+a11y-warning = Warning:
+a11y-ok = ok
+a11y-suggestion = Suggestion:
+a11y-current-branch = current branch: { $branch }
+a11y-branch = branch: { $branch }
+a11y-parent = parent: { $branch }
+a11y-packages = packages: { $packages }
+a11y-pr = PR #{ $pr }
+a11y-synced = status: synced
+a11y-behind = status: behind by { $count }
+a11y-restack = status: needs restack
+a11y-conflict = status: conflict
+a11y-detached = status: detached
+a11y-ci-passing = CI: passing
+a11y-ci-pending = CI: pending
+a11y-ci-failing = CI: failing
+
+## rung status
+
+status-empty = No branches in stack yet. Use `rung create <name>` to add one.
+status-watching = Watching for changes to stack branches (Ctrl-C to stop)...
+status-watch-changed = '{ $branch }' changed - { $descendants } now need(s) restacking; run `rung sync`
+status-no-remote = No GitHub remote - skipping CI status
+status-reviews-failed = Could not fetch reviews for { $branch }: { $error }
+status-checks-failed = Could not fetch checks for { $branch }: { $error }
+status-comments-failed = Could not fetch comments for { $branch }: { $error }
+status-stale-comment = The stack comment on #{ $pr } ({ $branch }) doesn't match the stack
+status-stale-comment-fix = Run `rung submit --comments-only` to update the comments without pushing
+status-heading = Stack
+status-more-above = { $count } more { $noun } above (`rung status --all` shows them)
+status-more-below = { $count } more { $noun } below (`rung status --all` shows them)
+status-optional-failing = (optional check failing)
+status-waiting-for = (waiting on { $reviewers } for { $days }d)
+status-waiting = (waiting on { $reviewers })
+status-stale = (stale { $days }d)
+status-no-commits = (no commits)
+status-outside-scope = ({ $count } outside scope)
+status-legend-synced = synced
+status-legend-needs-sync = needs sync/restack
+status-legend-conflict = conflict
+status-github-matches = Stack matches GitHub
+status-github-heading = GitHub
+status-github-untracked = #{ $pr } { $head } → { $base } is open but not in the stack
+status-github-merged = { $branch } #{ $pr } is merged
+status-github-closed = { $branch } #{ $pr } is closed
+status-github-base = { $branch } #{ $pr } is based on { $actual }, not { $expected }
+status-github-missing = { $branch } #{ $pr } was not found
+status-github-adopt = Run `rung adopt` to add the untracked PRs to the stack
+status-at-heading = Stack at backup { $backup } ({ $time })
+status-at-unchanged = (unchanged)
+status-at-moved = (now { $commit })
+status-at-deleted = (deleted)
+status-at-added = Added since: { $branches }
+status-at-undo-hint = This is the latest backup - `rung undo` puts the moved branches back
+
+## rung sync
+
+sync-merged = Merged PR #{ $pr } ({ $branch }) into { $base }
+sync-closed = PR #{ $pr } ({ $branch }) was closed and its branch deleted - removed it from the stack, keeping the local branch
+sync-reparented = Re-parented { $branch }: { $old } → { $new }
+sync-renamed = PR #{ $pr }'s branch was renamed on GitHub - renamed { $branch } to { $new }
+sync-renamed-remote = PR #{ $pr }'s branch was renamed on GitHub - { $branch } now pushes to { $remote }
+sync-removed = Removed { $branch } (branch no longer exists)
+sync-rebased = Rebased { $branch }: { $old } → { $new }
+sync-retargeted = Retargeted PR #{ $pr }: { $old } → { $new }
+sync-pushed = Pushed { $branches }
+sync-commented = Commented on { $prs }
+sync-requires-conflict = '{ $branch }' requires '{ $required }', which can only land after it - run `rung requires` on '{ $branch }' to fix
+sync-ready = Marked ready for review: { $prs }
+sync-ready-failed = Could not check drafts waiting for green checks: { $error }
+sync-summary = Summary:
+sync-range-diff = Range-diff for { $branch }:
+sync-abort-cancelled = Abort cancelled
+sync-aborted = Sync aborted - branches restored from backup
+sync-continuing = Continuing sync...
+sync-fetching = Fetching { $branch }...
+sync-fetch-failed = Could not fetch { $branch }: { $error }
+sync-unchanged = Nothing changed since the last sync
+sync-empty = No branches in stack - nothing to sync
+sync-syncing = Syncing { $count } branches...
+sync-dry-run = Dry run - would perform the following:
+sync-dry-run-merged = Merged PR #{ $pr } ({ $branch }) into { $base }
+sync-dry-run-closed = Closed PR #{ $pr } ({ $branch }) - branch deleted on GitHub, remove it from the stack
+sync-dry-run-reparent = Re-parent { $branch }: { $old } → { $new }
+sync-dry-run-renamed = Rename { $branch } to { $new } (PR #{ $pr }'s branch was renamed on GitHub)
+sync-dry-run-renamed-remote = Push { $branch } to { $remote } (PR #{ $pr }'s branch was renamed on GitHub)
+sync-dry-run-remove = Remove { $branch } (branch no longer exists)
+sync-dry-run-rebase = Branches to rebase:
+sync-dry-run-rebase-onto = → { $branch } (onto { $base }, { $reason })
+sync-dry-run-retarget = Retarget PR #{ $pr }: { $old } → { $new }
+sync-checking-prs = Checking PRs and validating bases...
+sync-ghost-parent = Ghost parent: PR #{ $pr } ({ $branch }) base is '{ $actual }' but should be '{ $expected }'
+sync-pr-fetch-failed = Could not fetch PR #{ $pr }
+sync-batch-failed = Batch PR fetch failed, falling back to individual: { $error }
+sync-pr-check-failed = Could not check PR #{ $pr }: { $error }
+sync-updating-bases = Updating PR base branches on GitHub...
+sync-base-unchanged = PR #{ $pr } base already '{ $base }' - skipping
+sync-base-update-failed = Could not update PR #{ $pr }: { $error }
+sync-pushing = Pushing to remote...
+sync-push-interrupted = Interrupted - { $count } branch(es) not pushed, run `rung sync` to push them
+sync-push-diverged = Skipped pushing { $branch } - it has diverged from the remote
+sync-push-theirs = Skipped pushing { $branch } - someone else pushed it last
+sync-rename-failed = Could not rename { $branch }: { $error }
+sync-push-failed = Could not push { $branch }: { $error }
+sync-comment-failed = Could not comment on PRs: { $error }
+sync-pr-comment-failed = Could not comment on PR #{ $pr }: { $error }
+sync-up-to-date = Stack is already up-to-date
+sync-complete = Synced { $count } branches (backup: { $backup })
+sync-conflict = Conflict in branch '{ $branch }'
+sync-replaying = Replaying { $commit } { $summary }
+sync-conflict-files = Conflicting files:
+sync-conflict-resolve = Resolve conflicts, then run: rung sync --continue
+sync-abort-hint = Or abort with: rung sync --abort
+sync-submit-hint = The rebased branches weren't pushed - `rung submit` pushes them and updates their PRs
+sync-exec-failed = `{ $command }` failed on '{ $branch }'
+sync-exec-fix = Fix the branch, then run: rung sync --continue
+sync-interrupted = Sync interrupted before rebasing '{ $branch }'
+sync-interrupted-resume = Resume with: rung sync --continue
+sync-interrupted-abort = Or restore all branches with: rung sync --abort
+
+## rung submit
+
+submit-empty = No branches in stack - nothing to submit
+submit-cancelled = Submit cancelled
+submit-submitting = Submitting to { $remote }...
+submit-interrupted = Interrupted after { $done } of { $total } branch(es) - run `rung submit` again to finish
+submit-log-failed = Could not record submit in the operations log: { $error }
+submit-auto-delete = Enabled automatic deletion of merged head branches
+submit-auto-delete-failed = Could not enable automatic deletion of merged head branches: { $error }
+submit-unsigned = { $commit } on '{ $branch }' isn't signed off by { $author }: { $summary }
+submit-unsigned-hint = Sign them off with `git rebase --signoff <parent>` on each branch, then `rung sync`
+submit-base-mismatch = '{ $branch }' is based on '{ $parent }', but [[bases]] maps it onto '{ $base }' - its PR targets '{ $parent }'
+submit-skip-empty = Skipping '{ $branch }' - it has no commits '{ $parent }' doesn't, so there's nothing to open a PR for
+submit-processing = Processing { $branch }...
+submit-pushing = Pushing { $branch }...
+submit-found-pr = Found existing PR #{ $pr }...
+submit-creating = Creating PR ({ $branch } → { $base })...
+submit-created = Created PR #{ $pr }: { $url }
+submit-updating = Updating PR #{ $pr }...
+submit-reopen-ask = '{ $branch }' had PR #{ $pr } ({ $title }), closed without merging. Reopen it rather than open a new one?
+submit-reopening = Reopening PR #{ $pr }...
+submit-labelled = Labelled: { $labels }
+submit-label-failed = Could not add labels: { $error }
+submit-review-requested = Review requested: { $reviewers }
+submit-review-failed = Could not request reviews: { $error }
+submit-milestone = Milestone: { $milestone }
+submit-milestone-failed = Could not set milestone: { $error }
+submit-project = Added to project #{ $project }
+submit-project-failed = Could not add to project #{ $project }: { $error }
+submit-nothing-planned = No branches to submit
+submit-would-push = → Would push { $count } branches:
+submit-would-create = → Would create { $count } new PRs for branches:
+submit-in-milestone = in milestone '{ $milestone }'
+submit-in-project = added to project #{ $project }
+submit-dry-run = (dry run - no changes made)
+submit-created-count = { $count } created
+submit-updated-count = { $count } updated
+submit-merge-hint = Once a PR is approved, `rung merge` on its branch merges it and rebases the branches above
+submit-done = Done! PRs: { $counts }
+submit-no-changes = No changes to submit
+submit-workspace-failed = Could not read workspace PRs: { $error }
+submit-updating-comments = Updating stack comments...
+submit-comments-updated = Updated the stack comment on { $count } PR(s)
+submit-pushed-count = Pushed { $count } branch(es)
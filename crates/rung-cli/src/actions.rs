@@ -0,0 +1,74 @@
+//! GitHub Actions workflow commands.
+//!
+//! Inside a workflow (`GITHUB_ACTIONS=true`), findings are also reported as
+//! `::warning::`/`::error::` annotations on stderr, and results are set as
+//! step outputs in `$GITHUB_OUTPUT`, so later steps can branch on them
+//! without parsing JSON. Outside Actions these do nothing.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use crate::output;
+
+/// Annotation level.
+#[derive(Debug, Clone, Copy)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+/// Whether rung is running inside a GitHub Actions workflow.
+#[must_use]
+pub fn is_active() -> bool {
+    std::env::var("GITHUB_ACTIONS").is_ok_and(|value| value == "true")
+}
+
+/// Report `message` as an annotation on the workflow run.
+pub fn annotate(level: Level, message: &str) {
+    if !is_active() {
+        return;
+    }
+    let command = match level {
+        Level::Error => "error",
+        Level::Warning => "warning",
+    };
+    eprintln!("::{command}::{}", escape(message));
+}
+
+/// Set step outputs, e.g. `("branches_rebased", "2")`.
+///
+/// Failing to write them is only a warning; the command's result stands.
+pub fn set_outputs(outputs: &[(&str, String)]) {
+    if !is_active() {
+        return;
+    }
+    let Some(path) = std::env::var_os("GITHUB_OUTPUT") else {
+        return;
+    };
+    let text: String = outputs
+        .iter()
+        .map(|(name, value)| {
+            if value.contains('\n') {
+                format!("{name}<<RUNG_EOF\n{value}\nRUNG_EOF\n")
+            } else {
+                format!("{name}={value}\n")
+            }
+        })
+        .collect();
+    let written = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()));
+    if let Err(e) = written {
+        output::warn(&format!("Could not set step outputs: {e}"));
+    }
+}
+
+/// Escape annotation data the way the Actions runner expects.
+fn escape(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
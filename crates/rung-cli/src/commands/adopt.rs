@@ -0,0 +1,118 @@
+//! `rung adopt` command - Add stacks of PRs opened on GitHub to the stack.
+
+use anyhow::{Context, Result};
+use rung_core::remote_status::{self, Adoption};
+use rung_core::{BranchName, StackBranch, pr_body};
+use serde::Serialize;
+
+use super::utils::{github_client, github_remote, load_config, open_repo_and_state, remote_pr};
+use crate::output;
+
+/// JSON output for adopt command.
+#[derive(Debug, Serialize)]
+struct AdoptOutput {
+    adopted: Vec<AdoptedBranch>,
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AdoptedBranch {
+    branch: String,
+    parent: String,
+    pr: u64,
+    /// Whether the local branch was created from the remote one.
+    fetched: bool,
+}
+
+/// Run the adopt command, taking `author`'s PRs rather than your own if given.
+pub fn run(json: bool, dry_run: bool, author: Option<&str>) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    if !dry_run {
+        repo.require_no_operation()?;
+    }
+    let config = load_config(&repo, &state)?;
+    let remote = github_remote(&repo, &config)?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+
+    let mut stack = state.load_stack()?;
+    let login = match author {
+        Some(login) => login.trim_start_matches('@').to_string(),
+        None => rt
+            .block_on(client.current_user())
+            .context("Failed to look up your GitHub user")?,
+    };
+    let prs = rt
+        .block_on(client.list_open_prs_by(&remote.owner, &remote.repo, &login))
+        .context("Failed to list open PRs")?;
+    let open: Vec<_> = prs.iter().map(remote_pr).collect();
+    let adoptions = remote_status::adoptions(&stack, &open);
+
+    let mut adopted = Vec::with_capacity(adoptions.len());
+    for Adoption { pr, parent } in adoptions {
+        let name = BranchName::new(&pr.head)
+            .with_context(|| format!("PR #{} has an invalid branch name", pr.number))?;
+        let parent = BranchName::new(&parent).context("Invalid parent branch name")?;
+        let fetched = !repo.branch_exists(&name);
+
+        if !dry_run {
+            if fetched {
+                repo.fetch_remote_branch(&name)
+                    .with_context(|| format!("Failed to fetch '{name}'"))?;
+                repo.create_branch_at(&name, repo.remote_branch_commit(&name)?)?;
+            }
+            let mut branch = StackBranch::new(name.clone(), Some(parent.clone()));
+            branch.pr = Some(pr.number);
+            branch.author = Some(login.clone());
+            // Notes mirrored into the PR body by `rung submit`
+            branch.notes = prs
+                .iter()
+                .find(|p| p.number == pr.number)
+                .and_then(|p| p.body.as_deref())
+                .map(pr_body::notes)
+                .unwrap_or_default();
+            stack.add_branch(branch);
+        }
+
+        adopted.push(AdoptedBranch {
+            branch: name.to_string(),
+            parent: parent.to_string(),
+            pr: pr.number,
+            fetched,
+        });
+    }
+
+    if !dry_run && !adopted.is_empty() {
+        state.save_stack(&stack)?;
+    }
+
+    if json {
+        let output = AdoptOutput { adopted, dry_run };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if adopted.is_empty() {
+        output::info("No untracked stacked PRs to adopt");
+        return Ok(());
+    }
+    if dry_run {
+        output::info("Dry run - would adopt the following:");
+    }
+    for branch in &adopted {
+        let source = if branch.fetched {
+            "from origin"
+        } else {
+            "local branch"
+        };
+        output::info(&format!(
+            "  #{} {} → {} ({source})",
+            branch.pr, branch.branch, branch.parent
+        ));
+    }
+    if !dry_run {
+        output::success(&format!("Adopted {} branch(es)", adopted.len()));
+    }
+
+    Ok(())
+}
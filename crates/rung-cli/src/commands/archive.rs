@@ -0,0 +1,255 @@
+//! `rung archive` and `rung unarchive` commands - Park and restore whole stacks.
+
+use anyhow::{Context, Result, anyhow, bail};
+use rung_core::archive::ArchivedBranch;
+use rung_core::{BranchName, StackArchive};
+use rung_git::Oid;
+use serde::Serialize;
+
+use super::utils::open_repo_and_state;
+use crate::{output, prompt};
+
+/// JSON output for archive/unarchive commands.
+#[derive(Debug, Serialize)]
+struct ArchiveOutput {
+    name: String,
+    branches: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    deleted_local: bool,
+}
+
+/// JSON summary of an archive for `--list`.
+#[derive(Debug, Serialize)]
+struct ArchiveListEntry {
+    name: String,
+    archived_at: String,
+    branches: Vec<String>,
+    prs: usize,
+}
+
+/// Run the archive command - park the stack containing the current branch.
+pub fn run(json: bool, name: Option<&str>, delete_local: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let current = repo.current_branch()?;
+    let mut stack = state.load_stack()?;
+
+    let members: Vec<_> = stack.chain_through(&current).into_iter().cloned().collect();
+    let Some(root) = members.first() else {
+        bail!("Current branch '{current}' is not in stack");
+    };
+
+    let archive_name = name.map_or_else(|| root.name.to_string(), String::from);
+    BranchName::new(&archive_name).context("Invalid archive name")?;
+    if state.has_archive(&archive_name) {
+        bail!("An archive named '{archive_name}' already exists");
+    }
+
+    if delete_local {
+        repo.require_clean()?;
+        // Checked before anything is written, so a refusal archives nothing
+        if root.parent.is_none() && members.iter().any(|b| b.name.as_str() == current) {
+            bail!("Cannot delete branches: root branch has no parent to check out");
+        }
+        let details: Vec<String> = members
+            .iter()
+            .map(|b| format!("Delete local branch '{}'", b.name))
+            .collect();
+        if !prompt::confirm_destructive(&state, "Delete the archived branches?", &details)? {
+            output::info("Archive cancelled");
+            return Ok(());
+        }
+    }
+
+    // Record tips and bases so the stack can be rebuilt later
+    let mut branches = Vec::with_capacity(members.len());
+    for member in &members {
+        let Ok(commit) = repo.branch_commit(&member.name) else {
+            if !json {
+                output::warn(&format!(
+                    "Skipping '{}' - branch no longer exists locally",
+                    member.name
+                ));
+            }
+            continue;
+        };
+
+        let base = member
+            .parent
+            .as_ref()
+            .and_then(|p| repo.branch_commit(p).ok())
+            .and_then(|parent| repo.merge_base(commit, parent).ok())
+            .map(|oid| oid.to_string());
+
+        branches.push(ArchivedBranch {
+            branch: member.clone(),
+            commit: commit.to_string(),
+            base,
+        });
+    }
+
+    let archive = StackArchive::new(archive_name, branches);
+    state.save_archive(&archive)?;
+
+    let archived_names: Vec<String> = members.iter().map(|b| b.name.to_string()).collect();
+    stack
+        .branches
+        .retain(|b| !archived_names.contains(&b.name.to_string()));
+    state.save_stack(&stack)?;
+
+    if delete_local {
+        // Step off the archived branches before deleting them
+        if let Some(fallback) = root
+            .parent
+            .as_ref()
+            .filter(|_| archived_names.contains(&current))
+        {
+            repo.checkout(fallback)?;
+            if !json {
+                output::info(&format!("Checked out '{fallback}'"));
+            }
+        }
+
+        for name in &archived_names {
+            if let Err(e) = repo.delete_branch(name) {
+                if !json {
+                    output::warn(&format!("Could not delete local branch '{name}': {e}"));
+                }
+            }
+        }
+    }
+
+    if json {
+        return output_json(&ArchiveOutput {
+            name: archive.name,
+            branches: archived_names,
+            deleted_local: delete_local,
+        });
+    }
+
+    output::success(&format!(
+        "Archived {} branch(es) as '{}'",
+        archive.branches.len(),
+        archive.name
+    ));
+    output::info(&format!("Restore with: rung unarchive {}", archive.name));
+
+    Ok(())
+}
+
+/// List archived stacks.
+pub fn run_list(json: bool) -> Result<()> {
+    let (_repo, state) = open_repo_and_state()?;
+    let archives = state.list_archives()?;
+
+    if json {
+        let entries: Vec<ArchiveListEntry> = archives
+            .iter()
+            .map(|a| ArchiveListEntry {
+                name: a.name.clone(),
+                archived_at: a.archived_at.to_rfc3339(),
+                branches: a.branch_names().into_iter().map(String::from).collect(),
+                prs: a.pr_count(),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if archives.is_empty() {
+        output::info("No archived stacks");
+        return Ok(());
+    }
+
+    for a in &archives {
+        output::essential(&format!(
+            "{}  ({} branch(es), archived {})",
+            a.name,
+            a.branches.len(),
+            a.archived_at.format("%Y-%m-%d")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Run the unarchive command - restore an archived stack.
+pub fn run_unarchive(json: bool, name: Option<&str>) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+
+    let archive = match name {
+        Some(n) => state.load_archive(n)?,
+        None => state
+            .list_archives()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No archived stacks to restore"))?,
+    };
+
+    let mut stack = state.load_stack()?;
+
+    // Resolve every branch before touching anything so a failure leaves no partial restore
+    let mut to_create: Vec<(&BranchName, Oid)> = vec![];
+    for archived in &archive.branches {
+        let branch_name = &archived.branch.name;
+        if stack.find_branch(branch_name).is_some() {
+            bail!("Branch '{branch_name}' is already in the stack");
+        }
+        if repo.branch_exists(branch_name) {
+            continue;
+        }
+
+        let recorded = Oid::from_str(&archived.commit)
+            .ok()
+            .filter(|oid| repo.commit_exists(*oid));
+        let target = recorded
+            .or_else(|| repo.remote_branch_commit(branch_name).ok())
+            .with_context(|| {
+                format!(
+                    "Cannot restore '{branch_name}': commit {} not found locally or on origin",
+                    &archived.commit[..8.min(archived.commit.len())]
+                )
+            })?;
+        to_create.push((branch_name, target));
+    }
+
+    for (branch_name, target) in &to_create {
+        repo.create_branch_at(branch_name, *target)?;
+    }
+
+    for archived in &archive.branches {
+        stack.add_branch(archived.branch.clone());
+    }
+    state.save_stack(&stack)?;
+    state.delete_archive(&archive.name)?;
+
+    let names: Vec<String> = archive
+        .branch_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    if json {
+        return output_json(&ArchiveOutput {
+            name: archive.name,
+            branches: names,
+            deleted_local: false,
+        });
+    }
+
+    output::success(&format!(
+        "Restored {} branch(es) from archive '{}'",
+        names.len(),
+        archive.name
+    ));
+    if !to_create.is_empty() {
+        output::info(&format!("Recreated {} local branch(es)", to_create.len()));
+    }
+
+    Ok(())
+}
+
+/// Output archive result as JSON.
+fn output_json(output: &ArchiveOutput) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(output)?);
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! `rung blame` command - Annotate a file's lines with the stack branch and
+//! PR that introduced them.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rung_git::Repository;
+use serde::Serialize;
+
+use super::utils::{commit_index, open_repo_and_state};
+use crate::output;
+
+/// One annotated line.
+#[derive(Debug, Serialize)]
+struct BlameLine {
+    line: usize,
+    commit: String,
+    /// The stack branch with the commit among its own, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr: Option<u64>,
+    content: String,
+}
+
+/// JSON output for the blame command.
+#[derive(Debug, Serialize)]
+struct BlameOutput {
+    file: String,
+    lines: Vec<BlameLine>,
+}
+
+/// Run the blame command on `file` as of `HEAD`. Lines from a stack
+/// branch's own commits are marked with the branch and its PR; the rest
+/// came from trunk.
+pub fn run(json: bool, file: &str) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let stack = state.load_stack()?;
+    let path = repo_path(&repo, file)?;
+    let head = repo.resolve_commit("HEAD")?;
+    let blamed = repo
+        .blame_lines(&path, head)
+        .with_context(|| format!("Could not blame '{path}' at HEAD"))?;
+
+    let index = commit_index(&repo, &state, &stack)?;
+
+    let lines: Vec<BlameLine> = blamed
+        .into_iter()
+        .enumerate()
+        .map(|(i, (commit, content))| {
+            let commit = commit.to_string();
+            let owner = index
+                .branch_of(&commit)
+                .and_then(|name| stack.find_branch(name));
+            BlameLine {
+                line: i + 1,
+                commit,
+                branch: owner.map(|b| b.name.to_string()),
+                pr: owner.and_then(|b| b.pr),
+                content,
+            }
+        })
+        .collect();
+
+    if json {
+        let out = BlameOutput { file: path, lines };
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    let labels: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            line.branch.as_deref().map_or_else(String::new, |branch| {
+                line.pr
+                    .map_or_else(|| branch.to_string(), |pr| format!("{branch} #{pr}"))
+            })
+        })
+        .collect();
+    let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let number_width = lines.len().to_string().len();
+    for (line, label) in lines.iter().zip(&labels) {
+        output::essential(&format!(
+            "{label:<width$} {} {:>number_width$} │ {}",
+            &line.commit[..7],
+            line.line,
+            line.content
+        ));
+    }
+    Ok(())
+}
+
+/// `file`, given relative to the current directory, as a path relative to
+/// the repository root with `/` separators.
+fn repo_path(repo: &Repository, file: &str) -> Result<String> {
+    let workdir = repo
+        .workdir()
+        .context("Cannot blame in a bare repository")?
+        .canonicalize()?;
+    let absolute = std::env::current_dir()?.join(file);
+    // A file deleted from the working tree can't be canonicalized; resolve
+    // its directory instead
+    let resolved = absolute.canonicalize().or_else(|_| {
+        let parent = absolute.parent().unwrap_or_else(|| Path::new("."));
+        let name = absolute.file_name().context("Not a file")?;
+        Ok::<_, anyhow::Error>(parent.canonicalize()?.join(name))
+    })?;
+    let relative = resolved
+        .strip_prefix(&workdir)
+        .with_context(|| format!("'{file}' is outside the repository"))?;
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
@@ -0,0 +1,125 @@
+//! `rung doctor --bugreport` - Bundle diagnostics for attaching to an issue.
+//!
+//! The bundle holds versions, the doctor's findings, the statuses GitHub
+//! answered its checks with, the config with credentials masked, the stack,
+//! and the tail of the operations log. Tokens are never included.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use rung_core::State;
+use rung_git::{GitVersion, Repository};
+use rung_github::HttpStatus;
+
+use crate::output;
+
+/// How many of the most recent operations log entries to include.
+const OPERATIONS_TAIL: usize = 100;
+
+/// Write the bundle to `rung-bugreport-<timestamp>.tar.gz` in the current
+/// directory.
+pub fn run(
+    json: bool,
+    repo: &Repository,
+    state: &State,
+    doctor: &serde_json::Value,
+    http: &[HttpStatus],
+) -> Result<()> {
+    let name = format!("rung-bugreport-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+    let staging = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+    let dir = staging.join(&name);
+    fs::create_dir_all(&dir).context("Failed to create the report directory")?;
+
+    let result = write_files(&dir, repo, state, doctor, http).and_then(|()| {
+        let tarball = std::env::current_dir()?.join(format!("{name}.tar.gz"));
+        pack(&staging, &name, &tarball)?;
+        Ok(tarball)
+    });
+    let _ = fs::remove_dir_all(&staging); // Best effort
+    let tarball = result?;
+
+    if json {
+        let output = serde_json::json!({ "bugreport": tarball });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        output::success(&format!("Wrote {}", tarball.display()));
+        output::info(
+            "It includes branch names and PR numbers - check it, then attach it to an issue at https://github.com/auswm85/rung/issues",
+        );
+    }
+    Ok(())
+}
+
+/// Write each part of the report into `dir`.
+fn write_files(
+    dir: &Path,
+    repo: &Repository,
+    state: &State,
+    doctor: &serde_json::Value,
+    http: &[HttpStatus],
+) -> Result<()> {
+    let write_json = |file: &str, value: &serde_json::Value| -> Result<()> {
+        fs::write(dir.join(file), serde_json::to_string_pretty(value)?)?;
+        Ok(())
+    };
+
+    let git = GitVersion::installed().map_or_else(|e| format!("unknown ({e})"), |v| v.to_string());
+    write_json(
+        "versions.json",
+        &serde_json::json!({
+            "rung": env!("CARGO_PKG_VERSION"),
+            "git": git,
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+            "sync_in_progress": state.is_sync_in_progress(),
+            "detached_head": repo.current_branch().is_err(),
+        }),
+    )?;
+    write_json("doctor.json", doctor)?;
+    write_json("http.json", &serde_json::to_value(http)?)?;
+
+    let config = match state.load_config() {
+        Ok(config) => config.to_redacted_toml()?,
+        Err(e) => format!("# Could not load config: {e}\n"),
+    };
+    fs::write(dir.join("config.toml"), config)?;
+
+    let stack = match state.load_stack() {
+        Ok(stack) => serde_json::to_value(&stack)?,
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    };
+    write_json("stack.json", &stack)?;
+    if let Ok(sync_state) = state.load_sync_state() {
+        write_json("sync_state.json", &serde_json::to_value(&sync_state)?)?;
+    }
+
+    let operations = state.load_operations().unwrap_or_default();
+    let mut log = String::new();
+    for operation in &operations[operations.len().saturating_sub(OPERATIONS_TAIL)..] {
+        log.push_str(&serde_json::to_string(operation)?);
+        log.push('\n');
+    }
+    fs::write(dir.join("operations.jsonl"), log)?;
+    Ok(())
+}
+
+/// Pack `staging/name` into a gzipped tarball at `tarball`.
+fn pack(staging: &Path, name: &str, tarball: &Path) -> Result<()> {
+    let output = std::process::Command::new("tar")
+        .arg("-czf")
+        .arg(tarball)
+        .arg("-C")
+        .arg(staging)
+        .arg(name)
+        .output()
+        .context("Failed to run tar")?;
+    if !output.status.success() {
+        bail!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+//! `rung cache` command - Manage cached GitHub results and branch states.
+
+use anyhow::Result;
+
+use super::CacheCommand;
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// Run the cache command.
+pub fn run(json: bool, command: &CacheCommand) -> Result<()> {
+    match command {
+        CacheCommand::Clear => {
+            let (_repo, state) = open_repo_and_state()?;
+            let cleared = state.clear_cache()?;
+
+            if json {
+                println!("{}", serde_json::json!({ "cleared": cleared }));
+            } else if cleared {
+                output::success("Cleared cached data");
+            } else {
+                output::info("Nothing cached");
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! `rung checks` command - CI check runs for the stack, with their timing.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use rung_core::ci::{self, CheckTiming, CiStatus, SlowCheck};
+use rung_github::CheckRun;
+use serde::Serialize;
+
+use super::utils::{github_reader, github_remote, load_config, open_repo_and_state};
+use crate::i18n::tr;
+use crate::output;
+
+/// One check run on a branch.
+#[derive(Debug, Serialize)]
+struct CheckReport {
+    name: String,
+    status: CiStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Utc>>,
+    /// Seconds from start to completion, once completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<i64>,
+}
+
+impl CheckReport {
+    fn new(run: &CheckRun) -> Self {
+        let parse = |at: Option<&str>| {
+            at.and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map(|at| at.with_timezone(&Utc))
+        };
+        let started_at = parse(run.started_at.as_deref());
+        let completed_at = parse(run.completed_at.as_deref()).filter(|_| !run.status.is_pending());
+        let status = match run.status {
+            status if status.is_pending() => CiStatus::Pending,
+            status if status.is_success() => CiStatus::Passing,
+            _ => CiStatus::Failing,
+        };
+        Self {
+            name: run.name.clone(),
+            status,
+            started_at,
+            completed_at,
+            duration_secs: started_at
+                .zip(completed_at)
+                .map(|(start, end)| (end - start).num_seconds()),
+        }
+    }
+
+    /// The timing column: the duration and when it ran, or how long it
+    /// has been running.
+    fn timing(&self) -> String {
+        let time = |at: DateTime<Utc>, format| at.with_timezone(&Local).format(format).to_string();
+        match (self.started_at, self.completed_at, self.duration_secs) {
+            (Some(start), Some(end), Some(secs)) => format!(
+                "{}  ({} → {})",
+                format_duration(secs),
+                time(start, "%Y-%m-%d %H:%M:%S"),
+                time(end, "%H:%M:%S")
+            ),
+            (Some(start), None, _) => format!(
+                "running for {}  (since {})",
+                format_duration((Utc::now() - start).num_seconds()),
+                time(start, "%Y-%m-%d %H:%M:%S")
+            ),
+            _ if self.status == CiStatus::Pending => "queued".to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// The check runs of one branch's tip.
+#[derive(Debug, Serialize)]
+struct BranchReport {
+    branch: String,
+    pr_number: u64,
+    checks: Vec<CheckReport>,
+}
+
+/// JSON output for the checks command.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ChecksOutput {
+    Branches { branches: Vec<BranchReport> },
+    Slowest { slowest: Vec<SlowCheck> },
+}
+
+/// Run the checks command: list each PR branch's check runs with when they
+/// ran and how long they took, or with `slowest` rank the checks by their
+/// longest run across the stack.
+pub fn run(json: bool, slowest: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let config = load_config(&repo, &state)?;
+    let stack = state.load_stack()?;
+    let remote = github_remote(&repo, &config)?;
+    let client = github_reader(&remote, &config, json).context("Failed to connect to GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+
+    let mut branches = vec![];
+    for branch in &stack.branches {
+        let Some(pr) = branch.pr else {
+            continue;
+        };
+        if client.is_anonymous() && client.budget_spent() {
+            if !json {
+                output::warn(&tr!("github-budget-spent"));
+            }
+            break;
+        }
+        let commit = repo.branch_commit(&branch.name)?.to_string();
+        match rt.block_on(client.get_check_runs(&remote.owner, &remote.repo, &commit)) {
+            Ok(runs) => branches.push(BranchReport {
+                branch: branch.name.to_string(),
+                pr_number: pr,
+                checks: runs.iter().map(CheckReport::new).collect(),
+            }),
+            Err(e) => {
+                if !json {
+                    output::warn(&format!(
+                        "Could not fetch checks for '{}': {e}",
+                        branch.name
+                    ));
+                }
+            }
+        }
+    }
+
+    if slowest {
+        let timings: Vec<CheckTiming> = branches
+            .iter()
+            .flat_map(|report| {
+                report.checks.iter().filter_map(|check| {
+                    check.duration_secs.map(|secs| CheckTiming {
+                        branch: report.branch.clone(),
+                        check: check.name.clone(),
+                        secs,
+                    })
+                })
+            })
+            .collect();
+        let slowest = ci::slowest(&timings);
+        if json {
+            return output_json(&ChecksOutput::Slowest { slowest });
+        }
+        print_slowest(&slowest);
+        return Ok(());
+    }
+
+    if json {
+        return output_json(&ChecksOutput::Branches { branches });
+    }
+    print_branches(&branches);
+    Ok(())
+}
+
+fn print_branches(branches: &[BranchReport]) {
+    if branches.is_empty() {
+        output::info("No branches with PRs - run `rung submit` first");
+        return;
+    }
+    for report in branches {
+        output::essential(&format!(
+            "{} {}",
+            report.branch,
+            output::pr_ref(Some(report.pr_number))
+        ));
+        if report.checks.is_empty() {
+            output::essential("  no checks reported");
+        }
+        let width = report
+            .checks
+            .iter()
+            .map(|c| c.name.len())
+            .max()
+            .unwrap_or(0);
+        for check in &report.checks {
+            output::essential(
+                format!(
+                    "  {} {:<width$}  {}",
+                    output::ci_indicator(Some(check.status)),
+                    check.name,
+                    check.timing()
+                )
+                .trim_end(),
+            );
+        }
+    }
+}
+
+fn print_slowest(slowest: &[SlowCheck]) {
+    if slowest.is_empty() {
+        output::info("No completed checks to time");
+        return;
+    }
+    output::essential("Checks by longest run across the stack:");
+    let width = slowest.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in slowest {
+        output::essential(&format!(
+            "  {:<width$}  {:>7} on {}, average {} over {} run(s)",
+            check.name,
+            format_duration(check.longest_secs),
+            check.longest_branch,
+            format_duration(check.average_secs),
+            check.runs
+        ));
+    }
+}
+
+/// Format seconds as the two largest units, e.g. "1h 4m" or "3m 12s".
+fn format_duration(secs: i64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Output the report as JSON.
+fn output_json(output: &ChecksOutput) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(output)?);
+    Ok(())
+}
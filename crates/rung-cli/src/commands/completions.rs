@@ -2,15 +2,69 @@
 
 use std::io;
 
-use clap::CommandFactory;
-use clap_complete::{Shell, generate};
+use clap::{Command, CommandFactory, ValueEnum};
+use clap_complete::{Generator, Shell, generate};
+use clap_complete_nushell::Nushell;
 
 use super::Cli;
 
+/// Shells completions can be generated for: clap's own, plus nushell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Elvish,
+    Fish,
+    Nushell,
+    #[value(name = "powershell")]
+    PowerShell,
+    Zsh,
+}
+
 /// Generate shell completions and print to stdout.
+///
+/// Command aliases (`c`, `st`, `sy`, ...) are visible aliases, so every
+/// generator completes them alongside the full names.
 #[allow(clippy::unnecessary_wraps)]
-pub fn run(shell: Shell) -> anyhow::Result<()> {
-    let mut cmd = Cli::command();
-    generate(shell, &mut cmd, "rung", &mut io::stdout());
+pub fn run(shell: CompletionShell) -> anyhow::Result<()> {
+    match shell {
+        CompletionShell::Bash => print(Shell::Bash),
+        CompletionShell::Elvish => print(Shell::Elvish),
+        CompletionShell::Fish => print(Shell::Fish),
+        CompletionShell::Nushell => print_command(Nushell, with_alias_commands(Cli::command())),
+        CompletionShell::PowerShell => print(Shell::PowerShell),
+        CompletionShell::Zsh => print(Shell::Zsh),
+    }
     Ok(())
 }
+
+fn print(generator: impl Generator) {
+    print_command(generator, Cli::command());
+}
+
+fn print_command(generator: impl Generator, mut cmd: Command) {
+    generate(generator, &mut cmd, "rung", &mut io::stdout());
+}
+
+/// The nushell generator ignores aliases, so turn each one into a hidden copy
+/// of the command it stands for.
+fn with_alias_commands(mut cmd: Command) -> Command {
+    let mut copies = vec![];
+    let mut aliased = vec![];
+    for sub in cmd.get_subcommands() {
+        for alias in sub.get_visible_aliases() {
+            copies.push(
+                sub.clone()
+                    .name(alias.to_string())
+                    .visible_alias(None)
+                    .hide(true),
+            );
+        }
+        if sub.get_visible_aliases().next().is_some() {
+            aliased.push(sub.get_name().to_string());
+        }
+    }
+    for name in aliased {
+        cmd = cmd.mut_subcommand(name, |sub| sub.visible_alias(None));
+    }
+    cmd.subcommands(copies)
+}
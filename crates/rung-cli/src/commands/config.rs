@@ -0,0 +1,112 @@
+//! `rung config` command - Read and change settings without editing TOML.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use rung_core::{Config, config};
+
+use super::utils::open_repo_and_state;
+use super::{ConfigCommand, ConfigScope};
+use crate::output;
+
+/// Run the config command.
+pub fn run(json: bool, command: &ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Get { key, scope } => get(json, key, scope),
+        ConfigCommand::Set { key, value, scope } => set(json, key, value, scope),
+        ConfigCommand::List { scope } => list(json, scope),
+    }
+}
+
+/// Print one setting, from the chosen file or else the config in effect.
+fn get(json: bool, key: &str, scope: &ConfigScope) -> Result<()> {
+    let value = match scope_path(scope)? {
+        Some(path) => config::read_key(&path, key)?,
+        None => effective_config()?.get(key),
+    };
+    let Some(value) = value else {
+        bail!("`{key}` is not set");
+    };
+
+    if json {
+        let output = serde_json::json!({ "key": key, "value": value });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if let toml::Value::String(text) = &value {
+        // Unquoted, for scripts
+        output::essential(text);
+    } else {
+        output::essential(&value.to_string());
+    }
+    Ok(())
+}
+
+/// Change one setting in the repository config, or with `--global` or
+/// `--team` the user-wide or team one.
+fn set(json: bool, key: &str, value: &str, scope: &ConfigScope) -> Result<()> {
+    if scope.global && !key.starts_with("orgs.") {
+        bail!(
+            "Only [orgs.<owner>] settings are read from the user-wide config - set `{key}` without --global"
+        );
+    }
+    let path = match scope_path(scope)? {
+        Some(path) => path,
+        None => open_repo_and_state()?.1.config_path(),
+    };
+    config::write_key(&path, key, value)?;
+    let value = config::read_key(&path, key)?.context("Setting was not saved")?;
+
+    if json {
+        let output = serde_json::json!({ "key": key, "value": value, "file": path });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        output::success(&format!("Set {key} = {value} in {}", path.display()));
+    }
+    Ok(())
+}
+
+/// List the settings in the chosen file, or else every setting in effect,
+/// defaults included.
+fn list(json: bool, scope: &ConfigScope) -> Result<()> {
+    let entries = match scope_path(scope)? {
+        Some(path) => config::read_entries(&path)?,
+        None => effective_config()?.entries(),
+    };
+
+    if json {
+        let map: serde_json::Map<String, serde_json::Value> = entries
+            .into_iter()
+            .map(|(key, value)| Ok((key, serde_json::to_value(value)?)))
+            .collect::<Result<_>>()?;
+        println!("{}", serde_json::to_string_pretty(&map)?);
+        return Ok(());
+    }
+    for (key, value) in &entries {
+        output::essential(&format!("{key} = {value}"));
+    }
+    Ok(())
+}
+
+/// The file `--local`, `--global` or `--team` picks, or `None` for none.
+fn scope_path(scope: &ConfigScope) -> Result<Option<PathBuf>> {
+    if scope.global {
+        return Config::user_path()
+            .context("No home directory for the user-wide config")
+            .map(Some);
+    }
+    if scope.local {
+        return Ok(Some(open_repo_and_state()?.1.config_path()));
+    }
+    if scope.team {
+        return Ok(Some(
+            open_repo_and_state()?.1.team_config_path().to_path_buf(),
+        ));
+    }
+    Ok(None)
+}
+
+/// The repository config over the team config, with the user-wide
+/// `[orgs.*]` added.
+fn effective_config() -> Result<Config> {
+    let (_repo, state) = open_repo_and_state()?;
+    Ok(state.load_config()?)
+}
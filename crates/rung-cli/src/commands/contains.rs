@@ -0,0 +1,288 @@
+//! `rung contains` command - Find where a commit is in the stack, and whether
+//! it landed on trunk.
+
+use anyhow::{Result, bail};
+use rung_core::commit_index::CommitIndex;
+use rung_core::landed::{Landing, Landings};
+use rung_git::{Oid, Repository};
+use serde::Serialize;
+
+use super::utils::{commit_index, open_repo_and_state};
+use crate::output;
+
+/// JSON output for contains command.
+#[derive(Debug, Serialize)]
+struct ContainsOutput {
+    commit: String,
+    summary: String,
+    /// The stack branch with the commit among its own commits.
+    branch: Option<BranchOutput>,
+    /// Trunk branches (local or on origin) that have the commit itself.
+    trunk: Vec<String>,
+    /// The recorded landing of the branch the commit was on.
+    landed: Option<LandedOutput>,
+    /// A trunk commit with the same patch ID: the commit, landed under
+    /// another id by a rebase or cherry-pick.
+    same_patch: Option<SamePatchOutput>,
+}
+
+/// The stack branch a commit is on.
+#[derive(Debug, Serialize)]
+struct BranchOutput {
+    name: String,
+    pr: Option<u64>,
+    /// Whether the branch on origin has the commit.
+    pushed: bool,
+}
+
+/// How a commit's branch landed.
+#[derive(Debug, Serialize)]
+struct LandedOutput {
+    branch: String,
+    pr_number: u64,
+    /// Commit on `into` that landed the branch.
+    commit: String,
+    into: String,
+    /// Whether the landed commit has the same changes as the branch (their
+    /// patch IDs match), or `None` when that can't be compared locally.
+    unchanged: Option<bool>,
+}
+
+/// A trunk commit with a commit's changes.
+#[derive(Debug, Serialize)]
+struct SamePatchOutput {
+    commit: String,
+    into: String,
+}
+
+/// Run the contains command: `target` is a revision, or else part of a
+/// commit message.
+pub fn run(json: bool, target: &str) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let stack = state.load_stack()?;
+    let landings = state.load_landings();
+
+    // The stack's trunks, and those branches landed into
+    let mut trunks: Vec<&str> = stack
+        .roots()
+        .iter()
+        .filter_map(|b| b.parent.as_deref())
+        .chain(landings.landings.iter().map(|l| l.into.as_str()))
+        .collect();
+    trunks.sort_unstable();
+    trunks.dedup();
+
+    let index = commit_index(&repo, &state, &stack)?;
+    let commit = match repo.resolve_commit(target) {
+        Ok(commit) => commit,
+        Err(_) => find_by_message(&repo, &index, &landings, target)?,
+    };
+    let summary = repo.commit_info(commit)?.summary;
+    let landing = landings.containing(&commit.to_string());
+
+    let branch = index
+        .branch_of(&commit.to_string())
+        .and_then(|name| stack.find_branch(name))
+        .map(|b| BranchOutput {
+            name: b.name.to_string(),
+            pr: b.pr,
+            pushed: has_remote_commit(&repo, b.remote_name(), commit),
+        });
+    let trunk: Vec<String> = trunks
+        .iter()
+        .filter(|t| has_commit(&repo, t, commit))
+        .map(|t| (*t).to_string())
+        .collect();
+    let landed = landing.map(|l| LandedOutput {
+        branch: l.branch.clone(),
+        pr_number: l.pr_number,
+        commit: l.commit.clone(),
+        into: l.into.clone(),
+        unchanged: unchanged(&repo, l),
+    });
+    let same_patch = if trunk.is_empty() && landed.is_none() {
+        trunks.iter().find_map(|t| same_patch_on(&repo, t, commit))
+    } else {
+        None
+    };
+
+    let out = ContainsOutput {
+        commit: commit.to_string(),
+        summary,
+        branch,
+        trunk,
+        landed,
+        same_patch,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+    print_report(&repo, &out, landing);
+    Ok(())
+}
+
+/// Print what was found about the commit.
+fn print_report(repo: &Repository, out: &ContainsOutput, landing: Option<&Landing>) {
+    output::info(&format!("{} {}", short(&out.commit), out.summary));
+    if let Some(branch) = &out.branch {
+        let pushed = if branch.pushed {
+            "pushed"
+        } else {
+            "not pushed"
+        };
+        output::success(&format!(
+            "On '{}' {}, {pushed}",
+            branch.name,
+            output::pr_ref(branch.pr)
+        ));
+    }
+    for t in &out.trunk {
+        output::success(&format!("On '{t}'"));
+    }
+    if let Some(same) = &out.same_patch {
+        output::success(&format!(
+            "Landed on '{}' as {} (same patch)",
+            same.into,
+            short(&same.commit)
+        ));
+    }
+    if let (Some(l), Some(landing)) = (&out.landed, landing) {
+        output::success(&format!(
+            "Landed on '{}' as {} with PR #{} ('{}')",
+            l.into,
+            short(&l.commit),
+            l.pr_number,
+            l.branch
+        ));
+        match l.unchanged {
+            Some(true) => output::info("The landed commit has the branch's changes unchanged"),
+            Some(false) => {
+                output::warn("The landed commit differs from the branch as submitted");
+                if let Some(diff) = range_diff(repo, landing) {
+                    output::essential(diff.trim_end());
+                }
+            }
+            None => {}
+        }
+    }
+    if out.trunk.is_empty() && out.landed.is_none() && out.same_patch.is_none() {
+        output::info("Not landed yet");
+    }
+}
+
+/// The one commit whose message contains `text` (ignoring case), among the
+/// stack branches' own commits and recorded landings.
+fn find_by_message(
+    repo: &Repository,
+    index: &CommitIndex,
+    landings: &Landings,
+    text: &str,
+) -> Result<Oid> {
+    let mut candidates: Vec<Oid> = index
+        .commits()
+        .chain(
+            landings
+                .landings
+                .iter()
+                .flat_map(|l| &l.commits)
+                .map(String::as_str),
+        )
+        .filter_map(|c| Oid::from_str(c).ok())
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let needle = text.to_lowercase();
+    let matches: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|oid| repo.commit_info(oid).ok())
+        .filter(|info| info.message.to_lowercase().contains(&needle))
+        .collect();
+    match matches.as_slice() {
+        [] => bail!("'{text}' is neither a commit nor part of a commit message in the stack"),
+        [only] => Ok(only.id),
+        many => {
+            let list: Vec<String> = many
+                .iter()
+                .map(|info| format!("  {} {}", short(&info.id.to_string()), info.summary))
+                .collect();
+            bail!(
+                "'{text}' matches {} commits; give one's SHA:\n{}",
+                many.len(),
+                list.join("\n")
+            )
+        }
+    }
+}
+
+/// Whether `branch`, locally or on origin, has `commit`.
+fn has_commit(repo: &Repository, branch: &str, commit: Oid) -> bool {
+    repo.branch_commit(branch)
+        .is_ok_and(|tip| is_ancestor(repo, commit, tip))
+        || has_remote_commit(repo, branch, commit)
+}
+
+/// Whether `branch` on origin has `commit`.
+fn has_remote_commit(repo: &Repository, branch: &str, commit: Oid) -> bool {
+    repo.remote_branch_commit(branch)
+        .is_ok_and(|tip| is_ancestor(repo, commit, tip))
+}
+
+fn is_ancestor(repo: &Repository, commit: Oid, tip: Oid) -> bool {
+    repo.merge_base(tip, commit).is_ok_and(|b| b == commit)
+}
+
+/// The commit on `trunk` (on origin, or else locally) since `commit` forked
+/// from it that makes the same changes.
+fn same_patch_on(repo: &Repository, trunk: &str, commit: Oid) -> Option<SamePatchOutput> {
+    let parent = repo.find_commit(commit).ok()?.parent_id(0).ok()?;
+    let patch = repo.patch_id(parent, commit).ok()?;
+    let tip = repo
+        .remote_branch_commit(trunk)
+        .or_else(|_| repo.branch_commit(trunk))
+        .ok()?;
+    let fork = repo.merge_base(tip, commit).ok()?;
+    repo.commits_between(fork, tip)
+        .ok()?
+        .into_iter()
+        .find(|&c| {
+            repo.find_commit(c)
+                .ok()
+                .and_then(|found| found.parent_id(0).ok())
+                .and_then(|p| repo.patch_id(p, c).ok())
+                == Some(patch)
+        })
+        .map(|c| SamePatchOutput {
+            commit: c.to_string(),
+            into: trunk.to_string(),
+        })
+}
+
+/// The recorded commits of `landing` as oids: its base, tip, landed commit
+/// and that commit's parent.
+fn landing_oids(repo: &Repository, landing: &Landing) -> Option<(Oid, Oid, Oid, Oid)> {
+    let base = Oid::from_str(&landing.base).ok()?;
+    let tip = Oid::from_str(&landing.tip).ok()?;
+    let landed = Oid::from_str(&landing.commit).ok()?;
+    let parent = repo.find_commit(landed).ok()?.parent_id(0).ok()?;
+    Some((base, tip, parent, landed))
+}
+
+/// Whether the landed commit's patch ID matches the branch's as submitted.
+fn unchanged(repo: &Repository, landing: &Landing) -> Option<bool> {
+    let (base, tip, parent, landed) = landing_oids(repo, landing)?;
+    let submitted = repo.patch_id(base, tip).ok()?;
+    Some(repo.patch_id(parent, landed).ok()? == submitted)
+}
+
+/// The range-diff from the branch as submitted to the commit that landed it.
+fn range_diff(repo: &Repository, landing: &Landing) -> Option<String> {
+    let (base, tip, parent, landed) = landing_oids(repo, landing)?;
+    repo.range_diff(base, tip, parent, landed).ok()
+}
+
+/// The first 7 hex digits of a commit id.
+fn short(id: &str) -> &str {
+    &id[..7.min(id.len())]
+}
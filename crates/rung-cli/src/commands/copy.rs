@@ -0,0 +1,145 @@
+//! `rung copy` command - Copy a branch's commits onto another base, e.g. to
+//! backport a fix to a release branch.
+
+use anyhow::{Context, Result, bail};
+use rung_core::BranchName;
+use rung_github::CreatePullRequest;
+use serde::Serialize;
+
+use super::forward_port::fork_point;
+use super::utils::{github_client, github_remote, load_config, open_repo_and_state, push};
+use crate::output;
+
+/// JSON output for the copy command.
+#[derive(Debug, Serialize)]
+struct CopyOutput {
+    branch: String,
+    copy: BranchName,
+    onto: String,
+    commits: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr_url: Option<String>,
+}
+
+/// Run the copy command.
+///
+/// Replays the commits unique to `branch` (the current branch by default)
+/// onto `onto` as a new branch, which isn't added to the stack. With `pr`,
+/// pushes the copy and opens a PR against `onto`.
+pub fn run(
+    json: bool,
+    branch: Option<&str>,
+    onto: &str,
+    name: Option<&str>,
+    pr: bool,
+    draft: bool,
+) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+
+    if state.is_sync_in_progress() {
+        bail!("Sync in progress - run `rung sync --continue` or `rung sync --abort` first");
+    }
+    if !repo.branch_exists(onto) {
+        bail!("Branch '{onto}' does not exist");
+    }
+    repo.require_clean()?;
+
+    let current = repo.current_branch()?;
+    let source = branch.unwrap_or(&current);
+    let stack = state.load_stack()?;
+    let tracked = stack
+        .find_branch(source)
+        .with_context(|| format!("Branch '{source}' is not in the stack"))?;
+
+    let copy = BranchName::new(name.map_or_else(|| tracked.forward_port_name(onto), String::from))
+        .context("Invalid branch name")?;
+    if repo.branch_exists(&copy) {
+        bail!("Branch '{copy}' already exists");
+    }
+
+    let tip = repo.branch_commit(source)?;
+    let from = fork_point(&repo, tracked, tip)?;
+    let commits = repo.count_commits_between(from, tip)?;
+    if commits == 0 {
+        bail!("Branch '{source}' has no commits of its own to copy");
+    }
+
+    match repo.copy_commits_onto(&copy, from, tip, repo.branch_commit(onto)?) {
+        Ok(()) => {}
+        Err(rung_git::Error::RebaseConflict(files)) => {
+            output::warn(&format!("Conflict while copying '{source}' onto '{onto}'"));
+            for file in &files {
+                println!("  → {file}");
+            }
+            output::info(&format!(
+                "Resolve and run `git rebase --continue` to finish '{copy}', or `git rebase --abort`"
+            ));
+            bail!("Copy of '{source}' stopped on conflicts");
+        }
+        Err(e) => return Err(e.into()),
+    }
+    repo.checkout(&current)?;
+
+    let mut output = CopyOutput {
+        branch: source.to_string(),
+        copy,
+        onto: onto.to_string(),
+        commits,
+        pr: None,
+        pr_url: None,
+    };
+
+    if pr {
+        let config = load_config(&repo, &state)?;
+        let remote = github_remote(&repo, &config)?;
+        push(
+            &repo,
+            &output.copy,
+            &output.copy,
+            false,
+            config.general.verify_pushes,
+        )?;
+
+        let (title, mut body) =
+            super::submit::get_pr_title_and_body(&repo, &output.copy, &config.pr);
+        if let Some(number) = tracked.pr {
+            body = format!("Copy of #{number} onto `{onto}`.\n\n{body}")
+                .trim_end()
+                .to_string();
+        }
+        let client =
+            github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+        let rt = tokio::runtime::Runtime::new()?;
+        let created = rt
+            .block_on(client.create_pr(
+                &remote.owner,
+                &remote.repo,
+                CreatePullRequest {
+                    title,
+                    body,
+                    head: output.copy.to_string(),
+                    base: onto.to_string(),
+                    draft,
+                },
+            ))
+            .context("Failed to create PR")?;
+        output.pr = Some(created.number);
+        output.pr_url = Some(created.html_url);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    output::success(&format!(
+        "Copied {} commit(s) from '{source}' onto '{onto}' as '{}'",
+        output.commits, output.copy
+    ));
+    if let Some(url) = &output.pr_url {
+        output::essential(url);
+    }
+    Ok(())
+}
@@ -1,22 +1,47 @@
 //! `rung create` command - Create a new branch in the stack.
 
+use std::fs;
+use std::path::Path;
+
 use anyhow::{Context, Result, bail};
-use rung_core::{BranchName, State, slugify, stack::StackBranch};
-use rung_git::Repository;
+use chrono::{DateTime, Utc};
+use rung_core::config::{CreateConfig, GeneralConfig, TemplateConfig};
+use rung_core::operations::{Operation, OperationKind};
+use rung_core::{BranchName, Config, Stack, State, slugify, stack::StackBranch};
+use rung_git::{CommitOptions, Repository};
 
-use crate::output;
+use super::utils::{check_origin, mapped_base};
+use crate::{output, prompt};
 
 /// Run the create command.
-pub fn run(name: Option<&str>, message: Option<&str>) -> Result<()> {
+#[allow(clippy::fn_params_excessive_bools, clippy::too_many_arguments)]
+pub fn run(
+    name: Option<&str>,
+    message: Option<&str>,
+    remote: Option<&str>,
+    no_verify: bool,
+    tracked: bool,
+    empty: bool,
+    signoff: bool,
+    force: bool,
+) -> Result<()> {
+    let started_at = Utc::now();
     // Determine the branch name: explicit > derived from message > error
     let name = match (name, message) {
         (Some(n), _) => n.to_string(),
         (None, Some(msg)) => slugify(msg),
         (None, None) => bail!("Either a branch name or --message must be provided"),
     };
+    if (tracked || empty || signoff) && message.is_none() {
+        bail!("--tracked, --empty and --signoff only apply when committing with --message");
+    }
 
     // Validate branch name
     let branch_name = BranchName::new(&name).context("Invalid branch name")?;
+    let remote = remote
+        .map(BranchName::new)
+        .transpose()
+        .context("Invalid remote branch name")?;
 
     // Validate message content (even when name is provided explicitly)
     if let Some(msg) = message {
@@ -30,58 +55,303 @@ pub fn run(name: Option<&str>, message: Option<&str>) -> Result<()> {
 
     // Get state manager
     let workdir = repo.workdir().context("Cannot run in bare repository")?;
-    let state = State::new(workdir)?;
+    let state = State::for_repository(&repo);
 
     // Ensure initialized
     if !state.is_initialized() {
         bail!("Rung not initialized - run `rung init` first");
     }
+    check_origin(&repo, &state)?;
+    repo.require_no_operation()?;
+    let config = state.load_config()?;
 
     // Get current branch (will be parent)
-    let parent_str = repo.current_branch()?;
-    let parent = BranchName::new(&parent_str).context("Invalid parent branch name")?;
+    let current = repo.current_branch()?;
 
     // Check if branch already exists
-    if repo.branch_exists(&name) {
-        bail!("Branch '{name}' already exists");
+    if repo.branch_exists(&branch_name) {
+        bail!("Branch '{branch_name}' already exists");
     }
+    if let Some(protected) = std::iter::once(&branch_name)
+        .chain(remote.as_ref())
+        .find(|name| config.general.is_protected(name))
+    {
+        bail!(
+            "'{protected}' is a protected branch (`general.protected_branches`) - pick another name"
+        );
+    }
+
+    let mut stack = state.load_stack()?;
+    let parent = if stack.find_branch(&current).is_none() {
+        new_stack_base(&repo, &config, &branch_name, current)?
+    } else {
+        BranchName::new(current).context("Invalid parent branch name")?
+    };
+    check_depth(stack.ancestry(&parent).len() + 1, &config.general, force)?;
+
+    // Stage like `git add -A` or `-u` (honouring .gitignore and clean filters
+    // such as Git LFS) before creating anything, so a refused commit leaves no branch
+    let staged = if message.is_none() {
+        false
+    } else if empty {
+        if repo.has_staged_changes()? {
+            bail!("--empty would commit the changes already staged - unstage them first");
+        }
+        true
+    } else {
+        if tracked {
+            repo.stage_tracked()
+        } else {
+            repo.stage_all()
+        }
+        .context("Failed to stage changes")?;
+        check_file_sizes(&repo, config.general.max_file_size_mb)?;
+        repo.has_staged_changes()?
+    };
 
     // Create the branch at current HEAD (parent's tip)
-    repo.create_branch(&name)?;
+    repo.create_branch(&branch_name)?;
 
     // Add to stack
-    let mut stack = state.load_stack()?;
-    let branch = StackBranch::new(branch_name, Some(parent.clone()));
+    let mut branch = StackBranch::new(branch_name.clone(), Some(parent.clone()));
+    branch.remote = remote;
+    branch.based_on = Some(repo.branch_commit(&parent)?.to_string());
     stack.add_branch(branch);
     state.save_stack(&stack)?;
 
     // Checkout the new branch
-    repo.checkout(&name)?;
+    repo.checkout(&branch_name)?;
 
-    // If message is provided, stage all changes and create a commit on the NEW branch
+    // If message is provided, commit the staged changes on the NEW branch
     if let Some(msg) = message {
-        // Check for changes before staging to provide clearer feedback
-        if repo.is_clean()? {
-            output::warn("Working directory is clean - branch created without commit");
-        } else {
-            repo.stage_all().context("Failed to stage changes")?;
-
-            if repo.has_staged_changes()? {
-                repo.create_commit(msg).context("Failed to create commit")?;
-                output::info(&format!("Created commit: {msg}"));
-            } else {
-                output::warn("No staged changes to commit (untracked files may exist)");
+        if staged {
+            let options = CommitOptions {
+                verify: !no_verify,
+                allow_empty: empty,
+                signoff: signoff || config.general.signoff,
+                ..CommitOptions::default()
+            };
+            let committed = repo.create_commit(msg, &options);
+            if let Err(e) = committed {
+                // Back out the branch, leaving the changes staged on the parent
+                repo.checkout(&parent)?;
+                repo.delete_branch(&branch_name)?;
+                stack.remove_branch(&branch_name);
+                state.save_stack(&stack)?;
+                bail!("Failed to create commit: {e}");
             }
+            output::info(&format!("Created commit: {msg}"));
+        } else {
+            output::warn("Working directory is clean - branch created without commit");
         }
     }
 
-    output::success(&format!("Created branch '{name}' with parent '{parent}'"));
+    output::success(&format!(
+        "Created branch '{branch_name}' with parent '{parent}'"
+    ));
+    report_next_steps(&stack, &branch_name, staged);
+    scaffold(workdir, &config.create, &branch_name, &parent);
+    record_create(&state, started_at, &stack, &branch_name, &parent);
+
+    Ok(())
+}
+
+/// Record the new branch in the operations log, for `rung summary`. It
+/// started a new stack if its parent isn't a stack branch.
+fn record_create(
+    state: &State,
+    started_at: DateTime<Utc>,
+    stack: &Stack,
+    branch: &str,
+    parent: &str,
+) {
+    let operation = Operation::finished(
+        started_at,
+        OperationKind::Create {
+            branch: branch.to_string(),
+            parent: parent.to_string(),
+            new_stack: stack.find_branch(parent).is_none(),
+        },
+    );
+    if let Err(e) = state.record_operation(&operation) {
+        output::warn(&format!(
+            "Could not record create in the operations log: {e}"
+        ));
+    }
+}
 
-    // Show position in stack
-    let ancestry = stack.ancestry(&name);
+/// Show the new branch's position in the stack and what to do next, given
+/// whether a commit was made on it.
+fn report_next_steps(stack: &Stack, branch: &str, committed: bool) {
+    let ancestry = stack.ancestry(branch);
     if ancestry.len() > 1 {
         output::info(&format!("Stack depth: {}", ancestry.len()));
     }
+    if committed {
+        output::hint("`rung submit` pushes it and opens a PR");
+    } else {
+        output::hint("Commit your changes, then `rung submit` pushes them and opens a PR");
+    }
+}
+
+/// Refuse to make the stack `depth` branches deep beyond `max_depth` unless
+/// forced, and warn from `warn_depth` on: long chains are slow to sync and
+/// submit, and hard to review.
+fn check_depth(depth: usize, general: &GeneralConfig, force: bool) -> Result<()> {
+    if general.max_depth > 0 && depth > general.max_depth && !force {
+        bail!(
+            "The stack would be {depth} branches deep, beyond max_depth ({}) - land the bottom branches, pass --force, or raise max_depth under [general]",
+            general.max_depth
+        );
+    }
+    if general.warn_depth > 0 && depth >= general.warn_depth {
+        output::warn(&format!(
+            "The stack is {depth} branches deep - consider landing the bottom branches first"
+        ));
+    }
+    Ok(())
+}
+
+/// Apply the `[create]` templates and run the scaffold command for a new branch.
+///
+/// The branch already exists at this point, so failures only warn.
+fn scaffold(workdir: &Path, config: &CreateConfig, branch: &str, parent: &str) {
+    for template in &config.templates {
+        let to = TemplateConfig::render(&template.to, branch, parent);
+        let dest = workdir.join(&to);
+        if dest.exists() {
+            output::warn(&format!("Template not applied - {to} already exists"));
+            continue;
+        }
+        let applied = fs::read_to_string(workdir.join(&template.from))
+            .map_err(|e| format!("could not read {}: {e}", template.from))
+            .and_then(|content| {
+                dest.parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|()| {
+                        fs::write(&dest, TemplateConfig::render(&content, branch, parent))
+                    })
+                    .map_err(|e| format!("could not write {to}: {e}"))
+            });
+        match applied {
+            Ok(()) => output::info(&format!("Created {to} from {}", template.from)),
+            Err(e) => output::warn(&format!("Template not applied - {e}")),
+        }
+    }
 
+    let Some(command) = &config.scaffold else {
+        return;
+    };
+    output::info(&format!("Running scaffold: {command}"));
+    let status = rung_core::shell::command(command)
+        .current_dir(workdir)
+        .env("RUNG_BRANCH", branch)
+        .env("RUNG_PARENT", parent)
+        .status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => output::warn(&format!("Scaffold command exited with {status}")),
+        Err(e) => output::warn(&format!("Could not run scaffold command: {e}")),
+    }
+}
+
+/// Where a new stack for `branch` starts: the current branch (a trunk), or
+/// the base `[[bases]]` maps it onto. Either way, makes sure that isn't stale.
+fn new_stack_base(
+    repo: &Repository,
+    config: &Config,
+    branch: &str,
+    current: String,
+) -> Result<BranchName> {
+    let base = match mapped_base(repo, config, branch)? {
+        Some(base) if base != current => {
+            let base = BranchName::new(base).context("Invalid parent branch name")?;
+            switch_to_base(repo, &base)?;
+            base
+        }
+        _ => BranchName::new(current).context("Invalid parent branch name")?,
+    };
+    check_trunk(repo, &base)?;
+    Ok(base)
+}
+
+/// Check out `base`, creating it from origin if there's no local branch, to
+/// start a new stack on it.
+fn switch_to_base(repo: &Repository, base: &BranchName) -> Result<()> {
+    if !repo.is_clean()? {
+        bail!(
+            "New branches of this name start from '{base}' ([[bases]]) - commit or stash your changes first"
+        );
+    }
+    if !repo.branch_exists(base) {
+        repo.create_branch_at(base, repo.remote_branch_commit(base)?)?;
+    }
+    repo.checkout(base)?;
+    output::info(&format!("Starting from '{base}' ([[bases]])"));
     Ok(())
 }
+
+/// Check that `trunk` (the current branch) is up to date with origin before
+/// starting a stack on it, offering to fast-forward it if it's behind.
+///
+/// Does nothing unless `origin/<trunk>` is tracked; a failed fetch only warns.
+fn check_trunk(repo: &Repository, trunk: &BranchName) -> Result<()> {
+    if repo.remote_branch_commit(trunk).is_err() {
+        return Ok(());
+    }
+    if let Err(e) = repo.fetch_remote_branch(trunk) {
+        output::warn(&format!(
+            "Could not fetch {trunk} to check it's up to date: {e}"
+        ));
+        return Ok(());
+    }
+    let local = repo.branch_commit(trunk)?;
+    let remote = repo.remote_branch_commit(trunk)?;
+    let merge_base = repo.merge_base(local, remote)?;
+    if merge_base == remote {
+        // Up to date, or only ahead with local commits
+        return Ok(());
+    }
+    if merge_base != local {
+        output::warn(&format!(
+            "'{trunk}' has diverged from origin/{trunk} - the new stack starts from your local '{trunk}'"
+        ));
+        return Ok(());
+    }
+
+    let behind = repo.count_commits_between(local, remote)?;
+    let question =
+        format!("'{trunk}' is {behind} commit(s) behind origin/{trunk}. Fast-forward it first?");
+    if !prompt::confirm(&question, true)? {
+        output::warn(&format!(
+            "'{trunk}' is {behind} commit(s) behind origin/{trunk} - the new branch will need a sync"
+        ));
+        return Ok(());
+    }
+    match repo.fast_forward(remote) {
+        Ok(()) => output::info(&format!("Fast-forwarded '{trunk}' to origin/{trunk}")),
+        Err(e) => output::warn(&format!("Could not fast-forward '{trunk}': {e}")),
+    }
+    Ok(())
+}
+
+/// Refuse to commit staged files larger than `limit_mb` (0 disables the check).
+fn check_file_sizes(repo: &Repository, limit_mb: u64) -> Result<()> {
+    if limit_mb == 0 {
+        return Ok(());
+    }
+    let large = repo.large_staged_files(limit_mb * 1024 * 1024)?;
+    if large.is_empty() {
+        return Ok(());
+    }
+    let files: Vec<String> = large
+        .iter()
+        .map(|(path, size)| format!("  {path} ({} MB)", size.div_ceil(1024 * 1024)))
+        .collect();
+    bail!(
+        "Refusing to commit files larger than {limit_mb} MB:\n{}\n\
+         Track them with Git LFS, add them to .gitignore, or raise `max_file_size_mb` under [general]. \
+         The changes are left staged.",
+        files.join("\n")
+    )
+}
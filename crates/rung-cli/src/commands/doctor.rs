@@ -1,13 +1,16 @@
 //! `rung doctor` command - Diagnose issues with the stack and repository.
 
 use anyhow::Result;
+use chrono::Utc;
 use colored::Colorize;
-use rung_core::State;
-use rung_git::Repository;
-use rung_github::{Auth, GitHubClient, PullRequestState};
+use rung_core::{State, config};
+use rung_git::{GitHubRemote, GitVersion, Repository};
+use rung_github::{HttpStatus, PullRequestState};
 use serde::Serialize;
 
-use crate::output;
+use super::utils::{github_client, origin_name, tip_time};
+use crate::i18n::tr;
+use crate::{actions, output};
 
 /// Diagnostic issue severity.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -35,6 +38,26 @@ struct DoctorOutput {
     issues: Vec<Issue>,
 }
 
+impl DoctorOutput {
+    fn new(issues: &[Issue]) -> Self {
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .count();
+        let warnings = issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+            .count();
+
+        Self {
+            healthy: errors == 0 && warnings == 0,
+            errors,
+            warnings,
+            issues: issues.to_vec(),
+        }
+    }
+}
+
 impl Issue {
     fn error(message: impl Into<String>) -> Self {
         Self {
@@ -59,8 +82,14 @@ impl Issue {
 }
 
 /// Run the doctor command.
-pub fn run(json: bool) -> Result<()> {
+///
+/// With `fix`, a corrupt stack file is restored from its previous version
+/// and pushed branches are made to track the origin branch rung pushes to.
+/// With `bugreport`, the checks run quietly and their results go into a
+/// bundle for attaching to an issue.
+pub fn run(json: bool, fix: bool, bugreport: bool) -> Result<()> {
     let mut issues: Vec<Issue> = Vec::new();
+    let verbose = !json && !bugreport;
 
     // Check if we're in a git repo
     let Ok(repo) = Repository::open_current() else {
@@ -71,18 +100,10 @@ pub fn run(json: bool) -> Result<()> {
         return Ok(());
     };
 
-    let Some(workdir) = repo.workdir() else {
-        if json {
-            return output_json(&[Issue::error("Cannot run in bare repository")]);
-        }
-        output::error("Cannot run in bare repository");
-        return Ok(());
-    };
-
-    let state = State::new(workdir)?;
+    let state = State::for_repository(&repo);
 
     // Check initialization
-    if !json {
+    if verbose {
         println!();
         print_check("Checking rung initialization...");
     }
@@ -91,84 +112,178 @@ pub fn run(json: bool) -> Result<()> {
             Issue::error("Rung not initialized in this repository")
                 .with_suggestion("Run `rung init` to initialize"),
         );
+        report_to_actions(&issues);
         if json {
             return output_json(&issues);
         }
         print_issues(&issues);
         return Ok(());
     }
-    if !json {
+    if verbose {
         print_ok();
     }
 
+    let mut http = vec![];
+    diagnose(&repo, &state, fix, verbose, &mut issues, &mut http)?;
+
+    report_to_actions(&issues);
+    if bugreport {
+        let report = serde_json::to_value(DoctorOutput::new(&issues))?;
+        return super::bugreport::run(json, &repo, &state, &report, &http);
+    }
+    if json {
+        return output_json(&issues);
+    }
+
+    println!();
+    print_issues(&issues);
+    print_summary(&issues);
+
+    Ok(())
+}
+
+/// Messages of the errors the doctor finds in an initialized repository,
+/// without printing anything. Warnings are left out.
+pub(super) fn errors(repo: &Repository, state: &State) -> Result<Vec<String>> {
+    let mut issues = vec![];
+    diagnose(repo, state, false, false, &mut issues, &mut vec![])?;
+    Ok(issues
+        .into_iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .map(|issue| issue.message)
+        .collect())
+}
+
+/// Run every check on an initialized repository, printing progress when
+/// `verbose`. The statuses of GitHub's responses are added to `http`.
+fn diagnose(
+    repo: &Repository,
+    state: &State,
+    fix: bool,
+    verbose: bool,
+    issues: &mut Vec<Issue>,
+    http: &mut Vec<HttpStatus>,
+) -> Result<()> {
+    // Check the git binary rung shells out to
+    if verbose {
+        print_check("Checking git version...");
+    }
+    check_git_version(&state.load_config()?, issues);
+    if verbose {
+        print_status(issues, "git version");
+    }
+
     // Check git state
-    if !json {
+    if verbose {
         print_check("Checking git state...");
     }
-    check_git_state(&repo, &mut issues);
-    if !json {
-        print_status(&issues, "git state");
+    check_git_state(repo, issues);
+    if verbose {
+        print_status(issues, "git state");
     }
 
     // Check stack integrity
-    if !json {
+    if verbose {
         print_check("Checking stack integrity...");
     }
-    let stack = state.load_stack()?;
-    check_stack_integrity(&repo, &stack, &mut issues);
-    if !json {
-        print_status(&issues, "stack integrity");
+    let Some(stack) = check_stack_file(state, fix, issues)? else {
+        if verbose {
+            print_status(issues, "stack integrity");
+        }
+        return Ok(());
+    };
+    check_stack_integrity(repo, &stack, issues);
+    if verbose {
+        print_status(issues, "stack integrity");
+    }
+
+    // Check where branches push and pull
+    if verbose {
+        print_check("Checking remote tracking...");
+    }
+    check_origin_matches(repo, state, issues);
+    check_remote_tracking(repo, &stack, fix, issues)?;
+    if verbose {
+        print_status(issues, "remote tracking");
     }
 
     // Check sync state
-    if !json {
+    if verbose {
         print_check("Checking sync state...");
     }
-    check_sync_state(&repo, &state, &stack, &mut issues);
-    if !json {
-        print_status(&issues, "sync state");
+    check_sync_state(repo, state, &stack, issues);
+    if verbose {
+        print_status(issues, "sync state");
     }
 
-    // Check GitHub connectivity
-    if !json {
-        print_check("Checking GitHub...");
+    // Check branch staleness
+    if verbose {
+        print_check("Checking branch activity...");
     }
-    check_github(&repo, &stack, &mut issues);
-    if !json {
-        print_status(&issues, "GitHub");
+    let config = state.load_config()?;
+    check_stale_branches(repo, &stack, config.general.stale_after_days, issues);
+    if verbose {
+        print_status(issues, "branch activity");
     }
 
-    // Output
-    if json {
-        return output_json(&issues);
+    // Check stack depth
+    if verbose {
+        print_check("Checking stack depth...");
+    }
+    check_stack_depth(&stack, config.general.warn_depth, issues);
+    if verbose {
+        print_status(issues, "stack depth");
     }
 
-    println!();
-    print_issues(&issues);
-    print_summary(&issues);
+    // Check the repository config against the team's
+    if verbose {
+        print_check("Checking team policy...");
+    }
+    check_team_policy(state, &config, &stack, issues)?;
+    if verbose {
+        print_status(issues, "team policy");
+    }
 
+    // Check GitHub connectivity
+    if verbose {
+        print_check("Checking GitHub...");
+    }
+    check_github(repo, &config, &stack, issues, http);
+    if verbose {
+        print_status(issues, "GitHub");
+    }
     Ok(())
 }
 
+/// Annotate the workflow run with each issue and set the `healthy`,
+/// `errors` and `warnings` step outputs, when running in GitHub Actions.
+fn report_to_actions(issues: &[Issue]) {
+    for issue in issues {
+        let level = match issue.severity {
+            Severity::Error => actions::Level::Error,
+            Severity::Warning => actions::Level::Warning,
+        };
+        match &issue.suggestion {
+            Some(suggestion) => {
+                actions::annotate(level, &format!("{}\n{suggestion}", issue.message));
+            }
+            None => actions::annotate(level, &issue.message),
+        }
+    }
+    let summary = DoctorOutput::new(issues);
+    actions::set_outputs(&[
+        ("healthy", summary.healthy.to_string()),
+        ("errors", summary.errors.to_string()),
+        ("warnings", summary.warnings.to_string()),
+    ]);
+}
+
 /// Output issues as JSON.
 fn output_json(issues: &[Issue]) -> Result<()> {
-    let errors = issues
-        .iter()
-        .filter(|i| i.severity == Severity::Error)
-        .count();
-    let warnings = issues
-        .iter()
-        .filter(|i| i.severity == Severity::Warning)
-        .count();
-
-    let output = DoctorOutput {
-        healthy: errors == 0 && warnings == 0,
-        errors,
-        warnings,
-        issues: issues.to_vec(),
-    };
-
-    println!("{}", serde_json::to_string_pretty(&output)?);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&DoctorOutput::new(issues))?
+    );
     Ok(())
 }
 
@@ -177,7 +292,16 @@ fn print_check(message: &str) {
 }
 
 fn print_ok() {
-    println!(" {}", "✓".green());
+    println!(" {}", ok_icon());
+}
+
+/// What marks a passed check: a green tick, or a word in accessible mode.
+fn ok_icon() -> String {
+    if output::is_accessible() {
+        tr!("a11y-ok")
+    } else {
+        "✓".green().to_string()
+    }
 }
 
 fn print_status(issues: &[Issue], _category: &str) {
@@ -185,11 +309,11 @@ fn print_status(issues: &[Issue], _category: &str) {
     let has_warnings = issues.iter().any(|i| i.severity == Severity::Warning);
 
     if has_errors {
-        println!(" {}", "✗".red());
+        println!(" {}", output::error_icon());
     } else if has_warnings {
-        println!(" {}", "⚠".yellow());
+        println!(" {}", output::warning_icon("⚠"));
     } else {
-        println!(" {}", "✓".green());
+        println!(" {}", ok_icon());
     }
 }
 
@@ -200,14 +324,19 @@ fn print_issues(issues: &[Issue]) {
 
     for issue in issues {
         let icon = match issue.severity {
-            Severity::Error => "✗".red(),
-            Severity::Warning => "⚠".yellow(),
+            Severity::Error => output::error_icon(),
+            Severity::Warning => output::warning_icon("⚠"),
         };
 
         println!("  {icon} {}", issue.message);
 
         if let Some(suggestion) = &issue.suggestion {
-            println!("    {} {suggestion}", "→".dimmed());
+            let arrow = if output::is_accessible() {
+                tr!("a11y-suggestion")
+            } else {
+                "→".dimmed().to_string()
+            };
+            println!("    {arrow} {suggestion}");
         }
     }
     println!();
@@ -241,29 +370,140 @@ fn print_summary(issues: &[Issue]) {
     println!();
 }
 
+/// Load the stack file, restoring the previous version if it's corrupt and `fix` is set.
+///
+/// Returns `None` if the stack is unusable, after recording why.
+fn check_stack_file(
+    state: &State,
+    fix: bool,
+    issues: &mut Vec<Issue>,
+) -> Result<Option<rung_core::Stack>> {
+    let message = match state.load_stack() {
+        Ok(stack) => return Ok(Some(stack)),
+        Err(rung_core::Error::CorruptStack { message, .. }) => message,
+        Err(e) => return Err(e.into()),
+    };
+
+    if fix && state.has_stack_backup() {
+        let stack = state.restore_stack_backup()?;
+        issues.push(
+            Issue::warning(format!(
+                "stack.json was corrupt ({message}) and has been restored from the previous version"
+            ))
+            .with_suggestion(
+                "Check `rung status`; the damaged file was kept as .git/rung/stack.json.corrupt",
+            ),
+        );
+        return Ok(Some(stack));
+    }
+
+    let suggestion = if state.has_stack_backup() {
+        "Run `rung doctor --fix` to restore the previous version"
+    } else {
+        "No backup available - remove .git/rung/stack.json, run `rung init` and re-create the stack"
+    };
+    issues.push(
+        Issue::error(format!("stack.json is corrupt: {message}")).with_suggestion(suggestion),
+    );
+    Ok(None)
+}
+
+/// Check that the git binary exists and supports the flags rung will pass it.
+fn check_git_version(config: &rung_core::Config, issues: &mut Vec<Issue>) {
+    let version = match GitVersion::installed() {
+        Ok(version) => version,
+        Err(e) => {
+            issues.push(
+                Issue::error(format!("Could not run git: {e}")).with_suggestion(format!(
+                    "Install git {} or newer from https://git-scm.com/downloads and make sure it's on PATH",
+                    GitVersion::MINIMUM
+                )),
+            );
+            return;
+        }
+    };
+
+    if version < GitVersion::MINIMUM {
+        issues.push(
+            Issue::error(format!(
+                "git {version} is too old - rung needs {} or newer",
+                GitVersion::MINIMUM
+            ))
+            .with_suggestion(upgrade_advice()),
+        );
+        return;
+    }
+
+    if config.sync.committer_date_is_author_date
+        && version < GitVersion::COMMITTER_DATE_IS_AUTHOR_DATE
+    {
+        issues.push(
+            Issue::warning(format!(
+                "git {version} can't keep committer dates when rebasing - needs {} or newer",
+                GitVersion::COMMITTER_DATE_IS_AUTHOR_DATE
+            ))
+            .with_suggestion(format!(
+                "{}, or turn off `committer_date_is_author_date` under [sync]",
+                upgrade_advice()
+            )),
+        );
+    }
+}
+
+/// Platform-specific advice for upgrading git.
+const fn upgrade_advice() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Upgrade with `brew install git` (Apple's bundled git lags behind)"
+    } else if cfg!(windows) {
+        "Upgrade with `winget upgrade Git.Git` or from https://git-scm.com/downloads"
+    } else {
+        "Upgrade with your package manager, or see https://git-scm.com/downloads"
+    }
+}
+
 /// Check git repository state.
 fn check_git_state(repo: &Repository, issues: &mut Vec<Issue>) {
-    // Check for dirty working directory
-    if !repo.is_clean().unwrap_or(false) {
+    // Check for dirty working directory (a bare clone has none)
+    if !repo.is_bare() && !repo.is_clean().unwrap_or(false) {
         issues.push(
             Issue::warning("Working directory has uncommitted changes")
                 .with_suggestion("Commit or stash changes before running rung commands"),
         );
     }
 
-    // Check for detached HEAD
-    if repo.current_branch().is_err() {
+    // Check the identity commits are made with
+    if repo.signature().is_err() {
+        issues.push(
+            Issue::error("No git identity: user.name or user.email is not set")
+                .with_suggestion(
+                    "Set them with `git config user.name` and `git config user.email`, or in an `includeIf` section covering this repository",
+                ),
+        );
+    }
+
+    // Check for a fresh repository, or detached HEAD
+    if let Some(unborn) = repo.unborn_branch() {
+        issues.push(
+            Issue::warning(format!("'{unborn}' has no commits yet"))
+                .with_suggestion(format!(
+                    "Make the first commit on '{unborn}' with `git commit`, then `rung create <name>` to start a stack"
+                )),
+        );
+    } else if repo.current_branch().is_err() {
         issues.push(
             Issue::error("HEAD is detached (not on a branch)")
                 .with_suggestion("Checkout a branch with `git checkout <branch>`"),
         );
     }
 
-    // Check for rebase in progress
-    if repo.is_rebasing() {
+    // Check for a merge, rebase, cherry-pick, etc. left unfinished
+    if let Some(op) = repo.in_progress() {
+        let mut resolution = op.resolution();
+        resolution[..1].make_ascii_uppercase();
         issues.push(
-            Issue::error("Rebase in progress")
-                .with_suggestion("Complete or abort the rebase before running rung commands"),
+            Issue::error(format!("A git {} is in progress", op.command())).with_suggestion(
+                format!("{resolution} before running commands that change branches"),
+            ),
         );
     }
 
@@ -271,6 +511,52 @@ fn check_git_state(repo: &Repository, issues: &mut Vec<Issue>) {
     // This is handled by State, so we skip it here
 }
 
+/// Check that commits are authored with an email of the GitHub account the
+/// token belongs to, as DCO and CLA checks expect. Skipped when the token
+/// can't read the account's emails.
+fn check_identity_matches_account(
+    repo: &Repository,
+    rt: &tokio::runtime::Runtime,
+    client: &rung_github::GitHubClient,
+    issues: &mut Vec<Issue>,
+) {
+    let Some(email) = repo
+        .author_signature()
+        .ok()
+        .and_then(|sig| sig.email().map(str::to_lowercase))
+    else {
+        return;
+    };
+    let Ok((login, emails)) = rt.block_on(async {
+        let login = client.current_user().await?;
+        let emails = client.current_user_emails().await?;
+        Ok::<_, rung_github::Error>((login, emails))
+    }) else {
+        return;
+    };
+
+    if !emails.iter().any(|e| e.eq_ignore_ascii_case(&email)) && !is_noreply(&email, &login) {
+        issues.push(
+            Issue::warning(format!(
+                "Commits are authored as {email}, which isn't an email of GitHub account @{login}"
+            ))
+            .with_suggestion(
+                "DCO and CLA checks may fail - add the email to your account, or set user.email (e.g. in an `includeIf` section for this directory)",
+            ),
+        );
+    }
+}
+
+/// Whether `email` is `login`'s GitHub noreply address, such as
+/// `123+octo@users.noreply.github.com`.
+fn is_noreply(email: &str, login: &str) -> bool {
+    let Some((user, domain)) = email.split_once('@') else {
+        return false;
+    };
+    let user = user.split_once('+').map_or(user, |(_, user)| user);
+    domain.starts_with("users.noreply.") && user.eq_ignore_ascii_case(login)
+}
+
 /// Check stack integrity.
 fn check_stack_integrity(repo: &Repository, stack: &rung_core::Stack, issues: &mut Vec<Issue>) {
     for branch in &stack.branches {
@@ -298,37 +584,120 @@ fn check_stack_integrity(repo: &Repository, stack: &rung_core::Stack, issues: &m
     }
 
     // Check for circular dependencies
-    for branch in &stack.branches {
-        if has_circular_dependency(stack, &branch.name, &mut vec![]) {
-            issues.push(Issue::error(format!(
-                "Circular dependency detected involving '{}'",
-                branch.name
-            )));
-        }
+    if let Err(e) = stack.topological_order() {
+        issues.push(
+            Issue::error(format!("Stack parents form a cycle: {e}"))
+                .with_suggestion("Fix the `parent` fields in .git/rung/stack.json"),
+        );
+    }
+}
+
+/// Check that origin still points at the GitHub repository the stack was set
+/// up for.
+fn check_origin_matches(repo: &Repository, state: &State, issues: &mut Vec<Issue>) {
+    let (Some(recorded), Some(current)) = (state.load_origin(), origin_name(repo, state)) else {
+        return;
+    };
+    if !recorded.eq_ignore_ascii_case(&current) {
+        issues.push(
+            Issue::error(format!(
+                "The stack was set up for {recorded}, but origin points at {current}"
+            ))
+            .with_suggestion(
+                "Point origin back at it, or run `rung init --reattach` if the repository moved",
+            ),
+        );
     }
 }
 
-/// Check if a branch has a circular dependency.
-fn has_circular_dependency(
+/// Check that each pushed stack branch tracks the origin branch rung pushes
+/// it to, and that git's push settings send a plain `git push` there too.
+/// With `fix`, wrong or missing upstreams are set.
+fn check_remote_tracking(
+    repo: &Repository,
     stack: &rung_core::Stack,
-    branch_name: &str,
-    visited: &mut Vec<String>,
-) -> bool {
-    if visited.contains(&branch_name.to_string()) {
-        return true;
+    fix: bool,
+    issues: &mut Vec<Issue>,
+) -> Result<()> {
+    if repo.config_string("remote.origin.url").is_none() {
+        return Ok(());
     }
 
-    visited.push(branch_name.to_string());
+    let push_default = repo.config_string("push.default");
+    if push_default.as_deref() == Some("matching") {
+        issues.push(
+            Issue::warning("push.default is `matching`, so `git push` pushes every branch that exists on origin")
+                .with_suggestion("Run `git config push.default simple`"),
+        );
+    }
+    if let Some(remote) = repo
+        .config_string("remote.pushDefault")
+        .filter(|remote| remote != "origin")
+    {
+        issues.push(
+            Issue::warning(format!(
+                "remote.pushDefault is '{remote}', but rung pushes stack branches to origin"
+            ))
+            .with_suggestion("Run `git config --unset remote.pushDefault`"),
+        );
+    }
 
-    if let Some(branch) = stack.find_branch(branch_name) {
-        if let Some(parent) = &branch.parent {
-            if stack.find_branch(parent).is_some() {
-                return has_circular_dependency(stack, parent, visited);
+    for branch in &stack.branches {
+        let remote_name = branch.remote_name();
+        if !repo.branch_exists(&branch.name) || repo.remote_branch_commit(remote_name).is_err() {
+            continue;
+        }
+
+        if let Some(remote) = repo
+            .config_string(&format!("branch.{}.pushRemote", branch.name))
+            .filter(|remote| remote != "origin")
+        {
+            issues.push(
+                Issue::warning(format!(
+                    "Branch '{}' pushes to '{remote}', but rung pushes it to origin",
+                    branch.name
+                ))
+                .with_suggestion(format!(
+                    "Run `git config --unset branch.{}.pushRemote`",
+                    branch.name
+                )),
+            );
+        }
+
+        if push_default.as_deref() == Some("current") && branch.name != *remote_name {
+            issues.push(
+                Issue::warning(format!(
+                    "`git push` on '{}' would create origin/{} instead of updating origin/{remote_name}",
+                    branch.name, branch.name
+                ))
+                .with_suggestion("Run `git config push.default upstream`, or push with `rung submit`"),
+            );
+        }
+
+        let tracking = match repo.upstream_of(&branch.name) {
+            Some((remote, merge)) if remote == "origin" && merge == remote_name.as_str() => {
+                continue;
             }
+            Some((remote, merge)) => format!("tracks {remote}/{merge}"),
+            None => "has no upstream".to_string(),
+        };
+        if fix {
+            repo.set_upstream(&branch.name, remote_name)?;
+            issues.push(Issue::warning(format!(
+                "Branch '{}' {tracking} and now tracks origin/{remote_name}",
+                branch.name
+            )));
+        } else {
+            issues.push(
+                Issue::warning(format!(
+                    "Branch '{}' {tracking}, but rung pushes it to origin/{remote_name}",
+                    branch.name
+                ))
+                .with_suggestion("Run `rung doctor --fix` to set it"),
+            );
         }
     }
-
-    false
+    Ok(())
 }
 
 /// Check sync state of branches.
@@ -379,26 +748,115 @@ fn check_sync_state(
     }
 }
 
-/// Check GitHub connectivity and PR state.
-fn check_github(repo: &Repository, stack: &rung_core::Stack, issues: &mut Vec<Issue>) {
-    // Check auth
-    let auth = Auth::auto();
-    let Ok(client) = GitHubClient::new(&auth) else {
+/// Check for branches that haven't been touched in a while.
+fn check_stale_branches(
+    repo: &Repository,
+    stack: &rung_core::Stack,
+    threshold_days: u32,
+    issues: &mut Vec<Issue>,
+) {
+    let now = Utc::now();
+    for branch in &stack.branches {
+        let tip_time = tip_time(repo, &branch.name);
+        if branch.is_stale(threshold_days, now, tip_time) {
+            issues.push(
+                Issue::warning(format!(
+                    "Branch '{}' has been stale for {} days",
+                    branch.name,
+                    branch.idle_days(now, tip_time)
+                ))
+                .with_suggestion("Merge, rebase, or delete abandoned branches"),
+            );
+        }
+    }
+}
+
+/// Check for stacks at least `warn_depth` branches deep.
+fn check_stack_depth(stack: &rung_core::Stack, warn_depth: usize, issues: &mut Vec<Issue>) {
+    let depth = stack.depth();
+    if warn_depth > 0 && depth >= warn_depth {
         issues.push(
-            Issue::error("GitHub authentication failed")
-                .with_suggestion("Set GITHUB_TOKEN or authenticate with `gh auth login`"),
+            Issue::warning(format!("The stack is {depth} branches deep"))
+                .with_suggestion("Land the bottom branches to keep syncs and reviews quick"),
         );
-        return;
-    };
+    }
+}
+
+/// Check for repository settings overriding the team config, for stack
+/// branches or settings the policy in effect rules out, and for invalid
+/// `[theme]` settings.
+fn check_team_policy(
+    state: &State,
+    config: &rung_core::Config,
+    stack: &rung_core::Stack,
+    issues: &mut Vec<Issue>,
+) -> Result<()> {
+    for overridden in config::team_overrides(state.config_path(), state.team_config_path())? {
+        issues.push(
+            Issue::warning(format!(
+                "`{}` is {} here, overriding the team's {}",
+                overridden.key, overridden.local, overridden.team
+            ))
+            .with_suggestion(format!(
+                "Remove it from {} to follow {}",
+                state.config_path().display(),
+                rung_core::Config::TEAM_PATH
+            )),
+        );
+    }
+    if let Err(e) = config.pr.title_regex() {
+        issues.push(Issue::error(e.to_string()));
+    }
+    for problem in output::Theme::from_config(&config.theme).1 {
+        issues.push(Issue::warning(problem).with_suggestion("Fix the `[theme]` config"));
+    }
+    for branch in &stack.branches {
+        if config.general.is_protected(&branch.name) {
+            issues.push(
+                Issue::error(format!(
+                    "Branch '{}' is protected by `general.protected_branches`",
+                    branch.name
+                ))
+                .with_suggestion("rung won't push it - move its commits to another branch"),
+            );
+        }
+    }
+    Ok(())
+}
 
+/// Check GitHub connectivity and PR state.
+fn check_github(
+    repo: &Repository,
+    config: &rung_core::Config,
+    stack: &rung_core::Stack,
+    issues: &mut Vec<Issue>,
+    http: &mut Vec<HttpStatus>,
+) {
     // Get repo info
     let Ok(origin_url) = repo.origin_url() else {
         issues.push(Issue::warning("No origin remote configured"));
         return;
     };
 
-    let Ok((owner, repo_name)) = Repository::parse_github_remote(&origin_url) else {
-        issues.push(Issue::warning("Origin is not a GitHub repository"));
+    let Ok(remote) = GitHubRemote::parse(&origin_url, &config.github.allowed_hosts()) else {
+        issues.push(
+            Issue::warning("Origin is not a GitHub repository")
+                .with_suggestion(
+                    "For GitHub Enterprise, add the host to `hosts` under [github]; for an SSH host alias, map it under `ssh_aliases`",
+                ),
+        );
+        return;
+    };
+    let (owner, repo_name) = (remote.owner.clone(), remote.repo.clone());
+
+    // Check auth
+    let Ok(client) = github_client(&remote, config) else {
+        issues.push(
+            Issue::error("GitHub authentication failed")
+                .with_suggestion(
+                    "Set GITHUB_TOKEN, authenticate with `gh auth login`, or store a token for github.com in git's credential helper",
+                ),
+        );
         return;
     };
 
@@ -438,4 +896,17 @@ fn check_github(repo: &Repository, stack: &rung_core::Stack, issues: &mut Vec<Is
             }
         }
     }
+
+    check_identity_matches_account(repo, &rt, &client, issues);
+
+    if let Some(rate_limit) = client.rate_limit().filter(rung_github::RateLimit::is_low) {
+        issues.push(
+            Issue::warning(format!(
+                "GitHub API budget is low: {} of {} requests left",
+                rate_limit.remaining, rate_limit.limit
+            ))
+            .with_suggestion("Waits such as `rung merge` slow down until it resets"),
+        );
+    }
+    http.extend(client.http_statuses());
 }
@@ -0,0 +1,136 @@
+//! `rung edit` command - Interactive rebase scoped to the current branch.
+
+use anyhow::{Context, Result, bail};
+use rung_core::stack::Stack;
+use rung_core::sync::{self, RestackResult};
+use rung_git::Repository;
+
+use super::utils::{backup_tips, open_repo_and_state, push};
+use crate::{output, prompt};
+
+/// Run the edit command.
+///
+/// Opens `git rebase -i` over only the commits unique to the current branch,
+/// then replays every descendant onto the rewritten branch.
+pub fn run(push: bool) -> Result<()> {
+    prompt::require_editor(
+        "GIT_SEQUENCE_EDITOR",
+        "Editing commits",
+        "set GIT_SEQUENCE_EDITOR to script the rebase",
+    )?;
+    let (repo, state) = open_repo_and_state()?;
+    repo.require_clean()?;
+
+    if state.is_sync_in_progress() {
+        bail!("Sync in progress - run `rung sync --continue` or `rung sync --abort` first");
+    }
+
+    let current = repo.current_branch()?;
+    let stack = state.load_stack()?;
+    let branch = stack
+        .find_branch(&current)
+        .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+    let parent = branch
+        .parent
+        .as_ref()
+        .with_context(|| format!("Branch '{current}' has no parent branch"))?;
+    let parent_tip = repo.branch_commit(parent)?;
+
+    // Capture tips before rewriting so descendants can be replayed with --onto
+    let mut affected: Vec<String> = vec![current.clone()];
+    affected.extend(
+        stack
+            .descendants_of(&current)
+            .iter()
+            .map(|b| b.name.to_string()),
+    );
+    let old_tips = sync::capture_tips(&repo, &affected)?;
+
+    backup_tips(&state, "edit", &old_tips)?;
+
+    output::info(&format!(
+        "Editing commits on '{current}' (onto '{parent}')..."
+    ));
+
+    match repo.rebase_interactive(parent_tip) {
+        Ok(()) if repo.is_rebasing() => {
+            output::warn("Rebase paused for editing");
+            output::info("Finish with `git rebase --continue`, then run `rung sync` to restack");
+            return Ok(());
+        }
+        Ok(()) => {}
+        Err(rung_git::Error::RebaseConflict(files)) => {
+            output::warn(&format!("Conflict while editing '{current}'"));
+            for file in &files {
+                println!("  → {file}");
+            }
+            output::info("Resolve, run `git rebase --continue`, then `rung sync` to restack");
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    if repo.branch_commit(&current)? == old_tips[&current] {
+        output::info("No changes made");
+        return Ok(());
+    }
+
+    output::success(&format!("Rewrote '{current}'"));
+
+    let config = state.load_config()?;
+    let result = sync::restack_descendants(&repo, &stack, &config, &current, &old_tips)?;
+    let rebased = match result {
+        RestackResult::Complete { rebased } => rebased,
+        RestackResult::Conflict {
+            at_branch,
+            conflict_files,
+            ..
+        } => {
+            output::warn(&format!("Conflict while restacking '{at_branch}'"));
+            for file in &conflict_files {
+                println!("  → {file}");
+            }
+            output::info("Resolve, run `git rebase --continue`, then `rung sync` to finish");
+            return Ok(());
+        }
+    };
+
+    repo.checkout(&current)?;
+    sync::record_bases(&repo, &state, &rebased)?;
+    if !rebased.is_empty() {
+        output::success(&format!("Restacked {} descendant(s)", rebased.len()));
+    }
+
+    let mut to_push = vec![current];
+    to_push.extend(rebased);
+    if push || confirm_push(to_push.len())? {
+        push_branches(&repo, &stack, &to_push, config.general.verify_pushes)?;
+    } else {
+        output::info("Run `rung submit` to push when ready");
+    }
+
+    Ok(())
+}
+
+/// Ask whether to push the rewritten branches (only on a terminal).
+pub(super) fn confirm_push(count: usize) -> Result<bool> {
+    prompt::confirm(&format!("Force-push {count} rewritten branch(es)?"), false)
+}
+
+/// Force-push the given branches under their remote names, reading each back
+/// from origin with `verify`.
+pub(super) fn push_branches(
+    repo: &Repository,
+    stack: &Stack,
+    branches: &[String],
+    verify: bool,
+) -> Result<()> {
+    for name in branches {
+        let branch = stack
+            .find_branch(name)
+            .with_context(|| format!("Branch '{name}' is not in the stack"))?;
+        push(repo, &branch.name, branch.remote_name(), true, verify)?;
+    }
+    output::success(&format!("Pushed {} branch(es)", branches.len()));
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! `rung foreach` command - Run a command in each directory a branch affects.
+
+use anyhow::{Context, Result, bail};
+use rung_core::scope;
+
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// Run the foreach command.
+///
+/// Directories come from the current branch's path scope if it has one,
+/// otherwise from the configured packages it touches. With `all`, the command
+/// runs in every configured package.
+pub fn run(all: bool, command: &[String]) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let workdir = repo.workdir().context("Cannot run in bare repository")?;
+    let config = state.load_config()?;
+
+    let dirs: Vec<String> = if all {
+        config.packages.iter().map(|p| p.path.clone()).collect()
+    } else {
+        let current = repo.current_branch()?;
+        let stack = state.load_stack()?;
+        let branch = stack
+            .find_branch(&current)
+            .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+
+        if branch.paths.is_empty() {
+            let files = match &branch.parent {
+                Some(parent) => repo.changed_files(&current, parent)?,
+                None => vec![],
+            };
+            scope::touched_packages(&files, &config.packages)
+                .iter()
+                .map(|p| p.path.clone())
+                .collect()
+        } else {
+            branch.paths.clone()
+        }
+    };
+
+    if dirs.is_empty() {
+        output::info("No affected directories - configure [[packages]] or run `rung scope <path>`");
+        return Ok(());
+    }
+
+    let script = command.join(" ");
+    let mut failed = vec![];
+    for dir in &dirs {
+        let display = if dir.is_empty() { "." } else { dir.as_str() };
+        output::info(&format!("{display}: {script}"));
+
+        let status = rung_core::shell::command(&script)
+            .current_dir(workdir.join(dir))
+            .status()
+            .with_context(|| format!("Failed to run command in {display}"))?;
+
+        if !status.success() {
+            output::error(&format!("{display}: exited with {status}"));
+            failed.push(display.to_string());
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("Command failed in: {}", failed.join(", "));
+    }
+
+    output::success(&format!("Ran in {} director(ies)", dirs.len()));
+    Ok(())
+}
@@ -0,0 +1,210 @@
+//! `rung forward-port` command - Copy a stack landing on a release branch
+//! onto another branch, such as `main`.
+
+use anyhow::{Context, Result, bail};
+use rung_core::{BranchName, StackBranch};
+use rung_git::{Oid, Repository};
+use serde::Serialize;
+
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// JSON output for forward-port command.
+#[derive(Debug, Serialize)]
+struct ForwardPortOutput {
+    target: String,
+    ported: Vec<PortedBranch>,
+    already_ported: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict: Option<String>,
+}
+
+/// A branch copied onto the target.
+#[derive(Debug, Serialize)]
+struct PortedBranch {
+    branch: String,
+    copy: String,
+    parent: String,
+}
+
+/// Run the forward-port command.
+///
+/// Replays each branch in the current stack that hasn't been ported to
+/// `target` yet onto the copy of its parent (or `target` itself for the
+/// root), registering the copies as a new stack on `target`.
+pub fn run(json: bool, target: &str, dry_run: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+
+    if state.is_sync_in_progress() {
+        bail!("Sync in progress - run `rung sync --continue` or `rung sync --abort` first");
+    }
+    if !repo.branch_exists(target) {
+        bail!("Branch '{target}' does not exist");
+    }
+
+    let current = repo.current_branch()?;
+    let mut stack = state.load_stack()?;
+    let members: Vec<StackBranch> = stack.chain_through(&current).into_iter().cloned().collect();
+    let Some(root) = members.first() else {
+        bail!("Current branch '{current}' is not in stack");
+    };
+    if root.parent.as_deref() == Some(target) {
+        bail!("The stack is already based on '{target}'");
+    }
+
+    // Copies stack like the originals, with the root's copy on `target`
+    let copy_parent = |branch: &StackBranch| {
+        members
+            .iter()
+            .find(|m| branch.parent.as_deref() == Some(m.name.as_str()))
+            .map_or_else(|| target.to_string(), |m| m.forward_port_name(target))
+    };
+
+    let mut result = ForwardPortOutput {
+        target: target.to_string(),
+        ported: vec![],
+        already_ported: vec![],
+        conflict: None,
+    };
+    let pending: Vec<&StackBranch> = members
+        .iter()
+        .filter(|b| {
+            if b.is_forward_ported(target) {
+                result.already_ported.push(b.name.to_string());
+                false
+            } else {
+                true
+            }
+        })
+        .collect();
+
+    if dry_run {
+        result.ported = pending
+            .iter()
+            .map(|b| PortedBranch {
+                branch: b.name.to_string(),
+                copy: b.forward_port_name(target),
+                parent: copy_parent(b),
+            })
+            .collect();
+        return report(json, &result, true);
+    }
+
+    if pending.is_empty() {
+        return report(json, &result, false);
+    }
+    repo.require_clean()?;
+
+    for branch in pending {
+        let copy = branch.forward_port_name(target);
+        let copy_name = BranchName::new(&copy)
+            .with_context(|| format!("Cannot forward-port '{}'", branch.name))?;
+        let parent = copy_parent(branch);
+
+        // A copy left by an earlier run that stopped on a conflict is kept
+        if !repo.branch_exists(&copy) {
+            let tip = repo.branch_commit(&branch.name)?;
+            let from = fork_point(&repo, branch, tip)?;
+            let onto = repo.branch_commit(&parent)?;
+            if !json {
+                output::info(&format!("Porting '{}' onto '{parent}'...", branch.name));
+            }
+            match repo.copy_commits_onto(&copy_name, from, tip, onto) {
+                Ok(()) => {}
+                Err(rung_git::Error::RebaseConflict(files)) => {
+                    state.save_stack(&stack)?;
+                    result.conflict = Some(copy.clone());
+                    if json {
+                        return report(json, &result, false);
+                    }
+                    report_conflict(&branch.name, &copy, &files, target);
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if stack.find_branch(&copy).is_none() {
+            let mut entry = StackBranch::new(copy_name, Some(BranchName::new(&parent)?));
+            entry.based_on = Some(repo.branch_commit(&parent)?.to_string());
+            stack.add_branch(entry);
+        }
+        if let Some(source) = stack.find_branch_mut(&branch.name) {
+            source.forward_ports.push(target.to_string());
+        }
+        state.save_stack(&stack)?;
+        result.ported.push(PortedBranch {
+            branch: branch.name.to_string(),
+            copy,
+            parent,
+        });
+    }
+
+    repo.checkout(&current)?;
+    report(json, &result, false)
+}
+
+/// Where `branch`'s own commits start: the fork point from its parent, or
+/// the parent tip rung last based it on when the parent is gone.
+pub(super) fn fork_point(repo: &Repository, branch: &StackBranch, tip: Oid) -> Result<Oid> {
+    if let Some(parent) = branch
+        .parent
+        .as_deref()
+        .and_then(|p| repo.branch_commit(p).ok())
+    {
+        return Ok(repo.merge_base(tip, parent)?);
+    }
+    branch
+        .based_on
+        .as_deref()
+        .and_then(|sha| Oid::from_str(sha).ok())
+        .filter(|oid| repo.commit_exists(*oid))
+        .with_context(|| {
+            format!(
+                "Cannot find where '{}' starts - its parent is gone",
+                branch.name
+            )
+        })
+}
+
+/// Explain how to carry on after porting `branch` as `copy` stopped on conflicts.
+fn report_conflict(branch: &str, copy: &str, files: &[String], target: &str) {
+    output::warn(&format!("Conflict while porting '{branch}' as '{copy}'"));
+    for file in files {
+        println!("  → {file}");
+    }
+    output::info(&format!(
+        "Resolve, run `git rebase --continue`, then `rung forward-port {target}` again"
+    ));
+}
+
+/// Print the outcome.
+fn report(json: bool, result: &ForwardPortOutput, dry_run: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(result)?);
+        return Ok(());
+    }
+
+    for name in &result.already_ported {
+        output::info(&format!(
+            "'{name}' was already ported to '{}'",
+            result.target
+        ));
+    }
+    if result.ported.is_empty() {
+        output::info(&format!("Nothing left to port to '{}'", result.target));
+        return Ok(());
+    }
+
+    let verb = if dry_run { "Would port" } else { "Ported" };
+    for ported in &result.ported {
+        output::success(&format!(
+            "{verb} '{}' as '{}' (onto '{}')",
+            ported.branch, ported.copy, ported.parent
+        ));
+    }
+    if !dry_run {
+        output::info("Run `rung submit` on the copies to open their PRs");
+    }
+    Ok(())
+}
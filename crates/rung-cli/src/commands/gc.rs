@@ -0,0 +1,86 @@
+//! `rung gc` command - Tidy up `.git/rung` so it doesn't grow without bound.
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use serde::Serialize;
+
+use super::sync_webhook::WORK_PREFIX;
+use super::utils::{load_config, open_repo_and_state};
+use crate::output;
+
+/// JSON output for the gc command.
+#[derive(Debug, Serialize)]
+struct GcOutput {
+    #[serde(flatten)]
+    state: rung_core::state::GcReport,
+    work_branches_removed: usize,
+}
+
+/// Run the gc command, keeping `keep_backups` backups (the configured
+/// retention by default) and `log_days` days of the operations log.
+pub fn run(json: bool, keep_backups: Option<usize>, log_days: u32) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let config = load_config(&repo, &state)?;
+
+    let keep = keep_backups.unwrap_or(config.general.backup_retention);
+    let cutoff = (log_days > 0).then(|| Utc::now() - Duration::days(i64::from(log_days)));
+    let report = state.gc(keep, cutoff)?;
+
+    // Work branches left behind by an interrupted `sync --from-webhook`
+    let current = repo.current_branch().ok();
+    let mut work_branches_removed = 0;
+    for branch in repo.list_branches()? {
+        if branch.starts_with(WORK_PREFIX)
+            && current.as_deref() != Some(branch.as_str())
+            && repo.delete_branch(&branch).is_ok()
+        {
+            work_branches_removed += 1;
+        }
+    }
+
+    if json {
+        let output = GcOutput {
+            state: report,
+            work_branches_removed,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let removed: Vec<String> = [
+        (report.backups_removed, "backup(s)"),
+        (report.operations_removed, "log entry(ies)"),
+        (report.temp_files_removed, "temp file(s)"),
+        (work_branches_removed, "work branch(es)"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, what)| format!("{count} {what}"))
+    .collect();
+    if removed.is_empty() {
+        output::info("Nothing to clean up");
+        return Ok(());
+    }
+    output::success(&format!(
+        "Removed {} - reclaimed {}",
+        removed.join(", "),
+        format_bytes(report.bytes_reclaimed)
+    ));
+    Ok(())
+}
+
+/// Format a byte count in the largest unit that keeps it at least 1.
+#[allow(clippy::cast_precision_loss)]
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
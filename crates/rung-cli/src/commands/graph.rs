@@ -0,0 +1,23 @@
+//! `rung graph` command - Export the stack as a Mermaid or Graphviz graph.
+
+use anyhow::Result;
+use rung_core::graph;
+
+use super::GraphFormat;
+use super::utils::open_repo_and_state_read_only;
+
+/// Run the graph command, printing the stack in `format`. PR states are
+/// the ones `rung sync` last saw.
+pub fn run(format: GraphFormat) -> Result<()> {
+    let (_repo, state) = open_repo_and_state_read_only()?;
+    let stack = state.load_stack()?;
+    let prs = state.load_pr_cache();
+    let state_of = |pr| prs.get(pr).map(|entry| entry.state);
+
+    let rendered = match format {
+        GraphFormat::Mermaid => graph::mermaid(&stack, state_of),
+        GraphFormat::Dot => graph::dot(&stack, state_of),
+    };
+    print!("{rendered}");
+    Ok(())
+}
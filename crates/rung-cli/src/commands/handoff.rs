@@ -0,0 +1,123 @@
+//! `rung handoff` command - Hand a stack over to a teammate.
+//!
+//! Pushes every branch and makes sure each has a PR (as `rung submit` does),
+//! comments on each PR with who is taking over and how, and prints the
+//! `rung adopt` command that picks the stack up. The PRs' bases record the
+//! stack's shape, so nothing else needs sharing.
+
+use anyhow::{Context, Result, bail};
+use rung_git::GitHubRemote;
+use rung_github::{CreateComment, GitHubClient, UpdateComment};
+use serde::Serialize;
+
+use super::utils::{github_client, github_remote, load_config, open_repo_and_state};
+use crate::output;
+
+/// Marks the handoff comment, so handing off again updates it.
+const HANDOFF_COMMENT_MARKER: &str = "<!-- rung-handoff -->";
+
+/// JSON output for the handoff command.
+#[derive(Debug, Serialize)]
+struct HandoffOutput {
+    from: String,
+    to: Option<String>,
+    prs: Vec<u64>,
+    command: String,
+}
+
+/// Run the handoff command, optionally naming the GitHub user taking over.
+pub fn run(json: bool, to: Option<&str>) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    if state.load_stack()?.is_empty() {
+        bail!("No branches in stack - nothing to hand off");
+    }
+
+    super::submit::submit_stack(json).context("Failed to push the stack and its PRs")?;
+
+    let stack = state.load_stack()?;
+    let config = load_config(&repo, &state)?;
+    let remote = github_remote(&repo, &config)?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let from = rt
+        .block_on(client.current_user())
+        .context("Failed to look up your GitHub user")?;
+
+    let to = to.map(|login| login.trim_start_matches('@'));
+    let command = format!("rung adopt --author {from}");
+    let body = handoff_comment(to, &command);
+    let prs: Vec<u64> = stack
+        .topological_order()?
+        .iter()
+        .filter_map(|branch| branch.pr)
+        .collect();
+    for &pr in &prs {
+        rt.block_on(upsert_handoff_comment(&client, &remote, pr, body.clone()))
+            .with_context(|| format!("Failed to comment on PR #{pr}"))?;
+    }
+
+    if json {
+        let output = HandoffOutput {
+            from,
+            to: to.map(String::from),
+            prs,
+            command,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    output::success(&format!("Handed off {} PR(s)", prs.len()));
+    match to {
+        Some(login) => output::info(&format!("For @{login} to pick the stack up:")),
+        None => output::info("To pick the stack up:"),
+    }
+    output::essential(&format!("  {command}"));
+    Ok(())
+}
+
+/// The comment left on each PR of the stack.
+fn handoff_comment(to: Option<&str>, command: &str) -> String {
+    let who = to.map_or_else(
+        || "This stack is up for handoff.".to_string(),
+        |login| format!("Handing this stack over to @{login}."),
+    );
+    format!(
+        "{HANDOFF_COMMENT_MARKER}\n{who} To pick it up, run `{command}` in a clone of this repository."
+    )
+}
+
+/// Create the handoff comment on `pr`, or update the one already there.
+async fn upsert_handoff_comment(
+    client: &GitHubClient,
+    remote: &GitHubRemote,
+    pr: u64,
+    body: String,
+) -> Result<()> {
+    let comments = client
+        .list_pr_comments(&remote.owner, &remote.repo, pr)
+        .await?;
+    let existing = comments.iter().find(|c| {
+        c.body
+            .as_ref()
+            .is_some_and(|b| b.starts_with(HANDOFF_COMMENT_MARKER))
+    });
+    match existing {
+        Some(comment) => {
+            client
+                .update_pr_comment(
+                    &remote.owner,
+                    &remote.repo,
+                    comment.id,
+                    UpdateComment { body },
+                )
+                .await?;
+        }
+        None => {
+            client
+                .create_pr_comment(&remote.owner, &remote.repo, pr, CreateComment { body })
+                .await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! `rung history` command - Show how rung moved a branch, from its reflog.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use rung_git::{ReflogEntry, Repository};
+use serde::Serialize;
+
+use crate::output;
+
+/// JSON output for one move.
+#[derive(Debug, Serialize)]
+struct Move {
+    /// Where the branch pointed before, or `None` when rung created it.
+    old: Option<String>,
+    new: String,
+    message: String,
+    at: DateTime<Utc>,
+}
+
+/// Run the history command: list the reflog entries of `branch` (the
+/// current branch by default) that rung wrote, newest first.
+pub fn run(json: bool, branch: Option<&str>) -> Result<()> {
+    let repo = Repository::open_current().context("Not inside a git repository")?;
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => repo.current_branch()?,
+    };
+    if !repo.branch_exists(&branch) {
+        bail!("Branch '{branch}' does not exist");
+    }
+
+    let moves: Vec<Move> = repo
+        .reflog(&branch)
+        .with_context(|| format!("Failed to read the reflog of '{branch}'"))?
+        .into_iter()
+        .filter(ReflogEntry::is_rung)
+        .map(|entry| Move {
+            old: (!entry.old.is_zero()).then(|| entry.old.to_string()),
+            new: entry.new.to_string(),
+            message: entry.message,
+            at: DateTime::from_timestamp(entry.time, 0).unwrap_or_default(),
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&moves)?);
+        return Ok(());
+    }
+    if moves.is_empty() {
+        output::info(&format!("rung hasn't moved '{branch}'"));
+        return Ok(());
+    }
+    for m in &moves {
+        let at = m.at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+        let from = m.old.as_deref().map_or("created", short);
+        output::essential(&format!(
+            "{at}  {from:>7} → {}  {}",
+            short(&m.new),
+            m.message
+        ));
+    }
+    Ok(())
+}
+
+/// The first 7 hex digits of a commit id.
+fn short(id: &str) -> &str {
+    &id[..7.min(id.len())]
+}
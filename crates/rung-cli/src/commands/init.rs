@@ -1,33 +1,199 @@
 //! `rung init` command - Initialize rung in the current repository.
 
-use anyhow::{Context, Result};
-use rung_core::State;
-use rung_git::Repository;
+use anyhow::{Context, Result, bail};
+use inquire::MultiSelect;
+use rung_core::{StackBranch, State};
+use rung_git::{Oid, Repository};
 
-use crate::output;
+use super::sync_webhook::WORK_PREFIX;
+use super::utils::origin_name;
+use crate::{output, prompt};
 
-/// Run the init command.
-pub fn run() -> Result<()> {
+/// Run the init command. With `import`, existing local branches are added
+/// to the stack, picked from a list when there's a terminal; with
+/// `reattach`, the stack is attached to the repository origin points at.
+pub fn run(import: bool, reattach: bool) -> Result<()> {
     // Open repository
     let repo = Repository::open_current().context("Not inside a git repository")?;
 
     // Get state manager
-    let workdir = repo
-        .workdir()
-        .context("Cannot initialize in bare repository")?;
-    let state = State::new(workdir)?;
+    if repo.is_bare() {
+        bail!("Cannot initialize in bare repository");
+    }
+    let state = State::for_repository(&repo);
+    if reattach {
+        return reattach_origin(&repo, &state);
+    }
 
     // Check if already initialized
     if state.is_initialized() {
+        if import {
+            return import_branches(&repo, &state);
+        }
         output::warn("Rung is already initialized in this repository");
         return Ok(());
     }
 
     // Initialize
     state.init()?;
+    if let Some(origin) = origin_name(&repo, &state) {
+        state.save_origin(&origin)?;
+    }
 
     output::success("Initialized rung in this repository");
     output::info(&format!("State stored in: {}", state.rung_dir().display()));
+    if let Some(unborn) = repo.unborn_branch() {
+        output::info(&format!(
+            "'{unborn}' has no commits yet - make the first commit on it with `git commit`, then `rung create <name>` to start a stack"
+        ));
+        return Ok(());
+    }
+    if import {
+        return import_branches(&repo, &state);
+    }
+    if let Ok(trunk) = trunk(&repo) {
+        let count = importable(&repo, &state, &trunk)?.len();
+        if count > 0 {
+            output::info(&format!(
+                "{count} existing branch(es) can be added to the stack with `rung init --import`"
+            ));
+            return Ok(());
+        }
+    }
+    output::info(
+        "New to stacked PRs? `rung tutorial` walks through the basics in a demo repository",
+    );
+
+    Ok(())
+}
+
+/// Record the GitHub repository origin points at as the stack's, replacing
+/// the one it was set up for.
+fn reattach_origin(repo: &Repository, state: &State) -> Result<()> {
+    if !state.is_initialized() {
+        bail!("Rung not initialized - run `rung init` first");
+    }
+    let origin = origin_name(repo, state).context("origin doesn't point at a GitHub repository")?;
+    let previous = state.load_origin();
+    state.save_origin(&origin)?;
+    match previous {
+        Some(previous) if !previous.eq_ignore_ascii_case(&origin) => {
+            output::success(&format!("Reattached the stack from {previous} to {origin}"));
+            if state.load_stack()?.branches.iter().any(|b| b.pr.is_some()) {
+                output::info(&format!(
+                    "The PR numbers the stack records now refer to PRs in {origin}"
+                ));
+            }
+        }
+        _ => output::success(&format!("The stack is attached to {origin}")),
+    }
+    Ok(())
+}
+
+/// Add existing local branches to the stack, each onto the nearest other
+/// imported branch it's built on, or else trunk.
+fn import_branches(repo: &Repository, state: &State) -> Result<()> {
+    let trunk = trunk(repo)?;
+    let candidates = importable(repo, state, &trunk)?;
+    if candidates.is_empty() {
+        output::info("No branches to import");
+        return Ok(());
+    }
+
+    let selected = if prompt::is_interactive() {
+        MultiSelect::new("Branches to import:", candidates)
+            .with_all_selected_by_default()
+            .prompt()
+            .context("Prompt cancelled")?
+    } else {
+        candidates
+    };
+    if selected.is_empty() {
+        output::info("Nothing selected");
+        return Ok(());
+    }
 
+    let mut stack = state.load_stack()?;
+    for (name, parent) in infer_parents(repo, &trunk, &selected)? {
+        stack.add_branch(StackBranch::try_new(&name, Some(&parent))?);
+        output::info(&format!("{name} ← {parent}"));
+    }
+    state.save_stack(&stack)?;
+    output::success(&format!("Imported {} branch(es)", selected.len()));
     Ok(())
 }
+
+/// The trunk imported branches start from: `main`, or else `master`.
+fn trunk(repo: &Repository) -> Result<String> {
+    ["main", "master"]
+        .into_iter()
+        .find(|name| repo.branch_exists(name))
+        .map(String::from)
+        .context("No main or master branch to import branches onto")
+}
+
+/// Local branches that could join the stack: not trunk, not already in the
+/// stack, not a rung work branch, and not already merged into trunk.
+fn importable(repo: &Repository, state: &State, trunk: &str) -> Result<Vec<String>> {
+    let stack = state.load_stack()?;
+    let trunk_tip = repo.branch_commit(trunk)?;
+    let mut branches: Vec<String> = repo
+        .list_branches()?
+        .into_iter()
+        .filter(|name| {
+            name != trunk && !name.starts_with(WORK_PREFIX) && stack.find_branch(name).is_none()
+        })
+        .filter(|name| {
+            repo.branch_commit(name)
+                .is_ok_and(|tip| !is_ancestor(repo, tip, trunk_tip) && tip != trunk_tip)
+        })
+        .collect();
+    branches.sort();
+    Ok(branches)
+}
+
+/// Each of `branches` with its parent: the nearest other branch among them
+/// whose tip it's built on, or else `trunk`. Parents come before their
+/// children.
+fn infer_parents(
+    repo: &Repository,
+    trunk: &str,
+    branches: &[String],
+) -> Result<Vec<(String, String)>> {
+    let tips = branches
+        .iter()
+        .map(|name| Ok((name.as_str(), repo.branch_commit(name)?)))
+        .collect::<Result<Vec<(&str, Oid)>>>()?;
+
+    let mut parents = vec![];
+    for &(name, tip) in &tips {
+        let ancestors: Vec<(&str, Oid)> = tips
+            .iter()
+            .copied()
+            .filter(|&(_, other)| is_ancestor(repo, other, tip))
+            .collect();
+        // The nearest is the one none of the others are built on
+        let parent = ancestors
+            .iter()
+            .find(|&&(_, candidate)| {
+                !ancestors
+                    .iter()
+                    .any(|&(_, other)| is_ancestor(repo, candidate, other))
+            })
+            .map_or(trunk, |&(parent, _)| parent);
+        parents.push((ancestors.len(), name.to_string(), parent.to_string()));
+    }
+    parents.sort_by_key(|(depth, _, _)| *depth);
+    Ok(parents
+        .into_iter()
+        .map(|(_, name, parent)| (name, parent))
+        .collect())
+}
+
+/// Whether `ancestor` is a strict ancestor of `commit`.
+fn is_ancestor(repo: &Repository, ancestor: Oid, commit: Oid) -> bool {
+    ancestor != commit
+        && repo
+            .merge_base(ancestor, commit)
+            .is_ok_and(|base| base == ancestor)
+}
@@ -1,12 +1,22 @@
 //! `rung log` command - show commits between the base branch and HEAD.
+//!
+//! Like `git log`, output goes through the pager when stdout is a terminal.
 
-use super::utils::open_repo_and_state;
-use crate::output;
+use std::fmt::Write;
+
+use super::utils::open_repo_and_state_read_only;
+use crate::{output, pager};
 use anyhow::{Result, bail};
+use colored::Colorize;
+use rung_core::State;
 
-// Run the log command.
-pub fn run() -> Result<()> {
-    let (repo, state) = open_repo_and_state()?;
+/// Run the log command.
+#[allow(clippy::fn_params_excessive_bools)]
+pub fn run(json: bool, files: bool, operations: bool, patch: bool, no_pager: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state_read_only()?;
+    if operations {
+        return print_operations(&state, json);
+    }
     let current = repo.current_branch()?;
     let stack = state.load_stack()?;
 
@@ -26,25 +36,96 @@ pub fn run() -> Result<()> {
     // Get commits
     let head_oid = repo.branch_commit(head.name.as_str())?;
     let base_oid = repo.branch_commit(base.as_str())?;
-    let commits = repo.commits_between(base_oid, head_oid)?;
+    let commits = repo.commit_infos_between(base_oid, head_oid)?;
 
     if commits.is_empty() {
         output::warn("Current branch has no commits");
         return Ok(());
     }
 
-    // Print commits
-    for commit in commits {
-        let commit = repo.find_commit(commit)?;
+    // Quiet mode keeps just the essentials: the changed files, if asked for
+    if output::is_quiet() {
+        if files {
+            for file in repo.changed_files(&current, base)? {
+                output::essential(&format!("  {file}"));
+            }
+        }
+        return Ok(());
+    }
 
-        let short_id = &commit.id().to_string()[..7];
-        let msg = commit.message().unwrap_or("").trim();
-        let sig = commit.author();
-        let author = sig.name().unwrap_or("unknown");
+    let mut text = String::new();
+    for commit in commits {
+        let short_id = &commit.id.to_string()[..7];
+        let msg = commit.message.trim();
+        let author = if commit.author_name.is_empty() {
+            "unknown"
+        } else {
+            &commit.author_name
+        };
 
         let msg = format!("{short_id:<10} {msg}     {author}");
-        output::info(&msg);
+        let _ = writeln!(text, "{}", output::info_line(&msg));
+        if patch {
+            let _ = writeln!(text, "{}", color_patch(&repo.commit_patch(commit.id)?));
+        }
+    }
+
+    if files {
+        let changed = repo.changed_files(&current, base)?;
+        let header = format!("Files changed ({}):", changed.len());
+        let _ = writeln!(text, "{}", output::info_line(&header));
+        for file in changed {
+            let _ = writeln!(text, "  {file}");
+        }
     }
 
+    pager::show(&repo, &text, !no_pager)
+}
+
+/// Color a patch the way `git show` does.
+fn color_patch(patch: &str) -> String {
+    patch
+        .lines()
+        .map(|line| {
+            if line.starts_with("diff ")
+                || line.starts_with("index ")
+                || line.starts_with("--- ")
+                || line.starts_with("+++ ")
+            {
+                line.bold().to_string()
+            } else if line.starts_with("@@") {
+                line.cyan().to_string()
+            } else if line.starts_with('+') {
+                line.green().to_string()
+            } else if line.starts_with('-') {
+                line.red().to_string()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Print the operations log, oldest first.
+fn print_operations(state: &State, json: bool) -> Result<()> {
+    let operations = state.load_operations()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&operations)?);
+        return Ok(());
+    }
+    if operations.is_empty() {
+        output::info("No operations recorded yet");
+        return Ok(());
+    }
+
+    for operation in &operations {
+        let at = operation
+            .finished_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M");
+        output::essential(&format!("{at}  {}", operation.summary()));
+    }
     Ok(())
 }
@@ -1,13 +1,26 @@
 //! `rung merge` command - Merge PR and clean up stack.
 
+use std::collections::HashMap;
+
 use anyhow::{Context, Result, bail};
-use rung_core::stack::Stack;
-use rung_core::{BranchName, State};
+use chrono::Utc;
+use inquire::Select;
+use rung_core::operations::{Operation, OperationKind};
+use rung_core::state::SyncState;
+use rung_core::{BranchName, Stack, State, pr_body};
 use rung_git::{Oid, Repository};
-use rung_github::{Auth, GitHubClient, MergeMethod, MergePullRequest, UpdatePullRequest};
+use rung_github::{
+    Auth, GitHubClient, MergeMethod, MergePullRequest, MergeSettings, PollOutcome, Poller,
+    PullRequest, PullRequestState, UpdatePullRequest,
+};
 use serde::Serialize;
 
-use crate::output;
+use super::utils::{
+    api_url, backup_tips, check_origin, enforce_policy, github_client, github_remote, load_config,
+    policy_violations, push, record_landing,
+};
+use crate::i18n::tr;
+use crate::{exit, output, prompt};
 
 /// JSON output for merge command.
 #[derive(Debug, Serialize)]
@@ -18,75 +31,334 @@ struct MergeOutput {
     #[serde(skip_serializing_if = "Option::is_none")]
     checked_out: Option<String>,
     descendants_rebased: usize,
+    /// Descendants left unrebased after a conflict, for `rung sync`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<String>,
+    /// Descendants left for `rung sync` to restack (`--no-rebase-descendants`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    deferred: Vec<String>,
 }
 
 /// Run the merge command.
-#[allow(clippy::too_many_lines)]
-pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
-    // Parse merge method
-    let merge_method = match method.to_lowercase().as_str() {
-        "squash" => MergeMethod::Squash,
-        "merge" => MergeMethod::Merge,
-        "rebase" => MergeMethod::Rebase,
-        _ => bail!("Invalid merge method: {method}. Use squash, merge, or rebase."),
-    };
-
+///
+/// Merges `target` (default: the current branch), or with `until`, every
+/// branch from the bottom of its stack up to and including `until`, along
+/// with the branches they require, each after those it depends on. Unless
+/// `rebase_descendants`, the descendants are only marked for `rung sync` to
+/// restack.
+#[allow(
+    clippy::fn_params_excessive_bools,
+    clippy::too_many_arguments,
+    clippy::too_many_lines
+)]
+pub fn run(
+    json: bool,
+    target: Option<&str>,
+    until: Option<&str>,
+    method: Option<&str>,
+    no_delete: bool,
+    when_ready: bool,
+    ignore_checks: bool,
+    dry_run: bool,
+    override_policy: bool,
+    rebase_descendants: bool,
+) -> Result<()> {
     // Open repository
     let repo = Repository::open_current().context("Not inside a git repository")?;
-    let workdir = repo.workdir().context("Cannot run in bare repository")?;
-    let state = State::new(workdir)?;
+    if repo.is_bare() {
+        bail!("Cannot run in bare repository");
+    }
+    let state = State::for_repository(&repo);
 
     // Ensure initialized
     if !state.is_initialized() {
         bail!("Rung not initialized - run `rung init` first");
     }
+    check_origin(&repo, &state)?;
 
-    // Get current branch
-    let current_branch = repo.current_branch()?;
+    // --method wins over the configured default
+    let config = load_config(&repo, &state)?;
+    let requested = method
+        .or(config.merge.method.as_deref())
+        .map(|name| {
+            MergeMethod::from_name(name).with_context(|| {
+                format!("Invalid merge method: {name}. Use squash, merge, or rebase.")
+            })
+        })
+        .transpose()?;
 
-    // Load stack and find the branch
+    // Merge the named branch, or the current one - or the stack up to `until`
+    let original_branch = repo.current_branch().ok();
     let stack = state.load_stack()?;
-    let branch = stack
-        .find_branch(&current_branch)
-        .ok_or_else(|| anyhow::anyhow!("Branch '{current_branch}' not in stack"))?;
+    let targets: Vec<String> = if until.is_some() {
+        let last = resolve_target(&stack, until, None)?;
+        stack
+            .landing_order(&last)
+            .iter()
+            .map(|b| b.name.to_string())
+            .collect()
+    } else {
+        vec![resolve_target(&stack, target, original_branch.as_deref())?]
+    };
+    if !json {
+        warn_unlanded_requirements(&stack, &targets);
+    }
+    let mut pr_numbers = vec![];
+    for name in &targets {
+        if stack.find_branch(name).is_some_and(|b| b.frozen) {
+            bail!(
+                "'{name}' is someone else's PR (added with `rung rebase --onto-pr`) - rung won't merge it"
+            );
+        }
+        let pr = stack.find_branch(name).and_then(|b| b.pr);
+        pr_numbers.push(pr.with_context(|| {
+            format!("No PR associated with branch '{name}'. Run `rung submit` first.")
+        })?);
+    }
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let remote = github_remote(&repo, &config);
+
+    // A dry run also works offline, assuming the requested method is allowed
+    let settings = match &remote {
+        Ok(remote) => {
+            let fetched = rt.block_on(async {
+                github_client(remote, &config)?
+                    .get_merge_settings(&remote.owner, &remote.repo)
+                    .await
+            });
+            if dry_run {
+                fetched.ok()
+            } else {
+                Some(fetched.context("Failed to read the repository's merge settings")?)
+            }
+        }
+        Err(_) => None,
+    };
+    let merge_method = choose_merge_method(requested, settings.as_ref())?;
+    // GitHub deletes the head branch itself, and deleting it again would fail
+    let no_delete = no_delete || settings.is_some_and(|s| s.delete_branch_on_merge);
+
+    // Required reviewers are checked against who each PR asked, when known
+    let mut reviewers = HashMap::new();
+    if let Some(remote) = remote
+        .as_ref()
+        .ok()
+        .filter(|_| !config.policy.reviewers.is_empty())
+    {
+        let client = github_client(remote, &config)?;
+        for (name, &pr) in targets.iter().zip(&pr_numbers) {
+            let fetched = rt.block_on(client.get_reviewers(&remote.owner, &remote.repo, pr));
+            if dry_run {
+                if let Ok(asked) = fetched {
+                    reviewers.insert(name.clone(), asked);
+                }
+            } else {
+                let asked =
+                    fetched.with_context(|| format!("Failed to list reviewers of PR #{pr}"))?;
+                reviewers.insert(name.clone(), asked);
+            }
+        }
+    }
+    let names: Vec<&str> = targets.iter().map(String::as_str).collect();
+    let violations = policy_violations(&repo, &stack, &config, &names, &reviewers)?;
+    enforce_policy(&violations, override_policy, json)?;
+
+    // Plan each merge against the stack as the earlier ones will leave it
+    let mut planned = stack;
+    let plans: Vec<MergePlan> = targets
+        .iter()
+        .zip(&pr_numbers)
+        .map(|(name, &pr)| {
+            let plan = MergePlan::new(
+                &planned,
+                name,
+                pr,
+                merge_method,
+                no_delete,
+                rebase_descendants,
+            );
+            planned.splice_out(name);
+            plan
+        })
+        .collect();
+    let steps: Vec<String> = plans.iter().flat_map(MergePlan::steps).collect();
+    if dry_run {
+        if !json {
+            output::info("Dry run - would perform the following:");
+            for step in steps {
+                println!("  → {step}");
+            }
+        } else if until.is_some() {
+            println!("{}", serde_json::to_string_pretty(&plans)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&plans[0])?);
+        }
+        return Ok(());
+    }
+    repo.require_no_operation()?;
+
+    let remote = remote?;
+    let api_base = api_url(&remote, &config);
+
+    let prs: Vec<String> = pr_numbers.iter().map(|n| format!("#{n}")).collect();
+    let question = if prs.len() == 1 {
+        format!("Merge PR {}?", prs[0])
+    } else {
+        format!("Merge PRs {}?", prs.join(", "))
+    };
+    if !prompt::confirm_destructive(&state, &question, &steps)? {
+        output::info("Merge cancelled");
+        return Ok(());
+    }
+
+    let ctx = MergeContext {
+        repo: &repo,
+        state: &state,
+        rt: &rt,
+        api_url: &api_base,
+        owner: &remote.owner,
+        repo_name: &remote.repo,
+        merge_method,
+        no_delete,
+        ignore_checks,
+        rebase_descendants,
+        json,
+    };
+    let mut merged = vec![];
+    for (i, name) in targets.iter().enumerate() {
+        // Later PRs were just rebased and pushed, so wait for their checks
+        merged.push(merge_branch(&ctx, name, when_ready || i > 0)?);
+    }
+
+    // Go back to where we started, unless it was merged
+    let checked_out = match original_branch {
+        Some(original) if !targets.contains(&original) && repo.branch_exists(&original) => {
+            repo.checkout(&original)?;
+            original
+        }
+        _ => repo.current_branch()?,
+    };
+
+    if json {
+        if let Some(last) = merged.last_mut() {
+            last.checked_out = Some(checked_out);
+        }
+        if until.is_some() {
+            println!("{}", serde_json::to_string_pretty(&merged)?);
+            return Ok(());
+        }
+        return output_json(&merged[0]);
+    }
+
+    output::info(&format!("Checked out '{checked_out}'"));
+    output::success("Merge complete!");
+    if !state.load_stack()?.is_empty() {
+        output::hint("Run `rung sync` to bring the rest of the stack up to date");
+    }
+
+    Ok(())
+}
 
-    // Get PR number
+/// Settings shared by every merge in one `rung merge` run.
+#[derive(Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)] // One per merge flag
+struct MergeContext<'a> {
+    repo: &'a Repository,
+    state: &'a State,
+    rt: &'a tokio::runtime::Runtime,
+    api_url: &'a str,
+    owner: &'a str,
+    repo_name: &'a str,
+    merge_method: MergeMethod,
+    no_delete: bool,
+    ignore_checks: bool,
+    rebase_descendants: bool,
+    json: bool,
+}
+
+/// Merge one branch's PR, rebase its descendants onto where it landed (or
+/// mark them for `rung sync` to), and delete the branch. Leaves the parent
+/// checked out.
+#[allow(clippy::too_many_lines)]
+fn merge_branch(
+    ctx: &MergeContext<'_>,
+    target_branch: &str,
+    when_ready: bool,
+) -> Result<MergeOutput> {
+    let MergeContext {
+        repo,
+        state,
+        rt,
+        api_url,
+        owner,
+        repo_name,
+        merge_method,
+        no_delete,
+        ignore_checks,
+        rebase_descendants,
+        json,
+    } = *ctx;
+    let target_branch = target_branch.to_string();
+
+    // The stack changes with every merge, so read it fresh
+    let stack = state.load_stack()?;
+    let branch = stack
+        .find_branch(&target_branch)
+        .ok_or_else(|| anyhow::anyhow!("Branch '{target_branch}' not in stack"))?;
     let pr_number = branch.pr.ok_or_else(|| {
-        anyhow::anyhow!("No PR associated with branch '{current_branch}'. Run `rung submit` first.")
+        anyhow::anyhow!("No PR associated with branch '{target_branch}'. Run `rung submit` first.")
     })?;
 
     // Get parent branch from stack (may be None for root branches)
     let stack_parent_branch = branch.parent.as_ref().map(ToString::to_string);
 
-    // Get remote info
-    let origin_url = repo.origin_url()?;
-    let (owner, repo_name) = Repository::parse_github_remote(&origin_url)?;
+    // Name of the merged branch on GitHub (may differ from the local name)
+    let remote_branch = branch.remote_name().to_string();
+
+    // Collect all descendants that need to be rebased
+    let descendants: Vec<String> = stack
+        .descendants_of(&target_branch)
+        .iter()
+        .map(|b| b.name.to_string())
+        .collect();
 
     if !json {
-        output::info(&format!("Merging PR #{pr_number} for {current_branch}..."));
+        output::info(&format!("Merging PR #{pr_number} for {target_branch}..."));
     }
 
-    // Collect all descendants that need to be rebased
-    let descendants = collect_descendants(&stack, &current_branch);
-
     // Capture old commits before any rebasing (needed for --onto)
     let mut old_commits: std::collections::HashMap<String, Oid> = std::collections::HashMap::new();
-    old_commits.insert(current_branch.clone(), repo.branch_commit(&current_branch)?);
+    old_commits.insert(target_branch.clone(), repo.branch_commit(&target_branch)?);
     for branch_name in &descendants {
         old_commits.insert(branch_name.clone(), repo.branch_commit(branch_name)?);
     }
 
     // Create GitHub client and merge
-    let rt = tokio::runtime::Runtime::new()?;
-    let parent_branch = rt.block_on(async {
-        let auth = Auth::auto();
-        let client = GitHubClient::new(&auth)?;
+    let started_at = Utc::now();
+    let (parent_branch, descendants_rebased, skipped) = rt.block_on(async {
+        let client = GitHubClient::with_base_url(&Auth::auto(), api_url)?;
 
         // Step 1: Validate PR is mergeable before making any changes
-        let pr = client
-            .get_pr(&owner, &repo_name, pr_number)
-            .await
-            .context("Failed to fetch PR status")?;
+        let pr = if when_ready {
+            if !json {
+                output::info(&format!(
+                    "Waiting for PR #{pr_number} to become mergeable..."
+                ));
+            }
+            wait_until_mergeable(&client, owner, repo_name, pr_number).await?
+        } else {
+            client
+                .get_pr(owner, repo_name, pr_number)
+                .await
+                .context("Failed to fetch PR status")?
+        };
+
+        // Land exactly the revision that's on disk
+        ensure_head_matches(repo, &pr, &target_branch)?;
+        if !ignore_checks {
+            ensure_required_checks(&client, owner, repo_name, &pr, json).await?;
+        }
 
         // Check mergeable state - GitHub returns None while computing
         if pr.mergeable == Some(false) {
@@ -101,6 +373,8 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
         let parent_branch = stack_parent_branch
             .clone()
             .unwrap_or_else(|| pr.base_branch.clone());
+        let parent_name =
+            BranchName::new(parent_branch.as_str()).context("Invalid parent branch name")?;
 
         // Collect child PRs and their original bases for potential rollback
         let mut shifted_prs: Vec<(u64, String)> = Vec::new();
@@ -118,7 +392,7 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
                 .map_or(parent_branch.as_str(), |p| p.as_str());
 
             // Only shift direct children of the merging branch
-            if stack_parent == current_branch {
+            if stack_parent == target_branch {
                 if let Some(child_pr_num) = branch_info.pr {
                     if !json {
                         output::info(&format!(
@@ -129,100 +403,157 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
                     let update = UpdatePullRequest {
                         title: None,
                         body: None,
-                        base: Some(parent_branch.clone()),
+                        base: Some(stack.remote_name_of(&parent_branch).to_string()),
                     };
                     client
-                        .update_pr(&owner, &repo_name, child_pr_num, update)
+                        .update_pr(owner, repo_name, child_pr_num, update)
                         .await
                         .with_context(|| format!("Failed to update PR #{child_pr_num} base"))?;
 
                     // Store original base for rollback only after successful update
-                    shifted_prs.push((child_pr_num, current_branch.clone()));
+                    shifted_prs.push((child_pr_num, remote_branch.clone()));
                 }
             }
         }
 
         // Step 3: Merge the PR
+        let (commit_title, commit_message) = if merge_method == MergeMethod::Squash {
+            let (title, message) = squash_commit(repo, &pr, &target_branch, &parent_branch);
+            (Some(title), Some(message))
+        } else {
+            (None, None) // Use GitHub's default
+        };
         let merge_request = MergePullRequest {
-            commit_title: None, // Use GitHub's default
-            commit_message: None,
+            commit_title,
+            commit_message,
             merge_method,
         };
 
         let merge_result = client
-            .merge_pr(&owner, &repo_name, pr_number, merge_request)
+            .merge_pr(owner, repo_name, pr_number, merge_request)
             .await;
 
         // Step 4: If merge fails, rollback the PR base changes
-        if let Err(merge_err) = merge_result {
-            if !shifted_prs.is_empty() {
-                if !json {
-                    output::warn("Merge failed, rolling back PR base changes...");
-                }
+        let merged = match merge_result {
+            Ok(merged) => merged,
+            Err(merge_err) => {
+                if !shifted_prs.is_empty() {
+                    if !json {
+                        output::warn("Merge failed, rolling back PR base changes...");
+                    }
 
-                for (child_pr_num, original_base) in &shifted_prs {
-                    let rollback = UpdatePullRequest {
-                        title: None,
-                        body: None,
-                        base: Some(original_base.clone()),
-                    };
-                    if let Err(e) = client
-                        .update_pr(&owner, &repo_name, *child_pr_num, rollback)
-                        .await
-                    {
-                        output::error(&format!("Failed to rollback PR #{child_pr_num} base: {e}"));
-                    } else if !json {
-                        output::info(&format!(
-                            "  Restored PR #{child_pr_num} base to '{original_base}'"
-                        ));
+                    for (child_pr_num, original_base) in &shifted_prs {
+                        let rollback = UpdatePullRequest {
+                            title: None,
+                            body: None,
+                            base: Some(original_base.clone()),
+                        };
+                        if let Err(e) = client
+                            .update_pr(owner, repo_name, *child_pr_num, rollback)
+                            .await
+                        {
+                            output::error(&format!(
+                                "Failed to rollback PR #{child_pr_num} base: {e}"
+                            ));
+                        } else if !json {
+                            output::info(&format!(
+                                "  Restored PR #{child_pr_num} base to '{original_base}'"
+                            ));
+                        }
                     }
                 }
+                return Err(anyhow::anyhow!(merge_err).context("Failed to merge PR"));
             }
-            return Err(anyhow::anyhow!(merge_err).context("Failed to merge PR"));
-        }
+        };
 
         if !json {
             output::success(&format!("Merged PR #{pr_number}"));
         }
+        record_landing(repo, state, &stack, branch, &merged.sha, &parent_branch);
 
         // Update stack immediately after merge succeeds
         // This ensures stack.json reflects reality even if rebases fail later
         {
             let mut stack = state.load_stack()?;
 
-            // Count children before re-parenting
-            let children_count = stack
-                .branches
+            // Note children before re-parenting
+            let children: Vec<String> = stack
+                .children_of(&target_branch)
                 .iter()
-                .filter(|b| b.parent.as_ref().is_some_and(|p| p == &current_branch))
-                .count();
+                .map(|b| b.name.to_string())
+                .collect();
 
             // Re-parent any children to point to the merged branch's parent
-            let new_parent =
-                BranchName::new(&parent_branch).context("Invalid parent branch name")?;
             for branch in &mut stack.branches {
-                if branch.parent.as_ref().is_some_and(|p| p == &current_branch) {
-                    branch.parent = Some(new_parent.clone());
+                // Left unrebased, each keeps the tip it's built on for sync
+                if !rebase_descendants && descendants.iter().any(|d| branch.name == *d) {
+                    branch.based_on = branch
+                        .parent
+                        .as_ref()
+                        .and_then(|p| old_commits.get(p.as_str()))
+                        .map(ToString::to_string);
+                    branch.needs_restack = true;
+                }
+                if branch.parent.as_ref().is_some_and(|p| p == &target_branch) {
+                    branch.parent = Some(parent_name.clone());
                 }
             }
 
             // Remove the merged branch from stack
-            stack.branches.retain(|b| b.name != current_branch);
+            stack.branches.retain(|b| b.name != target_branch);
             state.save_stack(&stack)?;
 
-            if !json && children_count > 0 {
+            if !json && !children.is_empty() {
                 output::info(&format!(
-                    "Re-parented {children_count} child branch(es) to '{parent_branch}'"
+                    "Re-parented {} child branch(es) to '{parent_branch}'",
+                    children.len()
+                ));
+            }
+
+            let operation = Operation::finished(
+                started_at,
+                OperationKind::Merge {
+                    branch: target_branch.clone(),
+                    pr_number,
+                    merge_sha: merged.sha,
+                    method: merge_method.to_string(),
+                    into: parent_branch.clone(),
+                    reparented: children,
+                },
+            );
+            if let Err(e) = state.record_operation(&operation) {
+                output::warn(&format!(
+                    "Could not record merge in the operations log: {e}"
                 ));
             }
         }
 
         // Fetch to get the merge commit on the parent branch
-        repo.fetch(&parent_branch)
+        repo.fetch(&parent_name)
             .with_context(|| format!("Failed to fetch {parent_branch}"))?;
 
+        // Back up the descendants before rebasing them, so `rung undo` can
+        // put them back (the merge itself stands)
+        let to_rebase: &[String] = if rebase_descendants {
+            &descendants
+        } else {
+            &[]
+        };
+        let backup_id = if to_rebase.is_empty() {
+            None
+        } else {
+            let tips: HashMap<String, Oid> = to_rebase
+                .iter()
+                .filter_map(|name| old_commits.get(name).map(|&oid| (name.clone(), oid)))
+                .collect();
+            Some(backup_tips(state, "merge", &tips)?)
+        };
+
         // Process each descendant: rebase and push (PR bases already updated)
-        for branch_name in &descendants {
+        let config = state.load_config()?;
+        let mut rebased: Vec<String> = vec![];
+        let mut skipped: Vec<String> = vec![];
+        for (i, branch_name) in to_rebase.iter().enumerate() {
             let branch_info = stack
                 .find_branch(branch_name)
                 .ok_or_else(|| anyhow::anyhow!("Branch '{branch_name}' not found in stack"))?;
@@ -232,10 +563,19 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
                 .as_ref()
                 .map_or(parent_branch.as_str(), |p| p.as_str());
 
+            // A skipped branch's descendants stay on it
+            if skipped.iter().any(|s| s == stack_parent) {
+                skipped.push(branch_name.clone());
+                if !json {
+                    output::info(&format!("  Skipping {branch_name}: its parent was skipped"));
+                }
+                continue;
+            }
+
             // Determine the new base for this branch
             // Direct children of merged branch → parent_branch
             // Grandchildren → their parent branch (which we just rebased)
-            let new_base = if stack_parent == current_branch {
+            let new_base = if stack_parent == target_branch {
                 parent_branch.clone()
             } else {
                 stack_parent.to_string()
@@ -259,33 +599,58 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
                 .copied()
                 .ok_or_else(|| anyhow::anyhow!("Could not find old commit for {stack_parent}"))?;
 
-            if let Err(e) = repo.rebase_onto_from(new_base_commit, old_base_commit) {
-                if !json {
-                    output::error(&format!(
-                        "Merged parent, but branch '{branch_name}' has conflicts: {e}"
-                    ));
-                    output::warn("Manual intervention required. After resolving conflicts:");
-                    output::info("  1. git rebase --continue");
-                    output::info("  2. git push --force-with-lease");
-                    output::info("  3. rung sync");
-                    output::info("");
-                    output::info(
-                        "Note: 'rung sync' will rebase any remaining descendant branches.",
-                    );
+            let options = rung_core::sync::rebase_options(&config, Some(branch_info));
+            match repo.rebase_onto_from(new_base_commit, old_base_commit, options) {
+                Ok(()) => {}
+                Err(rung_git::Error::RebaseConflict(files)) => {
+                    let pending = Pending {
+                        rebased: &rebased,
+                        remaining: descendants[i + 1..]
+                            .iter()
+                            .filter(|name| {
+                                !stack
+                                    .ancestry(name)
+                                    .iter()
+                                    .any(|b| skipped.iter().any(|s| b.name == *s))
+                            })
+                            .cloned()
+                            .collect(),
+                        old_commits: &old_commits,
+                        backup_id: backup_id.as_deref().unwrap_or_default(),
+                    };
+                    if triage_conflict(ctx, branch_name, files, &pending)? == Triage::Skipped {
+                        skipped.push(branch_name.clone());
+                        continue;
+                    }
+                }
+                Err(e) => {
+                    if !json {
+                        output::error(&format!(
+                            "Merged parent, but rebasing '{branch_name}' failed: {e}"
+                        ));
+                        output::info("Run `rung sync` to rebase the remaining descendants.");
+                    }
+                    bail!("Failed to rebase '{branch_name}' onto '{new_base}'");
                 }
-                bail!("Rebase conflict in '{branch_name}' - manual intervention required");
             }
 
             // Force push rebased branch
-            repo.push(branch_name, true)
-                .with_context(|| format!("Failed to push rebased {branch_name}"))?;
+            let verify = config.general.verify_pushes;
+            push(
+                repo,
+                &branch_info.name,
+                branch_info.remote_name(),
+                true,
+                verify,
+            )?;
+            rebased.push(branch_name.clone());
             if !json {
                 output::info(&format!("  Rebased and pushed {branch_name}"));
             }
 
             // Update PR base for grandchildren AFTER successful rebase
             // (direct children were already shifted before merge)
-            if stack_parent != current_branch {
+            if stack_parent != target_branch {
                 if let Some(child_pr_num) = branch_info.pr {
                     if !json {
                         output::info(&format!(
@@ -295,22 +660,37 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
                     let update = UpdatePullRequest {
                         title: None,
                         body: None,
-                        base: Some(new_base.clone()),
+                        base: Some(stack.remote_name_of(&new_base).to_string()),
                     };
                     client
-                        .update_pr(&owner, &repo_name, child_pr_num, update)
+                        .update_pr(owner, repo_name, child_pr_num, update)
                         .await
                         .with_context(|| format!("Failed to update PR #{child_pr_num} base"))?;
                 }
             }
         }
 
+        // Skipped branches keep their old base, so sync replays only their own commits
+        rung_core::sync::record_bases(repo, state, &rebased)?;
+        if !json && !rebase_descendants && !descendants.is_empty() {
+            output::info(&format!(
+                "Left {} for `rung sync` to restack",
+                descendants.join(", ")
+            ));
+        }
+        if !json && !skipped.is_empty() {
+            output::warn(&format!(
+                "Skipped {} - run `rung sync` to restack them",
+                skipped.join(", ")
+            ));
+        }
+
         // Delete remote branch AFTER descendants are safe
         if !no_delete {
-            match client.delete_ref(&owner, &repo_name, &current_branch).await {
+            match client.delete_ref(owner, repo_name, &remote_branch).await {
                 Ok(()) => {
                     if !json {
-                        output::info(&format!("Deleted remote branch '{current_branch}'"));
+                        output::info(&format!("Deleted remote branch '{remote_branch}'"));
                     }
                 }
                 Err(e) => {
@@ -321,19 +701,17 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
             }
         }
 
-        Ok::<_, anyhow::Error>(parent_branch)
+        Ok::<_, anyhow::Error>((parent_branch, rebased.len(), skipped))
     })?;
 
-    // Delete local branch and checkout parent
+    // Check out the parent so the merged branch can be deleted
     repo.checkout(&parent_branch)?;
-
-    // Try to delete local branch (may fail if we're on it, but we just checked out parent)
-    if let Err(e) = repo.delete_branch(&current_branch) {
+    if let Err(e) = repo.delete_branch(&target_branch) {
         if !json {
             output::warn(&format!("Could not delete local branch: {e}"));
         }
     } else if !json {
-        output::info(&format!("Deleted local branch '{current_branch}'"));
+        output::info(&format!("Deleted local branch '{target_branch}'"));
     }
 
     // Pull latest from parent to get the merge commit
@@ -343,20 +721,405 @@ pub fn run(json: bool, method: &str, no_delete: bool) -> Result<()> {
         }
     }
 
-    if json {
-        return output_json(&MergeOutput {
-            merged_branch: current_branch,
+    Ok(MergeOutput {
+        merged_branch: target_branch,
+        pr_number,
+        merge_method: merge_method.to_string(),
+        checked_out: None,
+        descendants_rebased,
+        skipped,
+        deferred: if rebase_descendants {
+            vec![]
+        } else {
+            descendants
+        },
+    })
+}
+
+/// Where the descendant rebases stand when one stops on a conflict.
+struct Pending<'a> {
+    /// Descendants already rebased and pushed.
+    rebased: &'a [String],
+    /// Descendants still to rebase after the conflicted one.
+    remaining: Vec<String>,
+    /// Every descendant's tip before the merge.
+    old_commits: &'a HashMap<String, Oid>,
+    /// Backup of the descendants taken before rebasing them.
+    backup_id: &'a str,
+}
+
+/// How a conflicted descendant rebase was dealt with, when merging goes on.
+#[derive(Debug, PartialEq, Eq)]
+enum Triage {
+    /// The conflicts were resolved and the rebase finished.
+    Resolved,
+    /// The rebase was given up, leaving the branch (and its descendants) as
+    /// they were for `rung sync`.
+    Skipped,
+}
+
+/// Deal with a conflict rebasing descendant `branch` after its ancestor merged.
+///
+/// In a terminal, offers to resolve it with `git mergetool`, skip the branch
+/// and carry on with the others, stop for `rung sync --continue`, or abort
+/// and restore every descendant. Otherwise (or with `--json`) it stops.
+fn triage_conflict(
+    ctx: &MergeContext<'_>,
+    branch: &str,
+    mut files: Vec<String>,
+    pending: &Pending<'_>,
+) -> Result<Triage> {
+    const MERGETOOL: &str = "Resolve with git mergetool";
+    const SKIP: &str = "Skip this branch and its descendants, continue with the others";
+    const STOP: &str = "Stop here and resolve by hand (then `rung sync --continue`)";
+    const ABORT: &str = "Abort and restore every descendant";
+
+    let MergeContext { repo, json, .. } = *ctx;
+    if !json {
+        output::error(&format!(
+            "Merged parent, but branch '{branch}' has conflicts in: {}",
+            files.join(", ")
+        ));
+    }
+    if json || !prompt::is_interactive() {
+        return stop_on_conflict(ctx, branch, pending);
+    }
+
+    loop {
+        let choice = Select::new(
+            "How do you want to continue?",
+            vec![MERGETOOL, SKIP, STOP, ABORT],
+        )
+        .prompt()
+        .context("Prompt cancelled")?;
+        match choice {
+            MERGETOOL => {
+                let workdir = repo.workdir().context("Cannot run in bare repository")?;
+                std::process::Command::new("git")
+                    .arg("mergetool")
+                    .args(&files)
+                    .current_dir(workdir)
+                    .status()
+                    .context("Failed to run git mergetool")?;
+                files = repo.conflicting_files()?;
+                if !files.is_empty() {
+                    output::warn(&format!("Still conflicted: {}", files.join(", ")));
+                    continue;
+                }
+                match repo.rebase_continue() {
+                    Ok(()) => return Ok(Triage::Resolved),
+                    Err(rung_git::Error::RebaseConflict(next)) => {
+                        output::warn(&format!(
+                            "The next commit conflicts too, in: {}",
+                            next.join(", ")
+                        ));
+                        files = next;
+                    }
+                    Err(e) => return Err(e).context("Failed to continue the rebase"),
+                }
+            }
+            SKIP => {
+                repo.rebase_abort()?;
+                output::info(&format!("  Skipped {branch}"));
+                return Ok(Triage::Skipped);
+            }
+            STOP => return stop_on_conflict(ctx, branch, pending),
+            _ => return abort_descendants(ctx, branch, pending),
+        }
+    }
+}
+
+/// Leave the conflicted rebase in place, recorded as a paused sync so `rung
+/// sync --continue` finishes it and rebases the remaining descendants, or
+/// `rung sync --abort` restores them all.
+fn stop_on_conflict(ctx: &MergeContext<'_>, branch: &str, pending: &Pending<'_>) -> Result<Triage> {
+    let MergeContext {
+        repo, state, json, ..
+    } = *ctx;
+    let mut branches = vec![branch.to_string()];
+    branches.extend(pending.remaining.iter().cloned());
+    let mut sync_state = SyncState::new(pending.backup_id.to_string(), branches);
+    sync_state.completed = pending.rebased.to_vec();
+    sync_state.conflicts = 1;
+    sync_state.worktree = repo.workdir().map(std::path::Path::to_path_buf);
+    state.save_sync_state(&sync_state)?;
+
+    if !json {
+        output::warn("After resolving the conflicts, run `rung sync --continue`");
+        output::info("  It rebases and pushes the remaining descendants.");
+        output::info("  `rung sync --abort` restores every descendant instead.");
+        if let Ok(files) = repo.conflicting_files() {
+            output::hint(&tr!("hint-stage-resolved", files = files.join(" ")));
+        }
+    }
+    exit::set(exit::Code::Paused);
+    bail!("Rebase conflict in '{branch}' - resolve it and run `rung sync --continue`")
+}
+
+/// Give up the conflicted rebase and put back every descendant rebased so
+/// far. The merge itself stands; `rung sync` restacks them later.
+fn abort_descendants(
+    ctx: &MergeContext<'_>,
+    branch: &str,
+    pending: &Pending<'_>,
+) -> Result<Triage> {
+    let MergeContext { repo, .. } = *ctx;
+    repo.rebase_abort()?;
+    for name in pending.rebased {
+        if let Some(&oid) = pending.old_commits.get(name) {
+            repo.reset_branch(name, oid)?;
+        }
+    }
+    output::info("Restored the descendants to where they were before the merge");
+    bail!("Stopped at the conflict in '{branch}' - run `rung sync` to restack the descendants")
+}
+
+/// Refuse to merge unless the PR's head on GitHub is the local branch tip,
+/// with a hint depending on which side has the extra commits.
+fn ensure_head_matches(repo: &Repository, pr: &PullRequest, branch: &str) -> Result<()> {
+    let local = repo.branch_commit(branch)?;
+    if pr.head_sha == local.to_string() {
+        return Ok(());
+    }
+
+    let remote = Oid::from_str(&pr.head_sha)
+        .ok()
+        .filter(|&oid| repo.commit_exists(oid));
+    let base = remote.and_then(|remote| repo.merge_base(remote, local).ok());
+    let hint = match (remote, base) {
+        (Some(remote), Some(base)) if base == remote => {
+            "the local branch has commits that aren't pushed - run `rung submit` first"
+        }
+        (Some(_), Some(base)) if base == local => {
+            "GitHub has commits the local branch doesn't - pull them first"
+        }
+        (None, _) => "GitHub has commits the local branch doesn't - fetch and pull them first",
+        _ => "they have diverged - pull the PR's commits, or `rung submit --force` to replace them",
+    };
+    bail!(
+        "PR #{} head ({}) doesn't match local '{branch}' ({}): {hint}",
+        pr.number,
+        pr.head_sha.get(..7).unwrap_or(&pr.head_sha),
+        &local.to_string()[..7]
+    )
+}
+
+/// Warn about branches the targets require that stay in the stack, unmerged.
+fn warn_unlanded_requirements(stack: &Stack, targets: &[String]) {
+    for name in targets {
+        let Some(branch) = stack.find_branch(name) else {
+            continue;
+        };
+        for required in &branch.requires {
+            if stack.find_branch(required).is_some() && !targets.contains(&required.to_string()) {
+                output::warn(&format!(
+                    "'{name}' requires '{required}', which hasn't landed - use --until {name} to land it first"
+                ));
+            }
+        }
+    }
+}
+
+/// The branch to merge: `target` names a stack branch or a PR number (`42` or
+/// `#42`); without it, the current branch.
+fn resolve_target(stack: &Stack, target: Option<&str>, current: Option<&str>) -> Result<String> {
+    let Some(target) = target else {
+        return current
+            .map(ToString::to_string)
+            .context("Not on a branch - name the branch or PR to merge");
+    };
+    if stack.find_branch(target).is_some() {
+        return Ok(target.to_string());
+    }
+    if let Ok(number) = target.trim_start_matches('#').parse::<u64>() {
+        return stack
+            .branches
+            .iter()
+            .find(|b| b.pr == Some(number))
+            .map(|b| b.name.to_string())
+            .with_context(|| format!("No branch in the stack has PR #{number}"));
+    }
+    bail!("Branch '{target}' not in stack")
+}
+
+/// Everything `rung merge` will change, worked out from the stack alone.
+#[derive(Debug, Serialize)]
+struct MergePlan {
+    branch: String,
+    pr_number: u64,
+    merge_method: String,
+    /// Stack parent the PR lands in (`None`: the PR's base on GitHub).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    into: Option<String>,
+    retarget: Vec<PlannedRetarget>,
+    rebase: Vec<PlannedRebase>,
+    /// Descendants marked for `rung sync` to restack instead of rebased.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    deferred: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delete_remote: Option<String>,
+    delete_local: String,
+}
+
+/// A descendant PR whose base is moved.
+#[derive(Debug, Serialize)]
+struct PlannedRetarget {
+    branch: String,
+    pr_number: u64,
+    base: String,
+}
+
+/// A descendant rebased and force-pushed after the merge.
+#[derive(Debug, Serialize)]
+struct PlannedRebase {
+    branch: String,
+    onto: String,
+}
+
+impl MergePlan {
+    fn new(
+        stack: &Stack,
+        branch_name: &str,
+        pr_number: u64,
+        method: MergeMethod,
+        no_delete: bool,
+        rebase_descendants: bool,
+    ) -> Self {
+        let branch = stack.find_branch(branch_name);
+        let into = branch
+            .and_then(|b| b.parent.as_ref())
+            .map(ToString::to_string);
+        let new_parent = into.as_deref().unwrap_or("(PR base)");
+
+        let mut retarget = vec![];
+        let mut rebase = vec![];
+        let mut deferred = vec![];
+        for descendant in stack.descendants_of(branch_name) {
+            // Direct children move onto the merged branch's parent
+            let onto = match descendant.parent.as_deref() {
+                Some(parent) if parent != branch_name => parent,
+                _ => new_parent,
+            };
+            if let Some(pr) = descendant.pr {
+                retarget.push(PlannedRetarget {
+                    branch: descendant.name.to_string(),
+                    pr_number: pr,
+                    base: stack.remote_name_of(onto).to_string(),
+                });
+            }
+            if rebase_descendants {
+                rebase.push(PlannedRebase {
+                    branch: descendant.name.to_string(),
+                    onto: onto.to_string(),
+                });
+            } else {
+                deferred.push(descendant.name.to_string());
+            }
+        }
+
+        Self {
+            branch: branch_name.to_string(),
             pr_number,
             merge_method: method.to_string(),
-            checked_out: Some(parent_branch),
-            descendants_rebased: descendants.len(),
-        });
+            into,
+            retarget,
+            rebase,
+            deferred,
+            delete_remote: (!no_delete).then(|| stack.remote_name_of(branch_name).to_string()),
+            delete_local: branch_name.to_string(),
+        }
     }
 
-    output::info(&format!("Checked out '{parent_branch}'"));
-    output::success("Merge complete!");
+    /// One line per change, in the order they happen.
+    fn steps(&self) -> Vec<String> {
+        let into = self
+            .into
+            .as_deref()
+            .map_or_else(|| "its base branch".to_string(), |p| format!("'{p}'"));
+        let mut steps = vec![format!(
+            "Merge PR #{} ({}) into {into} ({})",
+            self.pr_number, self.branch, self.merge_method
+        )];
+        steps.extend(self.retarget.iter().map(|r| {
+            format!(
+                "Retarget PR #{} ({}) to '{}'",
+                r.pr_number, r.branch, r.base
+            )
+        }));
+        steps.extend(
+            self.rebase
+                .iter()
+                .map(|r| format!("Rebase '{}' onto '{}' and force-push", r.branch, r.onto)),
+        );
+        steps.extend(
+            self.deferred
+                .iter()
+                .map(|name| format!("Mark '{name}' for `rung sync` to restack")),
+        );
+        if let Some(remote) = &self.delete_remote {
+            steps.push(format!("Delete remote branch '{remote}'"));
+        }
+        steps.push(format!("Delete local branch '{}'", self.delete_local));
+        steps
+    }
+}
 
-    Ok(())
+/// Title and message for squashing `pr`: "<PR title> (#N)", then the PR body
+/// with `Co-authored-by` trailers for everyone else who committed on the branch.
+fn squash_commit(
+    repo: &Repository,
+    pr: &PullRequest,
+    branch: &str,
+    parent: &str,
+) -> (String, String) {
+    // Co-authors are a nicety; a missing parent just means there are none
+    let commits: Vec<(String, String)> = repo
+        .branch_commit(parent)
+        .and_then(|base| repo.commit_infos_between(base, repo.branch_commit(branch)?))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|commit| (commit.author(), commit.message))
+        .collect();
+
+    let merger = repo.signature().ok();
+    let co_authors = pr_body::co_authors(
+        commits.iter().map(|(a, m)| (a.as_str(), m.as_str())),
+        merger.as_ref().and_then(|sig| sig.email()),
+    );
+    pr_body::squash_commit(
+        &pr.title,
+        pr.number,
+        pr.body.as_deref().unwrap_or_default(),
+        &co_authors,
+    )
+}
+
+/// The requested method, or the first one the repository allows.
+///
+/// Fails early, listing the allowed methods, when the repository has the
+/// requested one disabled. Without the settings, trusts the request.
+fn choose_merge_method(
+    requested: Option<MergeMethod>,
+    settings: Option<&MergeSettings>,
+) -> Result<MergeMethod> {
+    let Some(settings) = settings else {
+        return Ok(requested.unwrap_or_default());
+    };
+    let allowed = settings.allowed();
+    match requested {
+        Some(method) if settings.allows(method) => Ok(method),
+        Some(method) => {
+            let names: Vec<&str> = allowed.iter().map(|m| m.as_str()).collect();
+            bail!(
+                "The repository doesn't allow {method} merges - use one of: {}",
+                names.join(", ")
+            )
+        }
+        None => allowed
+            .first()
+            .copied()
+            .context("The repository has no merge methods enabled"),
+    }
 }
 
 /// Output merge result as JSON.
@@ -365,18 +1128,100 @@ fn output_json(output: &MergeOutput) -> Result<()> {
     Ok(())
 }
 
-/// Collect all descendants of a branch in topological order (parents before children).
-fn collect_descendants(stack: &Stack, root: &str) -> Vec<String> {
-    let mut descendants = Vec::new();
-    let mut queue = vec![root.to_string()];
+/// Refuse to merge `pr` while a check its base branch requires has failed or
+/// is still running. Failing optional checks only get a warning.
+async fn ensure_required_checks(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    pr: &PullRequest,
+    json: bool,
+) -> Result<()> {
+    let required = match client
+        .get_required_checks(owner, repo, &pr.base_branch)
+        .await
+    {
+        Ok(required) => required,
+        // No such branch visible to us, so no protection to honour
+        Err(rung_github::Error::ApiError { status: 404, .. }) => vec![],
+        Err(e) => return Err(e).context("Failed to read the base branch's required checks"),
+    };
+    if required.is_empty() {
+        return Ok(());
+    }
+    let runs = client
+        .get_check_runs(owner, repo, &pr.head_sha)
+        .await
+        .context("Failed to fetch checks")?;
 
-    while let Some(parent) = queue.pop() {
-        for branch in &stack.branches {
-            if branch.parent.as_ref().is_some_and(|p| p == &parent) {
-                descendants.push(branch.name.to_string());
-                queue.push(branch.name.to_string());
-            }
+    let (mut failing, mut pending) = (vec![], vec![]);
+    for name in &required {
+        match runs
+            .iter()
+            .find(|run| &run.name == name)
+            .map(|run| run.status)
+        {
+            Some(status) if status.is_success() => {}
+            Some(status) if !status.is_pending() => failing.push(name.as_str()),
+            _ => pending.push(name.as_str()),
         }
     }
-    descendants
+    let number = pr.number;
+    if !failing.is_empty() {
+        bail!(
+            "Required check(s) failing on PR #{number}: {} - pass --ignore-checks to merge anyway",
+            failing.join(", ")
+        );
+    }
+    if !pending.is_empty() {
+        bail!(
+            "Required check(s) still running on PR #{number}: {} - wait with --when-ready, or pass --ignore-checks",
+            pending.join(", ")
+        );
+    }
+
+    let optional: Vec<&str> = runs
+        .iter()
+        .filter(|run| !run.status.is_success() && !run.status.is_pending())
+        .filter(|run| !required.contains(&run.name))
+        .map(|run| run.name.as_str())
+        .collect();
+    if !optional.is_empty() && !json {
+        output::warn(&format!(
+            "Optional check(s) failing on PR #{number}: {} - not required, merging anyway",
+            optional.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Poll a PR until GitHub reports it as mergeable.
+///
+/// Keeps waiting while mergeability is being computed or the PR is blocked
+/// (pending checks or reviews), and fails early on states waiting won't fix.
+async fn wait_until_mergeable(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    pr_number: u64,
+) -> Result<PullRequest> {
+    let pr = Poller::default()
+        .run_paced(client, || async {
+            let pr = client.get_pr(owner, repo, pr_number).await?;
+            let pending = pr.state == PullRequestState::Open
+                && (pr.mergeable.is_none()
+                    || matches!(pr.mergeable_state.as_deref(), Some("blocked" | "unknown")));
+            Ok(if pending {
+                PollOutcome::Pending
+            } else {
+                PollOutcome::Ready(pr)
+            })
+        })
+        .await
+        .with_context(|| format!("Failed waiting for PR #{pr_number}"))?;
+
+    if pr.state != PullRequestState::Open {
+        bail!("PR #{pr_number} is no longer open");
+    }
+    Ok(pr)
 }
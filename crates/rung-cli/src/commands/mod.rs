@@ -2,20 +2,56 @@
 
 use clap::{Parser, Subcommand};
 
+pub mod adopt;
+pub mod archive;
+pub mod blame;
+pub mod bugreport;
+pub mod cache;
+pub mod checks;
 pub mod completions;
+pub mod config;
+pub mod contains;
+pub mod copy;
 pub mod create;
 pub mod doctor;
+pub mod edit;
+pub mod foreach;
+pub mod forward_port;
+pub mod gc;
+pub mod graph;
+pub mod handoff;
+pub mod history;
 pub mod init;
 pub mod log;
 pub mod merge;
 pub mod mv;
 pub mod navigate;
+pub mod note;
+pub mod rebase;
+pub mod rebase_merges;
+pub mod remote_name;
+pub mod requires;
+pub mod revert;
+pub mod reword;
+pub mod scope;
+pub mod snapshot;
+pub mod stack;
+pub mod state;
+pub mod stats;
 pub mod status;
+pub mod status_at;
 pub mod submit;
+pub mod submit_attach;
+pub mod summary;
 pub mod sync;
+pub mod sync_webhook;
+pub mod tidy;
+pub mod tutorial;
 pub mod undo;
 pub mod update;
 mod utils;
+pub mod verify;
+pub mod workspace;
 
 /// Rung - The developer's ladder for stacked PRs.
 ///
@@ -25,10 +61,11 @@ mod utils;
 #[command(name = "rung")]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[allow(clippy::struct_excessive_bools)] // Global flags
 pub struct Cli {
     /// Output as JSON (for tooling integration).
     ///
-    /// Supported by: status, doctor, sync, submit, merge
+    /// Supported by: status, doctor, sync, submit, merge, adopt, copy, forward-port, revert, log --operations, stats, gc, config
     #[arg(long, global = true)]
     pub json: bool,
 
@@ -39,24 +76,87 @@ pub struct Cli {
     #[arg(short, long, global = true, conflicts_with = "json")]
     pub quiet: bool,
 
+    /// Output stable, line-oriented records for scripts.
+    ///
+    /// Supported by: status, sync, submit. `--porcelain` means
+    /// `--porcelain=v1`; a version's records never change, though new record
+    /// types may be added, which parsers should skip.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "VERSION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "v1",
+        conflicts_with_all = ["json", "quiet"]
+    )]
+    pub porcelain: Option<PorcelainVersion>,
+
+    /// Print how long each phase took, and the time spent waiting on GitHub,
+    /// to stderr at the end.
+    ///
+    /// Supported by: status, sync, submit.
+    #[arg(long, global = true)]
+    pub timings: bool,
+
+    /// Skip confirmation prompts for destructive operations.
+    ///
+    /// Required to merge, delete branches, undo, or abort outside a terminal.
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+
+    /// Never prompt or use color, for bots and CI (implied when `CI` is set).
+    ///
+    /// Anything that would prompt fails with exit code 4 and the flag to
+    /// pass instead.
+    #[arg(long, global = true)]
+    pub non_interactive: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+/// Formats `rung graph` exports the stack in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// A Mermaid flowchart, which GitHub renders in Markdown.
+    Mermaid,
+    /// A Graphviz digraph, for `dot -Tsvg`.
+    Dot,
+}
+
+/// Versions of the `--porcelain` format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PorcelainVersion {
+    V1,
+}
+
 /// Available commands.
 #[derive(Subcommand)]
 pub enum Commands {
     /// Initialize rung in the current repository.
-    Init,
+    Init {
+        /// Add existing local branches to the stack, picked from a list
+        /// (all of them without a terminal). Parents are inferred from
+        /// which branches each is built on.
+        #[arg(long)]
+        import: bool,
+
+        /// Attach the stack to the GitHub repository origin points at now,
+        /// after the repository moved or origin was changed on purpose.
+        #[arg(long, conflicts_with = "import")]
+        reattach: bool,
+    },
 
-    /// Create a new branch in the stack. [alias: c]
+    /// Create a new branch in the stack.
     ///
     /// Creates a new branch with the current branch as its parent.
     /// Optionally stages all changes and creates a commit with the given message.
     ///
     /// If --message is provided without a branch name, the name is derived
     /// from the commit message (e.g., "feat: add auth" becomes "feat-add-auth").
-    #[command(alias = "c")]
+    #[command(visible_alias = "c")]
     #[command(group(
         clap::ArgGroup::new("create_input")
             .required(true)
@@ -69,24 +169,88 @@ pub enum Commands {
         /// Commit message. If provided, stages all changes and creates a commit.
         #[arg(long, short)]
         message: Option<String>,
+
+        /// Name to push the branch as, if different from the local name.
+        #[arg(long, value_name = "NAME")]
+        remote: Option<String>,
+
+        /// Skip the pre-commit and commit-msg hooks.
+        #[arg(long, short = 'n')]
+        no_verify: bool,
+
+        /// With --message, stage only changes to tracked files (`git add -u`)
+        /// instead of all changes.
+        #[arg(long, short = 'u')]
+        tracked: bool,
+
+        /// With --message, create an empty commit without staging anything,
+        /// e.g. for a placeholder PR.
+        #[arg(long, conflicts_with = "tracked")]
+        empty: bool,
+
+        /// With --message, add a `Signed-off-by` trailer for your git
+        /// identity (always on with `signoff` under [general]).
+        #[arg(long, short)]
+        signoff: bool,
+
+        /// Create the branch even if it makes the stack deeper than
+        /// `max_depth` under [general].
+        #[arg(long)]
+        force: bool,
     },
 
-    /// Display the current stack status. [alias: st]
+    /// Display the current stack status.
     ///
     /// Shows a tree view of all branches in the stack with their
     /// sync state and PR status.
-    #[command(alias = "st")]
+    #[command(visible_alias = "st")]
     Status {
-        /// Fetch latest PR status from GitHub.
+        /// Fetch CI status for branches with PRs from GitHub.
+        ///
+        /// Results are cached and shown by `rung status` and `rung move`.
         #[arg(long)]
         fetch: bool,
+
+        /// Print only `branch<TAB>PR URL` pairs for branches with a PR.
+        #[arg(long)]
+        urls: bool,
+
+        /// Keep watching, and report when a commit or amend leaves
+        /// descendant branches needing a restack.
+        #[arg(long, conflicts_with = "urls")]
+        watch: bool,
+
+        /// Compare the stack against your open PRs on GitHub, reporting
+        /// untracked stacked PRs, closed PRs and mismatched bases.
+        #[arg(long, conflicts_with = "urls")]
+        remote: bool,
+
+        /// Print a one-line summary such as `stack: 4 branches, 1 behind,
+        /// 2 PRs open, CI: 1 failing`, for CI logs and status bars.
+        #[arg(long, conflicts_with_all = ["urls", "watch", "remote"])]
+        short: bool,
+
+        /// Show every branch of a long stack, not just the page around the
+        /// current branch.
+        #[arg(long)]
+        all: bool,
+
+        /// Show the stack as a sync backup recorded it, next to where each
+        /// branch is now. Takes a backup ID (or its first digits) or a time
+        /// such as `2026-10-15 14:03`, which picks the newest backup taken by then.
+        #[arg(
+            long,
+            value_name = "BACKUP|TIME",
+            conflicts_with_all = ["fetch", "urls", "watch", "remote", "short"]
+        )]
+        at: Option<String>,
     },
 
-    /// Sync the stack by rebasing all branches. [alias: sy]
+    /// Sync the stack by rebasing all branches.
     ///
     /// Detects merged PRs, updates stack topology, rebases branches,
     /// updates GitHub PR base branches, and pushes all changes.
-    #[command(alias = "sy")]
+    #[command(visible_alias = "sy")]
     Sync {
         /// Show what would be done without making changes.
         #[arg(long)]
@@ -107,18 +271,39 @@ pub enum Commands {
         /// Base branch to sync against (defaults to "main").
         #[arg(long, short)]
         base: Option<String>,
+
+        /// Run a shell command after each branch is rebased (e.g. "cargo check").
+        /// The sync pauses on the first failure; fix it and run `--continue`.
+        #[arg(long, value_name = "CMD", conflicts_with_all = ["continue", "abort"])]
+        exec: Option<String>,
+
+        /// Show a `git range-diff` of each rebased branch, to confirm the
+        /// rebase didn't alter its commits.
+        #[arg(long, conflicts_with_all = ["abort", "dry_run"])]
+        show_range_diff: bool,
+
+        /// Restack every open PR stack on the base branch from GitHub, for
+        /// CI: rebase each PR onto its base and force-push it. The base
+        /// defaults to the branch pushed in a GitHub Actions push event.
+        #[arg(long, conflicts_with_all = ["continue", "abort", "dry_run", "exec", "show_range_diff"])]
+        from_webhook: bool,
     },
 
-    /// Push branches and create/update PRs. [alias: sm]
+    /// Push branches and create/update PRs.
     ///
     /// Pushes all stack branches to the remote and creates or
     /// updates pull requests with stack navigation links.
-    #[command(alias = "sm")]
+    #[command(visible_alias = "sm")]
     Submit {
         /// Create PRs as drafts (won't trigger CI).
         #[arg(long)]
         draft: bool,
 
+        /// Create PRs as drafts, and have `rung sync` and `rung status
+        /// --watch` mark each one ready for review once its checks pass.
+        #[arg(long, conflicts_with = "draft")]
+        draft_until_green: bool,
+
         /// Show what would be done without making changes.
         #[arg(long)]
         dry_run: bool,
@@ -127,74 +312,824 @@ pub enum Commands {
         #[arg(long)]
         force: bool,
 
-        /// Custom PR title for current branch (overrides auto-generated title).
-        #[arg(long, short)]
-        title: Option<String>,
+        /// Custom title for a new PR, as `<title>` for the current branch or
+        /// `<branch>=<title>` for another one. Repeatable.
+        #[arg(long, short, value_name = "[BRANCH=]TITLE")]
+        title: Vec<String>,
+
+        /// File holding the body for a new PR, as `<path>` for the current
+        /// branch or `<branch>=<path>` for another one. Repeatable.
+        #[arg(long, value_name = "[BRANCH=]PATH")]
+        body_file: Vec<String>,
+
+        /// File (such as a screenshot) to upload to the `[pr] asset_branch`
+        /// and link from a PR's body, as `<path>` for the current branch or
+        /// `<branch>=<path>` for another one. Repeatable.
+        #[arg(long, value_name = "[BRANCH=]PATH")]
+        attach: Vec<String>,
+
+        /// Put new PRs in this open milestone (overrides `[pr] milestone`).
+        #[arg(long, value_name = "TITLE")]
+        milestone: Option<String>,
+
+        /// Add new PRs to the project with this number, owned by the
+        /// repository's owner (overrides `[pr] project`).
+        #[arg(long, value_name = "NUMBER")]
+        project: Option<u64>,
+
+        /// Submit despite `[policy]` violations, warning about them instead.
+        /// For emergencies.
+        #[arg(long)]
+        override_policy: bool,
+
+        /// Only refresh the stack comment on each PR, without pushing
+        /// branches or creating PRs.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "draft", "draft_until_green", "dry_run", "force", "title", "body_file",
+                "attach", "milestone", "project", "override_policy", "prs_only", "push_only",
+                "reopen", "no_reopen",
+            ]
+        )]
+        comments_only: bool,
+
+        /// Only create PRs for branches without one, pushing just the
+        /// branches not on the remote yet. Existing PRs and the stack
+        /// comments are left alone.
+        #[arg(long, conflicts_with = "push_only")]
+        prs_only: bool,
+
+        /// Reopen a branch's PR that was closed without merging, rather than
+        /// opening a new one, without asking.
+        #[arg(long, conflicts_with_all = ["no_reopen", "prs_only"])]
+        reopen: bool,
+
+        /// Open a new PR for a branch whose PR was closed without merging,
+        /// without asking.
+        #[arg(long)]
+        no_reopen: bool,
+
+        /// Only push the branches, without creating or updating PRs.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "draft", "draft_until_green", "dry_run", "title", "body_file", "attach",
+                "milestone", "project", "override_policy", "reopen", "no_reopen",
+            ]
+        )]
+        push_only: bool,
     },
 
-    /// Undo the last sync operation. [alias: un]
+    /// Undo the last sync, or other command that rewrote branches.
     ///
-    /// Restores all branches to their state before the last sync.
-    #[command(alias = "un")]
-    Undo,
+    /// Restores all branches to their state before the last sync, rebase,
+    /// edit, reword or merge rebasing descendants, or just the ones picked.
+    #[command(visible_alias = "un")]
+    Undo {
+        /// Pick which branches from the last backup to restore.
+        #[arg(long, short, conflicts_with = "branch")]
+        interactive: bool,
+
+        /// Restore only this branch. Repeatable.
+        #[arg(long, short, value_name = "NAME")]
+        branch: Vec<String>,
+    },
 
-    /// Merge the current branch's PR and clean up. [alias: m]
+    /// Merge a branch's PR and clean up.
     ///
-    /// Merges the PR via GitHub API, deletes the remote branch,
-    /// removes it from the stack, and checks out the parent.
-    #[command(alias = "m")]
+    /// Merges the PR via GitHub API, deletes the remote branch, and removes
+    /// it from the stack. Merging the current branch checks out the parent;
+    /// otherwise you stay on the branch you started from.
+    #[command(visible_alias = "m")]
     Merge {
-        /// Merge method: squash (default), merge, or rebase.
-        #[arg(long, short, default_value = "squash")]
-        method: String,
+        /// Branch or PR number (`42` or `#42`) to merge instead of the current
+        /// branch. It doesn't need to be checked out.
+        #[arg(value_name = "BRANCH|PR")]
+        target: Option<String>,
+
+        /// Merge the bottom of the stack up to and including this branch (or
+        /// PR number), one PR at a time, waiting for checks in between.
+        /// Branches they require (see `rung requires`) are merged first.
+        #[arg(long, value_name = "BRANCH|PR", conflicts_with = "target")]
+        until: Option<String>,
+
+        /// Merge method: squash, merge, or rebase.
+        ///
+        /// Defaults to `method` under `[merge]` in config, otherwise the first
+        /// of squash, merge and rebase that the repository allows.
+        #[arg(long, short)]
+        method: Option<String>,
 
         /// Don't delete the remote branch after merge.
         #[arg(long)]
         no_delete: bool,
+
+        /// Wait for required checks and reviews before merging.
+        #[arg(long)]
+        when_ready: bool,
+
+        /// Merge even if checks branch protection requires have failed or
+        /// are still running.
+        #[arg(long)]
+        ignore_checks: bool,
+
+        /// Show what would be merged, retargeted, rebased and deleted, without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Merge despite `[policy]` violations, warning about them instead.
+        /// For emergencies.
+        #[arg(long)]
+        override_policy: bool,
+
+        /// Merge and retarget the children's PRs, but leave rebasing the
+        /// descendants to the next `rung sync`, marking them as needing a
+        /// restack.
+        #[arg(long, conflicts_with = "until")]
+        no_rebase_descendants: bool,
+    },
+
+    /// Add stacks of PRs opened on GitHub to the local stack.
+    ///
+    /// Finds your open PRs based on one another (or on stack branches) that
+    /// rung doesn't track, fetches their branches and registers them with
+    /// the parents their bases imply.
+    Adopt {
+        /// Show what would be adopted without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Adopt this GitHub user's PRs instead of yours, e.g. a stack they
+        /// handed over.
+        #[arg(long, value_name = "LOGIN")]
+        author: Option<String>,
     },
 
-    /// Navigate to the next branch in the stack (child). [alias: n]
-    #[command(alias = "n")]
+    /// Roll back a landed PR.
+    ///
+    /// Branches `revert-<number>` off the PR's base with a commit undoing
+    /// the commit that landed it, adds the branch to the stack as a new root,
+    /// and opens a PR.
+    Revert {
+        /// PR number (`42` or `#42`) to revert, or a commit from its branch
+        /// as submitted, found among recorded landings.
+        #[arg(value_name = "PR|COMMIT")]
+        pr: String,
+
+        /// Don't push the branch or open a PR.
+        #[arg(long)]
+        no_pr: bool,
+
+        /// Open the PR as a draft.
+        #[arg(long, conflicts_with = "no_pr")]
+        draft: bool,
+    },
+
+    /// Hand the stack over to a teammate.
+    ///
+    /// Pushes every branch, makes sure each has a PR, comments on each PR
+    /// with how to take over, and prints the `rung adopt` command to run.
+    Handoff {
+        /// GitHub user taking over, mentioned in the comments.
+        #[arg(long, value_name = "LOGIN")]
+        to: Option<String>,
+    },
+
+    /// Copy a branch's own commits onto another base as a new branch.
+    ///
+    /// For backports, e.g. of a fix to a release branch. The original branch
+    /// and the stack are left as they are.
+    Copy {
+        /// Branch to copy (defaults to the current branch).
+        branch: Option<String>,
+
+        /// Base branch to copy the commits onto.
+        #[arg(long, value_name = "BRANCH")]
+        onto: String,
+
+        /// Name for the new branch (defaults to `<branch>-to-<onto>`).
+        #[arg(long, short)]
+        name: Option<String>,
+
+        /// Push the new branch and open a PR against the base.
+        #[arg(long)]
+        pr: bool,
+
+        /// Open the PR as a draft.
+        #[arg(long, requires = "pr")]
+        draft: bool,
+    },
+
+    /// Copy the current stack onto another branch, e.g. from `release/x` to `main`.
+    ///
+    /// Replays each branch not yet ported onto the copy of its parent, as
+    /// `<branch>-to-<target>`, registers the copies as a new stack, and
+    /// remembers which branches were ported.
+    ForwardPort {
+        /// Branch to port the stack onto.
+        target: String,
+
+        /// Show what would be ported without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Navigate to the next branch in the stack (child).
+    #[command(visible_alias = "n")]
     Nxt,
 
-    /// Navigate to the previous branch in the stack (parent). [alias: p]
-    #[command(alias = "p")]
+    /// Navigate to the previous branch in the stack (parent).
+    #[command(visible_alias = "p")]
     Prv,
 
-    /// Interactive branch picker for quick navigation. [alias: mv]
+    /// Interactive branch picker for quick navigation.
     ///
     /// Opens a TUI list to select and jump to any branch in the stack.
-    #[command(alias = "mv")]
-    Move,
+    #[command(visible_alias = "mv")]
+    Move {
+        /// Jump straight to this branch instead of opening the picker.
+        #[arg(long, short)]
+        branch: Option<String>,
+    },
 
-    /// Diagnose issues with the stack and repository. [alias: doc]
+    /// Diagnose issues with the stack and repository.
     ///
     /// Checks stack integrity, git state, sync status, and GitHub connectivity.
-    #[command(alias = "doc")]
-    Doctor,
+    #[command(visible_alias = "doc")]
+    Doctor {
+        /// Restore a corrupt stack.json from its previous version, and set
+        /// upstreams of stack branches that track the wrong remote branch.
+        #[arg(long)]
+        fix: bool,
+
+        /// Bundle versions, the findings, the redacted config, the stack and
+        /// recent operations into a tarball to attach to an issue.
+        #[arg(long)]
+        bugreport: bool,
+    },
+
+    /// List each PR branch's CI check runs, with when they ran and how long.
+    ///
+    /// Covers the checks on each branch's local tip, as `rung status --fetch`
+    /// does.
+    Checks {
+        /// Rank checks by their longest completed run across the stack,
+        /// with the branch it was on and the average.
+        #[arg(long)]
+        slowest: bool,
+    },
+
+    /// Check that the whole stack is ready to land.
+    ///
+    /// Runs the doctor's checks, then checks each branch is synced, pushed to
+    /// an open PR with a valid title, green in CI, approved, mergeable and
+    /// within `[policy]`. Prints a pass/fail matrix and exits non-zero unless
+    /// everything passes.
+    Verify,
 
-    /// Update rung to the latest version. [alias: up]
+    /// Update rung to the latest version.
     ///
     /// Checks crates.io for the latest version and installs it using
     /// cargo-binstall (fast) or cargo install (fallback).
-    #[command(alias = "up")]
+    #[command(visible_alias = "up")]
     Update {
         /// Only check for updates without installing.
         #[arg(long)]
         check: bool,
     },
 
-    /// Generate shell completions. [alias: comp]
+    /// Generate shell completions.
     ///
     /// Outputs completion script to stdout. Redirect to a file and
     /// source it in your shell configuration.
-    #[command(alias = "comp")]
+    #[command(visible_alias = "comp")]
     Completions {
         /// Shell to generate completions for.
         #[arg(value_enum)]
-        shell: clap_complete::Shell,
+        shell: completions::CompletionShell,
     },
 
     /// Show commits between the base branch and HEAD
-    Log,
+    Log {
+        /// Also list the files changed on the branch.
+        #[arg(long)]
+        files: bool,
+
+        /// Show the log of operations rung has performed (merges, ...) instead.
+        #[arg(long, conflicts_with_all = ["files", "patch"])]
+        operations: bool,
+
+        /// Show each commit's diff.
+        #[arg(short, long)]
+        patch: bool,
+
+        /// Print directly instead of through the pager.
+        #[arg(long)]
+        no_pager: bool,
+    },
+
+    /// Show how rung moved a branch.
+    ///
+    /// Lists the branch's reflog entries that rung wrote, newest first: every
+    /// ref rung updates is logged as `rung <command>: <what>`.
+    History {
+        /// Branch to show [default: current branch].
+        branch: Option<String>,
+    },
+
+    /// Find where a commit is in the stack, and whether it landed on trunk.
+    ///
+    /// Reports the stack branch with the commit, whether it's pushed, and its
+    /// PR. The commit landed if trunk has it, a trunk commit makes the same
+    /// changes (same patch ID), or it's in the recorded landing of its
+    /// branch: after a squash or rebase merge, the branch's commits land as
+    /// new ones. A recorded landing is compared with the branch as submitted,
+    /// showing a range-diff when they differ.
+    Contains {
+        /// Commit to look for: a SHA or any revision, or else part of its
+        /// message.
+        commit: String,
+    },
+
+    /// Show which stack branch and PR introduced each line of a file.
+    ///
+    /// Blames the file as of `HEAD` and maps each line's commit to the stack
+    /// branch that has it among its own commits. Unmarked lines came from
+    /// trunk.
+    Blame {
+        /// File to annotate.
+        file: String,
+    },
+
+    /// Export the stack as a graph, with PR numbers and states.
+    ///
+    /// Prints a Mermaid flowchart for Markdown, or a Graphviz digraph. PR
+    /// states are the ones `rung sync` last saw.
+    Graph {
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = GraphFormat::Mermaid)]
+        format: GraphFormat,
+    },
+
+    /// Report workflow metrics from the operations log.
+    ///
+    /// PRs landed per week, average stack depth, time from submit to merge,
+    /// and conflicts per sync. Only covers what rung did in this clone.
+    Stats {
+        /// Number of calendar weeks to report on, including this one.
+        #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..))]
+        weeks: u32,
+
+        /// Report on sync conflicts instead: the syncs that hit them, and the
+        /// branches and files that conflict most.
+        #[arg(long)]
+        conflicts: bool,
+    },
+
+    /// Sum up what you shipped, ready to paste into a weekly update.
+    ///
+    /// Stacks and branches created, PRs opened, updated and merged, and syncs
+    /// with their conflicts. Read from the operations log, so nothing leaves
+    /// this machine, and only what rung did in this clone is covered.
+    Summary {
+        /// How far back to look: hours, days or weeks, like `12h`, `3d` or `2w`.
+        #[arg(long, default_value = "1w", value_parser = rung_core::stats::parse_period)]
+        since: chrono::TimeDelta,
+    },
+
+    /// List cleanups worth doing, and apply the ones picked.
+    ///
+    /// Suggests deleting local branches whose PRs merged, branches without a
+    /// PR idle for `stale_after_days`, and branches with no commits of their
+    /// own, and removing sync backups beyond `backup_retention`.
+    Tidy {
+        /// Apply every cleanup instead of picking (confirm, or pass --yes).
+        #[arg(long)]
+        all: bool,
+
+        /// Days idle before a branch without a PR is suggested
+        /// [default: `general.stale_after_days`].
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u32>,
+    },
+
+    /// Clean up old sync backups, operations log entries and leftover files.
+    ///
+    /// Keeps `.git/rung` from growing without bound. Never removes the backup
+    /// of a sync in progress.
+    Gc {
+        /// Number of sync backups to keep [default: `general.backup_retention`].
+        #[arg(long, value_name = "N")]
+        keep_backups: Option<usize>,
+
+        /// Drop operations log entries older than this many days (0 keeps all).
+        #[arg(long, value_name = "DAYS", default_value_t = 365)]
+        log_days: u32,
+    },
+
+    /// Interactively rebase the commits on the current branch.
+    ///
+    /// Runs `git rebase -i` onto the parent's tip so only this branch's commits
+    /// are shown, then restacks all descendants onto the result.
+    #[command(visible_alias = "e")]
+    Edit {
+        /// Force-push rewritten branches without asking.
+        #[arg(long)]
+        push: bool,
+    },
+
+    /// Change a commit message on the current branch.
+    ///
+    /// Rewords the branch tip (or the given commit), restacks descendants,
+    /// and can update the PR title and body to match the new message.
+    #[command(visible_alias = "rw")]
+    Reword {
+        /// Commit to reword (defaults to the branch tip).
+        commit: Option<String>,
+
+        /// New commit message. Opens your editor if omitted.
+        #[arg(long, short)]
+        message: Option<String>,
+
+        /// Update the PR title and body from the branch's tip commit.
+        #[arg(long)]
+        update_pr: bool,
+
+        /// Force-push rewritten branches without asking.
+        #[arg(long)]
+        push: bool,
+
+        /// Add a `Signed-off-by` trailer for your git identity to the new
+        /// message (always on with `signoff` under [general]).
+        #[arg(long, short)]
+        signoff: bool,
+    },
+
+    /// Show or set the paths the current branch is expected to change.
+    ///
+    /// `rung status` flags branches that change files outside their scope.
+    Scope {
+        /// Directories (relative to the repository root) to scope the branch to.
+        paths: Vec<String>,
+
+        /// Remove the branch's path scope.
+        #[arg(long, conflicts_with = "paths")]
+        clear: bool,
+    },
+
+    /// Show or set the name the current branch is pushed as.
+    ///
+    /// Used when the remote branch name differs from the local one, e.g.
+    /// pushing `me/feature` as `feature`.
+    RemoteName {
+        /// Branch name to use on the remote.
+        name: Option<String>,
+
+        /// Push under the local name again.
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
+    },
+
+    /// Stack the current branch on top of someone else's PR.
+    ///
+    /// Fetches the PR's head into a local branch, adds it to the stack as
+    /// the current branch's parent, and rebases the branch and its
+    /// descendants onto it. The PR's branch is frozen: `rung sync` refreshes
+    /// it from the PR, and moves its children onto its base once it merges,
+    /// but rung never rebases, pushes, submits or merges it.
+    Rebase {
+        /// Number of the PR to stack on.
+        #[arg(long, value_name = "NUMBER")]
+        onto_pr: u64,
+    },
+
+    /// Keep merge commits on the current branch when syncing.
+    ///
+    /// Sync then rebases the branch with `git rebase --rebase-merges`
+    /// instead of flattening it. Set `rebase_merges` under `[sync]` to do
+    /// this for every branch.
+    RebaseMerges {
+        /// Flatten the branch again.
+        #[arg(long)]
+        off: bool,
+    },
+
+    /// Show or set the branches the current branch requires to land first.
+    ///
+    /// For dependencies outside the parent chain, such as a sibling branch
+    /// adding a migration this one uses. Sync warns about requirements that
+    /// can't be met, and `rung merge --until` lands required branches first.
+    Requires {
+        /// Stack branches to require, replacing the current requirements.
+        branches: Vec<String>,
+
+        /// Remove the branch's requirements.
+        #[arg(long, conflicts_with = "branches")]
+        clear: bool,
+    },
+
+    /// Attach freeform notes to a stack branch, or list them.
+    ///
+    /// Notes are kept with the stack, one line each, for context like what
+    /// the branch is blocked on. With `notes = true` under `[pr]`, `rung
+    /// submit` mirrors them into a hidden comment in the branch's PR body.
+    Note {
+        #[command(subcommand)]
+        command: Option<NoteCommand>,
+
+        /// Branch whose notes to show or change (defaults to the current branch).
+        #[arg(long, global = true)]
+        branch: Option<String>,
+    },
+
+    /// Run a command in each directory the current branch affects.
+    ///
+    /// Uses the branch's path scope, or the configured packages it touches.
+    Foreach {
+        /// Run in every configured package instead.
+        #[arg(long)]
+        all: bool,
+
+        /// Command to run (e.g. `rung foreach -- cargo test`).
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Park the current stack in an archive.
+    ///
+    /// Records branch relationships, PR numbers, and commit SHAs for the
+    /// stack containing the current branch, then removes it from the active stack.
+    Archive {
+        /// Name for the archive (defaults to the stack's root branch).
+        name: Option<String>,
+
+        /// Delete the local branches after archiving.
+        #[arg(long)]
+        delete: bool,
+
+        /// List archived stacks instead of archiving.
+        #[arg(long, conflicts_with_all = ["name", "delete"])]
+        list: bool,
+    },
+
+    /// Capture or compare a named snapshot of every stack branch tip.
+    ///
+    /// Snapshots are stored as refs under `refs/rung/snapshots/<name>/` and
+    /// are never cleaned up automatically, unlike sync backups.
+    #[command(args_conflicts_with_subcommands = true)]
+    Snapshot {
+        #[command(subcommand)]
+        command: Option<SnapshotCommand>,
+
+        /// Name of the snapshot to create.
+        name: Option<String>,
+    },
+
+    /// Restore an archived stack.
+    ///
+    /// Recreates local branches from the recorded SHAs (or the remote if the
+    /// commits are gone) and adds them back to the stack.
+    Unarchive {
+        /// Archive to restore (defaults to the most recent).
+        name: Option<String>,
+    },
+
+    /// Inspect rung's recorded stack state.
+    State {
+        #[command(subcommand)]
+        command: StateCommand,
+    },
+
+    /// Move the stack to another clone or machine.
+    ///
+    /// An export is a standalone JSON file with each branch's parent, PR
+    /// number, tip and base commits. Importing it checks every commit exists
+    /// before tracking the branches.
+    Stack {
+        #[command(subcommand)]
+        command: StackCommand,
+    },
+
+    /// Read and change settings without editing `config.toml`.
+    ///
+    /// Keys are dotted paths, like `sync.push_comment` or
+    /// `orgs.acme.merge.method`. Values are checked against the setting
+    /// before anything is written.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Manage cached data.
+    ///
+    /// CI results fetched by `rung status --fetch` are cached in
+    /// `.git/rung/ci.json`, and branch states computed by `rung status` in
+    /// `.git/rung/status.json`. See `[cache]` in the config to limit or disable this.
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+
+    /// Work on stacks spanning several repositories.
+    ///
+    /// A workspace is a `rung-workspace.toml` file listing repository paths,
+    /// found by walking up from the current directory.
+    Workspace {
+        #[command(subcommand)]
+        command: WorkspaceCommand,
+    },
+
+    /// Learn stacked branches with a guided tour.
+    ///
+    /// Runs create, status, navigation and sync in a throwaway repository in
+    /// the temp directory. Never touches GitHub or your own repositories.
+    Tutorial {
+        /// Keep the demo repository afterwards and print its path.
+        #[arg(long)]
+        keep: bool,
+    },
+}
+
+/// Snapshot subcommands.
+#[derive(Subcommand)]
+pub enum SnapshotCommand {
+    /// Show what changed since a snapshot was taken.
+    Diff {
+        /// Snapshot to compare against.
+        name: String,
+    },
+
+    /// List existing snapshots.
+    List,
+
+    /// Delete a snapshot.
+    Delete {
+        /// Snapshot to delete.
+        name: String,
+    },
+}
+
+/// Note subcommands. Notes are numbered from 1, as listed.
+#[derive(Subcommand)]
+pub enum NoteCommand {
+    /// Add a note.
+    Add {
+        /// The note's text.
+        #[arg(required = true)]
+        text: Vec<String>,
+    },
+
+    /// List the notes.
+    List,
+
+    /// Replace a note's text.
+    Edit {
+        /// Number of the note to replace.
+        number: usize,
+
+        /// The new text.
+        #[arg(required = true)]
+        text: Vec<String>,
+    },
+
+    /// Remove a note.
+    Remove {
+        /// Number of the note to remove.
+        number: usize,
+    },
+
+    /// Remove every note.
+    Clear,
+}
+
+/// State subcommands.
+#[derive(Subcommand)]
+pub enum StateCommand {
+    /// Show how the stack changed: added and removed branches, new parents
+    /// and PR numbers.
+    ///
+    /// Compares the current stack with the version before its last change, or
+    /// with the stack as it was when a sync backup was taken.
+    Diff {
+        /// Compare with a sync backup (defaults to the latest) instead.
+        #[arg(long, value_name = "ID", num_args = 0..=1, default_missing_value = "latest")]
+        backup: Option<String>,
+    },
+}
+
+/// Stack subcommands.
+#[derive(Subcommand)]
+pub enum StackCommand {
+    /// Write every stack branch to a JSON file (or stdout).
+    Export {
+        /// File to write (defaults to stdout).
+        path: Option<String>,
+    },
+
+    /// Track the branches of an exported stack.
+    ///
+    /// Branches missing locally are created at their exported tips; ones that
+    /// exist are left where they are. Nothing changes unless every commit the
+    /// export refers to exists - fetch them first.
+    Import {
+        /// File written by `rung stack export`.
+        path: String,
+    },
+}
+
+/// Config subcommands.
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print a setting's value (the one in effect, unless a file is picked).
+    Get {
+        /// Dotted key, e.g. `general.backup_retention`.
+        key: String,
+
+        #[command(flatten)]
+        scope: ConfigScope,
+    },
+
+    /// Change a setting in the repository config (or with --global, the
+    /// user-wide one).
+    Set {
+        /// Dotted key, e.g. `general.backup_retention`.
+        key: String,
+
+        /// New value, as TOML (`true`, `5`, `["a", "b"]`). Strings needn't be quoted.
+        value: String,
+
+        #[command(flatten)]
+        scope: ConfigScope,
+    },
+
+    /// List every setting in effect, defaults included, or those a file sets.
+    List {
+        #[command(flatten)]
+        scope: ConfigScope,
+    },
+}
+
+/// Which config file `rung config` reads or writes.
+#[derive(clap::Args)]
+pub struct ConfigScope {
+    /// Use the repository config, `.git/rung/config.toml`.
+    #[arg(long, conflicts_with_all = ["global", "team"])]
+    pub local: bool,
+
+    /// Use the user-wide config, whose `[orgs.<owner>]` sections apply to
+    /// every repository.
+    #[arg(long, conflicts_with = "team")]
+    pub global: bool,
+
+    /// Use the team config checked in as `.rung/team.toml`, which the
+    /// repository config overrides.
+    #[arg(long)]
+    pub team: bool,
+}
+
+/// Cache subcommands.
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Remove all cached data.
+    Clear,
+}
+
+/// Workspace subcommands.
+#[derive(Subcommand)]
+pub enum WorkspaceCommand {
+    /// Create `rung-workspace.toml` in the current directory.
+    Init {
+        /// Repository paths to include, relative to the current directory.
+        repos: Vec<String>,
+    },
+
+    /// Add repositories to the workspace.
+    Add {
+        /// Repository paths, relative to the workspace file.
+        #[arg(required = true)]
+        repos: Vec<String>,
+    },
+
+    /// Run `rung status` in each repository.
+    Status {
+        /// Extra arguments for `rung status`.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Run `rung sync` in each repository.
+    Sync {
+        /// Extra arguments for `rung sync`.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Run `rung submit` in each repository, then link every PR to the PRs
+    /// in the other repositories.
+    Submit {
+        /// Extra arguments for `rung submit`.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
 }
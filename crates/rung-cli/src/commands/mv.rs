@@ -1,13 +1,35 @@
 //! `rung move` command - Interactive branch navigation.
 
+use std::fmt;
+
 use super::utils::open_repo_and_state;
-use crate::output;
+use crate::{output, prompt};
 use anyhow::{Context, Result, bail};
+use colored::Colorize;
 use inquire::Select;
+use rung_core::BranchState;
+use rung_git::Repository;
+
+/// A branch in the picker.
+struct Entry {
+    name: String,
+    label: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label)
+    }
+}
 
 /// Run the move command - interactive branch picker.
-pub fn run() -> Result<()> {
+///
+/// Shows the stack as a tree under its trunk branch(es), with sync state,
+/// PR numbers and cached CI results. Typing filters branches by fuzzy match.
+/// With `branch`, jumps there directly (for scripts and non-interactive use).
+pub fn run(branch: Option<&str>) -> Result<()> {
     let (repo, state) = open_repo_and_state()?;
+    repo.require_no_operation()?;
     let current = repo.current_branch()?;
     let stack = state.load_stack()?;
 
@@ -15,42 +37,96 @@ pub fn run() -> Result<()> {
         bail!("No branches in stack. Use `rung create <name>` to add one.");
     }
 
-    // Build display options with visual indicators
-    let options: Vec<String> = stack
-        .branches
-        .iter()
-        .map(|b| {
-            let marker = if b.name == current { " ◀" } else { "" };
-            let pr = b.pr.map(|n| format!(" #{n}")).unwrap_or_default();
-            format!("{}{}{}", b.name, pr, marker)
-        })
-        .collect();
-
-    // Find current branch index for pre-selection
-    let start_idx = stack
-        .branches
-        .iter()
-        .position(|b| b.name == current)
-        .unwrap_or(0);
-
-    let selection = Select::new("Jump to branch:", options)
+    if let Some(target) = branch {
+        if !repo.branch_exists(target) {
+            bail!("Branch '{target}' does not exist");
+        }
+        return switch_to(&repo, &current, target);
+    }
+    prompt::require_interactive("Picking a branch", "pass --branch <name>")?;
+
+    let ci_cache = state.load_ci_cache();
+    let marker = |name: &str| if name == current { " ◀" } else { "" };
+
+    // Trunks are the untracked branches the stack is built on (typically main)
+    let mut trunks: Vec<&str> = vec![];
+    for root in stack.roots() {
+        if let Some(parent) = root.parent.as_deref() {
+            if !trunks.contains(&parent) && repo.branch_exists(parent) {
+                trunks.push(parent);
+            }
+        }
+    }
+
+    let rows = stack.tree();
+    let mut entries = vec![];
+    for group in trunks.iter().copied().map(Some).chain([None]) {
+        if let Some(trunk) = group {
+            entries.push(Entry {
+                name: trunk.to_string(),
+                label: format!("{}{}", trunk.bold(), marker(trunk)),
+            });
+        }
+        let offset = usize::from(group.is_some());
+
+        let mut in_group = false;
+        for &(depth, branch) in &rows {
+            if depth == 0 {
+                let trunk = branch.parent.as_deref().filter(|p| trunks.contains(p));
+                in_group = trunk == group;
+            }
+            if !in_group {
+                continue;
+            }
+
+            let sync_state = rung_core::sync::branch_state(&repo, &stack, branch)
+                .unwrap_or(BranchState::Detached);
+            let ci = repo
+                .branch_commit(&branch.name)
+                .ok()
+                .and_then(|commit| ci_cache.status(&branch.name, &commit.to_string()));
+            let pr = branch.pr.map(|n| format!(" #{n}")).unwrap_or_default();
+            let ci = output::ci_indicator(ci);
+            let ci = if ci.is_empty() { ci } else { format!(" {ci}") };
+
+            entries.push(Entry {
+                name: branch.name.to_string(),
+                label: format!(
+                    "{}{} {}{pr}{ci}{}",
+                    "  ".repeat(depth + offset),
+                    output::state_indicator(&sync_state),
+                    branch.name,
+                    marker(&branch.name),
+                ),
+            });
+        }
+    }
+
+    // Pre-select the current branch
+    let start_idx = entries.iter().position(|e| e.name == current).unwrap_or(0);
+
+    // Match the typed filter against branch names only, not the decorations
+    let scorer = |input: &str, entry: &Entry, _: &str, idx: usize| {
+        Select::<Entry>::DEFAULT_SCORER(input, entry, &entry.name, idx)
+    };
+
+    let selection = Select::new("Jump to branch:", entries)
         .with_starting_cursor(start_idx)
         .with_page_size(10)
+        .with_scorer(&scorer)
         .prompt()
         .context("Selection cancelled")?;
 
-    // Extract branch name (everything before first space)
-    let branch_name = selection
-        .split_whitespace()
-        .next()
-        .context("Invalid selection")?;
+    switch_to(&repo, &current, &selection.name)
+}
 
-    if branch_name == current {
+/// Check out `target` unless it's already the current branch.
+fn switch_to(repo: &Repository, current: &str, target: &str) -> Result<()> {
+    if target == current {
         output::info("Already on this branch");
     } else {
-        repo.checkout(branch_name)?;
-        output::success(&format!("Switched to '{branch_name}'"));
+        repo.checkout(target)?;
+        output::success(&format!("Switched to '{target}'"));
     }
-
     Ok(())
 }
@@ -7,6 +7,7 @@ use anyhow::{Result, bail};
 /// Navigate to the next (child) branch in the stack.
 pub fn run_next() -> Result<()> {
     let (repo, state) = open_repo_and_state()?;
+    repo.require_no_operation()?;
 
     let current = repo.current_branch()?;
     let stack = state.load_stack()?;
@@ -38,6 +39,7 @@ pub fn run_next() -> Result<()> {
 /// Navigate to the previous (parent) branch in the stack.
 pub fn run_prev() -> Result<()> {
     let (repo, state) = open_repo_and_state()?;
+    repo.require_no_operation()?;
 
     let current = repo.current_branch()?;
     let stack = state.load_stack()?;
@@ -0,0 +1,102 @@
+//! `rung note` command - Attach freeform notes to a stack branch.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+
+use super::NoteCommand;
+use super::utils::{load_config, open_repo_and_state};
+use crate::output;
+
+/// JSON output for the note command.
+#[derive(Debug, Serialize)]
+struct NoteOutput {
+    branch: String,
+    notes: Vec<String>,
+}
+
+/// Run the note command on `branch` (the current branch by default). Without
+/// a subcommand the branch's notes are listed.
+pub fn run(json: bool, command: Option<NoteCommand>, branch: Option<&str>) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => repo.current_branch()?,
+    };
+    let mut stack = state.load_stack()?;
+    let entry = stack
+        .find_branch_mut(&branch)
+        .with_context(|| format!("Branch '{branch}' is not in the stack"))?;
+
+    let message = match command {
+        None | Some(NoteCommand::List) => None,
+        Some(NoteCommand::Add { text }) => {
+            entry.notes.push(note_text(&text)?);
+            Some(format!("Added note {} to '{branch}'", entry.notes.len()))
+        }
+        Some(NoteCommand::Edit { number, text }) => {
+            let index = note_index(&entry.notes, number)?;
+            entry.notes[index] = note_text(&text)?;
+            Some(format!("Updated note {number} on '{branch}'"))
+        }
+        Some(NoteCommand::Remove { number }) => {
+            let index = note_index(&entry.notes, number)?;
+            entry.notes.remove(index);
+            Some(format!("Removed note {number} from '{branch}'"))
+        }
+        Some(NoteCommand::Clear) => {
+            entry.notes.clear();
+            Some(format!("Cleared the notes on '{branch}'"))
+        }
+    };
+    let notes = entry.notes.clone();
+    let pr = entry.pr;
+    if message.is_some() {
+        state.save_stack(&stack)?;
+    }
+
+    if json {
+        let out = NoteOutput { branch, notes };
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    if let Some(message) = message {
+        output::success(&message);
+        if let Some(pr) = pr {
+            if load_config(&repo, &state)?.pr.notes {
+                output::hint(&format!("`rung submit` copies the notes into PR #{pr}"));
+            }
+        }
+        return Ok(());
+    }
+    if notes.is_empty() {
+        output::info(&format!(
+            "No notes on '{branch}' - add one with `rung note add <text>`"
+        ));
+    }
+    for (i, note) in notes.iter().enumerate() {
+        output::essential(&format!("{:>3}. {note}", i + 1));
+    }
+    Ok(())
+}
+
+/// The words of a note as one line.
+fn note_text(words: &[String]) -> Result<String> {
+    let text = words
+        .iter()
+        .flat_map(|word| word.split_whitespace())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        bail!("A note can't be empty");
+    }
+    Ok(text)
+}
+
+/// The index of note `number`, counting from 1 as they're listed.
+fn note_index(notes: &[String], number: usize) -> Result<usize> {
+    if number == 0 || number > notes.len() {
+        bail!("No note {number} - the branch has {} note(s)", notes.len());
+    }
+    Ok(number - 1)
+}
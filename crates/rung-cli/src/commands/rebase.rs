@@ -0,0 +1,201 @@
+//! `rung rebase --onto-pr` command - Stack the current branch on top of
+//! someone else's PR.
+
+use anyhow::{Context, Result, bail};
+use rung_core::sync::{self, SyncResult};
+use rung_core::{BranchName, Stack, StackBranch, State};
+use rung_git::Repository;
+use rung_github::PullRequestState;
+use serde::Serialize;
+
+use super::utils::{branch_base, github_client, github_remote, load_config, open_repo_and_state};
+use crate::exit;
+use crate::i18n::tr;
+use crate::output;
+
+/// JSON output for rebase command.
+#[derive(Debug, Serialize)]
+struct RebaseOutput {
+    branch: String,
+    onto_pr: u64,
+    /// Local branch tracking the PR's head.
+    parent: String,
+    /// Branches rebased onto it: the current branch and its descendants.
+    rebased: Vec<String>,
+}
+
+/// Run the rebase command: make PR `onto_pr`'s head the current branch's
+/// parent, as a frozen branch, and rebase the branch and its descendants
+/// onto it.
+pub fn run(json: bool, onto_pr: u64) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    repo.require_no_operation()?;
+    repo.require_clean()?;
+    if state.is_sync_in_progress() {
+        bail!("Sync in progress - run `rung sync --continue` or `rung sync --abort` first");
+    }
+
+    let current = repo.current_branch()?;
+    let mut stack = state.load_stack()?;
+    let branch = stack
+        .find_branch(&current)
+        .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+    if branch.frozen {
+        bail!("'{current}' is someone else's PR - check out your own branch to rebase it");
+    }
+
+    let config = load_config(&repo, &state)?;
+    let remote = github_remote(&repo, &config)?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let pr = rt
+        .block_on(client.get_pr(&remote.owner, &remote.repo, onto_pr))
+        .with_context(|| format!("Failed to fetch PR #{onto_pr}"))?;
+    if pr.state != PullRequestState::Open {
+        bail!("PR #{onto_pr} is not open");
+    }
+
+    // Its base, as a stack branch when it is one
+    let base = stack
+        .branches
+        .iter()
+        .find(|b| *b.remote_name() == pr.base_branch)
+        .map_or_else(|| pr.base_branch.clone(), |b| b.name.to_string());
+    if base == current
+        || stack
+            .descendants_of(&current)
+            .iter()
+            .any(|b| b.name == base)
+    {
+        bail!("PR #{onto_pr} is based on '{base}', which is built on '{current}'");
+    }
+    let base = BranchName::new(&base).context("Invalid base branch name")?;
+
+    let existing = stack
+        .branches
+        .iter()
+        .find(|b| b.frozen && b.pr == Some(onto_pr))
+        .map(|b| b.name.clone());
+    let head = BranchName::new(&pr.head_branch)
+        .with_context(|| format!("PR #{onto_pr} has an invalid branch name"))?;
+    let name = match existing.clone() {
+        Some(name) => name,
+        None => new_branch_name(&repo, &stack, &head, onto_pr)?,
+    };
+
+    // Where the branch starts now, so only its own commits are moved
+    let tip = repo.branch_commit(&current)?;
+    let trunk = branch.parent.as_deref().unwrap_or(&pr.base_branch);
+    let old_base = branch_base(&repo, &stack, branch, tip, trunk);
+
+    repo.fetch_pull_head(onto_pr, &name)
+        .with_context(|| format!("Failed to fetch PR #{onto_pr}"))?;
+    if existing.is_none() {
+        let mut frozen = StackBranch::new(name.clone(), Some(base));
+        frozen.pr = Some(onto_pr);
+        frozen.frozen = true;
+        frozen.author.clone_from(&pr.author);
+        if name != head {
+            frozen.remote = Some(head);
+        }
+        stack.add_branch(frozen);
+    } else if let Some(frozen) = stack.find_branch_mut(&name) {
+        frozen.parent = Some(base);
+    }
+    if let Some(branch) = stack.find_branch_mut(&current) {
+        branch.parent = Some(name.clone());
+        branch.based_on = old_base.map(|oid| oid.to_string());
+    }
+    state.save_stack(&stack)?;
+    let rebased = rebase_subtree(&repo, &state, &stack, &current, &pr.base_branch, json)?;
+
+    if json {
+        let output = RebaseOutput {
+            branch: current,
+            onto_pr,
+            parent: name.to_string(),
+            rebased,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    output::success(&format!(
+        "Rebased '{current}' onto {} ('{name}')",
+        output::pr_ref(Some(onto_pr))
+    ));
+    if rebased.len() > 1 {
+        output::info(&format!(
+            "  and {} descendant(s); `rung submit` pushes them",
+            rebased.len() - 1
+        ));
+    }
+    output::info(
+        "  `rung sync` keeps them on the PR as it changes, and onto its base once it merges",
+    );
+    Ok(())
+}
+
+/// A local name for the PR's branch: its head branch's, or `pr-<number>`
+/// when that is taken.
+fn new_branch_name(
+    repo: &Repository,
+    stack: &Stack,
+    head: &BranchName,
+    number: u64,
+) -> Result<BranchName> {
+    let taken = |name: &str| repo.branch_exists(name) || stack.find_branch(name).is_some();
+    if !taken(head) {
+        return Ok(head.clone());
+    }
+    let name = BranchName::new(format!("pr-{number}"))?;
+    if taken(&name) {
+        bail!("Branch '{name}' already exists - rename it to stack on PR #{number}");
+    }
+    Ok(name)
+}
+
+/// Rebase `current` and its descendants onto their parents, leaving the
+/// rest of the stack alone. Returns the branches rebased.
+fn rebase_subtree(
+    repo: &Repository,
+    state: &State,
+    stack: &Stack,
+    current: &str,
+    trunk: &str,
+    json: bool,
+) -> Result<Vec<String>> {
+    let mut moving: Vec<String> = stack
+        .descendants_of(current)
+        .iter()
+        .map(|b| b.name.to_string())
+        .collect();
+    moving.push(current.to_string());
+    let mut plan = sync::create_sync_plan(repo, stack, trunk)?;
+    plan.branches
+        .retain(|action| moving.contains(&action.branch));
+    let rebased = plan.branches.iter().map(|a| a.branch.clone()).collect();
+
+    match sync::execute_sync(repo, state, plan, None, "rebase")? {
+        SyncResult::AlreadySynced | SyncResult::Complete { .. } => Ok(rebased),
+        SyncResult::Paused {
+            at_branch,
+            conflict_files,
+            ..
+        } => {
+            if !json {
+                output::warn("After resolving the conflicts, run `rung sync --continue`");
+                output::info("  `rung sync --abort` puts the branches back instead.");
+                output::hint(&tr!(
+                    "hint-stage-resolved",
+                    files = conflict_files.join(" ")
+                ));
+            }
+            exit::set(exit::Code::Paused);
+            bail!("Rebase conflict in '{at_branch}' - resolve it and run `rung sync --continue`");
+        }
+        SyncResult::ExecFailed { at_branch, .. } | SyncResult::Interrupted { at_branch, .. } => {
+            bail!("Rebase stopped at '{at_branch}' - run `rung sync --continue` to finish it");
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! `rung rebase-merges` command - Keep merge commits on a branch when syncing.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// JSON output for rebase-merges command.
+#[derive(Debug, Serialize)]
+struct RebaseMergesOutput {
+    branch: String,
+    rebase_merges: bool,
+}
+
+/// Run the rebase-merges command.
+///
+/// Makes `rung sync` rebase the current branch with `--rebase-merges`, or
+/// flatten it again with `off`.
+pub fn run(json: bool, off: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let current = repo.current_branch()?;
+    let mut stack = state.load_stack()?;
+    let branch = stack
+        .find_branch_mut(&current)
+        .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+
+    branch.rebase_merges = !off;
+    state.save_stack(&stack)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&RebaseMergesOutput {
+                branch: current,
+                rebase_merges: !off,
+            })?
+        );
+        return Ok(());
+    }
+
+    if off {
+        output::success(&format!("'{current}' will be flattened when synced"));
+    } else {
+        output::success(&format!(
+            "'{current}' will keep its merge commits when synced"
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! `rung remote-name` command - Push a branch under a different name.
+
+use anyhow::{Context, Result};
+use rung_core::BranchName;
+use serde::Serialize;
+
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// JSON output for remote-name command.
+#[derive(Debug, Serialize)]
+struct RemoteNameOutput {
+    branch: String,
+    remote: String,
+}
+
+/// Run the remote-name command.
+///
+/// With a name, pushes the current branch under that name from now on; with
+/// `clear`, goes back to the local name; otherwise reports the remote name.
+pub fn run(json: bool, name: Option<&str>, clear: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let current = repo.current_branch()?;
+    let mut stack = state.load_stack()?;
+    let branch = stack
+        .find_branch_mut(&current)
+        .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+
+    let changed = clear || name.is_some();
+    if changed {
+        branch.remote = name
+            .map(BranchName::new)
+            .transpose()
+            .context("Invalid remote branch name")?
+            .filter(|remote| *remote != current);
+    }
+    let remote = branch.remote_name().to_string();
+    if changed {
+        state.save_stack(&stack)?;
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&RemoteNameOutput {
+                branch: current,
+                remote,
+            })?
+        );
+        return Ok(());
+    }
+
+    if changed {
+        output::success(&format!("'{current}' will be pushed as '{remote}'"));
+        output::info("Existing PRs keep their head branch - close and re-submit to move them");
+    } else {
+        output::info(&format!("'{current}' is pushed as '{remote}'"));
+    }
+
+    Ok(())
+}
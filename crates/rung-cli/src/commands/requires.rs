@@ -0,0 +1,84 @@
+//! `rung requires` command - Declare branches that must land before this one.
+
+use anyhow::{Context, Result, bail};
+use rung_core::BranchName;
+use serde::Serialize;
+
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// JSON output for requires command.
+#[derive(Debug, Serialize)]
+struct RequiresOutput {
+    branch: String,
+    requires: Vec<String>,
+}
+
+/// Run the requires command.
+///
+/// With branches, replaces the current branch's requirements; with `clear`,
+/// removes them; otherwise reports them.
+pub fn run(json: bool, branches: &[String], clear: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let current = repo.current_branch()?;
+    let mut stack = state.load_stack()?;
+    if stack.find_branch(&current).is_none() {
+        bail!("Branch '{current}' is not in the stack");
+    }
+
+    let changed = clear || !branches.is_empty();
+    if changed {
+        let mut requires = vec![];
+        for name in branches {
+            if stack.find_branch(name).is_none() {
+                bail!("Branch '{name}' is not in the stack");
+            }
+            if *name == current || stack.depends_on(name, &current) {
+                bail!("'{name}' can't land before '{current}' - it depends on it");
+            }
+            if stack.ancestry(&current).iter().any(|b| b.name == *name) {
+                bail!("'{name}' is already below '{current}' in the stack");
+            }
+            let name = BranchName::new(name).context("Invalid branch name")?;
+            if !requires.contains(&name) {
+                requires.push(name);
+            }
+        }
+        let branch = stack
+            .find_branch_mut(&current)
+            .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+        branch.requires = requires;
+        state.save_stack(&stack)?;
+    }
+
+    let requires: Vec<String> = stack
+        .find_branch(&current)
+        .map(|b| b.requires.iter().map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&RequiresOutput {
+                branch: current,
+                requires,
+            })?
+        );
+        return Ok(());
+    }
+
+    match (changed, requires.is_empty()) {
+        (true, true) => output::success(&format!("Cleared the requirements of '{current}'")),
+        (true, false) => output::success(&format!(
+            "'{current}' requires {} to land first",
+            requires.join(", ")
+        )),
+        (false, true) => output::info(&format!("'{current}' requires no other branches")),
+        (false, false) => output::info(&format!(
+            "'{current}' requires {} to land first",
+            requires.join(", ")
+        )),
+    }
+
+    Ok(())
+}
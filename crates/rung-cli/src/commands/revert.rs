@@ -0,0 +1,214 @@
+//! `rung revert` command - Roll back a landed PR with a revert PR.
+
+use anyhow::{Context, Result, bail};
+use rung_core::operations::OperationKind;
+use rung_core::{BranchName, StackBranch};
+use rung_git::{Oid, Repository};
+use rung_github::{CreatePullRequest, PullRequestState};
+use serde::Serialize;
+
+use super::utils::{github_client, github_remote, load_config, open_repo_and_state, push};
+use crate::output;
+
+/// JSON output for the revert command.
+#[derive(Debug, Serialize)]
+struct RevertOutput {
+    reverted_pr: u64,
+    reverted_commit: String,
+    branch: BranchName,
+    base: BranchName,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    conflict: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr_url: Option<String>,
+}
+
+/// Run the revert command.
+///
+/// Branches `revert-<number>` off the PR's base, commits the inverse of the
+/// commit that landed it, adds the branch to the stack as a new root and,
+/// unless `no_pr` is set, pushes it and opens a PR.
+pub fn run(json: bool, target: &str, no_pr: bool, draft: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let number = resolve_pr(&repo, &state, target)?;
+    if state.is_sync_in_progress() {
+        bail!("Sync in progress - run `rung sync --continue` or `rung sync --abort` first");
+    }
+    repo.require_clean()?;
+
+    let branch = BranchName::new(format!("revert-{number}"))?;
+    let mut stack = state.load_stack()?;
+    if repo.branch_exists(&branch) || stack.find_branch(&branch).is_some() {
+        bail!("Branch '{branch}' already exists");
+    }
+
+    let config = load_config(&repo, &state)?;
+    let remote = github_remote(&repo, &config)?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let landed = rt
+        .block_on(client.get_pr(&remote.owner, &remote.repo, number))
+        .with_context(|| format!("Failed to fetch PR #{number}"))?;
+    if landed.state != PullRequestState::Merged {
+        bail!("PR #{number} hasn't been merged - nothing to revert");
+    }
+
+    // GitHub knows the landed commit; rung's records cover older clients
+    let merge_sha = landed
+        .merge_commit_sha
+        .or_else(|| {
+            state
+                .load_landings()
+                .for_pr(number)
+                .map(|landing| landing.commit.clone())
+        })
+        .or_else(|| logged_merge_sha(&state, number))
+        .with_context(|| format!("Cannot tell which commit landed PR #{number}"))?;
+    let merged = Oid::from_str(&merge_sha).context("Invalid merge commit SHA")?;
+    let trunk = BranchName::new(landed.base_branch).context("Invalid base branch name")?;
+    let base = revert_base(&repo, &trunk, merged, json)?;
+
+    repo.create_branch_at(&branch, base)?;
+    repo.checkout(&branch)?;
+    let conflict = match repo.revert_commit(merged, config.general.signoff) {
+        Ok(_) => false,
+        Err(rung_git::Error::RevertConflict(files)) => {
+            if !json {
+                output::warn(&format!("Conflict while reverting PR #{number}"));
+                for file in &files {
+                    println!("  → {file}");
+                }
+            }
+            true
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut entry = StackBranch::new(branch.clone(), Some(trunk.clone()));
+    entry.based_on = Some(base.to_string());
+    stack.add_branch(entry);
+    state.save_stack(&stack)?;
+
+    let mut output = RevertOutput {
+        reverted_pr: number,
+        reverted_commit: merge_sha,
+        branch,
+        base: trunk,
+        conflict,
+        pr: None,
+        pr_url: None,
+    };
+
+    if !conflict && !no_pr {
+        push(
+            &repo,
+            &output.branch,
+            &output.branch,
+            false,
+            config.general.verify_pushes,
+        )?;
+        let (title, body) = super::submit::get_pr_title_and_body(&repo, &output.branch, &config.pr);
+        let body = format!("Reverts #{number}.\n\n{body}")
+            .trim_end()
+            .to_string();
+        let created = rt
+            .block_on(client.create_pr(
+                &remote.owner,
+                &remote.repo,
+                CreatePullRequest {
+                    title,
+                    body,
+                    head: output.branch.to_string(),
+                    base: output.base.to_string(),
+                    draft,
+                },
+            ))
+            .context("Failed to create PR")?;
+
+        if let Some(entry) = stack.find_branch_mut(&output.branch) {
+            entry.pr = Some(created.number);
+        }
+        state.save_stack(&stack)?;
+        output.pr = Some(created.number);
+        output.pr_url = Some(created.html_url);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_summary(&output);
+    }
+    Ok(())
+}
+
+/// Print what was reverted and what to do next.
+fn print_summary(result: &RevertOutput) {
+    if result.conflict {
+        output::info("Resolve, run `git revert --continue`, then `rung submit` to open the PR");
+        return;
+    }
+    output::success(&format!(
+        "Reverted PR #{} on '{}' (off '{}')",
+        result.reverted_pr, result.branch, result.base
+    ));
+    match &result.pr_url {
+        Some(url) => output::essential(url),
+        None => output::info("Run `rung submit` to open the PR"),
+    }
+}
+
+/// The PR `target` names: a number (`42` or `#42`), or a commit of a branch
+/// whose landing was recorded.
+fn resolve_pr(repo: &Repository, state: &rung_core::State, target: &str) -> Result<u64> {
+    if let Ok(number) = target.trim_start_matches('#').parse() {
+        return Ok(number);
+    }
+    let commit = repo
+        .resolve_commit(target)
+        .with_context(|| format!("'{target}' is neither a PR number nor a commit"))?;
+    state
+        .load_landings()
+        .containing(&commit.to_string())
+        .map(|landing| landing.pr_number)
+        .with_context(|| format!("No recorded landing includes commit '{target}'"))
+}
+
+/// Merge commit recorded when `rung merge` landed PR `number`.
+fn logged_merge_sha(state: &rung_core::State, number: u64) -> Option<String> {
+    state
+        .load_operations()
+        .ok()?
+        .into_iter()
+        .rev()
+        .find_map(|op| match op.kind {
+            OperationKind::Merge {
+                pr_number,
+                merge_sha,
+                ..
+            } if pr_number == number => Some(merge_sha),
+            _ => None,
+        })
+}
+
+/// Where to start the revert branch: the local `trunk` if it already has
+/// `merged`, otherwise `origin/<trunk>` freshly fetched.
+fn revert_base(repo: &Repository, trunk: &BranchName, merged: Oid, json: bool) -> Result<Oid> {
+    let contains = |tip: Oid| {
+        repo.commit_exists(merged) && repo.merge_base(tip, merged).is_ok_and(|b| b == merged)
+    };
+    if let Some(tip) = repo.branch_commit(trunk).ok().filter(|&tip| contains(tip)) {
+        return Ok(tip);
+    }
+
+    if !json {
+        output::info(&format!("Fetching '{trunk}' from origin..."));
+    }
+    repo.fetch_remote_branch(trunk)
+        .with_context(|| format!("Failed to fetch '{trunk}'"))?;
+    repo.remote_branch_commit(trunk)
+        .ok()
+        .filter(|&tip| contains(tip))
+        .with_context(|| format!("Commit {merged} is not on '{trunk}'"))
+}
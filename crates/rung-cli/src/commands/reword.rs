@@ -0,0 +1,229 @@
+//! `rung reword` command - Change a commit message and keep the PR in step.
+
+use std::fs;
+
+use anyhow::{Context, Result, bail};
+use rung_core::sync::{self, RestackResult};
+use rung_core::ticket::TicketMatcher;
+use rung_core::{State, pr_body};
+use rung_git::{Oid, Repository};
+use rung_github::UpdatePullRequest;
+
+use super::edit::{confirm_push, push_branches};
+use super::submit::{append_ticket_link, get_pr_title_and_body};
+use super::utils::{backup_tips, github_client, github_remote, load_config, open_repo_and_state};
+use crate::{output, prompt};
+
+/// Run the reword command.
+///
+/// Rewords `commit` (defaults to the branch tip), restacks descendants, and
+/// optionally syncs the PR title/body with the branch's tip commit message.
+/// With `signoff`, the new message is signed off.
+pub fn run(
+    commit: Option<&str>,
+    message: Option<&str>,
+    update_pr: bool,
+    push: bool,
+    signoff: bool,
+) -> Result<()> {
+    if message.is_none() {
+        prompt::require_editor(
+            "GIT_EDITOR",
+            "Editing the commit message",
+            "pass --message or set GIT_EDITOR",
+        )?;
+    }
+    let (repo, state) = open_repo_and_state()?;
+    repo.require_clean()?;
+
+    if state.is_sync_in_progress() {
+        bail!("Sync in progress - run `rung sync --continue` or `rung sync --abort` first");
+    }
+
+    let current = repo.current_branch()?;
+    let stack = state.load_stack()?;
+    let branch = stack
+        .find_branch(&current)
+        .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+    let parent = branch
+        .parent
+        .as_ref()
+        .with_context(|| format!("Branch '{current}' has no parent branch"))?;
+    let pr = branch.pr;
+
+    let tip = repo.branch_commit(&current)?;
+    let target = match commit {
+        Some(spec) => repo.resolve_commit(spec)?,
+        None => tip,
+    };
+
+    let own_commits = repo.commits_between(repo.branch_commit(parent)?, tip)?;
+    if !own_commits.contains(&target) {
+        bail!(
+            "Commit {} is not on '{current}' - only the branch's own commits can be reworded",
+            short(target)
+        );
+    }
+
+    let old_message = repo
+        .find_commit(target)?
+        .message()
+        .unwrap_or_default()
+        .to_string();
+    let mut new_message = match message {
+        Some(m) => m.trim().to_string(),
+        None => edit_message(&repo, &old_message)?,
+    };
+    let config = state.load_config()?;
+    if (signoff || config.general.signoff) && !new_message.is_empty() {
+        new_message = repo.sign_off(&new_message)?;
+    }
+
+    if new_message.is_empty() {
+        bail!("Aborting reword due to empty commit message");
+    }
+    if new_message == old_message.trim() {
+        output::info("Message unchanged");
+        return Ok(());
+    }
+
+    let mut affected: Vec<String> = vec![current.clone()];
+    affected.extend(
+        stack
+            .descendants_of(&current)
+            .iter()
+            .map(|b| b.name.to_string()),
+    );
+    let old_tips = sync::capture_tips(&repo, &affected)?;
+
+    backup_tips(&state, "reword", &old_tips)?;
+
+    repo.reword_commit(&current, target, &new_message)?;
+    output::success(&format!("Reworded {} on '{current}'", short(target)));
+
+    let rebased = match sync::restack_descendants(&repo, &stack, &config, &current, &old_tips)? {
+        RestackResult::Complete { rebased } => rebased,
+        RestackResult::Conflict {
+            at_branch,
+            conflict_files,
+            ..
+        } => {
+            output::warn(&format!("Conflict while restacking '{at_branch}'"));
+            for file in &conflict_files {
+                println!("  → {file}");
+            }
+            output::info("Resolve, run `git rebase --continue`, then `rung sync` to finish");
+            return Ok(());
+        }
+    };
+
+    sync::record_bases(&repo, &state, &rebased)?;
+    if !rebased.is_empty() {
+        repo.checkout(&current)?;
+        output::success(&format!("Restacked {} descendant(s)", rebased.len()));
+    }
+
+    let mut to_push = vec![current.clone()];
+    to_push.extend(rebased);
+    if push || confirm_push(to_push.len())? {
+        push_branches(&repo, &stack, &to_push, config.general.verify_pushes)?;
+    }
+
+    match (update_pr, pr) {
+        (true, Some(number)) => update_pr_from_tip(&repo, &state, &current, number)?,
+        (true, None) => output::info(&format!("'{current}' has no PR to update")),
+        (false, Some(_)) => output::info("Run with --update-pr to update the PR title and body"),
+        (false, None) => {}
+    }
+
+    Ok(())
+}
+
+/// Set the PR title and body from the branch's tip commit message.
+///
+/// A detected ticket ID is kept in the title, as `rung submit` adds it on creation.
+fn update_pr_from_tip(
+    repo: &Repository,
+    state: &State,
+    branch: &str,
+    pr_number: u64,
+) -> Result<()> {
+    let config = load_config(repo, state)?;
+    let remote = github_remote(repo, &config)?;
+    let (owner, repo_name) = (remote.owner.clone(), remote.repo.clone());
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+
+    let (mut title, mut body) = get_pr_title_and_body(repo, branch, &config.pr);
+    if let Some(tickets) = &config.tickets {
+        let matcher = TicketMatcher::new(tickets)?;
+        let message = repo.branch_commit_message(branch).unwrap_or_default();
+        if let Some(id) = matcher.find(branch, [message.as_str()]) {
+            title = matcher.apply_to_title(&title, &id);
+            if let Some(url) = matcher.url(&id) {
+                body = append_ticket_link(&body, &id, &url);
+            }
+        }
+    }
+    body = pr_body::append_footer(&body, &config.pr);
+    let update = UpdatePullRequest {
+        title: Some(title),
+        body: Some(body),
+        base: None,
+    };
+
+    rt.block_on(client.update_pr(&owner, &repo_name, pr_number, update))
+        .with_context(|| format!("Failed to update PR #{pr_number}"))?;
+    output::success(&format!("Updated PR #{pr_number}"));
+    Ok(())
+}
+
+/// Open the user's git editor on `initial` and return the edited message.
+///
+/// Lines starting with `#` are stripped, matching `git commit`.
+fn edit_message(repo: &Repository, initial: &str) -> Result<String> {
+    let workdir = repo.workdir().context("Cannot run in bare repository")?;
+    let editor = std::process::Command::new("git")
+        .args(["var", "GIT_EDITOR"])
+        .current_dir(workdir)
+        .output()
+        .context("Failed to determine git editor")?;
+    let editor = String::from_utf8_lossy(&editor.stdout).trim().to_string();
+    if editor.is_empty() {
+        bail!("No editor configured - pass the new message with --message");
+    }
+
+    let path = repo.git_dir().join("RUNG_REWORD_EDITMSG");
+    fs::write(
+        &path,
+        format!(
+            "{}\n\n# Enter the new commit message. Lines starting with '#' are ignored.\n",
+            initial.trim_end()
+        ),
+    )?;
+
+    // Let the shell expand editors configured with arguments (e.g. "code --wait")
+    let status = rung_core::shell::command_with_arg(&editor, &path)
+        .current_dir(workdir)
+        .status()
+        .context("Failed to launch editor")?;
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    if !status.success() {
+        bail!("Editor exited with {status}");
+    }
+
+    Ok(edited?
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string())
+}
+
+/// Abbreviated SHA for display.
+fn short(oid: Oid) -> String {
+    oid.to_string()[..8].to_string()
+}
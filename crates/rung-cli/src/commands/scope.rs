@@ -0,0 +1,110 @@
+//! `rung scope` command - Associate a branch with monorepo paths.
+
+use anyhow::{Context, Result};
+use rung_core::scope;
+use serde::Serialize;
+
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// JSON output for scope command.
+#[derive(Debug, Serialize)]
+struct ScopeOutput {
+    branch: String,
+    paths: Vec<String>,
+    packages: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    outside_scope: Vec<String>,
+}
+
+/// Run the scope command.
+///
+/// With paths, replaces the current branch's scope; with `clear`, removes it;
+/// otherwise reports the scope and the packages the branch touches.
+pub fn run(json: bool, paths: &[String], clear: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let current = repo.current_branch()?;
+    let mut stack = state.load_stack()?;
+    let branch = stack
+        .find_branch_mut(&current)
+        .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+
+    if clear || !paths.is_empty() {
+        branch.paths = paths
+            .iter()
+            .map(|p| scope::normalize_path(p))
+            .collect::<rung_core::Result<_>>()?;
+        let updated = branch.paths.clone();
+        state.save_stack(&stack)?;
+
+        if !json {
+            if updated.is_empty() {
+                output::success(&format!("Cleared path scope for '{current}'"));
+            } else {
+                output::success(&format!("Scoped '{current}' to: {}", updated.join(", ")));
+            }
+        }
+    }
+
+    let branch = stack
+        .find_branch(&current)
+        .with_context(|| format!("Branch '{current}' is not in the stack"))?;
+    let config = state.load_config()?;
+    let files = match &branch.parent {
+        Some(parent) => repo.changed_files(&current, parent)?,
+        None => vec![],
+    };
+    let packages: Vec<String> = scope::touched_packages(&files, &config.packages)
+        .iter()
+        .map(|p| p.name.clone())
+        .collect();
+    let outside: Vec<String> = scope::outside_scope(&files, &branch.paths)
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&ScopeOutput {
+                branch: current,
+                paths: branch.paths.clone(),
+                packages,
+                outside_scope: outside,
+            })?
+        );
+        return Ok(());
+    }
+
+    if clear || !paths.is_empty() {
+        // Just report problems after an update
+        warn_outside(&outside);
+        return Ok(());
+    }
+
+    if branch.paths.is_empty() {
+        output::info(&format!("'{current}' has no path scope"));
+    } else {
+        output::info(&format!(
+            "'{current}' is scoped to: {}",
+            branch.paths.join(", ")
+        ));
+    }
+    if !packages.is_empty() {
+        output::info(&format!("Touches packages: {}", packages.join(", ")));
+    }
+    warn_outside(&outside);
+
+    Ok(())
+}
+
+/// Warn about changed files outside the branch's scope.
+fn warn_outside(outside: &[String]) {
+    if outside.is_empty() {
+        return;
+    }
+    output::warn(&format!("{} changed file(s) outside scope:", outside.len()));
+    for file in outside {
+        println!("  → {file}");
+    }
+}
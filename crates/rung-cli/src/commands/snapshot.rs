@@ -0,0 +1,124 @@
+//! `rung snapshot` command - Capture and compare named stack states.
+
+use anyhow::Result;
+use colored::Colorize;
+use rung_core::snapshot::{self, SnapshotBranchDiff, SnapshotChange};
+use serde::Serialize;
+
+use super::SnapshotCommand;
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// JSON output for snapshot creation.
+#[derive(Debug, Serialize)]
+struct SnapshotOutput {
+    name: String,
+    branches: usize,
+}
+
+/// JSON output for snapshot diff.
+#[derive(Debug, Serialize)]
+struct SnapshotDiffOutput {
+    name: String,
+    branches: Vec<SnapshotBranchDiff>,
+}
+
+/// Run the snapshot command.
+pub fn run(json: bool, command: Option<SnapshotCommand>, name: Option<&str>) -> Result<()> {
+    match (command, name) {
+        (Some(SnapshotCommand::Diff { name }), _) => run_diff(json, &name),
+        (Some(SnapshotCommand::List), _) | (None, None) => run_list(json),
+        (Some(SnapshotCommand::Delete { name }), _) => run_delete(json, &name),
+        (None, Some(name)) => run_create(json, name),
+    }
+}
+
+fn run_create(json: bool, name: &str) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let stack = state.load_stack()?;
+    let captured = snapshot::create_snapshot(&repo, &stack, name)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&SnapshotOutput {
+                name: name.to_string(),
+                branches: captured,
+            })?
+        );
+        return Ok(());
+    }
+
+    output::success(&format!("Saved snapshot '{name}' ({captured} branch(es))"));
+    output::info(&format!("Compare later with: rung snapshot diff {name}"));
+    Ok(())
+}
+
+fn run_list(json: bool) -> Result<()> {
+    let (repo, _state) = open_repo_and_state()?;
+    let names = snapshot::list_snapshots(&repo)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&names)?);
+        return Ok(());
+    }
+
+    if names.is_empty() {
+        output::info("No snapshots. Create one with `rung snapshot <name>`.");
+    }
+    for name in names {
+        output::essential(&name);
+    }
+    Ok(())
+}
+
+fn run_diff(json: bool, name: &str) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let stack = state.load_stack()?;
+    let diffs = snapshot::diff_snapshot(&repo, &stack, name)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&SnapshotDiffOutput {
+                name: name.to_string(),
+                branches: diffs,
+            })?
+        );
+        return Ok(());
+    }
+
+    println!();
+    println!("  {} {}", "Changes since snapshot".bold(), name.cyan());
+    output::hr();
+    for diff in &diffs {
+        let description = match &diff.change {
+            SnapshotChange::Unchanged => "unchanged".dimmed().to_string(),
+            SnapshotChange::Moved {
+                commits_added,
+                commits_removed,
+            } => format!("+{commits_added} / -{commits_removed} commits")
+                .yellow()
+                .to_string(),
+            SnapshotChange::Deleted => "deleted".red().to_string(),
+            SnapshotChange::Added => "new since snapshot".green().to_string(),
+        };
+        println!("  {}  {description}", diff.branch);
+    }
+    output::hr();
+    println!();
+    Ok(())
+}
+
+fn run_delete(json: bool, name: &str) -> Result<()> {
+    let (repo, _state) = open_repo_and_state()?;
+    snapshot::delete_snapshot(&repo, name)?;
+
+    if json {
+        println!("{}", serde_json::json!({ "deleted": name }));
+        return Ok(());
+    }
+
+    output::success(&format!("Deleted snapshot '{name}'"));
+    Ok(())
+}
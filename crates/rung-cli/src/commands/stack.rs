@@ -0,0 +1,195 @@
+//! `rung stack` command - Export the stack, and import it in another clone.
+
+use std::collections::HashSet;
+use std::fs;
+
+use anyhow::{Context, Result, anyhow, bail};
+use rung_core::BranchName;
+use rung_core::export::{ExportedBranch, StackExport};
+use rung_git::Oid;
+use serde::Serialize;
+
+use super::StackCommand;
+use super::utils::{open_repo_and_state, open_repo_and_state_read_only};
+use crate::output;
+
+/// JSON output for `rung stack export <path>` and `rung stack import`.
+#[derive(Debug, Serialize)]
+struct StackOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    branches: Vec<String>,
+    /// Local branches created by an import.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    created: Vec<String>,
+}
+
+/// Run the stack command.
+pub fn run(json: bool, command: StackCommand) -> Result<()> {
+    match command {
+        StackCommand::Export { path } => run_export(json, path.as_deref()),
+        StackCommand::Import { path } => run_import(json, &path),
+    }
+}
+
+/// Write every stack branch, with its tip and base commits, to `path` or
+/// stdout.
+fn run_export(json: bool, path: Option<&str>) -> Result<()> {
+    let (repo, state) = open_repo_and_state_read_only()?;
+    let stack = state.load_stack()?;
+    if stack.is_empty() {
+        bail!("No stack branches to export");
+    }
+
+    let mut branches = Vec::with_capacity(stack.len());
+    for branch in stack.topological_order()? {
+        let Ok(commit) = repo.branch_commit(&branch.name) else {
+            output::warn(&format!(
+                "Skipping '{}' - branch no longer exists locally",
+                branch.name
+            ));
+            continue;
+        };
+        let base = branch
+            .parent
+            .as_ref()
+            .and_then(|p| repo.branch_commit(p).ok())
+            .and_then(|parent| repo.merge_base(commit, parent).ok())
+            .map(|oid| oid.to_string());
+        branches.push(ExportedBranch::new(branch, commit.to_string(), base));
+    }
+
+    let export = StackExport::new(branches);
+    let text = serde_json::to_string_pretty(&export)?;
+    let Some(path) = path else {
+        println!("{text}");
+        return Ok(());
+    };
+    fs::write(path, format!("{text}\n")).with_context(|| format!("Failed to write {path}"))?;
+
+    let names: Vec<String> = export
+        .branch_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    if json {
+        return output_json(&StackOutput {
+            path: Some(path.to_string()),
+            branches: names,
+            created: vec![],
+        });
+    }
+    output::success(&format!("Exported {} branch(es) to {path}", names.len()));
+    output::info(&format!("Import elsewhere with: rung stack import {path}"));
+    Ok(())
+}
+
+/// Track the branches of an export, once every commit it refers to is known
+/// to exist here.
+fn run_import(json: bool, path: &str) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+    let export =
+        StackExport::parse(&text).map_err(|e| anyhow!("{path} is not a stack export: {e}"))?;
+    if export.branches.is_empty() {
+        bail!("{path} has no branches to import");
+    }
+
+    let mut stack = state.load_stack()?;
+    let exported: HashSet<&str> = export.branch_names().into_iter().collect();
+
+    // Check everything before touching anything, so a bad export leaves no
+    // partial import
+    let mut missing = vec![];
+    let mut to_create: Vec<(&BranchName, Oid)> = vec![];
+    for branch in &export.branches {
+        let name = &branch.name;
+        if stack.find_branch(name).is_some() {
+            bail!("Branch '{name}' is already in the stack");
+        }
+        if let Some(parent) = &branch.parent {
+            let parent = parent.as_str();
+            if !exported.contains(parent)
+                && stack.find_branch(parent).is_none()
+                && !repo.branch_exists(parent)
+                && repo.remote_branch_commit(parent).is_err()
+            {
+                bail!(
+                    "'{name}' is based on '{parent}', which is not in the export, the stack or this repository"
+                );
+            }
+        }
+
+        let commit = Oid::from_str(&branch.commit)
+            .ok()
+            .filter(|oid| repo.commit_exists(*oid));
+        let Some(commit) = commit else {
+            missing.push(format!("{} (tip of '{name}')", short(&branch.commit)));
+            continue;
+        };
+        if let Some(base) = &branch.base {
+            if !Oid::from_str(base).is_ok_and(|oid| repo.commit_exists(oid)) {
+                missing.push(format!("{} (base of '{name}')", short(base)));
+            }
+        }
+
+        if !repo.branch_exists(name) {
+            to_create.push((name, commit));
+        } else if repo.branch_commit(name)? != commit && !json {
+            output::warn(&format!(
+                "'{name}' is not at the exported {} here - leaving it where it is",
+                short(&branch.commit)
+            ));
+        }
+    }
+    if !missing.is_empty() {
+        bail!(
+            "Commits the export refers to are not in this repository: {} - fetch them first (`git fetch origin`)",
+            missing.join(", ")
+        );
+    }
+
+    for branch in &export.branches {
+        stack.add_branch(branch.to_stack_branch());
+    }
+    // Refuse an export whose parents loop before saving anything
+    stack.topological_order()?;
+
+    for (name, commit) in &to_create {
+        repo.create_branch_at(name, *commit)?;
+    }
+    state.save_stack(&stack)?;
+
+    let names: Vec<String> = export
+        .branch_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let created: Vec<String> = to_create
+        .iter()
+        .map(|(name, _)| (*name).to_string())
+        .collect();
+    if json {
+        return output_json(&StackOutput {
+            path: Some(path.to_string()),
+            branches: names,
+            created,
+        });
+    }
+    output::success(&format!("Imported {} branch(es) from {path}", names.len()));
+    if !created.is_empty() {
+        output::info(&format!("Created {} local branch(es)", created.len()));
+    }
+    Ok(())
+}
+
+/// The first 8 characters of a SHA, as typed in the export.
+fn short(sha: &str) -> &str {
+    sha.get(..8).unwrap_or(sha)
+}
+
+/// Output stack export/import result as JSON.
+fn output_json(output: &StackOutput) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(output)?);
+    Ok(())
+}
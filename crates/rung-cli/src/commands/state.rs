@@ -0,0 +1,103 @@
+//! `rung state` command - Inspect rung's recorded stack state.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use rung_core::StackDiff;
+use serde::Serialize;
+
+use super::StateCommand;
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// JSON output for state diff.
+#[derive(Debug, Serialize)]
+struct StateDiffOutput {
+    /// "previous", or the sync backup ID compared against.
+    against: String,
+    #[serde(flatten)]
+    diff: StackDiff,
+}
+
+/// Run the state command.
+pub fn run(json: bool, command: StateCommand) -> Result<()> {
+    match command {
+        StateCommand::Diff { backup } => run_diff(json, backup.as_deref()),
+    }
+}
+
+fn run_diff(json: bool, backup: Option<&str>) -> Result<()> {
+    let (_repo, state) = open_repo_and_state()?;
+    let current = state.load_stack()?;
+
+    let (against, older) = match backup {
+        None => (
+            "previous".to_string(),
+            state
+                .load_previous_stack()
+                .context("No previous stack version to compare with")?,
+        ),
+        Some(id) => {
+            let id = if id == "latest" {
+                state.latest_backup().context("No sync backups found")?
+            } else {
+                id.to_string()
+            };
+            let stack = state
+                .load_backup_stack(&id)
+                .with_context(|| format!("Backup {id} has no stack copy to compare with"))?;
+            (id, stack)
+        }
+    };
+    let diff = older.diff(&current);
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&StateDiffOutput { against, diff })?
+        );
+        return Ok(());
+    }
+
+    let label = if backup.is_some() {
+        format!("backup {against}")
+    } else {
+        "the previous version".to_string()
+    };
+    if diff.is_empty() {
+        output::info(&format!("No stack changes since {label}"));
+        return Ok(());
+    }
+
+    println!();
+    println!("  {}", format!("Stack changes since {label}").bold());
+    output::hr();
+    for branch in &diff.added {
+        println!("  {} {branch}  {}", "+".green(), "added".green());
+    }
+    for branch in &diff.removed {
+        println!("  {} {branch}  {}", "-".red(), "removed".red());
+    }
+    for change in &diff.reparented {
+        let parent = |p: &Option<String>| p.clone().unwrap_or_else(|| "(root)".to_string());
+        println!(
+            "  {} {}  parent {} → {}",
+            "~".yellow(),
+            change.branch,
+            parent(&change.old),
+            parent(&change.new).cyan()
+        );
+    }
+    for change in &diff.pr_changes {
+        let pr = |n: Option<u64>| n.map_or_else(|| "none".to_string(), |n| format!("#{n}"));
+        println!(
+            "  {} {}  PR {} → {}",
+            "~".yellow(),
+            change.branch,
+            pr(change.old),
+            pr(change.new).cyan()
+        );
+    }
+    output::hr();
+    println!();
+    Ok(())
+}
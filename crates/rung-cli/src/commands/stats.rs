@@ -0,0 +1,136 @@
+//! `rung stats` command - Workflow metrics from the operations log.
+
+use anyhow::Result;
+use chrono::{Local, Utc};
+use rung_core::stats::{ConflictCount, ConflictStats, Stats};
+
+use super::utils::open_repo_and_state;
+use crate::output;
+
+/// Most branches and files listed in the text conflict report.
+const TOP_CONFLICTS: usize = 10;
+
+/// Run the stats command over the last `weeks` calendar weeks, reporting on
+/// sync conflicts alone with `conflicts`.
+pub fn run(json: bool, weeks: u32, conflicts: bool) -> Result<()> {
+    let (_repo, state) = open_repo_and_state()?;
+    let operations = state.load_operations()?;
+    if conflicts {
+        let report = ConflictStats::compute(&operations, weeks, Utc::now());
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        print_conflicts(&report, weeks);
+        return Ok(());
+    }
+    let report = Stats::compute(&operations, weeks, Utc::now());
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    output::essential(&format!(
+        "PRs landed: {} in {weeks} week(s), {:.1} per week",
+        report.merged, report.landed_per_week
+    ));
+    for week in &report.weeks {
+        output::essential(&format!(
+            "  {}  {:>3} {}",
+            week.week_start,
+            week.merged,
+            "█".repeat(week.merged)
+        ));
+    }
+
+    let depth = report
+        .average_stack_depth
+        .map_or_else(|| "-".to_string(), |depth| format!("{depth:.1}"));
+    output::essential(&format!("Average stack depth: {depth}"));
+
+    let time_to_merge = report
+        .average_time_to_merge_secs
+        .map_or_else(|| "-".to_string(), format_duration);
+    output::essential(&format!("Average submit to merge: {time_to_merge}"));
+
+    let per_sync = report
+        .conflicts_per_sync
+        .map_or_else(|| "-".to_string(), |ratio| format!("{ratio:.2}"));
+    output::essential(&format!(
+        "Conflicts per sync: {per_sync} ({} in {} sync(s))",
+        report.conflicts, report.syncs
+    ));
+
+    if report.merged == 0 && report.syncs == 0 {
+        output::info(
+            "Metrics come from `rung log --operations` - merge, submit and sync to fill it",
+        );
+    }
+    Ok(())
+}
+
+fn print_conflicts(report: &ConflictStats, weeks: u32) {
+    output::essential(&format!(
+        "Sync conflicts: {} in {} of {} sync(s) over {weeks} week(s)",
+        report.conflicts,
+        report.conflicted_syncs.len(),
+        report.syncs
+    ));
+    if report.conflicted_syncs.is_empty() {
+        if report.syncs == 0 {
+            output::info("Metrics come from `rung log --operations` - sync to fill it");
+        }
+        return;
+    }
+    for sync in &report.conflicted_syncs {
+        let mut branches: Vec<&str> = sync.conflicted.iter().map(|c| c.branch.as_str()).collect();
+        branches.dedup();
+        output::essential(&format!(
+            "  {}  {:>2} conflict(s)  {}",
+            sync.finished_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            sync.conflicts,
+            branches.join(", ")
+        ));
+    }
+    print_ranked("Branches that conflict most:", &report.branches);
+    print_ranked("Files that conflict most:", &report.files);
+    if report.files.iter().any(|file| file.conflicts > 1) {
+        output::hint(
+            "Files in conflict again and again are worth landing changes to sooner, or splitting out",
+        );
+    }
+}
+
+fn print_ranked(heading: &str, counts: &[ConflictCount]) {
+    if counts.is_empty() {
+        return;
+    }
+    output::essential(heading);
+    let shown = &counts[..counts.len().min(TOP_CONFLICTS)];
+    let width = shown
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for count in shown {
+        output::essential(&format!("  {:<width$}  {:>3}", count.name, count.conflicts));
+    }
+    if counts.len() > shown.len() {
+        output::essential(&format!("  ... and {} more", counts.len() - shown.len()));
+    }
+}
+
+/// Format seconds as the two largest units, e.g. "2d 4h" or "35m".
+fn format_duration(secs: i64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
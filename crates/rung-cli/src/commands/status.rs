@@ -1,148 +1,902 @@
 //! `rung status` command - Display the current stack status.
 
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result, bail};
-use rung_core::{BranchState, State};
-use rung_git::Repository;
+use chrono::{DateTime, Utc};
+use rung_core::ci::{CiCache, CiStatus, CiSummary};
+use rung_core::remote_status::{self, Discrepancy, RemotePrState};
+use rung_core::status_cache::{StatusCache, StatusEntry};
+use rung_core::{BranchState, Config, State, scope, sync};
+use rung_git::{GitHubRemote, Oid, Repository};
+use rung_github::IssueComment;
 use serde::Serialize;
 
-use crate::output;
+use super::submit::stale_stack_comment;
+use super::utils::{
+    check_origin, github_client, github_reader, github_remote, has_no_commits, my_open_prs,
+    ready_green_drafts, remote_pr, tip_time,
+};
+use crate::i18n::tr;
+use crate::{output, timings};
+
+/// How often `--watch` checks branch tips.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often `--watch` checks drafts waiting for green checks.
+const READY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Branches the tree shows at once without `--all`.
+const PAGE_SIZE: usize = 20;
 
 /// Run the status command.
-pub fn run(json: bool, _fetch: bool) -> Result<()> {
+#[allow(clippy::fn_params_excessive_bools, clippy::too_many_lines)]
+pub fn run(
+    json: bool,
+    fetch: bool,
+    urls: bool,
+    watch: bool,
+    against_remote: bool,
+    short: bool,
+    all: bool,
+) -> Result<()> {
     // Open repository
     let repo = Repository::open_current().context("Not inside a git repository")?;
 
-    // Get state manager
-    let workdir = repo.workdir().context("Cannot run in bare repository")?;
-    let state = State::new(workdir)?;
+    // Get state manager (a bare clone has the stack too, just no working tree)
+    let state = State::for_repository(&repo);
 
     // Ensure initialized
     if !state.is_initialized() {
         bail!("Rung not initialized - run `rung init` first");
     }
+    check_origin(&repo, &state)?;
 
     // Get current branch
     let current = repo.current_branch().ok();
 
     // Load stack
     let stack = state.load_stack()?;
+    let config = state.load_config()?;
+    let discrepancies = if against_remote {
+        Some(timings::time("remote comparison", || {
+            remote_discrepancies(&repo, &config, &stack)
+        })?)
+    } else {
+        None
+    };
 
     if stack.is_empty() {
-        if json {
-            println!("{}", serde_json::to_string_pretty(&JsonOutput::empty())?);
-        } else {
-            output::info("No branches in stack yet. Use `rung create <name>` to add one.");
-        }
-        return Ok(());
+        return print_empty(json, urls, short, discrepancies);
     }
 
-    // Compute branch states
+    let now = Utc::now();
+    let remote = github_remote(&repo, &config).ok();
+
+    let refreshed = if fetch {
+        timings::time("GitHub refresh", || {
+            refresh_from_github(&repo, &state, &config, &stack, remote.as_ref(), json)
+        })?
+    } else {
+        Refreshed {
+            ci: state.load_ci_cache(),
+            ..Refreshed::default()
+        }
+    };
+
+    // Compute branch states, reusing cached results for branches whose refs haven't moved
+    let mut status_cache = state.load_status_cache();
+    let names: Vec<&str> = stack.branches.iter().map(|b| b.name.as_str()).collect();
+    status_cache.retain_branches(&names);
+    let loaded_cache = status_cache.clone();
     let mut branches_with_state: Vec<BranchInfo> = vec![];
 
     for branch in &stack.branches {
-        let branch_state = compute_branch_state(&repo, branch, &stack)?;
+        // Only diff branches when the files are reported or mapped onto packages
+        let wants_files = json || !config.packages.is_empty() || !branch.paths.is_empty();
+        let (branch_state, files) = timings::time("branch states", || {
+            cached_branch_state(&repo, &stack, branch, &mut status_cache, wants_files)
+        })?;
+        let ci = repo
+            .branch_commit(&branch.name)
+            .ok()
+            .and_then(|commit| refreshed.ci.entry(&branch.name, &commit.to_string()));
+        let waiting = refreshed.reviews.get(branch.name.as_str());
+        let tip_time = tip_time(&repo, &branch.name);
+
         branches_with_state.push(BranchInfo {
             name: branch.name.to_string(),
             parent: branch.parent.as_ref().map(ToString::to_string),
             state: branch_state,
             pr: branch.pr,
+            author: branch.author.clone(),
+            ci: ci.map(|entry| entry.status),
+            optional_failing: ci.is_some_and(|entry| entry.optional_failing),
+            waiting_on: waiting.map(|w| w.reviewers.clone()).unwrap_or_default(),
+            waiting_days: waiting
+                .and_then(|w| w.since)
+                .map(|since| (now - since).num_days()),
+            pr_url: branch
+                .pr
+                .zip(remote.as_ref())
+                .map(|(number, remote)| format!("{}/pull/{number}", remote.web_url())),
             is_current: current.as_deref() == Some(branch.name.as_str()),
+            stale_days: branch
+                .is_stale(config.general.stale_after_days, now, tip_time)
+                .then(|| branch.idle_days(now, tip_time)),
+            no_commits: branch
+                .parent
+                .as_deref()
+                .is_some_and(|parent| has_no_commits(&repo, &branch.name, parent)),
+            packages: scope::touched_packages(&files, &config.packages)
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+            outside_scope: scope::outside_scope(&files, &branch.paths).len(),
+            stale_comment: refreshed.stale_comments.contains(branch.name.as_str()),
+            files,
         });
     }
 
-    if json {
-        let output = JsonOutput {
+    if status_cache != loaded_cache {
+        state.save_status_cache(&status_cache)?;
+    }
+
+    if short {
+        output::essential(&summary_line(&branches_with_state));
+    } else if urls {
+        print_urls(&branches_with_state, json)?;
+    } else if json {
+        print_json(&JsonOutput {
             branches: branches_with_state,
             current,
-        };
-        println!("{}", serde_json::to_string_pretty(&output)?);
+            remote: discrepancies,
+        })?;
+    } else {
+        print_tree(&branches_with_state, all);
+        print_stale_comments(&branches_with_state);
+        if let Some(discrepancies) = &discrepancies {
+            print_discrepancies(discrepancies);
+        }
+    }
+
+    if watch {
+        return watch_stack(&repo, &state, json);
+    }
+    Ok(())
+}
+
+/// Report an empty stack in the requested format.
+fn print_empty(
+    json: bool,
+    urls: bool,
+    short: bool,
+    discrepancies: Option<Vec<Discrepancy>>,
+) -> Result<()> {
+    if short {
+        output::essential(&summary_line(&[]));
+    } else if json && urls {
+        print_urls(&[], json)?;
+    } else if json {
+        print_json(&JsonOutput {
+            remote: discrepancies,
+            ..JsonOutput::empty()
+        })?;
     } else {
-        print_tree(&branches_with_state);
+        output::info(&tr!("status-empty"));
+        if let Some(discrepancies) = &discrepancies {
+            print_discrepancies(discrepancies);
+        }
+    }
+    Ok(())
+}
+
+/// Watch stack branches until Ctrl-C, reporting each time one moves and
+/// leaves its descendants needing a restack.
+///
+/// In JSON mode each report is printed as one line, and with `--porcelain` as
+/// a `changed <branch> <descendant>...` record.
+fn watch_stack(repo: &Repository, state: &State, json: bool) -> Result<()> {
+    crate::signal::install();
+    if !json {
+        output::info(&tr!("status-watching"));
     }
 
+    let mut tips = branch_tips(repo, &state.load_stack()?);
+    let mut ready_checked: Option<Instant> = None;
+    while !rung_core::interrupt::is_requested() {
+        if ready_checked.is_none_or(|at| at.elapsed() >= READY_INTERVAL) {
+            ready_checked = Some(Instant::now());
+            report_ready(repo, state, json)?;
+        }
+        std::thread::sleep(WATCH_INTERVAL);
+        let stack = state.load_stack()?;
+        let new_tips = branch_tips(repo, &stack);
+
+        for (name, tip) in &new_tips {
+            if tips.get(name) == Some(tip) {
+                continue;
+            }
+            let invalidated = stack
+                .children_of(name)
+                .iter()
+                .any(|child| sync::branch_state(repo, &stack, child).is_ok_and(|s| s.needs_sync()));
+            if !invalidated {
+                continue;
+            }
+            let descendants: Vec<String> = stack
+                .descendants_of(name)
+                .iter()
+                .map(|b| b.name.to_string())
+                .collect();
+
+            if output::is_porcelain() {
+                let mut fields = vec![name.as_str()];
+                fields.extend(descendants.iter().map(String::as_str));
+                output::record("changed", &fields);
+            } else if json {
+                let event = WatchEvent {
+                    branch: name,
+                    needs_restack: &descendants,
+                };
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                output::warn(&tr!(
+                    "status-watch-changed",
+                    branch = name,
+                    descendants = descendants.join(", ")
+                ));
+            }
+        }
+        tips = new_tips;
+    }
     Ok(())
 }
 
-/// Compute the sync state of a branch relative to its parent.
-fn compute_branch_state(
+/// Mark ready the drafts submitted with `--draft-until-green` whose checks
+/// now pass, reporting each. Failing to check is a warning; watching goes on.
+fn report_ready(repo: &Repository, state: &State, json: bool) -> Result<()> {
+    let ready = match ready_green_drafts(repo, state, json) {
+        Ok(ready) => ready,
+        Err(e) => {
+            if !json {
+                output::warn(&tr!("sync-ready-failed", error = e));
+            }
+            return Ok(());
+        }
+    };
+    for r in &ready {
+        if output::is_porcelain() {
+            output::record("ready", &[&r.branch, &r.pr_number.to_string()]);
+        } else if json {
+            let event = ReadyEvent {
+                branch: &r.branch,
+                ready: r.pr_number,
+            };
+            println!("{}", serde_json::to_string(&event)?);
+        } else {
+            output::success(&tr!("ready-marked", pr = r.pr_number, branch = r.branch));
+        }
+    }
+    Ok(())
+}
+
+/// Current tip of every stack branch that exists locally.
+fn branch_tips(repo: &Repository, stack: &rung_core::Stack) -> HashMap<String, Oid> {
+    stack
+        .branches
+        .iter()
+        .filter_map(|b| Some((b.name.to_string(), repo.branch_commit(&b.name).ok()?)))
+        .collect()
+}
+
+/// Reviews a branch's PR is still waiting for.
+struct Waiting {
+    /// Requested reviewers who haven't reviewed, teams as `owner/slug`.
+    reviewers: Vec<String>,
+    /// When the PR was opened.
+    since: Option<DateTime<Utc>>,
+}
+
+/// What `--fetch` found on GitHub.
+#[derive(Default)]
+struct Refreshed {
+    /// Check results, updated with every PR looked at.
+    ci: CiCache,
+    /// Reviews each open PR is waiting for, by branch.
+    reviews: HashMap<String, Waiting>,
+    /// Branches whose PR's stack comment no longer matches the stack.
+    stale_comments: HashSet<String>,
+}
+
+/// Fetch check runs for every branch with a PR and update the CI cache,
+/// judging each by the checks its base branch requires. Also finds the
+/// reviews each open PR is waiting for, and the PRs whose stack comment is
+/// out of date.
+///
+/// Failures are reported and skipped; the cache keeps whatever it had.
+fn refresh_from_github(
     repo: &Repository,
-    branch: &rung_core::stack::StackBranch,
+    state: &State,
+    config: &Config,
     stack: &rung_core::Stack,
-) -> Result<BranchState> {
-    let Some(parent_name) = &branch.parent else {
-        // Root branch, always synced
-        return Ok(BranchState::Synced);
+    remote: Option<&GitHubRemote>,
+    json: bool,
+) -> Result<Refreshed> {
+    let mut cache = state.load_ci_cache();
+    let mut reviews = HashMap::new();
+    let mut stale_comments = HashSet::new();
+    let Some(remote) = remote else {
+        if !json {
+            output::warn(&tr!("status-no-remote"));
+        }
+        return Ok(Refreshed {
+            ci: cache,
+            ..Refreshed::default()
+        });
     };
+    let client = github_reader(remote, config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let mut required_by_base: HashMap<String, Vec<String>> = HashMap::new();
 
-    // Check if parent exists in repo
-    if !repo.branch_exists(parent_name) {
-        // Check if parent is in stack (might be deleted)
-        if stack.find_branch(parent_name).is_some() {
-            return Ok(BranchState::Detached);
+    for branch in &stack.branches {
+        let Some(pr) = branch.pr else {
+            continue;
+        };
+        if client.is_anonymous() && client.budget_spent() {
+            if !json {
+                output::warn(&tr!("github-budget-spent"));
+            }
+            break;
+        }
+        match rt.block_on(client.get_review_requests(&remote.owner, &remote.repo, pr)) {
+            Ok(requests) if !requests.is_empty() => {
+                let teams = requests
+                    .teams
+                    .iter()
+                    .map(|slug| format!("{}/{slug}", remote.owner));
+                let waiting = Waiting {
+                    reviewers: requests.users.iter().cloned().chain(teams).collect(),
+                    since: requests
+                        .opened_at
+                        .as_deref()
+                        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                        .map(|at| at.with_timezone(&Utc)),
+                };
+                reviews.insert(branch.name.to_string(), waiting);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                if !json {
+                    output::warn(&tr!(
+                        "status-reviews-failed",
+                        branch = branch.name,
+                        error = e
+                    ));
+                }
+            }
+        }
+
+        // Someone else's PR (see `rung rebase --onto-pr`) has no comment of ours
+        if !branch.frozen {
+            let comments = rt.block_on(client.list_pr_comments(&remote.owner, &remote.repo, pr));
+            if comments_stale(stack, branch, comments, json) {
+                stale_comments.insert(branch.name.to_string());
+            }
+        }
+
+        let commit = repo.branch_commit(&branch.name)?.to_string();
+        let base = stack
+            .remote_name_of(branch.parent.as_deref().unwrap_or("main"))
+            .to_string();
+        let required = required_by_base.entry(base).or_insert_with_key(|base| {
+            // Unreadable protection leaves every check counting
+            rt.block_on(client.get_required_checks(&remote.owner, &remote.repo, base))
+                .unwrap_or_default()
+        });
+        match rt.block_on(client.get_check_runs(&remote.owner, &remote.repo, &commit)) {
+            Ok(runs) => {
+                let checks = runs.iter().map(|run| {
+                    let status = match run.status {
+                        status if status.is_pending() => CiStatus::Pending,
+                        status if status.is_success() => CiStatus::Passing,
+                        _ => CiStatus::Failing,
+                    };
+                    (run.name.as_str(), status)
+                });
+                if let Some(summary) = CiSummary::from_checks(checks, required) {
+                    cache.record_summary(branch.name.as_str(), commit, summary);
+                }
+            }
+            Err(e) => {
+                if !json {
+                    output::warn(&tr!(
+                        "status-checks-failed",
+                        branch = branch.name,
+                        error = e
+                    ));
+                }
+            }
+        }
+    }
+
+    let names: Vec<&str> = stack.branches.iter().map(|b| b.name.as_str()).collect();
+    cache.retain_branches(&names);
+    state.save_ci_cache(&cache)?;
+    Ok(Refreshed {
+        ci: cache,
+        reviews,
+        stale_comments,
+    })
+}
+
+/// Whether `comments`, as listed from `branch`'s PR, hold a stale stack
+/// comment. Failing to list them is reported, and counts as not stale.
+fn comments_stale(
+    stack: &rung_core::Stack,
+    branch: &rung_core::stack::StackBranch,
+    comments: rung_github::Result<Vec<IssueComment>>,
+    json: bool,
+) -> bool {
+    match comments {
+        Ok(comments) => stale_stack_comment(stack, &branch.name, &comments),
+        Err(e) => {
+            if !json {
+                output::warn(&tr!(
+                    "status-comments-failed",
+                    branch = branch.name,
+                    error = e
+                ));
+            }
+            false
         }
-        // Parent is external (like main), check if it exists
-        if !repo.branch_exists(parent_name) {
-            return Ok(BranchState::Detached);
+    }
+}
+
+/// Compare the stack against your pull requests on GitHub.
+fn remote_discrepancies(
+    repo: &Repository,
+    config: &Config,
+    stack: &rung_core::Stack,
+) -> Result<Vec<Discrepancy>> {
+    let remote = github_remote(repo, config)?;
+    let client = github_client(&remote, config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+
+    let open = rt.block_on(my_open_prs(&client, &remote))?;
+
+    let mut tracked = vec![];
+    for number in stack.branches.iter().filter_map(|b| b.pr) {
+        match rt.block_on(client.get_pr(&remote.owner, &remote.repo, number)) {
+            Ok(pr) => tracked.push(remote_pr(&pr)),
+            // Reported as missing
+            Err(rung_github::Error::ApiError { status: 404, .. }) => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to fetch PR #{number}")),
         }
     }
 
-    // Get commits
-    let branch_commit = repo.branch_commit(&branch.name)?;
-    let parent_commit = repo.branch_commit(parent_name)?;
+    Ok(remote_status::compare(stack, &open, &tracked))
+}
+
+/// A branch's state and (if `wants_files`) the files it changes, from
+/// `cache` when its refs haven't moved since. Updates `cache` otherwise.
+fn cached_branch_state(
+    repo: &Repository,
+    stack: &rung_core::Stack,
+    branch: &rung_core::stack::StackBranch,
+    cache: &mut StatusCache,
+    wants_files: bool,
+) -> Result<(BranchState, Vec<String>)> {
+    let key = StatusCache::key(repo, stack, branch);
+    let cached = key
+        .as_deref()
+        .and_then(|key| cache.get(&branch.name, key))
+        .cloned();
+    let state = match &cached {
+        Some(entry) => entry.state.clone(),
+        None => sync::branch_state(repo, stack, branch)?,
+    };
+    let cached_files = cached.and_then(|entry| entry.files);
+    let files = match &branch.parent {
+        Some(parent) if wants_files => cached_files
+            .clone()
+            .unwrap_or_else(|| repo.changed_files(&branch.name, parent).unwrap_or_default()),
+        _ => vec![],
+    };
+
+    if let Some(key) = key {
+        let entry = StatusEntry {
+            key,
+            state: state.clone(),
+            files: if wants_files && branch.parent.is_some() {
+                Some(files.clone())
+            } else {
+                cached_files
+            },
+        };
+        cache.insert(branch.name.as_str(), entry);
+    }
+    Ok((state, files))
+}
+
+/// Warn about PRs whose stack comment is out of date, and how to fix them.
+fn print_stale_comments(branches: &[BranchInfo]) {
+    let stale: Vec<_> = branches.iter().filter(|b| b.stale_comment).collect();
+    for branch in &stale {
+        output::warn(&tr!(
+            "status-stale-comment",
+            pr = branch.pr.unwrap_or_default(),
+            branch = branch.name.as_str()
+        ));
+    }
+    if !stale.is_empty() {
+        output::info(&tr!("status-stale-comment-fix"));
+    }
+}
+
+/// Print `branch<TAB>url` pairs (or a JSON list) for branches with a PR.
+fn print_urls(branches: &[BranchInfo], json: bool) -> Result<()> {
+    let with_urls = branches
+        .iter()
+        .filter_map(|b| Some((b, b.pr?, b.pr_url.as_ref()?)));
 
-    // Find merge base
-    let merge_base = repo.merge_base(branch_commit, parent_commit)?;
+    if output::is_porcelain() {
+        for (branch, pr, url) in with_urls {
+            output::record("url", &[&branch.name, &pr.to_string(), url]);
+        }
+        return Ok(());
+    }
+    if json {
+        let entries: Vec<PrUrlEntry<'_>> = with_urls
+            .map(|(b, pr, url)| PrUrlEntry {
+                branch: &b.name,
+                pr,
+                url,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
 
-    // If merge base is the parent commit, we're synced
-    if merge_base == parent_commit {
-        return Ok(BranchState::Synced);
+    for (branch, _, url) in with_urls {
+        output::essential(&format!("{}\t{url}", branch.name));
     }
+    Ok(())
+}
 
-    // Count how many commits behind
-    let commits_behind = repo.count_commits_between(merge_base, parent_commit)?;
+/// Print the status as JSON, or as porcelain records with `--porcelain`:
+///
+/// ```text
+/// branch <name> <parent> <state> <commits-behind> <pr> <ci>
+/// current <name>
+/// remote <kind> <pr> <branch> [<details>...]
+/// ```
+fn print_json(status: &JsonOutput) -> Result<()> {
+    if !output::is_porcelain() {
+        println!("{}", serde_json::to_string_pretty(status)?);
+        return Ok(());
+    }
+    for branch in &status.branches {
+        let (state, behind) = match &branch.state {
+            BranchState::Synced => ("synced", 0),
+            BranchState::Diverged { commits_behind } => ("diverged", *commits_behind),
+            BranchState::NeedsRestack => ("needs_restack", 0),
+            BranchState::Conflict { .. } => ("conflict", 0),
+            BranchState::Detached => ("detached", 0),
+        };
+        output::record(
+            "branch",
+            &[
+                &branch.name,
+                branch.parent.as_deref().unwrap_or_default(),
+                state,
+                &behind.to_string(),
+                &branch.pr.map(|pr| pr.to_string()).unwrap_or_default(),
+                &branch.ci.map(|ci| output::token(&ci)).unwrap_or_default(),
+            ],
+        );
+    }
+    if let Some(current) = &status.current {
+        output::record("current", &[current]);
+    }
+    for discrepancy in status.remote.iter().flatten() {
+        match discrepancy {
+            Discrepancy::Untracked { pr, head, base } => {
+                output::record("remote", &["untracked", &pr.to_string(), head, base]);
+            }
+            Discrepancy::NotOpen { branch, pr, state } => output::record(
+                "remote",
+                &["not_open", &pr.to_string(), branch, &output::token(state)],
+            ),
+            Discrepancy::BaseMismatch {
+                branch,
+                pr,
+                expected,
+                actual,
+            } => output::record(
+                "remote",
+                &["base_mismatch", &pr.to_string(), branch, expected, actual],
+            ),
+            Discrepancy::Missing { branch, pr } => {
+                output::record("remote", &["missing", &pr.to_string(), branch]);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One-line summary of the stack for CI job summaries and status bars, e.g.
+/// `stack: 4 branches, 1 behind, 2 PRs open, CI: 1 failing`.
+fn summary_line(branches: &[BranchInfo]) -> String {
+    let count = |pred: fn(&BranchInfo) -> bool| branches.iter().filter(|b| pred(b)).count();
+    let noun = |n: usize, one: &str, many: &str| format!("{n} {}", if n == 1 { one } else { many });
 
-    Ok(BranchState::Diverged { commits_behind })
+    let mut parts = vec![noun(branches.len(), "branch", "branches")];
+    let behind = count(|b| b.state.needs_sync());
+    if behind > 0 {
+        parts.push(format!("{behind} behind"));
+    }
+    let conflicts = count(|b| b.state.has_conflicts());
+    if conflicts > 0 {
+        parts.push(format!("{conflicts} conflicted"));
+    }
+    let prs = count(|b| b.pr.is_some());
+    if prs > 0 {
+        parts.push(format!("{} open", noun(prs, "PR", "PRs")));
+    }
+
+    let failing = count(|b| b.ci == Some(CiStatus::Failing));
+    let pending = count(|b| b.ci == Some(CiStatus::Pending));
+    if failing > 0 {
+        parts.push(format!("CI: {failing} failing"));
+    } else if pending > 0 {
+        parts.push(format!("CI: {pending} pending"));
+    } else if branches.iter().any(|b| b.ci.is_some()) {
+        parts.push("CI: passing".to_string());
+    }
+    format!("stack: {}", parts.join(", "))
 }
 
-/// Print a tree view of the stack.
-fn print_tree(branches: &[BranchInfo]) {
+/// Print a tree view of the stack. Unless `all` is set, long stacks are cut
+/// down to a page of branches around the current one.
+fn print_tree(branches: &[BranchInfo], all: bool) {
     println!();
-    println!("  {}", "Stack".bold());
+    println!("  {}", tr!("status-heading").bold());
     output::hr();
 
-    for branch in branches {
-        let state_icon = output::state_indicator(&branch.state);
-        let name = output::branch_name(&branch.name, branch.is_current);
-        let pr = output::pr_ref(branch.pr);
-
-        let parent_info = branch
-            .parent
-            .as_ref()
-            .map(|p| format!(" ← {}", p.dimmed()))
-            .unwrap_or_default();
+    let page = if all {
+        0..branches.len()
+    } else {
+        page_around(branches.len(), branches.iter().position(|b| b.is_current))
+    };
+    let noun = |n: usize| {
+        if n == 1 {
+            tr!("noun-branch")
+        } else {
+            tr!("noun-branches")
+        }
+    };
+    let more = |line: String| {
+        if output::is_accessible() {
+            println!("  {line}");
+        } else {
+            println!("  {}", format!("⋮ {line}").dimmed());
+        }
+    };
+    if page.start > 0 {
+        more(tr!(
+            "status-more-above",
+            count = page.start,
+            noun = noun(page.start)
+        ));
+    }
 
-        println!("  {state_icon} {name} {pr}{parent_info}");
+    for branch in &branches[page.clone()] {
+        let row = if output::is_accessible() {
+            accessible_row(branch)
+        } else {
+            tree_row(branch)
+        };
+        println!("  {row}");
+    }
+    if page.end < branches.len() {
+        let count = branches.len() - page.end;
+        more(tr!("status-more-below", count = count, noun = noun(count)));
     }
 
     output::hr();
     println!();
+    if output::is_accessible() {
+        return;
+    }
 
     // Legend
+    let theme = output::theme();
     println!(
-        "  {} synced  {} needs sync  {} conflict",
-        "●".green(),
-        "●".yellow(),
-        "●".red()
+        "  {} {}  {} {}  {} {}",
+        theme.synced.glyph(),
+        tr!("status-legend-synced"),
+        theme.diverged.glyph(),
+        tr!("status-legend-needs-sync"),
+        theme.conflict.glyph(),
+        tr!("status-legend-conflict")
     );
     println!();
 }
 
+/// A branch's row of the tree.
+fn tree_row(branch: &BranchInfo) -> String {
+    let state_icon = output::state_indicator(&branch.state);
+    let name = output::branch_name(&branch.name, branch.is_current);
+    let pr = output::pr_ref(branch.pr);
+    let ci = output::ci_indicator(branch.ci);
+    let ci_info = match (ci.is_empty(), branch.optional_failing) {
+        (true, _) => String::new(),
+        (false, false) => format!(" {ci}"),
+        (false, true) => format!(" {ci} {}", tr!("status-optional-failing").yellow()),
+    };
+
+    let waiting_info = waiting(branch)
+        .map(|waiting| format!(" {}", waiting.cyan()))
+        .unwrap_or_default();
+
+    let parent_info = branch
+        .parent
+        .as_ref()
+        .map(|p| format!(" ← {}", p.dimmed()))
+        .unwrap_or_default();
+
+    let stale_info = branch
+        .stale_days
+        .map(|days| format!(" {}", tr!("status-stale", days = days).yellow()))
+        .unwrap_or_default();
+
+    let empty_info = if branch.no_commits {
+        format!(" {}", tr!("status-no-commits").yellow())
+    } else {
+        String::new()
+    };
+
+    let package_info = if branch.packages.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", format!("[{}]", branch.packages.join(", ")).dimmed())
+    };
+
+    let scope_info = if branch.outside_scope > 0 {
+        format!(
+            " {}",
+            tr!("status-outside-scope", count = branch.outside_scope).yellow()
+        )
+    } else {
+        String::new()
+    };
+
+    let url_info = branch
+        .pr_url
+        .as_ref()
+        .map(|url| format!("  {}", url.dimmed()))
+        .unwrap_or_default();
+
+    format!(
+        "{state_icon} {name} {pr}{ci_info}{waiting_info}{parent_info}{package_info}{stale_info}{empty_info}{scope_info}{url_info}"
+    )
+}
+
+/// Who a branch's PR is waiting on for review, and for how long.
+fn waiting(branch: &BranchInfo) -> Option<String> {
+    if branch.waiting_on.is_empty() {
+        return None;
+    }
+    let reviewers = branch.waiting_on.join(", ");
+    Some(branch.waiting_days.map_or_else(
+        || tr!("status-waiting", reviewers = reviewers),
+        |days| tr!("status-waiting-for", reviewers = reviewers, days = days),
+    ))
+}
+
+/// A branch's row of the tree in words, one comma-separated field per fact,
+/// for screen readers.
+fn accessible_row(branch: &BranchInfo) -> String {
+    let mut fields = vec![
+        output::branch_name(&branch.name, branch.is_current),
+        output::state_indicator(&branch.state),
+        output::pr_ref(branch.pr),
+        output::ci_indicator(branch.ci),
+    ];
+    if branch.ci.is_some() && branch.optional_failing {
+        fields.push(tr!("status-optional-failing"));
+    }
+    fields.extend(waiting(branch));
+    if let Some(parent) = &branch.parent {
+        fields.push(tr!("a11y-parent", branch = parent));
+    }
+    if !branch.packages.is_empty() {
+        fields.push(tr!("a11y-packages", packages = branch.packages.join(", ")));
+    }
+    if let Some(days) = branch.stale_days {
+        fields.push(tr!("status-stale", days = days));
+    }
+    if branch.no_commits {
+        fields.push(tr!("status-no-commits"));
+    }
+    if branch.outside_scope > 0 {
+        fields.push(tr!("status-outside-scope", count = branch.outside_scope));
+    }
+    fields.extend(branch.pr_url.clone());
+    fields.retain(|field| !field.is_empty());
+    fields.join(", ")
+}
+
+/// The rows of a `len`-branch list to show: at most [`PAGE_SIZE`], centred
+/// on the `current` row where possible.
+fn page_around(len: usize, current: Option<usize>) -> std::ops::Range<usize> {
+    if len <= PAGE_SIZE {
+        return 0..len;
+    }
+    let start = current
+        .unwrap_or(0)
+        .saturating_sub(PAGE_SIZE / 2)
+        .min(len - PAGE_SIZE);
+    start..start + PAGE_SIZE
+}
+
+/// Print where the stack and GitHub disagree.
+fn print_discrepancies(discrepancies: &[Discrepancy]) {
+    if discrepancies.is_empty() {
+        output::success(&tr!("status-github-matches"));
+        return;
+    }
+
+    println!("  {}", tr!("status-github-heading").bold());
+    output::hr();
+    for discrepancy in discrepancies {
+        let text = match discrepancy {
+            Discrepancy::Untracked { pr, head, base } => {
+                tr!("status-github-untracked", pr = pr, head = head, base = base)
+            }
+            Discrepancy::NotOpen { branch, pr, state } => match state {
+                RemotePrState::Merged => tr!("status-github-merged", branch = branch, pr = pr),
+                _ => tr!("status-github-closed", branch = branch, pr = pr),
+            },
+            Discrepancy::BaseMismatch {
+                branch,
+                pr,
+                expected,
+                actual,
+            } => tr!(
+                "status-github-base",
+                branch = branch,
+                pr = pr,
+                actual = actual,
+                expected = expected
+            ),
+            Discrepancy::Missing { branch, pr } => {
+                tr!("status-github-missing", branch = branch, pr = pr)
+            }
+        };
+        println!("  {} {text}", output::warning_icon("!"));
+    }
+    output::hr();
+    if discrepancies
+        .iter()
+        .any(|d| matches!(d, Discrepancy::Untracked { .. }))
+    {
+        output::info(&tr!("status-github-adopt"));
+    }
+    println!();
+}
+
 use colored::Colorize;
 
 #[derive(Debug, Serialize)]
 struct JsonOutput {
     branches: Vec<BranchInfo>,
     current: Option<String>,
+    /// Where the stack and GitHub disagree, with `--remote`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<Vec<Discrepancy>>,
 }
 
 impl JsonOutput {
@@ -150,16 +904,70 @@ impl JsonOutput {
         Self {
             branches: vec![],
             current: None,
+            remote: None,
         }
     }
 }
 
 #[derive(Debug, Serialize)]
+struct WatchEvent<'a> {
+    branch: &'a str,
+    needs_restack: &'a [String],
+}
+
+/// JSON line for a draft `status --watch` marked ready for review.
+#[derive(Debug, Serialize)]
+struct ReadyEvent<'a> {
+    branch: &'a str,
+    ready: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct PrUrlEntry<'a> {
+    branch: &'a str,
+    pr: u64,
+    url: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(clippy::struct_excessive_bools)] // Independent per-branch flags
 struct BranchInfo {
     name: String,
     parent: Option<String>,
     state: BranchState,
     pr: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ci: Option<CiStatus>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    optional_failing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    waiting_on: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    waiting_days: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr_url: Option<String>,
     #[serde(skip_serializing_if = "std::ops::Not::not")]
     is_current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stale_days: Option<i64>,
+    /// Whether the branch has no commits its parent doesn't, so `rung
+    /// submit` skips it.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    no_commits: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    packages: Vec<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    outside_scope: usize,
+    /// Whether the PR's stack comment no longer matches the stack (only
+    /// checked with `--fetch`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stale_comment: bool,
+    files: Vec<String>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde skip_serializing_if passes by reference
+const fn is_zero(n: &usize) -> bool {
+    *n == 0
 }
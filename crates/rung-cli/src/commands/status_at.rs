@@ -0,0 +1,226 @@
+//! `rung status --at` - Show the stack as a sync backup recorded it.
+//!
+//! `rung sync`, `rung edit` and `rung reword` back up the stack and the tips
+//! of the branches they're about to rewrite. Rendering a backup next to where
+//! the branches are now shows what the operation changed, before deciding
+//! whether to `rung undo` it.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use colored::Colorize;
+use rung_core::State;
+use serde::Serialize;
+
+use super::utils::open_repo_and_state_read_only;
+use crate::i18n::tr;
+use crate::output;
+
+/// How a branch's tip compares with the one the backup recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TipChange {
+    Unchanged,
+    Moved,
+    Deleted,
+}
+
+/// A branch as the backup recorded it.
+#[derive(Debug, Serialize)]
+struct BranchAt {
+    name: String,
+    parent: Option<String>,
+    pr: Option<u64>,
+    /// Tip recorded in the backup, for the branches it was taken to restore.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    /// Tip now, if the branch still exists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    now: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    change: Option<TipChange>,
+}
+
+/// JSON output for `rung status --at`.
+#[derive(Debug, Serialize)]
+struct StatusAtOutput {
+    backup: String,
+    taken_at: DateTime<Utc>,
+    branches: Vec<BranchAt>,
+    /// Branches in the stack now that weren't then.
+    added: Vec<String>,
+}
+
+/// Run `rung status --at`, showing the backup `at` names: a backup ID (or
+/// the start of one), or a time, which picks the newest backup taken by then.
+pub fn run(json: bool, at: &str) -> Result<()> {
+    let (repo, state) = open_repo_and_state_read_only()?;
+    let backup = resolve_backup(&state, at)?;
+    let stack = state
+        .load_backup_stack(&backup)
+        .with_context(|| format!("Backup {backup} has no copy of the stack"))?;
+    let tips = state.load_backup(&backup)?;
+    let current = state.load_stack()?;
+
+    let branches: Vec<BranchAt> = stack
+        .branches
+        .iter()
+        .map(|branch| {
+            let commit = tips
+                .iter()
+                .find(|(name, _)| *name == branch.name.as_str())
+                .map(|(_, sha)| sha.clone());
+            let now = repo
+                .branch_commit(&branch.name)
+                .ok()
+                .map(|oid| oid.to_string());
+            let change = commit.as_ref().map(|commit| match &now {
+                None => TipChange::Deleted,
+                Some(now) if now == commit => TipChange::Unchanged,
+                Some(_) => TipChange::Moved,
+            });
+            BranchAt {
+                name: branch.name.to_string(),
+                parent: branch.parent.as_ref().map(ToString::to_string),
+                pr: branch.pr,
+                commit,
+                now,
+                change,
+            }
+        })
+        .collect();
+    let added = current
+        .branches
+        .iter()
+        .filter(|b| stack.find_branch(&b.name).is_none())
+        .map(|b| b.name.to_string())
+        .collect();
+    let taken_at = backup
+        .parse::<i64>()
+        .ok()
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .unwrap_or_default();
+    let out = StatusAtOutput {
+        backup,
+        taken_at,
+        branches,
+        added,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+    print_stack(&out);
+    if state
+        .latest_backup()
+        .is_ok_and(|latest| latest == out.backup)
+        && out
+            .branches
+            .iter()
+            .any(|b| b.change == Some(TipChange::Moved))
+    {
+        output::hint(&tr!("status-at-undo-hint"));
+    }
+    Ok(())
+}
+
+/// The backup `at` names. A time counts to the end of the minute or day it
+/// gives, so one copied from `rung log --operations` finds the backup that
+/// operation took.
+fn resolve_backup(state: &State, at: &str) -> Result<String> {
+    let ids = state.backup_ids();
+    if !at.is_empty() && at.bytes().all(|b| b.is_ascii_digit()) {
+        let matching: Vec<&String> = ids.iter().filter(|id| id.starts_with(at)).collect();
+        match matching.as_slice() {
+            [id] => return Ok((*id).clone()),
+            [] => {}
+            _ => bail!(
+                "'{at}' matches {} backups - give more digits",
+                matching.len()
+            ),
+        }
+    }
+    let time = parse_time(at).with_context(|| {
+        format!("'{at}' is neither a backup ID nor a time like '2026-10-15 14:03'")
+    })?;
+    state
+        .backup_at(time)
+        .with_context(|| format!("No backup was taken by {at}"))
+}
+
+/// Parse an RFC 3339 time, or a local `YYYY-MM-DD[ HH:MM[:SS]]`, as the
+/// last second it covers.
+fn parse_time(at: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(at) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let local = |naive: NaiveDateTime| {
+        Local
+            .from_local_datetime(&naive)
+            .latest()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    for (format, rest_of_it) in [("%Y-%m-%d %H:%M:%S", 0), ("%Y-%m-%d %H:%M", 59)] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(at, format) {
+            return local(naive).map(|time| time + Duration::seconds(rest_of_it));
+        }
+    }
+    NaiveDate::parse_from_str(at, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .and_then(local)
+}
+
+fn print_stack(out: &StatusAtOutput) {
+    let taken_at = out
+        .taken_at
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S");
+    println!();
+    println!(
+        "  {}",
+        tr!(
+            "status-at-heading",
+            backup = out.backup.as_str(),
+            time = taken_at.to_string()
+        )
+        .bold()
+    );
+    output::hr();
+    let short = |sha: &str| sha[..7.min(sha.len())].to_string();
+    for branch in &out.branches {
+        let parent = branch
+            .parent
+            .as_ref()
+            .map(|p| format!(" ← {}", p.dimmed()))
+            .unwrap_or_default();
+        let tip = match (&branch.commit, branch.change, &branch.now) {
+            (Some(commit), Some(TipChange::Moved), Some(now)) => format!(
+                "  {} {}",
+                short(commit),
+                tr!("status-at-moved", commit = short(now)).yellow()
+            ),
+            (Some(commit), Some(TipChange::Deleted), _) => {
+                format!("  {} {}", short(commit), tr!("status-at-deleted").red())
+            }
+            (Some(commit), _, _) => {
+                format!(
+                    "  {} {}",
+                    short(commit),
+                    tr!("status-at-unchanged").dimmed()
+                )
+            }
+            (None, _, _) => String::new(),
+        };
+        println!(
+            "  {} {}{parent}{tip}",
+            output::branch_name(&branch.name, false),
+            output::pr_ref(branch.pr)
+        );
+    }
+    output::hr();
+    if !out.added.is_empty() {
+        output::info(&tr!("status-at-added", branches = out.added.join(", ")));
+    }
+    println!();
+}
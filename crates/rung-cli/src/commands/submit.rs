@@ -1,16 +1,33 @@
 //! `rung submit` command - Push branches and create/update PRs.
 
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 
 use anyhow::{Context, Result, bail};
-use rung_core::{State, stack::StackBranch};
-use rung_git::Repository;
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream};
+use rung_core::config::{LabelConfig, PackageConfig, PolicyConfig, PrConfig};
+use rung_core::operations::{Operation, OperationKind, SubmittedPr};
+use rung_core::ticket::TicketMatcher;
+use rung_core::{
+    BranchName, State, conventional, pr_body, scope,
+    stack::{Stack, StackBranch, StackIndex},
+};
+use rung_git::{GitHubRemote, Repository};
 use rung_github::{
-    Auth, CreateComment, CreatePullRequest, GitHubClient, UpdateComment, UpdatePullRequest,
+    AddLabels, CreateComment, CreatePullRequest, GitHubClient, IssueComment, Milestone,
+    PullRequest, PullRequestState, RequestReviewers, UpdateComment, UpdatePullRequest,
 };
 use serde::Serialize;
 
-use crate::output;
+use super::submit_attach;
+use super::utils::{
+    check_origin, enforce_policy, github_client, github_remote, has_no_commits, load_config,
+    mapped_base, policy_violations, push, pushed_by_others,
+};
+use super::workspace::related_prs;
+use crate::i18n::tr;
+use crate::{output, prompt, timings};
 
 /// A planned action for a single branch.
 #[derive(Debug)]
@@ -21,6 +38,8 @@ enum PlannedBranchAction {
         pr_number: u64,
         pr_url: String,
         base: String,
+        /// Whether the PR was closed without merging, and is reopened first.
+        reopen: bool,
     },
     /// Create a new PR.
     Create {
@@ -29,13 +48,25 @@ enum PlannedBranchAction {
         body: String,
         base: String,
         draft: bool,
+        labels: Vec<String>,
+        /// Users and `org/team`s asked to review the new PR.
+        reviewers: Vec<String>,
     },
 }
 
 /// The complete submit plan describing what will happen.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct SubmitPlan {
     actions: Vec<PlannedBranchAction>,
+    /// Milestone new PRs are put in.
+    milestone: Option<Milestone>,
+    /// Project new PRs are added to.
+    project: Option<u64>,
+    /// Whether new PRs are marked ready for review once their checks pass.
+    ready_when_green: bool,
+    /// Branches without a PR left out for having no commits of their own,
+    /// each with its parent.
+    skipped: Vec<(String, String)>,
 }
 
 impl SubmitPlan {
@@ -52,6 +83,17 @@ impl SubmitPlan {
             .filter(|a| matches!(a, PlannedBranchAction::Update { .. }))
             .count()
     }
+
+    /// Branches to push, in order.
+    fn branches(&self) -> Vec<&str> {
+        self.actions
+            .iter()
+            .map(|a| match a {
+                PlannedBranchAction::Update { branch, .. }
+                | PlannedBranchAction::Create { branch, .. } => branch.as_str(),
+            })
+            .collect()
+    }
 }
 
 /// JSON output for submit command.
@@ -61,6 +103,9 @@ struct SubmitOutput {
     prs_updated: usize,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     branches: Vec<BranchSubmitInfo>,
+    /// Branches left out for having no commits of their own.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<String>,
     dry_run: bool,
 }
 
@@ -83,6 +128,8 @@ struct PlannedBranchInfo {
     pr_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     target_base: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
     action: SubmitAction,
 }
 
@@ -94,13 +141,48 @@ enum SubmitAction {
 }
 
 /// Configuration options for the submit command (planning phase).
-struct SubmitConfig<'a> {
+struct SubmitConfig {
     /// Create PRs as drafts.
     draft: bool,
-    /// Custom title for the current branch's PR.
-    custom_title: Option<&'a str>,
-    /// Current branch name (for custom title matching).
-    current_branch: Option<String>,
+    /// Mark new PRs ready for review once their checks pass.
+    ready_when_green: bool,
+    /// Custom titles for new PRs, by branch.
+    titles: HashMap<String, String>,
+    /// Custom bodies for new PRs, by branch.
+    bodies: HashMap<String, String>,
+    /// Title of the milestone new PRs are put in.
+    milestone: Option<String>,
+    /// Number of the project new PRs are added to.
+    project: Option<u64>,
+    /// Rules for labelling new PRs from commit messages.
+    labels: LabelConfig,
+    /// PR description settings.
+    pr: PrConfig,
+    /// Organizational rules, for the reviewers they require.
+    policy: PolicyConfig,
+    /// Ticket detection, if configured.
+    tickets: Option<TicketMatcher>,
+    /// Monorepo packages, for path-based labels.
+    packages: Vec<PackageConfig>,
+    /// PRs in other repositories related to each branch.
+    related: BTreeMap<String, Vec<String>>,
+    /// Whether to reopen a branch's closed PR instead of opening a new one.
+    reopen: Reopen,
+    /// Whether to check if the PRs the stack records were closed (not with
+    /// `--prs-only`, which leaves existing PRs alone).
+    check_closed: bool,
+}
+
+/// What `rung submit` does about a branch's PR that was closed without
+/// merging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reopen {
+    /// Ask, with a terminal; without one, open a new PR.
+    Ask,
+    /// Reopen it (`--reopen`).
+    Always,
+    /// Open a new PR beside it (`--no-reopen`).
+    Never,
 }
 
 /// Context for GitHub API operations.
@@ -109,70 +191,442 @@ struct GitHubContext<'a> {
     rt: &'a tokio::runtime::Runtime,
     owner: &'a str,
     repo_name: &'a str,
+    web_url: &'a str,
 }
 
 /// Run the submit command.
-#[allow(clippy::fn_params_excessive_bools)]
+#[allow(clippy::fn_params_excessive_bools, clippy::too_many_arguments)]
 pub fn run(
     json: bool,
     dry_run: bool,
     draft: bool,
+    draft_until_green: bool,
     force: bool,
-    custom_title: Option<&str>,
+    titles: &[String],
+    body_files: &[String],
+    attach: &[String],
+    milestone: Option<&str>,
+    project: Option<u64>,
+    override_policy: bool,
+    prs_only: bool,
+    reopen: Reopen,
 ) -> Result<()> {
+    let Some(output) = submit(
+        json,
+        dry_run,
+        draft,
+        draft_until_green,
+        force,
+        titles,
+        body_files,
+        attach,
+        milestone,
+        project,
+        override_policy,
+        prs_only,
+        reopen,
+    )?
+    else {
+        return Ok(());
+    };
+    if json {
+        return output_json(&output);
+    }
+
+    print_summary(output.prs_created, output.prs_updated);
+
+    // Output PR URLs for piping (essential output, not suppressed by --quiet)
+    for info in &output.branches {
+        output::essential(&info.pr_url);
+    }
+
+    Ok(())
+}
+
+/// Refresh the stack comment on every PR, without pushing branches or
+/// creating PRs.
+pub fn run_comments_only(json: bool) -> Result<()> {
+    let (repo, state, stack) = setup_submit()?;
+    let config = load_config(&repo, &state)?;
+    let remote = github_remote(&repo, &config)?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let gh = GitHubContext {
+        client: &client,
+        rt: &rt,
+        owner: &remote.owner,
+        repo_name: &remote.repo,
+        web_url: &remote.web_url(),
+    };
+
+    let ticket_links = match config
+        .tickets
+        .as_ref()
+        .map(TicketMatcher::new)
+        .transpose()?
+    {
+        Some(matcher) => branch_ticket_links(&repo, &stack.branches, &matcher),
+        None => HashMap::new(),
+    };
+    let related = branch_related_prs(&repo, &remote, &stack, &config.related, json);
+    update_stack_comments(&gh, &stack, &ticket_links, &related, json)?;
+
+    let updated = stack
+        .branches
+        .iter()
+        .filter(|b| !b.frozen && b.pr.is_some())
+        .count();
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "comments_updated": updated }))?
+        );
+    } else {
+        output::success(&tr!("submit-comments-updated", count = updated));
+    }
+    Ok(())
+}
+
+/// Push every branch, without creating or updating PRs or their comments.
+pub fn run_push_only(json: bool, force: bool) -> Result<()> {
+    let (repo, state, stack) = setup_submit()?;
+    let config = load_config(&repo, &state)?;
+    refuse_protected(&stack, &config)?;
+
+    crate::signal::install();
+    let mut pushed = vec![];
+    for branch in stack.topological_order()? {
+        if branch.frozen {
+            continue;
+        }
+        if rung_core::interrupt::is_requested() {
+            break;
+        }
+        if !json {
+            output::info(&tr!("submit-pushing", branch = branch.name));
+        }
+        timings::time("pushes", || {
+            push(
+                &repo,
+                &branch.name,
+                branch.remote_name(),
+                force,
+                config.general.verify_pushes,
+            )
+        })?;
+        pushed.push(branch.name.to_string());
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "pushed": pushed }))?
+        );
+    } else {
+        output::success(&tr!("submit-pushed-count", count = pushed.len()));
+    }
+    Ok(())
+}
+
+/// Refuse to submit a stack with a protected branch in it.
+fn refuse_protected(stack: &Stack, config: &rung_core::Config) -> Result<()> {
+    if let Some(branch) = stack.branches.iter().find(|b| {
+        !b.frozen
+            && (config.general.is_protected(&b.name)
+                || config.general.is_protected(b.remote_name()))
+    }) {
+        bail!(
+            "'{}' is a protected branch (`general.protected_branches`) - rung won't push it",
+            branch.name
+        );
+    }
+    Ok(())
+}
+
+/// Point stack roots without a parent at the base `[[bases]]` maps them onto,
+/// and warn about roots on another trunk: their commits are built on it, so
+/// their PRs keep targeting it.
+fn apply_base_rules(
+    repo: &Repository,
+    stack: &mut Stack,
+    config: &rung_core::Config,
+    json: bool,
+) -> Result<()> {
+    let roots: Vec<(String, Option<String>)> = stack
+        .branches
+        .iter()
+        .filter(|b| {
+            !b.frozen
+                && b.parent
+                    .as_deref()
+                    .is_none_or(|p| stack.find_branch(p).is_none())
+        })
+        .map(|b| {
+            (
+                b.name.to_string(),
+                b.parent.as_ref().map(ToString::to_string),
+            )
+        })
+        .collect();
+    for (name, parent) in roots {
+        let Some(base) = mapped_base(repo, config, &name)? else {
+            continue;
+        };
+        match parent {
+            None => {
+                if let Some(branch) = stack.find_branch_mut(&name) {
+                    branch.parent = Some(BranchName::new(&base)?);
+                }
+            }
+            Some(parent) if parent != base && !json => {
+                output::warn(&tr!(
+                    "submit-base-mismatch",
+                    branch = name,
+                    parent = parent,
+                    base = base
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Push every branch and create or update its PR with the defaults of a
+/// plain `rung submit`, printing progress but not the results.
+pub fn submit_stack(json: bool) -> Result<()> {
+    let output = submit(
+        json,
+        false,
+        false,
+        false,
+        false,
+        &[],
+        &[],
+        &[],
+        None,
+        None,
+        false,
+        false,
+        Reopen::Ask,
+    )?;
+    if let Some(output) = output.filter(|_| !json) {
+        print_summary(output.prs_created, output.prs_updated);
+    }
+    Ok(())
+}
+
+/// Submit the stack, returning what was done. Dry runs and an empty stack
+/// are reported here and return `None`.
+#[allow(
+    clippy::fn_params_excessive_bools,
+    clippy::too_many_arguments,
+    clippy::too_many_lines
+)]
+fn submit(
+    json: bool,
+    dry_run: bool,
+    draft: bool,
+    draft_until_green: bool,
+    force: bool,
+    titles: &[String],
+    body_files: &[String],
+    attach: &[String],
+    milestone: Option<&str>,
+    project: Option<u64>,
+    override_policy: bool,
+    prs_only: bool,
+    reopen: Reopen,
+) -> Result<Option<SubmitOutput>> {
     let (repo, state, mut stack) = setup_submit()?;
 
     if stack.is_empty() {
         if json {
             if dry_run {
-                return output_dry_run_json(&SubmitPlan { actions: vec![] });
+                output_dry_run_json(&SubmitPlan::default())?;
+                return Ok(None);
             }
-            return output_json(&SubmitOutput {
+            return Ok(Some(SubmitOutput {
                 prs_created: 0,
                 prs_updated: 0,
                 branches: vec![],
+                skipped: vec![],
                 dry_run: false,
-            });
+            }));
         }
-        output::info("No branches in stack - nothing to submit");
-        return Ok(());
+        output::info(&tr!("submit-empty"));
+        return Ok(None);
     }
 
+    let rung_config = load_config(&repo, &state)?;
+    refuse_protected(&stack, &rung_config)?;
+    apply_base_rules(&repo, &mut stack, &rung_config, json)?;
+    let names: Vec<&str> = stack
+        .branches
+        .iter()
+        .filter(|b| !b.frozen)
+        .map(|b| b.name.as_str())
+        .collect();
+    let violations = timings::time("policy", || {
+        policy_violations(&repo, &stack, &rung_config, &names, &HashMap::new())
+    })?;
+    enforce_policy(&violations, override_policy, json)?;
+    let tickets = rung_config
+        .tickets
+        .as_ref()
+        .map(TicketMatcher::new)
+        .transpose()?;
+    let require_signoff = rung_config.general.signoff;
+    let current_branch = repo.current_branch().ok();
+    let titles = per_branch(titles, &stack, current_branch.as_deref(), "--title")?;
+    let bodies = read_body_files(body_files, &stack, current_branch.as_deref())?;
+    let attachments = submit_attach::read_attachments(
+        attach,
+        &stack,
+        current_branch.as_deref(),
+        rung_config.pr.asset_branch.as_deref(),
+    )?;
+    let remote = github_remote(&repo, &rung_config)?;
+    let client =
+        github_client(&remote, &rung_config).context("Failed to authenticate with GitHub")?;
+    let verify_pushes = rung_config.general.verify_pushes;
     let config = SubmitConfig {
-        draft,
-        custom_title,
-        current_branch: repo.current_branch().ok(),
+        draft: draft || draft_until_green,
+        ready_when_green: draft_until_green,
+        titles,
+        bodies,
+        milestone: milestone
+            .map(String::from)
+            .or_else(|| rung_config.pr.milestone.clone()),
+        project: project.or(rung_config.pr.project),
+        labels: rung_config.labels,
+        pr: rung_config.pr,
+        policy: rung_config.policy,
+        tickets,
+        packages: rung_config.packages,
+        related: rung_config.related,
+        reopen: match reopen {
+            _ if prs_only => Reopen::Never,
+            Reopen::Ask if dry_run || !prompt::is_interactive() => Reopen::Never,
+            reopen => reopen,
+        },
+        check_closed: !prs_only,
     };
 
-    let (owner, repo_name) = get_remote_info(&repo)?;
-
-    let client = GitHubClient::new(&Auth::auto()).context("Failed to authenticate with GitHub")?;
     let rt = tokio::runtime::Runtime::new()?;
 
     let gh = GitHubContext {
         client: &client,
         rt: &rt,
-        owner: &owner,
-        repo_name: &repo_name,
+        owner: &remote.owner,
+        repo_name: &remote.repo,
+        web_url: &remote.web_url(),
     };
 
     // Phase 1: Create the plan (read-only, checks existing PRs)
-    let plan = create_submit_plan(&repo, &gh, &stack, &config)?;
+    let mut plan = timings::time("planning", || {
+        create_submit_plan(&repo, &gh, &stack, &config)
+    })?;
+    if prs_only {
+        plan.actions
+            .retain(|action| matches!(action, PlannedBranchAction::Create { .. }));
+    }
+    if require_signoff {
+        warn_unsigned_commits(&repo, &stack, &plan.branches());
+    }
+    if !json {
+        for (branch, parent) in &plan.skipped {
+            output::warn(&tr!("submit-skip-empty", branch = branch, parent = parent));
+        }
+    }
 
     // Single dry-run check point
     if dry_run {
-        return handle_dry_run_output(&plan, json, &gh);
+        handle_dry_run_output(&plan, json, &gh)?;
+        return Ok(None);
+    }
+
+    // --force would clobber whatever a teammate pushed on top
+    if force {
+        let others = pushed_by_others(&repo, &stack, &plan.branches());
+        if !others.is_empty() {
+            let details: Vec<String> = others
+                .iter()
+                .map(|(name, who)| format!("'{name}' was last pushed by {who}"))
+                .collect();
+            let question = "Force-push over branches someone else pushed?";
+            match prompt::confirm_destructive(&state, question, &details) {
+                Ok(true) => {}
+                Ok(false) => {
+                    output::info(&tr!("submit-cancelled"));
+                    return Ok(None);
+                }
+                Err(e) => bail!("{e} ({})", details.join("; ")),
+            }
+        }
     }
 
     // Phase 2: Execute the plan (mutations only)
+    // Ctrl-C stops between branches, after the current push has finished
+    crate::signal::install();
     if !json {
-        output::info(&format!("Submitting to {owner}/{repo_name}..."));
+        output::info(&tr!("submit-submitting", remote = remote));
+    }
+    if config.pr.delete_branch_on_merge && plan.count_creates() > 0 {
+        enable_branch_auto_delete(&gh, json);
     }
-    let branch_infos = execute_submit(&repo, &gh, &mut stack, &plan, force, json)?;
+    let started_at = Utc::now();
+    let branch_infos = execute_submit(
+        &repo,
+        &gh,
+        &mut stack,
+        &plan,
+        force,
+        prs_only,
+        verify_pushes,
+        json,
+    )?;
 
     // Save state and update comments (only after real execution)
     state.save_stack(&stack)?;
-    update_stack_comments(&gh, &stack.branches, json)?;
+    record_submit(&state, &stack, &branch_infos, started_at);
+    let interrupted = rung_core::interrupt::is_requested();
+    if interrupted && !json {
+        output::warn(&tr!(
+            "submit-interrupted",
+            done = branch_infos.len(),
+            total = plan.actions.len()
+        ));
+    }
+    let ticket_links = config
+        .tickets
+        .as_ref()
+        .map(|matcher| branch_ticket_links(&repo, &stack.branches, matcher))
+        .unwrap_or_default();
+    // Stack comments would list PRs that don't exist yet
+    if !interrupted && !prs_only {
+        let related = branch_related_prs(&repo, &remote, &stack, &config.related, json);
+        timings::time("stack comments", || {
+            update_stack_comments(&gh, &stack, &ticket_links, &related, json)
+        })?;
+        if config.pr.depends_on {
+            timings::time("depends-on updates", || update_depends_on(&gh, &stack))?;
+        }
+        if config.pr.notes {
+            timings::time("note updates", || update_notes(&gh, &stack))?;
+        }
+    }
+    if !interrupted && !attachments.is_empty() {
+        attach_files(
+            &repo,
+            &gh,
+            &stack,
+            &attachments,
+            &config,
+            verify_pushes,
+            json,
+        )?;
+    }
 
     let (created, updated) = branch_infos
         .iter()
@@ -180,41 +634,173 @@ pub fn run(
             SubmitAction::Created => (c + 1, u),
             SubmitAction::Updated => (c, u + 1),
         });
-    // Output results
+    Ok(Some(SubmitOutput {
+        prs_created: created,
+        prs_updated: updated,
+        branches: branch_infos,
+        skipped: plan.skipped.into_iter().map(|(branch, _)| branch).collect(),
+        dry_run: false,
+    }))
+}
+
+/// Add the submit to the operations log, for `rung stats`.
+fn record_submit(
+    state: &State,
+    stack: &Stack,
+    branch_infos: &[BranchSubmitInfo],
+    started_at: DateTime<Utc>,
+) {
+    if branch_infos.is_empty() {
+        return;
+    }
+    let created = branch_infos
+        .iter()
+        .filter(|info| matches!(info.action, SubmitAction::Created))
+        .map(|info| SubmittedPr {
+            branch: info.branch.clone(),
+            pr_number: info.pr_number,
+        })
+        .collect::<Vec<_>>();
+    let operation = Operation::finished(
+        started_at,
+        OperationKind::Submit {
+            updated: branch_infos.len() - created.len(),
+            created,
+            stack_depth: stack.depth(),
+        },
+    );
+    if let Err(e) = state.record_operation(&operation) {
+        output::warn(&tr!("submit-log-failed", error = e));
+    }
+}
+
+/// Turn on GitHub's head branch auto-delete, so PRs merged in the web UI
+/// don't leave their branches behind. Failures (e.g. no admin access) only warn.
+fn enable_branch_auto_delete(gh: &GitHubContext<'_>, json: bool) {
+    let result = gh.rt.block_on(async {
+        let settings = gh.client.get_merge_settings(gh.owner, gh.repo_name).await?;
+        if settings.delete_branch_on_merge {
+            return Ok(false);
+        }
+        gh.client
+            .enable_delete_branch_on_merge(gh.owner, gh.repo_name)
+            .await
+            .map(|()| true)
+    });
     if json {
-        return output_json(&SubmitOutput {
-            prs_created: created,
-            prs_updated: updated,
-            branches: branch_infos,
-            dry_run: false,
-        });
+        return;
     }
+    match result {
+        Ok(true) => output::info(&tr!("submit-auto-delete")),
+        Ok(false) => {}
+        Err(e) => output::warn(&tr!("submit-auto-delete-failed", error = e)),
+    }
+}
 
-    print_summary(created, updated);
+/// Output submit result as JSON, or with `--porcelain` as one
+/// `created|updated <branch> <pr> <url>` record per branch.
+fn output_json(output: &SubmitOutput) -> Result<()> {
+    if !output::is_porcelain() {
+        println!("{}", serde_json::to_string_pretty(output)?);
+        return Ok(());
+    }
+    for info in &output.branches {
+        output::record(
+            &output::token(&info.action),
+            &[&info.branch, &info.pr_number.to_string(), &info.pr_url],
+        );
+    }
+    Ok(())
+}
 
-    // Output PR URLs for piping (essential output, not suppressed by --quiet)
-    for info in &branch_infos {
-        output::essential(&info.pr_url);
+/// Map repeated `[<branch>=]<value>` flags to the branch each one is for.
+///
+/// The text before the first `=` only names a branch when it's in the stack,
+/// so values containing `=` still apply to the current branch.
+fn per_branch(
+    values: &[String],
+    stack: &Stack,
+    current_branch: Option<&str>,
+    flag: &str,
+) -> Result<HashMap<String, String>> {
+    let mut by_branch = HashMap::new();
+    for value in values {
+        let (branch, value) = match value.split_once('=') {
+            Some((branch, rest)) if stack.find_branch(branch).is_some() => (branch, rest),
+            _ => (
+                current_branch.with_context(|| {
+                    format!("{flag} needs a branch (`<branch>=...`) when not on a branch")
+                })?,
+                value.as_str(),
+            ),
+        };
+        if by_branch
+            .insert(branch.to_string(), value.to_string())
+            .is_some()
+        {
+            bail!("{flag} given more than once for {branch}");
+        }
     }
+    Ok(by_branch)
+}
 
-    Ok(())
+/// Read the PR bodies given with `--body-file`, by branch.
+fn read_body_files(
+    values: &[String],
+    stack: &Stack,
+    current_branch: Option<&str>,
+) -> Result<HashMap<String, String>> {
+    per_branch(values, stack, current_branch, "--body-file")?
+        .into_iter()
+        .map(|(branch, path)| {
+            let body = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read PR body for {branch} from {path}"))?;
+            Ok((branch, body))
+        })
+        .collect()
 }
 
-/// Output submit result as JSON.
-fn output_json(output: &SubmitOutput) -> Result<()> {
-    println!("{}", serde_json::to_string_pretty(output)?);
-    Ok(())
+/// Warn about each commit being submitted that its author hasn't signed off,
+/// for repositories requiring a DCO.
+fn warn_unsigned_commits(repo: &Repository, stack: &rung_core::stack::Stack, branches: &[&str]) {
+    let mut unsigned = 0;
+    for &name in branches {
+        let Some(parent) = stack.find_branch(name).and_then(|b| b.parent.as_deref()) else {
+            continue;
+        };
+        let (Ok(tip), Ok(base)) = (repo.branch_commit(name), repo.branch_commit(parent)) else {
+            continue;
+        };
+        for commit in repo.commit_infos_between(base, tip).unwrap_or_default() {
+            if !commit.is_signed_off() {
+                unsigned += 1;
+                output::warn(&tr!(
+                    "submit-unsigned",
+                    commit = &commit.id.to_string()[..7],
+                    branch = name,
+                    author = commit.author(),
+                    summary = commit.summary
+                ));
+            }
+        }
+    }
+    if unsigned > 0 {
+        output::info(&tr!("submit-unsigned-hint"));
+    }
 }
 
 /// Set up repository, state, and stack for submit.
 fn setup_submit() -> Result<(Repository, State, rung_core::stack::Stack)> {
     let repo = Repository::open_current().context("Not inside a git repository")?;
-    let workdir = repo.workdir().context("Cannot run in bare repository")?;
-    let state = State::new(workdir)?;
+    if repo.is_bare() {
+        bail!("Cannot run in bare repository");
+    }
+    let state = State::for_repository(&repo);
 
     if !state.is_initialized() {
         bail!("Rung not initialized - run `rung init` first");
     }
+    check_origin(&repo, &state)?;
 
     repo.require_clean()?;
     let stack = state.load_stack()?;
@@ -222,16 +808,11 @@ fn setup_submit() -> Result<(Repository, State, rung_core::stack::Stack)> {
     Ok((repo, state, stack))
 }
 
-/// Get owner and repo name from remote.
-fn get_remote_info(repo: &Repository) -> Result<(String, String)> {
-    let origin_url = repo.origin_url().context("No origin remote configured")?;
-    Repository::parse_github_remote(&origin_url).context("Could not parse GitHub remote URL")
-}
-
 /// Create a submit plan by checking existing PRs (read-only).
 ///
-/// This function iterates through all branches in the stack and determines
-/// what action would be taken for each branch (create new PR or update existing).
+/// This function iterates through all branches in the stack, parents first,
+/// and determines what action would be taken for each branch (create new PR
+/// or update existing).
 ///
 /// # Errors
 /// Returns error if any GitHub API calls fail.
@@ -239,64 +820,137 @@ fn create_submit_plan(
     repo: &Repository,
     gh: &GitHubContext<'_>,
     stack: &rung_core::stack::Stack,
-    config: &SubmitConfig<'_>,
+    config: &SubmitConfig,
 ) -> Result<SubmitPlan> {
     let mut actions = Vec::new();
+    let mut skipped: Vec<(String, String)> = vec![];
+    let title_pattern = config.pr.title_regex()?;
+    let closed = if config.check_closed {
+        closed_recorded_prs(gh, stack)
+    } else {
+        HashMap::new()
+    };
 
-    for branch in &stack.branches {
+    // Parents first, so a child's base exists by the time its PR is created
+    for branch in stack.topological_order()? {
+        // Someone else's PR, stacked on with `rung rebase --onto-pr`
+        if branch.frozen {
+            continue;
+        }
         let branch_name = &branch.name;
-        let base_branch = branch.parent.as_deref().unwrap_or("main").to_string();
-
-        // Get title and body from commit message, with custom title override for current branch
-        let (mut title, body) = get_pr_title_and_body(repo, branch_name);
-        if config.current_branch.as_deref() == Some(branch_name.as_str()) {
-            if let Some(custom) = config.custom_title {
-                title = custom.to_string();
-            }
+        let mut base_branch = branch.parent.as_deref().unwrap_or("main").to_string();
+        // A skipped parent isn't pushed; its own parent has the same commits
+        while let Some((_, parent)) = skipped.iter().find(|(name, _)| *name == base_branch) {
+            base_branch.clone_from(parent);
         }
+        // PRs refer to branches by their names on the remote
+        let remote_base = stack.remote_name_of(&base_branch).to_string();
+
+        let messages = branch_messages(repo, branch_name, &base_branch);
+        let (title, body) = new_pr_text(repo, branch_name, &messages, config);
 
         // Check if PR already exists (either from saved state or by querying GitHub)
-        if let Some(pr_number) = branch.pr {
+        if let Some(pr_number) = branch.pr.filter(|pr| !closed.contains_key(pr)) {
             // PR number is already known from saved state
-            let pr_url = format!(
-                "https://github.com/{}/{}/pull/{pr_number}",
-                gh.owner, gh.repo_name
-            );
+            let pr_url = format!("{}/pull/{pr_number}", gh.web_url);
             actions.push(PlannedBranchAction::Update {
                 branch: branch_name.to_string(),
                 pr_number,
                 pr_url,
-                base: base_branch,
+                base: remote_base,
+                reopen: false,
             });
         } else {
-            let existing = gh
-                .rt
-                .block_on(
-                    gh.client
-                        .find_pr_for_branch(gh.owner, gh.repo_name, branch_name),
-                )
-                .context("Failed to check for existing PR")?;
-
-            if let Some(pr) = existing {
+            let recorded = branch.pr.and_then(|pr| closed.get(&pr)).cloned();
+            let existing = existing_pr(gh, branch, recorded, config)?;
+
+            if let Some((pr, reopen)) = existing {
                 actions.push(PlannedBranchAction::Update {
                     branch: branch_name.to_string(),
                     pr_number: pr.number,
                     pr_url: pr.html_url,
-                    base: base_branch,
+                    base: remote_base,
+                    reopen,
                 });
+            } else if has_no_commits(repo, branch_name, &base_branch) {
+                // GitHub refuses a PR with no commits
+                skipped.push((branch_name.to_string(), base_branch));
             } else {
+                if let Some(pattern) = title_pattern.as_ref().filter(|p| !p.is_match(&title)) {
+                    bail!(
+                        "PR title for '{branch_name}' doesn't match `pr.title_pattern` ({pattern}): {title}\n\
+                         Reword the commit or pass --title {branch_name}=<title>"
+                    );
+                }
+                let mut labels =
+                    conventional::labels_for(messages.iter().map(String::as_str), &config.labels);
+                for label in package_labels(repo, branch_name, &base_branch, &config.packages) {
+                    if !labels.contains(&label) {
+                        labels.push(label);
+                    }
+                }
                 actions.push(PlannedBranchAction::Create {
                     branch: branch_name.to_string(),
                     title,
                     body,
-                    base: base_branch,
+                    base: remote_base,
                     draft: config.draft,
+                    labels,
+                    reviewers: new_pr_reviewers(repo, branch_name, &base_branch, config),
                 });
             }
         }
     }
 
-    Ok(SubmitPlan { actions })
+    let mut plan = SubmitPlan {
+        actions,
+        milestone: None,
+        project: config.project,
+        ready_when_green: config.ready_when_green,
+        skipped,
+    };
+    // Fail before anything is pushed if the milestone doesn't exist
+    if let Some(title) = config
+        .milestone
+        .as_deref()
+        .filter(|_| plan.count_creates() > 0)
+    {
+        let milestone = gh
+            .rt
+            .block_on(gh.client.find_milestone(gh.owner, gh.repo_name, title))
+            .context("Failed to look up milestones")?
+            .with_context(|| format!("No open milestone named '{title}'"))?;
+        plan.milestone = Some(milestone);
+    }
+
+    Ok(plan)
+}
+
+/// Title and body for `branch`'s PR: from its commit message, unless given
+/// on the command line, with the ticket it names and the configured footer.
+fn new_pr_text(
+    repo: &Repository,
+    branch: &str,
+    messages: &[String],
+    config: &SubmitConfig,
+) -> (String, String) {
+    let (mut title, mut body) = get_pr_title_and_body(repo, branch, &config.pr);
+    if let Some(custom) = config.titles.get(branch) {
+        title.clone_from(custom);
+    }
+    if let Some(custom) = config.bodies.get(branch) {
+        body = custom.trim_end().to_string();
+    }
+
+    if let Some(matcher) = &config.tickets {
+        if let Some(id) = matcher.find(branch, messages.iter().map(String::as_str)) {
+            title = matcher.apply_to_title(&title, &id);
+            if let Some(url) = matcher.url(&id) {
+                body = append_ticket_link(&body, &id, &url);
+            }
+        }
+    }
+    (title, pr_body::append_footer(&body, &config.pr))
 }
 
 /// Execute the submit plan (mutations only).
@@ -306,41 +960,61 @@ fn create_submit_plan(
 ///
 /// # Errors
 /// Returns error if any GitHub API calls or git operations fail.
+#[allow(
+    clippy::too_many_lines,
+    clippy::too_many_arguments,
+    clippy::fn_params_excessive_bools
+)]
 fn execute_submit(
     repo: &Repository,
     gh: &GitHubContext<'_>,
     stack: &mut rung_core::stack::Stack,
     plan: &SubmitPlan,
     force: bool,
+    prs_only: bool,
+    verify: bool,
     json: bool,
 ) -> Result<Vec<BranchSubmitInfo>> {
     let mut branch_infos = Vec::new();
 
     for action in &plan.actions {
+        // Stop between branches, so an interrupt never splits push and PR update
+        if rung_core::interrupt::is_requested() {
+            break;
+        }
         match action {
             PlannedBranchAction::Update {
                 branch,
                 pr_number,
                 pr_url,
                 base,
+                reopen,
             } => {
                 if !json {
-                    output::info(&format!("Processing {branch}..."));
-                    output::info(&format!("  Pushing {branch}..."));
+                    output::info(&tr!("submit-processing", branch = branch));
+                }
+                // Before pushing: GitHub won't reopen a PR whose branch was
+                // force-pushed since it was closed
+                if *reopen {
+                    reopen_pr(gh, *pr_number, json)?;
+                }
+                if !json {
+                    output::info(&format!("  {}", tr!("submit-pushing", branch = branch)));
                 }
 
                 // Push the branch
-                repo.push(branch, force)
-                    .with_context(|| format!("Failed to push {branch}"))?;
+                let (local, remote_branch) = push_names(stack, branch)?;
+                timings::time("pushes", || {
+                    push(repo, &local, &remote_branch, force, verify)
+                })?;
 
                 // Update the PR base branch
                 update_existing_pr(gh, *pr_number, base, json)?;
 
-                // Persist PR number if it was discovered during planning
+                // Persist PR number if it was discovered during planning, in
+                // place of a closed one recorded before
                 if let Some(stack_branch) = stack.branches.iter_mut().find(|b| &b.name == branch) {
-                    if stack_branch.pr.is_none() {
-                        stack_branch.pr = Some(*pr_number);
-                    }
+                    stack_branch.pr = Some(*pr_number);
                 }
 
                 branch_infos.push(BranchSubmitInfo {
@@ -356,26 +1030,39 @@ fn execute_submit(
                 body,
                 base,
                 draft,
+                labels,
+                reviewers,
             } => {
+                // With --prs-only, a branch already on the remote is left as it is
+                let (local, remote_branch) = push_names(stack, branch)?;
+                let needs_push = !prs_only || !repo.remote_branch_exists(&remote_branch)?;
                 if !json {
-                    output::info(&format!("Processing {branch}..."));
-                    output::info(&format!("  Pushing {branch}..."));
+                    output::info(&tr!("submit-processing", branch = branch));
+                    if needs_push {
+                        output::info(&format!("  {}", tr!("submit-pushing", branch = branch)));
+                    }
+                }
+                if needs_push {
+                    timings::time("pushes", || {
+                        push(repo, &local, &remote_branch, force, verify)
+                    })?;
                 }
-
-                // Push the branch
-                repo.push(branch, force)
-                    .with_context(|| format!("Failed to push {branch}"))?;
 
                 // Check if a PR was created between planning and execution
                 let existing = gh
                     .rt
-                    .block_on(gh.client.find_pr_for_branch(gh.owner, gh.repo_name, branch))
+                    .block_on(
+                        gh.client
+                            .find_pr_for_branch(gh.owner, gh.repo_name, &remote_branch),
+                    )
                     .context("Failed to check for existing PR")?;
 
-                let (pr_number, pr_url, was_created) = if let Some(pr) = existing {
+                ensure_base_pushed(gh, stack, branch, base)?;
+
+                let (pr_number, pr_url, author, was_created) = if let Some(pr) = existing {
                     // PR was created between planning and execution - update it instead
                     if !json {
-                        output::info(&format!("  Found existing PR #{}...", pr.number));
+                        output::info(&format!("  {}", tr!("submit-found-pr", pr = pr.number)));
                     }
 
                     let update = UpdatePullRequest {
@@ -391,17 +1078,20 @@ fn execute_submit(
                         )
                         .with_context(|| format!("Failed to update PR #{}", pr.number))?;
 
-                    (pr.number, pr.html_url, false)
+                    (pr.number, pr.html_url, pr.author, false)
                 } else {
                     // Create new PR
                     if !json {
-                        output::info(&format!("  Creating PR ({branch} → {base})..."));
+                        output::info(&format!(
+                            "  {}",
+                            tr!("submit-creating", branch = branch, base = base)
+                        ));
                     }
 
                     let create = CreatePullRequest {
                         title: title.clone(),
                         body: body.clone(),
-                        head: branch.clone(),
+                        head: remote_branch.into_inner(),
                         base: base.clone(),
                         draft: *draft,
                     };
@@ -412,15 +1102,23 @@ fn execute_submit(
                         .with_context(|| format!("Failed to create PR for {branch}"))?;
 
                     if !json {
-                        output::success(&format!("  Created PR #{}: {}", pr.number, pr.html_url));
+                        output::success(&format!(
+                            "  {}",
+                            tr!("submit-created", pr = pr.number, url = pr.html_url)
+                        ));
                     }
+                    apply_labels(gh, pr.number, labels, json);
+                    apply_reviewers(gh, pr.number, reviewers, json);
+                    apply_milestone_and_project(gh, pr.number, plan, json);
 
-                    (pr.number, pr.html_url, true)
+                    (pr.number, pr.html_url, pr.author, true)
                 };
 
-                // Update stack state with the PR number
+                // Update stack state with the PR number and who opened it
                 if let Some(stack_branch) = stack.branches.iter_mut().find(|b| &b.name == branch) {
                     stack_branch.pr = Some(pr_number);
+                    stack_branch.author = author;
+                    stack_branch.ready_when_green = plan.ready_when_green && was_created;
                 }
 
                 branch_infos.push(BranchSubmitInfo {
@@ -440,6 +1138,37 @@ fn execute_submit(
     Ok(branch_infos)
 }
 
+/// Local and remote names of stack branch `branch`, to push it under.
+fn push_names(stack: &Stack, branch: &str) -> Result<(BranchName, BranchName)> {
+    let branch = stack
+        .find_branch(branch)
+        .with_context(|| format!("Branch '{branch}' is not in the stack"))?;
+    Ok((branch.name.clone(), branch.remote_name().clone()))
+}
+
+/// Refuse to target a stack branch that isn't on GitHub yet, which would
+/// leave the PR based on the default branch with its parent's diff included.
+fn ensure_base_pushed(
+    gh: &GitHubContext<'_>,
+    stack: &Stack,
+    branch: &str,
+    base: &str,
+) -> Result<()> {
+    if !stack.branches.iter().any(|b| b.remote_name() == base) {
+        return Ok(());
+    }
+    let exists = gh
+        .rt
+        .block_on(gh.client.branch_exists(gh.owner, gh.repo_name, base))
+        .with_context(|| format!("Failed to check that {base} exists on GitHub"))?;
+    if !exists {
+        bail!(
+            "Parent branch '{base}' of {branch} is not on GitHub - run `rung submit` again to push it first"
+        );
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Dry-Run Output
 // ============================================================================
@@ -451,6 +1180,8 @@ struct DryRunOutput {
     prs_would_update: usize,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     branches: Vec<PlannedBranchInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<String>,
     dry_run: bool,
 }
 
@@ -485,13 +1216,20 @@ fn output_dry_run_json(plan: &SubmitPlan) -> Result<()> {
                 pr_number: Some(*pr_number),
                 pr_url: Some(pr_url.clone()),
                 target_base: None,
+                labels: vec![],
                 action: SubmitAction::Updated,
             },
-            PlannedBranchAction::Create { branch, base, .. } => PlannedBranchInfo {
+            PlannedBranchAction::Create {
+                branch,
+                base,
+                labels,
+                ..
+            } => PlannedBranchInfo {
                 branch: branch.clone(),
                 pr_number: None,
                 pr_url: None,
                 target_base: Some(base.clone()),
+                labels: labels.clone(),
                 action: SubmitAction::Created,
             },
         })
@@ -501,9 +1239,41 @@ fn output_dry_run_json(plan: &SubmitPlan) -> Result<()> {
         prs_would_create: plan.count_creates(),
         prs_would_update: plan.count_updates(),
         branches,
+        skipped: plan
+            .skipped
+            .iter()
+            .map(|(branch, _)| branch.clone())
+            .collect(),
         dry_run: true,
     };
 
+    if output::is_porcelain() {
+        // would-create <branch> <base>, would-update <branch> <pr> <url>
+        for branch in &output.branches {
+            let pr = branch
+                .pr_number
+                .map(|pr| pr.to_string())
+                .unwrap_or_default();
+            match branch.action {
+                SubmitAction::Created => output::record(
+                    "would-create",
+                    &[
+                        &branch.branch,
+                        branch.target_base.as_deref().unwrap_or_default(),
+                    ],
+                ),
+                SubmitAction::Updated => output::record(
+                    "would-update",
+                    &[
+                        &branch.branch,
+                        &pr,
+                        branch.pr_url.as_deref().unwrap_or_default(),
+                    ],
+                ),
+            }
+        }
+        return Ok(());
+    }
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
@@ -511,7 +1281,7 @@ fn output_dry_run_json(plan: &SubmitPlan) -> Result<()> {
 /// Print human-readable summary for dry-run mode.
 fn print_dry_run_summary(plan: &SubmitPlan, default_branch: &str) {
     if plan.actions.is_empty() {
-        output::info("No branches to submit");
+        output::info(&tr!("submit-nothing-planned"));
         return;
     }
 
@@ -530,7 +1300,12 @@ fn print_dry_run_summary(plan: &SubmitPlan, default_branch: &str) {
         .actions
         .iter()
         .filter_map(|a| match a {
-            PlannedBranchAction::Create { branch, base, .. } => Some((branch, base)),
+            PlannedBranchAction::Create {
+                branch,
+                base,
+                labels,
+                ..
+            } => Some((branch, base, labels)),
             PlannedBranchAction::Update { .. } => None,
         })
         .collect();
@@ -538,7 +1313,7 @@ fn print_dry_run_summary(plan: &SubmitPlan, default_branch: &str) {
     let mut parts = vec![];
 
     if !updates.is_empty() {
-        parts.push(format!("→ Would push {} branches:", updates.len()));
+        parts.push(tr!("submit-would-push", count = updates.len()));
         for (branch, pr_number) in &updates {
             parts.push(format!("  - {branch} (PR #{pr_number})"));
         }
@@ -546,22 +1321,32 @@ fn print_dry_run_summary(plan: &SubmitPlan, default_branch: &str) {
     }
 
     if !creates.is_empty() {
-        parts.push(format!(
-            "→ Would create {} new PRs for branches:",
-            creates.len()
-        ));
-        for (branch, base) in &creates {
+        parts.push(tr!("submit-would-create", count = creates.len()));
+        for (branch, base, labels) in &creates {
             let target = if base.is_empty() {
                 default_branch
             } else {
                 base
             };
-            parts.push(format!("  - {branch} → {target}"));
+            if labels.is_empty() {
+                parts.push(format!("  - {branch} → {target}"));
+            } else {
+                parts.push(format!("  - {branch} → {target} [{}]", labels.join(", ")));
+            }
+        }
+        if let Some(milestone) = &plan.milestone {
+            parts.push(format!(
+                "  {}",
+                tr!("submit-in-milestone", milestone = milestone.title)
+            ));
+        }
+        if let Some(project) = plan.project {
+            parts.push(format!("  {}", tr!("submit-in-project", project = project)));
         }
         parts.push(String::new());
     }
 
-    parts.push("(dry run - no changes made)".into());
+    parts.push(tr!("submit-dry-run"));
     output::essential(&parts.join("\n"));
 }
 
@@ -588,33 +1373,202 @@ fn generate_title(branch_name: &str) -> String {
 ///
 /// Returns (title, body) where:
 /// - title is the first line of the commit message
-/// - body is the remaining lines (after the first blank line), or empty string if none
+/// - body is the remaining lines (after the first blank line) formatted as
+///   markdown by [`pr_body::format_pr_body`], or empty string if none
 ///
 /// Falls back to generated title from branch name if commit message can't be read.
-fn get_pr_title_and_body(repo: &Repository, branch_name: &str) -> (String, String) {
-    if let Ok(message) = repo.branch_commit_message(branch_name) {
-        let mut lines = message.lines();
-        let title = lines.next().unwrap_or("").trim().to_string();
-
-        // Skip blank lines after title, then collect the rest as body
-        // Use trim_end() to preserve leading indentation for markdown formatting
-        let body: String = lines
-            .skip_while(|line| line.trim().is_empty())
-            .collect::<Vec<_>>()
-            .join("\n")
-            .trim_end()
-            .to_string();
-
+pub(super) fn get_pr_title_and_body(
+    repo: &Repository,
+    branch_name: &str,
+    config: &PrConfig,
+) -> (String, String) {
+    let tip = repo
+        .branch_commit(branch_name)
+        .and_then(|oid| repo.commit_info(oid))
+        .ok()
         // Only use commit message if title is non-empty
-        if !title.is_empty() {
-            return (title, body);
-        }
+        .filter(|tip| !tip.summary.is_empty());
+    if let Some(tip) = tip {
+        return (tip.summary, pr_body::format_pr_body(&tip.body, config));
     }
 
     // Fallback to slugified branch name
     (generate_title(branch_name), String::new())
 }
 
+/// Messages of the commits unique to a branch, oldest first.
+fn branch_messages(repo: &Repository, branch: &str, base: &str) -> Vec<String> {
+    let (Ok(tip), Ok(base_tip)) = (repo.branch_commit(branch), repo.branch_commit(base)) else {
+        return vec![];
+    };
+    repo.commit_infos_between(base_tip, tip)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|commit| commit.message)
+        .collect()
+}
+
+/// Reviewers to request on a branch's new PR: `[pr] reviewers`, plus those
+/// `[policy]` requires for the paths it changes.
+fn new_pr_reviewers(
+    repo: &Repository,
+    branch: &str,
+    base: &str,
+    config: &SubmitConfig,
+) -> Vec<String> {
+    let mut reviewers = config.pr.reviewers.clone();
+    if config.policy.reviewers.is_empty() {
+        return reviewers;
+    }
+    let files = repo.changed_files(branch, base).unwrap_or_default();
+    for reviewer in config.policy.reviewers_for(&files) {
+        if !reviewers.contains(&reviewer) {
+            reviewers.push(reviewer);
+        }
+    }
+    reviewers
+}
+
+/// Labels of the configured packages a branch touches.
+fn package_labels(
+    repo: &Repository,
+    branch: &str,
+    base: &str,
+    packages: &[PackageConfig],
+) -> Vec<String> {
+    if packages.is_empty() {
+        return vec![];
+    }
+    let files = repo.changed_files(branch, base).unwrap_or_default();
+    scope::touched_packages(&files, packages)
+        .into_iter()
+        .filter_map(|p| p.label.clone())
+        .collect()
+}
+
+/// Append a tracker link to a PR body.
+pub(super) fn append_ticket_link(body: &str, id: &str, url: &str) -> String {
+    let link = format!("Ticket: [{id}]({url})");
+    if body.is_empty() {
+        link
+    } else {
+        format!("{body}\n\n{link}")
+    }
+}
+
+/// Ticket links for each branch, keyed by branch name.
+fn branch_ticket_links(
+    repo: &Repository,
+    branches: &[StackBranch],
+    matcher: &TicketMatcher,
+) -> HashMap<String, String> {
+    branches
+        .iter()
+        .filter_map(|b| {
+            let base = b.parent.as_deref().unwrap_or("main");
+            let messages = branch_messages(repo, &b.name, base);
+            let id = matcher.find(&b.name, messages.iter().map(String::as_str))?;
+            Some((b.name.to_string(), matcher.markdown_link(&id)))
+        })
+        .collect()
+}
+
+/// Add labels to a newly created PR. Failures are reported but not fatal.
+fn apply_labels(gh: &GitHubContext<'_>, pr_number: u64, labels: &[String], json: bool) {
+    if labels.is_empty() {
+        return;
+    }
+
+    let request = AddLabels {
+        labels: labels.to_vec(),
+    };
+    let result = gh.rt.block_on(
+        gh.client
+            .add_labels(gh.owner, gh.repo_name, pr_number, request),
+    );
+    if json {
+        return;
+    }
+    match result {
+        Ok(_) => output::info(&format!(
+            "  {}",
+            tr!("submit-labelled", labels = labels.join(", "))
+        )),
+        Err(e) => output::warn(&format!("  {}", tr!("submit-label-failed", error = e))),
+    }
+}
+
+/// Request reviews on a new PR, warning on failure.
+fn apply_reviewers(gh: &GitHubContext<'_>, pr_number: u64, reviewers: &[String], json: bool) {
+    if reviewers.is_empty() {
+        return;
+    }
+
+    let result = gh.rt.block_on(gh.client.request_reviewers(
+        gh.owner,
+        gh.repo_name,
+        pr_number,
+        RequestReviewers::from_names(reviewers),
+    ));
+    if json {
+        return;
+    }
+    match result {
+        Ok(()) => output::info(&format!(
+            "  {}",
+            tr!("submit-review-requested", reviewers = reviewers.join(", "))
+        )),
+        Err(e) => output::warn(&format!("  {}", tr!("submit-review-failed", error = e))),
+    }
+}
+
+/// Put a new PR in the plan's milestone and project, warning on failure.
+fn apply_milestone_and_project(
+    gh: &GitHubContext<'_>,
+    pr_number: u64,
+    plan: &SubmitPlan,
+    json: bool,
+) {
+    if let Some(milestone) = &plan.milestone {
+        let result = gh.rt.block_on(gh.client.set_milestone(
+            gh.owner,
+            gh.repo_name,
+            pr_number,
+            milestone.number,
+        ));
+        if !json {
+            match result {
+                Ok(()) => output::info(&format!(
+                    "  {}",
+                    tr!("submit-milestone", milestone = milestone.title)
+                )),
+                Err(e) => {
+                    output::warn(&format!("  {}", tr!("submit-milestone-failed", error = e)));
+                }
+            }
+        }
+    }
+    if let Some(project) = plan.project {
+        let result =
+            gh.rt.block_on(
+                gh.client
+                    .add_to_project(gh.owner, gh.repo_name, pr_number, project),
+            );
+        if !json {
+            match result {
+                Ok(()) => {
+                    output::info(&format!("  {}", tr!("submit-project", project = project)));
+                }
+                Err(e) => output::warn(&format!(
+                    "  {}",
+                    tr!("submit-project-failed", project = project, error = e)
+                )),
+            }
+        }
+    }
+}
+
 /// Update an existing PR (only updates base branch, preserves description).
 fn update_existing_pr(
     gh: &GitHubContext<'_>,
@@ -623,7 +1577,7 @@ fn update_existing_pr(
     json: bool,
 ) -> Result<()> {
     if !json {
-        output::info(&format!("  Updating PR #{pr_number}..."));
+        output::info(&format!("  {}", tr!("submit-updating", pr = pr_number)));
     }
 
     let update = UpdatePullRequest {
@@ -642,177 +1596,461 @@ fn update_existing_pr(
     Ok(())
 }
 
+/// The recorded PRs of stack branches that were closed without merging, by
+/// number, fetched in one request. Any that can't be fetched count as open.
+fn closed_recorded_prs(gh: &GitHubContext<'_>, stack: &Stack) -> HashMap<u64, PullRequest> {
+    let numbers: Vec<u64> = stack
+        .branches
+        .iter()
+        .filter(|b| !b.frozen)
+        .filter_map(|b| b.pr)
+        .collect();
+    if numbers.is_empty() {
+        return HashMap::new();
+    }
+    let mut prs = gh
+        .rt
+        .block_on(gh.client.get_prs_batch(gh.owner, gh.repo_name, &numbers))
+        .unwrap_or_default();
+    prs.retain(|_, pr| pr.state == PullRequestState::Closed);
+    prs
+}
+
+/// An open PR for `branch`, or one closed without merging that is to be
+/// reopened (and so `true`): its `recorded` one, or else the last one GitHub
+/// has for it.
+fn existing_pr(
+    gh: &GitHubContext<'_>,
+    branch: &StackBranch,
+    recorded: Option<PullRequest>,
+    config: &SubmitConfig,
+) -> Result<Option<(PullRequest, bool)>> {
+    let open = gh
+        .rt
+        .block_on(
+            gh.client
+                .find_pr_for_branch(gh.owner, gh.repo_name, branch.remote_name()),
+        )
+        .context("Failed to check for existing PR")?;
+    if let Some(pr) = open {
+        return Ok(Some((pr, false)));
+    }
+    if config.reopen == Reopen::Never {
+        return Ok(None);
+    }
+    Ok(closed_pr_to_reopen(gh, branch, recorded, config.reopen)?.map(|pr| (pr, true)))
+}
+
+/// The PR `branch` had before, closed without merging, if there is one and
+/// it's to be reopened rather than a new one opened beside it, keeping its
+/// reviews and discussion. `recorded` is the closed PR the stack records.
+fn closed_pr_to_reopen(
+    gh: &GitHubContext<'_>,
+    branch: &StackBranch,
+    recorded: Option<PullRequest>,
+    reopen: Reopen,
+) -> Result<Option<PullRequest>> {
+    let closed = match recorded {
+        Some(pr) => Some(pr),
+        None => gh
+            .rt
+            .block_on(gh.client.find_closed_pr_for_branch(
+                gh.owner,
+                gh.repo_name,
+                branch.remote_name(),
+            ))
+            .context("Failed to check for closed PRs")?,
+    };
+    let Some(pr) = closed else {
+        return Ok(None);
+    };
+    if reopen == Reopen::Always {
+        return Ok(Some(pr));
+    }
+    let question = tr!(
+        "submit-reopen-ask",
+        branch = branch.name,
+        pr = pr.number,
+        title = pr.title
+    );
+    Ok(prompt::confirm(&question, true)?.then_some(pr))
+}
+
+/// Reopen PR `pr_number`, closed without merging.
+fn reopen_pr(gh: &GitHubContext<'_>, pr_number: u64, json: bool) -> Result<()> {
+    if !json {
+        output::info(&format!("  {}", tr!("submit-reopening", pr = pr_number)));
+    }
+    gh.rt
+        .block_on(gh.client.reopen_pr(gh.owner, gh.repo_name, pr_number))
+        .with_context(|| {
+            format!(
+                "Failed to reopen PR #{pr_number} - GitHub refuses if its branch was deleted or force-pushed since it was closed"
+            )
+        })?;
+    Ok(())
+}
+
 /// Print summary of submit operation.
 fn print_summary(created: usize, updated: usize) {
     if created > 0 || updated > 0 {
         let mut parts = vec![];
         if created > 0 {
-            parts.push(format!("{created} created"));
+            parts.push(tr!("submit-created-count", count = created));
         }
         if updated > 0 {
-            parts.push(format!("{updated} updated"));
+            parts.push(tr!("submit-updated-count", count = updated));
+        }
+        output::success(&tr!("submit-done", counts = parts.join(", ")));
+        if created > 0 {
+            output::hint(&tr!("submit-merge-hint"));
         }
-        output::success(&format!("Done! PRs: {}", parts.join(", ")));
     } else {
-        output::info("No changes to submit");
+        output::info(&tr!("submit-no-changes"));
     }
 }
 
 /// Marker to identify rung stack comments.
 const STACK_COMMENT_MARKER: &str = "<!-- rung-stack -->";
 
-/// Generate stack comment for a PR.
-fn generate_stack_comment(branches: &[StackBranch], current_pr: u64) -> String {
+/// PRs in other repositories to list in each branch's stack comment: those
+/// under `[related]` for the branch, then the stacks of the other repositories
+/// in the workspace, if any.
+fn branch_related_prs(
+    repo: &Repository,
+    remote: &GitHubRemote,
+    stack: &Stack,
+    configured: &BTreeMap<String, Vec<String>>,
+    json: bool,
+) -> HashMap<String, Vec<String>> {
+    let workspace = match repo.workdir().map(|dir| related_prs(dir, remote)) {
+        Some(Ok(prs)) => prs,
+        Some(Err(e)) => {
+            if !json {
+                output::warn(&tr!("submit-workspace-failed", error = format!("{e:#}")));
+            }
+            vec![]
+        }
+        None => vec![],
+    };
+    stack
+        .branches
+        .iter()
+        .map(|b| {
+            let mut prs = configured.get(b.name.as_str()).cloned().unwrap_or_default();
+            for pr in &workspace {
+                if !prs.contains(pr) {
+                    prs.push(pr.clone());
+                }
+            }
+            (b.name.to_string(), prs)
+        })
+        .filter(|(_, prs)| !prs.is_empty())
+        .collect()
+}
+
+/// Generate the stack comment for `current_name`'s PR.
+///
+/// `index` is built once per submit and shared by every comment, so each
+/// comment costs time linear in its chain.
+fn generate_stack_comment(
+    index: &StackIndex<'_>,
+    current_name: &str,
+    ticket_links: &HashMap<String, String>,
+    related: &HashMap<String, Vec<String>>,
+) -> String {
     let mut comment = String::from(STACK_COMMENT_MARKER);
     comment.push('\n');
 
-    // Find the current branch
-    let current_branch = branches.iter().find(|b| b.pr == Some(current_pr));
-    let current_name = current_branch.map_or("", |b| b.name.as_str());
-
     // Build the chain for this branch
-    let chain = build_branch_chain(branches, current_name);
+    let chain = index.chain_through(current_name);
 
     // Build stack list in markdown format (newest at top, so iterate in reverse)
-    for branch_name in chain.iter().rev() {
-        let branch = branches.iter().find(|b| &b.name == branch_name);
-        let is_current = branch_name == current_name;
-
-        if let Some(b) = branch {
-            let pointer = if is_current { " 👈" } else { "" };
-
-            if let Some(pr_num) = b.pr {
-                // GitHub auto-links and expands #number to show PR title
-                let _ = writeln!(comment, "* **#{pr_num}**{pointer}");
-            } else {
-                let _ = writeln!(comment, "* *(pending)* `{branch_name}`{pointer}");
-            }
+    for b in chain.iter().rev() {
+        let pointer = if b.name == current_name { " 👈" } else { "" };
+        let ticket = ticket_links
+            .get(b.name.as_str())
+            .map_or_else(String::new, |link| format!(" ({link})"));
+
+        if let Some(pr_num) = b.pr {
+            // GitHub auto-links and expands #number to show PR title
+            let _ = writeln!(comment, "* **#{pr_num}**{ticket}{pointer}");
+        } else {
+            let _ = writeln!(comment, "* *(pending)* `{}`{ticket}{pointer}", b.name);
         }
     }
 
-    // Add base branch (main)
-    let base = current_branch
-        .and_then(|b| {
-            // Walk up to find the root's parent
-            let mut current = b;
-            loop {
-                if let Some(ref parent) = current.parent {
-                    if let Some(p) = branches.iter().find(|br| &br.name == parent) {
-                        current = p;
-                    } else {
-                        return Some(parent.as_str());
-                    }
-                } else {
-                    return Some("main");
-                }
-            }
-        })
+    // Add base branch: the parent of the chain's root
+    let base = chain
+        .first()
+        .and_then(|root| root.parent.as_deref())
         .unwrap_or("main");
 
     let _ = writeln!(comment, "* `{base}`");
+    if let Some(prs) = related.get(current_name) {
+        comment.push_str("\n**Related PRs**\n\n");
+        for pr in prs {
+            let _ = writeln!(comment, "* {pr}");
+        }
+    }
     comment.push_str("\n---\n*Managed by [rung](https://github.com/auswm85/rung)*");
 
     comment
 }
 
+/// rung's stack comment among a PR's comments.
+fn find_stack_comment(comments: &[IssueComment]) -> Option<&IssueComment> {
+    comments.iter().find(|c| {
+        c.body
+            .as_ref()
+            .is_some_and(|b| b.contains(STACK_COMMENT_MARKER))
+    })
+}
+
+/// Whether the stack comment among `comments` on `branch`'s PR no longer
+/// shows the stack as it is, say after rearranging branches by hand. A PR
+/// without one isn't stale.
+pub fn stale_stack_comment(stack: &Stack, branch: &str, comments: &[IssueComment]) -> bool {
+    let Some(body) = find_stack_comment(comments).and_then(|c| c.body.as_deref()) else {
+        return false;
+    };
+    let expected = generate_stack_comment(&stack.index(), branch, &HashMap::new(), &HashMap::new());
+    comment_outline(body) != comment_outline(&expected)
+}
+
+/// The stack a stack comment lists: each PR number or pending branch, then
+/// the base, marking the PR it's on. Ticket links and related PRs, which
+/// don't depend on the stack's shape, are left out.
+fn comment_outline(comment: &str) -> Vec<String> {
+    comment
+        .lines()
+        .skip_while(|line| !line.starts_with("* "))
+        .take_while(|line| line.starts_with("* "))
+        .map(|line| {
+            let entry = line
+                .split("**")
+                .nth(1)
+                .or_else(|| line.split('`').nth(1))
+                .unwrap_or(line);
+            let pointer = if line.ends_with(" 👈") { " 👈" } else { "" };
+            format!("{entry}{pointer}")
+        })
+        .collect()
+}
+
+/// How many PRs have their comment or body reconciled at once.
+const RECONCILE_CONCURRENCY: usize = 8;
+
 /// Update stack comments on all PRs in the stack.
+///
+/// Runs after every branch is pushed, reconciling up to
+/// [`RECONCILE_CONCURRENCY`] PRs at a time.
 fn update_stack_comments(
     gh: &GitHubContext<'_>,
-    branches: &[StackBranch],
+    stack: &Stack,
+    ticket_links: &HashMap<String, String>,
+    related: &HashMap<String, Vec<String>>,
     json: bool,
 ) -> Result<()> {
     if !json {
-        output::info("Updating stack comments...");
+        output::info(&tr!("submit-updating-comments"));
     }
 
-    for branch in branches {
-        let Some(pr_number) = branch.pr else {
-            continue;
-        };
+    let index = stack.index();
+    let comments: Vec<(u64, String)> = stack
+        .branches
+        .iter()
+        .filter(|branch| !branch.frozen)
+        .filter_map(|branch| {
+            let pr_number = branch.pr?;
+            let body = generate_stack_comment(&index, &branch.name, ticket_links, related);
+            Some((pr_number, body))
+        })
+        .collect();
 
-        let comment_body = generate_stack_comment(branches, pr_number);
+    let results: Vec<Result<()>> = gh.rt.block_on(
+        stream::iter(comments)
+            .map(|(pr_number, body)| upsert_stack_comment(gh, pr_number, body))
+            .buffered(RECONCILE_CONCURRENCY)
+            .collect(),
+    );
+    results.into_iter().collect()
+}
 
-        // Find existing rung comment
-        let comments = gh
-            .rt
-            .block_on(
-                gh.client
-                    .list_pr_comments(gh.owner, gh.repo_name, pr_number),
-            )
-            .with_context(|| format!("Failed to list comments on PR #{pr_number}"))?;
+/// Update the rung comment on PR `pr_number`, or create it if missing.
+async fn upsert_stack_comment(gh: &GitHubContext<'_>, pr_number: u64, body: String) -> Result<()> {
+    let comments = gh
+        .client
+        .list_pr_comments(gh.owner, gh.repo_name, pr_number)
+        .await
+        .with_context(|| format!("Failed to list comments on PR #{pr_number}"))?;
+
+    if let Some(comment) = find_stack_comment(&comments) {
+        gh.client
+            .update_pr_comment(gh.owner, gh.repo_name, comment.id, UpdateComment { body })
+            .await
+            .with_context(|| format!("Failed to update comment on PR #{pr_number}"))?;
+    } else {
+        gh.client
+            .create_pr_comment(gh.owner, gh.repo_name, pr_number, CreateComment { body })
+            .await
+            .with_context(|| format!("Failed to create comment on PR #{pr_number}"))?;
+    }
+    Ok(())
+}
 
-        let existing_comment = comments.iter().find(|c| {
-            c.body
+/// Keep the `Depends on #<parent PR>` line in each PR body pointing at its
+/// parent's PR, removing it from PRs based on trunk (`[pr] depends_on`).
+fn update_depends_on(gh: &GitHubContext<'_>, stack: &Stack) -> Result<()> {
+    let prs: Vec<(u64, Option<u64>)> = stack
+        .branches
+        .iter()
+        .filter(|branch| !branch.frozen)
+        .filter_map(|branch| {
+            let parent_pr = branch
+                .parent
                 .as_ref()
-                .is_some_and(|b| b.contains(STACK_COMMENT_MARKER))
-        });
+                .and_then(|parent| stack.find_branch(parent))
+                .and_then(|parent| parent.pr);
+            Some((branch.pr?, parent_pr))
+        })
+        .collect();
 
-        if let Some(comment) = existing_comment {
-            // Update existing comment
-            let update = UpdateComment { body: comment_body };
-            gh.rt
-                .block_on(
-                    gh.client
-                        .update_pr_comment(gh.owner, gh.repo_name, comment.id, update),
-                )
-                .with_context(|| format!("Failed to update comment on PR #{pr_number}"))?;
-        } else {
-            // Create new comment
-            let create = CreateComment { body: comment_body };
-            gh.rt
-                .block_on(
-                    gh.client
-                        .create_pr_comment(gh.owner, gh.repo_name, pr_number, create),
-                )
-                .with_context(|| format!("Failed to create comment on PR #{pr_number}"))?;
-        }
-    }
+    let results: Vec<Result<()>> = gh.rt.block_on(
+        stream::iter(prs)
+            .map(|(pr_number, parent_pr)| set_depends_on(gh, pr_number, parent_pr))
+            .buffered(RECONCILE_CONCURRENCY)
+            .collect(),
+    );
+    results.into_iter().collect()
+}
 
-    Ok(())
+/// Keep the hidden notes block in each PR body matching its branch's notes
+/// (`[pr] notes`).
+fn update_notes(gh: &GitHubContext<'_>, stack: &Stack) -> Result<()> {
+    let prs: Vec<(u64, &[String])> = stack
+        .branches
+        .iter()
+        .filter(|branch| !branch.frozen)
+        .filter_map(|branch| Some((branch.pr?, branch.notes.as_slice())))
+        .collect();
+
+    let results: Vec<Result<()>> = gh.rt.block_on(
+        stream::iter(prs)
+            .map(|(pr_number, notes)| set_notes(gh, pr_number, notes))
+            .buffered(RECONCILE_CONCURRENCY)
+            .collect(),
+    );
+    results.into_iter().collect()
 }
 
-/// Build a chain of branches from root ancestor to all descendants.
-///
-/// Returns branch names in order from oldest ancestor to newest descendant.
-fn build_branch_chain(branches: &[StackBranch], current_name: &str) -> Vec<String> {
-    // Find all ancestors (walk up the parent chain)
-    let mut ancestors: Vec<String> = vec![];
-    let mut current = current_name.to_string();
-
-    loop {
-        if let Some(branch) = branches.iter().find(|b| b.name == current) {
-            if let Some(ref parent) = branch.parent {
-                // Check if parent is in the stack
-                if branches.iter().any(|b| b.name == *parent) {
-                    ancestors.push(parent.to_string());
-                    current = parent.to_string();
-                    continue;
-                }
+/// Upload the `--attach` files and link them from their branches' PR bodies.
+fn attach_files(
+    repo: &Repository,
+    gh: &GitHubContext<'_>,
+    stack: &Stack,
+    attachments: &[submit_attach::Attachment],
+    config: &SubmitConfig,
+    verify: bool,
+    json: bool,
+) -> Result<()> {
+    let Some(asset_branch) = config.pr.asset_branch.as_deref() else {
+        return Ok(());
+    };
+    let asset_branch = BranchName::new(asset_branch).context("Invalid asset_branch under [pr]")?;
+    let links = submit_attach::upload(repo, attachments, &asset_branch, gh.web_url, verify)?;
+    for (branch, links) in &links {
+        let Some(pr_number) = stack.find_branch(branch).and_then(|b| b.pr) else {
+            if !json {
+                output::warn(&format!(
+                    "'{branch}' has no PR to link its attachments from"
+                ));
             }
+            continue;
+        };
+        gh.rt.block_on(add_attachments(gh, pr_number, links))?;
+        if !json {
+            output::info(&format!(
+                "Attached {} file(s) to PR #{pr_number}",
+                links.len()
+            ));
         }
-        break;
     }
+    Ok(())
+}
 
-    // Reverse to get oldest ancestor first
-    ancestors.reverse();
-
-    // Start chain with ancestors, then current
-    let mut chain = ancestors;
-    chain.push(current_name.to_string());
+/// Append `links` to PR `pr_number`'s body, skipping any it already has.
+async fn add_attachments(gh: &GitHubContext<'_>, pr_number: u64, links: &[String]) -> Result<()> {
+    let pr = gh
+        .client
+        .get_pr(gh.owner, gh.repo_name, pr_number)
+        .await
+        .with_context(|| format!("Failed to fetch PR #{pr_number}"))?;
+    let body = pr.body.unwrap_or_default();
+    let updated = pr_body::add_attachments(&body, links);
+    if updated == body {
+        return Ok(());
+    }
+    let update = UpdatePullRequest {
+        title: None,
+        body: Some(updated),
+        base: None,
+    };
+    gh.client
+        .update_pr(gh.owner, gh.repo_name, pr_number, update)
+        .await
+        .with_context(|| format!("Failed to update PR #{pr_number}"))?;
+    Ok(())
+}
 
-    // Find all descendants (branches whose parent is in our chain)
-    let mut i = 0;
-    while i < chain.len() {
-        let parent_name = chain[i].clone();
-        for branch in branches {
-            if branch.parent.as_ref().is_some_and(|p| p == &parent_name)
-                && !chain.contains(&branch.name.to_string())
-            {
-                chain.push(branch.name.to_string());
-            }
-        }
-        i += 1;
+/// Write `notes` into PR `pr_number`'s body, if they aren't there already.
+async fn set_notes(gh: &GitHubContext<'_>, pr_number: u64, notes: &[String]) -> Result<()> {
+    let pr = gh
+        .client
+        .get_pr(gh.owner, gh.repo_name, pr_number)
+        .await
+        .with_context(|| format!("Failed to fetch PR #{pr_number}"))?;
+    let body = pr.body.unwrap_or_default();
+    let updated = pr_body::set_notes(&body, notes);
+    if updated == body {
+        return Ok(());
     }
+    let update = UpdatePullRequest {
+        title: None,
+        body: Some(updated),
+        base: None,
+    };
+    gh.client
+        .update_pr(gh.owner, gh.repo_name, pr_number, update)
+        .await
+        .with_context(|| format!("Failed to update PR #{pr_number}"))?;
+    Ok(())
+}
 
-    chain
+/// Point PR `pr_number`'s `Depends on` line at `parent_pr`, if it doesn't already.
+async fn set_depends_on(
+    gh: &GitHubContext<'_>,
+    pr_number: u64,
+    parent_pr: Option<u64>,
+) -> Result<()> {
+    let pr = gh
+        .client
+        .get_pr(gh.owner, gh.repo_name, pr_number)
+        .await
+        .with_context(|| format!("Failed to fetch PR #{pr_number}"))?;
+    let body = pr.body.unwrap_or_default();
+    let updated = pr_body::set_depends_on(&body, parent_pr);
+    if updated == body {
+        return Ok(());
+    }
+    let update = UpdatePullRequest {
+        title: None,
+        body: Some(updated),
+        base: None,
+    };
+    gh.client
+        .update_pr(gh.owner, gh.repo_name, pr_number, update)
+        .await
+        .with_context(|| format!("Failed to update PR #{pr_number}"))?;
+    Ok(())
 }
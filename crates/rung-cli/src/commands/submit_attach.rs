@@ -0,0 +1,159 @@
+//! `rung submit --attach` - Upload files for PR bodies to link to.
+//!
+//! GitHub's own image uploads (the `user-images` links the web editor makes)
+//! need a browser session, so they can't be made with a token. Instead the
+//! files are committed to an asset branch on origin (`[pr] asset_branch`),
+//! one commit per submit, and the PR bodies link to them there. Each file is
+//! named after its content's hash, so attaching it again changes nothing.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use rung_core::{BranchName, Stack};
+use rung_git::Repository;
+
+use super::utils::push;
+
+/// A file given with `--attach`, read before anything is pushed.
+pub struct Attachment {
+    /// Branch whose PR links to the file.
+    pub branch: String,
+    /// Name of the file on the asset branch.
+    entry: String,
+    /// Name the link shows.
+    label: String,
+    content: Vec<u8>,
+}
+
+impl Attachment {
+    /// Markdown linking to the file on `asset_branch`: an image for image
+    /// files, a plain link otherwise.
+    fn markdown(&self, web_url: &str, asset_branch: &str) -> String {
+        let url = format!("{web_url}/blob/{asset_branch}/{}?raw=true", self.entry);
+        if is_image(&self.label) {
+            format!("![{}]({url})", self.label)
+        } else {
+            format!("[{}]({url})", self.label)
+        }
+    }
+}
+
+/// Read the files given with `--attach`, as `<path>` for the current branch or
+/// `<branch>=<path>` for another one.
+///
+/// Done before submitting, so a missing file or asset branch stops `rung
+/// submit` before anything is pushed.
+pub fn read_attachments(
+    values: &[String],
+    stack: &Stack,
+    current_branch: Option<&str>,
+    asset_branch: Option<&str>,
+) -> Result<Vec<Attachment>> {
+    if values.is_empty() {
+        return Ok(vec![]);
+    }
+    let Some(asset_branch) = asset_branch else {
+        bail!(
+            "--attach needs a branch to upload to - set `asset_branch` under `[pr]`, e.g. `rung config set pr.asset_branch rung-assets`"
+        );
+    };
+    BranchName::new(asset_branch).context("Invalid asset_branch under [pr]")?;
+    if stack.find_branch(asset_branch).is_some() || current_branch == Some(asset_branch) {
+        bail!("The asset branch '{asset_branch}' can't be a stack branch or the one checked out");
+    }
+
+    values
+        .iter()
+        .map(|value| {
+            let (branch, path) = match value.split_once('=') {
+                Some((branch, path)) if stack.find_branch(branch).is_some() => (branch, path),
+                _ => (
+                    current_branch.context(
+                        "--attach needs a branch (`<branch>=<path>`) when not on a branch",
+                    )?,
+                    value.as_str(),
+                ),
+            };
+            if stack.find_branch(branch).is_none() {
+                bail!("Branch '{branch}' is not in the stack");
+            }
+            let content =
+                std::fs::read(path).with_context(|| format!("Failed to read {path} to attach"))?;
+            let label = Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().replace(['[', ']'], ""))
+                .with_context(|| format!("{path} is not a file"))?;
+            let hash = Repository::hash_blob(&content)?.to_string();
+            Ok(Attachment {
+                branch: branch.to_string(),
+                entry: format!("{}-{}", &hash[..12], url_safe(&label)),
+                label,
+                content,
+            })
+        })
+        .collect()
+}
+
+/// Commit the attachments to `asset_branch` on top of origin's copy, push it,
+/// and return the markdown for each branch's PR body.
+///
+/// With `verify`, the push is read back from origin (`general.verify_pushes`).
+pub fn upload(
+    repo: &Repository,
+    attachments: &[Attachment],
+    asset_branch: &BranchName,
+    web_url: &str,
+    verify: bool,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let remote_tip = if repo.remote_branch_exists(asset_branch)? {
+        repo.fetch_remote_branch(asset_branch)?;
+        Some(repo.remote_branch_commit(asset_branch)?)
+    } else {
+        None
+    };
+    let files: Vec<(String, Vec<u8>)> = attachments
+        .iter()
+        .map(|a| (a.entry.clone(), a.content.clone()))
+        .collect();
+    let message = format!("Attach {} file(s) for rung submit", files.len());
+    if let Some(commit) = repo.commit_files(remote_tip, &files, &message)? {
+        repo.set_reference(&format!("refs/heads/{asset_branch}"), commit, "attach")?;
+        push(repo, asset_branch, asset_branch, false, verify)?;
+    }
+
+    let mut links: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for attachment in attachments {
+        links
+            .entry(attachment.branch.clone())
+            .or_default()
+            .push(attachment.markdown(web_url, asset_branch));
+    }
+    Ok(links)
+}
+
+/// Whether GitHub renders `name` inline as an image.
+fn is_image(name: &str) -> bool {
+    let extension = Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    matches!(
+        extension.as_str(),
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp"
+    )
+}
+
+/// `name` with anything that would need escaping in a URL or a markdown link
+/// replaced by `-`.
+fn url_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
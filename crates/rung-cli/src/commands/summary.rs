@@ -0,0 +1,67 @@
+//! `rung summary` command - What was shipped lately, from the operations log.
+
+use anyhow::Result;
+use chrono::{Local, TimeDelta, Utc};
+use rung_core::stats::Recap;
+
+use super::utils::open_repo_and_state_read_only;
+use crate::output;
+
+/// Run the summary command over the `since` up to now, printing a markdown
+/// list for pasting into updates.
+pub fn run(json: bool, since: TimeDelta) -> Result<()> {
+    let (_repo, state) = open_repo_and_state_read_only()?;
+    let operations = state.load_operations()?;
+    let recap = Recap::compute(&operations, Utc::now() - since);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&recap)?);
+        return Ok(());
+    }
+
+    let since = recap.since.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    if recap.is_empty() {
+        output::info(&format!("Nothing recorded since {since}"));
+        output::info(
+            "The summary comes from `rung log --operations` - create, submit, merge and sync to fill it",
+        );
+        return Ok(());
+    }
+
+    output::essential(&format!("Since {since}:"));
+    if recap.branches_created > 0 {
+        output::essential(&format!(
+            "- Created {} branch(es), starting {} new stack(s)",
+            recap.branches_created, recap.stacks_created
+        ));
+    }
+    if !recap.prs_opened.is_empty() {
+        let prs: Vec<String> = recap
+            .prs_opened
+            .iter()
+            .map(|pr| format!("#{} ({})", pr.pr_number, pr.branch))
+            .collect();
+        output::essential(&format!("- Opened {} PR(s): {}", prs.len(), prs.join(", ")));
+    }
+    if recap.prs_updated > 0 {
+        output::essential(&format!(
+            "- Pushed {} update(s) to open PRs",
+            recap.prs_updated
+        ));
+    }
+    if !recap.prs_merged.is_empty() {
+        let prs: Vec<String> = recap
+            .prs_merged
+            .iter()
+            .map(|pr| format!("#{} ({}) into {}", pr.pr_number, pr.branch, pr.into))
+            .collect();
+        output::essential(&format!("- Merged {} PR(s): {}", prs.len(), prs.join(", ")));
+    }
+    if recap.syncs > 0 {
+        output::essential(&format!(
+            "- Synced {} time(s), rebasing {} branch(es), with {} conflict(s)",
+            recap.syncs, recap.rebased, recap.conflicts
+        ));
+    }
+    Ok(())
+}
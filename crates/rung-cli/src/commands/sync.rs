@@ -1,20 +1,36 @@
 //! `rung sync` command - Sync the stack by rebasing all branches.
 //!
-//! This command performs a full sync operation:
-//! 1. Detects PRs merged externally (via GitHub UI)
-//! 2. Updates stack topology for merged branches
+//! When neither the base nor any branch moved since the last sync, it stops
+//! after fetching. Otherwise it performs a full sync operation:
+//! 1. Detects PRs merged externally (via GitHub UI), and PR branches renamed there
+//! 2. Updates stack topology for merged and renamed branches
 //! 3. Rebases remaining branches onto their new parents
 //! 4. Updates GitHub PR base branches
 //! 5. Pushes all synced branches
 
+use std::fmt::Write;
+
 use anyhow::{Context, Result, bail};
-use rung_core::State;
-use rung_core::sync::{self, ExternalMergeInfo, ReconcileResult, ReparentedBranch, SyncResult};
-use rung_git::Repository;
-use rung_github::{Auth, GitHubClient, PullRequestState, UpdatePullRequest};
+use chrono::Utc;
+use rung_core::pr_cache::{PrCache, PrEntry};
+use rung_core::sync::{
+    self, ExternalCloseInfo, ExternalMergeInfo, ExternalRenameInfo, ReconcileResult, RenamedBranch,
+    ReparentedBranch, SyncMark, SyncPlan, SyncResult,
+};
+use rung_core::{BranchName, Config, Stack, State};
+use rung_git::{Oid, Repository};
+use rung_github::{
+    CreateComment, GitHubClient, PullRequestState, RECENTLY_CLOSED_LIMIT, UpdateComment,
+    UpdatePullRequest,
+};
 use serde::Serialize;
 
-use crate::output;
+use super::utils::{
+    ReadyPr, check_origin, commit_index, github_client, github_reader, github_remote, push,
+    pushed_by_others, ready_green_drafts, record_landing, remote_pr,
+};
+use crate::i18n::tr;
+use crate::{actions, exit, output, prompt, timings};
 
 /// JSON output for sync command.
 #[derive(Debug, Serialize)]
@@ -28,6 +44,349 @@ struct SyncOutput {
     conflict_branch: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     conflict_files: Vec<String>,
+    /// Commits on the new base that last touched the conflicting lines.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    conflict_commits: Vec<ConflictBlame>,
+    /// Blobs staged for each conflicting file.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    conflict_stages: Vec<ConflictStage>,
+    /// The branch's own commit being replayed when the conflict hit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict_replaying: Option<ReplayedCommit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exec_failed: Option<ExecFailure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interrupted_at: Option<String>,
+    #[serde(skip_serializing_if = "SyncReport::is_empty")]
+    report: SyncReport,
+}
+
+/// A commit behind a sync conflict, found by blaming the conflicting lines.
+#[derive(Debug, Serialize)]
+struct ConflictBlame {
+    file: String,
+    commit: String,
+    author: String,
+    summary: String,
+}
+
+/// Blobs staged for a conflicting file: the common ancestor's, the new
+/// base's (`ours`), and the replayed commit's (`theirs`).
+#[derive(Debug, Serialize)]
+struct ConflictStage {
+    file: String,
+    base: Option<String>,
+    ours: Option<String>,
+    theirs: Option<String>,
+}
+
+/// The commit a sync was replaying when it stopped on a conflict.
+#[derive(Debug, Serialize)]
+struct ReplayedCommit {
+    commit: String,
+    summary: String,
+}
+
+/// The `--exec` command that paused a sync.
+#[derive(Debug, Serialize)]
+struct ExecFailure {
+    branch: String,
+    command: String,
+}
+
+/// Everything a sync changed, reported together at the end.
+#[derive(Debug, Default, Serialize)]
+struct SyncReport {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    merged: Vec<MergedPr>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    closed: Vec<ClosedPr>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reparented: Vec<Reparented>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    renamed: Vec<Renamed>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    rebased: Vec<Rebased>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    retargeted: Vec<Retargeted>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pushed: Vec<String>,
+    /// PRs commented on after their push (`[sync] push_comment`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    commented: Vec<u64>,
+    /// Drafts marked ready for review once their checks passed
+    /// (`rung submit --draft-until-green`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ready: Vec<ReadyPr>,
+}
+
+#[derive(Debug, Serialize)]
+struct MergedPr {
+    branch: String,
+    pr_number: u64,
+    merged_into: String,
+}
+
+/// A PR closed on GitHub with its branch deleted, whose branch left the stack.
+#[derive(Debug, Serialize)]
+struct ClosedPr {
+    branch: String,
+    pr_number: u64,
+    /// Where its children moved.
+    parent: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Reparented {
+    branch: String,
+    old_parent: String,
+    new_parent: String,
+}
+
+/// A branch whose PR's head branch was renamed on GitHub.
+#[derive(Debug, Serialize)]
+struct Renamed {
+    branch: String,
+    pr_number: u64,
+    /// The PR's head branch now.
+    remote: String,
+    /// Local name now: `remote`, unless that was taken or the branch was
+    /// already pushed under another name.
+    new_name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Rebased {
+    branch: String,
+    old_sha: String,
+    new_sha: String,
+    /// `git range-diff` of the branch's commits, with `--show-range-diff`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range_diff: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Retargeted {
+    pr_number: u64,
+    old_base: String,
+    new_base: String,
+}
+
+impl SyncReport {
+    /// Start a report from the merges and stack cleanup found before rebasing.
+    fn new(reconcile: &ReconcileResult, removed: Vec<String>) -> Self {
+        Self {
+            merged: reconcile
+                .merged
+                .iter()
+                .map(|m| MergedPr {
+                    branch: m.name.clone(),
+                    pr_number: m.pr_number,
+                    merged_into: m.merged_into.clone(),
+                })
+                .collect(),
+            closed: reconcile
+                .closed
+                .iter()
+                .map(|c| ClosedPr {
+                    branch: c.name.clone(),
+                    pr_number: c.pr_number,
+                    parent: c.parent.clone(),
+                })
+                .collect(),
+            reparented: reconcile
+                .reparented
+                .iter()
+                .map(|r| Reparented {
+                    branch: r.name.clone(),
+                    old_parent: r.old_parent.clone(),
+                    new_parent: r.new_parent.clone(),
+                })
+                .collect(),
+            renamed: reconcile
+                .renamed
+                .iter()
+                .map(|r| Renamed {
+                    branch: r.name.to_string(),
+                    pr_number: r.pr_number,
+                    remote: r.remote.to_string(),
+                    new_name: r.new_name.to_string(),
+                })
+                .collect(),
+            removed,
+            ..Self::default()
+        }
+    }
+
+    /// Record the branches a completed sync rebased, from their backed-up tips.
+    fn add_rebased(&mut self, repo: &Repository, state: &State, backup_id: &str) {
+        let (Ok(backup), Ok(stack)) = (state.load_backup(backup_id), state.load_stack()) else {
+            return;
+        };
+        let old_tips: std::collections::HashMap<String, String> = backup.into_iter().collect();
+        for branch in &stack.branches {
+            let Some(old_sha) = old_tips.get(branch.name.as_str()) else {
+                continue;
+            };
+            if let Ok(new) = repo.branch_commit(&branch.name) {
+                let new_sha = new.to_string();
+                if &new_sha != old_sha {
+                    self.rebased.push(Rebased {
+                        branch: branch.name.to_string(),
+                        old_sha: old_sha.clone(),
+                        new_sha,
+                        range_diff: None,
+                    });
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.merged.is_empty()
+            && self.closed.is_empty()
+            && self.reparented.is_empty()
+            && self.renamed.is_empty()
+            && self.removed.is_empty()
+            && self.rebased.is_empty()
+            && self.retargeted.is_empty()
+            && self.pushed.is_empty()
+            && self.commented.is_empty()
+            && self.ready.is_empty()
+    }
+
+    /// Set the sync's step outputs when running in GitHub Actions.
+    fn set_outputs(&self, status: &SyncStatus, branches_rebased: usize) {
+        actions::set_outputs(&[
+            ("status", output::token(status)),
+            ("branches_rebased", branches_rebased.to_string()),
+            ("branches_merged", self.merged.len().to_string()),
+            ("branches_closed", self.closed.len().to_string()),
+            ("branches_pushed", self.pushed.len().to_string()),
+        ]);
+    }
+
+    /// Print one porcelain record per change.
+    fn print_porcelain(&self) {
+        print_reconcile_records(
+            &self.merged,
+            &self.closed,
+            &self.reparented,
+            &self.renamed,
+            &self.removed,
+        );
+        for r in &self.rebased {
+            output::record("rebased", &[&r.branch, &r.old_sha, &r.new_sha]);
+        }
+        for r in &self.retargeted {
+            output::record(
+                "retargeted",
+                &[&r.pr_number.to_string(), &r.old_base, &r.new_base],
+            );
+        }
+        for branch in &self.pushed {
+            output::record("pushed", &[branch]);
+        }
+        for pr in &self.commented {
+            output::record("commented", &[&pr.to_string()]);
+        }
+        for r in &self.ready {
+            output::record("ready", &[&r.branch, &r.pr_number.to_string()]);
+        }
+    }
+
+    /// Print one line per change.
+    fn print(&self) {
+        let short = |sha: &str| sha[..8.min(sha.len())].to_string();
+        let mut lines = vec![];
+        for m in &self.merged {
+            lines.push(tr!(
+                "sync-merged",
+                pr = m.pr_number,
+                branch = m.branch,
+                base = m.merged_into
+            ));
+        }
+        for c in &self.closed {
+            lines.push(tr!("sync-closed", pr = c.pr_number, branch = c.branch));
+        }
+        for r in &self.reparented {
+            lines.push(tr!(
+                "sync-reparented",
+                branch = r.branch,
+                old = r.old_parent,
+                new = r.new_parent
+            ));
+        }
+        for r in &self.renamed {
+            lines.push(if r.new_name == r.branch {
+                tr!(
+                    "sync-renamed-remote",
+                    pr = r.pr_number,
+                    branch = r.branch,
+                    remote = r.remote
+                )
+            } else {
+                tr!(
+                    "sync-renamed",
+                    pr = r.pr_number,
+                    branch = r.branch,
+                    new = r.new_name
+                )
+            });
+        }
+        for branch in &self.removed {
+            lines.push(tr!("sync-removed", branch = branch));
+        }
+        for r in &self.rebased {
+            lines.push(tr!(
+                "sync-rebased",
+                branch = r.branch,
+                old = short(&r.old_sha),
+                new = short(&r.new_sha)
+            ));
+        }
+        for r in &self.retargeted {
+            lines.push(tr!(
+                "sync-retargeted",
+                pr = r.pr_number,
+                old = r.old_base,
+                new = r.new_base
+            ));
+        }
+        if !self.pushed.is_empty() {
+            lines.push(tr!("sync-pushed", branches = self.pushed.join(", ")));
+        }
+        if !self.commented.is_empty() {
+            let prs: Vec<String> = self.commented.iter().map(|pr| format!("#{pr}")).collect();
+            lines.push(tr!("sync-commented", prs = prs.join(", ")));
+        }
+        if !self.ready.is_empty() {
+            let prs: Vec<String> = self
+                .ready
+                .iter()
+                .map(|r| format!("#{}", r.pr_number))
+                .collect();
+            lines.push(tr!("sync-ready", prs = prs.join(", ")));
+        }
+        if !lines.is_empty() {
+            output::info(&format!(
+                "{}\n    {}",
+                tr!("sync-summary"),
+                lines.join("\n    ")
+            ));
+        }
+        for r in &self.rebased {
+            if let Some(range_diff) = &r.range_diff {
+                output::info(&tr!("sync-range-diff", branch = r.branch));
+                for line in range_diff.lines() {
+                    println!("    {line}");
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -36,11 +395,17 @@ enum SyncStatus {
     AlreadySynced,
     Complete,
     Conflict,
+    ExecFailed,
+    Interrupted,
     Aborted,
 }
 
 /// Run the sync command.
-#[allow(clippy::fn_params_excessive_bools, clippy::too_many_lines)]
+#[allow(
+    clippy::fn_params_excessive_bools,
+    clippy::too_many_arguments,
+    clippy::too_many_lines
+)]
 pub fn run(
     json: bool,
     dry_run: bool,
@@ -48,18 +413,21 @@ pub fn run(
     abort: bool,
     no_push: bool,
     base: Option<&str>,
+    exec: Option<&str>,
+    show_range_diff: bool,
 ) -> Result<()> {
     // Open repository
     let repo = Repository::open_current().context("Not inside a git repository")?;
 
     // Get state manager
     let workdir = repo.workdir().context("Cannot run in bare repository")?;
-    let state = State::new(workdir)?;
+    let state = State::for_repository(&repo);
 
     // Ensure initialized
     if !state.is_initialized() {
         bail!("Rung not initialized - run `rung init` first");
     }
+    check_origin(&repo, &state)?;
 
     // Check for conflicting flags
     if continue_ && abort {
@@ -71,7 +439,19 @@ pub fn run(
         if !state.is_sync_in_progress() {
             bail!("No sync in progress to abort");
         }
+        ensure_sync_here(&state, workdir)?;
+        let backup_id = state.load_sync_state()?.backup_id;
+        let details: Vec<String> = state
+            .load_backup(&backup_id)?
+            .iter()
+            .map(|(branch, _)| format!("Discard rebased commits on '{branch}'"))
+            .collect();
+        if !prompt::confirm_destructive(&state, "Abort the sync?", &details)? {
+            output::info(&tr!("sync-abort-cancelled"));
+            return Ok(());
+        }
         sync::abort_sync(&repo, &state)?;
+        SyncReport::default().set_outputs(&SyncStatus::Aborted, 0);
         if json {
             return output_json(&SyncOutput {
                 status: SyncStatus::Aborted,
@@ -79,86 +459,159 @@ pub fn run(
                 backup_id: None,
                 conflict_branch: None,
                 conflict_files: vec![],
+                conflict_commits: vec![],
+                conflict_stages: vec![],
+                conflict_replaying: None,
+                exec_failed: None,
+                interrupted_at: None,
+                report: SyncReport::default(),
             });
         }
-        output::success("Sync aborted - branches restored from backup");
+        output::success(&tr!("sync-aborted"));
         return Ok(());
     }
 
+    // Stop between rebases on Ctrl-C rather than mid-way through one
+    crate::signal::install();
+
     // Handle continue
     if continue_ {
         if !state.is_sync_in_progress() {
             bail!("No sync in progress to continue");
         }
+        ensure_sync_here(&state, workdir)?;
         if !json {
-            output::info("Continuing sync...");
+            output::info(&tr!("sync-continuing"));
         }
-        let result = sync::continue_sync(&repo, &state)?;
+        let result = timings::time("rebases", || sync::continue_sync(&repo, &state))?;
 
         // If sync completed successfully, push the branches
-        if let SyncResult::Complete { .. } = &result {
+        let mut report = SyncReport::default();
+        if let SyncResult::Complete { backup_id, .. } = &result {
+            report.add_rebased(&repo, &state, backup_id);
+            store_range_diffs(&repo, &state, &mut report, backup_id, show_range_diff);
             if !no_push {
-                push_stack_branches(&repo, &state, json)?;
+                report.pushed = timings::time("pushes", || {
+                    push_stack_branches(&repo, &state, json, Some(backup_id))
+                })?;
+                report.commented = timings::time("push comments", || {
+                    comment_on_pushes(&repo, &state, &report, backup_id, json)
+                });
             }
+            report.ready = timings::time("draft readiness", || {
+                mark_green_drafts_ready(&repo, &state, json)
+            });
+            commit_index(&repo, &state, &state.load_stack()?)?;
         }
 
-        return handle_sync_result(result, json);
+        return handle_sync_result(&repo, result, report, json);
     }
 
     // Check for existing sync in progress
     if state.is_sync_in_progress() {
+        ensure_sync_here(&state, workdir)?;
         bail!("Sync already in progress - use --continue to resume or --abort to cancel");
     }
 
     // Ensure working directory is clean
     repo.require_clean()?;
 
-    // Determine base branch: use --base if provided, otherwise query GitHub
-    let base_branch = if let Some(b) = base {
-        b.to_string()
-    } else {
-        let origin_url = repo.origin_url().context("No origin remote configured")?;
-        let (owner, repo_name) = Repository::parse_github_remote(&origin_url)
-            .context("Could not parse GitHub remote URL")?;
-
-        let client = GitHubClient::new(&Auth::auto()).context(
-            "GitHub auth required to detect default branch. Use --base <branch> to specify manually.",
-        )?;
-        let rt = tokio::runtime::Runtime::new()?;
-        rt.block_on(client.get_default_branch(&owner, &repo_name))
-            .context("Could not fetch default branch. Use --base <branch> to specify manually.")?
+    // Determine base branch: use --base if provided, otherwise detect it
+    let config = state.load_config()?;
+    let base_branch = match base {
+        Some(b) => b.to_string(),
+        None => timings::time("base detection", || detect_base_branch(&repo, &config))?,
     };
 
+    let base_name = BranchName::new(base_branch.as_str()).context("Invalid base branch")?;
+
     // === Phase 0: Fetch base branch to ensure we have latest ===
-    if !json {
-        output::info(&format!("Fetching {base_branch}..."));
-    }
-    if let Err(e) = repo.fetch(&base_branch) {
+    // (a repository without an origin, like a local experiment, has nothing to fetch)
+    if repo.origin_url().is_ok() {
         if !json {
-            output::warn(&format!("Could not fetch {base_branch}: {e}"));
+            output::info(&tr!("sync-fetching", branch = base_branch));
+        }
+        if let Err(e) = timings::time("fetch", || repo.fetch(&base_name)) {
+            if !json {
+                output::warn(&tr!("sync-fetch-failed", branch = base_branch, error = e));
+            }
+            // Continue anyway - we'll work with what we have
+        }
+    }
+
+    let mut stack = state.load_stack()?;
+    if repo.origin_url().is_ok() {
+        timings::time("fetch", || refresh_frozen(&repo, &stack, json));
+    }
+    if !dry_run {
+        let mark = SyncMark::capture(&repo, &stack, &base_branch);
+        if mark.is_some() && state.load_sync_mark() == mark {
+            if !json {
+                output::info(&tr!("sync-unchanged"));
+            }
+            return handle_sync_result(
+                &repo,
+                SyncResult::AlreadySynced,
+                SyncReport::default(),
+                json,
+            );
         }
-        // Continue anyway - we'll work with what we have
     }
 
     // === Phase 1: Detect merged PRs and validate PR bases (Active Base Validation) ===
-    let reconcile_result = detect_and_reconcile_merged(&repo, &state, json, &base_branch)?;
+    let mut reconcile_result = timings::time("merged PR detection", || {
+        detect_and_reconcile_merged(&repo, &state, &config, &mut stack, json, &base_branch)
+    })?;
+
+    if dry_run {
+        // Plan against the local branches as they are, before any rename
+        for r in &reconcile_result.renamed {
+            stack.rename_branch(&r.new_name, &r.name);
+        }
+    } else if !reconcile_result.renamed.is_empty() {
+        rename_branches(&repo, &mut stack, &mut reconcile_result.renamed, json);
+    }
 
     // === Phase 2: Remove stale branches ===
-    let stale_result = sync::remove_stale_branches(&repo, &state)?;
-    if !json && !stale_result.removed.is_empty() {
-        output::warn(&format!(
-            "Removed {} stale branch(es) from stack:",
-            stale_result.removed.len()
-        ));
-        for branch in &stale_result.removed {
-            println!("  → {branch}");
+    let stale_result = timings::time("planning", || {
+        sync::remove_stale_branches(&repo, &mut stack)
+    });
+    if !json {
+        for conflict in stack.requirement_conflicts() {
+            output::warn(&tr!(
+                "sync-requires-conflict",
+                branch = conflict.branch,
+                required = conflict.requires
+            ));
         }
     }
 
-    // Load stack (after reconcile and stale branch cleanup)
-    let stack = state.load_stack()?;
+    if dry_run {
+        let plan = if stack.is_empty() {
+            SyncPlan { branches: vec![] }
+        } else {
+            sync::create_sync_plan(&repo, &stack, &base_branch)?
+        };
+        return print_dry_run(
+            &stack,
+            &reconcile_result,
+            &stale_result.removed,
+            &plan,
+            json,
+        );
+    }
+    if !reconcile_result.merged.is_empty()
+        || !reconcile_result.reparented.is_empty()
+        || !reconcile_result.closed.is_empty()
+        || !reconcile_result.renamed.is_empty()
+        || !stale_result.removed.is_empty()
+    {
+        state.save_stack(&stack)?;
+    }
+    let mut report = SyncReport::new(&reconcile_result, stale_result.removed);
 
     if stack.is_empty() {
+        report.set_outputs(&SyncStatus::AlreadySynced, 0);
         if json {
             return output_json(&SyncOutput {
                 status: SyncStatus::AlreadySynced,
@@ -166,60 +619,316 @@ pub fn run(
                 backup_id: None,
                 conflict_branch: None,
                 conflict_files: vec![],
+                conflict_commits: vec![],
+                conflict_stages: vec![],
+                conflict_replaying: None,
+                exec_failed: None,
+                interrupted_at: None,
+                report,
             });
         }
-        output::info("No branches in stack - nothing to sync");
+        report.print();
+        output::info(&tr!("sync-empty"));
         return Ok(());
     }
 
     // === Phase 3: Create and execute sync plan ===
-    let plan = sync::create_sync_plan(&repo, &stack, &base_branch)?;
-
-    if dry_run {
-        if !json {
-            output::info("Dry run - would perform the following:");
-            if !reconcile_result.merged.is_empty() {
-                println!("  Merged PRs detected: {}", reconcile_result.merged.len());
-            }
-            if !plan.is_empty() {
-                println!("  Branches to rebase:");
-                for action in &plan.branches {
-                    println!(
-                        "    → {} (onto {})",
-                        action.branch,
-                        &action.new_base[..8.min(action.new_base.len())]
-                    );
-                }
-            }
-        }
-        return Ok(());
-    }
+    let plan = timings::time("planning", || {
+        sync::create_sync_plan(&repo, &stack, &base_branch)
+    })?;
 
     let sync_result = if plan.is_empty() {
         SyncResult::AlreadySynced
     } else {
         if !json {
-            output::info(&format!("Syncing {} branches...", plan.branches.len()));
+            output::info(&tr!("sync-syncing", count = plan.branches.len()));
         }
-        sync::execute_sync(&repo, &state, plan)?
+        timings::time("rebases", || {
+            sync::execute_sync(&repo, &state, plan, exec, "sync")
+        })?
     };
 
-    // If sync paused on conflict, don't proceed with push/update
-    if let SyncResult::Paused { .. } = &sync_result {
-        return handle_sync_result(sync_result, json);
+    // If sync paused on conflict or was interrupted, don't proceed with push/update
+    if matches!(
+        sync_result,
+        SyncResult::Paused { .. } | SyncResult::ExecFailed { .. } | SyncResult::Interrupted { .. }
+    ) {
+        return handle_sync_result(&repo, sync_result, report, json);
+    }
+    let backup_id = match &sync_result {
+        SyncResult::Complete { backup_id, .. } => Some(backup_id.as_str()),
+        _ => None,
+    };
+    if let Some(backup_id) = backup_id {
+        report.add_rebased(&repo, &state, backup_id);
+        store_range_diffs(&repo, &state, &mut report, backup_id, show_range_diff);
     }
 
     // === Phase 4: Update GitHub PR base branches (reparented + repaired) ===
     if !reconcile_result.reparented.is_empty() || !reconcile_result.repaired.is_empty() {
-        update_pr_bases(&repo, &reconcile_result, json)?;
+        report.retargeted = timings::time("PR base updates", || {
+            update_pr_bases(&repo, &config, &reconcile_result, json)
+        })?;
     }
 
     // === Phase 5: Push all branches ===
     if !no_push {
-        push_stack_branches(&repo, &state, json)?;
+        report.pushed = timings::time("pushes", || {
+            push_stack_branches(&repo, &state, json, backup_id)
+        })?;
+        if let Some(backup_id) = backup_id {
+            report.commented = timings::time("push comments", || {
+                comment_on_pushes(&repo, &state, &report, backup_id, json)
+            });
+        }
+    }
+    report.ready = timings::time("draft readiness", || {
+        mark_green_drafts_ready(&repo, &state, json)
+    });
+
+    let stack = state.load_stack()?;
+    commit_index(&repo, &state, &stack)?;
+    if let Some(mark) = SyncMark::capture(&repo, &stack, &base_branch) {
+        state.save_sync_mark(&mark)?;
     }
+    handle_sync_result(&repo, sync_result, report, json)
+}
+
+/// Mark ready the drafts submitted with `--draft-until-green` whose checks
+/// pass. Failing to check them is a warning, not a failed sync.
+fn mark_green_drafts_ready(repo: &Repository, state: &State, json: bool) -> Vec<ReadyPr> {
+    ready_green_drafts(repo, state, json).unwrap_or_else(|e| {
+        if !json {
+            output::warn(&tr!("sync-ready-failed", error = e));
+        }
+        vec![]
+    })
+}
 
-    handle_sync_result(sync_result, json)
+/// Fail if the sync in progress runs in another worktree, where its rebase
+/// has to be finished.
+fn ensure_sync_here(state: &State, workdir: &std::path::Path) -> Result<()> {
+    if let Some(worktree) = state.load_sync_state()?.other_worktree(workdir) {
+        bail!(
+            "A sync is in progress in another worktree ({}) - continue or abort it there",
+            worktree.display()
+        );
+    }
+    Ok(())
+}
+
+/// JSON output for `sync --dry-run`.
+#[derive(Debug, Serialize)]
+struct DryRunOutput {
+    merged: Vec<MergedPr>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    closed: Vec<ClosedPr>,
+    reparented: Vec<Reparented>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    renamed: Vec<Renamed>,
+    removed: Vec<String>,
+    rebase: Vec<PlannedRebase>,
+    pr_base_updates: Vec<Retargeted>,
+    dry_run: bool,
+}
+
+/// A branch the sync would rebase.
+#[derive(Debug, Serialize)]
+struct PlannedRebase {
+    branch: String,
+    old_base: String,
+    new_base: String,
+    reason: RebaseReason,
+}
+
+/// Why a branch needs rebasing.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)] // Serialized names read as `parent_*`
+enum RebaseReason {
+    /// Its parent has commits the branch isn't based on.
+    ParentMoved,
+    /// Its parent's PR merged, so it moves onto the merge target.
+    ParentMerged,
+    /// Its parent's PR was closed and its branch deleted, so it moves onto
+    /// the grandparent.
+    ParentClosed,
+    /// Its parent is rebased earlier in the same sync.
+    ParentRebased,
+}
+
+impl std::fmt::Display for RebaseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ParentMoved => "parent moved",
+            Self::ParentMerged => "parent merged",
+            Self::ParentClosed => "parent closed",
+            Self::ParentRebased => "parent rebased",
+        })
+    }
+}
+
+/// The owner's configured `base_branch`, or else the repository's default
+/// branch on GitHub.
+fn detect_base_branch(repo: &Repository, config: &Config) -> Result<String> {
+    let remote = github_remote(repo, config)?;
+    if let Some(base) = config
+        .org(&remote.owner)
+        .and_then(|org| org.base_branch.clone())
+    {
+        return Ok(base);
+    }
+
+    let client = github_client(&remote, config).context(
+        "GitHub auth required to detect default branch. Use --base <branch> to specify manually.",
+    )?;
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(client.get_default_branch(&remote.owner, &remote.repo))
+        .context("Could not fetch default branch. Use --base <branch> to specify manually.")
+}
+
+/// Print what a sync would do, without changing anything.
+fn print_dry_run(
+    stack: &Stack,
+    reconcile: &ReconcileResult,
+    removed: &[String],
+    plan: &SyncPlan,
+    json: bool,
+) -> Result<()> {
+    let report = SyncReport::new(reconcile, removed.to_vec());
+    let mut rebase: Vec<PlannedRebase> = vec![];
+    for action in &plan.branches {
+        let moved_from = reconcile
+            .reparented
+            .iter()
+            .find(|r| r.name == action.branch)
+            .map(|r| r.old_parent.as_str());
+        let parent_rebased = stack
+            .find_branch(&action.branch)
+            .and_then(|b| b.parent.as_ref())
+            .is_some_and(|parent| rebase.iter().any(|r| r.branch == parent.as_str()));
+        let reason = match moved_from {
+            Some(old) if reconcile.closed.iter().any(|c| c.name == old) => {
+                RebaseReason::ParentClosed
+            }
+            Some(_) => RebaseReason::ParentMerged,
+            None if parent_rebased => RebaseReason::ParentRebased,
+            None => RebaseReason::ParentMoved,
+        };
+        rebase.push(PlannedRebase {
+            branch: action.branch.clone(),
+            old_base: action.old_base.clone(),
+            new_base: action.new_base.clone(),
+            reason,
+        });
+    }
+    let pr_base_updates: Vec<Retargeted> = reconcile
+        .reparented
+        .iter()
+        .chain(&reconcile.repaired)
+        .filter_map(|r| {
+            r.pr_number.map(|pr_number| Retargeted {
+                pr_number,
+                old_base: r.old_parent.clone(),
+                new_base: r.new_parent.clone(),
+            })
+        })
+        .collect();
+
+    if json {
+        let output = DryRunOutput {
+            merged: report.merged,
+            closed: report.closed,
+            reparented: report.reparented,
+            renamed: report.renamed,
+            removed: report.removed,
+            rebase,
+            pr_base_updates,
+            dry_run: true,
+        };
+        if output::is_porcelain() {
+            output.print_porcelain();
+        } else {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        return Ok(());
+    }
+
+    print_dry_run_text(&report, &rebase, &pr_base_updates);
+    Ok(())
+}
+
+/// Print a dry run's planned changes for people.
+fn print_dry_run_text(
+    report: &SyncReport,
+    rebase: &[PlannedRebase],
+    pr_base_updates: &[Retargeted],
+) {
+    output::info(&tr!("sync-dry-run"));
+    for m in &report.merged {
+        let line = tr!(
+            "sync-dry-run-merged",
+            pr = m.pr_number,
+            branch = m.branch,
+            base = m.merged_into
+        );
+        println!("  {line}");
+    }
+    for c in &report.closed {
+        let line = tr!("sync-dry-run-closed", pr = c.pr_number, branch = c.branch);
+        println!("  {line}");
+    }
+    for r in &report.reparented {
+        let line = tr!(
+            "sync-dry-run-reparent",
+            branch = r.branch,
+            old = r.old_parent,
+            new = r.new_parent
+        );
+        println!("  {line}");
+    }
+    for r in &report.renamed {
+        let line = if r.new_name == r.branch {
+            tr!(
+                "sync-dry-run-renamed-remote",
+                pr = r.pr_number,
+                branch = r.branch,
+                remote = r.remote
+            )
+        } else {
+            tr!(
+                "sync-dry-run-renamed",
+                pr = r.pr_number,
+                branch = r.branch,
+                new = r.new_name
+            )
+        };
+        println!("  {line}");
+    }
+    for branch in &report.removed {
+        println!("  {}", tr!("sync-dry-run-remove", branch = branch));
+    }
+    if !rebase.is_empty() {
+        println!("  {}", tr!("sync-dry-run-rebase"));
+        for r in rebase {
+            let line = tr!(
+                "sync-dry-run-rebase-onto",
+                branch = r.branch,
+                base = &r.new_base[..8.min(r.new_base.len())],
+                reason = r.reason
+            );
+            println!("    {line}");
+        }
+    }
+    for u in pr_base_updates {
+        let line = tr!(
+            "sync-dry-run-retarget",
+            pr = u.pr_number,
+            old = u.old_base,
+            new = u.new_base
+        );
+        println!("  {line}");
+    }
 }
 
 /// Threshold for switching from individual REST calls to batched GraphQL query.
@@ -236,20 +945,25 @@ const BATCH_THRESHOLD: usize = 5;
 /// The second check is a "self-healing" mechanism that detects "ghost parents" - PRs whose
 /// base branch on GitHub points to a deleted branch or doesn't match the stack's expectation.
 ///
-/// For efficiency, uses GraphQL batch fetching when there are more than 5 PRs to check.
+/// When every PR was open at the last check, one listing of recently closed PRs
+/// into the base stands in for fetching each PR (see [`rung_core::pr_cache`]).
+/// Otherwise, for efficiency, uses GraphQL batch fetching when there are more
+/// than 5 PRs to check.
 fn detect_and_reconcile_merged(
     repo: &Repository,
     state: &State,
+    config: &Config,
+    stack: &mut Stack,
     json: bool,
     base_branch: &str,
 ) -> Result<ReconcileResult> {
-    let stack = state.load_stack()?;
-
-    // Collect branches with PRs to check
+    // Collect branches with PRs to check, with parents as named on GitHub
     let branches_with_prs: Vec<_> = stack
         .branches
         .iter()
-        .filter_map(|b| b.pr.map(|pr| (b.name.to_string(), b.parent.clone(), pr)))
+        .filter_map(|b| {
+            b.pr.map(|pr| (b.name.to_string(), remote_parent(stack, b), pr))
+        })
         .collect();
 
     if branches_with_prs.is_empty() {
@@ -257,39 +971,269 @@ fn detect_and_reconcile_merged(
     }
 
     // Get GitHub client
-    let origin_url = repo.origin_url().context("No origin remote configured")?;
-    let (owner, repo_name) = Repository::parse_github_remote(&origin_url)
-        .context("Could not parse GitHub remote URL")?;
+    let remote = github_remote(repo, config)?;
+    let (owner, repo_name) = (remote.owner.clone(), remote.repo.clone());
 
-    let Ok(client) = GitHubClient::new(&Auth::auto()) else {
-        // If GitHub auth fails, skip merge detection but continue with sync
-        if !json {
-            output::warn("GitHub auth unavailable - skipping merge detection");
-        }
-        return Ok(ReconcileResult::default());
-    };
+    let client = github_reader(&remote, config, json)?;
 
     let rt = tokio::runtime::Runtime::new()?;
 
     if !json {
-        output::info("Checking PRs and validating bases...");
+        output::info(&tr!("sync-checking-prs"));
+    }
+
+    // Check each PR's status and validate base branches
+    let started = Utc::now();
+    let pr_numbers: Vec<u64> = branches_with_prs.iter().map(|(_, _, pr)| *pr).collect();
+    let mut checks = PrChecks {
+        cache: state.load_pr_cache(),
+        ..PrChecks::default()
+    };
+
+    let unchecked = if checks.cache.covers(&pr_numbers, started) {
+        check_recently_closed(
+            &rt,
+            &client,
+            &owner,
+            &repo_name,
+            &branches_with_prs,
+            base_branch,
+            &mut checks,
+        )
+    } else {
+        None
+    };
+
+    if let Some(unchecked) = unchecked {
+        // Only PRs whose base may have moved need a look of their own
+        fetch_prs_individually(
+            &rt,
+            &client,
+            &owner,
+            &repo_name,
+            &unchecked,
+            base_branch,
+            json,
+            &mut checks,
+        );
+    } else {
+        fetch_every_pr(
+            &rt,
+            &client,
+            &owner,
+            &repo_name,
+            &branches_with_prs,
+            base_branch,
+            json,
+            &mut checks,
+        );
+        checks.cache.verified_at = Some(started);
+    }
+
+    checks.cache.checked_at = Some(started);
+    checks.cache.retain(&pr_numbers);
+    state.save_pr_cache(&checks.cache)?;
+    for merged in &checks.merged {
+        if let (Some(branch), Some(commit)) =
+            (stack.find_branch(&merged.branch_name), &merged.merge_commit)
+        {
+            record_landing(repo, state, stack, branch, commit, &merged.merged_into);
+        }
+    }
+    let result = reconcile_checks(repo, stack, checks, base_branch)?;
+
+    if !json {
+        for ghost in &result.repaired {
+            output::warn(&tr!(
+                "sync-ghost-parent",
+                pr = ghost.pr_number.unwrap_or_default(),
+                branch = ghost.name,
+                actual = ghost.old_parent,
+                expected = ghost.new_parent
+            ));
+        }
+    }
+    Ok(result)
+}
+
+/// Bring the stack in line with what checking its PRs found: merged, closed
+/// and renamed PRs, and bases that don't match.
+fn reconcile_checks(
+    repo: &Repository,
+    stack: &mut Stack,
+    checks: PrChecks,
+    base_branch: &str,
+) -> Result<ReconcileResult> {
+    let PrChecks {
+        merged: merged_prs,
+        closed,
+        ghost_parents,
+        heads,
+        ..
+    } = checks;
+
+    // Reconcile the stack for merged PRs, and add ghost parent repairs
+    let mut result = if merged_prs.is_empty() {
+        ReconcileResult::default()
+    } else {
+        sync::reconcile_merged(stack, &merged_prs)?
+    };
+    // Other people's PRs aren't ours to retarget: follow their bases instead
+    let frozen = |name: &str| stack.find_branch(name).is_some_and(|b| b.frozen);
+    result.reparented.retain(|r| !frozen(&r.name));
+    let (followed, repaired): (Vec<_>, Vec<_>) =
+        ghost_parents.into_iter().partition(|g| frozen(&g.name));
+    result.repaired = repaired;
+    for mut ghost in followed {
+        if let (Some(branch), Ok(base)) = (
+            stack.find_branch_mut(&ghost.name),
+            BranchName::new(&ghost.old_parent),
+        ) {
+            branch.parent = Some(base);
+            std::mem::swap(&mut ghost.old_parent, &mut ghost.new_parent);
+            ghost.pr_number = None;
+            result.reparented.push(ghost);
+        }
+    }
+
+    // A closed PR only leaves the stack once its branch is gone from GitHub
+    let deleted: Vec<ExternalCloseInfo> = closed
+        .into_iter()
+        .filter(|(_, head)| {
+            BranchName::new(head.as_str())
+                .is_ok_and(|head| repo.remote_branch_exists(&head).is_ok_and(|exists| !exists))
+        })
+        .map(|(info, _)| info)
+        .collect();
+    sync::reconcile_closed(stack, &deleted, base_branch, &mut result);
+
+    let renamed = renamed_heads(stack, heads);
+    sync::reconcile_renamed(
+        stack,
+        &renamed,
+        |name| repo.branch_exists(name),
+        &mut result,
+    );
+    Ok(result)
+}
+
+/// Open PRs whose head branch isn't their branch's remote name, because it
+/// was renamed on GitHub.
+fn renamed_heads(stack: &Stack, heads: Vec<(String, u64, String)>) -> Vec<ExternalRenameInfo> {
+    heads
+        .into_iter()
+        .filter(|(branch, _, head)| {
+            stack
+                .find_branch(branch)
+                .is_some_and(|b| b.remote_name() != head)
+        })
+        .map(|(branch_name, pr_number, new_head)| ExternalRenameInfo {
+            branch_name,
+            pr_number,
+            new_head,
+        })
+        .collect()
+}
+
+/// What checking the stack's PRs found so far.
+#[derive(Default)]
+struct PrChecks {
+    /// PRs merged externally.
+    merged: Vec<ExternalMergeInfo>,
+    /// PRs closed without merging, with their head branches as named on
+    /// GitHub. Their branches may still exist.
+    closed: Vec<(ExternalCloseInfo, String)>,
+    /// Open PRs whose base doesn't match the stack.
+    ghost_parents: Vec<ReparentedBranch>,
+    /// Open PRs by branch, with their head branches as named on GitHub.
+    heads: Vec<(String, u64, String)>,
+    /// Last-known PR states, updated with every PR looked at.
+    cache: PrCache,
+}
+
+/// A branch's parent as named on the remote.
+fn remote_parent(
+    stack: &rung_core::stack::Stack,
+    branch: &rung_core::stack::StackBranch,
+) -> Option<rung_core::BranchName> {
+    let parent = branch.parent.as_ref()?;
+    let remote = stack.find_branch(parent).and_then(|p| p.remote.clone());
+    Some(remote.unwrap_or_else(|| parent.clone()))
+}
+
+/// Check the stack's PRs against one listing of recently closed PRs,
+/// returning the PRs that still need fetching: those whose base as last seen
+/// differs from the stack's. Returns `None` if the listing failed or doesn't
+/// reach back to the last check.
+#[allow(clippy::too_many_arguments)]
+fn check_recently_closed(
+    rt: &tokio::runtime::Runtime,
+    client: &GitHubClient,
+    owner: &str,
+    repo_name: &str,
+    branches_with_prs: &[(String, Option<rung_core::BranchName>, u64)],
+    base_branch: &str,
+    checks: &mut PrChecks,
+) -> Option<Vec<(String, Option<rung_core::BranchName>, u64)>> {
+    let closed = rt
+        .block_on(client.list_recently_closed_prs(owner, repo_name))
+        .ok()?;
+    if closed.len() >= RECENTLY_CLOSED_LIMIT
+        && !closed
+            .last()
+            .and_then(|pr| pr.updated_at.as_deref())
+            .is_some_and(|at| checks.cache.reaches_back(at))
+    {
+        return None;
     }
 
-    // Check each PR's status and validate base branches
-    let mut merged_prs = Vec::new();
-    let mut ghost_parents = Vec::new();
+    let mut unchecked = vec![];
+    for (branch_name, stack_parent, pr_number) in branches_with_prs {
+        if let Some(pr) = closed.iter().find(|pr| pr.number == *pr_number) {
+            process_pr_result(
+                pr,
+                branch_name,
+                stack_parent.as_ref(),
+                *pr_number,
+                base_branch,
+                checks,
+            );
+            continue;
+        }
+        let expected_base = stack_parent.as_ref().map_or(base_branch, |p| p.as_str());
+        if checks
+            .cache
+            .get(*pr_number)
+            .is_none_or(|entry| entry.base != expected_base)
+        {
+            unchecked.push((branch_name.clone(), stack_parent.clone(), *pr_number));
+        }
+    }
+    Some(unchecked)
+}
 
-    // Use batch fetch for larger stacks to reduce API calls
-    if branches_with_prs.len() > BATCH_THRESHOLD {
+/// Fetch every PR, in one GraphQL call for larger stacks.
+#[allow(clippy::too_many_arguments)]
+fn fetch_every_pr(
+    rt: &tokio::runtime::Runtime,
+    client: &GitHubClient,
+    owner: &str,
+    repo_name: &str,
+    branches_with_prs: &[(String, Option<rung_core::BranchName>, u64)],
+    base_branch: &str,
+    json: bool,
+    checks: &mut PrChecks,
+) {
+    // GraphQL needs a token
+    if branches_with_prs.len() > BATCH_THRESHOLD && !client.is_anonymous() {
         // Batch fetch all PRs in a single GraphQL call
         let pr_numbers: Vec<u64> = branches_with_prs.iter().map(|(_, _, pr)| *pr).collect();
-
-        let batch_result = rt.block_on(client.get_prs_batch(&owner, &repo_name, &pr_numbers));
+        let batch_result = rt.block_on(client.get_prs_batch(owner, repo_name, &pr_numbers));
 
         match batch_result {
             Ok(pr_map) => {
                 // Process the batch results
-                for (branch_name, stack_parent, pr_number) in &branches_with_prs {
+                for (branch_name, stack_parent, pr_number) in branches_with_prs {
                     if let Some(pr) = pr_map.get(pr_number) {
                         process_pr_result(
                             pr,
@@ -297,76 +1241,43 @@ fn detect_and_reconcile_merged(
                             stack_parent.as_ref(),
                             *pr_number,
                             base_branch,
-                            json,
-                            &mut merged_prs,
-                            &mut ghost_parents,
+                            checks,
                         );
                     } else if !json {
-                        output::warn(&format!("Could not fetch PR #{pr_number}"));
+                        output::warn(&tr!("sync-pr-fetch-failed", pr = pr_number));
                     }
                 }
             }
             Err(e) => {
                 if !json {
-                    output::warn(&format!(
-                        "Batch PR fetch failed, falling back to individual: {e}"
-                    ));
+                    output::warn(&tr!("sync-batch-failed", error = e));
                 }
                 // Fall back to individual fetches on actual failure
                 fetch_prs_individually(
-                    &rt,
-                    &client,
-                    &owner,
-                    &repo_name,
-                    &branches_with_prs,
+                    rt,
+                    client,
+                    owner,
+                    repo_name,
+                    branches_with_prs,
                     base_branch,
                     json,
-                    &mut merged_prs,
-                    &mut ghost_parents,
+                    checks,
                 );
             }
         }
     } else {
         // Small stack: use individual REST calls
         fetch_prs_individually(
-            &rt,
-            &client,
-            &owner,
-            &repo_name,
-            &branches_with_prs,
+            rt,
+            client,
+            owner,
+            repo_name,
+            branches_with_prs,
             base_branch,
             json,
-            &mut merged_prs,
-            &mut ghost_parents,
+            checks,
         );
     }
-
-    // If no merged PRs, just return with ghost parent repairs
-    if merged_prs.is_empty() {
-        return Ok(ReconcileResult {
-            merged: vec![],
-            reparented: vec![],
-            repaired: ghost_parents,
-        });
-    }
-
-    // Reconcile the stack for merged PRs
-    let mut result = sync::reconcile_merged(state, &merged_prs)?;
-
-    // Add ghost parent repairs
-    result.repaired = ghost_parents;
-
-    // Report re-parented branches
-    if !json {
-        for reparent in &result.reparented {
-            output::info(&format!(
-                "Re-parented {} → {} (was {})",
-                reparent.name, reparent.new_parent, reparent.old_parent
-            ));
-        }
-    }
-
-    Ok(result)
 }
 
 /// Fetch PRs individually using REST API (for small stacks or as fallback).
@@ -379,10 +1290,15 @@ fn fetch_prs_individually(
     branches_with_prs: &[(String, Option<rung_core::BranchName>, u64)],
     base_branch: &str,
     json: bool,
-    merged_prs: &mut Vec<ExternalMergeInfo>,
-    ghost_parents: &mut Vec<ReparentedBranch>,
+    checks: &mut PrChecks,
 ) {
     for (branch_name, stack_parent, pr_number) in branches_with_prs {
+        if client.is_anonymous() && client.budget_spent() {
+            if !json {
+                output::warn(&tr!("github-budget-spent"));
+            }
+            break;
+        }
         let pr_result = rt.block_on(client.get_pr(owner, repo_name, *pr_number));
 
         match pr_result {
@@ -393,61 +1309,81 @@ fn fetch_prs_individually(
                     stack_parent.as_ref(),
                     *pr_number,
                     base_branch,
-                    json,
-                    merged_prs,
-                    ghost_parents,
+                    checks,
                 );
             }
             Err(e) => {
                 // Log but don't fail - PR might have been deleted
                 if !json {
-                    output::warn(&format!("Could not check PR #{pr_number}: {e}"));
+                    output::warn(&tr!("sync-pr-check-failed", pr = pr_number, error = e));
                 }
             }
         }
     }
 }
 
+/// Fetch the heads of the frozen branches' PRs (see `rung rebase
+/// --onto-pr`), so their children are rebased onto the latest.
+fn refresh_frozen(repo: &Repository, stack: &Stack, json: bool) {
+    for branch in stack.branches.iter().filter(|b| b.frozen) {
+        let Some(pr) = branch.pr else { continue };
+        if let Err(e) = repo.fetch_pull_head(pr, &branch.name) {
+            if !json {
+                output::warn(&tr!("sync-fetch-failed", branch = branch.name, error = e));
+            }
+        }
+    }
+}
+
 /// Process a fetched PR: detect merges and ghost parents.
-#[allow(clippy::too_many_arguments)]
 fn process_pr_result(
     pr: &rung_github::PullRequest,
     branch_name: &str,
     stack_parent: Option<&rung_core::BranchName>,
     pr_number: u64,
     base_branch: &str,
-    json: bool,
-    merged_prs: &mut Vec<ExternalMergeInfo>,
-    ghost_parents: &mut Vec<ReparentedBranch>,
+    checks: &mut PrChecks,
 ) {
+    checks.cache.record(
+        pr_number,
+        PrEntry {
+            state: remote_pr(pr).state,
+            base: pr.base_branch.clone(),
+            updated_at: pr.updated_at.clone(),
+        },
+    );
+
+    if pr.state == PullRequestState::Closed {
+        checks.closed.push((
+            ExternalCloseInfo {
+                branch_name: branch_name.to_string(),
+                pr_number,
+            },
+            pr.head_branch.clone(),
+        ));
+    }
+
     if pr.state == PullRequestState::Merged {
         // PR was merged externally
-        if !json {
-            output::success(&format!(
-                "PR #{} ({}) merged into {}",
-                pr_number, branch_name, pr.base_branch
-            ));
-        }
-
-        merged_prs.push(ExternalMergeInfo {
+        checks.merged.push(ExternalMergeInfo {
             branch_name: branch_name.to_string(),
             pr_number,
             merged_into: pr.base_branch.clone(),
+            merge_commit: pr.merge_commit_sha.clone(),
         });
     } else {
+        if pr.state == PullRequestState::Open {
+            checks
+                .heads
+                .push((branch_name.to_string(), pr_number, pr.head_branch.clone()));
+        }
+
         // PR is still open - validate its base matches our expectation
         let expected_base = stack_parent.map_or(base_branch, |p| p.as_str());
 
         if pr.base_branch != expected_base {
             // Ghost parent detected! PR base doesn't match stack.json
-            if !json {
-                output::warn(&format!(
-                    "Ghost parent: PR #{} ({}) base is '{}' but should be '{}'",
-                    pr_number, branch_name, pr.base_branch, expected_base
-                ));
-            }
-
-            ghost_parents.push(ReparentedBranch {
+            checks.ghost_parents.push(ReparentedBranch {
                 name: branch_name.to_string(),
                 old_parent: pr.base_branch.clone(),
                 new_parent: expected_base.to_string(),
@@ -457,15 +1393,43 @@ fn process_pr_result(
     }
 }
 
-/// Update GitHub PR base branches for re-parented and repaired branches.
+/// Rename the local branches whose PR head branches were renamed on GitHub,
+/// and fetch their new remote branches. A branch that can't be renamed keeps
+/// its name and is pushed under the new one.
+fn rename_branches(
+    repo: &Repository,
+    stack: &mut Stack,
+    renamed: &mut [RenamedBranch],
+    json: bool,
+) {
+    for r in renamed {
+        if r.new_name != r.name {
+            if let Err(e) = repo.rename_branch(&r.name, &r.new_name) {
+                if !json {
+                    output::warn(&tr!("sync-rename-failed", branch = r.name, error = e));
+                }
+                stack.rename_branch(&r.new_name, &r.name);
+                if let Some(branch) = stack.find_branch_mut(&r.name) {
+                    branch.remote = Some(r.remote.clone());
+                }
+                r.new_name.clone_from(&r.name);
+            }
+        }
+        let _ = repo.fetch_remote_branch(&r.remote);
+    }
+}
+
+/// Update GitHub PR base branches for re-parented and repaired branches,
+/// returning the PRs that were retargeted.
 ///
 /// Implements a no-op check: re-fetches current PR state before PATCH to avoid
 /// redundant updates that would trigger unnecessary CI builds and PR timeline noise.
 fn update_pr_bases(
     repo: &Repository,
+    config: &Config,
     reconcile_result: &ReconcileResult,
     json: bool,
-) -> Result<()> {
+) -> Result<Vec<Retargeted>> {
     // Collect all PRs that need updating
     let updates_needed: Vec<_> = reconcile_result
         .reparented
@@ -478,18 +1442,17 @@ fn update_pr_bases(
         .collect();
 
     if updates_needed.is_empty() {
-        return Ok(());
+        return Ok(vec![]);
     }
 
-    let origin_url = repo.origin_url().context("No origin remote configured")?;
-    let (owner, repo_name) = Repository::parse_github_remote(&origin_url)
-        .context("Could not parse GitHub remote URL")?;
+    let remote = github_remote(repo, config)?;
+    let (owner, repo_name) = (remote.owner.clone(), remote.repo.clone());
 
-    let client = GitHubClient::new(&Auth::auto()).context("Failed to authenticate with GitHub")?;
+    let client = github_client(&remote, config).context("Failed to authenticate with GitHub")?;
     let rt = tokio::runtime::Runtime::new()?;
 
     if !json {
-        output::info("Updating PR base branches on GitHub...");
+        output::info(&tr!("sync-updating-bases"));
     }
 
     // Re-fetch current PR states to implement no-op check
@@ -513,14 +1476,13 @@ fn update_pr_bases(
         };
 
     // Apply updates with no-op check
+    let mut retargeted = vec![];
     for (pr_number, new_base, old_base) in updates_needed {
         // No-op check: skip if PR base is already what we want
         if let Some(current_base) = current_states.get(&pr_number) {
             if current_base == &new_base {
                 if !json {
-                    output::info(&format!(
-                        "PR #{pr_number} base already '{new_base}' - skipping"
-                    ));
+                    output::info(&tr!("sync-base-unchanged", pr = pr_number, base = new_base));
                 }
                 continue;
             }
@@ -533,22 +1495,20 @@ fn update_pr_bases(
         };
 
         match rt.block_on(client.update_pr(&owner, &repo_name, pr_number, update)) {
-            Ok(_) => {
-                if !json {
-                    output::success(&format!(
-                        "Updated PR #{pr_number} base: {old_base} → {new_base}"
-                    ));
-                }
-            }
+            Ok(_) => retargeted.push(Retargeted {
+                pr_number,
+                old_base,
+                new_base,
+            }),
             Err(e) => {
                 if !json {
-                    output::warn(&format!("Could not update PR #{pr_number}: {e}"));
+                    output::warn(&tr!("sync-base-update-failed", pr = pr_number, error = e));
                 }
             }
         }
     }
 
-    Ok(())
+    Ok(retargeted)
 }
 
 /// Fetch current base branches for a list of PRs individually.
@@ -568,43 +1528,384 @@ fn fetch_current_bases(
     result
 }
 
-/// Push all branches in the stack to remote.
-fn push_stack_branches(repo: &Repository, state: &State, json: bool) -> Result<()> {
-    let stack = state.load_stack()?;
+/// Push all branches in the stack to remote, returning the ones pushed.
+///
+/// Branches whose remote has commits rung never saw locally, or whose
+/// remote tip someone else pushed, are only force-pushed after confirmation;
+/// otherwise they are skipped.
+fn push_stack_branches(
+    repo: &Repository,
+    state: &State,
+    json: bool,
+    backup_id: Option<&str>,
+) -> Result<Vec<String>> {
+    let mut stack = state.load_stack()?;
+    // Other people's PR branches are theirs to push
+    stack.branches.retain(|b| !b.frozen);
+    let verify = state.load_config()?.general.verify_pushes;
 
     if stack.is_empty() {
-        return Ok(());
+        return Ok(vec![]);
+    }
+
+    let old_tips: std::collections::HashMap<String, String> = backup_id
+        .and_then(|id| state.load_backup(id).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let mut skipped = diverged_from_remote(repo, &stack, &old_tips);
+    if !skipped.is_empty() {
+        let details: Vec<String> = skipped
+            .iter()
+            .map(|name| {
+                format!("'{name}' has commits on the remote that are not in the local branch")
+            })
+            .collect();
+        let question = "Force-push over commits that only exist on the remote?";
+        match prompt::confirm_destructive(state, question, &details) {
+            Ok(true) => skipped.clear(),
+            Ok(false) => {}
+            Err(e) => {
+                if !json {
+                    output::warn(&e.to_string());
+                }
+            }
+        }
+    }
+
+    let names: Vec<&str> = stack
+        .branches
+        .iter()
+        .map(|b| b.name.as_str())
+        .filter(|name| !skipped.iter().any(|s| s == name))
+        .collect();
+    let mut theirs: Vec<String> = vec![];
+    let others = pushed_by_others(repo, &stack, &names);
+    if !others.is_empty() {
+        let details: Vec<String> = others
+            .iter()
+            .map(|(name, who)| format!("'{name}' was last pushed by {who}"))
+            .collect();
+        let question = "Force-push over branches someone else pushed?";
+        match prompt::confirm_destructive(state, question, &details) {
+            Ok(true) => {}
+            Ok(false) => theirs = others.into_iter().map(|(name, _)| name).collect(),
+            Err(e) => {
+                if !json {
+                    output::warn(&e.to_string());
+                }
+                theirs = others.into_iter().map(|(name, _)| name).collect();
+            }
+        }
     }
 
     if !json {
-        output::info("Pushing to remote...");
+        output::info(&tr!("sync-pushing"));
     }
 
-    let mut pushed = 0;
-    for branch in &stack.branches {
+    let mut pushed = vec![];
+    for (i, branch) in stack.branches.iter().enumerate() {
+        if rung_core::interrupt::is_requested() {
+            output::warn(&tr!(
+                "sync-push-interrupted",
+                count = stack.branches.len() - i
+            ));
+            break;
+        }
+        if skipped.contains(&branch.name.to_string()) {
+            if !json {
+                output::warn(&tr!("sync-push-diverged", branch = branch.name));
+            }
+            continue;
+        }
+        if theirs.contains(&branch.name.to_string()) {
+            if !json {
+                output::warn(&tr!("sync-push-theirs", branch = branch.name));
+            }
+            continue;
+        }
         if repo.branch_exists(&branch.name) {
-            match repo.push(&branch.name, true) {
-                Ok(()) => {
-                    pushed += 1;
-                }
+            match push(repo, &branch.name, branch.remote_name(), true, verify) {
+                Ok(()) => pushed.push(branch.name.to_string()),
                 Err(e) => {
                     if !json {
-                        output::warn(&format!("Could not push {}: {e}", branch.name));
+                        output::warn(&tr!(
+                            "sync-push-failed",
+                            branch = branch.name,
+                            error = e.root_cause()
+                        ));
                     }
                 }
             }
         }
     }
 
-    if !json && pushed > 0 {
-        output::success(&format!("Pushed {pushed} branch(es)"));
+    Ok(pushed)
+}
+
+/// A rebased branch's own commits, `old_base..old` before the sync and
+/// `new_base..new` after it.
+struct RebasedRange {
+    old_base: Oid,
+    old: Oid,
+    new_base: Oid,
+    new: Oid,
+}
+
+impl RebasedRange {
+    /// Find the range from the branch's parent, whose tip before the sync is
+    /// `old_parent` when the parent was rebased too.
+    fn find(
+        repo: &Repository,
+        parent: &str,
+        rebased: &Rebased,
+        old_parent: Option<&str>,
+    ) -> Result<Self> {
+        let old = Oid::from_str(&rebased.old_sha)?;
+        let new = Oid::from_str(&rebased.new_sha)?;
+        let parent_tip = repo.branch_commit(parent)?;
+        let old_parent = old_parent
+            .and_then(|sha| Oid::from_str(sha).ok())
+            .unwrap_or(parent_tip);
+        Ok(Self {
+            old_base: repo.merge_base(old, old_parent)?,
+            old,
+            new_base: repo.merge_base(new, parent_tip)?,
+            new,
+        })
+    }
+}
+
+/// Store the range-diff of each rebased branch with the sync's backup, and
+/// add them to the report when `show` is set. Best effort: a branch whose
+/// range-diff fails is left out.
+fn store_range_diffs(
+    repo: &Repository,
+    state: &State,
+    report: &mut SyncReport,
+    backup_id: &str,
+    show: bool,
+) {
+    let (Ok(stack), Ok(backup)) = (state.load_stack(), state.load_backup(backup_id)) else {
+        return;
+    };
+    let old_tips: std::collections::HashMap<String, String> = backup.into_iter().collect();
+
+    let mut stored = String::new();
+    for rebased in &mut report.rebased {
+        let Some(parent) = stack
+            .find_branch(&rebased.branch)
+            .and_then(|b| b.parent.as_ref())
+        else {
+            continue;
+        };
+        let old_parent = old_tips.get(parent.as_str()).map(String::as_str);
+        let Ok(range) = RebasedRange::find(repo, parent, rebased, old_parent) else {
+            continue;
+        };
+        let Ok(range_diff) = repo.range_diff(range.old_base, range.old, range.new_base, range.new)
+        else {
+            continue;
+        };
+        let _ = writeln!(stored, "{}:\n{range_diff}", rebased.branch);
+        if show {
+            rebased.range_diff = Some(range_diff);
+        }
+    }
+    if !stored.is_empty() {
+        let _ = state.save_range_diff(backup_id, &stored); // Best effort
+    }
+}
+
+/// Marks the comment `[sync] push_comment` keeps up to date on each PR.
+const PUSH_COMMENT_MARKER: &str = "<!-- rung-sync -->";
+
+/// Comment on the PR of each rebased branch that was pushed, when
+/// `[sync] push_comment` is on, returning the PRs commented on.
+///
+/// Best effort: the branches are already pushed, so failures are warnings.
+fn comment_on_pushes(
+    repo: &Repository,
+    state: &State,
+    report: &SyncReport,
+    backup_id: &str,
+    json: bool,
+) -> Vec<u64> {
+    let warn = |message: &str| {
+        if !json {
+            output::warn(message);
+        }
+    };
+    let (Ok(config), Ok(stack), Ok(backup)) = (
+        state.load_config(),
+        state.load_stack(),
+        state.load_backup(backup_id),
+    ) else {
+        return vec![];
+    };
+    if !config.sync.push_comment {
+        return vec![];
+    }
+    let old_tips: std::collections::HashMap<String, String> = backup.into_iter().collect();
+
+    let comments: Vec<(u64, String)> = report
+        .rebased
+        .iter()
+        .filter(|r| report.pushed.contains(&r.branch))
+        .filter_map(|r| {
+            let branch = stack.find_branch(&r.branch)?;
+            let parent = branch.parent.as_ref()?;
+            let old_parent = old_tips.get(parent.as_str()).map(String::as_str);
+            let body = push_comment(repo, &stack, parent, r, old_parent).ok()?;
+            Some((branch.pr?, body))
+        })
+        .collect();
+    if comments.is_empty() {
+        return vec![];
+    }
+
+    let client = github_remote(repo, &config)
+        .and_then(|remote| Ok((github_client(&remote, &config)?, remote)));
+    let (client, remote) = match client {
+        Ok(client) => client,
+        Err(e) => {
+            warn(&tr!("sync-comment-failed", error = e));
+            return vec![];
+        }
+    };
+    let Ok(rt) = tokio::runtime::Runtime::new() else {
+        return vec![];
+    };
+    let mut commented = vec![];
+    for (pr, body) in comments {
+        match rt.block_on(upsert_push_comment(&client, &remote, pr, body)) {
+            Ok(()) => commented.push(pr),
+            Err(e) => warn(&tr!("sync-pr-comment-failed", pr = pr, error = e)),
+        }
     }
+    commented
+}
+
+/// Describe a rebased branch's push: its new base, and whether its own
+/// commits changed along the way.
+fn push_comment(
+    repo: &Repository,
+    stack: &Stack,
+    parent: &str,
+    rebased: &Rebased,
+    old_parent: Option<&str>,
+) -> Result<String> {
+    let RebasedRange {
+        old_base,
+        old,
+        new_base,
+        new,
+    } = RebasedRange::find(repo, parent, rebased, old_parent)?;
+
+    let was = repo.count_commits_between(old_base, old)?;
+    let now = repo.count_commits_between(new_base, new)?;
+    let change = if was != now {
+        format!("{now} commit(s), was {was}")
+    } else if repo.patch_id(old_base, old)? == repo.patch_id(new_base, new)? {
+        "no content changes".to_string()
+    } else {
+        "the changes differ from before, e.g. from resolving conflicts".to_string()
+    };
+    Ok(format!(
+        "{PUSH_COMMENT_MARKER}\nRebased onto `{}` @ {} by `rung sync`: {change}.",
+        stack.remote_name_of(parent),
+        &new_base.to_string()[..7]
+    ))
+}
 
+/// Create the push comment on `pr`, or update the one already there.
+async fn upsert_push_comment(
+    client: &GitHubClient,
+    remote: &rung_git::GitHubRemote,
+    pr: u64,
+    body: String,
+) -> Result<()> {
+    let comments = client
+        .list_pr_comments(&remote.owner, &remote.repo, pr)
+        .await?;
+    let existing = comments.iter().find(|c| {
+        c.body
+            .as_ref()
+            .is_some_and(|b| b.starts_with(PUSH_COMMENT_MARKER))
+    });
+    match existing {
+        Some(comment) => {
+            client
+                .update_pr_comment(
+                    &remote.owner,
+                    &remote.repo,
+                    comment.id,
+                    UpdateComment { body },
+                )
+                .await?;
+        }
+        None => {
+            client
+                .create_pr_comment(&remote.owner, &remote.repo, pr, CreateComment { body })
+                .await?;
+        }
+    }
     Ok(())
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn handle_sync_result(result: SyncResult, json: bool) -> Result<()> {
+/// Branches whose remote-tracking ref has commits that neither the local
+/// branch nor its pre-sync tip contain. Force-pushing them would drop work
+/// pushed from somewhere else; rewriting commits we had is expected.
+fn diverged_from_remote(
+    repo: &Repository,
+    stack: &rung_core::Stack,
+    old_tips: &std::collections::HashMap<String, String>,
+) -> Vec<String> {
+    let contains = |tip: Oid, commit: Oid| {
+        repo.merge_base(tip, commit)
+            .is_ok_and(|base| base == commit)
+    };
+
+    stack
+        .branches
+        .iter()
+        .filter(|b| {
+            let (Ok(local), Ok(remote)) = (
+                repo.branch_commit(&b.name),
+                repo.remote_branch_commit(b.remote_name()),
+            ) else {
+                return false;
+            };
+            let old = old_tips
+                .get(b.name.as_str())
+                .and_then(|sha| Oid::from_str(sha).ok());
+            !contains(local, remote) && !old.is_some_and(|old| contains(old, remote))
+        })
+        .map(|b| b.name.to_string())
+        .collect()
+}
+
+#[allow(clippy::unnecessary_wraps, clippy::too_many_lines)]
+fn handle_sync_result(
+    repo: &Repository,
+    result: SyncResult,
+    report: SyncReport,
+    json: bool,
+) -> Result<()> {
+    if !json {
+        report.print();
+    }
+    let (status, branches_rebased) = match &result {
+        SyncResult::AlreadySynced => (SyncStatus::AlreadySynced, 0),
+        SyncResult::Complete {
+            branches_rebased, ..
+        } => (SyncStatus::Complete, *branches_rebased),
+        SyncResult::Paused { .. } => (SyncStatus::Conflict, 0),
+        SyncResult::ExecFailed { .. } => (SyncStatus::ExecFailed, 0),
+        SyncResult::Interrupted { .. } => (SyncStatus::Interrupted, 0),
+    };
+    report.set_outputs(&status, branches_rebased);
+
     match result {
         SyncResult::AlreadySynced => {
             if json {
@@ -614,9 +1915,15 @@ fn handle_sync_result(result: SyncResult, json: bool) -> Result<()> {
                     backup_id: None,
                     conflict_branch: None,
                     conflict_files: vec![],
+                    conflict_commits: vec![],
+                    conflict_stages: vec![],
+                    conflict_replaying: None,
+                    exec_failed: None,
+                    interrupted_at: None,
+                    report,
                 });
             }
-            output::success("Stack is already up-to-date");
+            output::success(&tr!("sync-up-to-date"));
         }
         SyncResult::Complete {
             branches_rebased,
@@ -629,18 +1936,46 @@ fn handle_sync_result(result: SyncResult, json: bool) -> Result<()> {
                     backup_id: Some(backup_id),
                     conflict_branch: None,
                     conflict_files: vec![],
+                    conflict_commits: vec![],
+                    conflict_stages: vec![],
+                    conflict_replaying: None,
+                    exec_failed: None,
+                    interrupted_at: None,
+                    report,
                 });
             }
-            output::success(&format!(
-                "Synced {branches_rebased} branches (backup: {})",
-                &backup_id[..8.min(backup_id.len())]
+            output::success(&tr!(
+                "sync-complete",
+                count = branches_rebased,
+                backup = &backup_id[..8.min(backup_id.len())]
             ));
+            if branches_rebased > 0 && report.pushed.is_empty() {
+                output::hint(&tr!("sync-submit-hint"));
+            }
         }
         SyncResult::Paused {
             at_branch,
             conflict_files,
             backup_id,
         } => {
+            exit::set(exit::Code::Paused);
+            actions::set_outputs(&[("conflict_branch", at_branch.clone())]);
+            actions::annotate(
+                actions::Level::Error,
+                &format!(
+                    "Sync stopped on a conflict in '{at_branch}': {}",
+                    conflict_files.join(", ")
+                ),
+            );
+            let conflict_commits = blame_conflicts(repo, &conflict_files);
+            let conflict_stages = stage_conflicts(repo);
+            let conflict_replaying = repo.replaying_commit().and_then(|oid| {
+                let info = repo.commit_info(oid).ok()?;
+                Some(ReplayedCommit {
+                    commit: oid.to_string(),
+                    summary: info.summary,
+                })
+            });
             if json {
                 return output_json(&SyncOutput {
                     status: SyncStatus::Conflict,
@@ -648,23 +1983,271 @@ fn handle_sync_result(result: SyncResult, json: bool) -> Result<()> {
                     backup_id: Some(backup_id),
                     conflict_branch: Some(at_branch),
                     conflict_files,
+                    conflict_commits,
+                    conflict_stages,
+                    conflict_replaying,
+                    exec_failed: None,
+                    interrupted_at: None,
+                    report,
                 });
             }
-            output::warn(&format!("Conflict in branch '{at_branch}'"));
-            output::info("Conflicting files:");
+            output::warn(&tr!("sync-conflict", branch = at_branch));
+            if let Some(replayed) = &conflict_replaying {
+                output::info(&tr!(
+                    "sync-replaying",
+                    commit = &replayed.commit[..7],
+                    summary = replayed.summary
+                ));
+            }
+            output::info(&tr!("sync-conflict-files"));
             for file in &conflict_files {
-                println!("  → {file}");
+                match conflict_stages.iter().find(|s| &s.file == file) {
+                    Some(stage) => println!("  → {file} ({})", stage.describe()),
+                    None => println!("  → {file}"),
+                }
+                for blame in conflict_commits.iter().filter(|b| &b.file == file) {
+                    println!(
+                        "      {} {} ({})",
+                        &blame.commit[..7],
+                        blame.summary,
+                        blame.author
+                    );
+                }
             }
             println!();
-            output::info("Resolve conflicts, then run: rung sync --continue");
-            output::info("Or abort with: rung sync --abort");
+            output::info(&tr!("sync-conflict-resolve"));
+            output::info(&tr!("sync-abort-hint"));
+            output::hint(&tr!(
+                "hint-stage-resolved",
+                files = conflict_files.join(" ")
+            ));
+        }
+        SyncResult::ExecFailed {
+            at_branch,
+            command,
+            backup_id,
+        } => {
+            exit::set(exit::Code::Paused);
+            actions::annotate(
+                actions::Level::Error,
+                &format!("Sync stopped: `{command}` failed on '{at_branch}'"),
+            );
+            if json {
+                return output_json(&SyncOutput {
+                    status: SyncStatus::ExecFailed,
+                    branches_rebased: None,
+                    backup_id: Some(backup_id),
+                    conflict_branch: None,
+                    conflict_files: vec![],
+                    conflict_commits: vec![],
+                    conflict_stages: vec![],
+                    conflict_replaying: None,
+                    exec_failed: Some(ExecFailure {
+                        branch: at_branch,
+                        command,
+                    }),
+                    interrupted_at: None,
+                    report,
+                });
+            }
+            output::warn(&tr!(
+                "sync-exec-failed",
+                command = command,
+                branch = at_branch
+            ));
+            output::info(&tr!("sync-exec-fix"));
+            output::info(&tr!("sync-abort-hint"));
+        }
+        SyncResult::Interrupted {
+            at_branch,
+            backup_id,
+        } => {
+            exit::set(exit::Code::Interrupted);
+            if json {
+                return output_json(&SyncOutput {
+                    status: SyncStatus::Interrupted,
+                    branches_rebased: None,
+                    backup_id: Some(backup_id),
+                    conflict_branch: None,
+                    conflict_files: vec![],
+                    conflict_commits: vec![],
+                    conflict_stages: vec![],
+                    conflict_replaying: None,
+                    exec_failed: None,
+                    interrupted_at: Some(at_branch),
+                    report,
+                });
+            }
+            output::warn(&tr!("sync-interrupted", branch = at_branch));
+            output::info(&tr!("sync-interrupted-resume"));
+            output::info(&tr!("sync-interrupted-abort"));
         }
     }
     Ok(())
 }
 
-/// Output sync result as JSON.
+/// The commits behind each conflict, best effort: a file that can't be
+/// blamed just lists none.
+fn blame_conflicts(repo: &Repository, files: &[String]) -> Vec<ConflictBlame> {
+    files
+        .iter()
+        .flat_map(|file| {
+            repo.conflict_blame(file)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |commit| ConflictBlame {
+                    file: file.clone(),
+                    commit: commit.id.to_string(),
+                    author: commit.author,
+                    summary: commit.summary,
+                })
+        })
+        .collect()
+}
+
+/// The staged blobs of each conflicting file, best effort.
+fn stage_conflicts(repo: &Repository) -> Vec<ConflictStage> {
+    let sha = |oid: Option<Oid>| oid.map(|oid| oid.to_string());
+    repo.conflict_stages()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|stages| ConflictStage {
+            file: stages.path,
+            base: sha(stages.base),
+            ours: sha(stages.ours),
+            theirs: sha(stages.theirs),
+        })
+        .collect()
+}
+
+impl ConflictStage {
+    /// Abbreviated blobs, e.g. `base 1a2b3c4, ours 5d6e7f8, theirs deleted`.
+    fn describe(&self) -> String {
+        fn short(blob: Option<&String>) -> &str {
+            blob.map_or("deleted", |b| &b[..7])
+        }
+        format!(
+            "base {}, ours {}, theirs {}",
+            short(self.base.as_ref()),
+            short(self.ours.as_ref()),
+            short(self.theirs.as_ref())
+        )
+    }
+}
+
+/// Output sync result as JSON, or as porcelain records with `--porcelain`.
 fn output_json(output: &SyncOutput) -> Result<()> {
-    println!("{}", serde_json::to_string_pretty(output)?);
+    if output::is_porcelain() {
+        output.print_porcelain();
+    } else {
+        println!("{}", serde_json::to_string_pretty(output)?);
+    }
     Ok(())
 }
+
+impl SyncOutput {
+    /// Print the result as porcelain records, `status <status>` first.
+    fn print_porcelain(&self) {
+        output::record("status", &[&output::token(&self.status)]);
+        if let Some(backup_id) = &self.backup_id {
+            output::record("backup", &[backup_id]);
+        }
+        if let Some(branch) = &self.conflict_branch {
+            output::record("conflict", &[branch]);
+        }
+        for file in &self.conflict_files {
+            output::record("conflict-file", &[file]);
+        }
+        for blame in &self.conflict_commits {
+            output::record("conflict-commit", &[&blame.commit, &blame.file]);
+        }
+        if let Some(replayed) = &self.conflict_replaying {
+            output::record("conflict-replaying", &[&replayed.commit]);
+        }
+        for stage in &self.conflict_stages {
+            let blob = |blob: &Option<String>| blob.clone().unwrap_or_else(|| "-".into());
+            output::record(
+                "conflict-stages",
+                &[
+                    &stage.file,
+                    &blob(&stage.base),
+                    &blob(&stage.ours),
+                    &blob(&stage.theirs),
+                ],
+            );
+        }
+        if let Some(failure) = &self.exec_failed {
+            output::record("exec-failed", &[&failure.branch, &failure.command]);
+        }
+        if let Some(branch) = &self.interrupted_at {
+            output::record("interrupted", &[branch]);
+        }
+        self.report.print_porcelain();
+    }
+}
+
+impl DryRunOutput {
+    /// Print the plan as porcelain records, after `status dry_run`.
+    fn print_porcelain(&self) {
+        output::record("status", &["dry_run"]);
+        print_reconcile_records(
+            &self.merged,
+            &self.closed,
+            &self.reparented,
+            &self.renamed,
+            &self.removed,
+        );
+        for rebase in &self.rebase {
+            output::record(
+                "would-rebase",
+                &[
+                    &rebase.branch,
+                    &rebase.old_base,
+                    &rebase.new_base,
+                    &output::token(&rebase.reason),
+                ],
+            );
+        }
+        for retarget in &self.pr_base_updates {
+            output::record(
+                "would-retarget",
+                &[
+                    &retarget.pr_number.to_string(),
+                    &retarget.old_base,
+                    &retarget.new_base,
+                ],
+            );
+        }
+    }
+}
+
+/// Porcelain records for the merges and stack cleanup found before rebasing.
+fn print_reconcile_records(
+    merged: &[MergedPr],
+    closed: &[ClosedPr],
+    reparented: &[Reparented],
+    renamed: &[Renamed],
+    removed: &[String],
+) {
+    for m in merged {
+        output::record(
+            "merged",
+            &[&m.branch, &m.pr_number.to_string(), &m.merged_into],
+        );
+    }
+    for c in closed {
+        output::record("closed", &[&c.branch, &c.pr_number.to_string(), &c.parent]);
+    }
+    for r in reparented {
+        output::record("reparented", &[&r.branch, &r.old_parent, &r.new_parent]);
+    }
+    for r in renamed {
+        output::record(
+            "renamed",
+            &[&r.branch, &r.pr_number.to_string(), &r.remote, &r.new_name],
+        );
+    }
+    for branch in removed {
+        output::record("removed", &[branch]);
+    }
+}
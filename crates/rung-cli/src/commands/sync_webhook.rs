@@ -0,0 +1,291 @@
+//! `rung sync --from-webhook` - Restack every PR stack on a branch that was
+//! just pushed to, for GitHub Actions and other automation.
+//!
+//! Works from GitHub alone, so it needs no `rung init` or local stack: a
+//! stack is a chain of open PRs based on one another, rooted on the pushed
+//! branch. Each branch is rebased onto its parent's new tip and force-pushed
+//! with a lease. A branch that conflicts is left alone, along with everything
+//! stacked on it, and the other stacks carry on.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use rung_core::remote_status::{self, RemotePr};
+use rung_core::{BranchName, Config, State};
+use rung_git::{Oid, Repository};
+use serde::Serialize;
+
+use super::utils::{check_origin, github_client, github_remote, load_config, push, remote_pr};
+use crate::{actions, exit, output};
+
+/// Prefix for the local branches rebased and pushed, so branches of the same
+/// name in the checkout are never touched.
+pub const WORK_PREFIX: &str = "rung-webhook/";
+
+/// JSON output, for the workflow to act on (e.g. comment on conflicting PRs).
+#[derive(Debug, Default, Serialize)]
+struct WebhookOutput {
+    base: String,
+    rebased: Vec<BranchRef>,
+    up_to_date: Vec<BranchRef>,
+    conflicts: Vec<Conflict>,
+    skipped: Vec<Skipped>,
+    pushed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct BranchRef {
+    branch: String,
+    pr: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Conflict {
+    branch: String,
+    pr: u64,
+    files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Skipped {
+    branch: String,
+    pr: u64,
+    reason: String,
+}
+
+/// Run sync in webhook mode against `base`, or the branch named by the
+/// GitHub Actions push event when `base` isn't given.
+pub fn run(json: bool, base: Option<&str>, no_push: bool) -> Result<()> {
+    let repo = Repository::open_current().context("Not inside a git repository")?;
+    if repo.is_bare() {
+        bail!("Cannot run in bare repository");
+    }
+    let state = State::for_repository(&repo);
+    check_origin(&repo, &state)?;
+    repo.require_clean()?;
+
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => event_base()?,
+    };
+    let config = load_config(&repo, &state)?;
+    let remote = github_remote(&repo, &config)?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let open: Vec<_> = rt
+        .block_on(client.list_open_prs(&remote.owner, &remote.repo))
+        .context("Failed to list open PRs")?
+        .iter()
+        .map(remote_pr)
+        .collect();
+    let stacked = remote_status::stacked_on(&base, &open);
+
+    repo.fetch_remote_branch(&BranchName::new(base.as_str()).context("Invalid base branch")?)
+        .with_context(|| format!("Failed to fetch {base}"))?;
+    let original = repo.current_branch().ok();
+    let original_head = repo.resolve_commit("HEAD")?;
+
+    let mut report = WebhookOutput {
+        base: base.clone(),
+        pushed: !no_push,
+        ..WebhookOutput::default()
+    };
+    // New tips of the base and of each branch handled so far
+    let mut new_tips = HashMap::from([(base.clone(), repo.remote_branch_commit(&base)?)]);
+    let mut old_tips: HashMap<String, Oid> = HashMap::new();
+
+    for pr in stacked {
+        let branch = pr.head.clone();
+        let outcome = match new_tips.get(&pr.base) {
+            Some(&new_base) => restack(&repo, &config, pr, new_base, &mut old_tips, no_push)?,
+            None => Outcome::Skipped(format!("{} was not restacked", pr.base)),
+        };
+        match outcome {
+            Outcome::Rebased(tip) => {
+                new_tips.insert(branch.clone(), tip);
+                report.rebased.push(BranchRef {
+                    branch,
+                    pr: pr.number,
+                });
+            }
+            Outcome::UpToDate(tip) => {
+                new_tips.insert(branch.clone(), tip);
+                report.up_to_date.push(BranchRef {
+                    branch,
+                    pr: pr.number,
+                });
+            }
+            Outcome::Conflict(files) => report.conflicts.push(Conflict {
+                branch,
+                pr: pr.number,
+                files,
+            }),
+            Outcome::Skipped(reason) => report.skipped.push(Skipped {
+                branch,
+                pr: pr.number,
+                reason,
+            }),
+        }
+    }
+
+    // Put the checkout back and drop the work branches
+    match &original {
+        Some(branch) => repo.checkout(branch)?,
+        None => repo.checkout_detached(original_head)?,
+    }
+    for head in old_tips.keys() {
+        let _ = repo.delete_branch(&format!("{WORK_PREFIX}{head}")); // Best effort
+    }
+
+    if !report.conflicts.is_empty() {
+        exit::set(exit::Code::Paused);
+    }
+    report_to_actions(&report);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+/// What became of one PR's branch.
+enum Outcome {
+    /// Rebased (and pushed, unless `--no-push`) to this tip.
+    Rebased(Oid),
+    /// Already on its base's tip.
+    UpToDate(Oid),
+    /// The rebase conflicted in these files, and was aborted.
+    Conflict(Vec<String>),
+    /// Not restacked, for this reason.
+    Skipped(String),
+}
+
+/// Rebase `pr`'s branch onto `new_base` on a work branch and push it,
+/// recording the branch's tip before the rebase in `old_tips`.
+fn restack(
+    repo: &Repository,
+    config: &Config,
+    pr: &RemotePr,
+    new_base: Oid,
+    old_tips: &mut HashMap<String, Oid>,
+    no_push: bool,
+) -> Result<Outcome> {
+    let head = match BranchName::new(pr.head.as_str()) {
+        Ok(head) => head,
+        Err(e) => return Ok(Outcome::Skipped(e.to_string())),
+    };
+    if let Err(e) = repo.fetch_remote_branch(&head) {
+        return Ok(Outcome::Skipped(format!("could not fetch: {e}")));
+    }
+    let tip = repo.remote_branch_commit(&pr.head)?;
+    old_tips.insert(pr.head.clone(), tip);
+
+    // Commits from the parent's old tip on are the branch's own
+    let old_parent = old_tips.get(&pr.base).copied().unwrap_or(new_base);
+    let old_base = repo.merge_base(tip, old_parent)?;
+    if old_base == new_base {
+        return Ok(Outcome::UpToDate(tip));
+    }
+
+    let work = BranchName::new(format!("{WORK_PREFIX}{head}"))?;
+    if repo.branch_exists(&work) {
+        repo.reset_branch(&work, tip)?;
+    } else {
+        repo.create_branch_at(&work, tip)?;
+    }
+    repo.checkout(&work)?;
+    let options = rung_core::sync::rebase_options(config, None);
+    match repo.rebase_onto_from(new_base, old_base, options) {
+        Ok(()) => {}
+        Err(rung_git::Error::RebaseConflict(files)) => {
+            let _ = repo.rebase_abort(); // Best effort
+            return Ok(Outcome::Conflict(files));
+        }
+        Err(e) => {
+            let _ = repo.rebase_abort(); // Best effort
+            return Ok(Outcome::Skipped(format!("rebase failed: {e}")));
+        }
+    }
+
+    if !no_push {
+        if let Err(e) = push(repo, &work, &head, true, config.general.verify_pushes) {
+            return Ok(Outcome::Skipped(e.root_cause().to_string()));
+        }
+    }
+    Ok(Outcome::Rebased(repo.branch_commit(&work)?))
+}
+
+/// The pushed-to branch from the GitHub Actions event payload.
+fn event_base() -> Result<String> {
+    let Some(path) = std::env::var_os("GITHUB_EVENT_PATH") else {
+        bail!("No base branch - pass --base <branch>, or run from a GitHub Actions push event");
+    };
+    let event: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(&path).context("Failed to read the GitHub event payload")?,
+    )
+    .context("Invalid GitHub event payload")?;
+    event["ref"]
+        .as_str()
+        .and_then(|r| r.strip_prefix("refs/heads/"))
+        .map(String::from)
+        .context("The GitHub event isn't a branch push - pass --base <branch>")
+}
+
+/// Annotate each conflict and set the counts as step outputs, when running
+/// in GitHub Actions.
+fn report_to_actions(report: &WebhookOutput) {
+    for conflict in &report.conflicts {
+        actions::annotate(
+            actions::Level::Warning,
+            &format!(
+                "Restacking {} (#{}) conflicts in: {}",
+                conflict.branch,
+                conflict.pr,
+                conflict.files.join(", ")
+            ),
+        );
+    }
+    actions::set_outputs(&[
+        ("branches_rebased", report.rebased.len().to_string()),
+        ("branches_up_to_date", report.up_to_date.len().to_string()),
+        ("conflicts", report.conflicts.len().to_string()),
+        ("skipped", report.skipped.len().to_string()),
+    ]);
+}
+
+fn print_report(report: &WebhookOutput) {
+    let total = report.rebased.len()
+        + report.up_to_date.len()
+        + report.conflicts.len()
+        + report.skipped.len();
+    if total == 0 {
+        output::info(&format!("No open PR stacks on {}", report.base));
+        return;
+    }
+    for branch in &report.rebased {
+        let action = if report.pushed {
+            "Rebased and pushed"
+        } else {
+            "Rebased"
+        };
+        output::success(&format!("{action} {} (#{})", branch.branch, branch.pr));
+    }
+    for branch in &report.up_to_date {
+        output::info(&format!("{} (#{}) is up to date", branch.branch, branch.pr));
+    }
+    for conflict in &report.conflicts {
+        output::warn(&format!(
+            "Conflict in {} (#{}): {}",
+            conflict.branch,
+            conflict.pr,
+            conflict.files.join(", ")
+        ));
+    }
+    for skipped in &report.skipped {
+        output::warn(&format!(
+            "Skipped {} (#{}): {}",
+            skipped.branch, skipped.pr, skipped.reason
+        ));
+    }
+}
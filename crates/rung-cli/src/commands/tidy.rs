@@ -0,0 +1,243 @@
+//! `rung tidy` command - Suggest cleanups and apply the ones picked.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use inquire::MultiSelect;
+use rung_core::remote_status::RemotePrState;
+use rung_core::{Config, Stack, State};
+use rung_git::Repository;
+use serde::Serialize;
+
+use super::utils::{has_no_commits, load_config, open_repo_and_state, tip_time};
+use crate::{output, prompt};
+
+/// A cleanup `rung tidy` suggests.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Cleanup {
+    /// A local branch whose PR merged.
+    Merged { branch: String, pr: u64 },
+    /// A stack branch without a PR that hasn't been touched in a while.
+    Stale { branch: String, idle_days: i64 },
+    /// A stack branch with no commits of its own.
+    Empty { branch: String },
+    /// Sync backups beyond `backup_retention`.
+    Backups { count: usize },
+}
+
+impl Cleanup {
+    /// The local branch the cleanup deletes, if any.
+    fn branch(&self) -> Option<&str> {
+        match self {
+            Self::Merged { branch, .. } | Self::Stale { branch, .. } | Self::Empty { branch } => {
+                Some(branch)
+            }
+            Self::Backups { .. } => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Merged { branch, pr } => format!("Delete '{branch}' - PR #{pr} merged"),
+            Self::Stale { branch, idle_days } => {
+                format!("Delete '{branch}' - no PR, idle for {idle_days} days")
+            }
+            Self::Empty { branch } => format!("Delete '{branch}' - no commits of its own"),
+            Self::Backups { count } => format!("Remove {count} old sync backup(s)"),
+        }
+    }
+}
+
+/// JSON output for the tidy command.
+#[derive(Debug, Serialize)]
+struct TidyOutput<'a> {
+    cleanups: &'a [Cleanup],
+    applied: bool,
+}
+
+/// Run the tidy command. With `all`, every cleanup is applied after
+/// confirming; otherwise they're picked from a list. Branches without a PR
+/// are suggested after `older_than` idle days (`stale_after_days` by default).
+pub fn run(json: bool, all: bool, older_than: Option<u32>) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let config = load_config(&repo, &state)?;
+    let mut stack = state.load_stack()?;
+    let older_than = older_than.unwrap_or(config.general.stale_after_days);
+
+    let cleanups = find_cleanups(&repo, &state, &stack, &config, older_than);
+    if cleanups.is_empty() {
+        if json {
+            return output_json(&cleanups, false);
+        }
+        output::info("Nothing to tidy");
+        return Ok(());
+    }
+
+    let labels: Vec<String> = cleanups.iter().map(Cleanup::describe).collect();
+    let selected: Vec<&Cleanup> = if all {
+        if !prompt::confirm_destructive(&state, "Apply every cleanup?", &labels)? {
+            output::info("Tidy cancelled");
+            return Ok(());
+        }
+        cleanups.iter().collect()
+    } else if json {
+        return output_json(&cleanups, false);
+    } else if !prompt::is_interactive() {
+        for label in &labels {
+            output::essential(&format!("  {label}"));
+        }
+        output::info(
+            "Run `rung tidy` in a terminal to pick cleanups, or pass --all --yes to apply them all",
+        );
+        return Ok(());
+    } else {
+        let picked = MultiSelect::new("Cleanups to apply:", labels.clone())
+            .prompt()
+            .context("Prompt cancelled")?;
+        cleanups
+            .iter()
+            .zip(&labels)
+            .filter(|(_, label)| picked.contains(label))
+            .map(|(cleanup, _)| cleanup)
+            .collect()
+    };
+    if selected.is_empty() {
+        output::info("Nothing selected");
+        return Ok(());
+    }
+
+    repo.require_no_operation()?;
+    apply(&repo, &state, &mut stack, &config, &selected, json)?;
+    if json {
+        return output_json(&cleanups, true);
+    }
+    Ok(())
+}
+
+/// Cleanups worth doing: local branches whose PRs merged (as `rung sync`
+/// last saw, or recorded in the landings), leaf branches without a PR idle
+/// for `older_than` days, branches with no commits of their own, and
+/// backups beyond the retention. The current branch and branches with
+/// children that would need rebasing are left alone.
+fn find_cleanups(
+    repo: &Repository,
+    state: &State,
+    stack: &Stack,
+    config: &Config,
+    older_than: u32,
+) -> Vec<Cleanup> {
+    let current = repo.current_branch().ok();
+    let prs = state.load_pr_cache();
+    let now = Utc::now();
+    let mut cleanups = vec![];
+
+    for branch in &stack.branches {
+        let name = branch.name.as_str();
+        if branch.frozen || current.as_deref() == Some(name) || !repo.branch_exists(name) {
+            continue;
+        }
+        let leaf = stack.children_of(name).is_empty();
+        let merged = branch.pr.filter(|pr| {
+            prs.get(*pr)
+                .is_some_and(|entry| entry.state == RemotePrState::Merged)
+        });
+        if let Some(pr) = merged {
+            if leaf {
+                cleanups.push(Cleanup::Merged {
+                    branch: name.to_string(),
+                    pr,
+                });
+            }
+        } else if branch
+            .parent
+            .as_deref()
+            .is_some_and(|parent| has_no_commits(repo, name, parent))
+        {
+            cleanups.push(Cleanup::Empty {
+                branch: name.to_string(),
+            });
+        } else if branch.pr.is_none() && leaf {
+            let idle_days = branch.idle_days(now, tip_time(repo, name));
+            if idle_days >= i64::from(older_than) {
+                cleanups.push(Cleanup::Stale {
+                    branch: name.to_string(),
+                    idle_days,
+                });
+            }
+        }
+    }
+
+    // Landed branches left behind locally, unless they moved since
+    for landing in state.load_landings().landings {
+        let seen = cleanups.iter().any(|c| c.branch() == Some(&landing.branch));
+        if seen
+            || stack.find_branch(&landing.branch).is_some()
+            || current.as_deref() == Some(landing.branch.as_str())
+        {
+            continue;
+        }
+        let unchanged = repo
+            .branch_commit(&landing.branch)
+            .is_ok_and(|tip| tip.to_string() == landing.tip);
+        if unchanged {
+            cleanups.push(Cleanup::Merged {
+                branch: landing.branch,
+                pr: landing.pr_number,
+            });
+        }
+    }
+
+    let excess = state
+        .backup_count()
+        .saturating_sub(config.general.backup_retention);
+    if excess > 0 {
+        cleanups.push(Cleanup::Backups { count: excess });
+    }
+    cleanups
+}
+
+/// Apply `selected`: deleted branches leave the stack (their children move
+/// to their parent) before the branches themselves go. Reports each
+/// deletion unless `json`.
+fn apply(
+    repo: &Repository,
+    state: &State,
+    stack: &mut Stack,
+    config: &Config,
+    selected: &[&Cleanup],
+    json: bool,
+) -> Result<()> {
+    let branches: Vec<&str> = selected.iter().filter_map(|c| c.branch()).collect();
+    let before = stack.len();
+    for name in &branches {
+        stack.splice_out(name);
+    }
+    if stack.len() != before {
+        state.save_stack(stack)?;
+    }
+
+    for name in branches {
+        let tip = repo.branch_commit(name)?.to_string();
+        repo.delete_branch(name)?;
+        if !json {
+            output::success(&format!("Deleted '{name}' (was {})", &tip[..8]));
+        }
+    }
+    if selected
+        .iter()
+        .any(|c| matches!(c, Cleanup::Backups { .. }))
+    {
+        let removed = state.cleanup_backups(config.general.backup_retention)?;
+        if !json {
+            output::success(&format!("Removed {removed} old sync backup(s)"));
+        }
+    }
+    Ok(())
+}
+
+/// Output the cleanups as JSON.
+fn output_json(cleanups: &[Cleanup], applied: bool) -> Result<()> {
+    let output = TidyOutput { cleanups, applied };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//! `rung tutorial` command - A guided tour of stacked branches in a throwaway
+//! repository.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use crate::{output, prompt};
+
+/// One step of the tour: some explanation, an optional change to the demo
+/// repository, then the commands to run in it.
+struct Step {
+    title: &'static str,
+    text: &'static str,
+    setup: Setup,
+    commands: &'static [&'static [&'static str]],
+}
+
+/// What a step does to the repository before its command.
+enum Setup {
+    None,
+    /// Write a file in the working tree.
+    Write(&'static str, &'static str),
+    /// Commit a new file on `main`, as a teammate's merged PR would.
+    AdvanceMain(&'static str),
+}
+
+const STEPS: &[Step] = &[
+    Step {
+        title: "Set up rung",
+        text: "This demo repository has a single commit on `main`. `rung init` starts \
+               tracking a stack of branches in it (stored under .git/rung).",
+        setup: Setup::None,
+        commands: &[&["init"]],
+    },
+    Step {
+        title: "Create the first branch",
+        text: "A stack is a chain of small branches, each reviewed as its own PR. \
+               With a message, `rung create` stages your changes, commits them and \
+               names the branch after the message.",
+        setup: Setup::Write("greeting.txt", "Hello!\n"),
+        commands: &[&["create", "-m", "Add greeting"]],
+    },
+    Step {
+        title: "Stack a second branch on top",
+        text: "The next change builds on the first, so it goes on a new branch whose \
+               parent is `add-greeting` rather than `main`.",
+        setup: Setup::Write("farewell.txt", "Goodbye!\n"),
+        commands: &[&["create", "-m", "Add farewell"]],
+    },
+    Step {
+        title: "Look at the stack",
+        text: "`rung status` shows each branch, its parent and whether it is up to date.",
+        setup: Setup::None,
+        commands: &[&["status"]],
+    },
+    Step {
+        title: "Move around the stack",
+        text: "`rung prv` checks out the parent branch and `rung nxt` the child.",
+        setup: Setup::None,
+        commands: &[&["prv"], &["nxt"]],
+    },
+    Step {
+        title: "Main moves on",
+        text: "Meanwhile a teammate's PR lands on `main`. Your stack is now based on an \
+               old commit, which status reports as diverged.",
+        setup: Setup::AdvanceMain("teammate.txt"),
+        commands: &[&["status"]],
+    },
+    Step {
+        title: "Sync the stack",
+        text: "`rung sync` rebases every branch onto its parent's new tip, parents \
+               first. (Here it skips pushing, as there is no GitHub remote.)",
+        setup: Setup::None,
+        commands: &[&["sync", "--base", "main", "--no-push"], &["status"]],
+    },
+];
+
+/// Run the tutorial.
+pub fn run(keep: bool) -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("rung-tutorial-{}", std::process::id()));
+    let result = tour(&dir);
+
+    if keep {
+        output::info(&format!("Demo repository kept at {}", dir.display()));
+    } else {
+        let _ = fs::remove_dir_all(&dir); // Best effort
+    }
+    result
+}
+
+fn tour(dir: &Path) -> Result<()> {
+    create_demo_repo(dir)?;
+    let exe = std::env::current_exe().context("Could not locate the rung executable")?;
+    let interactive = prompt::is_interactive();
+
+    println!("{}", "Welcome to rung!".bold());
+    println!(
+        "This tour runs real rung commands in a throwaway repository at {}.\n\
+         Nothing here touches GitHub or your own repositories.",
+        dir.display()
+    );
+
+    for step in STEPS {
+        if interactive {
+            pause()?;
+        }
+        println!();
+        println!("{}", format!("── {} ──", step.title).bold());
+        println!("{}", step.text);
+        match step.setup {
+            Setup::None => {}
+            Setup::Write(file, content) => fs::write(dir.join(file), content)?,
+            Setup::AdvanceMain(file) => advance_main(dir, file)?,
+        }
+
+        for args in step.commands {
+            println!();
+            println!("{}", format!("$ rung {}", shell_words(args)).cyan());
+            let status = Command::new(&exe)
+                .args(*args)
+                .current_dir(dir)
+                .status()
+                .with_context(|| format!("Failed to run rung {}", args.join(" ")))?;
+            if !status.success() {
+                bail!("rung {} failed in the demo repository", args.join(" "));
+            }
+        }
+    }
+
+    println!();
+    println!("{}", "── Next steps ──".bold());
+    println!(
+        "In a real repository, `rung submit` pushes every branch and opens one PR per\n\
+         branch, each based on its parent. When the bottom PR is approved, `rung merge`\n\
+         merges it and moves the rest of the stack onto `main`. See `rung --help`."
+    );
+    output::success("Tutorial complete");
+    Ok(())
+}
+
+/// Wait for Enter.
+fn pause() -> Result<()> {
+    println!();
+    print!("{}", "Press Enter to continue...".dimmed());
+    std::io::Write::flush(&mut std::io::stdout())?;
+    std::io::stdin().read_line(&mut String::new())?;
+    Ok(())
+}
+
+/// Quote arguments containing spaces, for display.
+fn shell_words(args: &[&str]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.contains(' ') {
+                format!("\"{arg}\"")
+            } else {
+                (*arg).to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A repository with one commit on `main` and its own committer identity.
+fn create_demo_repo(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    git(dir, &["init", "-q"])?;
+    git(dir, &["symbolic-ref", "HEAD", "refs/heads/main"])?;
+    git(dir, &["config", "user.name", "Rung Tutorial"])?;
+    git(dir, &["config", "user.email", "tutorial@example.com"])?;
+    git(dir, &["config", "commit.gpgsign", "false"])?;
+    fs::write(dir.join("README.md"), "# Demo\n")?;
+    git(dir, &["add", "."])?;
+    git(dir, &["commit", "-qm", "Initial commit"])?;
+    Ok(())
+}
+
+/// Commit `file` on `main`, then return to the branch that was checked out.
+fn advance_main(dir: &Path, file: &str) -> Result<()> {
+    let current = String::from_utf8(
+        Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .current_dir(dir)
+            .output()?
+            .stdout,
+    )?;
+    git(dir, &["checkout", "-q", "main"])?;
+    fs::write(dir.join(file), "A teammate's change\n")?;
+    git(dir, &["add", file])?;
+    git(dir, &["commit", "-qm", "Teammate's change"])?;
+    git(dir, &["checkout", "-q", current.trim()])?;
+    Ok(())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -1,34 +1,116 @@
-//! `rung undo` command - Undo the last sync operation.
+//! `rung undo` command - Undo the last sync or other history rewrite.
+//!
+//! Every command that rewrites branches (`sync`, `rebase --onto-pr`, `edit`,
+//! `reword`, and `merge` rebasing descendants) backs them up first, recording
+//! its name, so this restores whichever ran last.
 
 use anyhow::{Context, Result, bail};
+use inquire::MultiSelect;
 use rung_core::State;
 use rung_core::sync;
 use rung_git::Repository;
 
-use crate::output;
+use super::utils::check_origin;
+use crate::{output, prompt};
 
-/// Run the undo command.
-pub fn run() -> Result<()> {
+/// Run the undo command, restoring every branch in the last backup, the
+/// `branches` given, or those picked with `interactive`.
+pub fn run(interactive: bool, branches: &[String]) -> Result<()> {
     // Open repository
     let repo = Repository::open_current().context("Not inside a git repository")?;
 
     // Get state manager
-    let workdir = repo.workdir().context("Cannot run in bare repository")?;
-    let state = State::new(workdir)?;
+    if repo.is_bare() {
+        bail!("Cannot run in bare repository");
+    }
+    let state = State::for_repository(&repo);
 
     // Ensure initialized
     if !state.is_initialized() {
         bail!("Rung not initialized - run `rung init` first");
     }
+    check_origin(&repo, &state)?;
+    repo.require_no_operation()?;
+
+    let backup_id = state.latest_backup()?;
+    let backup = state.load_backup(&backup_id)?;
+    let operation = state
+        .backup_operation(&backup_id)
+        .unwrap_or_else(|| "sync".to_string());
+    for branch in branches {
+        if !backup.iter().any(|(name, _)| name == branch) {
+            bail!("'{branch}' isn't in the last {operation}'s backup");
+        }
+    }
+
+    let selected: Vec<&(String, String)> = if interactive {
+        let selected = pick_branches(&repo, &backup)?;
+        if selected.is_empty() {
+            output::info("Nothing selected - undo cancelled");
+            return Ok(());
+        }
+        selected
+    } else {
+        let selected: Vec<_> = backup
+            .iter()
+            .filter(|(name, _)| branches.is_empty() || branches.contains(name))
+            .collect();
+        let details: Vec<String> = selected
+            .iter()
+            .map(|(branch, sha)| format!("Reset '{branch}' to {}", &sha[..8.min(sha.len())]))
+            .collect();
+        let question = format!("Undo the last {operation}?");
+        if !prompt::confirm_destructive(&state, &question, &details)? {
+            output::info("Undo cancelled");
+            return Ok(());
+        }
+        selected
+    };
 
     // Perform undo
-    let result = sync::undo_sync(&repo, &state)?;
+    let names: Vec<String> = selected.iter().map(|(name, _)| name.clone()).collect();
+    let result = sync::undo_sync_branches(&repo, &state, &names)?;
 
     output::success(&format!(
-        "Restored {} branches from backup {}",
+        "Restored {} branches from the {operation} backup {}",
         result.branches_restored,
         &result.backup_id[..8.min(result.backup_id.len())]
     ));
+    let remaining = backup.len() - names.len();
+    if remaining > 0 {
+        output::info(&format!(
+            "{remaining} branch(es) left in the backup - run `rung undo` again to restore them"
+        ));
+    }
 
     Ok(())
 }
+
+/// Let the user pick which of the backed-up branches to restore.
+fn pick_branches<'a>(
+    repo: &Repository,
+    backup: &'a [(String, String)],
+) -> Result<Vec<&'a (String, String)>> {
+    prompt::require_interactive("Picking branches to restore", "pass --branch <name>")?;
+
+    let short = |sha: &str| sha[..8.min(sha.len())].to_string();
+    let labels: Vec<String> = backup
+        .iter()
+        .map(|(branch, sha)| {
+            let now = repo
+                .branch_commit(branch)
+                .map_or_else(|_| "missing".to_string(), |c| short(&c.to_string()));
+            format!("{branch} ({now} → {})", short(sha))
+        })
+        .collect();
+
+    let picked = MultiSelect::new("Branches to restore:", labels.clone())
+        .prompt()
+        .context("Prompt cancelled")?;
+    Ok(backup
+        .iter()
+        .zip(&labels)
+        .filter(|(_, label)| picked.contains(label))
+        .map(|(entry, _)| entry)
+        .collect())
+}
@@ -1,16 +1,529 @@
+use std::collections::HashMap;
+
 use anyhow::{Context, Result, bail};
-use rung_core::State;
-use rung_git::Repository;
+use chrono::{DateTime, Utc};
+use rung_core::ci::{CiStatus, CiSummary};
+use rung_core::commit_index::CommitIndex;
+use rung_core::landed::Landing;
+use rung_core::policy::{BranchChanges, Policy, Violation};
+use rung_core::remote_status::{RemotePr, RemotePrState};
+use rung_core::ticket::TicketMatcher;
+use rung_core::{BranchName, Config, Stack, StackBranch, State};
+use rung_git::{GitHubRemote, Oid, Repository};
+use rung_github::{Auth, GitHubClient, PullRequest, PullRequestState};
+use serde::Serialize;
+
+use crate::i18n::tr;
+use crate::output;
 
 /// Helper to open repo and state.
 pub fn open_repo_and_state() -> Result<(Repository, State)> {
+    let (repo, state) = open_repo_and_state_read_only()?;
+    if repo.is_bare() {
+        bail!("Cannot run in bare repository");
+    }
+    Ok((repo, state))
+}
+
+/// Open repo and state for a command that only reads them, which also works
+/// in a bare clone.
+pub fn open_repo_and_state_read_only() -> Result<(Repository, State)> {
     let repo = Repository::open_current().context("Not inside a git repository")?;
-    let workdir = repo.workdir().context("Cannot run in bare repository")?;
-    let state = State::new(workdir)?;
+    let state = State::for_repository(&repo);
 
     if !state.is_initialized() {
         bail!("Rung not initialized - run `rung init` first");
     }
+    check_origin(&repo, &state)?;
 
     Ok((repo, state))
 }
+
+/// The GitHub repository `origin` points at, as `owner/repo`, if it points
+/// at one.
+pub fn origin_name(repo: &Repository, state: &State) -> Option<String> {
+    let hosts = state
+        .load_config()
+        .unwrap_or_default()
+        .github
+        .allowed_hosts();
+    repo.github_remote(&hosts)
+        .ok()
+        .map(|remote| format!("{}/{}", remote.owner, remote.repo))
+}
+
+/// Refuse to work on a stack set up for another GitHub repository than the
+/// one `origin` points at now, as when the remote was changed, so its PR
+/// numbers aren't used against the wrong repository. A stack set up before
+/// origins were recorded has the current one recorded.
+pub fn check_origin(repo: &Repository, state: &State) -> Result<()> {
+    let Some(current) = origin_name(repo, state) else {
+        return Ok(());
+    };
+    match state.load_origin() {
+        Some(recorded) if !recorded.eq_ignore_ascii_case(&current) => bail!(
+            "This stack was set up for {recorded}, but origin now points at {current}. \
+             If the repository moved, run `rung init --reattach` to use the stack with {current}"
+        ),
+        Some(_) => {}
+        None => {
+            let _ = state.save_origin(&current);
+        }
+    }
+    Ok(())
+}
+
+/// Load the config with the `[orgs.<owner>]` overrides for `origin`'s owner applied.
+pub fn load_config(repo: &Repository, state: &State) -> Result<Config> {
+    let config = state.load_config()?;
+    Ok(match repo.github_remote(&config.github.allowed_hosts()) {
+        Ok(remote) => config.for_owner(&remote.owner),
+        Err(_) => config,
+    })
+}
+
+/// The GitHub repository `origin` points at, on github.com or a host listed
+/// under `[github]`, possibly through an SSH alias mapped there.
+pub fn github_remote(repo: &Repository, config: &Config) -> Result<GitHubRemote> {
+    let url = repo.origin_url().context("No origin remote configured")?;
+    GitHubRemote::parse(&url, &config.github.allowed_hosts()).context(
+        "Could not parse GitHub remote URL - for GitHub Enterprise, add the host to `hosts` under [github]; for an SSH host alias, map it under `ssh_aliases`",
+    )
+}
+
+/// API base for `remote`: `api_url` under `[github]` if set, else derived from its host.
+pub fn api_url(remote: &GitHubRemote, config: &Config) -> String {
+    config
+        .github
+        .api_url
+        .clone()
+        .unwrap_or_else(|| remote.api_url())
+}
+
+/// The base `[[bases]]` maps `branch` onto, among the local branches and
+/// those on origin, or `None` when no rule applies to it.
+///
+/// # Errors
+/// Returns error if a rule applies but no branch matches its base.
+pub fn mapped_base(repo: &Repository, config: &Config, branch: &str) -> Result<Option<String>> {
+    let Some(rule) = config.base_rule(branch) else {
+        return Ok(None);
+    };
+    let mut candidates = repo.list_branches()?;
+    candidates.extend(repo.list_remote_branches()?);
+    let base = rule
+        .resolve(candidates.iter().map(String::as_str))
+        .with_context(|| {
+            format!(
+                "'{branch}' matches the [[bases]] rule for '{}', but no branch matches '{}'",
+                rule.branches, rule.base
+            )
+        })?;
+    Ok(Some(base))
+}
+
+/// A GitHub client for `remote`'s API.
+pub fn github_client(remote: &GitHubRemote, config: &Config) -> rung_github::Result<GitHubClient> {
+    GitHubClient::with_base_url(&Auth::auto(), api_url(remote, config))
+}
+
+/// A client for reading from `remote`: [`github_client`], or without a
+/// token an anonymous one, which can read public repositories on a small
+/// rate limit.
+pub fn github_reader(
+    remote: &GitHubRemote,
+    config: &Config,
+    json: bool,
+) -> rung_github::Result<GitHubClient> {
+    github_client(remote, config).or_else(|_| {
+        if !json {
+            output::warn(&tr!("github-anonymous"));
+        }
+        GitHubClient::anonymous(api_url(remote, config))
+    })
+}
+
+/// The authenticated user's open PRs on `remote`.
+pub async fn my_open_prs(client: &GitHubClient, remote: &GitHubRemote) -> Result<Vec<RemotePr>> {
+    let login = client
+        .current_user()
+        .await
+        .context("Failed to look up your GitHub user")?;
+    let prs = client
+        .list_open_prs_by(&remote.owner, &remote.repo, &login)
+        .await
+        .context("Failed to list open PRs")?;
+    Ok(prs.iter().map(remote_pr).collect())
+}
+
+/// A PR as `rung_core::remote_status` compares it.
+pub fn remote_pr(pr: &PullRequest) -> RemotePr {
+    RemotePr {
+        number: pr.number,
+        head: pr.head_branch.clone(),
+        base: pr.base_branch.clone(),
+        state: match pr.state {
+            PullRequestState::Open => RemotePrState::Open,
+            PullRequestState::Closed => RemotePrState::Closed,
+            PullRequestState::Merged => RemotePrState::Merged,
+        },
+    }
+}
+
+/// Push `branch` to `remote_branch` on origin. With `verify` (`verify_pushes`
+/// under `[general]`), the branch is then read back from origin, and a push
+/// that didn't land at the local tip fails rather than letting PRs be
+/// updated against another commit.
+///
+/// The mismatch is returned as it is, so it isn't hidden behind "Failed to
+/// push".
+pub fn push(
+    repo: &Repository,
+    branch: &BranchName,
+    remote_branch: &BranchName,
+    force: bool,
+    verify: bool,
+) -> Result<()> {
+    repo.push_to(branch, remote_branch, force)
+        .with_context(|| format!("Failed to push {branch}"))?;
+    if verify {
+        repo.verify_pushed(branch, remote_branch)?;
+    }
+    Ok(())
+}
+
+/// Back up branch tips captured before `operation` rewrites them, so `rung
+/// undo` can restore them.
+pub fn backup_tips(state: &State, operation: &str, tips: &HashMap<String, Oid>) -> Result<String> {
+    let refs: Vec<(String, String)> = tips
+        .iter()
+        .map(|(name, oid)| (name.clone(), oid.to_string()))
+        .collect();
+    let refs: Vec<(&str, &str)> = refs.iter().map(|(b, c)| (b.as_str(), c.as_str())).collect();
+    Ok(state.create_backup(operation, &refs)?)
+}
+
+/// Whether `branch` has no commits `parent` doesn't, such as after its
+/// changes were folded into the parent. `false` when either can't be read.
+pub fn has_no_commits(repo: &Repository, branch: &str, parent: &str) -> bool {
+    let (Ok(tip), Ok(parent_tip)) = (repo.branch_commit(branch), repo.branch_commit(parent)) else {
+        return false;
+    };
+    repo.merge_base(tip, parent_tip)
+        .is_ok_and(|base| base == tip)
+}
+
+/// When the tip of `branch` was committed, so a branch worked on with plain
+/// git isn't reported stale. `None` when it can't be read.
+pub fn tip_time(repo: &Repository, branch: &str) -> Option<DateTime<Utc>> {
+    let tip = repo.branch_commit(branch).ok()?;
+    DateTime::from_timestamp(repo.commit_time(tip).ok()?, 0)
+}
+
+/// Stack branches among `names` whose remote tip was committed by someone
+/// else, each with who that was. Force-pushing them would throw away what a
+/// teammate pushed. Branches already matching the remote are left out, as is
+/// everything when git has no identity to compare against.
+pub fn pushed_by_others(
+    repo: &Repository,
+    stack: &rung_core::Stack,
+    names: &[&str],
+) -> Vec<(String, String)> {
+    let Some(me) = repo
+        .signature()
+        .ok()
+        .and_then(|me| me.email().map(str::to_lowercase))
+    else {
+        return vec![];
+    };
+
+    names
+        .iter()
+        .filter_map(|&name| {
+            let branch = stack.find_branch(name)?;
+            let remote = repo.remote_branch_commit(branch.remote_name()).ok()?;
+            if repo.branch_commit(name).ok()? == remote {
+                return None;
+            }
+            let commit = repo.find_commit(remote).ok()?;
+            let committer = commit.committer();
+            let email = committer.email()?;
+            if email.eq_ignore_ascii_case(&me) {
+                return None;
+            }
+            let opened_by = branch
+                .author
+                .as_ref()
+                .map(|author| format!(", PR opened by @{author}"))
+                .unwrap_or_default();
+            let who = committer.name().unwrap_or("someone");
+            Some((name.to_string(), format!("{who} <{email}>{opened_by}")))
+        })
+        .collect()
+}
+
+/// Check `[policy]` against each of `branches`, compared with its parent in
+/// `stack`. `reviewers` holds who was asked to review each branch's PR,
+/// where that's known.
+pub fn policy_violations(
+    repo: &Repository,
+    stack: &Stack,
+    config: &Config,
+    branches: &[&str],
+    reviewers: &HashMap<String, Vec<String>>,
+) -> Result<Vec<Violation>> {
+    let policy = Policy::from_config(&config.policy);
+    if policy.is_empty() {
+        return Ok(vec![]);
+    }
+    // A ticket is required even without `[tickets]`, using its default pattern
+    let tickets = TicketMatcher::new(&config.tickets.clone().unwrap_or_default())?;
+
+    let mut violations = vec![];
+    for &name in branches {
+        let parent = stack
+            .find_branch(name)
+            .and_then(|b| b.parent.as_deref())
+            .unwrap_or("main");
+        let files = repo.changed_files(name, parent).unwrap_or_default();
+        let messages: Vec<String> = match (repo.branch_commit(parent), repo.branch_commit(name)) {
+            (Ok(base), Ok(tip)) => repo
+                .commit_infos_between(base, tip)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|commit| commit.message)
+                .collect(),
+            _ => vec![],
+        };
+        let ticket = tickets.find(name, messages.iter().map(String::as_str));
+        violations.extend(policy.check(&BranchChanges {
+            branch: name,
+            files: &files,
+            lines: repo.changed_lines(name, parent).unwrap_or_default(),
+            ticket: ticket.as_deref(),
+            reviewers: reviewers.get(name).map(Vec::as_slice),
+        }));
+    }
+    Ok(violations)
+}
+
+/// Stop on policy `violations`, or with `override_policy` only warn about
+/// them. With `json`, violations that stop the command are printed as
+/// `{"policy_violations": [...]}` first.
+pub fn enforce_policy(violations: &[Violation], override_policy: bool, json: bool) -> Result<()> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+    if override_policy {
+        if !json {
+            for violation in violations {
+                output::warn(&format!("Policy overridden: {violation}"));
+            }
+        }
+        return Ok(());
+    }
+
+    if json {
+        let report = serde_json::json!({ "policy_violations": violations });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for violation in violations {
+            output::error(&violation.to_string());
+        }
+    }
+    bail!(
+        "{} policy violation(s) - fix them, or pass --override-policy in an emergency",
+        violations.len()
+    )
+}
+
+/// A draft PR marked ready for review once its checks passed.
+#[derive(Debug, Serialize)]
+pub struct ReadyPr {
+    pub branch: String,
+    pub pr_number: u64,
+}
+
+/// Mark ready for review the draft PRs of branches submitted with
+/// `--draft-until-green` whose checks now pass, returning them.
+///
+/// Checks count as `rung verify` counts them: those branch protection
+/// requires, or else every check. A PR with no checks reported yet keeps
+/// waiting, and a branch whose PR is no longer an open draft stops being
+/// watched. Nothing is done while a sync is paused.
+pub fn ready_green_drafts(repo: &Repository, state: &State, json: bool) -> Result<Vec<ReadyPr>> {
+    let mut stack = state.load_stack()?;
+    let waiting: Vec<(String, u64)> = stack
+        .branches
+        .iter()
+        .filter(|b| b.ready_when_green)
+        .filter_map(|b| Some((b.name.to_string(), b.pr?)))
+        .collect();
+    if waiting.is_empty() || state.is_sync_in_progress() {
+        return Ok(vec![]);
+    }
+
+    let config = load_config(repo, state)?;
+    let remote = github_remote(repo, &config)?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let (owner, name) = (remote.owner.as_str(), remote.repo.as_str());
+
+    let mut ready = vec![];
+    let mut done = vec![];
+    for (branch, number) in waiting {
+        let pr = match rt.block_on(client.get_pr(owner, name, number)) {
+            Ok(pr) => pr,
+            Err(e) => {
+                if !json {
+                    output::warn(&tr!("ready-check-failed", pr = number, error = e));
+                }
+                continue;
+            }
+        };
+        if pr.state != PullRequestState::Open || !pr.draft {
+            done.push(branch);
+            continue;
+        }
+
+        // Unreadable protection leaves every check counting
+        let required = rt
+            .block_on(client.get_required_checks(owner, name, &pr.base_branch))
+            .unwrap_or_default();
+        let runs = match rt.block_on(client.get_check_runs(owner, name, &pr.head_sha)) {
+            Ok(runs) => runs,
+            Err(e) => {
+                if !json {
+                    output::warn(&tr!("ready-check-failed", pr = number, error = e));
+                }
+                continue;
+            }
+        };
+        let checks = runs.iter().map(|run| {
+            let status = match run.status {
+                status if status.is_pending() => CiStatus::Pending,
+                status if status.is_success() => CiStatus::Passing,
+                _ => CiStatus::Failing,
+            };
+            (run.name.as_str(), status)
+        });
+        if !CiSummary::from_checks(checks, &required)
+            .is_some_and(|summary| summary.status == CiStatus::Passing)
+        {
+            continue;
+        }
+
+        match rt.block_on(client.mark_ready_for_review(owner, name, number)) {
+            Ok(()) => {
+                done.push(branch.clone());
+                ready.push(ReadyPr {
+                    branch,
+                    pr_number: number,
+                });
+            }
+            Err(e) => {
+                if !json {
+                    output::warn(&tr!("ready-failed", pr = number, error = e));
+                }
+            }
+        }
+    }
+
+    if !done.is_empty() {
+        for branch in &mut stack.branches {
+            if done.iter().any(|name| branch.name == *name) {
+                branch.ready_when_green = false;
+            }
+        }
+        state.save_stack(&stack)?;
+    }
+    Ok(ready)
+}
+
+/// Record where `branch`'s PR landed (`commit` on `into`), for `rung
+/// contains` and `rung revert`. Best effort: a branch whose commits can't
+/// be worked out, say because it was deleted, just isn't recorded.
+pub fn record_landing(
+    repo: &Repository,
+    state: &State,
+    stack: &Stack,
+    branch: &StackBranch,
+    commit: &str,
+    into: &str,
+) {
+    let Some(pr) = branch.pr else {
+        return;
+    };
+    let Ok(tip) = repo.branch_commit(&branch.name) else {
+        return;
+    };
+    let landing = branch_base(repo, stack, branch, tip, into)
+        .and_then(|base| Landing::capture(repo, &branch.name, pr, base, tip, commit, into).ok());
+    if let Some(landing) = landing {
+        let _ = state.record_landing(landing);
+    }
+}
+
+/// The index of the stack branches' own commits, with branches that moved
+/// since they were indexed walked again. Saved when anything changed.
+pub fn commit_index(repo: &Repository, state: &State, stack: &Stack) -> Result<CommitIndex> {
+    let mut index = state.load_commit_index();
+    let names: Vec<&str> = stack.branches.iter().map(|b| b.name.as_str()).collect();
+    let mut changed = index.retain_branches(&names);
+    for branch in &stack.branches {
+        let Some(key) = CommitIndex::key(repo, stack, branch) else {
+            continue;
+        };
+        if index.get(&branch.name, &key).is_some() {
+            continue;
+        }
+        let commits = own_commits(repo, stack, branch, "main")
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        changed |= index.insert(branch.name.as_str(), key, commits);
+    }
+    if changed {
+        state.save_commit_index(&index)?;
+    }
+    Ok(index)
+}
+
+/// `branch`'s own commits: those since it forked from its parent (`trunk`
+/// for a branch without one).
+fn own_commits(repo: &Repository, stack: &Stack, branch: &StackBranch, trunk: &str) -> Vec<Oid> {
+    let Ok(tip) = repo.branch_commit(&branch.name) else {
+        return vec![];
+    };
+    branch_base(repo, stack, branch, tip, trunk)
+        .and_then(|base| repo.commits_between(base, tip).ok())
+        .unwrap_or_default()
+}
+
+/// Where `branch` (at `tip`) starts: its recorded base, or else where it
+/// forks from its parent (`trunk` for a branch without one). A trunk parent
+/// is read from origin when fetched, since the local trunk may lag behind.
+pub fn branch_base(
+    repo: &Repository,
+    stack: &Stack,
+    branch: &StackBranch,
+    tip: Oid,
+    trunk: &str,
+) -> Option<Oid> {
+    let is_ancestor = |base: Oid| repo.merge_base(base, tip).is_ok_and(|m| m == base);
+    let parent = branch.parent.as_deref().unwrap_or(trunk);
+    branch
+        .based_on
+        .as_deref()
+        .and_then(|base| Oid::from_str(base).ok())
+        .filter(|&base| is_ancestor(base))
+        .or_else(|| {
+            let parent_tip = match stack.find_branch(parent) {
+                Some(_) => repo.branch_commit(parent),
+                None => repo
+                    .remote_branch_commit(parent)
+                    .or_else(|_| repo.branch_commit(parent)),
+            };
+            parent_tip.and_then(|p| repo.merge_base(p, tip)).ok()
+        })
+}
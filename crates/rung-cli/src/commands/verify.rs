@@ -0,0 +1,501 @@
+//! `rung verify` command - Check that the whole stack is ready to land.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use rung_core::ci::{CiStatus, CiSummary};
+use rung_core::{BranchState, Config, Stack, StackBranch, sync};
+use rung_git::{GitHubRemote, Repository};
+use rung_github::{GitHubClient, PullRequest, PullRequestState, ReviewState};
+use serde::Serialize;
+
+use super::utils::{
+    github_client, github_remote, load_config, open_repo_and_state, policy_violations,
+};
+use crate::{exit, output};
+
+/// Column headings of the matrix, in order.
+const COLUMNS: [&str; 7] = [
+    "stack",
+    "pr",
+    "title",
+    "ci",
+    "reviews",
+    "mergeable",
+    "policy",
+];
+
+/// Result of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Pass,
+    Fail,
+    /// Not decided yet, such as checks still running.
+    Pending,
+    /// Not configured, or nothing to check.
+    Skipped,
+}
+
+/// One check's outcome, with why it didn't pass.
+#[derive(Debug, Clone, Serialize)]
+struct Check {
+    outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl Check {
+    const fn pass() -> Self {
+        Self {
+            outcome: Outcome::Pass,
+            detail: None,
+        }
+    }
+
+    const fn skipped() -> Self {
+        Self {
+            outcome: Outcome::Skipped,
+            detail: None,
+        }
+    }
+
+    fn fail(detail: impl Into<String>) -> Self {
+        Self {
+            outcome: Outcome::Fail,
+            detail: Some(detail.into()),
+        }
+    }
+
+    fn pending(detail: impl Into<String>) -> Self {
+        Self {
+            outcome: Outcome::Pending,
+            detail: Some(detail.into()),
+        }
+    }
+
+    const fn is_ok(&self) -> bool {
+        matches!(self.outcome, Outcome::Pass | Outcome::Skipped)
+    }
+}
+
+/// Every check for one branch, in column order.
+#[derive(Debug, Serialize)]
+struct BranchChecks {
+    stack: Check,
+    pr: Check,
+    title: Check,
+    ci: Check,
+    reviews: Check,
+    mergeable: Check,
+    policy: Check,
+}
+
+impl BranchChecks {
+    const fn columns(&self) -> [&Check; 7] {
+        [
+            &self.stack,
+            &self.pr,
+            &self.title,
+            &self.ci,
+            &self.reviews,
+            &self.mergeable,
+            &self.policy,
+        ]
+    }
+}
+
+/// One row of the matrix.
+#[derive(Debug, Serialize)]
+struct BranchReport {
+    branch: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr_number: Option<u64>,
+    ready: bool,
+    checks: BranchChecks,
+}
+
+/// JSON output for the verify command.
+#[derive(Debug, Serialize)]
+struct VerifyOutput {
+    ready: bool,
+    doctor: Check,
+    branches: Vec<BranchReport>,
+}
+
+/// GitHub access shared by every branch's checks.
+struct GitHub<'a> {
+    client: GitHubClient,
+    remote: &'a GitHubRemote,
+    rt: tokio::runtime::Runtime,
+    /// Required checks, by base branch.
+    required: HashMap<String, Vec<String>>,
+}
+
+/// Run the verify command.
+///
+/// Runs the doctor's checks, then checks every stack branch: synced with its
+/// parent, pushed to an open PR, titled to `pr.title_pattern`, green CI,
+/// approved, mergeable and within `[policy]`. Prints a pass/fail matrix and
+/// exits non-zero unless everything passes.
+pub fn run(json: bool) -> Result<()> {
+    let (repo, state) = open_repo_and_state()?;
+    let config = load_config(&repo, &state)?;
+    let stack = state.load_stack()?;
+
+    let doctor = match super::doctor::errors(&repo, &state)? {
+        errors if errors.is_empty() => Check::pass(),
+        errors => Check::fail(errors.join("; ")),
+    };
+
+    let remote = github_remote(&repo, &config);
+    let mut github = match &remote {
+        Ok(remote) => Some(GitHub {
+            client: github_client(remote, &config).context("Failed to authenticate with GitHub")?,
+            remote,
+            rt: tokio::runtime::Runtime::new()?,
+            required: HashMap::new(),
+        }),
+        Err(_) => None,
+    };
+
+    let sync_in_progress = state.is_sync_in_progress();
+    let mut branches = vec![];
+    for branch in stack.topological_order()? {
+        let checks = verify_branch(
+            &repo,
+            &config,
+            &stack,
+            branch,
+            github.as_mut(),
+            sync_in_progress,
+        )?;
+        branches.push(BranchReport {
+            branch: branch.name.to_string(),
+            pr_number: branch.pr,
+            ready: checks.columns().iter().all(|check| check.is_ok()),
+            checks,
+        });
+    }
+
+    let result = VerifyOutput {
+        ready: doctor.is_ok() && branches.iter().all(|b| b.ready),
+        doctor,
+        branches,
+    };
+    if !result.ready {
+        exit::set(exit::Code::Error);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+    print_matrix(&result);
+    Ok(())
+}
+
+/// Run every check on one branch. Without `github`, the checks that need it
+/// fail.
+fn verify_branch(
+    repo: &Repository,
+    config: &Config,
+    stack: &Stack,
+    branch: &StackBranch,
+    github: Option<&mut GitHub<'_>>,
+    sync_in_progress: bool,
+) -> Result<BranchChecks> {
+    let stack_check = if sync_in_progress {
+        Check::fail("sync in progress")
+    } else if !repo.branch_exists(&branch.name) {
+        Check::fail("branch missing locally")
+    } else {
+        match sync::branch_state(repo, stack, branch)? {
+            BranchState::Synced => Check::pass(),
+            BranchState::Diverged { commits_behind } => Check::fail(format!(
+                "{commits_behind} commit(s) behind its parent - run `rung sync`"
+            )),
+            BranchState::NeedsRestack => Check::fail("parent was rewritten - run `rung sync`"),
+            BranchState::Conflict { .. } => Check::fail("conflict - run `rung sync --continue`"),
+            BranchState::Detached => Check::fail("parent branch is gone"),
+        }
+    };
+
+    let Some(github) = github else {
+        let no_remote = || Check::fail("no GitHub remote");
+        return Ok(BranchChecks {
+            stack: stack_check,
+            pr: no_remote(),
+            title: Check::skipped(),
+            ci: no_remote(),
+            reviews: no_remote(),
+            mergeable: no_remote(),
+            policy: policy_check(repo, config, stack, branch, None)?,
+        });
+    };
+    let Some(number) = branch.pr else {
+        return Ok(BranchChecks {
+            stack: stack_check,
+            pr: Check::fail("no PR - run `rung submit`"),
+            title: Check::skipped(),
+            ci: Check::skipped(),
+            reviews: Check::skipped(),
+            mergeable: Check::skipped(),
+            policy: policy_check(repo, config, stack, branch, None)?,
+        });
+    };
+
+    let (owner, name) = (&github.remote.owner, &github.remote.repo);
+    let pr = match github
+        .rt
+        .block_on(github.client.get_pr(owner, name, number))
+    {
+        Ok(pr) => pr,
+        Err(e) => {
+            let unreadable = || Check::fail(format!("could not fetch PR #{number}: {e}"));
+            return Ok(BranchChecks {
+                stack: stack_check,
+                pr: unreadable(),
+                title: Check::skipped(),
+                ci: unreadable(),
+                reviews: unreadable(),
+                mergeable: unreadable(),
+                policy: policy_check(repo, config, stack, branch, None)?,
+            });
+        }
+    };
+
+    let reviewers = if config.policy.reviewers.is_empty() {
+        None
+    } else {
+        Some(
+            github
+                .rt
+                .block_on(github.client.get_reviewers(owner, name, number))
+                .with_context(|| format!("Failed to list reviewers of PR #{number}"))?,
+        )
+    };
+    Ok(BranchChecks {
+        stack: stack_check,
+        pr: pr_check(repo, branch, &pr),
+        title: title_check(config, &pr)?,
+        ci: ci_check(github, &pr),
+        reviews: reviews_check(github, &pr),
+        mergeable: mergeable_check(&pr),
+        policy: policy_check(repo, config, stack, branch, reviewers)?,
+    })
+}
+
+/// The PR is open, not a draft, and has the local commits.
+fn pr_check(repo: &Repository, branch: &StackBranch, pr: &PullRequest) -> Check {
+    match pr.state {
+        PullRequestState::Open => {}
+        PullRequestState::Closed => return Check::fail(format!("PR #{} is closed", pr.number)),
+        PullRequestState::Merged => {
+            return Check::fail(format!("PR #{} is already merged", pr.number));
+        }
+    }
+    if pr.draft {
+        return Check::fail(format!("PR #{} is a draft", pr.number));
+    }
+    match repo.branch_commit(&branch.name) {
+        Ok(tip) if tip.to_string() == pr.head_sha => Check::pass(),
+        Ok(_) => Check::fail("local commits not pushed - run `rung submit`"),
+        Err(_) => Check::fail("branch missing locally"),
+    }
+}
+
+/// The PR title matches `pr.title_pattern`.
+fn title_check(config: &Config, pr: &PullRequest) -> Result<Check> {
+    Ok(match config.pr.title_regex()? {
+        None => Check::skipped(),
+        Some(pattern) if pattern.is_match(&pr.title) => Check::pass(),
+        Some(pattern) => Check::fail(format!(
+            "title doesn't match `pr.title_pattern` ({pattern})"
+        )),
+    })
+}
+
+/// The checks the base branch requires pass, or all checks without
+/// protection.
+fn ci_check(github: &mut GitHub<'_>, pr: &PullRequest) -> Check {
+    let (owner, name) = (&github.remote.owner, &github.remote.repo);
+    let required = github
+        .required
+        .entry(pr.base_branch.clone())
+        .or_insert_with_key(|base| {
+            // Unreadable protection leaves every check counting
+            github
+                .rt
+                .block_on(github.client.get_required_checks(owner, name, base))
+                .unwrap_or_default()
+        });
+    let runs = match github
+        .rt
+        .block_on(github.client.get_check_runs(owner, name, &pr.head_sha))
+    {
+        Ok(runs) => runs,
+        Err(e) => return Check::fail(format!("could not fetch checks: {e}")),
+    };
+    let checks = runs.iter().map(|run| {
+        let status = match run.status {
+            status if status.is_pending() => CiStatus::Pending,
+            status if status.is_success() => CiStatus::Passing,
+            _ => CiStatus::Failing,
+        };
+        (run.name.as_str(), status)
+    });
+    match CiSummary::from_checks(checks, required).map(|summary| summary.status) {
+        None | Some(CiStatus::Passing) => Check::pass(),
+        Some(CiStatus::Pending) => Check::pending("checks still running"),
+        Some(CiStatus::Failing) => Check::fail("checks failing"),
+    }
+}
+
+/// Someone approved, nobody's latest review asks for changes, and no
+/// requested review is outstanding.
+fn reviews_check(github: &GitHub<'_>, pr: &PullRequest) -> Check {
+    let (owner, name) = (&github.remote.owner, &github.remote.repo);
+    let reviews = match github
+        .rt
+        .block_on(github.client.get_reviews(owner, name, pr.number))
+    {
+        Ok(reviews) => reviews,
+        Err(e) => return Check::fail(format!("could not fetch reviews: {e}")),
+    };
+
+    // Each reviewer's latest verdict; comments don't replace one
+    let mut verdicts: Vec<(&str, ReviewState)> = vec![];
+    for review in reviews
+        .iter()
+        .filter(|r| r.state != ReviewState::Commented && r.state != ReviewState::Pending)
+    {
+        match verdicts.iter_mut().find(|(who, _)| *who == review.author) {
+            Some(verdict) => verdict.1 = review.state,
+            None => verdicts.push((&review.author, review.state)),
+        }
+    }
+    let blocking: Vec<&str> = verdicts
+        .iter()
+        .filter(|(_, state)| *state == ReviewState::ChangesRequested)
+        .map(|(who, _)| *who)
+        .collect();
+    if !blocking.is_empty() {
+        return Check::fail(format!("changes requested by {}", blocking.join(", ")));
+    }
+
+    let waiting = github
+        .rt
+        .block_on(github.client.get_review_requests(owner, name, pr.number))
+        .unwrap_or_default();
+    if !waiting.is_empty() {
+        let teams = waiting.teams.iter().map(|slug| format!("{owner}/{slug}"));
+        let names: Vec<String> = waiting.users.iter().cloned().chain(teams).collect();
+        return Check::pending(format!("waiting on {}", names.join(", ")));
+    }
+    if verdicts
+        .iter()
+        .any(|(_, state)| *state == ReviewState::Approved)
+    {
+        Check::pass()
+    } else {
+        Check::pending("not approved yet")
+    }
+}
+
+/// GitHub reports the PR as mergeable.
+fn mergeable_check(pr: &PullRequest) -> Check {
+    match (pr.mergeable, pr.mergeable_state.as_deref()) {
+        (Some(false), _) | (_, Some("dirty")) => Check::fail("conflicts with its base"),
+        (_, Some("blocked")) => Check::fail("blocked by branch protection"),
+        (None, _) | (_, Some("unknown")) => Check::pending("GitHub is still computing"),
+        _ => Check::pass(),
+    }
+}
+
+/// The branch breaks no `[policy]` rule.
+fn policy_check(
+    repo: &Repository,
+    config: &Config,
+    stack: &Stack,
+    branch: &StackBranch,
+    reviewers: Option<Vec<String>>,
+) -> Result<Check> {
+    if rung_core::policy::Policy::from_config(&config.policy).is_empty() {
+        return Ok(Check::skipped());
+    }
+    let reviewers: HashMap<String, Vec<String>> = reviewers
+        .map(|asked| HashMap::from([(branch.name.to_string(), asked)]))
+        .unwrap_or_default();
+    let violations = policy_violations(repo, stack, config, &[&branch.name], &reviewers)?;
+    Ok(if violations.is_empty() {
+        Check::pass()
+    } else {
+        let messages: Vec<String> = violations
+            .iter()
+            .map(|v| format!("{} ({})", v.message, v.rule))
+            .collect();
+        Check::fail(messages.join("; "))
+    })
+}
+
+/// Print the matrix, then why each failing check failed.
+fn print_matrix(result: &VerifyOutput) {
+    let width = result
+        .branches
+        .iter()
+        .map(|b| b.branch.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Branch".len());
+    let header: Vec<String> = COLUMNS.iter().map(|c| format!("{c:<9}")).collect();
+    println!(
+        "{:<width$}  {}",
+        "Branch",
+        header.join(" ").trim_end().bold()
+    );
+    for report in &result.branches {
+        let cells: Vec<String> = report
+            .checks
+            .columns()
+            .iter()
+            .map(|check| format!("{}        ", symbol(check.outcome)))
+            .collect();
+        println!("{:<width$}  {}", report.branch, cells.join(" ").trim_end());
+    }
+    println!();
+
+    let doctor = symbol(result.doctor.outcome);
+    match &result.doctor.detail {
+        Some(detail) => println!("  {doctor} doctor: {detail}"),
+        None => println!("  {doctor} doctor"),
+    }
+    for report in &result.branches {
+        for (column, check) in COLUMNS.iter().zip(report.checks.columns()) {
+            if let Some(detail) = check.detail.as_ref().filter(|_| !check.is_ok()) {
+                println!(
+                    "  {} {} {column}: {detail}",
+                    symbol(check.outcome),
+                    report.branch
+                );
+            }
+        }
+    }
+    println!();
+
+    if result.ready {
+        output::success("Stack is ready to land");
+    } else {
+        output::error("Stack is not ready to land");
+    }
+}
+
+/// One-character marker for an outcome.
+fn symbol(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::Pass => "✓".green().to_string(),
+        Outcome::Fail => "✗".red().to_string(),
+        Outcome::Pending => "…".yellow().to_string(),
+        Outcome::Skipped => "-".dimmed().to_string(),
+    }
+}
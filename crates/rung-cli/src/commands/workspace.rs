@@ -0,0 +1,290 @@
+//! `rung workspace` command - Work on stacks spanning several repositories.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use rung_core::{Config, State, Workspace, pr_body};
+use rung_git::{GitHubRemote, Repository};
+use rung_github::UpdatePullRequest;
+use serde::Serialize;
+
+use super::WorkspaceCommand;
+use super::utils::{check_origin, github_client, github_remote, load_config};
+use crate::{output, prompt};
+
+/// Global flags passed on to `rung` in each repository.
+#[derive(Debug, Clone, Copy)]
+struct Flags {
+    json: bool,
+    quiet: bool,
+    yes: bool,
+}
+
+/// JSON output for one repository.
+#[derive(Debug, Serialize)]
+struct RepoOutput {
+    repo: String,
+    success: bool,
+    /// The command's own JSON output, if it produced any.
+    output: Option<serde_json::Value>,
+}
+
+/// Run the workspace command.
+pub fn run(json: bool, quiet: bool, yes: bool, command: WorkspaceCommand) -> Result<()> {
+    let flags = Flags { json, quiet, yes };
+    match command {
+        WorkspaceCommand::Init { repos } => run_init(&repos),
+        WorkspaceCommand::Add { repos } => run_add(&repos),
+        WorkspaceCommand::Status { args } => {
+            run_in_repos(&find_workspace()?, "status", &args, flags)
+        }
+        WorkspaceCommand::Sync { args } => run_in_repos(&find_workspace()?, "sync", &args, flags),
+        WorkspaceCommand::Submit { args } => run_submit(&args, flags),
+    }
+}
+
+fn find_workspace() -> Result<Workspace> {
+    let cwd = std::env::current_dir()?;
+    let workspace = Workspace::find(&cwd)?.with_context(|| {
+        format!(
+            "No {} found - run `rung workspace init <repos>...` first",
+            Workspace::FILE_NAME
+        )
+    })?;
+    if workspace.repos.is_empty() {
+        bail!("The workspace has no repositories - add some with `rung workspace add <path>`");
+    }
+    Ok(workspace)
+}
+
+fn run_init(repos: &[String]) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    if cwd.join(Workspace::FILE_NAME).exists() {
+        bail!("{} already exists", Workspace::FILE_NAME);
+    }
+    let mut workspace = Workspace::new(&cwd);
+    add_repos(&mut workspace, repos)?;
+    workspace.save()?;
+    output::success(&format!(
+        "Created {} with {} repositor(ies)",
+        Workspace::FILE_NAME,
+        workspace.repos.len()
+    ));
+    Ok(())
+}
+
+fn run_add(repos: &[String]) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let mut workspace = Workspace::find(&cwd)?.with_context(|| {
+        format!(
+            "No {} found - run `rung workspace init` first",
+            Workspace::FILE_NAME
+        )
+    })?;
+    let added = add_repos(&mut workspace, repos)?;
+    workspace.save()?;
+    output::success(&format!("Added {added} repositor(ies) to the workspace"));
+    Ok(())
+}
+
+/// Add `repos` to the workspace, checking each is a git repository.
+fn add_repos(workspace: &mut Workspace, repos: &[String]) -> Result<usize> {
+    let mut added = 0;
+    for repo in repos {
+        Repository::open(workspace.root.join(repo))
+            .with_context(|| format!("{repo} is not a git repository"))?;
+        if workspace.add(repo)? {
+            added += 1;
+        } else {
+            output::warn(&format!("{repo} is already in the workspace"));
+        }
+    }
+    Ok(added)
+}
+
+/// Run `rung <command> <args>` in each repository of the workspace.
+///
+/// Keeps going after a failure, then reports every repository that failed.
+fn run_in_repos(workspace: &Workspace, command: &str, args: &[String], flags: Flags) -> Result<()> {
+    let exe = std::env::current_exe().context("Could not locate the rung executable")?;
+    let mut failed = vec![];
+    let mut outputs = vec![];
+
+    for (repo, path) in workspace.repos.iter().zip(workspace.repo_paths()) {
+        let mut child = Command::new(&exe);
+        for (set, flag) in [
+            (flags.json, "--json"),
+            (flags.quiet, "--quiet"),
+            (flags.yes, "--yes"),
+            (prompt::is_non_interactive(), "--non-interactive"),
+        ] {
+            if set {
+                child.arg(flag);
+            }
+        }
+        child.arg(command).args(args).current_dir(&path);
+
+        let success = if flags.json {
+            let out = child
+                .stderr(Stdio::inherit())
+                .output()
+                .with_context(|| format!("Failed to run rung in {repo}"))?;
+            outputs.push(RepoOutput {
+                repo: repo.clone(),
+                success: out.status.success(),
+                output: serde_json::from_slice(&out.stdout).ok(),
+            });
+            out.status.success()
+        } else {
+            if !flags.quiet {
+                println!();
+                println!("{}", format!("── {repo} ──").bold());
+            }
+            child
+                .status()
+                .with_context(|| format!("Failed to run rung in {repo}"))?
+                .success()
+        };
+
+        if !success {
+            failed.push(repo.clone());
+        }
+    }
+
+    if flags.json {
+        println!("{}", serde_json::to_string_pretty(&outputs)?);
+    }
+    if !failed.is_empty() {
+        bail!("rung {command} failed in: {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+/// A repository's PRs, for cross-linking.
+struct RepoPrs {
+    repo: String,
+    remote: GitHubRemote,
+    client: rung_github::GitHubClient,
+    prs: Vec<u64>,
+}
+
+fn run_submit(args: &[String], flags: Flags) -> Result<()> {
+    let workspace = find_workspace()?;
+    run_in_repos(&workspace, "submit", args, flags)?;
+    if args.iter().any(|arg| arg == "--dry-run") {
+        return Ok(());
+    }
+
+    let repos: Vec<RepoPrs> = workspace
+        .repos
+        .iter()
+        .zip(workspace.repo_paths())
+        .map(|(repo, path)| repo_prs(repo, &path))
+        .collect::<Result<_>>()?;
+    let linked = link_prs(&repos, flags.json)?;
+    if !flags.json {
+        output::success(&format!("Linked {linked} PR(s) across repositories"));
+    }
+    Ok(())
+}
+
+fn repo_prs(name: &str, path: &Path) -> Result<RepoPrs> {
+    let (remote, config, prs) = stack_prs(path).with_context(|| name.to_string())?;
+    let client = github_client(&remote, &config).context("Failed to authenticate with GitHub")?;
+    Ok(RepoPrs {
+        repo: name.to_string(),
+        remote,
+        client,
+        prs,
+    })
+}
+
+/// The GitHub repository, config and PR numbers of the stack in `path`.
+fn stack_prs(path: &Path) -> Result<(GitHubRemote, Config, Vec<u64>)> {
+    let repo = Repository::open(path).context("Failed to open repository")?;
+    let state = State::for_repository(&repo);
+    check_origin(&repo, &state)?;
+    let config = load_config(&repo, &state)?;
+    let remote = github_remote(&repo, &config)?;
+    let prs = state
+        .load_stack()?
+        .branches
+        .iter()
+        .filter_map(|b| b.pr)
+        .collect();
+    Ok((remote, config, prs))
+}
+
+/// PRs in the other repositories of the workspace containing `workdir`, as
+/// referenced from `remote`. Empty outside a workspace.
+pub fn related_prs(workdir: &Path, remote: &GitHubRemote) -> Result<Vec<String>> {
+    let Some(workspace) = Workspace::find(workdir)? else {
+        return Ok(vec![]);
+    };
+    let this = workdir.canonicalize()?;
+    let mut related = vec![];
+    for (name, path) in workspace.repos.iter().zip(workspace.repo_paths()) {
+        if path.canonicalize().is_ok_and(|path| path == this) {
+            continue;
+        }
+        let (other, _, prs) = stack_prs(&path).with_context(|| name.clone())?;
+        related.extend(prs.into_iter().map(|pr| reference(remote, &other, pr)));
+    }
+    Ok(related)
+}
+
+/// How `pr` in `target` is referenced from a PR in `from`: `owner/repo#N`,
+/// which GitHub expands, or a full URL across hosts.
+fn reference(from: &GitHubRemote, target: &GitHubRemote, pr: u64) -> String {
+    if from.host == target.host {
+        format!("{target}#{pr}")
+    } else {
+        format!("{}/pull/{pr}", target.web_url())
+    }
+}
+
+/// Set the related-PRs section of every PR to the PRs in the other repositories.
+///
+/// Returns the number of PR descriptions changed.
+fn link_prs(repos: &[RepoPrs], json: bool) -> Result<usize> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let mut linked = 0;
+    for current in repos {
+        let related: Vec<String> = repos
+            .iter()
+            .filter(|other| other.repo != current.repo)
+            .flat_map(|other| {
+                other
+                    .prs
+                    .iter()
+                    .map(|&pr| reference(&current.remote, &other.remote, pr))
+            })
+            .collect();
+
+        for &number in &current.prs {
+            let (owner, name) = (&current.remote.owner, &current.remote.repo);
+            let pr = rt
+                .block_on(current.client.get_pr(owner, name, number))
+                .with_context(|| format!("Failed to fetch PR #{number} in {}", current.repo))?;
+            let body = pr.body.unwrap_or_default();
+            let updated = pr_body::set_related_prs(&body, &related);
+            if updated == body {
+                continue;
+            }
+            let update = UpdatePullRequest {
+                title: None,
+                body: Some(updated),
+                base: None,
+            };
+            rt.block_on(current.client.update_pr(owner, name, number, update))
+                .with_context(|| format!("Failed to update PR #{number} in {}", current.repo))?;
+            if !json {
+                output::info(&format!("Linked {}#{number}", current.remote));
+            }
+            linked += 1;
+        }
+    }
+    Ok(linked)
+}
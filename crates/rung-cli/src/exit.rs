@@ -0,0 +1,51 @@
+//! Process exit codes, part of rung's interface for scripts and CI.
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0    | Success |
+//! | 1    | Error |
+//! | 2    | Invalid arguments |
+//! | 3    | Stopped on a conflict (`sync`, `merge`) or a failing `sync --exec` |
+//! | 4    | Input needed but no terminal: a prompt or editor, with no flag given |
+//! | 130  | Interrupted |
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::prompt::NeedsInput;
+
+/// Exit codes other than success and clap's usage errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Code {
+    Error = 1,
+    Paused = 3,
+    NeedsInput = 4,
+    Interrupted = 130,
+}
+
+static PENDING: AtomicU8 = AtomicU8::new(0);
+
+/// Exit with `code` after the command finishes, for outcomes it reports
+/// normally (including as JSON) that still aren't a success.
+pub fn set(code: Code) {
+    PENDING.store(code as u8, Ordering::Relaxed);
+}
+
+/// Exit code for a command that returned normally.
+pub fn pending() -> i32 {
+    i32::from(PENDING.load(Ordering::Relaxed))
+}
+
+/// Exit code for a command that failed with `error`.
+pub fn for_error(error: &anyhow::Error) -> i32 {
+    let needs_input = || {
+        error
+            .chain()
+            .any(|cause| cause.downcast_ref::<NeedsInput>().is_some())
+    };
+    match pending() {
+        0 if needs_input() => Code::NeedsInput as i32,
+        0 => Code::Error as i32,
+        code => code,
+    }
+}
@@ -0,0 +1,155 @@
+//! Translatable messages for human-readable output.
+//!
+//! Messages live in catalogs written in a subset of the Fluent syntax: one
+//! `id = text` per message, indented lines continuing it, `#` comments, and
+//! `{ $name }` for arguments. English is built in; a translation is read from
+//! `~/.config/rung/locales/<lang>.ftl` (or under `$XDG_CONFIG_HOME`), where
+//! `<lang>` comes from `RUNG_LANG`, `LC_ALL`, `LC_MESSAGES` or `LANG`.
+//! Messages a translation leaves out fall back to English.
+//!
+//! Only human text goes through here: JSON, porcelain output and exit codes
+//! stay the same in every language.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// The built-in English catalog.
+const ENGLISH: &str = include_str!("../locales/en.ftl");
+
+/// Messages by id: the translation, then English.
+struct Catalogs {
+    translated: HashMap<String, String>,
+    english: HashMap<String, String>,
+}
+
+static CATALOGS: OnceLock<Catalogs> = OnceLock::new();
+
+/// Look up a message and fill in its arguments; use [`tr!`] rather than
+/// calling this directly.
+pub fn text(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let catalogs = CATALOGS.get_or_init(|| Catalogs {
+        translated: load_translation().unwrap_or_default(),
+        english: parse(ENGLISH),
+    });
+    catalogs
+        .translated
+        .get(id)
+        .or_else(|| catalogs.english.get(id))
+        .map_or_else(|| id.to_string(), |message| format(message, args))
+}
+
+/// Look up a message by id, with `name = value` arguments for its
+/// `{ $name }` placeables.
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::text($id, &[])
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::text($id, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+])
+    };
+}
+pub(crate) use tr;
+
+/// The catalog for the user's language, if they have one.
+fn load_translation() -> Option<HashMap<String, String>> {
+    let dir = locales_dir()?;
+    candidates(&language()?).into_iter().find_map(|lang| {
+        std::fs::read_to_string(dir.join(format!("{lang}.ftl")))
+            .ok()
+            .map(|source| parse(&source))
+    })
+}
+
+/// Where translations are installed, next to the user config.
+fn locales_dir() -> Option<PathBuf> {
+    rung_core::Config::user_path()?
+        .parent()
+        .map(|dir| dir.join("locales"))
+}
+
+/// The user's language, or `None` for English.
+fn language() -> Option<String> {
+    let value = ["RUNG_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())?;
+    // `de_DE.UTF-8@euro` names the language `de-DE`
+    let lang = value
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    let english = lang.is_empty()
+        || lang == "C"
+        || lang == "POSIX"
+        || lang.eq_ignore_ascii_case("en")
+        || lang.to_ascii_lowercase().starts_with("en-");
+    (!english).then_some(lang)
+}
+
+/// Catalog names to try for `lang`, most specific first: `de-DE`, then `de`.
+fn candidates(lang: &str) -> Vec<String> {
+    let mut names = vec![lang.to_string()];
+    if let Some((base, _)) = lang.split_once('-') {
+        names.push(base.to_string());
+    }
+    names
+}
+
+/// Messages in a catalog's source. Lines that aren't messages, comments or
+/// continuations are skipped.
+fn parse(source: &str) -> HashMap<String, String> {
+    let mut messages = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+    for line in source.lines() {
+        if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            if let Some((_, message)) = current.as_mut() {
+                if !message.is_empty() {
+                    message.push('\n');
+                }
+                message.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((id, message)) = current.take() {
+            messages.insert(id, message);
+        }
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        if let Some((id, message)) = line.split_once('=') {
+            current = Some((id.trim().to_string(), message.trim().to_string()));
+        }
+    }
+    if let Some((id, message)) = current {
+        messages.insert(id, message);
+    }
+    messages
+}
+
+/// `message` with its `{ $name }` placeables replaced by `args`. Placeables
+/// without an argument are left as written.
+fn format(message: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let placeable = &rest[start..=start + len];
+        let name = placeable[1..placeable.len() - 1].trim();
+        match name
+            .strip_prefix('$')
+            .and_then(|name| args.iter().find(|(arg, _)| *arg == name))
+        {
+            Some((_, value)) => out.push_str(&value.to_string()),
+            None => out.push_str(placeable),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -1,54 +1,291 @@
 //! Rung CLI - The developer's ladder for stacked PRs.
 
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, FromArgMatches};
 
+mod actions;
 mod commands;
+mod exit;
+mod i18n;
 mod output;
+mod pager;
+mod prompt;
+mod signal;
+mod timings;
 
 use commands::{Cli, Commands};
 
+#[allow(clippy::too_many_lines)] // One dispatch arm per command
 fn main() {
     // Respect NO_COLOR environment variable (https://no-color.org/)
     if std::env::var("NO_COLOR").is_ok() {
         colored::control::set_override(false);
     }
 
-    let cli = Cli::parse();
-    output::set_quiet(cli.quiet);
-    let json = cli.json;
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Some(command) = matches.subcommand_name() {
+        rung_git::set_reflog_command(command);
+    }
+    if cli.porcelain.is_some()
+        && !matches!(
+            cli.command,
+            Commands::Status { at: None, .. }
+                | Commands::Sync {
+                    from_webhook: false,
+                    ..
+                }
+                | Commands::Submit { .. }
+        )
+    {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--porcelain is only supported by status (without --at), sync (without --from-webhook) and submit",
+            )
+            .exit();
+    }
+    if cli.timings
+        && !matches!(
+            cli.command,
+            Commands::Status { .. } | Commands::Sync { .. } | Commands::Submit { .. }
+        )
+    {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--timings is only supported by status, sync and submit",
+            )
+            .exit();
+    }
+    output::set_quiet(cli.quiet || cli.porcelain.is_some());
+    output::set_porcelain(cli.porcelain.is_some());
+    let config = output::current_config();
+    output::set_accessible(output::accessible_requested(config.as_ref()));
+    if let Some(config) = &config {
+        output::set_theme(output::Theme::from_config(&config.theme).0);
+    }
+    prompt::set_assume_yes(cli.yes);
+    if cli.non_interactive || prompt::is_ci() {
+        prompt::set_non_interactive(true);
+        colored::control::set_override(false);
+    }
+    // Porcelain output is rendered from the same results as JSON
+    let json = cli.json || cli.porcelain.is_some();
+    output::set_hints(!json && config.as_ref().is_none_or(|config| config.general.hints));
+    timings::set_enabled(cli.timings);
+    let started = std::time::Instant::now();
 
     let result = match cli.command {
-        Commands::Init => commands::init::run(),
-        Commands::Create { name, message } => {
-            commands::create::run(name.as_deref(), message.as_deref())
-        }
-        Commands::Status { fetch } => commands::status::run(json, fetch),
+        Commands::Init { import, reattach } => commands::init::run(import, reattach),
+        Commands::Create {
+            name,
+            message,
+            remote,
+            no_verify,
+            tracked,
+            empty,
+            signoff,
+            force,
+        } => commands::create::run(
+            name.as_deref(),
+            message.as_deref(),
+            remote.as_deref(),
+            no_verify,
+            tracked,
+            empty,
+            signoff,
+            force,
+        ),
+        Commands::Status { at: Some(at), .. } => commands::status_at::run(json, &at),
+        Commands::Status {
+            fetch,
+            urls,
+            watch,
+            remote,
+            short,
+            all,
+            at: None,
+        } => commands::status::run(json, fetch, urls, watch, remote, short, all),
+        Commands::Sync {
+            from_webhook: true,
+            no_push,
+            base,
+            ..
+        } => commands::sync_webhook::run(json, base.as_deref(), no_push),
         Commands::Sync {
             dry_run,
             continue_,
             abort,
             no_push,
             base,
-        } => commands::sync::run(json, dry_run, continue_, abort, no_push, base.as_deref()),
+            exec,
+            show_range_diff,
+            ..
+        } => commands::sync::run(
+            json,
+            dry_run,
+            continue_,
+            abort,
+            no_push,
+            base.as_deref(),
+            exec.as_deref(),
+            show_range_diff,
+        ),
+        Commands::Submit {
+            comments_only: true,
+            ..
+        } => commands::submit::run_comments_only(json),
+        Commands::Submit {
+            push_only: true,
+            force,
+            ..
+        } => commands::submit::run_push_only(json, force),
         Commands::Submit {
             draft,
+            draft_until_green,
             dry_run,
             force,
             title,
-        } => commands::submit::run(json, dry_run, draft, force, title.as_deref()),
-        Commands::Undo => commands::undo::run(),
-        Commands::Merge { method, no_delete } => commands::merge::run(json, &method, no_delete),
+            body_file,
+            attach,
+            milestone,
+            project,
+            override_policy,
+            prs_only,
+            reopen,
+            no_reopen,
+            ..
+        } => commands::submit::run(
+            json,
+            dry_run,
+            draft,
+            draft_until_green,
+            force,
+            &title,
+            &body_file,
+            &attach,
+            milestone.as_deref(),
+            project,
+            override_policy,
+            prs_only,
+            match (reopen, no_reopen) {
+                (true, _) => commands::submit::Reopen::Always,
+                (_, true) => commands::submit::Reopen::Never,
+                _ => commands::submit::Reopen::Ask,
+            },
+        ),
+        Commands::Undo {
+            interactive,
+            branch,
+        } => commands::undo::run(interactive, &branch),
+        Commands::Merge {
+            target,
+            until,
+            method,
+            no_delete,
+            when_ready,
+            ignore_checks,
+            dry_run,
+            override_policy,
+            no_rebase_descendants,
+        } => commands::merge::run(
+            json,
+            target.as_deref(),
+            until.as_deref(),
+            method.as_deref(),
+            no_delete,
+            when_ready,
+            ignore_checks,
+            dry_run,
+            override_policy,
+            !no_rebase_descendants,
+        ),
+        Commands::Copy {
+            branch,
+            onto,
+            name,
+            pr,
+            draft,
+        } => commands::copy::run(json, branch.as_deref(), &onto, name.as_deref(), pr, draft),
+        Commands::ForwardPort { target, dry_run } => {
+            commands::forward_port::run(json, &target, dry_run)
+        }
         Commands::Nxt => commands::navigate::run_next(),
         Commands::Prv => commands::navigate::run_prev(),
-        Commands::Move => commands::mv::run(),
-        Commands::Doctor => commands::doctor::run(json),
+        Commands::Adopt { dry_run, author } => {
+            commands::adopt::run(json, dry_run, author.as_deref())
+        }
+        Commands::Revert { pr, no_pr, draft } => commands::revert::run(json, &pr, no_pr, draft),
+        Commands::Handoff { to } => commands::handoff::run(json, to.as_deref()),
+        Commands::Move { branch } => commands::mv::run(branch.as_deref()),
+        Commands::Doctor { fix, bugreport } => commands::doctor::run(json, fix, bugreport),
+        Commands::Verify => commands::verify::run(json),
+        Commands::Checks { slowest } => commands::checks::run(json, slowest),
+        Commands::History { branch } => commands::history::run(json, branch.as_deref()),
+        Commands::Contains { commit } => commands::contains::run(json, &commit),
+        Commands::Blame { file } => commands::blame::run(json, &file),
         Commands::Update { check } => commands::update::run(check),
         Commands::Completions { shell } => commands::completions::run(shell),
-        Commands::Log => commands::log::run(),
+        Commands::Log {
+            files,
+            operations,
+            patch,
+            no_pager,
+        } => commands::log::run(json, files, operations, patch, no_pager),
+        Commands::Tidy { all, older_than } => commands::tidy::run(json, all, older_than),
+        Commands::Graph { format } => commands::graph::run(format),
+        Commands::Stats { weeks, conflicts } => commands::stats::run(json, weeks, conflicts),
+        Commands::Summary { since } => commands::summary::run(json, since),
+        Commands::Gc {
+            keep_backups,
+            log_days,
+        } => commands::gc::run(json, keep_backups, log_days),
+        Commands::Edit { push } => commands::edit::run(push),
+        Commands::Reword {
+            commit,
+            message,
+            update_pr,
+            push,
+            signoff,
+        } => commands::reword::run(
+            commit.as_deref(),
+            message.as_deref(),
+            update_pr,
+            push,
+            signoff,
+        ),
+        Commands::Scope { paths, clear } => commands::scope::run(json, &paths, clear),
+        Commands::RemoteName { name, clear } => {
+            commands::remote_name::run(json, name.as_deref(), clear)
+        }
+        Commands::Rebase { onto_pr } => commands::rebase::run(json, onto_pr),
+        Commands::RebaseMerges { off } => commands::rebase_merges::run(json, off),
+        Commands::Requires { branches, clear } => commands::requires::run(json, &branches, clear),
+        Commands::Note { command, branch } => commands::note::run(json, command, branch.as_deref()),
+        Commands::Foreach { all, command } => commands::foreach::run(all, &command),
+        Commands::Archive { list: true, .. } => commands::archive::run_list(json),
+        Commands::Archive { name, delete, .. } => {
+            commands::archive::run(json, name.as_deref(), delete)
+        }
+        Commands::Snapshot { command, name } => {
+            commands::snapshot::run(json, command, name.as_deref())
+        }
+        Commands::Unarchive { name } => commands::archive::run_unarchive(json, name.as_deref()),
+        Commands::State { command } => commands::state::run(json, command),
+        Commands::Stack { command } => commands::stack::run(json, command),
+        Commands::Config { command } => commands::config::run(json, &command),
+        Commands::Cache { command } => commands::cache::run(json, &command),
+        Commands::Workspace { command } => {
+            commands::workspace::run(json, cli.quiet, cli.yes, command)
+        }
+        Commands::Tutorial { keep } => commands::tutorial::run(keep),
     };
 
+    timings::report(started.elapsed());
     if let Err(e) = result {
         output::error(&e.to_string());
-        std::process::exit(1);
+        std::process::exit(exit::for_error(&e));
     }
+    std::process::exit(exit::pending());
 }
@@ -1,45 +1,325 @@
 //! Terminal output formatting utilities.
 
+use std::sync::OnceLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use colored::Colorize;
-use rung_core::BranchState;
+use colored::{Color, ColoredString, Colorize};
+use rung_core::ci::CiStatus;
+use rung_core::config::{ThemeConfig, ThemeStyle};
+use rung_core::{BranchState, Config, State};
+use rung_git::Repository;
+
+use crate::i18n::tr;
 
 static QUIET_MODE: AtomicBool = AtomicBool::new(false);
+static PORCELAIN_MODE: AtomicBool = AtomicBool::new(false);
+static ACCESSIBLE_MODE: AtomicBool = AtomicBool::new(false);
+static HINTS: AtomicBool = AtomicBool::new(true);
+static THEME: OnceLock<Theme> = OnceLock::new();
 
 /// Set quiet mode globally. Call once at startup.
 pub fn set_quiet(quiet: bool) {
     QUIET_MODE.store(quiet, Ordering::Relaxed);
 }
 
-fn is_quiet() -> bool {
+/// Whether quiet mode is on.
+pub fn is_quiet() -> bool {
     QUIET_MODE.load(Ordering::Relaxed)
 }
 
+/// Set porcelain mode globally. Call once at startup.
+pub fn set_porcelain(porcelain: bool) {
+    PORCELAIN_MODE.store(porcelain, Ordering::Relaxed);
+}
+
+/// Whether `--porcelain` was given. Commands that support it take their JSON
+/// path, then print [`record`]s in place of the JSON.
+pub fn is_porcelain() -> bool {
+    PORCELAIN_MODE.load(Ordering::Relaxed)
+}
+
+/// Set accessible mode globally. Call once at startup.
+pub fn set_accessible(accessible: bool) {
+    ACCESSIBLE_MODE.store(accessible, Ordering::Relaxed);
+}
+
+/// Whether output should spell out in words what glyphs and colors show,
+/// for screen readers.
+pub fn is_accessible() -> bool {
+    ACCESSIBLE_MODE.load(Ordering::Relaxed)
+}
+
+/// Turn next-step hints on or off globally. Call once at startup.
+pub fn set_hints(hints: bool) {
+    HINTS.store(hints, Ordering::Relaxed);
+}
+
+/// Whether accessible mode is asked for: by `RUNG_ACCESSIBLE`, or failing
+/// that by `general.accessible` in `config`.
+pub fn accessible_requested(config: Option<&Config>) -> bool {
+    if let Ok(value) = std::env::var("RUNG_ACCESSIBLE") {
+        return !matches!(value.as_str(), "" | "0" | "false");
+    }
+    config.is_some_and(|config| config.general.accessible)
+}
+
+/// The current repository's config, for the output settings read at
+/// startup, or `None` outside a repository or if it can't be read.
+pub fn current_config() -> Option<Config> {
+    Repository::open_current()
+        .ok()
+        .map(|repo| State::for_repository(&repo))
+        .and_then(|state| state.load_config().ok())
+}
+
+/// Set the theme globally. Call once at startup; until then (and if never
+/// called) the default theme is used.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The theme in use.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// How one themed element is drawn: its color and glyph.
+#[derive(Debug, Clone)]
+pub struct Style {
+    paint: Paint,
+    glyph: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Paint {
+    Color(Color),
+    Dimmed,
+}
+
+impl Style {
+    fn new(paint: Paint, glyph: &str) -> Self {
+        Self {
+            paint,
+            glyph: glyph.to_string(),
+        }
+    }
+
+    /// `text` in this style's color.
+    pub fn paint(&self, text: &str) -> ColoredString {
+        match self.paint {
+            Paint::Color(color) => text.color(color),
+            Paint::Dimmed => text.dimmed(),
+        }
+    }
+
+    /// The glyph, in this style's color.
+    pub fn glyph(&self) -> String {
+        self.paint(&self.glyph).to_string()
+    }
+
+    /// This style with the color and glyph `style` sets. Problems with them
+    /// are added to `problems`, naming the element as `name`.
+    fn apply(mut self, name: &str, style: Option<&ThemeStyle>, problems: &mut Vec<String>) -> Self {
+        let Some(style) = style else {
+            return self;
+        };
+        if let Some(color) = &style.color {
+            match parse_paint(color) {
+                Some(paint) => self.paint = paint,
+                None => problems.push(format!("Unknown color '{color}' for `theme.{name}`")),
+            }
+        }
+        if let Some(glyph) = &style.glyph {
+            self.glyph.clone_from(glyph);
+        }
+        self
+    }
+}
+
+/// A color name (as `colored` knows them), `#rrggbb`, or `dimmed`.
+fn parse_paint(color: &str) -> Option<Paint> {
+    if color.eq_ignore_ascii_case("dimmed") {
+        return Some(Paint::Dimmed);
+    }
+    if let Some(hex) = color.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return (hex.len() == 6).then_some(()).and_then(|()| {
+            Some(Paint::Color(Color::TrueColor {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            }))
+        });
+    }
+    color.parse().ok().map(Paint::Color)
+}
+
+/// Colors and glyphs for branch states, the current branch and PR numbers,
+/// from the `[theme]` config.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub synced: Style,
+    pub diverged: Style,
+    pub restack: Style,
+    pub conflict: Style,
+    pub detached: Style,
+    pub current: Style,
+    pub pr: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            synced: Style::new(Paint::Color(Color::Green), "●"),
+            diverged: Style::new(Paint::Color(Color::Yellow), "●"),
+            restack: Style::new(Paint::Color(Color::Yellow), "●"),
+            conflict: Style::new(Paint::Color(Color::Red), "●"),
+            detached: Style::new(Paint::Dimmed, "○"),
+            current: Style::new(Paint::Color(Color::Cyan), "▶"),
+            pr: Style::new(Paint::Dimmed, "#"),
+        }
+    }
+}
+
+impl Theme {
+    /// Blue and yellow rather than green and red, and a different shape for
+    /// each state, so no state is told apart by color alone.
+    fn colorblind() -> Self {
+        Self {
+            synced: Style::new(Paint::Color(Color::Blue), "●"),
+            diverged: Style::new(Paint::Color(Color::Yellow), "◐"),
+            restack: Style::new(Paint::Color(Color::Yellow), "◑"),
+            conflict: Style::new(Paint::Color(Color::Magenta), "✖"),
+            ..Self::default()
+        }
+    }
+
+    /// The theme `config` describes, and what's wrong with it. Unknown
+    /// presets and colors fall back to the default ones.
+    pub fn from_config(config: &ThemeConfig) -> (Self, Vec<String>) {
+        let mut problems = vec![];
+        let base = match config.preset.as_deref() {
+            None | Some("default") => Self::default(),
+            Some("colorblind") => Self::colorblind(),
+            Some(other) => {
+                problems.push(format!(
+                    "Unknown `theme.preset` '{other}' (expected one of: {})",
+                    ThemeConfig::PRESETS.join(", ")
+                ));
+                Self::default()
+            }
+        };
+        let theme = Self {
+            synced: base
+                .synced
+                .apply("synced", config.synced.as_ref(), &mut problems),
+            diverged: base
+                .diverged
+                .apply("diverged", config.diverged.as_ref(), &mut problems),
+            restack: base
+                .restack
+                .apply("restack", config.restack.as_ref(), &mut problems),
+            conflict: base
+                .conflict
+                .apply("conflict", config.conflict.as_ref(), &mut problems),
+            detached: base
+                .detached
+                .apply("detached", config.detached.as_ref(), &mut problems),
+            current: base
+                .current
+                .apply("current", config.current.as_ref(), &mut problems),
+            pr: base.pr.apply("pr", config.pr.as_ref(), &mut problems),
+        };
+        (theme, problems)
+    }
+}
+
+/// Print one porcelain record: its type, then its fields, space-separated.
+///
+/// Empty fields print as `-`. Only the last field may contain spaces, so it
+/// is the one to use for paths and commands.
+pub fn record(kind: &str, fields: &[&str]) {
+    let mut line = kind.to_string();
+    for field in fields {
+        line.push(' ');
+        line.push_str(if field.is_empty() { "-" } else { field });
+    }
+    println!("{line}");
+}
+
+/// The serialized name of a unit enum variant, for porcelain fields.
+pub fn token<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
 /// Print a success message (suppressed in quiet mode).
 pub fn success(msg: &str) {
-    if !is_quiet() {
+    if is_quiet() {
+        return;
+    }
+    if is_accessible() {
+        println!("{msg}");
+    } else {
         println!("{} {}", "✓".green(), msg);
     }
 }
 
 /// Print an error message (always prints to stderr).
 pub fn error(msg: &str) {
-    eprintln!("{} {}", "✗".red(), msg);
+    eprintln!("{} {}", error_icon(), msg);
 }
 
 /// Print a warning message (always prints to stderr).
 pub fn warn(msg: &str) {
-    eprintln!("{} {}", "!".yellow(), msg);
+    eprintln!("{} {}", warning_icon("!"), msg);
+}
+
+/// What marks an error: a red cross, or a word in accessible mode.
+pub fn error_icon() -> String {
+    if is_accessible() {
+        tr!("a11y-error")
+    } else {
+        "✗".red().to_string()
+    }
+}
+
+/// What marks a warning: `glyph` in yellow, or a word in accessible mode.
+pub fn warning_icon(glyph: &str) -> String {
+    if is_accessible() {
+        tr!("a11y-warning")
+    } else {
+        glyph.yellow().to_string()
+    }
 }
 
 /// Print an info message (suppressed in quiet mode).
 pub fn info(msg: &str) {
     if !is_quiet() {
-        println!("{} {}", "→".blue(), msg);
+        println!("{}", info_line(msg));
     }
 }
 
+/// An info message formatted as [`info`] prints it, for output that is
+/// collected before printing (such as for a pager).
+pub fn info_line(msg: &str) -> String {
+    if is_accessible() {
+        return msg.to_string();
+    }
+    format!("{} {}", "→".blue(), msg)
+}
+
+/// Print a hint at what to run next (to stderr, so it stays out of piped
+/// output). Off with `general.hints = false`, and in quiet and JSON modes.
+pub fn hint(msg: &str) {
+    if is_quiet() || !HINTS.load(Ordering::Relaxed) {
+        return;
+    }
+    eprintln!("{}", format!("{} {msg}", tr!("hint-prefix")).dimmed());
+}
+
 /// Print essential machine-readable output (always prints).
 ///
 /// Use for results that should be available for piping, like PR URLs.
@@ -50,21 +330,42 @@ pub fn essential(msg: &str) {
 /// Get the status indicator for a branch state.
 #[must_use]
 pub fn state_indicator(state: &BranchState) -> String {
+    if is_accessible() {
+        return match state {
+            BranchState::Synced => tr!("a11y-synced"),
+            BranchState::Diverged { commits_behind } => {
+                tr!("a11y-behind", count = commits_behind)
+            }
+            BranchState::NeedsRestack => tr!("a11y-restack"),
+            BranchState::Conflict { .. } => tr!("a11y-conflict"),
+            BranchState::Detached => tr!("a11y-detached"),
+        };
+    }
+    let theme = theme();
     match state {
-        BranchState::Synced => "●".green().to_string(),
+        BranchState::Synced => theme.synced.glyph(),
         BranchState::Diverged { commits_behind } => {
-            format!("{} ({}↓)", "●".yellow(), commits_behind)
+            format!("{} ({}↓)", theme.diverged.glyph(), commits_behind)
         }
-        BranchState::Conflict { .. } => "●".red().to_string(),
-        BranchState::Detached => "○".dimmed().to_string(),
+        BranchState::NeedsRestack => format!("{} (restack)", theme.restack.glyph()),
+        BranchState::Conflict { .. } => theme.conflict.glyph(),
+        BranchState::Detached => theme.detached.glyph(),
     }
 }
 
 /// Get a colored branch name with current indicator.
 #[must_use]
 pub fn branch_name(name: &str, is_current: bool) -> String {
+    if is_accessible() {
+        return if is_current {
+            tr!("a11y-current-branch", branch = name)
+        } else {
+            tr!("a11y-branch", branch = name)
+        };
+    }
     if is_current {
-        format!("{} {}", "▶".cyan(), name.cyan().bold())
+        let current = &theme().current;
+        format!("{} {}", current.glyph(), current.paint(name).bold())
     } else {
         format!("  {name}")
     }
@@ -73,12 +374,37 @@ pub fn branch_name(name: &str, is_current: bool) -> String {
 /// Format a PR reference.
 #[must_use]
 pub fn pr_ref(number: Option<u64>) -> String {
-    number.map_or_else(String::new, |n| format!("#{n}").dimmed().to_string())
+    if is_accessible() {
+        return number.map_or_else(String::new, |n| tr!("a11y-pr", pr = n));
+    }
+    let pr = &theme().pr;
+    number.map_or_else(String::new, |n| {
+        pr.paint(&format!("{}{n}", pr.glyph)).to_string()
+    })
+}
+
+/// Format a cached CI result.
+#[must_use]
+pub fn ci_indicator(status: Option<CiStatus>) -> String {
+    if is_accessible() {
+        return match status {
+            Some(CiStatus::Passing) => tr!("a11y-ci-passing"),
+            Some(CiStatus::Pending) => tr!("a11y-ci-pending"),
+            Some(CiStatus::Failing) => tr!("a11y-ci-failing"),
+            None => String::new(),
+        };
+    }
+    match status {
+        Some(CiStatus::Passing) => "✓".green().to_string(),
+        Some(CiStatus::Pending) => "◷".yellow().to_string(),
+        Some(CiStatus::Failing) => "✗".red().to_string(),
+        None => String::new(),
+    }
 }
 
-/// Print a horizontal line (suppressed in quiet mode).
+/// Print a horizontal line (suppressed in quiet and accessible modes).
 pub fn hr() {
-    if !is_quiet() {
+    if !is_quiet() && !is_accessible() {
         println!("{}", "─".repeat(50).dimmed());
     }
 }
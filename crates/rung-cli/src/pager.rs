@@ -0,0 +1,69 @@
+//! Paging long output, the way git does.
+//!
+//! Output goes through the user's pager only when stdout is a terminal, so
+//! pipes, scripts and non-interactive mode always get it printed directly.
+
+use std::io::{IsTerminal, Write};
+use std::process::Stdio;
+
+use anyhow::{Context, Result};
+use rung_git::Repository;
+
+/// The pager git would use: `GIT_PAGER`, then `core.pager`, then `PAGER`,
+/// then `less`. `None` when it's set to nothing or `cat`.
+fn pager_command(repo: &Repository) -> Option<String> {
+    let pager = std::env::var("GIT_PAGER")
+        .ok()
+        .or_else(|| repo.config_string("core.pager"))
+        .or_else(|| std::env::var("PAGER").ok())
+        .unwrap_or_else(|| "less".to_string());
+    let pager = pager.trim();
+    (!pager.is_empty() && pager != "cat").then(|| pager.to_string())
+}
+
+/// Exit codes of `sh` and `cmd` when the command isn't found.
+const NOT_FOUND: [i32; 2] = [127, 9009];
+
+/// Show `text` through the pager, or print it when paging is off (`enabled`
+/// is false, stdout isn't a terminal, or no pager is configured) or the pager
+/// can't be run, such as `less` on Windows.
+///
+/// # Errors
+/// Returns error if waiting for the pager fails.
+pub fn show(repo: &Repository, text: &str, enabled: bool) -> Result<()> {
+    let pager =
+        if enabled && !crate::prompt::is_non_interactive() && std::io::stdout().is_terminal() {
+            pager_command(repo)
+        } else {
+            None
+        };
+    let Some(pager) = pager else {
+        print!("{text}");
+        return Ok(());
+    };
+
+    // Same defaults as git: quit if it fits on one screen, keep colors
+    let mut command = rung_core::shell::command(&pager);
+    command.stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    if std::env::var_os("LV").is_none() {
+        command.env("LV", "-c");
+    }
+    let Ok(mut child) = command.spawn() else {
+        print!("{text}");
+        return Ok(());
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe; that's not an error
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let status = child
+        .wait()
+        .with_context(|| format!("Failed to run pager `{pager}`"))?;
+    if status.code().is_some_and(|code| NOT_FOUND.contains(&code)) {
+        print!("{text}");
+    }
+    Ok(())
+}
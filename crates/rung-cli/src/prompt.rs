@@ -0,0 +1,132 @@
+//! Interactive prompts that never hang in scripts.
+//!
+//! Every prompt and editor launch goes through here. When rung isn't attached
+//! to a terminal (CI, pipes, git hooks) prompts either fall back to a safe
+//! answer or fail with a message naming the flag that replaces them.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+use inquire::Confirm;
+use rung_core::State;
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Answer yes to destructive-operation confirmations (`--yes`). Call once at startup.
+pub fn set_assume_yes(yes: bool) {
+    ASSUME_YES.store(yes, Ordering::Relaxed);
+}
+
+/// Never prompt, even with a terminal (`--non-interactive` or CI). Call once at startup.
+pub fn set_non_interactive(non_interactive: bool) {
+    NON_INTERACTIVE.store(non_interactive, Ordering::Relaxed);
+}
+
+/// Whether non-interactive mode is on.
+#[must_use]
+pub fn is_non_interactive() -> bool {
+    NON_INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// Whether `CI` is set in the environment, as GitHub Actions and most other
+/// CI systems do.
+#[must_use]
+pub fn is_ci() -> bool {
+    std::env::var("CI").is_ok_and(|value| !matches!(value.as_str(), "" | "0" | "false"))
+}
+
+/// Whether prompts can be shown: both stdin and stdout must be terminals,
+/// and non-interactive mode must be off.
+#[must_use]
+pub fn is_interactive() -> bool {
+    !is_non_interactive() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Error for input that can't be asked for, such as a confirmation without
+/// a terminal. Exits with [`crate::exit::Code::NeedsInput`].
+#[derive(Debug)]
+pub struct NeedsInput(String);
+
+impl std::fmt::Display for NeedsInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NeedsInput {}
+
+/// Fail with a clear message ([`NeedsInput`]) unless prompts can be shown.
+///
+/// `action` describes what needs the terminal ("Picking a branch"), and
+/// `alternative` how to do it without one ("pass --branch <name>").
+///
+/// # Errors
+/// Returns an error when not running interactively.
+pub fn require_interactive(action: &str, alternative: &str) -> Result<()> {
+    if !is_interactive() {
+        return Err(NeedsInput(format!(
+            "{action} needs an interactive terminal - {alternative}"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Fail unless an editor can be opened: either rung is interactive, or
+/// `env_var` (e.g. `GIT_EDITOR`) is set to script the editor.
+///
+/// # Errors
+/// Returns an error when neither is the case.
+pub fn require_editor(env_var: &str, action: &str, alternative: &str) -> Result<()> {
+    if std::env::var_os(env_var).is_some() {
+        return Ok(());
+    }
+    require_interactive(action, alternative)
+}
+
+/// Ask a yes/no question.
+///
+/// Returns `false` without asking when not interactive, so callers must only
+/// use this where declining is safe and a flag provides the "yes".
+///
+/// # Errors
+/// Returns an error if the prompt is cancelled.
+pub fn confirm(message: &str, default: bool) -> Result<bool> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+
+    Confirm::new(message)
+        .with_default(default)
+        .prompt()
+        .context("Prompt cancelled")
+}
+
+/// Confirm a destructive operation after listing what it will do.
+///
+/// Skipped by the global `--yes` flag or `general.assume_yes` in config.
+/// Without a terminal this fails with a hint to pass `--yes`, rather than
+/// guessing. The summary goes to stderr so `--json` output stays clean.
+///
+/// # Errors
+/// Returns an error when not interactive (and not skipped), or if the prompt
+/// is cancelled.
+pub fn confirm_destructive(state: &State, question: &str, details: &[String]) -> Result<bool> {
+    let configured = state
+        .load_config()
+        .is_ok_and(|config| config.general.assume_yes);
+    if ASSUME_YES.load(Ordering::Relaxed) || configured {
+        return Ok(true);
+    }
+    require_interactive(question.trim_end_matches('?'), "pass --yes to confirm")?;
+
+    for detail in details {
+        eprintln!("  → {detail}");
+    }
+    Confirm::new(question)
+        .with_default(false)
+        .prompt()
+        .context("Prompt cancelled")
+}
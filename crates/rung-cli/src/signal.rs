@@ -0,0 +1,29 @@
+//! Ctrl-C handling for commands that shouldn't stop half-way.
+
+use crate::{exit, output};
+
+/// Install a Ctrl-C handler for the rest of the process.
+///
+/// The first Ctrl-C asks the running operation to stop at the next safe
+/// point (see [`rung_core::interrupt`]); a second one exits immediately.
+pub fn install() {
+    std::thread::spawn(|| {
+        let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        rt.block_on(async {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                if rung_core::interrupt::is_requested() {
+                    std::process::exit(exit::Code::Interrupted as i32);
+                }
+                rung_core::interrupt::request();
+                output::warn(
+                    "Interrupted - stopping after the current step (Ctrl-C again to quit now)",
+                );
+            }
+        });
+    });
+}
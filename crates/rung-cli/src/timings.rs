@@ -0,0 +1,112 @@
+//! Phase-by-phase timings for `--timings`.
+//!
+//! Commands wrap their slow steps in [`time`]; at exit [`report`] prints how
+//! long each phase took in total, what was left over, and how long was spent
+//! waiting on the GitHub API.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use rung_github::ApiTime;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Time and run count of each phase, in the order phases first ran.
+static PHASES: Mutex<Vec<Phase>> = Mutex::new(Vec::new());
+
+/// One named phase, summed over every time it ran.
+struct Phase {
+    name: &'static str,
+    elapsed: Duration,
+    runs: usize,
+}
+
+/// Turn timing on. Call once at startup.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Run `f` as part of `phase`.
+///
+/// Phases shouldn't nest: their times are reported side by side and what's
+/// left of the total is reported as "other".
+pub fn time<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+    let started = Instant::now();
+    let result = f();
+    let elapsed = started.elapsed();
+    if let Ok(mut phases) = PHASES.lock() {
+        match phases.iter_mut().find(|p| p.name == phase) {
+            Some(p) => {
+                p.elapsed += elapsed;
+                p.runs += 1;
+            }
+            None => phases.push(Phase {
+                name: phase,
+                elapsed,
+                runs: 1,
+            }),
+        }
+    }
+    result
+}
+
+/// Print the breakdown of a command that took `total` to stderr, keeping
+/// stdout for `--json` and `--porcelain`.
+pub fn report(total: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(phases) = PHASES.lock() else {
+        return;
+    };
+    for line in render(&phases, total, rung_github::api_time()) {
+        eprintln!("{line}");
+    }
+}
+
+/// The report's lines.
+fn render(phases: &[Phase], total: Duration, api: ApiTime) -> Vec<String> {
+    let mut rows: Vec<(String, Duration)> = phases
+        .iter()
+        .map(|p| {
+            let label = if p.runs > 1 {
+                format!("{} ×{}", p.name, p.runs)
+            } else {
+                p.name.to_string()
+            };
+            (label, p.elapsed)
+        })
+        .collect();
+    let measured: Duration = phases.iter().map(|p| p.elapsed).sum();
+    rows.push(("other".to_string(), total.saturating_sub(measured)));
+    rows.push(("total".to_string(), total));
+
+    let width = rows
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("GitHub API".len());
+    let mut lines = vec!["Timings:".to_string()];
+    for (label, elapsed) in &rows {
+        lines.push(format!("  {label:<width$}  {}", seconds(*elapsed)));
+    }
+    if api.requests > 0 {
+        lines.push(format!(
+            "  {:<width$}  {} waiting on {} request(s), within the phases above",
+            "GitHub API",
+            seconds(api.waited),
+            api.requests
+        ));
+    }
+    lines
+}
+
+/// `elapsed` in seconds, to the millisecond.
+fn seconds(elapsed: Duration) -> String {
+    format!("{:.3}s", elapsed.as_secs_f64())
+}
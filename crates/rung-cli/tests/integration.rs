@@ -60,7 +60,14 @@ fn setup_git_repo() -> TempDir {
 
 /// Helper to get rung command.
 fn rung() -> Command {
-    Command::new(env!("CARGO_BIN_EXE_rung"))
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rung"));
+    // Keep a CI run of these tests from switching on Actions output
+    command
+        .env_remove("GITHUB_ACTIONS")
+        .env_remove("GITHUB_OUTPUT");
+    // Tests match English glyph output whatever the machine's settings
+    command.env("RUNG_LANG", "en").env_remove("RUNG_ACCESSIBLE");
+    command
 }
 
 // ============================================================================
@@ -146,6 +153,114 @@ fn test_init_not_in_git_repo() {
         .stderr(predicate::str::contains("git repository"));
 }
 
+#[test]
+fn test_init_before_first_commit() {
+    let temp = TempDir::new().expect("Failed to create temp dir");
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    git(&["init", "-b", "main"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "user.name", "Test User"]);
+
+    rung()
+        .arg("init")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("'main' has no commits yet"));
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'main' has no commits yet - make the first commit",
+        ));
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .assert()
+        .stdout(predicate::str::contains("'main' has no commits yet"))
+        .stdout(predicate::str::contains("HEAD is detached").not());
+
+    // Once the first commit is made, stacking works as usual
+    git(&["commit", "--allow-empty", "-m", "Initial commit"]);
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_init_import() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    for (branch, base) in [("feat-a", "main"), ("feat-b", "feat-a"), ("fix-c", "main")] {
+        git(&["checkout", "-q", "-b", branch, base]);
+        git(&["commit", "-q", "--allow-empty", "-m", branch]);
+    }
+    // Already in main, so not offered
+    git(&["branch", "merged", "main"]);
+    git(&["checkout", "-q", "main"]);
+
+    rung()
+        .arg("init")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3 existing branch(es) can be added to the stack with `rung init --import`",
+        ));
+
+    // Without a terminal every branch is imported onto the one it's built on
+    rung()
+        .args(["init", "--import"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feat-b ← feat-a"))
+        .stdout(predicate::str::contains("Imported 3 branch(es)"));
+
+    let stack: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("read stack"),
+    )
+    .expect("parse stack");
+    let parents: Vec<(&str, &str)> = stack["branches"]
+        .as_array()
+        .expect("branches")
+        .iter()
+        .map(|b| {
+            (
+                b["name"].as_str().expect("name"),
+                b["parent"].as_str().expect("parent"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        parents,
+        [("feat-a", "main"), ("fix-c", "main"), ("feat-b", "feat-a")]
+    );
+
+    rung()
+        .args(["init", "--import"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No branches to import"));
+}
+
 // ============================================================================
 // Status command tests
 // ============================================================================
@@ -179,355 +294,7925 @@ fn test_status_empty_stack() {
 }
 
 #[test]
-fn test_status_json_output() {
+fn test_localized_messages() {
     let temp = setup_git_repo();
+    let home = TempDir::new().expect("Failed to create config home");
+    let locales = home.path().join("rung").join("locales");
+    fs::create_dir_all(&locales).expect("Failed to create locales dir");
+    fs::write(
+        locales.join("de.ftl"),
+        "# Partial translation\n\
+         status-empty = Noch keine Branches im Stack.\n\
+         status-heading = Stapel\n\
+         status-legend-synced =\n    synchronisiert\n",
+    )
+    .expect("Failed to write catalog");
+    let localized = |args: &[&str], lang: &str| {
+        let mut command = rung();
+        command
+            .args(args)
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("RUNG_LANG", lang)
+            .current_dir(&temp);
+        command
+    };
 
     rung().arg("init").current_dir(&temp).assert().success();
+    localized(&["status"], "de_DE.UTF-8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Noch keine Branches im Stack."));
 
-    let output = rung()
-        .args(["status", "--json"])
+    rung()
+        .args(["create", "feature-a"])
         .current_dir(&temp)
         .assert()
         .success();
+    // Messages the catalog leaves out stay English
+    localized(&["status"], "de")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Stapel"))
+        .stdout(predicate::str::contains("synchronisiert"))
+        .stdout(predicate::str::contains("needs sync/restack"));
+    localized(&["status"], "fr")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Stack"));
 
-    // Verify it's valid JSON
-    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    assert!(
-        serde_json::from_str::<serde_json::Value>(&stdout).is_ok(),
-        "Status --json should produce valid JSON"
-    );
+    // JSON stays the same in every language
+    let json = localized(&["status", "--json"], "de")
+        .output()
+        .expect("Failed to run status");
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).expect("Invalid JSON");
+    assert_eq!(json["branches"][0]["name"], "feature-a");
 }
 
-// ============================================================================
-// Create command tests
-// ============================================================================
-
 #[test]
-fn test_create_branch() {
+fn test_accessible_mode() {
     let temp = setup_git_repo();
-
     rung().arg("init").current_dir(&temp).assert().success();
-
-    // Create a new branch in the stack
     rung()
-        .args(["create", "feature-1"])
+        .args(["create", "feature-a"])
         .current_dir(&temp)
         .assert()
-        .success()
-        .stdout(predicate::str::contains("feature-1"));
+        .success();
 
-    // Verify we're on the new branch
-    let output = StdCommand::new("git")
-        .args(["branch", "--show-current"])
+    // States are spelled out rather than shown as glyphs and colors
+    let output = rung()
+        .arg("status")
+        .env("RUNG_ACCESSIBLE", "1")
         .current_dir(&temp)
         .output()
-        .expect("Failed to get current branch");
+        .expect("Failed to run status");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("current branch: feature-a, status: synced, parent: main"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains('●') && !stdout.contains('▶') && !stdout.contains('─'));
 
-    let current_branch = String::from_utf8_lossy(&output.stdout);
+    let output = rung()
+        .arg("doctor")
+        .env("RUNG_ACCESSIBLE", "1")
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run doctor");
+    let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(
-        current_branch.trim() == "feature-1",
-        "Should be on feature-1 branch"
+        stdout.contains("Checking rung initialization... ok"),
+        "{stdout}"
     );
+    assert!(!stdout.contains('✓'));
 
-    // Status should show the branch in the stack
+    // Also switched on in config, and off again by the environment
+    rung()
+        .args(["config", "set", "general.accessible", "true"])
+        .current_dir(&temp)
+        .assert()
+        .success();
     rung()
         .arg("status")
         .current_dir(&temp)
         .assert()
         .success()
-        .stdout(predicate::str::contains("feature-1"));
+        .stdout(predicate::str::contains("current branch: feature-a"));
+    rung()
+        .arg("status")
+        .env("RUNG_ACCESSIBLE", "0")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("●"));
 }
 
 #[test]
-fn test_create_stacked_branches() {
+fn test_theme() {
     let temp = setup_git_repo();
-
     rung().arg("init").current_dir(&temp).assert().success();
-
-    // Create first branch
     rung()
-        .args(["create", "feature-1"])
+        .args(["create", "feature-a"])
         .current_dir(&temp)
         .assert()
         .success();
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[theme]\npreset = \"colorblind\"\nsynced = { glyph = \"S\" }\n\
+         current = { glyph = \">\", color = \"chartreuse\" }\n",
+    )
+    .expect("Failed to write config");
 
-    // Make a commit on feature-1
-    let file = temp.path().join("feature1.txt");
-    fs::write(&file, "feature 1 content").expect("Failed to write file");
-
-    StdCommand::new("git")
-        .args(["add", "."])
+    let output = rung()
+        .arg("status")
         .current_dir(&temp)
         .output()
-        .expect("Failed to git add");
+        .expect("Failed to run status");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("S > feature-a"), "{stdout}");
+    // The colorblind preset's glyphs for the states not overridden
+    assert!(stdout.contains("S synced  ◐ needs sync"), "{stdout}");
 
-    StdCommand::new("git")
-        .args(["commit", "-m", "Add feature 1"])
-        .current_dir(&temp)
-        .output()
-        .expect("Failed to commit");
+    let doctor = json_output(&temp, &["doctor"]);
+    let messages: Vec<_> = doctor["issues"]
+        .as_array()
+        .expect("issues")
+        .iter()
+        .filter_map(|issue| issue["message"].as_str())
+        .collect();
+    assert!(
+        messages.contains(&"Unknown color 'chartreuse' for `theme.current`"),
+        "{messages:?}"
+    );
+}
 
-    // Create second branch stacked on first
-    rung()
-        .args(["create", "feature-2"])
+#[test]
+fn test_status_json_output() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    let output = rung()
+        .args(["status", "--json"])
         .current_dir(&temp)
         .assert()
         .success();
 
-    // Status should show both branches
-    rung()
-        .arg("status")
-        .current_dir(&temp)
-        .assert()
-        .success()
-        .stdout(predicate::str::contains("feature-1"))
-        .stdout(predicate::str::contains("feature-2"));
+    // Verify it's valid JSON
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        serde_json::from_str::<serde_json::Value>(&stdout).is_ok(),
+        "Status --json should produce valid JSON"
+    );
 }
 
 #[test]
-fn test_create_alias() {
+fn test_worktree_shares_stack() {
     let temp = setup_git_repo();
-
     rung().arg("init").current_dir(&temp).assert().success();
-
-    // Use 'c' alias instead of 'create'
     rung()
-        .args(["c", "feature-alias"])
+        .args(["create", "feature-1"])
         .current_dir(&temp)
         .assert()
-        .success()
-        .stdout(predicate::str::contains("feature-alias"));
-}
+        .success();
+    let other = TempDir::new().expect("Failed to create temp dir");
+    let worktree = other.path().join("wt");
+    for args in [
+        &["checkout", "-q", "main"][..],
+        &[
+            "worktree",
+            "add",
+            "-q",
+            &worktree.display().to_string(),
+            "feature-1",
+        ],
+    ] {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    }
 
-// ============================================================================
-// Navigation command tests
-// ============================================================================
+    // The worktree sees the same stack, stacking on its own current branch
+    rung()
+        .args(["create", "feature-2"])
+        .current_dir(&worktree)
+        .assert()
+        .success();
+    let status = |dir: &std::path::Path| -> serde_json::Value {
+        let output = rung()
+            .args(["status", "--json"])
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run rung");
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON")
+    };
+    let main = status(temp.path());
+    assert_eq!(main["current"], "main");
+    assert_eq!(main["branches"][1]["name"], "feature-2");
+    assert_eq!(main["branches"][1]["parent"], "feature-1");
+    assert_eq!(status(&worktree)["current"], "feature-2");
+    assert!(!temp.path().join(".git/worktrees/wt/rung").exists());
+}
 
 #[test]
-fn test_navigate_up_down() {
+fn test_status_urls() {
     let temp = setup_git_repo();
 
     rung().arg("init").current_dir(&temp).assert().success();
-
-    // Create a branch
     rung()
         .args(["create", "feature-1"])
         .current_dir(&temp)
         .assert()
         .success();
-
-    // Navigate to parent (main)
-    rung().arg("prv").current_dir(&temp).assert().success();
-
-    // Verify we're on main
-    let output = StdCommand::new("git")
-        .args(["branch", "--show-current"])
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
         .current_dir(&temp)
         .output()
-        .expect("Failed to get current branch");
+        .expect("Failed to add remote");
 
-    let current_branch = String::from_utf8_lossy(&output.stdout);
-    assert!(current_branch.trim() == "main", "Should be on main branch");
+    // Record a PR number as `rung submit` would
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("Failed to read stack"))
+            .expect("Invalid stack.json");
+    stack["branches"][0]["pr"] = serde_json::json!(42);
+    fs::write(&stack_path, stack.to_string()).expect("Failed to write stack");
 
-    // Navigate to child (feature-1)
-    rung().arg("nxt").current_dir(&temp).assert().success();
+    rung()
+        .args(["status", "--urls"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout("feature-1\thttps://github.com/acme/widgets/pull/42\n");
 
-    let output = StdCommand::new("git")
-        .args(["branch", "--show-current"])
+    let output = rung()
+        .args(["status", "--json"])
         .current_dir(&temp)
         .output()
-        .expect("Failed to get current branch");
-
-    let current_branch = String::from_utf8_lossy(&output.stdout);
-    assert!(
-        current_branch.trim() == "feature-1",
-        "Should be on feature-1 branch"
+        .expect("Failed to run status");
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(
+        json["branches"][0]["pr_url"],
+        "https://github.com/acme/widgets/pull/42"
     );
 }
 
 #[test]
-fn test_navigate_no_parent() {
+fn test_status_urls_enterprise_remote() {
     let temp = setup_git_repo();
 
     rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "ssh://git@github.example.com:7999/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("Failed to read stack"))
+            .expect("Invalid stack.json");
+    stack["branches"][0]["pr"] = serde_json::json!(42);
+    fs::write(&stack_path, stack.to_string()).expect("Failed to write stack");
 
-    // Try to navigate to parent from main (exits 0 with info message)
+    // Unknown hosts aren't treated as GitHub
     rung()
-        .arg("prv")
+        .args(["status", "--urls"])
         .current_dir(&temp)
         .assert()
         .success()
-        .stdout(predicate::str::contains("no parent"));
-}
-
-#[test]
-fn test_navigate_no_child() {
-    let temp = setup_git_repo();
-
-    rung().arg("init").current_dir(&temp).assert().success();
+        .stdout("");
 
-    // Try to navigate to child from main with no children (exits 0 with info message)
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[github]\nhosts = [\"github.example.com\"]\n",
+    )
+    .expect("Failed to write config");
     rung()
-        .arg("nxt")
+        .args(["status", "--urls"])
         .current_dir(&temp)
         .assert()
         .success()
-        .stdout(predicate::str::contains("no children"));
+        .stdout("feature-1\thttps://github.example.com/acme/widgets/pull/42\n");
 }
 
-// Note: Interactive move command tests are limited because inquire
-// requires a TTY which is not available in the test environment.
-// The command is tested via help output only.
-
 #[test]
-fn test_move_in_help() {
-    // Verify move command is registered and shows in main help
+fn test_status_uses_cached_branch_states() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
     rung()
-        .arg("--help")
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
         .assert()
-        .success()
-        .stdout(predicate::str::contains("move"))
-        .stdout(predicate::str::contains("Interactive branch picker"));
+        .success();
+
+    let status = |temp: &TempDir| -> serde_json::Value {
+        let output = rung()
+            .args(["status", "--json"])
+            .current_dir(temp)
+            .output()
+            .expect("Failed to run status");
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output")
+    };
+    assert_eq!(status(&temp)["branches"][0]["state"]["status"], "synced");
+
+    // An unchanged branch is read back from the cache
+    let cache_path = temp.path().join(".git/rung/status.json");
+    let mut cache: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&cache_path).expect("Failed to read cache"))
+            .expect("Invalid status.json");
+    cache["branches"]["feature-1"]["state"] = serde_json::json!({ "status": "detached" });
+    fs::write(&cache_path, cache.to_string()).expect("Failed to write cache");
+    assert_eq!(status(&temp)["branches"][0]["state"]["status"], "detached");
+
+    // Moving the parent invalidates it
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "Move main"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+    StdCommand::new("git")
+        .args(["branch", "-f", "main", "HEAD"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to move main");
+    StdCommand::new("git")
+        .args(["reset", "--hard", "HEAD~1"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to reset");
+    let json = status(&temp);
+    assert_eq!(json["branches"][0]["state"]["status"], "diverged");
+    assert_eq!(json["branches"][0]["state"]["commits_behind"], 1);
 }
 
 // ============================================================================
-// Doctor command tests
+// Create command tests
 // ============================================================================
 
 #[test]
-fn test_doctor_healthy_repo() {
+fn test_create_branch() {
     let temp = setup_git_repo();
 
     rung().arg("init").current_dir(&temp).assert().success();
 
+    // Create a new branch in the stack
     rung()
-        .arg("doctor")
+        .args(["create", "feature-1"])
         .current_dir(&temp)
         .assert()
         .success()
-        .stdout(predicate::str::contains("✓").or(predicate::str::contains("OK")));
-}
+        .stdout(predicate::str::contains("feature-1"));
+
+    // Verify we're on the new branch
+    let output = StdCommand::new("git")
+        .args(["branch", "--show-current"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to get current branch");
+
+    let current_branch = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        current_branch.trim() == "feature-1",
+        "Should be on feature-1 branch"
+    );
+
+    // Status should show the branch in the stack
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feature-1"));
+}
+
+#[test]
+fn test_deep_stack_guard_rails() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[general]\nwarn_depth = 3\nmax_depth = 4\n",
+    )
+    .expect("Failed to write config");
+    let create = |name: &str| rung().args(["create", name]).current_dir(&temp).assert();
+
+    create("b1")
+        .success()
+        .stderr(predicate::str::contains("deep").not());
+    create("b2").success();
+    create("b3")
+        .success()
+        .stderr(predicate::str::contains("The stack is 3 branches deep"));
+    create("b4").success();
+    create("b5")
+        .failure()
+        .stderr(predicate::str::contains("beyond max_depth (4)"));
+    rung()
+        .args(["create", "b5", "--force"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let doctor = json_output(&temp, &["doctor"]);
+    assert!(
+        doctor["issues"]
+            .as_array()
+            .expect("No issues")
+            .iter()
+            .any(|i| i["message"] == "The stack is 5 branches deep"),
+        "{doctor}"
+    );
+
+    // Long stacks are shown a page at a time around the current branch
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[general]\nwarn_depth = 0\nmax_depth = 0\n",
+    )
+    .expect("Failed to write config");
+    for n in 6..=23 {
+        create(&format!("b{n}")).success();
+    }
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 more branches above"))
+        .stdout(predicate::str::contains(" b1 ").not())
+        .stdout(predicate::str::contains(" b23 "));
+    rung()
+        .args(["status", "--all"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(" b1 "))
+        .stdout(predicate::str::contains("more branches").not());
+}
+
+#[test]
+fn test_create_prints_next_step_hints() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    rung()
+        .args(["create", "empty-branch"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "hint: Commit your changes, then `rung submit`",
+        ));
+
+    fs::write(temp.path().join("feature.txt"), "feature").expect("Failed to write file");
+    rung()
+        .args(["create", "-m", "Add feature"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "hint: `rung submit` pushes it and opens a PR",
+        ));
+
+    // Quiet output has no hints, and experts can turn them off
+    rung()
+        .args(["-q", "create", "quiet-branch"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("hint:").not());
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[general]\nhints = false\n",
+    )
+    .expect("Failed to write config");
+    rung()
+        .args(["create", "no-hints"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("hint:").not());
+}
+
+#[test]
+fn test_create_with_message_stages_like_git() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[general]\nmax_file_size_mb = 1\n",
+    )
+    .expect("Failed to write config");
+
+    // Too large: refused before the branch is created
+    fs::write(temp.path().join("big.bin"), vec![0u8; 2 * 1024 * 1024]).expect("write");
+    rung()
+        .args(["create", "-m", "Add data"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("big.bin (2 MB)"));
+    let output = StdCommand::new("git")
+        .args(["branch", "--list", "add-data"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to list branches");
+    assert!(output.stdout.is_empty());
+
+    // Ignored files are left out, new untracked files are committed
+    StdCommand::new("git")
+        .args(["reset", "-q"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to unstage");
+    fs::write(temp.path().join(".gitignore"), "*.bin\n").expect("write");
+    fs::write(temp.path().join("notes.txt"), "notes").expect("write");
+    rung()
+        .args(["create", "-m", "Add notes"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created commit: Add notes"));
+    let output = StdCommand::new("git")
+        .args(["show", "--name-only", "--format=", "HEAD"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to show commit");
+    let files = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        files.lines().collect::<Vec<_>>(),
+        [".gitignore", "notes.txt"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_create_tracked_and_empty() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // --tracked commits the README edit but leaves the new file untracked
+    fs::write(temp.path().join("README.md"), "# Edited\n").expect("write");
+    fs::write(temp.path().join("scratch.txt"), "scratch").expect("write");
+    rung()
+        .args(["create", "-m", "Edit readme", "--tracked"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    assert_eq!(
+        git(&["show", "--name-only", "--format=", "HEAD"]),
+        "README.md"
+    );
+    assert_eq!(git(&["status", "--porcelain"]), "?? scratch.txt");
+
+    // --empty refuses to sweep up staged changes
+    git(&["add", "scratch.txt"]);
+    rung()
+        .args(["create", "-m", "Placeholder", "--empty"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unstage them first"));
+
+    git(&["reset", "-q"]);
+    rung()
+        .args(["create", "-m", "Placeholder", "--empty"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created commit: Placeholder"));
+    assert_eq!(git(&["rev-parse", "--abbrev-ref", "HEAD"]), "placeholder");
+    assert_eq!(git(&["show", "--name-only", "--format=", "HEAD"]), "");
+
+    // Both need a message
+    rung()
+        .args(["create", "other", "--empty"])
+        .current_dir(&temp)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_create_applies_scaffolding() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    fs::create_dir(temp.path().join(".rung")).expect("mkdir");
+    fs::write(
+        temp.path().join(".rung/changelog.md"),
+        "## {branch}\n\nStacked on {parent}.\n",
+    )
+    .expect("write template");
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[create]\n\
+         scaffold = 'echo \"$RUNG_BRANCH <- $RUNG_PARENT\" >> branches.txt'\n\
+         templates = [{ from = \".rung/changelog.md\", to = \"changelog/{branch}.md\" }]\n",
+    )
+    .expect("write config");
+
+    rung()
+        .args(["create", "feature-a"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created changelog/feature-a.md"));
+
+    let entry =
+        fs::read_to_string(temp.path().join("changelog/feature-a.md")).expect("template applied");
+    assert_eq!(entry, "## feature-a\n\nStacked on main.\n");
+    let tracking = fs::read_to_string(temp.path().join("branches.txt")).expect("scaffold ran");
+    assert_eq!(tracking, "feature-a <- main\n");
+
+    // An existing destination is left alone, and a failing command only warns
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[create]\n\
+         scaffold = 'exit 3'\n\
+         templates = [{ from = \".rung/changelog.md\", to = \"changelog/feature-a.md\" }]\n",
+    )
+    .expect("write config");
+    rung()
+        .args(["create", "feature-b"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("already exists"))
+        .stderr(predicate::str::contains("Scaffold command exited"));
+    assert_eq!(
+        fs::read_to_string(temp.path().join("changelog/feature-a.md")).expect("read"),
+        entry
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_create_runs_commit_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    let hooks = temp.path().join(".git/hooks");
+    fs::create_dir_all(&hooks).expect("Failed to create hooks dir");
+    let install = |name: &str, script: &str| {
+        let path = hooks.join(name);
+        fs::write(&path, script).expect("Failed to write hook");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod");
+    };
+    install("pre-commit", "#!/bin/sh\necho 'lint failed' >&2\nexit 1\n");
+    install(
+        "commit-msg",
+        "#!/bin/sh\nprintf '\\nReviewed-by: Hook\\n' >> \"$1\"\n",
+    );
+    fs::write(temp.path().join("feature.txt"), "feature").expect("write");
+
+    // A rejected commit leaves no branch behind
+    rung()
+        .args(["create", "-m", "Add feature"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pre-commit rejected the commit"));
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("add-feature").not());
+
+    // --no-verify skips both hooks
+    rung()
+        .args(["create", "-m", "Add feature", "--no-verify"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // commit-msg can edit the message
+    install("pre-commit", "#!/bin/sh\nexit 0\n");
+    fs::write(temp.path().join("more.txt"), "more").expect("write");
+    rung()
+        .args(["create", "-m", "Add more"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let output = StdCommand::new("git")
+        .args(["log", "--format=%B", "-2"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to read log");
+    let log = String::from_utf8_lossy(&output.stdout);
+    assert!(log.contains("Add more\nReviewed-by: Hook"));
+    assert_eq!(log.matches("Reviewed-by").count(), 1);
+}
+
+#[test]
+fn test_create_stacked_branches() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Create first branch
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Make a commit on feature-1
+    let file = temp.path().join("feature1.txt");
+    fs::write(&file, "feature 1 content").expect("Failed to write file");
+
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to git add");
+
+    StdCommand::new("git")
+        .args(["commit", "-m", "Add feature 1"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+
+    // Create second branch stacked on first
+    rung()
+        .args(["create", "feature-2"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Status should show both branches
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feature-1"))
+        .stdout(predicate::str::contains("feature-2"));
+}
+
+#[test]
+fn test_submit_per_branch_overrides() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Bodies are read before anything is pushed
+    rung()
+        .args(["submit", "--body-file", "feature-1=missing.md"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to read PR body for feature-1 from missing.md",
+        ));
+
+    // A bare title is for the current branch, clashing with the named one
+    rung()
+        .args(["submit", "-t", "a=b", "-t", "feature-1=Feature one"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--title given more than once for feature-1",
+        ));
+}
+
+#[test]
+fn test_create_with_remote_name() {
+    let temp = setup_git_repo();
+    let remote = TempDir::new().expect("Failed to create temp dir");
+    StdCommand::new("git")
+        .args(["init", "--bare"])
+        .current_dir(&remote)
+        .output()
+        .expect("Failed to init bare repo");
+    StdCommand::new("git")
+        .args(["remote", "add", "origin"])
+        .arg(remote.path())
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "me/feature", "--remote", "feature"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    rung()
+        .args(["--json", "remote-name"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""remote": "feature""#));
+
+    // Sync pushes under the remote name
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let refs = StdCommand::new("git")
+        .args(["for-each-ref", "--format=%(refname)"])
+        .current_dir(&remote)
+        .output()
+        .expect("Failed to list remote refs");
+    let refs = String::from_utf8_lossy(&refs.stdout);
+    assert!(refs.contains("refs/heads/feature"), "{refs}");
+    assert!(!refs.contains("me/feature"), "{refs}");
+
+    rung()
+        .args(["remote-name", "--clear"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pushed as 'me/feature'"));
+}
+
+#[test]
+fn test_create_uses_base_rules() {
+    let temp = setup_git_repo();
+    for branch in ["release/1.9", "release/1.10", "release/1.2"] {
+        StdCommand::new("git")
+            .args(["branch", branch])
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to create branch");
+    }
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[[bases]]\nbranches = \"hotfix/*\"\nbase = \"release/*\"\n",
+    )
+    .expect("Failed to write config");
+
+    // A hotfix starts from the newest release, wherever it's created
+    rung()
+        .args(["create", "hotfix/login"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Created branch 'hotfix/login' with parent 'release/1.10'",
+        ));
+    let stack =
+        fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("Failed to read stack");
+    assert!(stack.contains(r#""parent": "release/1.10""#), "{stack}");
+
+    // Other branches, and branches stacked on the hotfix, are unaffected
+    rung()
+        .args(["create", "hotfix/login-tests"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("with parent 'hotfix/login'"));
+    StdCommand::new("git")
+        .args(["checkout", "-q", "main"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to checkout main");
+    rung()
+        .args(["create", "feature"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("with parent 'main'"));
+}
+
+#[test]
+fn test_create_alias() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Use 'c' alias instead of 'create'
+    rung()
+        .args(["c", "feature-alias"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feature-alias"));
+}
+
+#[test]
+fn test_completions_include_aliases() {
+    rung()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[alias: st]"));
+
+    for (shell, alias) in [
+        ("bash", "rung,st)"),
+        ("fish", "-a \"st\""),
+        ("powershell", "'st'"),
+        ("zsh", "'st:"),
+        ("nushell", "export extern \"rung st\""),
+    ] {
+        rung()
+            .args(["completions", shell])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(alias));
+    }
+}
+
+// ============================================================================
+// Navigation command tests
+// ============================================================================
+
+#[test]
+fn test_navigate_up_down() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Create a branch
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Navigate to parent (main)
+    rung().arg("prv").current_dir(&temp).assert().success();
+
+    // Verify we're on main
+    let output = StdCommand::new("git")
+        .args(["branch", "--show-current"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to get current branch");
+
+    let current_branch = String::from_utf8_lossy(&output.stdout);
+    assert!(current_branch.trim() == "main", "Should be on main branch");
+
+    // Navigate to child (feature-1)
+    rung().arg("nxt").current_dir(&temp).assert().success();
+
+    let output = StdCommand::new("git")
+        .args(["branch", "--show-current"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to get current branch");
+
+    let current_branch = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        current_branch.trim() == "feature-1",
+        "Should be on feature-1 branch"
+    );
+}
+
+#[test]
+fn test_navigate_no_parent() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Try to navigate to parent from main (exits 0 with info message)
+    rung()
+        .arg("prv")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no parent"));
+}
+
+#[test]
+fn test_navigate_no_child() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Try to navigate to child from main with no children (exits 0 with info message)
+    rung()
+        .arg("nxt")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no children"));
+}
+
+// Note: Interactive move command tests are limited because inquire
+// requires a TTY which is not available in the test environment.
+// The command is tested via help output only.
+
+#[test]
+fn test_move_in_help() {
+    // Verify move command is registered and shows in main help
+    rung()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("move"))
+        .stdout(predicate::str::contains("Interactive branch picker"));
+}
+
+#[test]
+fn test_prompts_without_terminal() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Tests never run on a terminal, so prompts must fail rather than hang
+    rung()
+        .arg("move")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --branch <name>"));
+    rung()
+        .arg("reword")
+        .env_remove("GIT_EDITOR")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --message"));
+
+    rung()
+        .args(["move", "--branch", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Switched to 'main'"));
+    let output = StdCommand::new("git")
+        .args(["branch", "--show-current"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to get current branch");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "main");
+}
+
+// ============================================================================
+// Doctor command tests
+// ============================================================================
+
+#[test]
+fn test_doctor_healthy_repo() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓").or(predicate::str::contains("OK")));
+}
+
+#[test]
+fn test_doctor_not_initialized() {
+    let temp = setup_git_repo();
+
+    // Doctor on uninitialized repo reports the issue (exits 0 with diagnostic info)
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("not initialized"));
+}
+
+#[test]
+fn test_ssh_alias_remote() {
+    let temp = setup_git_repo();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "git@github-work:acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .assert()
+        .stdout(predicate::str::contains(
+            "Origin is not a GitHub repository",
+        ))
+        .stdout(predicate::str::contains("ssh_aliases"));
+
+    // Mapped to the host it stands for, the alias resolves to the repository
+    rung()
+        .args([
+            "config",
+            "set",
+            "github.ssh_aliases.github-work",
+            "github.com",
+        ])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .assert()
+        .stdout(predicate::str::contains("Origin is not a GitHub repository").not());
+    rung().arg("status").current_dir(&temp).assert().success();
+    let origin =
+        fs::read_to_string(temp.path().join(".git/rung/origin")).expect("No origin recorded");
+    assert_eq!(origin.trim(), "acme/widgets");
+}
+
+#[test]
+fn test_github_actions_output() {
+    let temp = setup_json_fixture();
+    let outputs = temp.path().join(".git/step-outputs");
+    let actions = |args: &[&str]| {
+        rung()
+            .args(args)
+            .current_dir(&temp)
+            .env("GITHUB_ACTIONS", "true")
+            .env("GITHUB_OUTPUT", &outputs)
+            .output()
+            .expect("Failed to run rung")
+    };
+
+    // Doctor findings become annotations, and the counts step outputs
+    let output = actions(&["--json", "doctor"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("::warning::No origin remote configured"),
+        "{stderr}"
+    );
+    serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("JSON stays clean");
+    let written = fs::read_to_string(&outputs).expect("No step outputs");
+    assert!(written.contains("healthy=false\n"), "{written}");
+    assert!(written.contains("errors=0\n"), "{written}");
+
+    fs::remove_file(&outputs).expect("Failed to remove outputs");
+    assert!(actions(&["sync", "--base", "main"]).status.success());
+    let written = fs::read_to_string(&outputs).expect("No step outputs");
+    assert!(written.contains("status=complete\n"), "{written}");
+    assert!(written.contains("branches_rebased=2\n"), "{written}");
+
+    // Nothing is written outside Actions
+    fs::remove_file(&outputs).expect("Failed to remove outputs");
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .env("GITHUB_OUTPUT", &outputs)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("::warning::").not());
+    assert!(!outputs.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_doctor_checks_git_version() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // A stand-in git that only answers --version
+    let bin = TempDir::new().expect("Failed to create temp dir");
+    let fake_git = |version: &str| {
+        let path = bin.path().join("git");
+        fs::write(&path, format!("#!/bin/sh\necho 'git version {version}'\n")).expect("write");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod");
+    };
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    fake_git("2.17.1");
+    rung()
+        .arg("doctor")
+        .env("PATH", &path)
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "git 2.17.1 is too old - rung needs 2.18.0 or newer",
+        ));
+
+    // Optional modes are checked against the version they need
+    fake_git("2.25.0");
+    rung()
+        .arg("doctor")
+        .env("PATH", &path)
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("can't keep committer dates").not());
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[sync]\ncommitter_date_is_author_date = true\n",
+    )
+    .expect("write config");
+    rung()
+        .arg("doctor")
+        .env("PATH", &path)
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "git 2.25.0 can't keep committer dates when rebasing - needs 2.29.0 or newer",
+        ));
+}
+
+#[test]
+fn test_doctor_fix_restores_corrupt_stack() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Truncate stack.json as an interrupted write would
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let content = fs::read_to_string(&stack_path).unwrap();
+    fs::write(&stack_path, &content[..content.len() / 2]).unwrap();
+
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("corrupt"));
+
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rung doctor --fix"));
+
+    rung()
+        .args(["doctor", "--fix"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "restored from the previous version",
+        ));
+
+    rung().arg("status").current_dir(&temp).assert().success();
+    assert!(temp.path().join(".git/rung/stack.json.corrupt").exists());
+}
+
+#[test]
+fn test_doctor_checks_remote_tracking() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    StdCommand::new("git")
+        .args(["init", "-q", "--bare"])
+        .current_dir(&origin)
+        .output()
+        .expect("Failed to run git");
+    git(&["remote", "add", "origin", &origin.path().to_string_lossy()]);
+    git(&["push", "-q", "origin", "main", "add-widget", "add-gadget"]);
+    git(&["branch", "-u", "origin/main", "add-gadget"]);
+    git(&["config", "push.default", "matching"]);
+
+    let output = rung()
+        .args(["--json", "doctor"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run rung");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for message in [
+        "push.default is `matching`",
+        "Branch 'add-widget' has no upstream, but rung pushes it to origin/add-widget",
+        "Branch 'add-gadget' tracks origin/main, but rung pushes it to origin/add-gadget",
+    ] {
+        assert!(stdout.contains(message), "{stdout}");
+    }
+
+    rung()
+        .args(["doctor", "--fix"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Branch 'add-gadget' tracks origin/main and now tracks origin/add-gadget",
+        ));
+    let upstream = StdCommand::new("git")
+        .args(["config", "branch.add-widget.merge"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    assert_eq!(
+        String::from_utf8_lossy(&upstream.stdout).trim(),
+        "refs/heads/add-widget"
+    );
+    rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("upstream").not())
+        .stdout(predicate::str::contains("now tracks").not());
+}
+
+#[test]
+fn test_doctor_counts_commits_as_activity() {
+    let temp = setup_json_fixture();
+    // Both branches were created by rung long ago...
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("read stack"))
+            .expect("parse stack");
+    for branch in stack["branches"].as_array_mut().expect("branches") {
+        branch["created"] = "2020-01-01T00:00:00Z".into();
+    }
+    fs::write(&stack_path, stack.to_string()).expect("write stack");
+    // ...but only add-widget has been committed to since
+    let amend = StdCommand::new("git")
+        .args(["commit", "-q", "--amend", "--no-edit"])
+        .env("GIT_COMMITTER_DATE", "2020-01-01T00:00:00Z")
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    assert!(amend.status.success());
+
+    let output = rung()
+        .args(["--json", "doctor"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run rung");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Branch 'add-gadget' has been stale for"),
+        "{stdout}"
+    );
+    assert!(
+        !stdout.contains("Branch 'add-widget' has been stale"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_refuses_to_mutate_during_git_merge() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args(["merge", "--no-ff", "--no-commit", "main"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+
+    rung()
+        .args(["create", "feature-3"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("a git merge is in progress"))
+        .stderr(predicate::str::contains("`git merge --abort`"));
+    rung()
+        .arg("prv")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("a git merge is in progress"));
+
+    // Read-only commands still work
+    rung().arg("status").current_dir(&temp).assert().success();
+    rung().arg("log").current_dir(&temp).assert().success();
+    let output = rung()
+        .arg("doctor")
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run rung");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("A git merge is in progress"), "{stdout}");
+
+    StdCommand::new("git")
+        .args(["merge", "--abort"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    rung().arg("prv").current_dir(&temp).assert().success();
+}
+
+#[test]
+fn test_identity_from_include_if() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://github.com/acme/widgets.git",
+    ]);
+    let api = mock_github_with(vec![
+        (
+            "GET /user/emails ",
+            r#"[{"email":"ana@work.example","verified":true},{"email":"old@work.example","verified":false}]"#
+                .to_string(),
+        ),
+        ("GET /user ", r#"{"login":"octo"}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+    let mismatch = |json: &serde_json::Value| {
+        json["issues"]
+            .as_array()
+            .expect("No issues")
+            .iter()
+            .any(|i| i["message"].as_str().is_some_and(|m| m.contains("@octo")))
+    };
+
+    // The repository's own identity isn't one of the account's emails
+    assert!(mismatch(&json_output(&temp, &["doctor"])));
+
+    // A conditional include for this directory supplies the work identity
+    let work = temp.path().join(".git/work.gitconfig");
+    fs::write(&work, "[user]\n\temail = ana@work.example\n").expect("write");
+    let dir = fs::canonicalize(temp.path()).expect("canonicalize");
+    git(&[
+        "config",
+        &format!("includeIf.gitdir:{}/.path", dir.display()),
+        &work.display().to_string(),
+    ]);
+    assert!(!mismatch(&json_output(&temp, &["doctor"])));
+
+    fs::write(temp.path().join("report.txt"), "report").expect("write");
+    rung()
+        .args(["create", "-m", "Add report"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    assert_eq!(
+        git(&["log", "-1", "--format=%ae %ce"]),
+        "ana@work.example ana@work.example"
+    );
+}
+
+#[test]
+fn test_signoff() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let signed = "Signed-off-by: Test User <test@example.com>";
+
+    fs::write(temp.path().join("report.txt"), "report").expect("write");
+    rung()
+        .args(["create", "--signoff", "-m", "Add report"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    assert_eq!(
+        git(&["log", "-1", "--format=%B"]),
+        format!("Add report\n\n{signed}")
+    );
+
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!(
+            "[general]\nsignoff = true\n\n[github]\napi_url = \"{}\"\n",
+            mock_github()
+        ),
+    )
+    .expect("Failed to write config");
+
+    // Rewording with signoff configured keeps a single trailer
+    rung()
+        .args(["reword", "-m", "Add the report", "--push"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run rung");
+    assert_eq!(
+        git(&["log", "-1", "--format=%B"]),
+        format!("Add the report\n\n{signed}")
+    );
+
+    // The fixture's own commits were never signed off
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://github.com/acme/widgets.git",
+    ]);
+    let output = rung()
+        .args(["submit", "--dry-run"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .output()
+        .expect("Failed to run rung");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "on 'add-widget' isn't signed off by Test User <test@example.com>: Add widget"
+        ),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Add gadget"), "{stderr}");
+    assert!(!stderr.contains("Add the report"), "{stderr}");
+}
+
+#[test]
+fn test_create_warns_when_trunk_is_behind_origin() {
+    let temp = setup_git_repo();
+    let remote = TempDir::new().expect("Failed to create temp dir");
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+    };
+    git(remote.path(), &["init", "--bare"]);
+    git(
+        temp.path(),
+        &["remote", "add", "origin", &remote.path().to_string_lossy()],
+    );
+    git(temp.path(), &["push", "origin", "main"]);
+
+    // Someone else pushes to main
+    let other = TempDir::new().expect("Failed to create temp dir");
+    git(
+        other.path(),
+        &["clone", "-b", "main", &remote.path().to_string_lossy(), "."],
+    );
+    git(other.path(), &["config", "user.email", "other@example.com"]);
+    git(other.path(), &["config", "user.name", "Other"]);
+    fs::write(other.path().join("new.txt"), "new\n").expect("Failed to write file");
+    git(other.path(), &["add", "."]);
+    git(other.path(), &["commit", "-m", "Upstream change"]);
+    git(other.path(), &["push", "origin", "main"]);
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "'main' is 1 commit(s) behind origin/main",
+        ));
+}
+
+// ============================================================================
+// Sync command tests
+// ============================================================================
+
+#[test]
+fn test_sync_dry_run() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Create a branch
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Dry run should succeed without making changes
+    // Note: --base main is required since there's no origin remote in tests
+    rung()
+        .args(["sync", "--dry-run", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_sync_uses_org_base_branch() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/Acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+
+    // Org sections in the user-wide config apply to every repository
+    let config_home = TempDir::new().expect("Failed to create temp dir");
+    fs::create_dir(config_home.path().join("rung")).expect("mkdir");
+    fs::write(
+        config_home.path().join("rung/config.toml"),
+        "[orgs.acme]\nbase_branch = \"main\"\n",
+    )
+    .expect("write user config");
+
+    // Without the override this would need GitHub to find the default branch
+    rung()
+        .args(["sync", "--dry-run", "--json"])
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env_remove("GITHUB_TOKEN")
+        .current_dir(&temp)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_sync_dry_run_json() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .args(["create", "feature-2"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Move main forward so the whole stack needs a rebase
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git")
+    };
+    git(&["checkout", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Advance main"]);
+    git(&["checkout", "feature-2"]);
+    let before = git(&["rev-parse", "feature-1"]).stdout;
+
+    let output = rung()
+        .args(["--json", "sync", "--dry-run", "--base", "main"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run sync");
+    let plan: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(plan["dry_run"], true);
+    assert_eq!(plan["rebase"][0]["branch"], "feature-1");
+    assert_eq!(plan["rebase"][0]["reason"], "parent_moved");
+    assert_eq!(plan["rebase"][1]["branch"], "feature-2");
+    assert_eq!(plan["rebase"][1]["reason"], "parent_rebased");
+    assert_eq!(plan["merged"].as_array().map(Vec::len), Some(0));
+    assert_eq!(plan["pr_base_updates"].as_array().map(Vec::len), Some(0));
+
+    // Nothing was rebased
+    assert_eq!(git(&["rev-parse", "feature-1"]).stdout, before);
+}
+
+#[test]
+fn test_sync_exec_pauses_on_failure() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    git(&["checkout", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Advance main"]);
+    git(&["checkout", "feature-1"]);
+
+    rung()
+        .args([
+            "sync",
+            "--base",
+            "main",
+            "--no-push",
+            "--exec",
+            "test -f ok.txt",
+        ])
+        .current_dir(&temp)
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "`test -f ok.txt` failed on 'feature-1'",
+        ));
+
+    // The branch stays rebased and the sync resumable
+    assert!(temp.path().join("main.txt").exists());
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Sync already in progress"));
+
+    fs::write(temp.path().join("ok.txt"), "").expect("Failed to write file");
+    rung()
+        .args(["sync", "--continue", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Synced 1 branches"));
+}
+
+#[test]
+fn test_history_lists_rung_reflog_entries() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git")
+    };
+    // A commit made with git itself isn't rung's
+    fs::write(temp.path().join("feature.txt"), "feature\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Feature work"]);
+    git(&["checkout", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Advance main"]);
+    git(&["checkout", "feature-1"]);
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    let reflog = git(&["reflog", "show", "--format=%gs", "feature-1"]);
+    let reflog = String::from_utf8_lossy(&reflog.stdout);
+    assert!(
+        reflog.contains("rung sync: rebase feature-1 onto "),
+        "{reflog}"
+    );
+    assert!(
+        reflog.contains("rung create: create feature-1 at "),
+        "{reflog}"
+    );
+
+    let output = rung()
+        .args(["history", "--json"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run history");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    let messages: Vec<&str> = json
+        .as_array()
+        .expect("Expected an array")
+        .iter()
+        .map(|m| m["message"].as_str().unwrap_or_default())
+        .collect();
+    assert!(
+        messages.iter().all(|m| m.starts_with("rung ")),
+        "{messages:?}"
+    );
+    assert!(messages[0].starts_with("rung sync: rebase feature-1 onto "));
+    assert!(
+        messages
+            .last()
+            .is_some_and(|m| m.starts_with("rung create:"))
+    );
+    assert!(json[messages.len() - 1]["old"].is_null());
+
+    rung()
+        .args(["history", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hasn't moved 'main'"));
+}
+
+#[test]
+fn test_sync_rebase_merges() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    // feature-1 merges in a side branch
+    git(&["checkout", "-b", "side"]);
+    fs::write(temp.path().join("side.txt"), "side\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Side work"]);
+    git(&["checkout", "feature-1"]);
+    fs::write(temp.path().join("feature.txt"), "feature\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Feature work"]);
+    git(&["merge", "--no-ff", "side", "-m", "Merge side"]);
+
+    // Move main forward so feature-1 needs a rebase
+    git(&["checkout", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Advance main"]);
+    git(&["checkout", "feature-1"]);
+
+    rung()
+        .arg("rebase-merges")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("keep its merge commits"));
+
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    assert_eq!(
+        git(&["rev-list", "--merges", "--count", "main..feature-1"]),
+        "1"
+    );
+    assert_eq!(
+        git(&["merge-base", "main", "feature-1"]),
+        git(&["rev-parse", "main"])
+    );
+}
+
+#[test]
+fn test_sync_keeps_committer_dates() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[sync]\ncommitter_date_is_author_date = true\n",
+    )
+    .expect("Failed to write config");
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .env("GIT_AUTHOR_DATE", "2020-01-01T00:00:00Z")
+            .env("GIT_COMMITTER_DATE", "2020-01-01T00:00:00Z")
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    fs::write(temp.path().join("feature.txt"), "feature\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Feature work"]);
+
+    // Move main forward so feature-1 needs a rebase
+    git(&["checkout", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Advance main"]);
+    git(&["checkout", "feature-1"]);
+
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    assert_eq!(
+        git(&["merge-base", "main", "feature-1"]),
+        git(&["rev-parse", "main"])
+    );
+    assert_eq!(
+        git(&["log", "-1", "--format=%cI", "feature-1"]),
+        "2020-01-01T00:00:00+00:00"
+    );
+}
+
+#[test]
+fn test_status_flags_children_of_amended_branch() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    for (branch, file) in [("feature-1", "one.txt"), ("feature-2", "two.txt")] {
+        rung()
+            .args(["create", branch])
+            .current_dir(&temp)
+            .assert()
+            .success();
+        fs::write(temp.path().join(file), "content\n").expect("Failed to write file");
+        git(&["add", "."]);
+        git(&["commit", "-m", branch]);
+    }
+
+    // Amend feature-1 underneath feature-2
+    git(&["checkout", "feature-1"]);
+    fs::write(temp.path().join("one.txt"), "amended\n").expect("Failed to write file");
+    git(&["commit", "-a", "--amend", "-m", "feature-1 (amended)"]);
+
+    let status = |temp: &TempDir| -> serde_json::Value {
+        let output = rung()
+            .args(["--json", "status"])
+            .current_dir(temp)
+            .output()
+            .expect("Failed to run status");
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output")
+    };
+    let before = status(&temp);
+    assert_eq!(before["branches"][0]["state"]["status"], "synced");
+    assert_eq!(before["branches"][1]["state"]["status"], "needs_restack");
+
+    // Sync replays only feature-2's own commit onto the amended feature-1
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let after = status(&temp);
+    assert_eq!(after["branches"][1]["state"]["status"], "synced");
+    assert_eq!(git(&["rev-list", "--count", "feature-1..feature-2"]), "1");
+}
+
+#[test]
+fn test_status_watch_reports_invalidated_descendants() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    for (branch, file) in [("feature-1", "one.txt"), ("feature-2", "two.txt")] {
+        rung()
+            .args(["create", branch])
+            .current_dir(&temp)
+            .assert()
+            .success();
+        fs::write(temp.path().join(file), "content\n").expect("Failed to write file");
+        git(&["add", "."]);
+        git(&["commit", "-m", branch]);
+    }
+
+    let mut watcher = StdCommand::new(env!("CARGO_BIN_EXE_rung"))
+        .args(["--json", "status", "--watch"])
+        .current_dir(&temp)
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to start watch");
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+
+    git(&["checkout", "feature-1"]);
+    fs::write(temp.path().join("one.txt"), "amended\n").expect("Failed to write file");
+    git(&["commit", "-a", "--amend", "-m", "feature-1 (amended)"]);
+    std::thread::sleep(std::time::Duration::from_millis(2500));
+
+    watcher.kill().expect("Failed to stop watch");
+    let output = watcher
+        .wait_with_output()
+        .expect("Failed to read watch output");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(r#"{"branch":"feature-1","needs_restack":["feature-2"]}"#),
+        "unexpected output: {stdout}"
+    );
+}
+
+#[test]
+fn test_sync_nothing_to_sync() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Create a branch
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Sync when already up to date
+    // Note: --base main is required since there's no origin remote in tests
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up-to-date"));
+}
+
+#[test]
+fn test_sync_skips_when_nothing_moved() {
+    let temp = setup_json_fixture();
+    let sync = || {
+        rung()
+            .args(["sync", "--base", "main"])
+            .current_dir(&temp)
+            .assert()
+            .success()
+    };
+
+    sync().stdout(predicate::str::contains("Syncing 2 branches"));
+    assert!(temp.path().join(".git/rung/last_sync.json").exists());
+    sync().stdout(predicate::str::contains(
+        "Nothing changed since the last sync",
+    ));
+
+    // Moving the base means there is work to do again
+    for args in [
+        &["checkout", "-q", "main"][..],
+        &["commit", "-q", "--allow-empty", "-m", "Advance main again"],
+        &["checkout", "-q", "add-gadget"],
+    ] {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    }
+    sync().stdout(predicate::str::contains("Syncing 2 branches"));
+}
+
+#[test]
+fn test_sync_checks_merges_from_closed_listing() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+    let prs = serde_json::json!([
+        api_pr(1, "add-widget", "main", "open", "octo"),
+        api_pr(2, "add-gadget", "add-widget", "open", "octo"),
+    ]);
+    let sync = |closed: String| {
+        let (api, requests) = mock_github_recording(vec![
+            ("/pulls?state=closed&sort=updated", closed),
+            ("/pulls/1 ", prs[0].to_string()),
+            ("/pulls/2 ", prs[1].to_string()),
+        ]);
+        fs::write(
+            temp.path().join(".git/rung/config.toml"),
+            format!("[github]\napi_url = \"{api}\"\n"),
+        )
+        .expect("Failed to write config");
+        let json = json_output(&temp, &["sync", "--no-push", "--base", "main"]);
+        let requests = requests.lock().expect("Poisoned").clone();
+        (json, requests)
+    };
+
+    // The first sync looks at each PR, later ones list recently closed PRs
+    let (_, requests) = sync("[]".to_string());
+    assert!(
+        requests
+            .iter()
+            .any(|r| r.starts_with("GET /repos/acme/widgets/pulls/1 "))
+    );
+    assert!(temp.path().join(".git/rung/prs.json").exists());
+    let (json, requests) = sync("[]".to_string());
+    assert_eq!(json["status"], "already_synced");
+    assert_eq!(requests.len(), 1, "unexpected requests: {requests:?}");
+    assert!(requests[0].contains("pulls?state=closed"));
+
+    // A merge shows up in the listing (which reports `merged_at`, not `merged`)
+    let mut merged = api_pr(1, "add-widget", "main", "closed", "octo");
+    merged["merged_at"] = serde_json::json!("2030-01-01T00:00:00Z");
+    let (json, requests) = sync(serde_json::json!([merged]).to_string());
+    assert_eq!(json["report"]["merged"][0]["pr_number"], 1);
+    assert!(
+        !requests
+            .iter()
+            .any(|r| r.starts_with("GET /repos/acme/widgets/pulls/1 "))
+    );
+}
+
+#[test]
+fn test_sync_recorded_in_stats() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Move main forward so feature-1 needs a rebase
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    git(&["checkout", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Advance main"]);
+    git(&["checkout", "feature-1"]);
+
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    let output = rung()
+        .args(["--json", "stats"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run stats");
+    let stats: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(stats["syncs"], 1);
+    assert_eq!(stats["conflicts"], 0);
+    assert_eq!(stats["average_stack_depth"], 1.0);
+    assert_eq!(stats["weeks"].as_array().map(Vec::len), Some(8));
+
+    rung()
+        .args(["stats", "--weeks", "2"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("PRs landed: 0 in 2 week(s)"))
+        .stdout(predicate::str::contains(
+            "Conflicts per sync: 0.00 (0 in 1 sync(s))",
+        ));
+}
+
+#[test]
+fn test_sync_report() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let old_sha = git(&["rev-parse", "feature-1"]);
+    git(&["checkout", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("Failed to write file");
+    git(&["add", "."]);
+    git(&["commit", "-m", "Advance main"]);
+    git(&["checkout", "feature-1"]);
+
+    let output = rung()
+        .args(["--json", "sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run sync");
+    let result: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(result["status"], "complete");
+    let rebased = &result["report"]["rebased"][0];
+    assert_eq!(rebased["branch"], "feature-1");
+    assert_eq!(rebased["old_sha"], old_sha.as_str());
+    assert_eq!(
+        rebased["new_sha"],
+        git(&["rev-parse", "feature-1"]).as_str()
+    );
+    assert!(result["report"].get("pushed").is_none());
+
+    // Branches deleted outside rung are reported as removed
+    git(&["checkout", "main"]);
+    git(&["branch", "-D", "feature-1"]);
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Summary:"))
+        .stdout(predicate::str::contains(
+            "Removed feature-1 (branch no longer exists)",
+        ));
+}
+
+#[test]
+fn test_sync_show_range_diff() {
+    let temp = setup_json_fixture();
+
+    let output = rung()
+        .args([
+            "--json",
+            "sync",
+            "--base",
+            "main",
+            "--no-push",
+            "--show-range-diff",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run sync");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    let rebased = json["report"]["rebased"].as_array().expect("rebased");
+    assert_eq!(rebased.len(), 2);
+    // Each branch's one commit came through unchanged
+    for (branch, subject) in rebased.iter().zip(["Add widget", "Add gadget"]) {
+        let range_diff = branch["range_diff"].as_str().expect("range_diff");
+        assert!(range_diff.starts_with("1:  "), "{range_diff}");
+        assert!(range_diff.contains(" = 1:  "), "{range_diff}");
+        assert!(range_diff.trim_end().ends_with(subject), "{range_diff}");
+    }
+
+    // Kept with the backup
+    let backup_id = json["backup_id"].as_str().expect("backup_id");
+    let stored = fs::read_to_string(
+        temp.path()
+            .join(".git/rung/refs")
+            .join(backup_id)
+            .join(".range-diff"),
+    )
+    .expect("Failed to read range-diff");
+    assert!(stored.starts_with("add-widget:\n1:  "), "{stored}");
+    assert!(stored.contains("add-gadget:\n1:  "), "{stored}");
+}
+
+#[test]
+fn test_sync_follows_branch_renamed_on_github() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(
+        temp.path(),
+        &["push", "-q", "origin", "main", "add-widget", "add-gadget"],
+    );
+    record_prs(&temp, &[1, 2]);
+
+    // Renamed in the GitHub UI, which also retargets #2
+    git(origin.path(), &["branch", "-m", "add-widget", "widget"]);
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/pulls/1 ",
+            api_pr(1, "widget", "main", "open", "octo").to_string(),
+        ),
+        (
+            "/pulls/2 ",
+            api_pr(2, "add-gadget", "widget", "open", "octo").to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["--json", "sync", "--base", "main"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run rung");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|_| panic!("Invalid JSON: {}", String::from_utf8_lossy(&output.stderr)));
+    assert_eq!(
+        json["report"]["renamed"],
+        serde_json::json!([{
+            "branch": "add-widget",
+            "pr_number": 1,
+            "remote": "widget",
+            "new_name": "widget",
+        }])
+    );
+
+    // Renamed locally, with the child still on it and no base "repaired"
+    assert_eq!(
+        git(temp.path(), &["branch", "--list", "add-widget", "widget"]),
+        "widget"
+    );
+    let stack = fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("stack");
+    let stack: serde_json::Value = serde_json::from_str(&stack).expect("Invalid stack.json");
+    let parents: Vec<(&str, &str)> = stack["branches"]
+        .as_array()
+        .expect("branches")
+        .iter()
+        .map(|b| {
+            (
+                b["name"].as_str().unwrap_or_default(),
+                b["parent"].as_str().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert!(parents.contains(&("widget", "main")), "{parents:?}");
+    assert!(parents.contains(&("add-gadget", "widget")), "{parents:?}");
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        !requests.iter().any(|r| r.starts_with("PATCH")),
+        "{requests:?}"
+    );
+
+    // Pushed to the renamed branch
+    assert_eq!(
+        git(origin.path(), &["rev-parse", "widget"]),
+        git(temp.path(), &["rev-parse", "widget"])
+    );
+    assert_eq!(git(origin.path(), &["branch", "--list", "add-widget"]), "");
+}
+
+#[test]
+fn test_sync_push_comment() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(
+        temp.path(),
+        &["push", "-q", "origin", "main", "add-widget", "add-gadget"],
+    );
+    record_prs(&temp, &[1, 2]);
+
+    let prs = serde_json::json!([
+        api_pr(1, "add-widget", "main", "open", "octo"),
+        api_pr(2, "add-gadget", "add-widget", "open", "octo"),
+    ]);
+    let (api, requests) = mock_github_recording(vec![
+        ("/pulls/1 ", prs[0].to_string()),
+        ("/pulls/2 ", prs[1].to_string()),
+        (
+            "GET /repos/acme/widgets/issues/1/comments",
+            "[]".to_string(),
+        ),
+        (
+            "GET /repos/acme/widgets/issues/2/comments",
+            r#"[{"id":7,"body":"<!-- rung-sync -->\nRebased earlier"}]"#.to_string(),
+        ),
+        ("/issues/", r#"{"id":8,"body":""}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n\n[sync]\npush_comment = true\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["--json", "sync", "--base", "main"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run rung");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|_| panic!("Invalid JSON: {}", String::from_utf8_lossy(&output.stderr)));
+    assert_eq!(json["report"]["commented"], serde_json::json!([1, 2]));
+
+    let main = git(temp.path(), &["rev-parse", "--short=7", "main"]);
+    let widget = git(temp.path(), &["rev-parse", "--short=7", "add-widget"]);
+    let comment = |base: &str, sha: &str| {
+        serde_json::json!({
+            "body": format!("<!-- rung-sync -->\nRebased onto `{base}` @ {sha} by `rung sync`: no content changes.")
+        })
+        .to_string()
+    };
+    let requests = requests.lock().expect("Poisoned").clone();
+    // A new comment on #1, and #2's earlier one updated in place
+    assert!(requests.contains(&format!(
+        "POST /repos/acme/widgets/issues/1/comments {}",
+        comment("main", &main)
+    )));
+    assert!(requests.contains(&format!(
+        "PATCH /repos/acme/widgets/issues/comments/7 {}",
+        comment("add-widget", &widget)
+    )));
+}
+
+#[test]
+fn test_sync_marks_green_drafts_ready() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(
+        temp.path(),
+        &["push", "-q", "origin", "main", "add-widget", "add-gadget"],
+    );
+    record_prs(&temp, &[1, 2]);
+
+    // Submitted with `--draft-until-green`
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("Failed to read stack"))
+            .expect("Invalid stack.json");
+    stack["branches"][0]["ready_when_green"] = serde_json::json!(true);
+    fs::write(&stack_path, stack.to_string()).expect("Failed to write stack");
+
+    let mut draft = api_pr(1, "add-widget", "main", "open", "octo");
+    draft["draft"] = serde_json::json!(true);
+    let runs = serde_json::json!({
+        "check_runs": [{ "name": "test", "status": "completed", "conclusion": "success" }]
+    });
+    let (api, requests) = mock_github_recording(vec![
+        ("/pulls/1 ", draft.to_string()),
+        (
+            "/pulls/2 ",
+            api_pr(2, "add-gadget", "add-widget", "open", "octo").to_string(),
+        ),
+        ("/check-runs ", runs.to_string()),
+        ("/status ", r#"{"statuses":[]}"#.to_string()),
+        ("/branches/", "{}".to_string()),
+        (
+            "/graphql",
+            r#"{"data":{"repository":{"pullRequest":{"id":"PR_1"}}}}"#.to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["--json", "sync", "--base", "main"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run rung");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|_| panic!("Invalid JSON: {}", String::from_utf8_lossy(&output.stderr)));
+    assert_eq!(
+        json["report"]["ready"],
+        serde_json::json!([{ "branch": "add-widget", "pr_number": 1 }])
+    );
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        requests
+            .iter()
+            .any(|r| r.contains("markPullRequestReadyForReview") && r.contains("PR_1"))
+    );
+
+    // Marked once, then no longer watched
+    let stack = fs::read_to_string(&stack_path).expect("Failed to read stack");
+    assert!(!stack.contains("ready_when_green"));
+}
+
+#[test]
+fn test_submit_phases() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let submit = |args: &[&str]| {
+        let output = rung()
+            .args(["--json", "submit"])
+            .args(args)
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .output()
+            .expect("Failed to run rung");
+        assert!(
+            output.status.success(),
+            "submit {args:?} failed: {output:?}"
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("Invalid JSON")
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+
+    let output = submit(&["--push-only"]);
+    assert_eq!(
+        output["pushed"],
+        serde_json::json!(["add-widget", "add-gadget"])
+    );
+    let on_origin = |branch: &str| git(origin.path(), &["rev-parse", branch]);
+    assert_eq!(
+        on_origin("add-gadget"),
+        git(temp.path(), &["rev-parse", "add-gadget"])
+    );
+
+    // add-widget already has its PR; a new commit on it stays local
+    record_prs(&temp, &[1]);
+    git(temp.path(), &["checkout", "-q", "add-widget"]);
+    git(
+        temp.path(),
+        &["commit", "-q", "--allow-empty", "-m", "Tweak widget"],
+    );
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/pulls/1 ",
+            api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+        ),
+        ("GET /repos/acme/widgets/pulls?", "[]".to_string()),
+        (
+            "/branches/add-widget ",
+            r#"{"name":"add-widget"}"#.to_string(),
+        ),
+        (
+            "POST /repos/acme/widgets/pulls ",
+            api_pr(2, "add-gadget", "add-widget", "open", "octo").to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = submit(&["--prs-only"]);
+    assert_eq!(output["prs_created"], 1);
+    assert_eq!(output["prs_updated"], 0);
+    assert_ne!(
+        on_origin("add-widget"),
+        git(temp.path(), &["rev-parse", "add-widget"])
+    );
+    let writes: Vec<_> = requests
+        .lock()
+        .expect("Poisoned")
+        .iter()
+        .filter(|r| !r.starts_with("GET"))
+        .map(|r| r.split(' ').take(2).collect::<Vec<_>>().join(" "))
+        .collect();
+    assert_eq!(writes, ["POST /repos/acme/widgets/pulls"]);
+}
+
+#[test]
+fn test_submit_pushes_parent_before_child_pr() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let submit = |branch_route: Option<&'static str>| {
+        let mut routes = vec![
+            ("GET /repos/acme/widgets/pulls?", "[]".to_string()),
+            (
+                "POST /repos/acme/widgets/pulls ",
+                api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+            ),
+            ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+            ("/comments ", r#"{"id":0,"body":null}"#.to_string()),
+        ];
+        if let Some(route) = branch_route {
+            routes.push((route, r#"{"name":"add-widget"}"#.to_string()));
+        }
+        let (api, requests) = mock_github_recording(routes);
+        fs::write(
+            temp.path().join(".git/rung/config.toml"),
+            format!("[github]\napi_url = \"{api}\"\n"),
+        )
+        .expect("Failed to write config");
+        let assert = rung()
+            .arg("submit")
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .assert();
+        let requests = requests.lock().expect("Poisoned").clone();
+        (assert, requests)
+    };
+    let position = |requests: &[String], prefix: &str, body: &str| {
+        requests
+            .iter()
+            .position(|r| r.starts_with(prefix) && r.contains(body))
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+
+    // GitHub doesn't have the parent: add-gadget's PR isn't created on main
+    let (assert, requests) = submit(None);
+    assert.failure().stderr(predicate::str::contains(
+        "Parent branch 'add-widget' of add-gadget is not on GitHub",
+    ));
+    let creates: Vec<_> = requests
+        .iter()
+        .filter(|r| r.starts_with("POST /repos/acme/widgets/pulls "))
+        .collect();
+    assert_eq!(creates.len(), 1, "{requests:?}");
+    assert!(
+        creates[0].contains(r#""head":"add-widget""#),
+        "{requests:?}"
+    );
+
+    // Once it's there, the parent is pushed and checked before the child's PR
+    let (assert, requests) = submit(Some("GET /repos/acme/widgets/branches/add-widget "));
+    assert.success();
+    let parent_pr = position(&requests, "POST", r#""head":"add-widget""#);
+    let check = position(&requests, "GET /repos/acme/widgets/branches/add-widget", "");
+    let child_pr = position(&requests, "POST", r#""head":"add-gadget""#);
+    assert!(
+        parent_pr.is_some() && parent_pr < check && check < child_pr,
+        "{requests:?}"
+    );
+    assert_eq!(
+        git(origin.path(), &["rev-parse", "add-widget"]),
+        git(temp.path(), &["rev-parse", "add-widget"])
+    );
+}
+
+#[test]
+fn test_submit_enables_branch_auto_delete() {
+    for enabled in [false, true] {
+        let temp = setup_json_fixture();
+        let origin = TempDir::new().expect("Failed to create temp dir");
+        let url = "https://github.com/acme/widgets.git";
+        let redirect = [
+            ("GIT_CONFIG_COUNT", "1".to_string()),
+            (
+                "GIT_CONFIG_KEY_0",
+                format!("url.{}.insteadOf", origin.path().display()),
+            ),
+            ("GIT_CONFIG_VALUE_0", url.to_string()),
+        ];
+        let git = |dir: &std::path::Path, args: &[&str]| {
+            let output = StdCommand::new("git")
+                .args(args)
+                .envs(redirect.iter().map(|(k, v)| (k, v)))
+                .current_dir(dir)
+                .output()
+                .expect("Failed to run git");
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+        git(origin.path(), &["init", "-q", "--bare"]);
+        git(temp.path(), &["remote", "add", "origin", url]);
+        git(temp.path(), &["push", "-q", "origin", "main"]);
+
+        let (api, requests) = mock_github_recording(vec![
+            ("PATCH /repos/acme/widgets ", "{}".to_string()),
+            (
+                "GET /repos/acme/widgets ",
+                serde_json::json!({ "delete_branch_on_merge": enabled }).to_string(),
+            ),
+            ("GET /repos/acme/widgets/pulls?", "[]".to_string()),
+            ("/branches/", r#"{"name":"add-widget"}"#.to_string()),
+            (
+                "POST /repos/acme/widgets/pulls ",
+                api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+            ),
+            ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+            ("/comments ", r#"{"id":0,"body":null}"#.to_string()),
+        ]);
+        fs::write(
+            temp.path().join(".git/rung/config.toml"),
+            format!("[github]\napi_url = \"{api}\"\n\n[pr]\ndelete_branch_on_merge = true\n"),
+        )
+        .expect("Failed to write config");
+
+        rung()
+            .arg("submit")
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .assert()
+            .success();
+        let requests = requests.lock().expect("Poisoned").clone();
+        let patches: Vec<_> = requests
+            .iter()
+            .filter(|r| r.starts_with("PATCH /repos/acme/widgets "))
+            .collect();
+        if enabled {
+            assert!(patches.is_empty(), "{requests:?}");
+        } else {
+            assert_eq!(patches.len(), 1, "{requests:?}");
+            assert!(patches[0].contains(r#""delete_branch_on_merge":true"#));
+        }
+    }
+}
+
+#[test]
+fn test_submit_sets_milestone_and_project() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+
+    let ids = serde_json::json!({ "data": { "repository": {
+        "pullRequest": { "id": "PR_1" },
+        "owner": { "projectV2": { "id": "PVT_4" } },
+    } } });
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/milestones?",
+            r#"[{"number":2,"title":"v1.0"},{"number":3,"title":"v2.0"}]"#.to_string(),
+        ),
+        ("PATCH /repos/acme/widgets/issues/1 ", "{}".to_string()),
+        ("POST /graphql ", ids.to_string()),
+        ("GET /repos/acme/widgets/pulls?", "[]".to_string()),
+        ("/branches/", r#"{"name":"add-widget"}"#.to_string()),
+        (
+            "POST /repos/acme/widgets/pulls ",
+            api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+        ),
+        ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+        ("/comments ", r#"{"id":0,"body":null}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n\n[pr]\nmilestone = \"v2.0\"\nproject = 4\n"),
+    )
+    .expect("Failed to write config");
+
+    rung()
+        .arg("submit")
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Milestone: v2.0"))
+        .stdout(predicate::str::contains("Added to project #4"));
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        requests
+            .iter()
+            .any(|r| r.starts_with("PATCH /repos/acme/widgets/issues/1 ")
+                && r.contains(r#""milestone":3"#)),
+        "{requests:?}"
+    );
+    assert!(
+        requests.iter().any(|r| r.starts_with("POST /graphql ")
+            && r.contains("addProjectV2ItemById")
+            && r.contains(r#""project":"PVT_4""#)
+            && r.contains(r#""content":"PR_1""#)),
+        "{requests:?}"
+    );
+}
+
+#[test]
+fn test_submit_lists_related_prs_in_stack_comment() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+
+    let (api, requests) = mock_github_recording(vec![
+        ("GET /repos/acme/widgets/pulls?", "[]".to_string()),
+        ("/branches/", r#"{"name":"add-widget"}"#.to_string()),
+        (
+            "POST /repos/acme/widgets/pulls ",
+            api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+        ),
+        ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+        ("/comments ", r#"{"id":0,"body":null}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!(
+            "[github]\napi_url = \"{api}\"\n\n[related]\nadd-widget = [\"acme/web-client#42\"]\n"
+        ),
+    )
+    .expect("Failed to write config");
+
+    rung()
+        .arg("submit")
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .assert()
+        .success();
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        requests.iter().any(
+            |r| r.starts_with("POST /repos/acme/widgets/issues/1/comments ")
+                && r.contains("**Related PRs**")
+                && r.contains("* acme/web-client#42")
+        ),
+        "{requests:?}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_submit_verifies_pushes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    let push_only = || {
+        rung()
+            .args(["submit", "--push-only"])
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .assert()
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+
+    // Origin accepts the push, then something moves the branch back a commit
+    let hook = origin.path().join("hooks/post-receive");
+    fs::write(
+        &hook,
+        "#!/bin/sh\nwhile read old new ref; do\n  [ \"$ref\" = refs/heads/add-widget ] && git update-ref \"$ref\" \"$new^\"\ndone\nexit 0\n",
+    )
+    .expect("Failed to write hook");
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).expect("chmod");
+
+    // Not noticed by default
+    push_only().success();
+
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[general]\nverify_pushes = true\n",
+    )
+    .expect("Failed to write config");
+    push_only()
+        .failure()
+        .stderr(predicate::str::contains("pushed add-widget at"))
+        .stderr(predicate::str::contains("but origin has"));
+}
+
+#[test]
+fn test_submit_reopens_closed_prs() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    for (dir, args) in [
+        (origin.path(), &["init", "-q", "--bare"][..]),
+        (temp.path(), &["remote", "add", "origin", url]),
+        (temp.path(), &["push", "-q", "origin", "main"]),
+    ] {
+        StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+    }
+
+    // add-widget's recorded PR was closed; add-gadget had one closed too
+    record_prs(&temp, &[1]);
+    let batch = serde_json::json!({ "data": { "repository": { "pr0": {
+        "number": 1, "title": "Add widget", "state": "CLOSED", "merged": false,
+        "isDraft": false, "headRefName": "add-widget", "headRefOid": "0".repeat(40),
+        "baseRefName": "main", "url": "https://github.com/acme/widgets/pull/1",
+    } } } });
+    let (api, requests) = mock_github_recording(vec![
+        ("/graphql", batch.to_string()),
+        (
+            "pulls?head=acme:add-gadget&state=closed",
+            serde_json::json!([api_pr(5, "add-gadget", "add-widget", "closed", "octo")])
+                .to_string(),
+        ),
+        ("GET /repos/acme/widgets/pulls?", "[]".to_string()),
+        (
+            "/pulls/1 ",
+            api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+        ),
+        (
+            "/pulls/5 ",
+            api_pr(5, "add-gadget", "add-widget", "open", "octo").to_string(),
+        ),
+        ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+        ("/comments ", r#"{"id":0,"body":null}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["--json", "submit", "--reopen"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run rung");
+    assert!(output.status.success(), "{output:?}");
+    let output: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    assert_eq!(output["prs_created"], 0);
+    assert_eq!(output["prs_updated"], 2);
+
+    let requests = requests.lock().expect("Poisoned").clone();
+    for pr in [1, 5] {
+        let reopen = format!("PATCH /repos/acme/widgets/pulls/{pr} {{\"state\":\"open\"}}");
+        assert!(requests.contains(&reopen), "{requests:?}");
+    }
+    assert!(
+        !requests
+            .iter()
+            .any(|r| r.starts_with("POST /repos/acme/widgets/pulls ")),
+        "{requests:?}"
+    );
+    let stack =
+        fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("Failed to read stack");
+    assert!(stack.contains(r#""pr": 5"#), "{stack}");
+}
+
+#[test]
+fn test_submit_skips_branches_without_commits() {
+    let temp = setup_json_fixture();
+    // add-widget's change was folded into main
+    for args in [
+        &[
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ][..],
+        &["branch", "-f", "add-widget", "main"],
+    ] {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    }
+
+    let status = json_output(&temp, &["status"]);
+    assert_eq!(status["branches"][0]["no_commits"], true);
+    assert!(status["branches"][1].get("no_commits").is_none());
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(no commits)"));
+
+    let api = mock_github_with(vec![
+        ("GET /repos/acme/widgets/pulls?", "[]".to_string()),
+        (
+            "/repos/acme/widgets ",
+            r#"{"default_branch":"main"}"#.to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let plan = json_output(&temp, &["submit", "--dry-run"]);
+    assert_eq!(plan["skipped"], serde_json::json!(["add-widget"]));
+    assert_eq!(plan["prs_would_create"], 1);
+    assert_eq!(plan["branches"][0]["branch"], "add-gadget");
+    assert_eq!(plan["branches"][0]["target_base"], "main");
+
+    rung()
+        .args(["submit", "--dry-run"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Skipping 'add-widget' - it has no commits 'main' doesn't",
+        ));
+}
+
+#[test]
+fn test_submit_depends_on() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+    record_prs(&temp, &[1, 2]);
+
+    let mut gadget = api_pr(2, "add-gadget", "add-widget", "open", "octo");
+    gadget["body"] = serde_json::json!("Adds the gadget.");
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/pulls/1 ",
+            api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+        ),
+        ("/pulls/2 ", gadget.to_string()),
+        ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+        ("/comments", r#"{"id":8,"body":""}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n\n[pr]\ndepends_on = true\n"),
+    )
+    .expect("Failed to write config");
+
+    rung()
+        .args(["submit"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .assert()
+        .success();
+
+    // Only the child gets the line; the PR on trunk is left alone
+    let body = serde_json::json!({
+        "body": "<!-- rung-depends -->Depends on #1<!-- /rung-depends -->\n\nAdds the gadget."
+    });
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        requests.contains(&format!("PATCH /repos/acme/widgets/pulls/2 {body}")),
+        "{requests:#?}"
+    );
+    assert!(
+        !requests
+            .iter()
+            .any(|r| r.contains("Depends on #") && r.contains("pulls/1 "))
+    );
+}
+
+#[test]
+fn test_note_mirrored_into_pr() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+    record_prs(&temp, &[1, 2]);
+
+    let note = |args: &[&str]| json_output(&temp, &[&["note"], args].concat());
+    note(&["add", "Blocked", "on", "INFRA-12"]);
+    note(&["add", "typo"]);
+    note(&["edit", "2", "Needs a second review"]);
+    let notes = note(&["remove", "2"]);
+    assert_eq!(notes["branch"], "add-gadget");
+    assert_eq!(notes["notes"], serde_json::json!(["Blocked on INFRA-12"]));
+    assert!(
+        note(&["list", "--branch", "add-widget"])["notes"]
+            .as_array()
+            .is_some_and(Vec::is_empty)
+    );
+    rung()
+        .args(["note", "remove", "3"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No note 3"));
+
+    let mut gadget = api_pr(2, "add-gadget", "add-widget", "open", "octo");
+    gadget["body"] = serde_json::json!("Adds the gadget.");
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/pulls/1 ",
+            api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+        ),
+        ("/pulls/2 ", gadget.to_string()),
+        ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+        ("/comments", r#"{"id":8,"body":""}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n\n[pr]\nnotes = true\n"),
+    )
+    .expect("Failed to write config");
+
+    rung()
+        .args(["submit"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .assert()
+        .success();
+
+    // The notes go in a hidden comment; the branch without notes is left alone
+    let body = serde_json::json!({
+        "body": "Adds the gadget.\n\n<!-- rung-notes\nBlocked on INFRA-12\n/rung-notes -->"
+    });
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        requests.contains(&format!("PATCH /repos/acme/widgets/pulls/2 {body}")),
+        "{requests:#?}"
+    );
+    assert!(
+        !requests
+            .iter()
+            .any(|r| r.contains("rung-notes") && r.contains("pulls/1 "))
+    );
+}
+
+#[test]
+fn test_submit_attach_uploads_to_asset_branch() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+    record_prs(&temp, &[1, 2]);
+    let shots = TempDir::new().expect("Failed to create temp dir");
+    let screenshot = shots.path().join("after form.png");
+    fs::write(&screenshot, "not really a png").expect("write");
+    let hash = git(
+        temp.path(),
+        &["hash-object", screenshot.to_str().expect("UTF-8 path")],
+    );
+    let attach = format!("--attach={}", screenshot.display());
+
+    // Nowhere to upload to yet, so nothing is pushed
+    rung()
+        .args(["submit", &attach])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--attach needs a branch to upload to",
+        ));
+
+    let mut gadget = api_pr(2, "add-gadget", "add-widget", "open", "octo");
+    gadget["body"] = serde_json::json!("Restyles the form.");
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/pulls/1 ",
+            api_pr(1, "add-widget", "main", "open", "octo").to_string(),
+        ),
+        ("/pulls/2 ", gadget.to_string()),
+        ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+        ("/comments", r#"{"id":8,"body":""}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n\n[pr]\nasset_branch = \"rung-assets\"\n"),
+    )
+    .expect("Failed to write config");
+    let submit = || {
+        rung()
+            .args(["submit", &attach])
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .assert()
+            .success();
+    };
+    submit();
+
+    let entry = format!("{}-after-form.png", &hash[..12]);
+    assert_eq!(
+        git(origin.path(), &["ls-tree", "--name-only", "rung-assets"]),
+        entry
+    );
+    let body = serde_json::json!({
+        "body": format!(
+            "Restyles the form.\n\n![after form.png](https://github.com/acme/widgets/blob/rung-assets/{entry}?raw=true)"
+        )
+    });
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        requests.contains(&format!("PATCH /repos/acme/widgets/pulls/2 {body}")),
+        "{requests:#?}"
+    );
+
+    // The same file again is already on the asset branch
+    let tip = git(origin.path(), &["rev-parse", "rung-assets"]);
+    submit();
+    assert_eq!(git(origin.path(), &["rev-parse", "rung-assets"]), tip);
+}
+
+#[test]
+fn test_sync_reconciles_closed_mid_stack_pr() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    // add-widget's PR was closed and its branch deleted; GitHub moved #2 onto main
+    git(temp.path(), &["push", "-q", "origin", "main", "add-gadget"]);
+    record_prs(&temp, &[1, 2]);
+
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/pulls/1 ",
+            api_pr(1, "add-widget", "main", "closed", "octo").to_string(),
+        ),
+        (
+            "/pulls/2 ",
+            api_pr(2, "add-gadget", "main", "open", "octo").to_string(),
+        ),
+        ("/comments", "[]".to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["--json", "sync", "--base", "main"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run rung");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|_| panic!("Invalid JSON: {}", String::from_utf8_lossy(&output.stderr)));
+    assert_eq!(json["status"], "complete");
+    assert_eq!(
+        json["report"]["closed"],
+        serde_json::json!([{ "branch": "add-widget", "pr_number": 1, "parent": "main" }])
+    );
+    assert_eq!(
+        json["report"]["reparented"],
+        serde_json::json!([{ "branch": "add-gadget", "old_parent": "add-widget", "new_parent": "main" }])
+    );
+
+    // The stack matches GitHub, and add-gadget keeps add-widget's commit
+    let status = json_output(&temp, &["status"]);
+    assert_eq!(status["branches"].as_array().map(Vec::len), Some(1));
+    assert_eq!(status["branches"][0]["parent"], "main");
+    assert_eq!(status["branches"][0]["state"]["status"], "synced");
+    git(
+        temp.path(),
+        &["merge-base", "--is-ancestor", "main", "add-gadget"],
+    );
+    let log = git(temp.path(), &["log", "--format=%s", "main..add-gadget"]);
+    assert_eq!(log, "Add gadget\nAdd widget");
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        !requests.iter().any(|r| r.starts_with("PATCH")),
+        "{requests:#?}"
+    );
+}
+
+#[test]
+fn test_handoff() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+    record_prs(&temp, &[1, 2]);
+
+    let prs = serde_json::json!([
+        api_pr(1, "add-widget", "main", "open", "octo"),
+        api_pr(2, "add-gadget", "add-widget", "open", "octo"),
+    ]);
+    let (api, requests) = mock_github_recording(vec![
+        ("/user ", r#"{"login":"octo"}"#.to_string()),
+        ("/pulls/1 ", prs[0].to_string()),
+        ("/pulls/2 ", prs[1].to_string()),
+        (
+            "GET /repos/acme/widgets/issues/1/comments",
+            "[]".to_string(),
+        ),
+        (
+            "GET /repos/acme/widgets/issues/2/comments",
+            r#"[{"id":7,"body":"<!-- rung-handoff -->\nEarlier handoff"}]"#.to_string(),
+        ),
+        ("/issues/", r#"{"id":8,"body":""}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["--json", "handoff", "--to", "@mona"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run rung");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|_| panic!("Invalid JSON: {}", String::from_utf8_lossy(&output.stderr)));
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "from": "octo",
+            "to": "mona",
+            "prs": [1, 2],
+            "command": "rung adopt --author octo",
+        })
+    );
+
+    // Every branch was pushed
+    for branch in ["add-widget", "add-gadget"] {
+        git(origin.path(), &["rev-parse", "--verify", branch]);
+    }
+    let comment = serde_json::json!({
+        "body": "<!-- rung-handoff -->\nHanding this stack over to @mona. To pick it up, run `rung adopt --author octo` in a clone of this repository."
+    })
+    .to_string();
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(requests.contains(&format!(
+        "POST /repos/acme/widgets/issues/1/comments {comment}"
+    )));
+    assert!(requests.contains(&format!(
+        "PATCH /repos/acme/widgets/issues/comments/7 {comment}"
+    )));
+    // Each PR also got its stack comment, however the requests interleaved
+    for pr in [1, 2] {
+        let create = format!("POST /repos/acme/widgets/issues/{pr}/comments ");
+        assert!(
+            requests
+                .iter()
+                .any(|r| r.starts_with(&create) && r.contains("rung-stack")),
+            "No stack comment on #{pr}"
+        );
+    }
+}
+
+#[test]
+fn test_force_push_over_others() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |args: &[&str], env: &[(&str, &str)]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .envs(env.iter().copied())
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    StdCommand::new("git")
+        .args(["init", "-q", "--bare"])
+        .current_dir(&origin)
+        .output()
+        .expect("Failed to init origin");
+    git(&["remote", "add", "origin", url], &[]);
+    git(
+        &["push", "-q", "origin", "main", "add-widget", "add-gadget"],
+        &[],
+    );
+
+    // A teammate pushes a commit on add-widget, then it is amended locally
+    let mona = [
+        ("GIT_COMMITTER_NAME", "Mona"),
+        ("GIT_COMMITTER_EMAIL", "mona@example.com"),
+    ];
+    git(&["checkout", "-q", "add-widget"], &[]);
+    git(
+        &["commit", "-q", "--allow-empty", "-m", "Tweak widget"],
+        &mona,
+    );
+    git(&["push", "-q", "origin", "add-widget"], &[]);
+    git(
+        &[
+            "commit",
+            "-q",
+            "--amend",
+            "--allow-empty",
+            "-m",
+            "Tweak widget more",
+        ],
+        &[],
+    );
+    record_prs(&temp, &[1, 2]);
+
+    let prs = serde_json::json!([
+        api_pr(1, "add-widget", "main", "open", "octo"),
+        api_pr(2, "add-gadget", "add-widget", "open", "octo"),
+    ]);
+    let api = mock_github_with(vec![
+        ("/pulls/1 ", prs[0].to_string()),
+        ("/pulls/2 ", prs[1].to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["submit", "--force"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run rung");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("'add-widget' was last pushed by Mona <mona@example.com>"),
+        "unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_sync_conflict_blame() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(temp.path().join("README.md"), "# Branch\n").expect("write");
+    rung()
+        .args(["create", "-m", "Edit readme"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    git(&["checkout", "-q", "main"]);
+    fs::write(temp.path().join("other.txt"), "other\n").expect("write");
+    git(&["add", "."]);
+    git(&["commit", "-qm", "Unrelated change"]);
+    fs::write(temp.path().join("README.md"), "# Main\n").expect("write");
+    git(&["commit", "-qam", "Retitle readme"]);
+    let culprit = git(&["rev-parse", "HEAD"]);
+    git(&["checkout", "-q", "edit-readme"]);
+    let replayed = git(&["rev-parse", "HEAD"]);
+    let blob = |rev: &str| git(&["rev-parse", &format!("{rev}:README.md")]);
+    let stages = serde_json::json!([{
+        "file": "README.md",
+        "base": blob("HEAD~1"),
+        "ours": blob("main"),
+        "theirs": blob("HEAD"),
+    }]);
+
+    let output = rung()
+        .args(["--json", "sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run rung");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    // Only the trunk commit that touched the conflicting line
+    assert_eq!(
+        json["conflict_commits"],
+        serde_json::json!([{
+            "file": "README.md",
+            "commit": culprit,
+            "author": "Test User",
+            "summary": "Retitle readme",
+        }])
+    );
+    // The branch's commit being replayed, and the blobs on each side
+    assert_eq!(
+        json["conflict_replaying"],
+        serde_json::json!({ "commit": replayed, "summary": "Edit readme" })
+    );
+    assert_eq!(json["conflict_stages"], stages);
+    rung()
+        .args(["sync", "--abort", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(format!(
+            "{} Retitle readme (Test User)",
+            &culprit[..7]
+        )))
+        .stdout(predicate::str::contains(format!(
+            "README.md (base {}, ours {}, theirs {})",
+            &stages[0]["base"].as_str().expect("base")[..7],
+            &stages[0]["ours"].as_str().expect("ours")[..7],
+            &stages[0]["theirs"].as_str().expect("theirs")[..7],
+        )));
+}
+
+#[test]
+fn test_sync_conflict_stages_with_deleted_side() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(temp.path().join("README.md"), "# Branch\n").expect("write");
+    rung()
+        .args(["create", "-m", "Edit readme"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let replayed = git(&["rev-parse", "HEAD"]);
+    let base = git(&["rev-parse", "HEAD~1:README.md"]);
+    let theirs = git(&["rev-parse", "HEAD:README.md"]);
+    git(&["checkout", "-q", "main"]);
+    git(&["rm", "-q", "README.md"]);
+    git(&["commit", "-qm", "Drop readme"]);
+    git(&["checkout", "-q", "edit-readme"]);
+
+    // Trunk deleted the file the branch edits: no blob on its side
+    let porcelain = porcelain_output(&temp, &["sync", "--base", "main", "--no-push"]);
+    assert!(
+        porcelain.contains(&format!("conflict-replaying {replayed}\n")),
+        "{porcelain}"
+    );
+    assert!(
+        porcelain.contains(&format!("conflict-stages README.md {base} - {theirs}\n")),
+        "{porcelain}"
+    );
+    rung()
+        .args(["sync", "--abort", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(format!(
+            "Replaying {} Edit readme",
+            &replayed[..7]
+        )))
+        .stdout(predicate::str::contains(format!(
+            "README.md (base {}, ours deleted, theirs {})",
+            &base[..7],
+            &theirs[..7]
+        )));
+}
+
+#[test]
+fn test_stats_conflicts() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(temp.path().join("README.md"), "# Branch\n").expect("write");
+    rung()
+        .args(["create", "-m", "Edit readme"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    git(&["checkout", "-q", "main"]);
+    fs::write(temp.path().join("README.md"), "# Main\n").expect("write");
+    git(&["commit", "-qam", "Retitle readme"]);
+    git(&["checkout", "-q", "edit-readme"]);
+
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .code(3);
+    fs::write(temp.path().join("README.md"), "# Both\n").expect("write");
+    git(&["add", "README.md"]);
+    rung()
+        .args(["sync", "--continue", "--no-push"])
+        .current_dir(&temp)
+        .env("GIT_EDITOR", "true")
+        .assert()
+        .success();
+
+    let stats = json_output(&temp, &["stats", "--conflicts"]);
+    assert_eq!(stats["syncs"], 1);
+    assert_eq!(stats["conflicts"], 1);
+    assert_eq!(
+        stats["conflicted_syncs"][0]["conflicted"],
+        serde_json::json!([{ "branch": "edit-readme", "files": ["README.md"] }])
+    );
+    assert_eq!(
+        stats["branches"],
+        serde_json::json!([{ "name": "edit-readme", "conflicts": 1 }])
+    );
+    assert_eq!(
+        stats["files"],
+        serde_json::json!([{ "name": "README.md", "conflicts": 1 }])
+    );
+    rung()
+        .args(["stats", "--conflicts"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Sync conflicts: 1 in 1 of 1 sync(s) over 8 week(s)",
+        ))
+        .stdout(predicate::str::contains("Files that conflict most:"));
+}
+
+#[test]
+fn test_summary() {
+    use std::io::Write;
+
+    let temp = setup_json_fixture();
+
+    // Both branches were recorded by `rung create`, the first starting a stack
+    let summary = json_output(&temp, &["summary"]);
+    assert_eq!(summary["stacks_created"], 1);
+    assert_eq!(summary["branches_created"], 2);
+    assert_eq!(summary["prs_merged"], serde_json::json!([]));
+
+    // A merge as `rung merge` records it, an hour ago
+    let finished = chrono::Utc::now() - chrono::TimeDelta::hours(1);
+    let mut log = fs::OpenOptions::new()
+        .append(true)
+        .open(temp.path().join(".git/rung/operations.jsonl"))
+        .expect("Failed to open operations log");
+    writeln!(
+        log,
+        r#"{{"started_at":"{finished}","finished_at":"{finished}","op":"merge","branch":"add-widget","pr_number":7,"merge_sha":"89abcdef0123","method":"squash","into":"main"}}"#,
+        finished = finished.to_rfc3339()
+    )
+    .expect("Failed to write operations log");
+
+    rung()
+        .args(["summary", "--since", "2h"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "- Created 2 branch(es), starting 1 new stack(s)",
+        ))
+        .stdout(predicate::str::contains(
+            "- Merged 1 PR(s): #7 (add-widget) into main",
+        ));
+
+    let summary = json_output(&temp, &["summary", "--since", "1h"]);
+    assert_eq!(summary["branches_created"], 2);
+    assert_eq!(summary["prs_merged"], serde_json::json!([]));
+
+    rung()
+        .args(["summary", "--since", "1y"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid period '1y'"));
+}
+
+// ============================================================================
+// Merge command tests
+// ============================================================================
+
+/// Stack of feature-1 → feature-2 → feature-3 where only the first two have
+/// PRs (#1 and #2), with feature-1 checked out.
+fn setup_merge_stack() -> TempDir {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    for name in ["feature-1", "feature-2", "feature-3"] {
+        rung()
+            .args(["create", name])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+
+    // Record PR numbers as `rung submit` would (feature-3 has none yet)
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("Failed to read stack"))
+            .expect("Invalid stack.json");
+    stack["branches"][0]["pr"] = serde_json::json!(1);
+    stack["branches"][1]["pr"] = serde_json::json!(2);
+    fs::write(&stack_path, stack.to_string()).expect("Failed to write stack");
+
+    rung()
+        .args(["move", "--branch", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp
+}
+
+#[test]
+fn test_merge_dry_run() {
+    let temp = setup_merge_stack();
+
+    // No GitHub remote needed: without one, merge settings aren't checked
+    rung()
+        .args(["merge", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merge PR #1 (feature-1) into 'main' (squash)",
+        ))
+        .stdout(predicate::str::contains(
+            "Retarget PR #2 (feature-2) to 'main'",
+        ))
+        .stdout(predicate::str::contains(
+            "Rebase 'feature-3' onto 'feature-2'",
+        ))
+        .stdout(predicate::str::contains("Delete remote branch 'feature-1'"));
+
+    let output = rung()
+        .args(["--json", "merge", "--dry-run", "--no-delete"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run merge");
+    let plan: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(plan["into"], "main");
+    assert_eq!(plan["retarget"].as_array().map(Vec::len), Some(1));
+    assert_eq!(plan["rebase"].as_array().map(Vec::len), Some(2));
+    assert!(plan.get("delete_remote").is_none());
+
+    // Rebasing can be left to the next sync
+    rung()
+        .args(["merge", "--dry-run", "--no-rebase-descendants"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Mark 'feature-3' for `rung sync` to restack",
+        ))
+        .stdout(predicate::str::contains("Rebase '").not());
+
+    // The stack is untouched
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feature-1"));
+
+    // The configured method is the default, and --method overrides it
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[merge]\nmethod = \"rebase\"\n",
+    )
+    .expect("Failed to write config");
+    rung()
+        .args(["merge", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("into 'main' (rebase)"));
+    rung()
+        .args(["merge", "--dry-run", "--method", "merge"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("into 'main' (merge)"));
+    rung()
+        .args(["merge", "--dry-run", "--method", "fast-forward"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid merge method: fast-forward",
+        ));
+}
+
+#[test]
+fn test_merge_by_name_or_pr() {
+    let temp = setup_merge_stack();
+
+    // Any branch can be merged by name or PR number without checking it out
+    rung()
+        .args(["move", "--branch", "feature-3"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .args(["merge", "feature-1", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Merge PR #1 (feature-1)"));
+    rung()
+        .args(["merge", "#2", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merge PR #2 (feature-2) into 'feature-1'",
+        ))
+        .stdout(predicate::str::contains(
+            "Rebase 'feature-3' onto 'feature-1'",
+        ));
+    rung()
+        .args(["merge", "9", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No branch in the stack has PR #9"));
+}
+
+#[test]
+fn test_merge_until_dry_run() {
+    let temp = setup_merge_stack();
+    rung()
+        .args(["move", "--branch", "feature-3"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Each merge is planned against the stack the previous one leaves
+    rung()
+        .args(["merge", "--until", "feature-2", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merge PR #1 (feature-1) into 'main'",
+        ))
+        .stdout(predicate::str::contains(
+            "Merge PR #2 (feature-2) into 'main'",
+        ))
+        .stdout(predicate::str::contains("Rebase 'feature-3' onto 'main'"));
+
+    let output = rung()
+        .args(["--json", "merge", "--until", "#2", "--dry-run"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run merge");
+    let plans: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(plans[0]["branch"], "feature-1");
+    assert_eq!(plans[1]["branch"], "feature-2");
+    assert_eq!(plans[1]["into"], "main");
+
+    // Every branch up to the named one needs a PR
+    rung()
+        .args(["merge", "--until", "feature-3", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No PR associated with branch 'feature-3'",
+        ));
+}
+
+#[test]
+fn test_merge_refuses_head_that_differs_from_local() {
+    let temp = setup_merge_stack();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://github.com/acme/widgets.git",
+    ]);
+    let commit = |file: &str| {
+        fs::write(temp.path().join(file), "change\n").expect("write");
+        git(&["add", file]);
+        git(&["commit", "-qm", file]);
+        git(&["rev-parse", "HEAD"])
+    };
+
+    // Commits on either side of the local tip, kept alive by a tag
+    let local = commit("local.txt");
+    let behind = git(&["rev-parse", "feature-1~1"]);
+    let ahead = commit("ahead.txt");
+    git(&["tag", "ahead"]);
+    git(&["reset", "-q", "--hard", &local]);
+    git(&["checkout", "-q", "--detach", &behind]);
+    let sibling = commit("sibling.txt");
+    git(&["tag", "sibling"]);
+    git(&["checkout", "-q", "feature-1"]);
+
+    for (head, hint) in [
+        (behind, "the local branch has commits that aren't pushed"),
+        (
+            ahead,
+            "GitHub has commits the local branch doesn't - pull them first",
+        ),
+        (sibling, "they have diverged"),
+    ] {
+        let mut pr = api_pr(1, "feature-1", "main", "open", "octo");
+        pr["head"]["sha"] = serde_json::json!(head);
+        let (api, requests) = mock_github_recording(vec![
+            ("/repos/acme/widgets ", "{}".to_string()),
+            (
+                "/pulls/1/merge ",
+                serde_json::json!({ "sha": "0".repeat(40), "merged": true, "message": "Merged" })
+                    .to_string(),
+            ),
+            ("/pulls/1 ", pr.to_string()),
+        ]);
+        fs::write(
+            temp.path().join(".git/rung/config.toml"),
+            format!("[github]\napi_url = \"{api}\"\n"),
+        )
+        .expect("Failed to write config");
+
+        rung()
+            .args(["--yes", "merge", "--ignore-checks"])
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "PR #1 head ({}) doesn't match local 'feature-1' ({})",
+                &head[..7],
+                &local[..7]
+            )))
+            .stderr(predicate::str::contains(hint));
+        let requests = requests.lock().expect("Poisoned").clone();
+        assert!(
+            !requests.iter().any(|r| r.contains("/merge")),
+            "{requests:?}"
+        );
+    }
+    assert_eq!(git(&["rev-parse", "feature-1"]), local);
+}
+
+#[test]
+fn test_merge_conflict_pauses_resumably() {
+    let temp = setup_git_repo();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    for (content, message) in [("one\n", "Add one"), ("two\n", "Change two")] {
+        fs::write(temp.path().join("a.txt"), content).expect("write");
+        rung()
+            .args(["create", "-m", message])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(
+        temp.path(),
+        &["push", "-q", "origin", "main", "add-one", "change-two"],
+    );
+    record_prs(&temp, &[1, 2]);
+
+    // The squash merge lands a version of a.txt that change-two conflicts with
+    git(temp.path(), &["checkout", "-q", "-b", "squashed", "main"]);
+    fs::write(temp.path().join("a.txt"), "squashed\n").expect("write");
+    git(temp.path(), &["add", "."]);
+    git(temp.path(), &["commit", "-qm", "Add one (#1)"]);
+    git(temp.path(), &["push", "-q", "origin", "squashed:main"]);
+    git(temp.path(), &["checkout", "-q", "add-one"]);
+    git(temp.path(), &["branch", "-q", "-D", "squashed"]);
+
+    let mut pr = api_pr(1, "add-one", "main", "open", "octo");
+    pr["head"]["sha"] = serde_json::json!(git(temp.path(), &["rev-parse", "add-one"]));
+    let (api, _requests) = mock_github_recording(vec![
+        ("/repos/acme/widgets ", "{}".to_string()),
+        (
+            "/pulls/1/merge ",
+            serde_json::json!({ "sha": "0".repeat(40), "merged": true, "message": "Merged" })
+                .to_string(),
+        ),
+        ("/pulls/1 ", pr.to_string()),
+        (
+            "/pulls/2 ",
+            api_pr(2, "change-two", "main", "open", "octo").to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    // Without a terminal it stops, leaving a sync to continue
+    rung()
+        .args(["--yes", "merge", "--ignore-checks"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("rung sync --continue"));
+    assert!(temp.path().join(".git/rung/sync_state").exists());
+
+    fs::write(temp.path().join("a.txt"), "two\n").expect("write");
+    git(temp.path(), &["add", "a.txt"]);
+    rung()
+        .args(["sync", "--continue"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .env("GIT_EDITOR", "true")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .assert()
+        .success();
+    assert_eq!(
+        git(temp.path(), &["rev-parse", "change-two~1"]),
+        git(temp.path(), &["rev-parse", "origin/main"])
+    );
+    assert_eq!(
+        git(temp.path(), &["rev-parse", "change-two"]),
+        git(origin.path(), &["rev-parse", "change-two"])
+    );
+}
+
+#[test]
+fn test_merge_leaves_descendants_for_sync() {
+    let temp = setup_git_repo();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    for (file, message) in [("a.txt", "Add one"), ("b.txt", "Add two")] {
+        fs::write(temp.path().join(file), "one\n").expect("write");
+        rung()
+            .args(["create", "-m", message])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+    let old_two = git(temp.path(), &["rev-parse", "add-two"]);
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(
+        temp.path(),
+        &["push", "-q", "origin", "main", "add-one", "add-two"],
+    );
+    record_prs(&temp, &[1, 2]);
+
+    // The squash merge lands a different a.txt, so replaying add-one's
+    // commit onto it would conflict
+    git(temp.path(), &["checkout", "-q", "-b", "squashed", "main"]);
+    fs::write(temp.path().join("a.txt"), "squashed\n").expect("write");
+    git(temp.path(), &["add", "."]);
+    git(temp.path(), &["commit", "-qm", "Add one (#1)"]);
+    git(temp.path(), &["push", "-q", "origin", "squashed:main"]);
+    git(temp.path(), &["checkout", "-q", "add-one"]);
+    git(temp.path(), &["branch", "-q", "-D", "squashed"]);
+
+    let mut pr = api_pr(1, "add-one", "main", "open", "octo");
+    pr["head"]["sha"] = serde_json::json!(git(temp.path(), &["rev-parse", "add-one"]));
+    let (api, requests) = mock_github_recording(vec![
+        ("/repos/acme/widgets ", "{}".to_string()),
+        (
+            "/pulls/1/merge ",
+            serde_json::json!({ "sha": "0".repeat(40), "merged": true, "message": "Merged" })
+                .to_string(),
+        ),
+        ("/pulls/1 ", pr.to_string()),
+        (
+            "/pulls/2 ",
+            api_pr(2, "add-two", "main", "open", "octo").to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args([
+            "--json",
+            "--yes",
+            "merge",
+            "--ignore-checks",
+            "--no-rebase-descendants",
+        ])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .output()
+        .expect("Failed to run merge");
+    assert!(output.status.success(), "merge failed: {output:?}");
+    let merged: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(merged["descendants_rebased"], 0);
+    assert_eq!(merged["deferred"], serde_json::json!(["add-two"]));
+
+    // The child PR was retargeted, but the branch wasn't touched
+    assert!(
+        requests
+            .lock()
+            .expect("lock")
+            .iter()
+            .any(|r| r.contains("/pulls/2") && r.contains("\"base\":\"main\""))
+    );
+    assert_eq!(git(temp.path(), &["rev-parse", "add-two"]), old_two);
+    assert_eq!(git(origin.path(), &["rev-parse", "add-two"]), old_two);
+    let status = json_output(&temp, &["status"]);
+    assert_eq!(status["branches"][0]["name"], "add-two");
+    assert_eq!(status["branches"][0]["state"]["status"], "needs_restack");
+}
+
+#[test]
+fn test_merge_leaves_branch_delete_to_github() {
+    let temp = setup_git_repo();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(temp.path().join("a.txt"), "one\n").expect("write");
+    rung()
+        .args(["create", "-m", "Add one"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main", "add-one"]);
+    record_prs(&temp, &[1]);
+
+    let mut pr = api_pr(1, "add-one", "main", "open", "octo");
+    pr["head"]["sha"] = serde_json::json!(git(temp.path(), &["rev-parse", "add-one"]));
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/repos/acme/widgets ",
+            r#"{"delete_branch_on_merge":true}"#.to_string(),
+        ),
+        (
+            "/pulls/1/merge ",
+            serde_json::json!({ "sha": "0".repeat(40), "merged": true, "message": "Merged" })
+                .to_string(),
+        ),
+        ("/pulls/1 ", pr.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+    let merge = |args: &[&str]| {
+        rung()
+            .args(["--yes", "merge", "--ignore-checks"])
+            .args(args)
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .assert()
+            .success()
+    };
+
+    // GitHub deletes the head branch itself
+    merge(&["--dry-run"])
+        .stdout(predicate::str::contains("Merge PR #1 (add-one)"))
+        .stdout(predicate::str::contains("Delete remote branch").not());
+    merge(&[]).stdout(predicate::str::contains("Deleted remote branch").not());
+    let requests = requests.lock().expect("Poisoned").clone();
+    assert!(
+        requests
+            .iter()
+            .any(|r| r.starts_with("PUT /repos/acme/widgets/pulls/1/merge")),
+        "{requests:?}"
+    );
+    assert!(
+        !requests.iter().any(|r| r.starts_with("DELETE")),
+        "{requests:?}"
+    );
+}
+
+#[test]
+fn test_contains_finds_squash_merged_commit() {
+    let temp = setup_git_repo();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(temp.path().join("a.txt"), "one\n").expect("write");
+    rung()
+        .args(["create", "-m", "Add one"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let original = git(temp.path(), &["rev-parse", "add-one"]);
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    git(temp.path(), &["push", "-q", "origin", "main", "add-one"]);
+    record_prs(&temp, &[1]);
+
+    // GitHub squashes the branch into a new commit on main
+    git(temp.path(), &["checkout", "-q", "-b", "squashed", "main"]);
+    fs::write(temp.path().join("a.txt"), "one\n").expect("write");
+    git(temp.path(), &["add", "."]);
+    git(temp.path(), &["commit", "-qm", "Add one (#1)"]);
+    let squash = git(temp.path(), &["rev-parse", "HEAD"]);
+    git(temp.path(), &["push", "-q", "origin", "squashed:main"]);
+    git(temp.path(), &["checkout", "-q", "add-one"]);
+    git(temp.path(), &["branch", "-q", "-D", "squashed"]);
+
+    let mut pr = api_pr(1, "add-one", "main", "open", "octo");
+    pr["head"]["sha"] = serde_json::json!(original);
+    let (api, _requests) = mock_github_recording(vec![
+        ("/repos/acme/widgets ", "{}".to_string()),
+        (
+            "/pulls/1/merge ",
+            serde_json::json!({ "sha": squash, "merged": true, "message": "Merged" }).to_string(),
+        ),
+        ("/pulls/1 ", pr.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+    rung()
+        .args(["--yes", "merge", "--ignore-checks"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .envs(redirect.iter().map(|(k, v)| (k, v)))
+        .assert()
+        .success();
+
+    let out = json_output(&temp, &["contains", &original]);
+    assert_eq!(out["commit"], original);
+    assert_eq!(out["trunk"], serde_json::json!([]));
+    assert_eq!(out["landed"]["pr_number"], 1);
+    assert_eq!(out["landed"]["branch"], "add-one");
+    assert_eq!(out["landed"]["commit"], squash);
+    assert_eq!(out["landed"]["into"], "main");
+    assert_eq!(out["landed"]["unchanged"], true);
+
+    // The squash commit itself is on main
+    let out = json_output(&temp, &["contains", &squash]);
+    assert_eq!(out["trunk"], serde_json::json!(["main"]));
+    assert!(out["landed"].is_null());
+}
+
+#[test]
+fn test_contains_finds_branch_by_message() {
+    let temp = setup_git_repo();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    rung().arg("init").current_dir(&temp).assert().success();
+    for (file, message) in [("a.txt", "Add one"), ("b.txt", "Add two")] {
+        fs::write(temp.path().join(file), "content\n").expect("write");
+        rung()
+            .args(["create", "-m", message])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+    git(origin.path(), &["init", "-q", "--bare"]);
+    let origin_url = origin.path().display().to_string();
+    git(temp.path(), &["remote", "add", "origin", &origin_url]);
+    git(temp.path(), &["push", "-q", "origin", "main", "add-one"]);
+    record_prs(&temp, &[1, 2]);
+
+    let out = json_output(&temp, &["contains", "add TWO"]);
+    assert_eq!(out["commit"], git(temp.path(), &["rev-parse", "add-two"]));
+    assert_eq!(out["summary"], "Add two");
+    assert_eq!(out["branch"]["name"], "add-two");
+    assert_eq!(out["branch"]["pr"], 2);
+    assert_eq!(out["branch"]["pushed"], false);
+    assert_eq!(out["trunk"], serde_json::json!([]));
+    assert!(out["same_patch"].is_null());
+
+    rung()
+        .args(["contains", "Add"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("matches 2 commits"));
+
+    // Cherry-picked onto main, the commit landed under another id
+    git(temp.path(), &["checkout", "-q", "main"]);
+    git(temp.path(), &["cherry-pick", "-x", "add-one"]);
+    git(temp.path(), &["push", "-q", "origin", "main"]);
+    let picked = git(temp.path(), &["rev-parse", "main"]);
+    let out = json_output(&temp, &["contains", "add one"]);
+    assert_eq!(out["branch"]["name"], "add-one");
+    assert_eq!(out["branch"]["pushed"], true);
+    assert_eq!(out["trunk"], serde_json::json!([]));
+    assert_eq!(out["same_patch"]["commit"], picked);
+    assert_eq!(out["same_patch"]["into"], "main");
+}
+
+#[test]
+fn test_graph_exports_stack() {
+    let temp = setup_json_fixture();
+    record_prs(&temp, &[12, 13]);
+    fs::write(
+        temp.path().join(".git/rung/prs.json"),
+        r#"{"prs": {"12": {"state": "merged", "base": "main"}}}"#,
+    )
+    .expect("Failed to write PR cache");
+
+    rung()
+        .arg("graph")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("flowchart TD\n"))
+        .stdout(predicate::str::contains(
+            "n1[\"add-widget<br/>#12 merged\"]",
+        ))
+        .stdout(predicate::str::contains("n2[\"add-gadget<br/>#13\"]"))
+        .stdout(predicate::str::contains("n1 --> n2"));
+
+    rung()
+        .args(["graph", "--format", "dot"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "n0 [label=\"main\", shape=ellipse];",
+        ))
+        .stdout(predicate::str::contains(
+            "n1 [label=\"add-widget\\n#12 merged\", color=\"#8250df\"];",
+        ))
+        .stdout(predicate::str::contains("n0 -> n1;"));
+}
+
+#[test]
+fn test_requires_orders_merge_until() {
+    let temp = setup_merge_stack();
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    git(&["checkout", "-q", "main"]);
+    rung()
+        .args(["create", "migration"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("Failed to read stack"))
+            .expect("Invalid stack.json");
+    stack["branches"][3]["pr"] = serde_json::json!(4);
+    fs::write(&stack_path, stack.to_string()).expect("Failed to write stack");
+
+    git(&["checkout", "-q", "feature-2"]);
+    rung()
+        .args(["requires", "feature-3"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("depends on it"));
+    rung()
+        .args(["requires", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already below"));
+    let output = json_output(&temp, &["requires", "migration"]);
+    assert_eq!(output["requires"], serde_json::json!(["migration"]));
+
+    // Merging feature-2 alone leaves its requirement behind
+    rung()
+        .args(["merge", "feature-2", "--dry-run"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "'feature-2' requires 'migration', which hasn't landed",
+        ));
+
+    let plans = json_output(&temp, &["merge", "--until", "feature-2", "--dry-run"]);
+    let order: Vec<&str> = plans
+        .as_array()
+        .expect("Expected an array")
+        .iter()
+        .filter_map(|plan| plan["branch"].as_str())
+        .collect();
+    assert_eq!(order, ["feature-1", "migration", "feature-2"]);
+
+    rung()
+        .args(["requires", "--clear"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let stack = fs::read_to_string(&stack_path).expect("Failed to read stack");
+    assert!(!stack.contains("requires"));
+}
+
+// ============================================================================
+// Undo command tests
+// ============================================================================
+
+#[test]
+fn test_undo_no_backup() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Undo with no sync to undo
+    rung()
+        .arg("undo")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("backup").or(predicate::str::contains("nothing to undo")));
+}
+
+#[test]
+fn test_undo_requires_confirmation() {
+    let temp = setup_git_repo();
+    let commit = |file: &str, msg: &str| {
+        fs::write(temp.path().join(file), msg).expect("Failed to write file");
+        StdCommand::new("git")
+            .args(["add", "."])
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to git add");
+        StdCommand::new("git")
+            .args(["commit", "-m", msg])
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to commit");
+    };
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    commit("feature.txt", "Feature");
+    rung().arg("prv").current_dir(&temp).assert().success();
+    commit("main.txt", "Main moves on");
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Without a terminal, destructive commands need --yes
+    rung()
+        .arg("undo")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --yes"));
+    rung()
+        .args(["undo", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 branches"));
+}
+
+#[test]
+fn test_undo_some_branches() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let widget = git(&["rev-parse", "add-widget"]);
+    let gadget = git(&["rev-parse", "add-gadget"]);
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    rung()
+        .args(["undo", "--branch", "add-sprocket", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("isn't in the last sync's backup"));
+    rung()
+        .args(["undo", "--interactive"])
+        .current_dir(&temp)
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("pass --branch <name>"));
+
+    rung()
+        .args(["undo", "--branch", "add-gadget", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 branches"))
+        .stdout(predicate::str::contains("1 branch(es) left in the backup"));
+    assert_eq!(git(&["rev-parse", "add-gadget"]), gadget);
+    assert_ne!(git(&["rev-parse", "add-widget"]), widget);
+
+    // The rest can still be restored
+    rung()
+        .args(["undo", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 branches"));
+    assert_eq!(git(&["rev-parse", "add-widget"]), widget);
+}
+
+#[test]
+fn test_status_at_backup() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let widget = git(&["rev-parse", "add-widget"]);
+    rung()
+        .args(["sync", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .args(["create", "add-sprocket"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let backup = fs::read_dir(temp.path().join(".git/rung/refs"))
+        .expect("No backups")
+        .next()
+        .expect("No backup")
+        .expect("Unreadable backup")
+        .file_name()
+        .to_string_lossy()
+        .to_string();
+
+    // By ID, by its first digits, or by a time after it was taken
+    for at in [backup.as_str(), &backup[..8], "2999-01-01"] {
+        let status = json_output(&temp, &["status", "--at", at]);
+        assert_eq!(status["backup"], backup.as_str());
+        assert_eq!(status["branches"][0]["name"], "add-widget");
+        assert_eq!(status["branches"][0]["commit"], widget.as_str());
+        assert_eq!(status["branches"][0]["change"], "moved");
+        assert_eq!(status["branches"][1]["parent"], "add-widget");
+        assert_eq!(status["added"], serde_json::json!(["add-sprocket"]));
+    }
+
+    rung()
+        .args(["status", "--at", &backup])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Stack at backup {backup}"
+        )))
+        .stdout(predicate::str::contains(format!("{} (now ", &widget[..7])))
+        .stderr(predicate::str::contains(
+            "`rung undo` puts the moved branches back",
+        ));
+    rung()
+        .args(["status", "--at", "2000-01-01 09:30"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No backup was taken by 2000-01-01 09:30",
+        ));
+}
+
+#[test]
+fn test_refuses_stack_for_another_origin() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://github.com/acme/widgets.git",
+    ]);
+    rung().arg("init").current_dir(&temp).assert().success();
+    let recorded = || {
+        fs::read_to_string(temp.path().join(".git/rung/origin"))
+            .expect("No origin recorded")
+            .trim()
+            .to_string()
+    };
+    assert_eq!(recorded(), "acme/widgets");
+
+    git(&[
+        "remote",
+        "set-url",
+        "origin",
+        "git@github.com:acme/gadgets.git",
+    ]);
+    for args in [&["status"][..], &["create", "feature"]] {
+        rung()
+            .args(args)
+            .current_dir(&temp)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "This stack was set up for acme/widgets, but origin now points at acme/gadgets",
+            ))
+            .stderr(predicate::str::contains("rung init --reattach"));
+    }
+    let issues = json_output(&temp, &["doctor"]);
+    assert!(
+        issues["issues"]
+            .as_array()
+            .is_some_and(|issues| issues.iter().any(|i| i["message"]
+                .as_str()
+                .is_some_and(|m| m.contains("acme/gadgets")))),
+        "{issues}"
+    );
+
+    rung()
+        .args(["init", "--reattach"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Reattached the stack from acme/widgets to acme/gadgets",
+        ));
+    assert_eq!(recorded(), "acme/gadgets");
+    rung()
+        .args(["create", "feature"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // A stack from before origins were recorded takes the current one
+    fs::remove_file(temp.path().join(".git/rung/origin")).expect("remove");
+    rung().arg("status").current_dir(&temp).assert().success();
+    assert_eq!(recorded(), "acme/gadgets");
+}
+
+#[test]
+fn test_read_only_commands_in_bare_clone() {
+    let temp = setup_json_fixture();
+    let bare = TempDir::new().expect("Failed to create temp dir");
+    let clone = bare.path().join("repo.git");
+    StdCommand::new("git")
+        .args(["clone", "-q", "--bare"])
+        .arg(temp.path())
+        .arg(&clone)
+        .output()
+        .expect("Failed to clone");
+    StdCommand::new("git")
+        .args(["symbolic-ref", "HEAD", "refs/heads/add-gadget"])
+        .current_dir(&clone)
+        .output()
+        .expect("Failed to set HEAD");
+    fs::create_dir(clone.join("rung")).expect("mkdir");
+    for file in ["stack.json", "config.toml"] {
+        let from = temp.path().join(".git/rung").join(file);
+        if from.exists() {
+            fs::copy(from, clone.join("rung").join(file)).expect("copy");
+        }
+    }
+
+    let output = rung()
+        .args(["--json", "status"])
+        .current_dir(&clone)
+        .output()
+        .expect("Failed to run rung");
+    assert!(output.status.success(), "{output:?}");
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    assert_eq!(status["current"], "add-gadget");
+    assert_eq!(status["branches"][1]["name"], "add-gadget");
+    for args in [&["log"][..], &["graph"], &["doctor"]] {
+        rung().args(args).current_dir(&clone).assert().success();
+    }
+    rung()
+        .args(["create", "add-sprocket"])
+        .current_dir(&clone)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot run in bare repository"));
+
+    // GIT_DIR and GIT_WORK_TREE point rung at a repository from anywhere
+    let output = rung()
+        .args(["--json", "status"])
+        .current_dir(bare.path())
+        .env("GIT_DIR", temp.path().join(".git"))
+        .env("GIT_WORK_TREE", temp.path())
+        .output()
+        .expect("Failed to run rung");
+    assert!(output.status.success(), "{output:?}");
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    assert_eq!(status["current"], "add-gadget");
+}
+
+// ============================================================================
+// Log command tests
+// ============================================================================
+
+#[test]
+fn test_log() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    // Create first branch
+    rung()
+        .args(["create", "feature"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // Make a commit on feature
+    let file = temp.path().join("feature1.txt");
+    fs::write(&file, "feature 1 content").expect("Failed to write file");
+
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to git add");
+
+    StdCommand::new("git")
+        .args(["commit", "-m", "Add feature"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+
+    rung()
+        .arg("log")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Add feature"));
+}
+
+#[test]
+fn test_log_patch() {
+    let temp = setup_json_fixture();
+
+    // Output to a pipe is never paged, whatever the pager
+    rung()
+        .args(["log", "--patch"])
+        .current_dir(&temp)
+        .env("PAGER", "false")
+        .env("GIT_PAGER", "false")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Add gadget"))
+        .stdout(predicate::str::contains(
+            "diff --git a/gadget.txt b/gadget.txt",
+        ))
+        .stdout(predicate::str::contains("+gadget"))
+        .stdout(predicate::str::contains("widget.txt").not());
+
+    rung()
+        .args(["log", "--no-pager"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Add gadget"))
+        .stdout(predicate::str::contains("diff --git").not());
+
+    rung()
+        .args(["-q", "log", "--patch"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_log_operations() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["log", "--operations"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No operations recorded yet"));
+
+    // An entry as `rung merge` records it
+    fs::write(
+        temp.path().join(".git/rung/operations.jsonl"),
+        concat!(
+            r#"{"started_at":"2026-01-05T10:00:00Z","finished_at":"2026-01-05T10:01:00Z","#,
+            r#""op":"merge","branch":"feat-a","pr_number":7,"merge_sha":"89abcdef0123","#,
+            r#""method":"squash","into":"main","reparented":["feat-b"]}"#,
+            "\n"
+        ),
+    )
+    .expect("Failed to write operations log");
+
+    rung()
+        .args(["log", "--operations"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "PR #7 (feat-a) into main via squash → 89abcde; re-parented feat-b",
+        ));
+
+    let output = rung()
+        .args(["--json", "log", "--operations"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run log");
+    let ops: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(ops[0]["op"], "merge");
+    assert_eq!(ops[0]["merge_sha"], "89abcdef0123");
+}
+
+#[test]
+fn test_gc() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .arg("gc")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to clean up"));
+
+    let rung_dir = temp.path().join(".git/rung");
+    for id in ["100", "200"] {
+        fs::create_dir_all(rung_dir.join("refs").join(id)).expect("Failed to create backup");
+        fs::write(rung_dir.join("refs").join(id).join("feat"), "abc123")
+            .expect("Failed to write backup");
+    }
+    fs::write(
+        rung_dir.join("operations.jsonl"),
+        concat!(
+            r#"{"started_at":"2020-01-05T10:00:00Z","finished_at":"2020-01-05T10:01:00Z","#,
+            r#""op":"merge","branch":"feat-a","pr_number":7,"merge_sha":"89abcdef0123","#,
+            r#""method":"squash","into":"main","reparented":[]}"#,
+            "\n"
+        ),
+    )
+    .expect("Failed to write operations log");
+    fs::write(rung_dir.join("stack.json.tmp"), "{").expect("Failed to write temp file");
+    StdCommand::new("git")
+        .args(["branch", "rung-webhook/feat"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to create branch");
+
+    let output = rung()
+        .args(["gc", "--keep-backups", "1", "--json"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run gc");
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(json["backups_removed"], 1);
+    assert_eq!(json["operations_removed"], 1);
+    assert_eq!(json["temp_files_removed"], 1);
+    assert_eq!(json["work_branches_removed"], 1);
+
+    assert!(rung_dir.join("refs/200").exists());
+    assert!(!rung_dir.join("refs/100").exists());
+    assert!(!rung_dir.join("stack.json.tmp").exists());
+}
+
+#[test]
+fn test_tidy() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    git(&["checkout", "-q", "main"]);
+    rung()
+        .args(["create", "empty-one"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    git(&["checkout", "-q", "main"]);
+    record_prs(&temp, &[12, 13]);
+    fs::write(
+        temp.path().join(".git/rung/prs.json"),
+        r#"{"prs": {"12": {"state": "open", "base": "main"}, "13": {"state": "merged", "base": "add-widget"}}}"#,
+    )
+    .expect("Failed to write PR cache");
+
+    // Without a terminal the cleanups are only listed
+    rung()
+        .arg("tidy")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Delete 'add-gadget' - PR #13 merged",
+        ))
+        .stdout(predicate::str::contains(
+            "Delete 'empty-one' - no commits of its own",
+        ))
+        .stdout(predicate::str::contains("'add-widget'").not());
+    let listed = json_output(&temp, &["tidy"]);
+    assert_eq!(listed["applied"], false);
+    assert_eq!(listed["cleanups"][0]["kind"], "merged");
+    assert_eq!(listed["cleanups"][1]["kind"], "empty");
+
+    rung()
+        .args(["tidy", "--all"])
+        .current_dir(&temp)
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("pass --yes"));
+    rung()
+        .args(["tidy", "--all", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deleted 'add-gadget'"));
+
+    let branches = StdCommand::new("git")
+        .args(["branch", "--format=%(refname:short)"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    assert_eq!(
+        String::from_utf8_lossy(&branches.stdout),
+        "add-widget\nmain\n"
+    );
+    let stack = fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("read stack");
+    assert!(
+        !stack.contains("add-gadget") && !stack.contains("empty-one"),
+        "{stack}"
+    );
+    rung()
+        .arg("tidy")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to tidy"));
+}
+
+#[test]
+fn test_config_command() {
+    let temp = setup_git_repo();
+    let home = TempDir::new().expect("Failed to create temp dir");
+    rung().arg("init").current_dir(&temp).assert().success();
+    let config_path = temp.path().join(".git/rung/config.toml");
+    fs::write(&config_path, "[general]\nbackup_retention = 5 # a week\n")
+        .expect("Failed to write config");
+
+    rung()
+        .args(["config", "set", "general.backup_retention", "10"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .args(["config", "set", "sync.push_comment", "yes"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected a boolean"));
+    rung()
+        .args(["config", "set", "general.no_such_key", "1"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown key"));
+    assert_eq!(
+        fs::read_to_string(&config_path).expect("Failed to read config"),
+        "[general]\nbackup_retention = 10 # a week\n"
+    );
+
+    rung()
+        .args(["config", "get", "general.backup_retention"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout("10\n");
+    // Defaults are in effect, but not set in the file
+    rung()
+        .args(["config", "get", "general.default_remote"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout("origin\n");
+    rung()
+        .args(["config", "get", "--local", "general.default_remote"])
+        .current_dir(&temp)
+        .assert()
+        .code(1);
+
+    let output = rung()
+        .args(["config", "list", "--json"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run config list");
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(json["general.backup_retention"], 10);
+    assert_eq!(json["sync.push_comment"], false);
+
+    // The user-wide config only supplies [orgs.*]
+    rung()
+        .args(["config", "set", "--global", "general.assume_yes", "true"])
+        .env("XDG_CONFIG_HOME", home.path())
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("without --global"));
+    rung()
+        .args([
+            "config",
+            "set",
+            "--global",
+            "orgs.acme.merge.method",
+            "rebase",
+        ])
+        .env("XDG_CONFIG_HOME", home.path())
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .args(["config", "list"])
+        .env("XDG_CONFIG_HOME", home.path())
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "orgs.acme.merge.method = \"rebase\"",
+        ));
+}
+
+#[test]
+fn test_team_config() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{}\"\n", mock_github()),
+    )
+    .expect("Failed to write config");
+
+    // Written like any other config, into the worktree to check in
+    for (key, value) in [
+        ("general.protected_branches", r#"["main", "release/*"]"#),
+        ("pr.title_pattern", "^(feat|fix): "),
+        ("pr.reviewers", r#"["acme/platform"]"#),
+    ] {
+        rung()
+            .args(["config", "set", "--team", key, value])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+    assert!(temp.path().join(".rung/team.toml").exists());
+    rung()
+        .args(["config", "get", "pr.title_pattern"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout("^(feat|fix): \n");
+
+    rung()
+        .args(["create", "release/2.0"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'release/2.0' is a protected branch",
+        ));
+    // "Add widget" doesn't match, so nothing is pushed
+    rung()
+        .args(["submit", "--dry-run"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "PR title for 'add-widget' doesn't match `pr.title_pattern`",
+        ));
+
+    // The repository config wins, and doctor points out where it differs
+    rung()
+        .args(["config", "set", "pr.title_pattern", "."])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    json_output(&temp, &["submit", "--dry-run"]);
+    let doctor = json_output(&temp, &["doctor"]);
+    let messages: Vec<&str> = doctor["issues"]
+        .as_array()
+        .expect("No issues")
+        .iter()
+        .filter_map(|issue| issue["message"].as_str())
+        .collect();
+    assert!(
+        messages
+            .contains(&r#"`pr.title_pattern` is "." here, overriding the team's "^(feat|fix): ""#),
+        "{messages:?}"
+    );
+}
+
+#[test]
+fn test_policy() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    let policy = "[policy]\nrequire_ticket = true\n\
+                  forbidden_paths = [{ branches = \"add-*\", paths = [\"gadget.txt\"] }]\n\
+                  reviewers = [{ path = \"widget.txt\", reviewers = [\"acme/platform\"] }]\n";
+    let write_config = |api: &str| {
+        fs::write(
+            temp.path().join(".git/rung/config.toml"),
+            format!("[github]\napi_url = \"{api}\"\n{policy}"),
+        )
+        .expect("Failed to write config");
+    };
+    write_config(&mock_github_with(vec![
+        ("/pulls?", "[]".to_string()),
+        (
+            "/repos/acme/widgets ",
+            r#"{"default_branch": "main"}"#.to_string(),
+        ),
+    ]));
+
+    let rules = |report: &serde_json::Value| -> Vec<String> {
+        report["policy_violations"]
+            .as_array()
+            .expect("No violations")
+            .iter()
+            .map(|v| format!("{} {}", v["branch"], v["rule"]).replace('"', ""))
+            .collect()
+    };
+    let report = json_output(&temp, &["submit", "--dry-run"]);
+    assert_eq!(
+        rules(&report),
+        [
+            "add-widget require-ticket",
+            "add-gadget require-ticket",
+            "add-gadget forbidden-path"
+        ]
+    );
+    rung()
+        .args(["submit", "--dry-run"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'add-gadget' changes 'gadget.txt', which branches matching 'add-*' may not change",
+        ))
+        .stderr(predicate::str::contains("3 policy violation(s)"));
+    rung()
+        .args(["submit", "--dry-run", "--override-policy"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Policy overridden:"));
+
+    // Merging also checks who was asked to review
+    record_prs(&temp, &[1, 2]);
+    let requested = |reviewer: &str| format!(r#"[{{"event": "review_requested", {reviewer}}}]"#);
+    write_config(&mock_github_with(vec![
+        (
+            "/issues/1/events",
+            requested(r#""requested_reviewer": {"login": "octo"}"#),
+        ),
+        ("/pulls/1/reviews", "[]".to_string()),
+    ]));
+    let report = json_output(&temp, &["merge", "add-widget", "--dry-run"]);
+    assert_eq!(
+        rules(&report),
+        ["add-widget require-ticket", "add-widget required-reviewers"]
+    );
+
+    write_config(&mock_github_with(vec![
+        (
+            "/issues/1/events",
+            requested(r#""requested_team": {"slug": "platform"}"#),
+        ),
+        ("/pulls/1/reviews", "[]".to_string()),
+    ]));
+    let report = json_output(&temp, &["merge", "add-widget", "--dry-run"]);
+    assert_eq!(rules(&report), ["add-widget require-ticket"]);
+}
+
+#[test]
+fn test_verify() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+    let head = StdCommand::new("git")
+        .args(["rev-parse", "add-widget"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    let mut widget = api_pr(1, "add-widget", "main", "open", "octo");
+    widget["head"]["sha"] = String::from_utf8_lossy(&head.stdout).trim().into();
+    widget["mergeable"] = true.into();
+    widget["mergeable_state"] = "clean".into();
+    let review = |state: &str| format!(r#"[{{"user": {{"login": "octo"}}, "state": "{state}"}}]"#);
+    let runs = serde_json::json!({ "check_runs": [
+        { "name": "build", "status": "completed", "conclusion": "success", "details_url": null },
+    ] });
+    let api = mock_github_with(vec![
+        ("/pulls/1/reviews", review("APPROVED")),
+        ("/pulls/2/reviews", review("CHANGES_REQUESTED")),
+        ("/pulls/1 ", widget.to_string()),
+        (
+            "/pulls/2 ",
+            api_pr(2, "add-gadget", "add-widget", "open", "octo").to_string(),
+        ),
+        ("/check-runs ", runs.to_string()),
+        ("/status ", r#"{"statuses":[]}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let report = json_output(&temp, &["verify"]);
+    assert_eq!(report["ready"], false);
+    assert_eq!(report["doctor"]["outcome"], "pass", "{report}");
+    let outcomes = |i: usize| -> Vec<String> {
+        let checks = &report["branches"][i]["checks"];
+        [
+            "stack",
+            "pr",
+            "title",
+            "ci",
+            "reviews",
+            "mergeable",
+            "policy",
+        ]
+        .iter()
+        .map(|column| format!("{column}={}", checks[column]["outcome"]).replace('"', ""))
+        .collect()
+    };
+    // main moved on since add-widget was created
+    assert_eq!(
+        outcomes(0),
+        [
+            "stack=fail",
+            "pr=pass",
+            "title=skipped",
+            "ci=pass",
+            "reviews=pass",
+            "mergeable=pass",
+            "policy=skipped"
+        ]
+    );
+    assert_eq!(
+        report["branches"][1]["checks"]["pr"]["detail"],
+        "local commits not pushed - run `rung submit`"
+    );
+    assert_eq!(
+        report["branches"][1]["checks"]["reviews"]["detail"],
+        "changes requested by octo"
+    );
+
+    rung()
+        .arg("verify")
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("stack     pr        title"))
+        .stdout(predicate::str::contains(
+            "add-gadget reviews: changes requested by octo",
+        ))
+        .stderr(predicate::str::contains("Stack is not ready to land"));
+}
+
+#[test]
+fn test_doctor_bugreport() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+    let widget = api_pr(1, "add-widget", "main", "open", "octo");
+    let api = mock_github_with(vec![("/pulls/1 ", widget.to_string())]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = rung()
+        .args(["doctor", "--bugreport", "--json"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .output()
+        .expect("Failed to run doctor");
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    let tarball = json["bugreport"].as_str().expect("No bugreport path");
+    assert!(tarball.ends_with(".tar.gz"));
+
+    let unpacked = TempDir::new().expect("Failed to create temp dir");
+    let status = StdCommand::new("tar")
+        .args(["-xzf", tarball, "-C"])
+        .arg(unpacked.path())
+        .status()
+        .expect("Failed to run tar");
+    assert!(status.success());
+    let dir = fs::read_dir(unpacked.path())
+        .expect("Failed to read unpacked report")
+        .next()
+        .expect("Empty tarball")
+        .expect("Bad entry")
+        .path();
+    let read = |file: &str| fs::read_to_string(dir.join(file)).expect(file);
+
+    let versions: serde_json::Value =
+        serde_json::from_str(&read("versions.json")).expect("Invalid versions.json");
+    assert_eq!(versions["rung"], env!("CARGO_PKG_VERSION"));
+    assert!(read("doctor.json").contains("Could not fetch PR #2"));
+    let http: serde_json::Value =
+        serde_json::from_str(&read("http.json")).expect("Invalid http.json");
+    assert_eq!(http[0]["path"], "/repos/acme/widgets/pulls/1");
+    assert_eq!(http[0]["status"], 200);
+    assert_eq!(http[1]["status"], 404);
+    assert!(read("config.toml").contains("api_url"));
+    assert!(read("stack.json").contains("add-gadget"));
+    assert!(read("operations.jsonl").contains(r#""op":"create""#));
+    assert!(!read("config.toml").contains("test-token"));
+}
+
+#[test]
+fn test_log_files_and_status_json_files() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    fs::write(temp.path().join("feature1.txt"), "feature 1").expect("Failed to write file");
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to git add");
+    StdCommand::new("git")
+        .args(["commit", "-m", "Add feature"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+
+    rung()
+        .args(["log", "--files"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Files changed (1)"))
+        .stdout(predicates::str::contains("feature1.txt"));
+
+    let output = rung()
+        .args(["status", "--json"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run status");
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(json["branches"][0]["files"][0], "feature1.txt");
+}
+
+// ============================================================================
+// Edit command tests
+// ============================================================================
+
+#[test]
+fn test_edit_restacks_descendants() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    for (file, msg) in [("one.txt", "First"), ("two.txt", "Second")] {
+        fs::write(temp.path().join(file), msg).expect("Failed to write file");
+        StdCommand::new("git")
+            .args(["add", "."])
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to git add");
+        StdCommand::new("git")
+            .args(["commit", "-m", msg])
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to commit");
+    }
+    rung()
+        .args(["create", "child"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    fs::write(temp.path().join("child.txt"), "child").expect("Failed to write file");
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to git add");
+    StdCommand::new("git")
+        .args(["commit", "-m", "Child"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+    rung().arg("prv").current_dir(&temp).assert().success();
+
+    // Drop the first commit on feature-1 without opening an editor
+    rung()
+        .arg("edit")
+        .env("GIT_SEQUENCE_EDITOR", "sed -i -e '1s/^pick/drop/'")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restacked 1 descendant(s)"));
+
+    let output = StdCommand::new("git")
+        .args(["log", "--format=%s", "main..child"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to read log");
+    let log = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(log.lines().collect::<Vec<_>>(), vec!["Child", "Second"]);
+}
+
+// ============================================================================
+// Reword command tests
+// ============================================================================
+
+#[test]
+fn test_reword_earlier_commit_restacks_descendants() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    for (file, msg) in [("one.txt", "First"), ("two.txt", "Second")] {
+        fs::write(temp.path().join(file), msg).expect("Failed to write file");
+        StdCommand::new("git")
+            .args(["add", "."])
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to git add");
+        StdCommand::new("git")
+            .args(["commit", "-m", msg])
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to commit");
+    }
+    rung()
+        .args(["create", "child"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    fs::write(temp.path().join("child.txt"), "child").expect("Failed to write file");
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to git add");
+    StdCommand::new("git")
+        .args(["commit", "-m", "Child"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+    rung().arg("prv").current_dir(&temp).assert().success();
+
+    rung()
+        .args(["reword", "HEAD~1", "-m", "First, renamed"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restacked 1 descendant(s)"));
+
+    let output = StdCommand::new("git")
+        .args(["log", "--format=%s", "main..child"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to read log");
+    let log = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        log.lines().collect::<Vec<_>>(),
+        vec!["Child", "Second", "First, renamed"]
+    );
+
+    // Commits below the branch can't be reworded
+    rung()
+        .args(["reword", "main", "-m", "Nope"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only the branch's own commits"));
+
+    // The reword was backed up, so undo names it and puts both branches back
+    rung()
+        .args(["undo", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Restored 2 branches from the reword backup",
+        ));
+    let output = StdCommand::new("git")
+        .args(["log", "--format=%s", "main..child"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to read log");
+    let log = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        log.lines().collect::<Vec<_>>(),
+        vec!["Child", "Second", "First"]
+    );
+}
+
+// ============================================================================
+// Scope and foreach command tests
+// ============================================================================
+
+#[test]
+fn test_scope_flags_files_and_foreach_runs_in_packages() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        "[[packages]]\nname = \"api\"\npath = \"services/api\"\n",
+    )
+    .expect("Failed to write config");
+    rung()
+        .args(["create", "feature-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    fs::create_dir_all(temp.path().join("services/api")).expect("Failed to create dir");
+    fs::write(temp.path().join("services/api/lib.rs"), "api").expect("Failed to write file");
+    fs::write(temp.path().join("notes.md"), "notes").expect("Failed to write file");
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to git add");
+    StdCommand::new("git")
+        .args(["commit", "-m", "Touch api"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+
+    rung()
+        .args(["scope", "./services/api/"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes.md"));
+
+    let output = rung()
+        .args(["status", "--json"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run status");
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(json["branches"][0]["packages"][0], "api");
+    assert_eq!(json["branches"][0]["outside_scope"], 1);
+
+    rung()
+        .args(["foreach", "--", "ls"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs"));
+}
+
+// ============================================================================
+// Archive command tests
+// ============================================================================
+
+#[test]
+fn test_archive_and_unarchive() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "parked-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .args(["create", "parked-2"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    rung()
+        .args(["archive", "--delete", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived 2 branch(es)"));
+
+    // Branches are gone from the stack and from git
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("parked-1").not());
+    let output = StdCommand::new("git")
+        .args(["branch", "--list", "parked-*"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to list branches");
+    assert!(String::from_utf8_lossy(&output.stdout).trim().is_empty());
+
+    rung()
+        .args(["archive", "--list"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("parked-1"));
+
+    rung()
+        .args(["unarchive", "parked-1"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 2 branch(es)"));
+
+    rung()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("parked-1"))
+        .stdout(predicate::str::contains("parked-2"));
+}
+
+#[test]
+fn test_archive_delete_refuses_parentless_root_first() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "parked-1"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    // A root tracked without a parent leaves nothing to check out instead
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("Failed to read stack"))
+            .expect("Invalid stack.json");
+    stack["branches"][0]["parent"] = serde_json::Value::Null;
+    let stack = stack.to_string();
+    fs::write(&stack_path, &stack).expect("Failed to write stack");
+
+    rung()
+        .args(["archive", "--delete", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("root branch has no parent"));
+    assert_eq!(
+        fs::read_to_string(&stack_path).expect("Failed to read stack"),
+        stack
+    );
+    assert!(!temp.path().join(".git/rung/archives").exists());
+}
+
+#[test]
+fn test_stack_export_and_import() {
+    let temp = setup_json_fixture();
+    let export = temp.path().join("stack-export.json");
+    let export_path = export.to_str().expect("UTF-8 path");
+    rung()
+        .args(["stack", "export", export_path])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 2 branch(es)"));
+    let exported: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&export).expect("read export"))
+            .expect("Invalid JSON");
+    assert_eq!(exported["branches"][1]["name"], "add-gadget");
+    assert_eq!(exported["branches"][1]["parent"], "add-widget");
+
+    // A fresh clone has the commits, but only main as a local branch
+    let clone = TempDir::new().expect("Failed to create temp dir");
+    let status = StdCommand::new("git")
+        .args(["clone", "-q", "--branch", "main"])
+        .arg(temp.path())
+        .arg(clone.path())
+        .status()
+        .expect("Failed to clone");
+    assert!(status.success());
+    rung().arg("init").current_dir(&clone).assert().success();
+
+    // Commits the clone doesn't have stop the import before anything changes
+    let mut unknown = exported;
+    unknown["branches"][1]["commit"] = "1".repeat(40).into();
+    let unknown_path = temp.path().join("unknown.json");
+    fs::write(&unknown_path, unknown.to_string()).expect("write");
+    rung()
+        .args(["stack", "import"])
+        .arg(&unknown_path)
+        .current_dir(&clone)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("11111111 (tip of 'add-gadget')"));
+    let rev_parse = |dir: &std::path::Path, rev: &str| {
+        let output = StdCommand::new("git")
+            .args(["rev-parse", "--verify", "-q", rev])
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    assert!(rev_parse(clone.path(), "refs/heads/add-widget").is_empty());
+
+    let output = json_output(&clone, &["stack", "import", export_path]);
+    assert_eq!(
+        output["created"],
+        serde_json::json!(["add-widget", "add-gadget"])
+    );
+    assert_eq!(
+        rev_parse(clone.path(), "add-gadget"),
+        rev_parse(temp.path(), "add-gadget")
+    );
+    rung()
+        .arg("status")
+        .current_dir(&clone)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("add-gadget"));
+
+    rung()
+        .args(["stack", "import", export_path])
+        .current_dir(&clone)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Branch 'add-widget' is already in the stack",
+        ));
+}
+
+// ============================================================================
+// Forward-port, copy and revert command tests
+// ============================================================================
+
+#[test]
+fn test_forward_port() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    // A stack landing on release/1.0, while main moved on
+    git(&["checkout", "-qb", "release/1.0"]);
+    rung().arg("init").current_dir(&temp).assert().success();
+    for name in ["widget", "gadget"] {
+        fs::write(temp.path().join(format!("{name}.txt")), name).expect("write");
+        rung()
+            .args(["create", "-m", &format!("Add {name}")])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+    git(&["checkout", "-q", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("write");
+    git(&["add", "."]);
+    git(&["commit", "-qm", "Advance main"]);
+    git(&["checkout", "-q", "add-gadget"]);
+
+    let output = json_output(&temp, &["forward-port", "main", "--dry-run"]);
+    assert_eq!(
+        output["ported"],
+        serde_json::json!([
+            { "branch": "add-widget", "copy": "add-widget-to-main", "parent": "main" },
+            { "branch": "add-gadget", "copy": "add-gadget-to-main", "parent": "add-widget-to-main" },
+        ])
+    );
+    assert!(git(&["branch", "--list", "*-to-main"]).is_empty());
+
+    rung()
+        .args(["forward-port", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Ported 'add-gadget' as 'add-gadget-to-main'",
+        ));
+
+    // Each copy carries only its own commit, on top of main
+    assert_eq!(git(&["branch", "--show-current"]), "add-gadget");
+    assert_eq!(
+        git(&["log", "--format=%s", "main..add-gadget-to-main"]),
+        "Add gadget\nAdd widget"
+    );
+    assert_eq!(
+        git(&["merge-base", "main", "add-widget-to-main"]),
+        git(&["rev-parse", "main"])
+    );
+
+    let status = json_output(&temp, &["status"]);
+    assert!(
+        status["branches"]
+            .as_array()
+            .expect("branches")
+            .iter()
+            .any(|b| b["name"] == "add-gadget-to-main" && b["parent"] == "add-widget-to-main")
+    );
+
+    let output = json_output(&temp, &["forward-port", "main"]);
+    assert_eq!(output["ported"], serde_json::json!([]));
+    assert_eq!(
+        output["already_ported"],
+        serde_json::json!(["add-widget", "add-gadget"])
+    );
+}
+
+#[test]
+fn test_copy() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let stack_before =
+        fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("Failed to read stack");
+    let tip_before = git(&["rev-parse", "add-gadget"]);
+
+    let output = json_output(&temp, &["copy", "--onto", "main"]);
+    assert_eq!(output["copy"], "add-gadget-to-main");
+    assert_eq!(output["commits"], 1);
+    assert!(output.get("pr").is_none());
+
+    // Only add-gadget's own commit is copied, and nothing else moves
+    assert_eq!(
+        git(&["log", "--format=%s", "main..add-gadget-to-main"]),
+        "Add gadget"
+    );
+    assert_eq!(git(&["branch", "--show-current"]), "add-gadget");
+    assert_eq!(git(&["rev-parse", "add-gadget"]), tip_before);
+    assert_eq!(
+        fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("Failed to read"),
+        stack_before
+    );
+
+    rung()
+        .args(["copy", "add-widget", "--onto", "main", "--name", "hotfix"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Copied 1 commit(s) from 'add-widget' onto 'main' as 'hotfix'",
+        ));
+    rung()
+        .args(["copy", "--onto", "main"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Branch 'add-gadget-to-main' already exists",
+        ));
+}
+
+#[test]
+fn test_revert() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://github.com/acme/widgets.git",
+    ]);
+
+    // add-widget was squash-merged into main
+    git(&["checkout", "-q", "main"]);
+    fs::write(temp.path().join("widget.txt"), "widget").expect("write");
+    git(&["add", "."]);
+    git(&["commit", "-qm", "Add widget (#1)"]);
+    let squash = git(&["rev-parse", "HEAD"]);
+    git(&["checkout", "-q", "add-gadget"]);
+
+    let mut merged = api_pr(1, "add-widget", "main", "merged", "octo");
+    merged["merge_commit_sha"] = squash.clone().into();
+    let open = api_pr(2, "add-gadget", "add-widget", "open", "octo");
+    let api = mock_github_with(vec![
+        ("/pulls/1 ", merged.to_string()),
+        ("/pulls/2 ", open.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    rung()
+        .args(["revert", "2", "--no-pr"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("PR #2 hasn't been merged"));
+
+    let output = json_output(&temp, &["revert", "#1", "--no-pr"]);
+    assert_eq!(output["branch"], "revert-1");
+    assert_eq!(output["reverted_commit"], squash.as_str());
+
+    // The revert branch undoes the squash commit on top of main
+    assert_eq!(git(&["branch", "--show-current"]), "revert-1");
+    assert_eq!(
+        git(&["log", "--format=%s", "main..revert-1"]),
+        "Revert \"Add widget (#1)\""
+    );
+    assert!(!temp.path().join("widget.txt").exists());
+
+    let status = json_output(&temp, &["status"]);
+    assert!(
+        status["branches"]
+            .as_array()
+            .expect("branches")
+            .iter()
+            .any(|b| b["name"] == "revert-1" && b["parent"] == "main")
+    );
+}
+
+// ============================================================================
+// Snapshot command tests
+// ============================================================================
+
+#[test]
+fn test_snapshot_and_diff() {
+    let temp = setup_git_repo();
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "feature"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    rung()
+        .args(["snapshot", "before-refactor"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Saved snapshot 'before-refactor'"));
+
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "Refactor"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to commit");
+
+    rung()
+        .args(["snapshot", "diff", "before-refactor"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+1 / -0 commits"));
+
+    rung()
+        .args(["snapshot", "diff", "missing"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no snapshot named 'missing'"));
+}
+
+#[test]
+fn test_state_diff() {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+    let diff_json = |args: &[&str]| -> serde_json::Value {
+        let output = rung()
+            .args(["state", "diff", "--json"])
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run state diff");
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output")
+    };
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    rung()
+        .args(["create", "-m", "Feature 1", "--empty"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    rung()
+        .args(["create", "-m", "Feature 2", "--empty"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    rung()
+        .args(["state", "diff"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ feature-2"));
+
+    // Sync backs up the stack with the branch tips
+    git(&["checkout", "main"]);
+    git(&["commit", "--allow-empty", "-m", "Advance main"]);
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    assert_eq!(diff_json(&["--backup"])["added"], serde_json::json!([]));
+
+    // A later sync drops the deleted branch
+    git(&["branch", "-D", "feature-2"]);
+    rung()
+        .args(["sync", "--base", "main", "--no-push"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let diff = diff_json(&["--backup"]);
+    assert_eq!(diff["removed"], serde_json::json!(["feature-2"]));
+    assert!(
+        diff["against"]
+            .as_str()
+            .is_some_and(|id| id.parse::<i64>().is_ok())
+    );
+    assert_eq!(diff_json(&[])["removed"], serde_json::json!(["feature-2"]));
+
+    rung()
+        .args(["state", "diff", "--backup", "0"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Backup 0 has no stack copy"));
+}
+
+#[test]
+fn test_cache_clear() {
+    let temp = setup_git_repo();
+    rung().arg("init").current_dir(&temp).assert().success();
+
+    let ci_path = temp.path().join(".git/rung/ci.json");
+    fs::write(
+        &ci_path,
+        r#"{"branches":{"feature":{"commit":"abc123","status":"passing","checked_at":"2026-01-01T00:00:00Z"}}}"#,
+    )
+    .expect("write cache");
+
+    rung()
+        .args(["cache", "clear"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cleared cached data"));
+    assert!(!ci_path.exists());
+
+    rung()
+        .args(["cache", "clear"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing cached"));
+}
+
+#[test]
+fn test_workspace_status() {
+    let root = TempDir::new().expect("Failed to create temp dir");
+    let api = setup_git_repo();
+    let client = setup_git_repo();
+    let name = |repo: &TempDir| {
+        format!(
+            "../{}",
+            repo.path()
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        )
+    };
+    for (repo, branch) in [(&api, "api-endpoint"), (&client, "client-call")] {
+        rung().arg("init").current_dir(repo).assert().success();
+        rung()
+            .args(["create", branch])
+            .current_dir(repo)
+            .assert()
+            .success();
+    }
+
+    rung()
+        .args(["workspace", "status"])
+        .current_dir(&root)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No rung-workspace.toml found"));
+
+    rung()
+        .args(["workspace", "init", &name(&api)])
+        .current_dir(&root)
+        .assert()
+        .success();
+    rung()
+        .args(["workspace", "add", &name(&client), &name(&api)])
+        .current_dir(&root)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added 1 repositor(ies)"));
+    rung()
+        .args(["workspace", "add", "missing"])
+        .current_dir(&root)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing is not a git repository"));
+
+    rung()
+        .args(["workspace", "status"])
+        .current_dir(&root)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("api-endpoint"))
+        .stdout(predicate::str::contains("client-call"));
+
+    let output = rung()
+        .args(["--json", "workspace", "status"])
+        .current_dir(&root)
+        .output()
+        .expect("Failed to run workspace status");
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    assert_eq!(json[0]["repo"], name(&api));
+    assert_eq!(json[1]["success"], true);
+    assert_eq!(json[1]["output"]["branches"][0]["name"], "client-call");
+}
+
+// ============================================================================
+// JSON output contract tests
+// ============================================================================
+
+/// Serve a minimal GitHub API on localhost: no open PRs, everything else 404.
+/// Returns the base URL.
+fn mock_github() -> String {
+    mock_github_with(vec![("/pulls?", "[]".to_string())])
+}
+
+/// Serve a GitHub API on localhost answering each request with the body of
+/// the first route whose pattern appears in the request line, or a 404.
+/// Returns the base URL.
+fn mock_github_with(routes: Vec<(&'static str, String)>) -> String {
+    mock_github_recording(routes).0
+}
+
+/// Like [`mock_github_with`], also recording each request as its request
+/// line (method and path) followed by its body, if any.
+fn mock_github_recording(
+    routes: Vec<(&'static str, String)>,
+) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!(
+        "http://{}",
+        listener.local_addr().expect("No local address")
+    );
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let recorded = std::sync::Arc::clone(&requests);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            let mut reader = BufReader::new(&stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).ok();
+            let mut length = 0;
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                line.clear();
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).ok();
+            let request = request_line.split(" HTTP/").next().unwrap_or_default();
+            recorded
+                .lock()
+                .expect("Poisoned")
+                .push(format!("{request} {}", String::from_utf8_lossy(&body)));
+
+            let (status, body) = routes
+                .iter()
+                .find(|(pattern, _)| request_line.contains(pattern))
+                .map_or(
+                    ("404 Not Found", r#"{"message":"Not Found"}"#),
+                    |(_, body)| ("200 OK", body.as_str()),
+                );
+            let response = format!(
+                "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            (&stream).write_all(response.as_bytes()).ok();
+        }
+    });
+    (url, requests)
+}
+
+/// Stack of add-widget → add-gadget, one commit each, with main moved on since
+/// and add-gadget checked out.
+fn setup_json_fixture() -> TempDir {
+    let temp = setup_git_repo();
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    for name in ["widget", "gadget"] {
+        fs::write(temp.path().join(format!("{name}.txt")), name).expect("write");
+        rung()
+            .args(["create", "-m", &format!("Add {name}")])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+    git(&["checkout", "-q", "main"]);
+    fs::write(temp.path().join("main.txt"), "main\n").expect("write");
+    git(&["add", "."]);
+    git(&["commit", "-qm", "Advance main"]);
+    git(&["checkout", "-q", "add-gadget"]);
+    temp
+}
+
+/// Run `rung --json <args>` and parse its output.
+fn json_output(temp: &TempDir, args: &[&str]) -> serde_json::Value {
+    let output = rung()
+        .arg("--json")
+        .args(args)
+        .current_dir(temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .expect("Failed to run rung");
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!(
+            "rung {} printed invalid JSON ({e}): {}{}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+    })
+}
+
+/// Compare JSON output against its snapshot in `tests/snapshots`, with
+/// commit SHAs, timestamps and backup IDs masked. Update snapshots with `cargo insta review`
+/// when a change to the output is intended.
+macro_rules! assert_json_output {
+    ($name:expr, $value:expr) => {
+        insta::with_settings!({
+            filters => vec![
+                (r"\b[0-9a-f]{40}\b", "[sha]"),
+                (r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})", "[timestamp]"),
+                (r#""backup_id": "\d+""#, r#""backup_id": "[id]""#),
+            ],
+        }, {
+            insta::assert_json_snapshot!($name, $value);
+        });
+    };
+}
+
+#[test]
+fn test_json_status() {
+    let temp = setup_json_fixture();
+    assert_json_output!("status", json_output(&temp, &["status"]));
+}
+
+#[test]
+fn test_json_sync() {
+    let temp = setup_json_fixture();
+    assert_json_output!(
+        "sync_dry_run",
+        json_output(&temp, &["sync", "--dry-run", "--base", "main"])
+    );
+    assert_json_output!(
+        "sync",
+        json_output(&temp, &["sync", "--base", "main", "--no-push"])
+    );
+    assert_json_output!(
+        "log_operations",
+        json_output(&temp, &["log", "--operations"])
+    );
+}
+
+#[test]
+fn test_json_submit_and_doctor() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{}\"\n", mock_github()),
+    )
+    .expect("Failed to write config");
+
+    assert_json_output!(
+        "submit_dry_run",
+        json_output(&temp, &["submit", "--dry-run"])
+    );
+    assert_json_output!("doctor", json_output(&temp, &["doctor"]));
+}
+
+/// Run `rung --porcelain <args>` and return its stdout.
+fn porcelain_output(temp: &TempDir, args: &[&str]) -> String {
+    let output = rung()
+        .arg("--porcelain")
+        .args(args)
+        .current_dir(temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .expect("Failed to run rung");
+    String::from_utf8(output.stdout).expect("Porcelain output should be UTF-8")
+}
+
+/// Compare porcelain output against its snapshot in `tests/snapshots`, with
+/// commit SHAs and backup IDs masked. The v1 format must not change, so
+/// snapshots should only ever gain new record types.
+macro_rules! assert_porcelain_output {
+    ($name:expr, $value:expr) => {
+        insta::with_settings!({
+            filters => vec![
+                (r"\b[0-9a-f]{40}\b", "[sha]"),
+                (r"(?m)^backup \d+$", "backup [id]"),
+            ],
+        }, {
+            insta::assert_snapshot!($name, $value);
+        });
+    };
+}
+
+#[test]
+fn test_porcelain() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{}\"\n", mock_github()),
+    )
+    .expect("Failed to write config");
+
+    assert_porcelain_output!("porcelain_status", porcelain_output(&temp, &["status"]));
+    assert_porcelain_output!(
+        "porcelain_submit_dry_run",
+        porcelain_output(&temp, &["submit", "--dry-run"])
+    );
+    assert_porcelain_output!(
+        "porcelain_sync_dry_run",
+        porcelain_output(&temp, &["sync", "--dry-run", "--base", "main"])
+    );
+    assert_porcelain_output!(
+        "porcelain_sync",
+        porcelain_output(&temp, &["sync", "--base", "main", "--no-push"])
+    );
+
+    // Only where a format is defined, and never mixed with JSON
+    rung()
+        .args(["log", "--porcelain"])
+        .current_dir(&temp)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--porcelain is only supported by status",
+        ));
+    rung()
+        .args(["status", "--porcelain", "--json"])
+        .current_dir(&temp)
+        .assert()
+        .code(2);
+}
 
 #[test]
-fn test_doctor_not_initialized() {
-    let temp = setup_git_repo();
+fn test_timings() {
+    let temp = setup_json_fixture();
+    let timings = |args: &[&str]| {
+        let output = rung()
+            .args(["--json", "--timings"])
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run rung");
+        assert!(output.status.success(), "rung {args:?} failed");
+        // The breakdown goes to stderr, leaving the JSON intact
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("Invalid JSON");
+        String::from_utf8_lossy(&output.stderr).to_string()
+    };
+
+    let status = timings(&["status"]);
+    assert!(status.starts_with("Timings:\n"), "{status}");
+    assert!(status.contains("  branch states ×2"), "{status}");
+    assert!(status.contains("  total"), "{status}");
+
+    let sync = timings(&["sync", "--base", "main", "--no-push"]);
+    for phase in ["planning ×2", "rebases", "other", "total"] {
+        assert!(
+            sync.contains(&format!("  {phase} ")),
+            "{phase} missing: {sync}"
+        );
+    }
+    // Nothing asked GitHub anything
+    assert!(!sync.contains("GitHub API"), "{sync}");
 
-    // Doctor on uninitialized repo reports the issue (exits 0 with diagnostic info)
     rung()
-        .arg("doctor")
+        .args(["log", "--timings"])
+        .current_dir(&temp)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--timings is only supported by status, sync and submit",
+        ));
+}
+
+/// A pull request as the GitHub REST API returns it.
+fn api_pr(number: u64, head: &str, base: &str, state: &str, author: &str) -> serde_json::Value {
+    serde_json::json!({
+        "number": number,
+        "title": head,
+        "body": null,
+        "state": if state == "open" { "open" } else { "closed" },
+        "merged": state == "merged",
+        "draft": false,
+        "html_url": format!("https://github.com/acme/widgets/pull/{number}"),
+        "head": { "ref": head, "sha": "0".repeat(40) },
+        "base": { "ref": base, "sha": "0".repeat(40) },
+        "mergeable": null,
+        "mergeable_state": null,
+        "user": { "login": author },
+    })
+}
+
+/// Record PR numbers for the stack's branches, in order, as `rung submit` would.
+fn record_prs(temp: &TempDir, prs: &[u64]) {
+    let stack_path = temp.path().join(".git/rung/stack.json");
+    let mut stack: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stack_path).expect("Failed to read stack"))
+            .expect("Invalid stack.json");
+    for (i, pr) in prs.iter().enumerate() {
+        stack["branches"][i]["pr"] = serde_json::json!(pr);
+    }
+    fs::write(&stack_path, stack.to_string()).expect("Failed to write stack");
+}
+
+#[test]
+fn test_status_fetch_includes_commit_statuses() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+
+    // A legacy status named like a check run is superseded by it
+    let runs = serde_json::json!({ "check_runs": [
+        { "name": "build", "status": "completed", "conclusion": "success", "details_url": null },
+    ] });
+    let statuses = serde_json::json!({ "state": "failure", "statuses": [
+        { "context": "build", "state": "failure", "target_url": null },
+        { "context": "ci/jenkins", "state": "pending", "target_url": null },
+    ] });
+    let api = mock_github_with(vec![
+        ("/check-runs ", runs.to_string()),
+        ("/status ", statuses.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = json_output(&temp, &["status", "--fetch"]);
+    for branch in output["branches"].as_array().expect("No branches") {
+        assert_eq!(branch["ci"], "pending", "{branch}");
+    }
+}
+
+#[test]
+fn test_status_fetch_without_token() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+
+    let runs = serde_json::json!({ "check_runs": [
+        { "name": "build", "status": "completed", "conclusion": "success", "details_url": null },
+    ] });
+    let api = mock_github_with(vec![
+        ("/check-runs ", runs.to_string()),
+        (
+            "/status ",
+            r#"{"state":"success","statuses":[]}"#.to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+    let gh_config = TempDir::new().expect("Failed to create temp dir");
+    let without_token = |json: bool| {
+        let mut command = rung();
+        if json {
+            command.arg("--json");
+        }
+        command
+            .args(["status", "--fetch"])
+            .current_dir(&temp)
+            .env_remove("GITHUB_TOKEN")
+            .env("GH_CONFIG_DIR", gh_config.path())
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .output()
+            .expect("Failed to run rung")
+    };
+
+    // Public data is read without a token, rather than skipped
+    let output = without_token(false);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("No GitHub token - reading without one"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = without_token(true);
+    let output: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    for branch in output["branches"].as_array().expect("No branches") {
+        assert_eq!(branch["ci"], "passing", "{branch}");
+    }
+}
+
+#[test]
+fn test_status_short() {
+    let temp = setup_json_fixture();
+    record_prs(&temp, &[1, 2]);
+    let tip = StdCommand::new("git")
+        .args(["rev-parse", "add-gadget"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    let ci = serde_json::json!({ "branches": { "add-gadget": {
+        "commit": String::from_utf8_lossy(&tip.stdout).trim(),
+        "status": "failing",
+        "checked_at": "2024-01-01T00:00:00Z",
+    } } });
+    fs::write(temp.path().join(".git/rung/ci.json"), ci.to_string()).expect("Failed to write");
+
+    // Printed even with --quiet, so it can feed status bars
+    rung()
+        .args(["--quiet", "status", "--short"])
         .current_dir(&temp)
         .assert()
         .success()
-        .stdout(predicate::str::contains("not initialized"));
+        .stdout("stack: 2 branches, 1 behind, 2 PRs open, CI: 1 failing\n");
 }
 
-// ============================================================================
-// Sync command tests
-// ============================================================================
+#[test]
+fn test_stale_stack_comments() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+
+    // #1's comment predates add-gadget; #2's is current, ticket link and all
+    let comment = |id: u64, body: &str| serde_json::json!([{ "id": id, "body": body }]).to_string();
+    let (api, requests) = mock_github_recording(vec![
+        (
+            "/issues/1/comments ",
+            comment(11, "<!-- rung-stack -->\n* **#1** 👈\n* `main`\n"),
+        ),
+        (
+            "/issues/2/comments ",
+            comment(
+                12,
+                "<!-- rung-stack -->\n* **#2** ([T-1](https://t/1)) 👈\n* **#1**\n* `main`\n",
+            ),
+        ),
+        ("/issues/comments/", r#"{"id":0,"body":null}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = json_output(&temp, &["status", "--fetch"]);
+    let stale: Vec<_> = output["branches"]
+        .as_array()
+        .expect("No branches")
+        .iter()
+        .map(|b| (b["name"].as_str(), b["stale_comment"].as_bool()))
+        .collect();
+    assert_eq!(
+        stale,
+        [(Some("add-widget"), Some(true)), (Some("add-gadget"), None)]
+    );
+
+    // Only the comments are touched: nothing is pushed, no PR is created
+    requests.lock().expect("Poisoned").clear();
+    let output = json_output(&temp, &["submit", "--comments-only"]);
+    assert_eq!(output["comments_updated"], 2);
+    let writes: Vec<_> = requests
+        .lock()
+        .expect("Poisoned")
+        .iter()
+        .filter(|r| !r.starts_with("GET"))
+        .map(|r| r.split(' ').take(2).collect::<Vec<_>>().join(" "))
+        .collect();
+    assert_eq!(
+        writes,
+        [
+            "PATCH /repos/acme/widgets/issues/comments/11",
+            "PATCH /repos/acme/widgets/issues/comments/12",
+        ]
+    );
+}
 
 #[test]
-fn test_sync_dry_run() {
+fn test_submit_comments_for_more_prs_than_run_at_once() {
     let temp = setup_git_repo();
-
     rung().arg("init").current_dir(&temp).assert().success();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    // Ten PRs based on main, more than are reconciled at once
+    let branches: Vec<_> = (1..=10)
+        .map(|pr| {
+            serde_json::json!({
+                "name": format!("feature-{pr}"),
+                "parent": "main",
+                "pr": pr,
+                "created": "2026-01-01T00:00:00Z",
+            })
+        })
+        .collect();
+    fs::write(
+        temp.path().join(".git/rung/stack.json"),
+        serde_json::json!({ "branches": branches }).to_string(),
+    )
+    .expect("Failed to write stack");
 
-    // Create a branch
+    // Listing the comments of #4 and #9 fails
+    let (api, requests) = mock_github_recording(vec![
+        ("/issues/4/comments ", "not json".to_string()),
+        ("/issues/9/comments ", "not json".to_string()),
+        ("GET /repos/acme/widgets/issues/", "[]".to_string()),
+        ("/comments ", r#"{"id":0,"body":null}"#.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    // The first failure in stack order is reported, whichever came back first
     rung()
-        .args(["create", "feature-1"])
+        .args(["submit", "--comments-only"])
         .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains("Failed to list comments on PR #4"));
+
+    // A failure doesn't stop the other PRs from getting their comment
+    let requests = requests.lock().expect("Poisoned").clone();
+    let mut commented: Vec<u64> = requests
+        .iter()
+        .filter_map(|r| {
+            r.strip_prefix("POST /repos/acme/widgets/issues/")?
+                .split_once("/comments ")?
+                .0
+                .parse()
+                .ok()
+        })
+        .collect();
+    commented.sort_unstable();
+    assert_eq!(commented, [1, 2, 3, 5, 6, 7, 8, 10]);
+    // Each comment lists the PR it's on
+    for pr in [1, 10] {
+        let create = format!("POST /repos/acme/widgets/issues/{pr}/comments ");
+        assert!(
+            requests
+                .iter()
+                .any(|r| r.starts_with(&create) && r.contains(&format!("**#{pr}** 👈"))),
+            "{requests:?}"
+        );
+    }
+}
+
+#[test]
+fn test_status_fetch_pending_reviewers() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+
+    // A merged PR waits for nobody, whatever GitHub still lists
+    let mut widget = api_pr(1, "add-widget", "main", "open", "octo");
+    widget["created_at"] = "2020-01-01T00:00:00Z".into();
+    widget["requested_reviewers"] = serde_json::json!([{ "login": "alice" }]);
+    widget["requested_teams"] = serde_json::json!([{ "slug": "core" }]);
+    let mut gadget = api_pr(2, "add-gadget", "add-widget", "merged", "octo");
+    gadget["requested_reviewers"] = serde_json::json!([{ "login": "bob" }]);
+    let api = mock_github_with(vec![
+        ("/pulls/1 ", widget.to_string()),
+        ("/pulls/2 ", gadget.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = json_output(&temp, &["status", "--fetch"]);
+    let branches = output["branches"].as_array().expect("No branches");
+    assert_eq!(
+        branches[0]["waiting_on"],
+        serde_json::json!(["alice", "acme/core"])
+    );
+    assert!(
+        branches[0]["waiting_days"]
+            .as_i64()
+            .expect("No waiting_days")
+            > 365
+    );
+    assert!(branches[1].get("waiting_on").is_none());
 
-    // Dry run should succeed without making changes
-    // Note: --base main is required since there's no origin remote in tests
     rung()
-        .args(["sync", "--dry-run", "--base", "main"])
+        .args(["status", "--fetch"])
         .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("waiting on alice, acme/core for"));
 }
 
 #[test]
-fn test_sync_nothing_to_sync() {
-    let temp = setup_git_repo();
+fn test_required_checks() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+    let head = StdCommand::new("git")
+        .args(["rev-parse", "add-widget"])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to run git");
+    let mut widget = api_pr(1, "add-widget", "main", "open", "octo");
+    widget["head"]["sha"] = String::from_utf8_lossy(&head.stdout).trim().into();
 
-    rung().arg("init").current_dir(&temp).assert().success();
+    // main requires `build`; add-widget isn't protected, so every check counts
+    let protection = serde_json::json!({ "protection": {
+        "required_status_checks": { "contexts": ["build"] },
+    } });
+    let serve = |build: &str| {
+        let runs = serde_json::json!({ "check_runs": [
+            { "name": "build", "status": "completed", "conclusion": build, "details_url": null },
+            { "name": "lint", "status": "completed", "conclusion": "failure", "details_url": null },
+        ] });
+        let api = mock_github_with(vec![
+            ("/repos/acme/widgets ", "{}".to_string()),
+            ("/branches/main ", protection.to_string()),
+            ("/pulls/1 ", widget.to_string()),
+            ("/check-runs ", runs.to_string()),
+            ("/status ", r#"{"statuses":[]}"#.to_string()),
+        ]);
+        fs::write(
+            temp.path().join(".git/rung/config.toml"),
+            format!("[github]\napi_url = \"{api}\"\n"),
+        )
+        .expect("Failed to write config");
+    };
 
-    // Create a branch
+    serve("success");
+    let output = json_output(&temp, &["status", "--fetch"]);
+    let branches = output["branches"].as_array().expect("No branches");
+    assert_eq!(branches[0]["ci"], "passing");
+    assert_eq!(branches[0]["optional_failing"], true);
+    assert_eq!(branches[1]["ci"], "failing");
+    assert!(branches[1].get("optional_failing").is_none());
+
+    serve("failure");
     rung()
-        .args(["create", "feature-1"])
+        .args(["merge", "add-widget", "--yes"])
         .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains(
+            "Required check(s) failing on PR #1: build",
+        ));
+}
+
+#[test]
+fn test_status_remote() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+
+    record_prs(&temp, &[1, 2]);
+
+    // add-widget's PR was retargeted, add-gadget's merged, and a PR stacked
+    // on add-gadget was opened outside rung (alongside someone else's)
+    let widget = api_pr(1, "add-widget", "develop", "open", "octo");
+    let gadget = api_pr(2, "add-gadget", "add-widget", "merged", "octo");
+    let sprocket = api_pr(3, "add-sprocket", "add-gadget", "open", "octo");
+    let theirs = api_pr(4, "their-fix", "add-widget", "open", "someone");
+    let api = mock_github_with(vec![
+        ("/user ", r#"{"login":"octo"}"#.to_string()),
+        (
+            "/pulls?state=open",
+            serde_json::json!([widget, sprocket, theirs]).to_string(),
+        ),
+        ("/pulls/1 ", widget.to_string()),
+        ("/pulls/2 ", gadget.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = json_output(&temp, &["status", "--remote"]);
+    assert_eq!(
+        output["remote"],
+        serde_json::json!([
+            {
+                "kind": "base_mismatch",
+                "branch": "add-widget",
+                "pr": 1,
+                "expected": "main",
+                "actual": "develop",
+            },
+            { "kind": "not_open", "branch": "add-gadget", "pr": 2, "state": "merged" },
+            { "kind": "untracked", "pr": 3, "head": "add-sprocket", "base": "add-gadget" },
+        ])
+    );
 
-    // Sync when already up to date
-    // Note: --base main is required since there's no origin remote in tests
     rung()
-        .args(["sync", "--base", "main"])
+        .args(["status", "--remote"])
         .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
         .assert()
         .success()
-        .stdout(predicate::str::contains("up-to-date"));
+        .stdout(predicate::str::contains(
+            "#3 add-sprocket → add-gadget is open but not in the stack",
+        ))
+        .stdout(predicate::str::contains("add-gadget #2 is merged"));
+}
+
+#[test]
+fn test_adopt_author() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+
+    // mona continued the stack, e.g. after it was handed over
+    let prs = serde_json::json!([
+        api_pr(1, "add-widget", "main", "open", "octo"),
+        api_pr(2, "add-gadget", "add-widget", "open", "octo"),
+        api_pr(3, "add-sprocket", "add-gadget", "open", "mona"),
+    ]);
+    let api = mock_github_with(vec![
+        ("/user ", r#"{"login":"octo"}"#.to_string()),
+        ("/pulls?state=open", prs.to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = json_output(&temp, &["adopt", "--dry-run"]);
+    assert_eq!(output["adopted"], serde_json::json!([]));
+    let output = json_output(&temp, &["adopt", "--dry-run", "--author", "@mona"]);
+    assert_eq!(
+        output["adopted"],
+        serde_json::json!([
+            { "branch": "add-sprocket", "parent": "add-gadget", "pr": 3, "fetched": true },
+        ])
+    );
+}
+
+#[test]
+fn test_adopt() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    // origin looks like GitHub, but git itself (not libgit2, which rung
+    // reads the URL with) is pointed at a bare repository
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+    let adopt = |args: &[&str]| {
+        let output = rung()
+            .args(["--json", "adopt"])
+            .args(args)
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .output()
+            .expect("Failed to run rung");
+        assert!(
+            output.status.success(),
+            "rung adopt failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("Invalid JSON")
+    };
+
+    // A stack continued on GitHub: add-sprocket (only on origin) and
+    // add-cog (also local), both opened as PRs outside rung
+    for name in ["sprocket", "cog"] {
+        git(temp.path(), &["checkout", "-qb", &format!("add-{name}")]);
+        fs::write(temp.path().join(format!("{name}.txt")), name).expect("write");
+        git(temp.path(), &["add", "."]);
+        git(temp.path(), &["commit", "-qm", &format!("Add {name}")]);
+        git(
+            temp.path(),
+            &["push", "-q", "origin", &format!("add-{name}")],
+        );
+    }
+    git(temp.path(), &["checkout", "-q", "add-gadget"]);
+    git(temp.path(), &["branch", "-D", "add-sprocket"]);
+
+    record_prs(&temp, &[1, 2]);
+
+    let prs = serde_json::json!([
+        api_pr(1, "add-widget", "main", "open", "octo"),
+        api_pr(2, "add-gadget", "add-widget", "open", "octo"),
+        api_pr(3, "add-sprocket", "add-gadget", "open", "octo"),
+        api_pr(4, "add-cog", "add-sprocket", "open", "octo"),
+    ]);
+    let api = mock_github_with(vec![
+        ("/user ", r#"{"login":"octo"}"#.to_string()),
+        ("/pulls?state=open", prs.to_string()),
+        ("/pulls/1 ", prs[0].to_string()),
+        ("/pulls/2 ", prs[1].to_string()),
+        ("/pulls/3 ", prs[2].to_string()),
+        ("/pulls/4 ", prs[3].to_string()),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let expected = serde_json::json!([
+        { "branch": "add-sprocket", "parent": "add-gadget", "pr": 3, "fetched": true },
+        { "branch": "add-cog", "parent": "add-sprocket", "pr": 4, "fetched": false },
+    ]);
+    assert_eq!(adopt(&["--dry-run"])["adopted"], expected);
+    assert!(
+        !fs::read_to_string(temp.path().join(".git/rung/stack.json"))
+            .expect("Failed to read stack")
+            .contains("add-sprocket")
+    );
+
+    assert_eq!(adopt(&[])["adopted"], expected);
+    assert!(temp.path().join(".git/refs/heads/add-sprocket").exists());
+
+    let output = json_output(&temp, &["status", "--remote"]);
+    assert_eq!(output["remote"], serde_json::json!([]));
+    let branches: Vec<(&str, &str)> = output["branches"]
+        .as_array()
+        .expect("branches")
+        .iter()
+        .map(|b| {
+            (
+                b["name"].as_str().expect("name"),
+                b["parent"].as_str().expect("parent"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        branches[2..],
+        [("add-sprocket", "add-gadget"), ("add-cog", "add-sprocket")]
+    );
+}
+
+#[test]
+fn test_rebase_onto_pr() {
+    let temp = setup_json_fixture();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let run = |args: &[&str]| {
+        let output = rung()
+            .arg("--json")
+            .args(args)
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .output()
+            .expect("Failed to run rung");
+        assert!(output.status.success(), "rung {args:?} failed: {output:?}");
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("Invalid JSON")
+    };
+    StdCommand::new("git")
+        .args(["init", "-q", "--bare"])
+        .current_dir(&origin)
+        .output()
+        .expect("Failed to init origin");
+    git(&["remote", "add", "origin", url]);
+    git(&["push", "-q", "origin", "main"]);
+
+    // Someone else's PR #7, opened from their-feature
+    let their_commit = |message: &str| {
+        git(&["checkout", "-q", "--detach", "main"]);
+        fs::write(temp.path().join("theirs.txt"), message).expect("write");
+        git(&["add", "."]);
+        git(&["commit", "-qm", message]);
+        git(&["push", "-qf", "origin", "HEAD:refs/pull/7/head"]);
+        git(&["checkout", "-q", "add-widget"]);
+    };
+    their_commit("Their feature");
+    let api = mock_github_with(vec![(
+        "/pulls/7 ",
+        api_pr(7, "their-feature", "main", "open", "mona").to_string(),
+    )]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = run(&["rebase", "--onto-pr", "7"]);
+    assert_eq!(output["parent"], "their-feature");
+    assert_eq!(
+        output["rebased"],
+        serde_json::json!(["add-widget", "add-gadget"])
+    );
+    let stack: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(temp.path().join(".git/rung/stack.json")).expect("read stack"),
+    )
+    .expect("Invalid stack.json");
+    assert_eq!(stack["branches"][1]["parent"], "their-feature");
+    let frozen = &stack["branches"][0];
+    assert_eq!(frozen["name"], "their-feature");
+    assert_eq!(
+        (&frozen["parent"], &frozen["pr"], &frozen["frozen"]),
+        (&"main".into(), &7.into(), &true.into())
+    );
+    // Only the stack's own commits were moved
+    assert_eq!(
+        git(&["rev-list", "--count", "their-feature..add-gadget"]),
+        "2"
+    );
+
+    // Sync follows the PR as it changes, and never pushes it
+    their_commit("Their feature, revised");
+    run(&["sync", "--base", "main"]);
+    assert_eq!(
+        git(&["rev-parse", "their-feature"]),
+        git(&["ls-remote", "origin", "refs/pull/7/head"])[..40]
+    );
+    assert_eq!(
+        git(&["rev-list", "--count", "their-feature..add-gadget"]),
+        "2"
+    );
+    assert_eq!(
+        git(&["ls-remote", "origin", "refs/heads/their-feature"]),
+        ""
+    );
+    assert_ne!(git(&["ls-remote", "origin", "refs/heads/add-gadget"]), "");
+}
+
+#[test]
+fn test_sync_from_webhook() {
+    let temp = setup_git_repo();
+    let origin = TempDir::new().expect("Failed to create temp dir");
+    let url = "https://github.com/acme/widgets.git";
+    let redirect = [
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("url.{}.insteadOf", origin.path().display()),
+        ),
+        ("GIT_CONFIG_VALUE_0", url.to_string()),
+    ];
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let commit = |file: &str, content: &str| {
+        fs::write(temp.path().join(file), content).expect("write");
+        git(temp.path(), &["add", "."]);
+        git(temp.path(), &["commit", "-qm", file]);
+    };
+    git(origin.path(), &["init", "-q", "--bare"]);
+    git(temp.path(), &["branch", "-M", "main"]);
+    git(temp.path(), &["remote", "add", "origin", url]);
+
+    // a and b stacked on main, c on main but at odds with the next push
+    for (branch, from, file) in [
+        ("a", "main", "a.txt"),
+        ("b", "a", "b.txt"),
+        ("c", "main", "README.md"),
+    ] {
+        git(temp.path(), &["checkout", "-qb", branch, from]);
+        commit(file, branch);
+    }
+    git(temp.path(), &["checkout", "-q", "main"]);
+    commit("README.md", "# Moved on\n");
+    git(
+        temp.path(),
+        &["push", "-q", "origin", "main", "a", "b", "c"],
+    );
+
+    let prs = serde_json::json!([
+        api_pr(1, "a", "main", "open", "octo"),
+        api_pr(2, "b", "a", "open", "octo"),
+        api_pr(3, "c", "main", "open", "mona"),
+    ]);
+    let api = mock_github_with(vec![("/pulls?state=open", prs.to_string())]);
+    fs::create_dir_all(temp.path().join(".git/rung")).expect("Failed to create dir");
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+    let event = temp.path().join(".git/event.json");
+    fs::write(&event, r#"{"ref":"refs/heads/main"}"#).expect("Failed to write event");
+
+    let restack = |args: &[&str]| {
+        let output = rung()
+            .args(["--json", "sync", "--from-webhook"])
+            .args(args)
+            .current_dir(&temp)
+            .env("GITHUB_TOKEN", "test-token")
+            .env("GITHUB_EVENT_PATH", &event)
+            .envs(redirect.iter().map(|(k, v)| (k, v)))
+            .output()
+            .expect("Failed to run rung");
+        assert_eq!(
+            output.status.code(),
+            Some(3),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("Invalid JSON")
+    };
+    let prs_of = |output: &serde_json::Value, key: &str| -> Vec<u64> {
+        output[key]
+            .as_array()
+            .expect(key)
+            .iter()
+            .map(|b| b["pr"].as_u64().expect("pr"))
+            .collect()
+    };
+
+    let output = restack(&["--base", "main"]);
+    assert_eq!(prs_of(&output, "rebased"), [1, 2]);
+    assert_eq!(
+        output["conflicts"],
+        serde_json::json!([{ "branch": "c", "pr": 3, "files": ["README.md"] }])
+    );
+    for branch in ["a", "b"] {
+        git(
+            origin.path(),
+            &["merge-base", "--is-ancestor", "main", branch],
+        );
+    }
+    assert_eq!(git(temp.path(), &["branch", "--show-current"]), "main");
+    assert_eq!(
+        git(temp.path(), &["branch", "--list", "rung-webhook/*"]),
+        ""
+    );
+
+    // The base comes from the push event; a and b are current now
+    let output = restack(&[]);
+    assert_eq!(output["base"], "main");
+    assert_eq!(prs_of(&output, "up_to_date"), [1, 2]);
+    assert_eq!(prs_of(&output, "conflicts"), [3]);
 }
 
 // ============================================================================
-// Undo command tests
+// Automation guarantees (README "Automation and CI")
 // ============================================================================
 
 #[test]
-fn test_undo_no_backup() {
-    let temp = setup_git_repo();
+fn test_non_interactive_never_colors() {
+    let temp = setup_json_fixture();
+    let status = |args: &[&str], env: &[(&str, &str)]| {
+        let output = rung()
+            .args(args)
+            .arg("status")
+            .current_dir(&temp)
+            .env("CLICOLOR_FORCE", "1")
+            .env_remove("CI")
+            .envs(env.iter().copied())
+            .output()
+            .expect("Failed to run rung");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("utf-8")
+    };
 
-    rung().arg("init").current_dir(&temp).assert().success();
+    assert!(status(&[], &[]).contains('\x1b'), "colors not forced");
+    assert!(!status(&["--non-interactive"], &[]).contains('\x1b'));
+    assert!(!status(&[], &[("CI", "true")]).contains('\x1b'));
+    assert!(status(&[], &[("CI", "false")]).contains('\x1b'));
+}
 
-    // Undo with no sync to undo
+#[test]
+fn test_exit_code_needs_input() {
+    let temp = setup_json_fixture();
+    // Prompts fail even when a terminal might be present
     rung()
-        .arg("undo")
+        .args(["--non-interactive", "move"])
         .current_dir(&temp)
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("backup").or(predicate::str::contains("nothing to undo")));
+        .code(4)
+        .stderr(predicate::str::contains("pass --branch <name>"));
+    rung()
+        .args(["move", "--branch", "add-widget"])
+        .current_dir(&temp)
+        .assert()
+        .code(0);
+    rung()
+        .args(["status", "--no-such-flag"])
+        .current_dir(&temp)
+        .assert()
+        .code(2);
+    rung()
+        .args(["create", "add-widget"])
+        .current_dir(&temp)
+        .assert()
+        .code(1);
 }
 
-// ============================================================================
-// Log command tests
-// ============================================================================
-
 #[test]
-fn test_log() {
+fn test_exit_code_sync_conflict() {
     let temp = setup_git_repo();
-
+    let git = |args: &[&str]| {
+        StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+    };
     rung().arg("init").current_dir(&temp).assert().success();
-
-    // Create first branch
+    fs::write(temp.path().join("README.md"), "# Branch\n").expect("write");
     rung()
-        .args(["create", "feature"])
+        .args(["create", "-m", "Edit readme"])
         .current_dir(&temp)
         .assert()
         .success();
+    git(&["checkout", "-q", "main"]);
+    fs::write(temp.path().join("README.md"), "# Main\n").expect("write");
+    git(&["commit", "-qam", "Edit readme on main"]);
+    git(&["checkout", "-q", "edit-readme"]);
 
-    // Make a commit on feature
-    let file = temp.path().join("feature1.txt");
-    fs::write(&file, "feature 1 content").expect("Failed to write file");
-
-    StdCommand::new("git")
-        .args(["add", "."])
+    let output = rung()
+        .args([
+            "--json",
+            "--non-interactive",
+            "sync",
+            "--base",
+            "main",
+            "--no-push",
+        ])
         .current_dir(&temp)
         .output()
-        .expect("Failed to git add");
+        .expect("Failed to run rung");
+    assert_eq!(output.status.code(), Some(3));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    assert_eq!(json["status"], "conflict");
+    assert_eq!(json["conflict_branch"], "edit-readme");
 
-    StdCommand::new("git")
-        .args(["commit", "-m", "Add feature"])
+    rung()
+        .args(["sync", "--abort", "--yes"])
         .current_dir(&temp)
-        .output()
-        .expect("Failed to commit");
+        .assert()
+        .code(0);
+}
 
+// ============================================================================
+// Tutorial command tests
+// ============================================================================
+
+#[test]
+fn test_tutorial() {
+    // Runs straight through without a terminal, outside any repository
+    let temp = TempDir::new().expect("Failed to create temp dir");
     rung()
-        .arg("log")
+        .arg("tutorial")
         .current_dir(&temp)
         .assert()
         .success()
-        .stdout(predicates::str::contains("Add feature"));
+        .stdout(predicate::str::contains(
+            "$ rung create -m \"Add farewell\"",
+        ))
+        .stdout(predicate::str::contains("Rebased add-greeting"))
+        .stdout(predicate::str::contains("Tutorial complete"))
+        .stderr(predicate::str::contains("Could not fetch").not());
 }
 
 // ============================================================================
@@ -579,3 +8264,185 @@ fn test_invalid_subcommand() {
         .failure()
         .stderr(predicate::str::contains("invalid"));
 }
+
+#[test]
+fn test_checks_timing() {
+    let temp = setup_json_fixture();
+    StdCommand::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/acme/widgets.git",
+        ])
+        .current_dir(&temp)
+        .output()
+        .expect("Failed to add remote");
+    record_prs(&temp, &[1, 2]);
+
+    let runs = serde_json::json!({ "check_runs": [
+        { "name": "lint", "status": "completed", "conclusion": "success", "details_url": null,
+          "started_at": "2026-01-05T10:00:00Z", "completed_at": "2026-01-05T10:00:40Z" },
+        { "name": "test", "status": "completed", "conclusion": "failure", "details_url": null,
+          "started_at": "2026-01-05T10:00:00Z", "completed_at": "2026-01-05T10:05:00Z" },
+        { "name": "deploy", "status": "in_progress", "conclusion": null, "details_url": null,
+          "started_at": "2026-01-05T10:05:00Z", "completed_at": null },
+    ] });
+    let api = mock_github_with(vec![
+        ("/check-runs ", runs.to_string()),
+        (
+            "/status ",
+            r#"{"state":"success","statuses":[]}"#.to_string(),
+        ),
+    ]);
+    fs::write(
+        temp.path().join(".git/rung/config.toml"),
+        format!("[github]\napi_url = \"{api}\"\n"),
+    )
+    .expect("Failed to write config");
+
+    let output = json_output(&temp, &["checks"]);
+    let branches = output["branches"].as_array().expect("No branches");
+    assert_eq!(branches.len(), 2);
+    assert_eq!(branches[0]["branch"], "add-widget");
+    let checks = &branches[0]["checks"];
+    assert_eq!(checks[1]["name"], "test");
+    assert_eq!(checks[1]["status"], "failing");
+    assert_eq!(checks[1]["duration_secs"], 300);
+    assert_eq!(checks[2]["status"], "pending");
+    assert!(checks[2].get("duration_secs").is_none(), "{checks}");
+
+    // Running checks aren't timed yet
+    let output = json_output(&temp, &["checks", "--slowest"]);
+    let slowest = output["slowest"].as_array().expect("No slowest");
+    assert_eq!(slowest.len(), 2);
+    assert_eq!(slowest[0]["name"], "test");
+    assert_eq!(slowest[0]["longest_secs"], 300);
+    assert_eq!(slowest[0]["runs"], 2);
+    assert_eq!(slowest[1]["name"], "lint");
+
+    rung()
+        .args(["checks", "--slowest"])
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "test    5m 0s on add-widget, average 5m 0s over 2 run(s)",
+        ));
+    rung()
+        .arg("checks")
+        .current_dir(&temp)
+        .env("GITHUB_TOKEN", "test-token")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("running for"));
+}
+
+#[test]
+fn test_blame_marks_stack_branches() {
+    let temp = setup_git_repo();
+    let src = temp.path().join("src");
+    fs::create_dir(&src).expect("mkdir");
+    fs::write(src.join("lib.txt"), "trunk\n").expect("write");
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(&temp)
+        .output()
+        .expect("git add");
+    StdCommand::new("git")
+        .args(["commit", "-qm", "Add lib"])
+        .current_dir(&temp)
+        .output()
+        .expect("git commit");
+
+    rung().arg("init").current_dir(&temp).assert().success();
+    for (name, content) in [
+        ("widget", "trunk\nwidget\n"),
+        ("gadget", "trunk\nwidget\ngadget\n"),
+    ] {
+        fs::write(src.join("lib.txt"), content).expect("write");
+        rung()
+            .args(["create", "-m", &format!("Add {name}")])
+            .current_dir(&temp)
+            .assert()
+            .success();
+    }
+    record_prs(&temp, &[7]);
+
+    // Paths are relative to the current directory
+    rung()
+        .args(["blame", "lib.txt"])
+        .current_dir(&src)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"(?m)^ {13} [0-9a-f]{7} 1 │ trunk$").expect("regex"))
+        .stdout(
+            predicate::str::is_match(r"(?m)^add-widget #7 [0-9a-f]{7} 2 │ widget$").expect("regex"),
+        )
+        .stdout(
+            predicate::str::is_match(r"(?m)^add-gadget    [0-9a-f]{7} 3 │ gadget$").expect("regex"),
+        );
+
+    let output = json_output(&temp, &["blame", "src/lib.txt"]);
+    assert_eq!(output["file"], "src/lib.txt");
+    let lines = output["lines"].as_array().expect("No lines");
+    assert!(lines[0].get("branch").is_none());
+    assert_eq!(lines[1]["branch"], "add-widget");
+    assert_eq!(lines[1]["pr"], 7);
+    assert_eq!(lines[2]["branch"], "add-gadget");
+    assert!(lines[2].get("pr").is_none());
+}
+
+#[test]
+fn test_commit_index_follows_rebases() {
+    let temp = setup_json_fixture();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&temp)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let index = || -> serde_json::Value {
+        serde_json::from_str(
+            &fs::read_to_string(temp.path().join(".git/rung/commits.json")).expect("read index"),
+        )
+        .expect("parse index")
+    };
+    let before = git(&["rev-parse", "add-widget"]);
+
+    let output = json_output(&temp, &["contains", "Add widget"]);
+    assert_eq!(output["branch"]["name"], "add-widget");
+    assert_eq!(
+        index()["branches"]["add-widget"]["commits"],
+        serde_json::json!([before])
+    );
+
+    // Sync rebases both branches onto the new main and reindexes them
+    rung()
+        .args(["sync", "--no-push", "--base", "main"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let after = git(&["rev-parse", "add-widget"]);
+    assert_ne!(after, before);
+    assert_eq!(
+        index()["branches"]["add-widget"]["commits"],
+        serde_json::json!([after])
+    );
+    let output = json_output(&temp, &["contains", &after]);
+    assert_eq!(output["branch"]["name"], "add-widget");
+
+    // A branch moved outside rung is walked again on the next lookup
+    git(&["commit", "-q", "--amend", "-m", "Add gadget, amended"]);
+    let amended = git(&["rev-parse", "HEAD"]);
+    let output = json_output(&temp, &["blame", "gadget.txt"]);
+    assert_eq!(output["lines"][0]["branch"], "add-gadget");
+    assert_eq!(output["lines"][0]["commit"], amended.as_str());
+    assert_eq!(
+        index()["branches"]["add-gadget"]["commits"],
+        serde_json::json!([amended])
+    );
+}
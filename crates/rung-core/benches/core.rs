@@ -0,0 +1,165 @@
+//! Benchmarks for sync planning, status computation, stack walks and stack
+//! serialization.
+//!
+//! Stacks are synthetic: a linear chain of branches, each a few commits on top
+//! of its parent, over a trunk with a long history. Trunk has moved on by one
+//! commit, so every branch needs a rebase. Run with `cargo bench -p rung-core`.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rung_core::stack::StackBranch;
+use rung_core::{Stack, sync};
+use tempfile::TempDir;
+
+/// Stack depths to measure.
+const SIZES: [usize; 3] = [10, 50, 200];
+/// Commits on trunk below the stack.
+const TRUNK_DEPTH: usize = 1_000;
+/// Commits on each branch.
+const BRANCH_DEPTH: usize = 5;
+
+/// A repository holding a synthetic stack of `size` branches.
+struct Fixture {
+    _temp: TempDir,
+    repo: rung_git::Repository,
+    stack: Stack,
+}
+
+impl Fixture {
+    fn new(size: usize) -> Self {
+        let temp = TempDir::new().unwrap();
+        let git = git2::Repository::init(temp.path()).unwrap();
+        let sig = git2::Signature::now("Bench", "bench@example.com").unwrap();
+        let tree_id = git.treebuilder(None).unwrap().write().unwrap();
+        let tree = git.find_tree(tree_id).unwrap();
+
+        // Commits share the empty tree; only the history shape matters here
+        let extend = |parent: Option<git2::Oid>, count: usize, label: &str| {
+            let mut tip = parent;
+            for i in 0..count {
+                let parents: Vec<git2::Commit<'_>> = tip
+                    .map(|oid| git.find_commit(oid).unwrap())
+                    .into_iter()
+                    .collect();
+                let parents: Vec<&git2::Commit<'_>> = parents.iter().collect();
+                let message = format!("{label} {i}");
+                tip = Some(
+                    git.commit(None, &sig, &sig, &message, &tree, &parents)
+                        .unwrap(),
+                );
+            }
+            tip.unwrap()
+        };
+
+        let base = extend(None, TRUNK_DEPTH, "trunk");
+        let mut stack = Stack::new();
+        let mut parent = ("main".to_string(), base);
+        for i in 0..size {
+            let name = format!("feature-{i}");
+            let tip = extend(Some(parent.1), BRANCH_DEPTH, &name);
+            git.branch(&name, &git.find_commit(tip).unwrap(), false)
+                .unwrap();
+            stack.add_branch(StackBranch::try_new(&name, Some(parent.0.as_str())).unwrap());
+            parent = (name, tip);
+        }
+        let trunk = extend(Some(base), 1, "trunk moved");
+        git.branch("main", &git.find_commit(trunk).unwrap(), true)
+            .unwrap();
+
+        let repo = rung_git::Repository::open(temp.path()).unwrap();
+        Self {
+            _temp: temp,
+            repo,
+            stack,
+        }
+    }
+}
+
+fn bench_sync_plan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("create_sync_plan");
+    group.sample_size(10);
+    for size in SIZES {
+        let fixture = Fixture::new(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &fixture, |b, f| {
+            b.iter(|| sync::create_sync_plan(&f.repo, black_box(&f.stack), "main").unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn bench_branch_states(c: &mut Criterion) {
+    let mut group = c.benchmark_group("branch_states");
+    group.sample_size(10);
+    for size in SIZES {
+        let fixture = Fixture::new(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &fixture, |b, f| {
+            b.iter(|| {
+                for branch in &f.stack.branches {
+                    black_box(sync::branch_state(&f.repo, &f.stack, branch).unwrap());
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+/// A linear stack of `size` branches with PRs, without a repository.
+fn chain_stack(size: usize) -> Stack {
+    let mut stack = Stack::new();
+    for i in 0..size {
+        let parent = if i == 0 {
+            "main".to_string()
+        } else {
+            format!("feature-{}", i - 1)
+        };
+        let mut branch = StackBranch::try_new(format!("feature-{i}"), Some(parent)).unwrap();
+        branch.pr = Some(1_000 + i as u64);
+        stack.add_branch(branch);
+    }
+    stack
+}
+
+/// The chain every PR's stack comment lists, as `rung submit` walks it.
+fn bench_stack_chains(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stack_chains");
+    for size in SIZES {
+        let stack = chain_stack(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &stack, |b, s| {
+            b.iter(|| {
+                let index = s.index();
+                for branch in &s.branches {
+                    black_box(index.chain_through(&branch.name));
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_stack_serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stack_serialization");
+    for size in SIZES {
+        let stack = chain_stack(size);
+        let json = serde_json::to_string_pretty(&stack).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("serialize", size), &stack, |b, s| {
+            b.iter(|| serde_json::to_string_pretty(black_box(s)).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("deserialize", size), &json, |b, j| {
+            b.iter(|| serde_json::from_str::<Stack>(black_box(j)).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_sync_plan,
+    bench_branch_states,
+    bench_stack_chains,
+    bench_stack_serialization
+);
+criterion_main!(benches);
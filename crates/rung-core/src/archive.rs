@@ -0,0 +1,93 @@
+//! Archived stacks parked outside of `stack.json`.
+//!
+//! Archiving records everything needed to bring a stack back later (branch
+//! relationships, PR numbers, and commit SHAs) so the branches can be removed
+//! from the active stack without losing track of them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::stack::StackBranch;
+
+/// A stack that has been parked with `rung archive`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackArchive {
+    /// Archive name (defaults to the stack's root branch).
+    pub name: String,
+
+    /// When the stack was archived.
+    pub archived_at: DateTime<Utc>,
+
+    /// Archived branches in stack order (parents before children).
+    pub branches: Vec<ArchivedBranch>,
+}
+
+/// A single branch recorded in an archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedBranch {
+    /// The stack entry as it was when archived.
+    #[serde(flatten)]
+    pub branch: StackBranch,
+
+    /// Tip commit SHA of the branch.
+    pub commit: String,
+
+    /// Merge-base SHA with the parent branch, if it could be determined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+}
+
+impl StackArchive {
+    /// Create a new archive from the given branches.
+    #[must_use]
+    pub fn new(name: impl Into<String>, branches: Vec<ArchivedBranch>) -> Self {
+        Self {
+            name: name.into(),
+            archived_at: Utc::now(),
+            branches,
+        }
+    }
+
+    /// Names of all archived branches.
+    #[must_use]
+    pub fn branch_names(&self) -> Vec<&str> {
+        self.branches
+            .iter()
+            .map(|b| b.branch.name.as_str())
+            .collect()
+    }
+
+    /// Number of branches that had an open PR when archived.
+    #[must_use]
+    pub fn pr_count(&self) -> usize {
+        self.branches
+            .iter()
+            .filter(|b| b.branch.pr.is_some())
+            .count()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let branch = StackBranch::try_new("feature/x", Some("main")).unwrap();
+        let archive = StackArchive::new(
+            "feature/x",
+            vec![ArchivedBranch {
+                branch,
+                commit: "abc123".into(),
+                base: None,
+            }],
+        );
+
+        let json = serde_json::to_string(&archive).unwrap();
+        let loaded: StackArchive = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.branch_names(), vec!["feature/x"]);
+        assert_eq!(loaded.branches[0].commit, "abc123");
+        assert_eq!(loaded.pr_count(), 0);
+    }
+}
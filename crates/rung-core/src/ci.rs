@@ -0,0 +1,349 @@
+//! Cached CI results for stack branches.
+//!
+//! Fetching check runs needs a GitHub round trip per branch, which is too
+//! slow for prompts like `rung move`. Results fetched by `rung status --fetch`
+//! are kept in `.git/rung/ci.json`, keyed by the commit they were reported
+//! for, so a branch that has moved on since shows no marker rather than a
+//! stale one.
+//!
+//! Where branch protection requires particular checks, the result covers
+//! only those, and a failing optional check is noted separately.
+//!
+//! Check durations across the stack are summarised for `rung checks
+//! --slowest`.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Overall CI result for a commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStatus {
+    /// Some checks are still queued or running.
+    Pending,
+    /// All checks passed (or were skipped).
+    Passing,
+    /// At least one check failed.
+    Failing,
+}
+
+impl CiStatus {
+    /// Combine individual check results: any failure wins, then anything
+    /// still running. Returns `None` when there are no checks.
+    #[must_use]
+    pub fn combine(checks: impl IntoIterator<Item = Self>) -> Option<Self> {
+        checks.into_iter().max_by_key(|status| match status {
+            Self::Passing => 0,
+            Self::Pending => 1,
+            Self::Failing => 2,
+        })
+    }
+}
+
+/// CI result for a commit, judged by the checks branch protection requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CiSummary {
+    /// Combined result of the required checks, or of every check when none
+    /// are required.
+    pub status: CiStatus,
+
+    /// Whether a check outside the required set failed.
+    pub optional_failing: bool,
+}
+
+impl CiSummary {
+    /// Summarise named check results against the names of the `required`
+    /// checks. A required check that hasn't reported yet counts as pending.
+    /// Returns `None` when there is nothing to summarise.
+    #[must_use]
+    pub fn from_checks<'a>(
+        checks: impl IntoIterator<Item = (&'a str, CiStatus)>,
+        required: &[String],
+    ) -> Option<Self> {
+        let checks: Vec<(&str, CiStatus)> = checks.into_iter().collect();
+        if required.is_empty() {
+            let status = CiStatus::combine(checks.iter().map(|&(_, status)| status))?;
+            return Some(Self {
+                status,
+                optional_failing: false,
+            });
+        }
+
+        let status = CiStatus::combine(required.iter().map(|name| {
+            checks
+                .iter()
+                .find(|(check, _)| check == name)
+                .map_or(CiStatus::Pending, |&(_, status)| status)
+        }))?;
+        let optional_failing = checks.iter().any(|(check, status)| {
+            *status == CiStatus::Failing && !required.iter().any(|name| name == check)
+        });
+        Some(Self {
+            status,
+            optional_failing,
+        })
+    }
+}
+
+/// How long one completed check took on one branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckTiming {
+    /// Branch the check ran for.
+    pub branch: String,
+    /// Check name.
+    pub check: String,
+    /// Seconds from start to completion.
+    pub secs: i64,
+}
+
+/// One check's durations across the stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlowCheck {
+    /// Check name.
+    pub name: String,
+    /// Its longest run, in seconds.
+    pub longest_secs: i64,
+    /// Branch of the longest run.
+    pub longest_branch: String,
+    /// Mean run, in seconds.
+    pub average_secs: i64,
+    /// Runs timed.
+    pub runs: usize,
+}
+
+/// Group `timings` by check, longest run first, so the checks that hold
+/// the stack up the most come first.
+#[must_use]
+pub fn slowest(timings: &[CheckTiming]) -> Vec<SlowCheck> {
+    let mut by_check: BTreeMap<&str, Vec<&CheckTiming>> = BTreeMap::new();
+    for timing in timings {
+        by_check.entry(&timing.check).or_default().push(timing);
+    }
+    let mut checks: Vec<SlowCheck> = by_check
+        .into_iter()
+        .filter_map(|(name, runs)| {
+            // The last maximum wins, so reversed the earliest branch does
+            let longest = runs.iter().rev().max_by_key(|timing| timing.secs)?;
+            let total: i64 = runs.iter().map(|timing| timing.secs).sum();
+            Some(SlowCheck {
+                name: name.to_string(),
+                longest_secs: longest.secs,
+                longest_branch: longest.branch.clone(),
+                average_secs: total / i64::try_from(runs.len()).unwrap_or(i64::MAX),
+                runs: runs.len(),
+            })
+        })
+        .collect();
+    checks.sort_by_key(|check| std::cmp::Reverse(check.longest_secs));
+    checks
+}
+
+/// A cached CI result for one branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CiEntry {
+    /// Commit SHA the checks ran against.
+    pub commit: String,
+
+    /// Combined result of the checks.
+    pub status: CiStatus,
+
+    /// Whether a check branch protection doesn't require failed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional_failing: bool,
+
+    /// When the result was fetched.
+    pub checked_at: DateTime<Utc>,
+}
+
+/// CI results by branch name, stored in `.git/rung/ci.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CiCache {
+    /// Entries keyed by local branch name.
+    #[serde(default)]
+    pub branches: BTreeMap<String, CiEntry>,
+}
+
+impl CiCache {
+    /// Record the result for a branch at `commit`.
+    pub fn record(
+        &mut self,
+        branch: impl Into<String>,
+        commit: impl Into<String>,
+        status: CiStatus,
+    ) {
+        self.record_summary(
+            branch,
+            commit,
+            CiSummary {
+                status,
+                optional_failing: false,
+            },
+        );
+    }
+
+    /// Record the result for a branch at `commit`, including whether an
+    /// optional check failed.
+    pub fn record_summary(
+        &mut self,
+        branch: impl Into<String>,
+        commit: impl Into<String>,
+        summary: CiSummary,
+    ) {
+        self.branches.insert(
+            branch.into(),
+            CiEntry {
+                commit: commit.into(),
+                status: summary.status,
+                optional_failing: summary.optional_failing,
+                checked_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Cached entry for `branch`, if it was recorded for `commit`.
+    #[must_use]
+    pub fn entry(&self, branch: &str, commit: &str) -> Option<&CiEntry> {
+        self.branches
+            .get(branch)
+            .filter(|entry| entry.commit == commit)
+    }
+
+    /// Cached status for `branch`, if it was recorded for `commit`.
+    #[must_use]
+    pub fn status(&self, branch: &str, commit: &str) -> Option<CiStatus> {
+        self.entry(branch, commit).map(|entry| entry.status)
+    }
+
+    /// Drop entries for branches not in `keep`.
+    pub fn retain_branches(&mut self, keep: &[&str]) {
+        self.branches
+            .retain(|name, _| keep.contains(&name.as_str()));
+    }
+
+    /// Drop entries fetched before `cutoff`. Returns whether any were dropped.
+    pub fn expire_before(&mut self, cutoff: DateTime<Utc>) -> bool {
+        let before = self.branches.len();
+        self.branches.retain(|_, entry| entry.checked_at >= cutoff);
+        self.branches.len() != before
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine() {
+        use CiStatus::{Failing, Passing, Pending};
+
+        assert_eq!(CiStatus::combine([]), None);
+        assert_eq!(CiStatus::combine([Passing, Passing]), Some(Passing));
+        assert_eq!(CiStatus::combine([Passing, Pending]), Some(Pending));
+        assert_eq!(
+            CiStatus::combine([Pending, Failing, Passing]),
+            Some(Failing)
+        );
+    }
+
+    #[test]
+    fn test_summary_from_required_checks() {
+        use CiStatus::{Failing, Passing, Pending};
+
+        // Nothing required: every check counts
+        let summary = CiSummary::from_checks([("build", Passing), ("lint", Failing)], &[]);
+        assert_eq!(
+            summary,
+            Some(CiSummary {
+                status: Failing,
+                optional_failing: false
+            })
+        );
+        assert_eq!(CiSummary::from_checks([], &[]), None);
+
+        // Only required checks decide; an optional failure is noted
+        let required = vec!["build".to_string()];
+        let summary = CiSummary::from_checks([("build", Passing), ("lint", Failing)], &required);
+        assert_eq!(
+            summary,
+            Some(CiSummary {
+                status: Passing,
+                optional_failing: true
+            })
+        );
+
+        // A required check that hasn't reported is pending
+        let summary = CiSummary::from_checks([("lint", Passing)], &required).unwrap();
+        assert_eq!(summary.status, Pending);
+        assert!(!summary.optional_failing);
+    }
+
+    #[test]
+    fn test_slowest() {
+        let timing = |branch: &str, check: &str, secs| CheckTiming {
+            branch: branch.to_string(),
+            check: check.to_string(),
+            secs,
+        };
+        let report = slowest(&[
+            timing("a", "lint", 30),
+            timing("a", "test", 300),
+            timing("b", "test", 500),
+            timing("b", "lint", 40),
+        ]);
+
+        assert_eq!(
+            report,
+            [
+                SlowCheck {
+                    name: "test".to_string(),
+                    longest_secs: 500,
+                    longest_branch: "b".to_string(),
+                    average_secs: 400,
+                    runs: 2,
+                },
+                SlowCheck {
+                    name: "lint".to_string(),
+                    longest_secs: 40,
+                    longest_branch: "b".to_string(),
+                    average_secs: 35,
+                    runs: 2,
+                },
+            ]
+        );
+        assert!(slowest(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_status_requires_matching_commit() {
+        let mut cache = CiCache::default();
+        cache.record("feature", "abc123", CiStatus::Passing);
+
+        assert_eq!(cache.status("feature", "abc123"), Some(CiStatus::Passing));
+        assert_eq!(cache.status("feature", "def456"), None);
+        assert_eq!(cache.status("other", "abc123"), None);
+
+        cache.record("other", "def456", CiStatus::Failing);
+        cache.retain_branches(&["other"]);
+        assert!(cache.status("feature", "abc123").is_none());
+
+        let json = serde_json::to_string(&cache).unwrap();
+        let parsed: CiCache = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.status("other", "def456"), Some(CiStatus::Failing));
+    }
+
+    #[test]
+    fn test_expire_before() {
+        let mut cache = CiCache::default();
+        cache.record("old", "abc123", CiStatus::Passing);
+        cache.branches.get_mut("old").unwrap().checked_at = Utc::now() - chrono::Duration::hours(2);
+        cache.record("new", "def456", CiStatus::Pending);
+
+        assert!(cache.expire_before(Utc::now() - chrono::Duration::hours(1)));
+        assert!(cache.status("old", "abc123").is_none());
+        assert_eq!(cache.status("new", "def456"), Some(CiStatus::Pending));
+        assert!(!cache.expire_before(Utc::now() - chrono::Duration::hours(1)));
+    }
+}
@@ -0,0 +1,175 @@
+//! Which commits belong to which stack branch.
+//!
+//! Mapping a commit to the branch that introduced it means walking every
+//! branch's own commits, which adds up in deep stacks. The walks are kept in
+//! `.git/rung/commits.json`, keyed by the refs they were computed from (the
+//! branch tip, its parent's tip and the base recorded for the branch), so a
+//! branch that was rebased, amended or reset is walked again and the others
+//! aren't. `rung sync` refreshes the index once it's done rebasing.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stack::{Stack, StackBranch};
+
+/// One branch's own commits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Ref state the commits were found from (see [`CommitIndex::key`]).
+    pub key: String,
+
+    /// The branch's own commits, oldest first.
+    pub commits: Vec<String>,
+}
+
+/// Own commits by branch name, stored in `.git/rung/commits.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitIndex {
+    /// Entries keyed by local branch name.
+    #[serde(default)]
+    pub branches: BTreeMap<String, IndexEntry>,
+}
+
+impl CommitIndex {
+    /// The ref state a branch's own commits depend on, or `None` if the
+    /// branch doesn't exist locally. A parent outside the stack is read from
+    /// origin where fetched, as the branch's base is.
+    #[must_use]
+    pub fn key(repo: &rung_git::Repository, stack: &Stack, branch: &StackBranch) -> Option<String> {
+        let tip = repo.branch_commit(&branch.name).ok()?;
+        let parent_tip = branch.parent.as_deref().and_then(|parent| {
+            if stack.find_branch(parent).is_some() {
+                repo.branch_commit(parent).ok()
+            } else {
+                repo.remote_branch_commit(parent)
+                    .or_else(|_| repo.branch_commit(parent))
+                    .ok()
+            }
+        });
+        Some(format!(
+            "{tip}:{}:{}",
+            parent_tip.map(|oid| oid.to_string()).unwrap_or_default(),
+            branch.based_on.as_deref().unwrap_or("")
+        ))
+    }
+
+    /// `branch`'s own commits, if they were found for `key`.
+    #[must_use]
+    pub fn get(&self, branch: &str, key: &str) -> Option<&[String]> {
+        self.branches
+            .get(branch)
+            .filter(|entry| entry.key == key)
+            .map(|entry| entry.commits.as_slice())
+    }
+
+    /// Record `branch`'s own commits for `key`. Returns whether the index
+    /// changed.
+    pub fn insert(&mut self, branch: impl Into<String>, key: String, commits: Vec<String>) -> bool {
+        let branch = branch.into();
+        let entry = IndexEntry { key, commits };
+        if self.branches.get(&branch) == Some(&entry) {
+            return false;
+        }
+        self.branches.insert(branch, entry);
+        true
+    }
+
+    /// Drop entries for branches not in `keep`. Returns whether any were
+    /// dropped.
+    pub fn retain_branches(&mut self, keep: &[&str]) -> bool {
+        let before = self.branches.len();
+        self.branches
+            .retain(|name, _| keep.contains(&name.as_str()));
+        self.branches.len() != before
+    }
+
+    /// The branch with `commit` among its own commits.
+    #[must_use]
+    pub fn branch_of(&self, commit: &str) -> Option<&str> {
+        self.branches
+            .iter()
+            .find(|(_, entry)| entry.commits.iter().any(|c| c == commit))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Every indexed commit, by branch in name order.
+    pub fn commits(&self) -> impl Iterator<Item = &str> {
+        self.branches
+            .values()
+            .flat_map(|entry| entry.commits.iter().map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_requires_matching_key() {
+        let mut index = CommitIndex::default();
+        assert!(index.insert("feature", "a:b:".into(), vec!["c1".into(), "c2".into()]));
+        assert!(!index.insert("feature", "a:b:".into(), vec!["c1".into(), "c2".into()]));
+        assert_eq!(
+            index.get("feature", "a:b:"),
+            Some(["c1".to_string(), "c2".to_string()].as_slice())
+        );
+        // Moved since, say by a rebase
+        assert_eq!(index.get("feature", "x:b:"), None);
+        assert_eq!(index.get("other", "a:b:"), None);
+
+        assert!(index.insert("feature", "x:b:".into(), vec!["c3".into()]));
+        assert!(index.insert("other", "d:x:".into(), vec!["c4".into()]));
+        assert_eq!(index.branch_of("c3"), Some("feature"));
+        assert_eq!(index.branch_of("c1"), None);
+        assert_eq!(index.commits().collect::<Vec<_>>(), ["c3", "c4"]);
+
+        assert!(index.retain_branches(&["other"]));
+        assert!(!index.retain_branches(&["other"]));
+        assert_eq!(index.branch_of("c3"), None);
+    }
+
+    #[test]
+    fn test_key_follows_refs() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "Initial"]);
+        git(&["checkout", "-q", "-b", "a"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "On a"]);
+        git(&["checkout", "-q", "-b", "b"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "On b"]);
+        let repo = rung_git::Repository::open(temp.path()).unwrap();
+
+        let mut stack = Stack::default();
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        let key = |name: &str| CommitIndex::key(&repo, &stack, stack.find_branch(name).unwrap());
+        let (a, b) = (key("a").unwrap(), key("b").unwrap());
+
+        // Amending `a` moves both its key and its child's
+        git(&["checkout", "-q", "a"]);
+        git(&[
+            "commit",
+            "-q",
+            "--amend",
+            "--allow-empty",
+            "-m",
+            "On a, amended",
+        ]);
+        assert_ne!(key("a").unwrap(), a);
+        assert_ne!(key("b").unwrap(), b);
+
+        git(&["branch", "-q", "-D", "b"]);
+        assert_eq!(key("b"), None);
+    }
+}
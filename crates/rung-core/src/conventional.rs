@@ -0,0 +1,155 @@
+//! Conventional commit parsing.
+//!
+//! Recognizes subjects of the form `type(scope)!: description` as described by
+//! <https://www.conventionalcommits.org> and maps them to PR labels.
+
+use crate::config::LabelConfig;
+
+/// A parsed conventional commit header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalCommit {
+    /// Commit type, lowercased (e.g. `feat`, `fix`).
+    pub kind: String,
+
+    /// Optional scope from `type(scope):`.
+    pub scope: Option<String>,
+
+    /// Whether the commit is marked as a breaking change.
+    pub breaking: bool,
+
+    /// Description after the colon.
+    pub description: String,
+}
+
+impl ConventionalCommit {
+    /// Parse a full commit message.
+    ///
+    /// Returns `None` if the subject line isn't a conventional commit header.
+    #[must_use]
+    pub fn parse(message: &str) -> Option<Self> {
+        let mut lines = message.lines();
+        let subject = lines.next()?.trim();
+
+        let (header, description) = subject.split_once(':')?;
+        let description = description.trim();
+        if description.is_empty() {
+            return None;
+        }
+
+        let (header, bang) = header
+            .strip_suffix('!')
+            .map_or((header, false), |h| (h, true));
+
+        let (kind, scope) = match header.split_once('(') {
+            Some((kind, rest)) => {
+                let scope = rest.strip_suffix(')')?.trim();
+                if scope.is_empty() {
+                    return None;
+                }
+                (kind, Some(scope.to_string()))
+            }
+            None => (header, None),
+        };
+
+        if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        let footer_breaking = lines.any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+
+        Some(Self {
+            kind: kind.to_ascii_lowercase(),
+            scope,
+            breaking: bang || footer_breaking,
+            description: description.to_string(),
+        })
+    }
+}
+
+/// Collect the labels implied by a set of commit messages.
+///
+/// Labels are de-duplicated and returned in first-seen order. Messages that
+/// aren't conventional commits are ignored.
+#[must_use]
+pub fn labels_for<'a>(
+    messages: impl IntoIterator<Item = &'a str>,
+    config: &LabelConfig,
+) -> Vec<String> {
+    if !config.conventional {
+        return vec![];
+    }
+
+    let mut labels: Vec<String> = Vec::new();
+    let mut push = |label: &String| {
+        if !labels.contains(label) {
+            labels.push(label.clone());
+        }
+    };
+
+    for commit in messages.into_iter().filter_map(ConventionalCommit::parse) {
+        if let Some(label) = config.types.get(&commit.kind) {
+            push(label);
+        }
+        if let Some(label) = commit.scope.as_ref().and_then(|s| config.scopes.get(s)) {
+            push(label);
+        }
+        if commit.breaking {
+            if let Some(label) = &config.breaking {
+                push(label);
+            }
+        }
+    }
+
+    labels
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conventional_commit() {
+        let commit = ConventionalCommit::parse("feat(api): add login endpoint").unwrap();
+        assert_eq!(commit.kind, "feat");
+        assert_eq!(commit.scope.as_deref(), Some("api"));
+        assert!(!commit.breaking);
+        assert_eq!(commit.description, "add login endpoint");
+
+        assert!(ConventionalCommit::parse("Fix!: drop v1").unwrap().breaking);
+        assert!(
+            ConventionalCommit::parse("refactor: x\n\nBREAKING CHANGE: removed y")
+                .unwrap()
+                .breaking
+        );
+
+        assert!(ConventionalCommit::parse("Add login endpoint").is_none());
+        assert!(ConventionalCommit::parse("feat():missing scope").is_none());
+        assert!(ConventionalCommit::parse("see http://example.com").is_none());
+        assert!(ConventionalCommit::parse("feat:").is_none());
+    }
+
+    #[test]
+    fn test_labels_for() {
+        let mut config = LabelConfig::default();
+        config.scopes.insert("api".into(), "area: api".into());
+        config.breaking = Some("breaking".into());
+
+        let labels = labels_for(
+            [
+                "feat(api): one",
+                "fix: two",
+                "feat!: three",
+                "chore: four",
+                "plain",
+            ],
+            &config,
+        );
+        assert_eq!(labels, vec!["enhancement", "area: api", "bug", "breaking"]);
+
+        config.conventional = false;
+        assert!(labels_for(["feat: one"], &config).is_empty());
+    }
+}
@@ -53,10 +53,38 @@ pub enum Error {
     #[error("no backup found - nothing to undo")]
     NoBackupFound,
 
+    /// Archive not found.
+    #[error("no archived stack named '{0}'")]
+    ArchiveNotFound(String),
+
+    /// Snapshot not found.
+    #[error("no snapshot named '{0}'")]
+    SnapshotNotFound(String),
+
+    /// Snapshot already exists.
+    #[error("a snapshot named '{0}' already exists")]
+    SnapshotExists(String),
+
+    /// No stack branch exists locally to snapshot.
+    #[error("no stack branches to snapshot")]
+    NothingToSnapshot,
+
+    /// Stack export written with a newer schema.
+    #[error("stack export version {0} is newer than this rung reads - upgrade rung to import it")]
+    UnsupportedExport(u32),
+
     /// Sync already in progress.
     #[error("sync already in progress - run `rung sync --continue` or `rung sync --abort`")]
     SyncInProgress,
 
+    /// Invalid configuration value.
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
+    /// The stack file exists but can't be parsed (e.g. truncated by a crash).
+    #[error("stack file {} is corrupt ({message}) - run `rung doctor --fix` to restore the previous version", .file.display())]
+    CorruptStack { file: PathBuf, message: String },
+
     /// State file parsing error.
     #[error("failed to parse {file}: {message}")]
     StateParseError { file: PathBuf, message: String },
@@ -75,5 +103,18 @@ pub enum Error {
 
     /// Git operation error.
     #[error("git error: {0}")]
-    Git(#[from] rung_git::Error),
+    Git(rung_git::Error),
+}
+
+impl From<rung_git::Error> for Error {
+    /// Keeps a rejected [`crate::BranchName`] an `InvalidBranchName`, since
+    /// the type now lives in rung-git.
+    fn from(e: rung_git::Error) -> Self {
+        match e {
+            rung_git::Error::InvalidBranchName { name, reason } => {
+                Self::InvalidBranchName { name, reason }
+            }
+            e => Self::Git(e),
+        }
+    }
 }
@@ -0,0 +1,154 @@
+//! Stack definitions exported by `rung stack export`, to be imported in
+//! another clone or on another machine.
+//!
+//! Unlike `stack.json`, an export records the commits each branch is at, so
+//! the import can check they exist before taking it on. Only what it takes to
+//! rebuild the stack is kept: notes, scopes and the like stay behind.
+//!
+//! The schema is stable: fields are only ever added, and `version` changes if
+//! one is removed or changes meaning.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::BranchName;
+use crate::stack::StackBranch;
+
+/// Current schema version of an export.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// A stack definition written by `rung stack export`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackExport {
+    /// Schema version ([`EXPORT_VERSION`]).
+    pub version: u32,
+
+    /// When the stack was exported.
+    pub exported_at: DateTime<Utc>,
+
+    /// Exported branches in stack order (parents before children).
+    pub branches: Vec<ExportedBranch>,
+}
+
+/// A single branch recorded in an export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedBranch {
+    /// Branch name.
+    pub name: BranchName,
+
+    /// Parent branch name (None for branches based on the trunk).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<BranchName>,
+
+    /// Associated PR number, if submitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr: Option<u64>,
+
+    /// Name of the branch on the remote, when it differs from the local name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<BranchName>,
+
+    /// Tip commit SHA of the branch.
+    pub commit: String,
+
+    /// Merge-base SHA with the parent branch, if it could be determined.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+}
+
+impl StackExport {
+    /// Create a new export from the given branches.
+    #[must_use]
+    pub fn new(branches: Vec<ExportedBranch>) -> Self {
+        Self {
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            branches,
+        }
+    }
+
+    /// Parse an export, refusing one written with a newer schema.
+    ///
+    /// # Errors
+    /// Returns error if `json` isn't an export this version of rung reads.
+    pub fn parse(json: &str) -> crate::Result<Self> {
+        let export: Self = serde_json::from_str(json)?;
+        if export.version > EXPORT_VERSION {
+            return Err(crate::Error::UnsupportedExport(export.version));
+        }
+        Ok(export)
+    }
+
+    /// Names of all exported branches.
+    #[must_use]
+    pub fn branch_names(&self) -> Vec<&str> {
+        self.branches.iter().map(|b| b.name.as_str()).collect()
+    }
+}
+
+impl ExportedBranch {
+    /// Record `branch` at `commit`, based on `base`.
+    #[must_use]
+    pub fn new(branch: &StackBranch, commit: String, base: Option<String>) -> Self {
+        Self {
+            name: branch.name.clone(),
+            parent: branch.parent.clone(),
+            pr: branch.pr,
+            remote: branch.remote.clone(),
+            commit,
+            base,
+        }
+    }
+
+    /// The stack entry to track the branch with, based on the recorded
+    /// merge-base so a sync can tell if the parent was rewritten since.
+    #[must_use]
+    pub fn to_stack_branch(&self) -> StackBranch {
+        let mut branch = StackBranch::new(self.name.clone(), self.parent.clone());
+        branch.pr = self.pr;
+        branch.remote.clone_from(&self.remote);
+        branch.based_on.clone_from(&self.base);
+        branch
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_roundtrip() {
+        let mut branch = StackBranch::try_new("feature/x", Some("main")).unwrap();
+        branch.pr = Some(7);
+        branch.notes.push("not exported".into());
+        let export = StackExport::new(vec![ExportedBranch::new(
+            &branch,
+            "abc123".into(),
+            Some("def456".into()),
+        )]);
+
+        let json = serde_json::to_string(&export).unwrap();
+        assert!(!json.contains("not exported"));
+        let loaded = StackExport::parse(&json).unwrap();
+        assert_eq!(loaded.branch_names(), vec!["feature/x"]);
+        let restored = loaded.branches[0].to_stack_branch();
+        assert_eq!(
+            restored.parent.as_ref().map(BranchName::as_str),
+            Some("main")
+        );
+        assert_eq!(restored.pr, Some(7));
+        assert_eq!(restored.based_on.as_deref(), Some("def456"));
+        assert!(restored.notes.is_empty());
+    }
+
+    #[test]
+    fn test_export_rejects_newer_version_and_bad_names() {
+        let newer = r#"{"version": 2, "exported_at": "2026-01-01T00:00:00Z", "branches": []}"#;
+        assert!(StackExport::parse(newer).is_err());
+
+        let bad_name = r#"{"version": 1, "exported_at": "2026-01-01T00:00:00Z",
+            "branches": [{"name": "bad..name", "commit": "abc123"}]}"#;
+        assert!(StackExport::parse(bad_name).is_err());
+    }
+}
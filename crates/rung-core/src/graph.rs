@@ -0,0 +1,234 @@
+//! The stack as a graph, for `rung graph`.
+//!
+//! Renders the stack's branches, the trunks they start from and each PR's
+//! number and state as Mermaid (for Markdown on GitHub) or Graphviz DOT.
+//! Parent links are solid edges; `requires` links are dashed.
+
+use std::fmt::Write as _;
+
+use crate::remote_status::RemotePrState;
+use crate::stack::{Stack, StackBranch};
+
+/// A node of the graph: a trunk, or a stack branch and its PR's state.
+struct Node<'a> {
+    name: &'a str,
+    branch: Option<&'a StackBranch>,
+    state: Option<RemotePrState>,
+}
+
+impl Node<'_> {
+    /// The PR line under the branch name, such as `#12 open`.
+    fn pr_line(&self) -> Option<String> {
+        let pr = self.branch?.pr?;
+        Some(self.state.map_or_else(
+            || format!("#{pr}"),
+            |state| format!("#{pr} {}", state_name(state)),
+        ))
+    }
+}
+
+/// The stack's nodes, trunks first, and its edges as node indices:
+/// parent links, then `requires` links.
+struct Graph<'a> {
+    nodes: Vec<Node<'a>>,
+    parents: Vec<(usize, usize)>,
+    requires: Vec<(usize, usize)>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(stack: &'a Stack, state_of: impl Fn(u64) -> Option<RemotePrState>) -> Self {
+        let tree = stack.tree();
+        let mut nodes: Vec<Node<'a>> = vec![];
+        for parent in tree.iter().filter_map(|(_, b)| b.parent.as_deref()) {
+            if stack.find_branch(parent).is_none() && !nodes.iter().any(|n| n.name == parent) {
+                nodes.push(Node {
+                    name: parent,
+                    branch: None,
+                    state: None,
+                });
+            }
+        }
+        nodes.extend(tree.iter().map(|(_, branch)| Node {
+            name: &branch.name,
+            branch: Some(branch),
+            state: branch.pr.and_then(&state_of),
+        }));
+
+        let position = |name: &str| nodes.iter().position(|n| n.name == name);
+        let mut parents = vec![];
+        let mut requires = vec![];
+        for (_, branch) in &tree {
+            let Some(child) = position(&branch.name) else {
+                continue;
+            };
+            if let Some(parent) = branch.parent.as_deref().and_then(position) {
+                parents.push((parent, child));
+            }
+            requires.extend(
+                branch
+                    .requires
+                    .iter()
+                    .filter_map(|r| position(r))
+                    .map(|required| (required, child)),
+            );
+        }
+        Self {
+            nodes,
+            parents,
+            requires,
+        }
+    }
+}
+
+/// Render the stack as a Mermaid flowchart, with PR states as classes.
+/// `state_of` gives the last-known state of a PR.
+#[must_use]
+pub fn mermaid(stack: &Stack, state_of: impl Fn(u64) -> Option<RemotePrState>) -> String {
+    let graph = Graph::new(stack, state_of);
+    let mut out = String::from("flowchart TD\n");
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let name = node.name.replace('"', "#quot;");
+        let label = match node.pr_line() {
+            Some(pr) => format!("{name}<br/>{pr}"),
+            None => name,
+        };
+        if node.branch.is_some() {
+            let _ = writeln!(out, "    n{i}[\"{label}\"]");
+        } else {
+            let _ = writeln!(out, "    n{i}([\"{label}\"])");
+        }
+    }
+    for (from, to) in &graph.parents {
+        let _ = writeln!(out, "    n{from} --> n{to}");
+    }
+    for (from, to) in &graph.requires {
+        let _ = writeln!(out, "    n{from} -.-> n{to}");
+    }
+    for state in [
+        RemotePrState::Open,
+        RemotePrState::Merged,
+        RemotePrState::Closed,
+    ] {
+        let members: Vec<_> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.state == Some(state))
+            .map(|(i, _)| format!("n{i}"))
+            .collect();
+        if !members.is_empty() {
+            let name = state_name(state);
+            let _ = writeln!(out, "    classDef {name} stroke:{}", color(state));
+            let _ = writeln!(out, "    class {} {name}", members.join(","));
+        }
+    }
+    out
+}
+
+/// Render the stack as a Graphviz digraph, with PR states as outline colors.
+/// `state_of` gives the last-known state of a PR.
+#[must_use]
+pub fn dot(stack: &Stack, state_of: impl Fn(u64) -> Option<RemotePrState>) -> String {
+    let graph = Graph::new(stack, state_of);
+    let mut out = String::from("digraph stack {\n    node [shape=box];\n");
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let name = node.name.replace('\\', "\\\\").replace('"', "\\\"");
+        let label = match node.pr_line() {
+            Some(pr) => format!("{name}\\n{pr}"),
+            None => name,
+        };
+        let mut attrs = format!("label=\"{label}\"");
+        if node.branch.is_none() {
+            attrs.push_str(", shape=ellipse");
+        }
+        if let Some(state) = node.state {
+            let _ = write!(attrs, ", color=\"{}\"", color(state));
+        }
+        let _ = writeln!(out, "    n{i} [{attrs}];");
+    }
+    for (from, to) in &graph.parents {
+        let _ = writeln!(out, "    n{from} -> n{to};");
+    }
+    for (from, to) in &graph.requires {
+        let _ = writeln!(out, "    n{from} -> n{to} [style=dashed];");
+    }
+    out.push_str("}\n");
+    out
+}
+
+const fn state_name(state: RemotePrState) -> &'static str {
+    match state {
+        RemotePrState::Open => "open",
+        RemotePrState::Closed => "closed",
+        RemotePrState::Merged => "merged",
+    }
+}
+
+/// GitHub's color for a PR state.
+const fn color(state: RemotePrState) -> &'static str {
+    match state {
+        RemotePrState::Open => "#1a7f37",
+        RemotePrState::Closed => "#cf222e",
+        RemotePrState::Merged => "#8250df",
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::BranchName;
+
+    fn stack() -> Stack {
+        let mut stack = Stack::default();
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("main")).unwrap());
+        stack.branches[0].pr = Some(1);
+        stack.branches[1].pr = Some(2);
+        stack.branches[1].requires = vec![BranchName::new("c").unwrap()];
+        stack
+    }
+
+    fn state_of(pr: u64) -> Option<RemotePrState> {
+        (pr == 1).then_some(RemotePrState::Merged)
+    }
+
+    #[test]
+    fn test_mermaid() {
+        assert_eq!(
+            mermaid(&stack(), state_of),
+            "flowchart TD
+    n0([\"main\"])
+    n1[\"a<br/>#1 merged\"]
+    n2[\"b<br/>#2\"]
+    n3[\"c\"]
+    n0 --> n1
+    n1 --> n2
+    n0 --> n3
+    n3 -.-> n2
+    classDef merged stroke:#8250df
+    class n1 merged
+"
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        assert_eq!(
+            dot(&stack(), state_of),
+            "digraph stack {
+    node [shape=box];
+    n0 [label=\"main\", shape=ellipse];
+    n1 [label=\"a\\n#1 merged\", color=\"#8250df\"];
+    n2 [label=\"b\\n#2\"];
+    n3 [label=\"c\"];
+    n0 -> n1;
+    n1 -> n2;
+    n0 -> n3;
+    n3 -> n2 [style=dashed];
+}
+"
+        );
+    }
+}
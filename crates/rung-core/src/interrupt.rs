@@ -0,0 +1,20 @@
+//! Cooperative interruption of long-running operations.
+//!
+//! The CLI owns the actual signal handler and calls [`request`] on Ctrl-C.
+//! Engines such as [`crate::sync`] check [`is_requested`] between steps and
+//! stop at a point that can be resumed, instead of dying mid-rebase.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Ask running operations to stop at the next safe point.
+pub fn request() {
+    REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Check whether an interruption has been requested.
+#[must_use]
+pub fn is_requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
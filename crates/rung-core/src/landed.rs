@@ -0,0 +1,146 @@
+//! Where merged branches landed on trunk.
+//!
+//! A squash or rebase merge lands a branch's changes as new commits, so the
+//! branch's own commits never reach trunk and `git branch --contains` can't
+//! tell they landed. `rung merge` and `rung sync` record each landing in
+//! `.git/rung/landed.json`: the branch's commits as submitted, and the commit
+//! on trunk that landed them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A branch's PR that landed on trunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Landing {
+    /// The merged branch.
+    pub branch: String,
+
+    /// The merged PR.
+    pub pr_number: u64,
+
+    /// Commit the branch was built on.
+    pub base: String,
+
+    /// The branch's tip when it merged.
+    pub tip: String,
+
+    /// The branch's own commits (`base..tip`), newest first.
+    #[serde(default)]
+    pub commits: Vec<String>,
+
+    /// Commit on `into` that landed the PR: the squash commit, the merge
+    /// commit, or the last commit rebased onto it.
+    pub commit: String,
+
+    /// Branch the PR was merged into.
+    pub into: String,
+
+    /// When the landing was recorded.
+    pub landed_at: DateTime<Utc>,
+}
+
+impl Landing {
+    /// Record `branch`'s commits from `base` to `tip` as landed by `commit`.
+    ///
+    /// # Errors
+    /// Returns error if the branch's commits can't be listed.
+    pub fn capture(
+        repo: &rung_git::Repository,
+        branch: &str,
+        pr_number: u64,
+        base: rung_git::Oid,
+        tip: rung_git::Oid,
+        commit: &str,
+        into: &str,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            branch: branch.to_string(),
+            pr_number,
+            base: base.to_string(),
+            tip: tip.to_string(),
+            commits: repo
+                .commits_between(base, tip)?
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            commit: commit.to_string(),
+            into: into.to_string(),
+            landed_at: Utc::now(),
+        })
+    }
+
+    /// Whether `sha` (a full commit id) was one of the branch's commits.
+    #[must_use]
+    pub fn contains(&self, sha: &str) -> bool {
+        self.tip == sha || self.commits.iter().any(|c| c == sha)
+    }
+}
+
+/// Recorded landings, oldest first, stored in `.git/rung/landed.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Landings {
+    /// Every landing recorded.
+    #[serde(default)]
+    pub landings: Vec<Landing>,
+}
+
+impl Landings {
+    /// Add `landing`, replacing any earlier one for the same PR.
+    pub fn record(&mut self, landing: Landing) {
+        self.landings.retain(|l| l.pr_number != landing.pr_number);
+        self.landings.push(landing);
+    }
+
+    /// How PR `number` landed, if recorded.
+    #[must_use]
+    pub fn for_pr(&self, number: u64) -> Option<&Landing> {
+        self.landings.iter().find(|l| l.pr_number == number)
+    }
+
+    /// The landing of the branch that had commit `sha` (a full commit id),
+    /// most recent first.
+    #[must_use]
+    pub fn containing(&self, sha: &str) -> Option<&Landing> {
+        self.landings.iter().rev().find(|l| l.contains(sha))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn landing(pr_number: u64, commits: &[&str], commit: &str) -> Landing {
+        Landing {
+            branch: format!("branch-{pr_number}"),
+            pr_number,
+            base: "base".into(),
+            tip: commits[0].into(),
+            commits: commits.iter().map(ToString::to_string).collect(),
+            commit: commit.into(),
+            into: "main".into(),
+            landed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_landings() {
+        let mut landings = Landings::default();
+        landings.record(landing(1, &["b2", "b1"], "s1"));
+        landings.record(landing(2, &["c1"], "s2"));
+
+        assert_eq!(landings.containing("b1").unwrap().commit, "s1");
+        assert_eq!(landings.containing("c1").unwrap().pr_number, 2);
+        assert!(landings.containing("s1").is_none());
+        assert_eq!(landings.for_pr(2).unwrap().branch, "branch-2");
+
+        // Landing a PR again replaces its entry
+        landings.record(landing(1, &["b3"], "s3"));
+        assert_eq!(landings.landings.len(), 2);
+        assert!(landings.containing("b1").is_none());
+        assert_eq!(landings.for_pr(1).unwrap().commit, "s3");
+
+        let json = serde_json::to_string(&landings).unwrap();
+        assert_eq!(serde_json::from_str::<Landings>(&json).unwrap(), landings);
+    }
+}
@@ -3,15 +3,37 @@
 //! Core library for Rung providing stack management, state persistence,
 //! and the sync engine for dependent PR stacks.
 
-pub mod branch_name;
+pub mod archive;
+pub mod ci;
+pub mod commit_index;
 pub mod config;
+pub mod conventional;
 pub mod error;
+pub mod export;
+pub mod graph;
+pub mod interrupt;
+pub mod landed;
+pub mod operations;
+pub mod policy;
+pub mod pr_body;
+pub mod pr_cache;
+pub mod remote_status;
+pub mod scope;
+pub mod shell;
+pub mod snapshot;
 pub mod stack;
 pub mod state;
+pub mod stats;
+pub mod status_cache;
+pub mod summary;
 pub mod sync;
+pub mod ticket;
+pub mod workspace;
 
-pub use branch_name::{BranchName, slugify};
+pub use archive::StackArchive;
 pub use config::Config;
 pub use error::{Error, Result};
-pub use stack::{BranchState, Stack, StackBranch};
+pub use rung_git::{BranchName, slugify};
+pub use stack::{BranchState, RequirementConflict, Stack, StackBranch, StackDiff};
 pub use state::State;
+pub use workspace::Workspace;
@@ -0,0 +1,242 @@
+//! Audit trail of what rung changed and when.
+//!
+//! Operations are appended to `.git/rung/operations.jsonl`, one JSON object
+//! per line, and shown by `rung log --operations`. The file is append-only
+//! so concurrent or interrupted runs never lose earlier entries. `rung stats`
+//! and `rung summary` derive their reports from the same file.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A recorded operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    /// When the operation started.
+    pub started_at: DateTime<Utc>,
+
+    /// When the operation finished.
+    pub finished_at: DateTime<Utc>,
+
+    /// What was done.
+    #[serde(flatten)]
+    pub kind: OperationKind,
+}
+
+/// The kinds of operation that are recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum OperationKind {
+    /// A PR was merged with `rung merge`.
+    Merge {
+        /// The merged branch.
+        branch: String,
+        /// The merged PR.
+        pr_number: u64,
+        /// Merge commit SHA reported by GitHub.
+        merge_sha: String,
+        /// Merge method (squash, merge, or rebase).
+        method: String,
+        /// Branch the PR was merged into.
+        into: String,
+        /// Children moved onto `into`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reparented: Vec<String>,
+    },
+
+    /// Branches were pushed and PRs opened or updated with `rung submit`.
+    Submit {
+        /// PRs opened by this submit.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        created: Vec<SubmittedPr>,
+        /// Number of existing PRs updated.
+        #[serde(default)]
+        updated: usize,
+        /// Longest parent chain in the stack at the time.
+        stack_depth: usize,
+    },
+
+    /// A `rung sync` ran to completion.
+    Sync {
+        /// Number of branches rebased.
+        rebased: usize,
+        /// Times the sync stopped on a conflict before completing.
+        #[serde(default)]
+        conflicts: usize,
+        /// Where each of those conflicts was, in order. Empty for syncs
+        /// recorded before conflicts were.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        conflicted: Vec<SyncConflict>,
+        /// Longest parent chain in the stack at the time.
+        stack_depth: usize,
+    },
+
+    /// A branch was added to the stack with `rung create`.
+    Create {
+        /// The new branch.
+        branch: String,
+        /// Its parent.
+        parent: String,
+        /// Whether it started a new stack (its parent isn't a stack branch).
+        #[serde(default)]
+        new_stack: bool,
+    },
+}
+
+/// A conflict a sync stopped on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncConflict {
+    /// The branch being rebased.
+    pub branch: String,
+    /// Files with conflicts.
+    pub files: Vec<String>,
+}
+
+/// A PR opened by `rung submit`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmittedPr {
+    /// The submitted branch.
+    pub branch: String,
+    /// The new PR.
+    pub pr_number: u64,
+}
+
+impl Operation {
+    /// Record an operation that started at `started_at` and just finished.
+    #[must_use]
+    pub fn finished(started_at: DateTime<Utc>, kind: OperationKind) -> Self {
+        Self {
+            started_at,
+            finished_at: Utc::now(),
+            kind,
+        }
+    }
+
+    /// One-line description for `rung log --operations`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        match &self.kind {
+            OperationKind::Merge {
+                branch,
+                pr_number,
+                merge_sha,
+                method,
+                into,
+                reparented,
+            } => {
+                let reparented = if reparented.is_empty() {
+                    String::new()
+                } else {
+                    format!("; re-parented {}", reparented.join(", "))
+                };
+                format!(
+                    "merge   PR #{pr_number} ({branch}) into {into} via {method} → {}{reparented}",
+                    &merge_sha[..7.min(merge_sha.len())]
+                )
+            }
+            OperationKind::Submit {
+                created, updated, ..
+            } => {
+                let prs: Vec<String> = created
+                    .iter()
+                    .map(|pr| format!("#{} ({})", pr.pr_number, pr.branch))
+                    .collect();
+                let created = if prs.is_empty() {
+                    "no new PRs".to_string()
+                } else {
+                    format!("opened {}", prs.join(", "))
+                };
+                format!("submit  {created}; {updated} updated")
+            }
+            OperationKind::Sync {
+                rebased, conflicts, ..
+            } => format!("sync    rebased {rebased} branch(es); {conflicts} conflict(s)"),
+            OperationKind::Create {
+                branch,
+                parent,
+                new_stack,
+            } => {
+                let new_stack = if *new_stack { " (new stack)" } else { "" };
+                format!("create  {branch} on {parent}{new_stack}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn merge_op() -> Operation {
+        Operation::finished(
+            Utc::now(),
+            OperationKind::Merge {
+                branch: "feat-a".into(),
+                pr_number: 12,
+                merge_sha: "0123456789abcdef".into(),
+                method: "squash".into(),
+                into: "main".into(),
+                reparented: vec!["feat-b".into()],
+            },
+        )
+    }
+
+    #[test]
+    fn test_serialized_as_tagged_line() {
+        let op = merge_op();
+        let line = serde_json::to_string(&op).unwrap();
+        assert!(line.contains(r#""op":"merge""#));
+        assert!(!line.contains('\n'));
+
+        let parsed: Operation = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.kind, op.kind);
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            merge_op().summary(),
+            "merge   PR #12 (feat-a) into main via squash → 0123456; re-parented feat-b"
+        );
+
+        let submit = Operation::finished(
+            Utc::now(),
+            OperationKind::Submit {
+                created: vec![SubmittedPr {
+                    branch: "feat-c".into(),
+                    pr_number: 13,
+                }],
+                updated: 2,
+                stack_depth: 3,
+            },
+        );
+        assert_eq!(submit.summary(), "submit  opened #13 (feat-c); 2 updated");
+
+        let sync = Operation::finished(
+            Utc::now(),
+            OperationKind::Sync {
+                rebased: 3,
+                conflicts: 1,
+                conflicted: vec![SyncConflict {
+                    branch: "feat-c".into(),
+                    files: vec!["src/lib.rs".into()],
+                }],
+                stack_depth: 3,
+            },
+        );
+        assert_eq!(
+            sync.summary(),
+            "sync    rebased 3 branch(es); 1 conflict(s)"
+        );
+
+        let create = Operation::finished(
+            Utc::now(),
+            OperationKind::Create {
+                branch: "feat-d".into(),
+                parent: "main".into(),
+                new_stack: true,
+            },
+        );
+        assert_eq!(create.summary(), "create  feat-d on main (new stack)");
+    }
+}
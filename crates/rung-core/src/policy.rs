@@ -0,0 +1,315 @@
+//! Organizational rules a branch must follow before it is submitted or merged.
+//!
+//! [`Policy::from_config`] builds the built-in rules from `[policy]`, and
+//! tools embedding rung can add their own [`Rule`]s. Checking a branch yields
+//! [`Violation`]s, which `rung submit` and `rung merge` stop on unless told
+//! to override them.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::config::{ForbiddenPathsRule, PathReviewersRule, PolicyConfig};
+use crate::scope::path_in;
+
+/// What the rules see of one branch.
+#[derive(Debug, Clone, Copy)]
+pub struct BranchChanges<'a> {
+    /// Local branch name.
+    pub branch: &'a str,
+
+    /// Files changed since the branch left its parent.
+    pub files: &'a [String],
+
+    /// Lines changed since the branch left its parent, added plus removed.
+    pub lines: usize,
+
+    /// Ticket named by the branch or its commits.
+    pub ticket: Option<&'a str>,
+
+    /// Users and `org/team`s asked to review the branch's PR, or `None`
+    /// when there is no PR to ask about yet.
+    pub reviewers: Option<&'a [String]>,
+}
+
+/// A rule a branch breaks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// Name of the rule, such as `max-pr-lines`.
+    pub rule: String,
+
+    /// Branch breaking it.
+    pub branch: String,
+
+    /// What is wrong, phrased to follow the branch name.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' {} ({})", self.branch, self.message, self.rule)
+    }
+}
+
+/// One organizational rule.
+pub trait Rule {
+    /// Short kebab-case name reported with violations.
+    fn name(&self) -> &'static str;
+
+    /// How `changes` breaks the rule, one message per problem.
+    fn check(&self, changes: &BranchChanges<'_>) -> Vec<String>;
+}
+
+/// The rules every branch is checked against.
+#[derive(Default)]
+pub struct Policy {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Policy {
+    /// The built-in rules enabled in `config`.
+    #[must_use]
+    pub fn from_config(config: &PolicyConfig) -> Self {
+        let mut policy = Self::default();
+        if config.max_pr_lines > 0 {
+            policy = policy.with_rule(MaxLines(config.max_pr_lines));
+        }
+        if config.require_ticket {
+            policy = policy.with_rule(RequireTicket);
+        }
+        if !config.forbidden_paths.is_empty() {
+            policy = policy.with_rule(ForbiddenPaths(config.forbidden_paths.clone()));
+        }
+        if !config.reviewers.is_empty() {
+            policy = policy.with_rule(RequiredReviewers(config.reviewers.clone()));
+        }
+        policy
+    }
+
+    /// Add a rule.
+    #[must_use]
+    pub fn with_rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Whether there are no rules to check.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Every rule `changes` breaks, in rule order.
+    #[must_use]
+    pub fn check(&self, changes: &BranchChanges<'_>) -> Vec<Violation> {
+        self.rules
+            .iter()
+            .flat_map(|rule| {
+                rule.check(changes).into_iter().map(|message| Violation {
+                    rule: rule.name().to_string(),
+                    branch: changes.branch.to_string(),
+                    message,
+                })
+            })
+            .collect()
+    }
+}
+
+/// `max_pr_lines`: keep PRs small enough to review.
+struct MaxLines(usize);
+
+impl Rule for MaxLines {
+    fn name(&self) -> &'static str {
+        "max-pr-lines"
+    }
+
+    fn check(&self, changes: &BranchChanges<'_>) -> Vec<String> {
+        if changes.lines <= self.0 {
+            return vec![];
+        }
+        vec![format!(
+            "changes {} lines, more than the {} allowed",
+            changes.lines, self.0
+        )]
+    }
+}
+
+/// `require_ticket`: tie every branch to a ticket.
+struct RequireTicket;
+
+impl Rule for RequireTicket {
+    fn name(&self) -> &'static str {
+        "require-ticket"
+    }
+
+    fn check(&self, changes: &BranchChanges<'_>) -> Vec<String> {
+        if changes.ticket.is_some() {
+            return vec![];
+        }
+        vec!["names no ticket in its branch name or commit messages".to_string()]
+    }
+}
+
+/// `forbidden_paths`: keep some branches away from some files.
+struct ForbiddenPaths(Vec<ForbiddenPathsRule>);
+
+impl Rule for ForbiddenPaths {
+    fn name(&self) -> &'static str {
+        "forbidden-path"
+    }
+
+    fn check(&self, changes: &BranchChanges<'_>) -> Vec<String> {
+        let mut messages = vec![];
+        for rule in self.0.iter().filter(|r| r.applies_to(changes.branch)) {
+            for file in changes.files {
+                if rule.paths.iter().any(|path| path_in(file, path)) {
+                    messages.push(format!(
+                        "changes '{file}', which branches matching '{}' may not change",
+                        rule.branches
+                    ));
+                }
+            }
+        }
+        messages
+    }
+}
+
+/// `reviewers`: make sure owners of a path are asked to review changes to it.
+struct RequiredReviewers(Vec<PathReviewersRule>);
+
+impl Rule for RequiredReviewers {
+    fn name(&self) -> &'static str {
+        "required-reviewers"
+    }
+
+    fn check(&self, changes: &BranchChanges<'_>) -> Vec<String> {
+        let Some(asked) = changes.reviewers else {
+            return vec![];
+        };
+        let mut messages = vec![];
+        for rule in self.0.iter().filter(|r| r.applies_to(changes.files)) {
+            for reviewer in &rule.reviewers {
+                if !asked.iter().any(|a| a.eq_ignore_ascii_case(reviewer)) {
+                    messages.push(format!(
+                        "changes '{}' but {reviewer} wasn't asked to review",
+                        rule.path
+                    ));
+                }
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn changes<'a>(branch: &'a str, files: &'a [String]) -> BranchChanges<'a> {
+        BranchChanges {
+            branch,
+            files,
+            lines: 10,
+            ticket: Some("ABC-1"),
+            reviewers: None,
+        }
+    }
+
+    fn policy(toml: &str) -> Policy {
+        let config: crate::Config = toml::from_str(toml).unwrap();
+        Policy::from_config(&config.policy)
+    }
+
+    #[test]
+    fn test_default_policy_is_empty() {
+        assert!(Policy::from_config(&PolicyConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_max_lines_and_ticket() {
+        let policy = policy("[policy]\nmax_pr_lines = 5\nrequire_ticket = true\n");
+        let files = vec!["src/lib.rs".to_string()];
+        let mut branch = changes("feature", &files);
+        branch.ticket = None;
+
+        let rules: Vec<String> = policy.check(&branch).into_iter().map(|v| v.rule).collect();
+        assert_eq!(rules, ["max-pr-lines", "require-ticket"]);
+
+        branch.lines = 5;
+        branch.ticket = Some("ABC-1");
+        assert!(policy.check(&branch).is_empty());
+    }
+
+    #[test]
+    fn test_forbidden_paths_by_branch() {
+        let policy = policy(
+            "[[policy.forbidden_paths]]\nbranches = \"release/*\"\npaths = [\"migrations\"]\n",
+        );
+        let files = vec!["migrations/001.sql".to_string(), "src/lib.rs".to_string()];
+
+        let violations = policy.check(&changes("release/1.0", &files));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "'release/1.0' changes 'migrations/001.sql', which branches matching 'release/*' may not change (forbidden-path)"
+        );
+        assert!(policy.check(&changes("feature", &files)).is_empty());
+    }
+
+    #[test]
+    fn test_required_reviewers() {
+        let policy = policy(
+            "[[policy.reviewers]]\npath = \"billing\"\nreviewers = [\"acme/billing\", \"octocat\"]\n",
+        );
+        let files = vec!["billing/invoice.rs".to_string()];
+        let mut branch = changes("feature", &files);
+        // Without a PR there's nobody to have asked yet
+        assert!(policy.check(&branch).is_empty());
+
+        let asked = vec!["Octocat".to_string()];
+        branch.reviewers = Some(&asked);
+        let violations = policy.check(&branch);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("acme/billing"));
+
+        let other = vec!["docs/readme.md".to_string()];
+        branch.files = &other;
+        assert!(policy.check(&branch).is_empty());
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        struct NoWip;
+        impl Rule for NoWip {
+            fn name(&self) -> &'static str {
+                "no-wip"
+            }
+            fn check(&self, changes: &BranchChanges<'_>) -> Vec<String> {
+                if changes.branch.starts_with("wip") {
+                    vec!["is still a work in progress".to_string()]
+                } else {
+                    vec![]
+                }
+            }
+        }
+
+        let policy = Policy::default().with_rule(NoWip);
+        assert_eq!(policy.check(&changes("wip-login", &[])).len(), 1);
+        assert!(policy.check(&changes("login", &[])).is_empty());
+    }
+
+    #[test]
+    fn test_reviewers_for() {
+        let config: crate::Config = toml::from_str(
+            "[[policy.reviewers]]\npath = \"billing\"\nreviewers = [\"acme/billing\"]\n\
+             [[policy.reviewers]]\npath = \"billing/tax\"\nreviewers = [\"acme/billing\", \"tax-lead\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.policy.reviewers_for(&["billing/tax/vat.rs"]),
+            ["acme/billing", "tax-lead"]
+        );
+        assert!(config.policy.reviewers_for(&["src/lib.rs"]).is_empty());
+    }
+}
@@ -0,0 +1,579 @@
+//! Turning commit message bodies into PR descriptions.
+//!
+//! Commit bodies are usually hard-wrapped at ~72 columns, which reads badly
+//! once rendered on GitHub. Prose paragraphs are re-joined, while lists,
+//! headings, quotes, tables and code blocks are kept as written. Trailers
+//! (`Signed-off-by: ...`) stay in their own block and can be stripped.
+//!
+//! Going the other way, [`squash_commit`] builds the commit a PR is squashed
+//! into from its title and description.
+
+use std::fmt::Write;
+
+use crate::config::PrConfig;
+
+/// Kind of the last line written, deciding whether the next line may join it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Blank,
+    Prose,
+    ListItem,
+    Verbatim,
+    IndentedCode,
+}
+
+/// Format a commit message body (everything after the subject) as a PR body.
+///
+/// The footer from `config` is not added here - see [`append_footer`].
+#[must_use]
+pub fn format_pr_body(body: &str, config: &PrConfig) -> String {
+    let (content, trailers) = split_trailers(body);
+    let trailers: Vec<&str> = trailers
+        .into_iter()
+        .filter(|line| !is_stripped(line, &config.strip_trailers))
+        .collect();
+
+    let mut out = reflow(content);
+    if !trailers.is_empty() {
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(&trailers.join("\n"));
+    }
+    out
+}
+
+/// Append the configured footer, separated from the body by a blank line.
+#[must_use]
+pub fn append_footer(body: &str, config: &PrConfig) -> String {
+    match config.footer.as_deref().map(str::trim) {
+        Some(footer) if !footer.is_empty() && body.is_empty() => footer.to_string(),
+        Some(footer) if !footer.is_empty() => format!("{body}\n\n{footer}"),
+        _ => body.to_string(),
+    }
+}
+
+/// Marks the start of the related-PRs section rung maintains in PR bodies.
+const RELATED_START: &str = "<!-- rung-related -->";
+/// Marks the end of the related-PRs section.
+const RELATED_END: &str = "<!-- /rung-related -->";
+
+/// Replace the related-PRs section at the end of `body` with `related`
+/// (cross-repository references such as `owner/repo#12`), or remove it
+/// when `related` is empty. The rest of the body is left alone.
+#[must_use]
+pub fn set_related_prs(body: &str, related: &[String]) -> String {
+    let mut out = match (body.find(RELATED_START), body.find(RELATED_END)) {
+        (Some(start), Some(end)) if start < end => format!(
+            "{}{}",
+            &body[..start],
+            body[end + RELATED_END.len()..].trim_start()
+        ),
+        _ => body.to_string(),
+    };
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    if related.is_empty() {
+        return out;
+    }
+
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(RELATED_START);
+    out.push_str("\n**Related PRs**\n\n");
+    for pr in related {
+        let _ = writeln!(out, "* {pr}");
+    }
+    out.push_str(RELATED_END);
+    out
+}
+
+/// Marks the start of the "Depends on" line rung maintains in PR bodies.
+const DEPENDS_START: &str = "<!-- rung-depends -->";
+/// Marks the end of the "Depends on" line.
+const DEPENDS_END: &str = "<!-- /rung-depends -->";
+
+/// Put a `Depends on #<parent>` line at the top of `body`, replacing the one
+/// rung wrote before, or remove it when `parent` is `None`.
+#[must_use]
+pub fn set_depends_on(body: &str, parent: Option<u64>) -> String {
+    let rest = match (body.find(DEPENDS_START), body.find(DEPENDS_END)) {
+        (Some(start), Some(end)) if start < end => format!(
+            "{}{}",
+            &body[..start],
+            body[end + DEPENDS_END.len()..].trim_start()
+        ),
+        _ => body.to_string(),
+    };
+    match parent {
+        Some(pr) if rest.is_empty() => format!("{DEPENDS_START}Depends on #{pr}{DEPENDS_END}"),
+        Some(pr) => format!("{DEPENDS_START}Depends on #{pr}{DEPENDS_END}\n\n{rest}"),
+        None => rest,
+    }
+}
+
+/// Marks the start of the hidden notes block rung maintains in PR bodies.
+const NOTES_START: &str = "<!-- rung-notes";
+/// Marks the end of the notes block.
+const NOTES_END: &str = "/rung-notes -->";
+
+/// Replace the hidden block of branch notes in `body` with `notes`, or
+/// remove it when `notes` is empty.
+///
+/// The notes go one per line inside an HTML comment, so they don't render.
+/// An existing block is replaced where it is; a new one goes at the end.
+#[must_use]
+pub fn set_notes(body: &str, notes: &[String]) -> String {
+    let block = (!notes.is_empty()).then(|| {
+        let mut block = format!("{NOTES_START}\n");
+        for note in notes {
+            // `-->` would end the comment early
+            let _ = writeln!(block, "{}", note.replace("-->", "-- >"));
+        }
+        block.push_str(NOTES_END);
+        block
+    });
+    match (body.find(NOTES_START), body.find(NOTES_END)) {
+        (Some(start), Some(end)) if start < end => {
+            let before = &body[..start];
+            let after = &body[end + NOTES_END.len()..];
+            block.map_or_else(
+                || {
+                    format!("{}{}", before.trim_end(), after.trim_end())
+                        .trim_start()
+                        .to_string()
+                },
+                |block| format!("{before}{block}{after}"),
+            )
+        }
+        _ => match block {
+            Some(block) if body.trim().is_empty() => block,
+            Some(block) => format!("{}\n\n{block}", body.trim_end()),
+            None => body.to_string(),
+        },
+    }
+}
+
+/// The notes in `body`'s hidden notes block, as [`set_notes`] wrote them.
+#[must_use]
+pub fn notes(body: &str) -> Vec<String> {
+    match (body.find(NOTES_START), body.find(NOTES_END)) {
+        (Some(start), Some(end)) if start < end => body[start + NOTES_START.len()..end]
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Append each of `links` (markdown for an attached file) that `body`
+/// doesn't contain yet, one per line, so attaching a file again is a no-op.
+#[must_use]
+pub fn add_attachments(body: &str, links: &[String]) -> String {
+    let new: Vec<&str> = links
+        .iter()
+        .map(String::as_str)
+        .filter(|link| !body.contains(link))
+        .collect();
+    match (new.is_empty(), body.trim().is_empty()) {
+        (true, _) => body.to_string(),
+        (false, true) => new.join("\n"),
+        (false, false) => format!("{}\n\n{}", body.trim_end(), new.join("\n")),
+    }
+}
+
+/// Title and message for squash-merging a PR.
+///
+/// Written the way careful users do in the GitHub UI: `<PR title> (#<number>)`,
+/// then the PR body with a `Co-authored-by` trailer for each of `co_authors`
+/// it doesn't mention yet.
+#[must_use]
+pub fn squash_commit(
+    pr_title: &str,
+    pr_number: u64,
+    pr_body: &str,
+    co_authors: &[String],
+) -> (String, String) {
+    let pr_title = pr_title.trim();
+    let suffix = format!("(#{pr_number})");
+    let title = if pr_title.ends_with(&suffix) {
+        pr_title.to_string()
+    } else {
+        format!("{pr_title} {suffix}")
+    };
+
+    let body = pr_body.trim_end();
+    let new_trailers: Vec<String> = co_authors
+        .iter()
+        .filter(|author| {
+            let email = email_of(author);
+            !body
+                .lines()
+                .any(|line| is_co_author(line) && email_of(line).eq_ignore_ascii_case(email))
+        })
+        .map(|author| format!("Co-authored-by: {author}"))
+        .collect();
+    if new_trailers.is_empty() {
+        return (title, body.to_string());
+    }
+
+    let (_, existing) = split_trailers(body);
+    let separator = match (body.is_empty(), existing.is_empty()) {
+        (true, _) => "",
+        (false, true) => "\n\n",
+        (false, false) => "\n",
+    };
+    (
+        title,
+        format!("{body}{separator}{}", new_trailers.join("\n")),
+    )
+}
+
+/// Everyone to credit on a squash commit, given each commit's author and message.
+///
+/// That's the authors plus anyone they already credit with `Co-authored-by`,
+/// in first-seen order and once per email. `merger_email` is left out, since
+/// the squash commit is already attributed to them.
+#[must_use]
+pub fn co_authors<'a>(
+    commits: impl IntoIterator<Item = (&'a str, &'a str)>,
+    merger_email: Option<&str>,
+) -> Vec<String> {
+    let mut seen: Vec<String> = merger_email.map(str::to_lowercase).into_iter().collect();
+    let mut authors = vec![];
+    for (author, message) in commits {
+        let credited = message
+            .lines()
+            .filter(|line| is_co_author(line))
+            .filter_map(|line| line.split_once(':').map(|(_, value)| value.trim()));
+        for person in std::iter::once(author.trim()).chain(credited) {
+            let email = email_of(person).to_lowercase();
+            if !email.is_empty() && !seen.contains(&email) {
+                seen.push(email);
+                authors.push(person.to_string());
+            }
+        }
+    }
+    authors
+}
+
+fn is_co_author(line: &str) -> bool {
+    line.split_once(':')
+        .is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("Co-authored-by"))
+}
+
+/// The address in `Name <email>`, or an empty string.
+fn email_of(person: &str) -> &str {
+    person
+        .rsplit_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map_or("", |(email, _)| email.trim())
+}
+
+/// Split off the final paragraph if every line in it is a `Key: value` trailer.
+fn split_trailers(body: &str) -> (&str, Vec<&str>) {
+    let body = body.trim_end();
+    let start = body.rfind("\n\n").map_or(0, |i| i + 2);
+    let last = &body[start..];
+
+    if !last.is_empty() && last.lines().all(is_trailer) {
+        (body[..start].trim_end(), last.lines().collect())
+    } else {
+        (body, vec![])
+    }
+}
+
+/// Check for a git trailer line such as `Signed-off-by: Jane <jane@example.com>`.
+fn is_trailer(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, value)| {
+        !key.is_empty()
+            && !value.trim().is_empty()
+            && key.starts_with(|c: char| c.is_ascii_alphabetic())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+fn is_stripped(trailer: &str, strip: &[String]) -> bool {
+    trailer
+        .split_once(':')
+        .is_some_and(|(key, _)| strip.iter().any(|s| s.eq_ignore_ascii_case(key)))
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
+        return true;
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+}
+
+fn is_verbatim(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with('#') || trimmed.starts_with('>') || trimmed.starts_with('|')
+}
+
+/// Re-join hard-wrapped prose while leaving markdown structure intact.
+fn reflow(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut last = Block::Blank;
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+
+        if in_fence || is_fence(line) {
+            if is_fence(line) {
+                in_fence = !in_fence;
+            }
+            lines.push(line.to_string());
+            last = Block::Verbatim;
+            continue;
+        }
+
+        let indented = line.starts_with("    ") || line.starts_with('\t');
+        let block = if line.is_empty() {
+            Block::Blank
+        } else if indented && matches!(last, Block::Blank | Block::IndentedCode) {
+            Block::IndentedCode
+        } else if is_list_item(line) {
+            Block::ListItem
+        } else if is_verbatim(line) {
+            Block::Verbatim
+        } else {
+            Block::Prose
+        };
+
+        let joins = block == Block::Prose && matches!(last, Block::Prose | Block::ListItem);
+        match lines.last_mut() {
+            Some(prev) if joins => {
+                prev.push(' ');
+                prev.push_str(line.trim_start());
+            }
+            _ => lines.push(line.to_string()),
+        }
+
+        // A joined line continues whatever block it was joined to
+        if !joins {
+            last = block;
+        }
+    }
+
+    // Keep leading indentation, which is significant for code blocks
+    lines
+        .join("\n")
+        .trim_start_matches('\n')
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn config(strip: &[&str], footer: Option<&str>) -> PrConfig {
+        PrConfig {
+            strip_trailers: strip.iter().map(ToString::to_string).collect(),
+            footer: footer.map(String::from),
+            ..PrConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_reflows_prose_but_keeps_structure() {
+        let body = "This change wraps\nat 72 columns.\n\n- first item\n  continues here\n- second\n\n```\nlet x = 1;\nlet y = 2;\n```\n\n## Notes\n> quoted\n> text";
+        let formatted = format_pr_body(body, &PrConfig::default());
+        assert_eq!(
+            formatted,
+            "This change wraps at 72 columns.\n\n- first item continues here\n- second\n\n```\nlet x = 1;\nlet y = 2;\n```\n\n## Notes\n> quoted\n> text"
+        );
+    }
+
+    #[test]
+    fn test_keeps_indented_code() {
+        let body = "Example:\n\n    cargo test\n    cargo build";
+        assert_eq!(format_pr_body(body, &PrConfig::default()), body);
+
+        let body = "    cargo test\n    cargo build";
+        assert_eq!(format_pr_body(body, &PrConfig::default()), body);
+    }
+
+    #[test]
+    fn test_trailers_separated_and_stripped() {
+        let body = "Explain the fix.\n\nSigned-off-by: A <a@example.com>\nChange-Id: I123\nCo-authored-by: B <b@example.com>";
+
+        assert_eq!(
+            format_pr_body(body, &PrConfig::default()),
+            "Explain the fix.\n\nSigned-off-by: A <a@example.com>\nChange-Id: I123\nCo-authored-by: B <b@example.com>"
+        );
+        assert_eq!(
+            format_pr_body(body, &config(&["signed-off-by", "Change-Id"], None)),
+            "Explain the fix.\n\nCo-authored-by: B <b@example.com>"
+        );
+        assert_eq!(
+            format_pr_body(
+                "Signed-off-by: A <a@example.com>",
+                &config(&["Signed-off-by"], None)
+            ),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_prose_paragraph_is_not_a_trailer() {
+        let body = "First paragraph.\n\nNote: this is prose, not a trailer\nbecause it wraps.";
+        assert_eq!(
+            format_pr_body(body, &PrConfig::default()),
+            "First paragraph.\n\nNote: this is prose, not a trailer because it wraps."
+        );
+    }
+
+    #[test]
+    fn test_squash_commit() {
+        let co_authors = vec!["Bo <bo@example.com>".to_string()];
+
+        let (title, message) = squash_commit(
+            "PROJ-12: Add login",
+            7,
+            "Adds the login form.\r\n",
+            &co_authors,
+        );
+        assert_eq!(title, "PROJ-12: Add login (#7)");
+        assert_eq!(
+            message,
+            "Adds the login form.\n\nCo-authored-by: Bo <bo@example.com>"
+        );
+
+        // Joins an existing trailer block, and skips co-authors already named
+        let (title, message) = squash_commit(
+            "Add login (#7)",
+            7,
+            "Body\n\nSigned-off-by: Al <al@example.com>",
+            &co_authors,
+        );
+        assert_eq!(title, "Add login (#7)");
+        assert_eq!(
+            message,
+            "Body\n\nSigned-off-by: Al <al@example.com>\nCo-authored-by: Bo <bo@example.com>"
+        );
+        let (_, message) = squash_commit("t", 7, "co-authored-by: B <BO@example.com>", &co_authors);
+        assert_eq!(message, "co-authored-by: B <BO@example.com>");
+
+        let (_, message) = squash_commit("t", 7, "", &co_authors);
+        assert_eq!(message, "Co-authored-by: Bo <bo@example.com>");
+    }
+
+    #[test]
+    fn test_co_authors() {
+        let commits = [
+            ("Me <me@example.com>", "First"),
+            (
+                "Bo <bo@example.com>",
+                "Second\n\nCo-authored-by: Cy <cy@example.com>\nCo-authored-by: Me <ME@example.com>",
+            ),
+            ("Bo <Bo@Example.com>", "Third"),
+        ];
+        assert_eq!(
+            co_authors(commits, Some("me@example.com")),
+            vec!["Bo <bo@example.com>", "Cy <cy@example.com>"]
+        );
+        assert_eq!(co_authors(commits, None).len(), 3);
+    }
+
+    #[test]
+    fn test_append_footer() {
+        let cfg = config(&[], Some("Reviewed with rung"));
+        assert_eq!(append_footer("Body", &cfg), "Body\n\nReviewed with rung");
+        assert_eq!(append_footer("", &cfg), "Reviewed with rung");
+        assert_eq!(append_footer("Body", &PrConfig::default()), "Body");
+    }
+
+    #[test]
+    fn test_set_related_prs() {
+        let related = vec!["acme/client#7".to_string(), "acme/docs#3".to_string()];
+        let body = set_related_prs("Adds the endpoint.", &related);
+        assert_eq!(
+            body,
+            "Adds the endpoint.\n\n<!-- rung-related -->\n**Related PRs**\n\n\
+             * acme/client#7\n* acme/docs#3\n<!-- /rung-related -->"
+        );
+
+        // Replaced in place, not appended again
+        let updated = set_related_prs(&body, &related[..1]);
+        assert_eq!(updated.matches("rung-related -->").count(), 2);
+        assert!(!updated.contains("acme/docs#3"));
+        assert!(updated.starts_with("Adds the endpoint.\n\n"));
+
+        assert_eq!(set_related_prs(&body, &[]), "Adds the endpoint.");
+        assert!(set_related_prs("", &related).starts_with("<!-- rung-related -->"));
+    }
+
+    #[test]
+    fn test_set_notes() {
+        let notes = vec![
+            "Blocked on INFRA-12".to_string(),
+            "Don't merge --> before Friday".to_string(),
+        ];
+        let body = set_notes("Adds the endpoint.", &notes);
+        assert_eq!(
+            body,
+            "Adds the endpoint.\n\n<!-- rung-notes\nBlocked on INFRA-12\n\
+             Don't merge -- > before Friday\n/rung-notes -->"
+        );
+        assert_eq!(
+            super::notes(&body),
+            ["Blocked on INFRA-12", "Don't merge -- > before Friday"]
+        );
+        assert_eq!(set_notes(&body, &notes), body);
+
+        // Replaced where it is, leaving later sections after it
+        let with_footer = format!("{body}\n\nFooter");
+        let updated = set_notes(&with_footer, &notes[..1]);
+        assert!(updated.ends_with("Blocked on INFRA-12\n/rung-notes -->\n\nFooter"));
+        assert_eq!(set_notes(&with_footer, &[]), "Adds the endpoint.\n\nFooter");
+
+        assert_eq!(set_notes(&body, &[]), "Adds the endpoint.");
+        assert!(set_notes("", &notes).starts_with("<!-- rung-notes\n"));
+        assert!(super::notes("Adds the endpoint.").is_empty());
+    }
+
+    #[test]
+    fn test_add_attachments() {
+        let links = vec![
+            "![before.png](https://x/before.png)".to_string(),
+            "![after.png](https://x/after.png)".to_string(),
+        ];
+        let body = add_attachments("Restyles the form.", &links);
+        assert_eq!(
+            body,
+            "Restyles the form.\n\n![before.png](https://x/before.png)\n\
+             ![after.png](https://x/after.png)"
+        );
+        assert_eq!(add_attachments(&body, &links), body);
+        assert_eq!(add_attachments("", &links[..1]), links[0]);
+    }
+
+    #[test]
+    fn test_set_depends_on() {
+        let body = set_depends_on("Adds the endpoint.", Some(12));
+        assert_eq!(
+            body,
+            "<!-- rung-depends -->Depends on #12<!-- /rung-depends -->\n\nAdds the endpoint."
+        );
+        assert_eq!(set_depends_on(&body, Some(12)), body);
+        assert!(set_depends_on(&body, Some(15)).contains("Depends on #15<"));
+        assert!(!set_depends_on(&body, Some(15)).contains("#12"));
+        assert_eq!(set_depends_on(&body, None), "Adds the endpoint.");
+        assert_eq!(set_depends_on("", None), "");
+        assert!(set_depends_on("", Some(3)).ends_with("-->"));
+    }
+}
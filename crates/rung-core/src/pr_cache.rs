@@ -0,0 +1,147 @@
+//! Last-known states of the stack's pull requests.
+//!
+//! Checking each PR for a merge costs a GitHub request per PR on every sync.
+//! `rung sync` keeps what it last saw in `.git/rung/prs.json`: while every PR
+//! was open at the last check, one listing of recently closed PRs shows which
+//! of them merged since. Every PR is still checked at least once a day, which
+//! also catches bases changed and branches renamed on GitHub.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::remote_status::RemotePrState;
+
+/// Hours after which every PR is checked again.
+const FULL_CHECK_HOURS: i64 = 24;
+
+/// What GitHub last reported for a PR.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrEntry {
+    /// Open, closed or merged.
+    pub state: RemotePrState,
+
+    /// Base branch on the remote.
+    pub base: String,
+
+    /// When the PR last changed on GitHub, if reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// PR states by number, stored in `.git/rung/prs.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrCache {
+    /// When the PRs were last checked, one way or the other.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked_at: Option<DateTime<Utc>>,
+
+    /// When every PR was last fetched on its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<DateTime<Utc>>,
+
+    /// Entries keyed by PR number.
+    #[serde(default)]
+    pub prs: BTreeMap<u64, PrEntry>,
+}
+
+impl PrCache {
+    /// Cached entry for PR `number`.
+    #[must_use]
+    pub fn get(&self, number: u64) -> Option<&PrEntry> {
+        self.prs.get(&number)
+    }
+
+    /// Record what GitHub reported for PR `number`.
+    pub fn record(&mut self, number: u64, entry: PrEntry) {
+        self.prs.insert(number, entry);
+    }
+
+    /// Drop entries for PRs not in `keep`.
+    pub fn retain(&mut self, keep: &[u64]) {
+        self.prs.retain(|number, _| keep.contains(number));
+    }
+
+    /// Whether a listing of recently closed PRs is enough to check `prs`:
+    /// each was open when last seen, and every PR was fetched within the
+    /// last day.
+    #[must_use]
+    pub fn covers(&self, prs: &[u64], now: DateTime<Utc>) -> bool {
+        let fresh = self.checked_at.is_some()
+            && self
+                .verified_at
+                .is_some_and(|at| now - at < Duration::hours(FULL_CHECK_HOURS));
+        fresh
+            && prs.iter().all(|number| {
+                self.get(*number)
+                    .is_some_and(|entry| entry.state == RemotePrState::Open)
+            })
+    }
+
+    /// Whether a newest-first listing whose last PR was updated at `oldest`
+    /// reaches back to the last check, so nothing closed since was cut off.
+    #[must_use]
+    pub fn reaches_back(&self, oldest: &str) -> bool {
+        let Some(checked_at) = self.checked_at else {
+            return false;
+        };
+        DateTime::parse_from_rfc3339(oldest).is_ok_and(|oldest| oldest <= checked_at)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn open(base: &str) -> PrEntry {
+        PrEntry {
+            state: RemotePrState::Open,
+            base: base.into(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_covers() {
+        let now = Utc::now();
+        let mut cache = PrCache::default();
+        cache.record(1, open("main"));
+        cache.record(2, open("a"));
+        assert!(!cache.covers(&[1, 2], now), "never checked");
+
+        cache.checked_at = Some(now);
+        cache.verified_at = Some(now - Duration::hours(1));
+        assert!(cache.covers(&[1, 2], now));
+        assert!(!cache.covers(&[1, 3], now), "PR 3 was never seen");
+        assert!(!cache.covers(&[1], now + Duration::hours(FULL_CHECK_HOURS)));
+
+        cache.prs.get_mut(&2).unwrap().state = RemotePrState::Merged;
+        assert!(!cache.covers(&[1, 2], now));
+
+        cache.retain(&[1]);
+        assert!(cache.get(2).is_none());
+        assert!(cache.covers(&[1], now));
+    }
+
+    #[test]
+    fn test_reaches_back() {
+        let mut cache = PrCache::default();
+        assert!(!cache.reaches_back("2024-01-01T00:00:00Z"));
+
+        cache.checked_at = Some("2024-06-01T12:00:00Z".parse().unwrap());
+        assert!(cache.reaches_back("2024-06-01T11:59:59Z"));
+        assert!(!cache.reaches_back("2024-06-01T12:00:01Z"));
+        assert!(!cache.reaches_back("yesterday"));
+    }
+
+    #[test]
+    fn test_serde_keys() {
+        let mut cache = PrCache::default();
+        cache.record(12, open("main"));
+        let json = serde_json::to_string(&cache).unwrap();
+        assert_eq!(json, r#"{"prs":{"12":{"state":"open","base":"main"}}}"#);
+        assert_eq!(serde_json::from_str::<PrCache>(&json).unwrap(), cache);
+    }
+}
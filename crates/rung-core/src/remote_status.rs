@@ -0,0 +1,351 @@
+//! Comparing the local stack against pull requests on GitHub.
+//!
+//! `rung status --remote` treats GitHub as the source of truth: PRs can be
+//! opened, closed or rebased there without rung noticing. This module works
+//! out where `stack.json` and GitHub disagree; fetching the PRs is left to
+//! the caller.
+
+use serde::{Deserialize, Serialize};
+
+use crate::stack::Stack;
+
+/// State of a pull request on GitHub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemotePrState {
+    /// Still open.
+    Open,
+    /// Closed without merging.
+    Closed,
+    /// Merged.
+    Merged,
+}
+
+/// A pull request as GitHub reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemotePr {
+    /// PR number.
+    pub number: u64,
+    /// Head branch on the remote.
+    pub head: String,
+    /// Base branch on the remote.
+    pub base: String,
+    /// Open, closed or merged.
+    pub state: RemotePrState,
+}
+
+/// One way the local stack and GitHub disagree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// An open PR of yours is stacked on (or under) other PRs, but its
+    /// branch isn't in the stack.
+    Untracked {
+        /// PR number.
+        pr: u64,
+        /// Head branch on the remote.
+        head: String,
+        /// Base branch on the remote.
+        base: String,
+    },
+
+    /// A tracked PR is no longer open.
+    NotOpen {
+        /// Local branch.
+        branch: String,
+        /// PR number.
+        pr: u64,
+        /// Closed or merged.
+        state: RemotePrState,
+    },
+
+    /// A tracked PR isn't based on the branch's parent.
+    BaseMismatch {
+        /// Local branch.
+        branch: String,
+        /// PR number.
+        pr: u64,
+        /// The parent's name on the remote.
+        expected: String,
+        /// The PR's base on GitHub.
+        actual: String,
+    },
+
+    /// A tracked PR couldn't be found on GitHub.
+    Missing {
+        /// Local branch.
+        branch: String,
+        /// PR number.
+        pr: u64,
+    },
+}
+
+/// An untracked PR that `rung adopt` would add to the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adoption {
+    /// The PR, whose head becomes the branch name.
+    pub pr: RemotePr,
+    /// Local branch the new stack branch goes on.
+    pub parent: String,
+}
+
+/// Find where `stack` and GitHub disagree.
+///
+/// `open` is every open PR of yours; `tracked` holds what GitHub reports for
+/// each PR number recorded in the stack. Untracked PRs are the ones
+/// [`adoptions`] would add.
+#[must_use]
+pub fn compare(stack: &Stack, open: &[RemotePr], tracked: &[RemotePr]) -> Vec<Discrepancy> {
+    let mut found = vec![];
+
+    for branch in &stack.branches {
+        let Some(number) = branch.pr else {
+            continue;
+        };
+        let Some(pr) = tracked.iter().find(|pr| pr.number == number) else {
+            found.push(Discrepancy::Missing {
+                branch: branch.name.to_string(),
+                pr: number,
+            });
+            continue;
+        };
+        if pr.state != RemotePrState::Open {
+            found.push(Discrepancy::NotOpen {
+                branch: branch.name.to_string(),
+                pr: number,
+                state: pr.state,
+            });
+            continue;
+        }
+        if let Some(parent) = &branch.parent {
+            let expected = stack.remote_name_of(parent);
+            if pr.base != expected {
+                found.push(Discrepancy::BaseMismatch {
+                    branch: branch.name.to_string(),
+                    pr: number,
+                    expected: expected.to_string(),
+                    actual: pr.base.clone(),
+                });
+            }
+        }
+    }
+
+    found.extend(
+        adoptions(stack, open)
+            .into_iter()
+            .map(|adoption| Discrepancy::Untracked {
+                pr: adoption.pr.number,
+                head: adoption.pr.head,
+                base: adoption.pr.base,
+            }),
+    );
+
+    found
+}
+
+/// The stacked PRs among `open` whose branches the stack doesn't track,
+/// parents first: those based on another of your branches, or that another
+/// of your PRs is based on.
+#[must_use]
+pub fn adoptions(stack: &Stack, open: &[RemotePr]) -> Vec<Adoption> {
+    let tracked_as = |remote: &str| stack.branches.iter().find(|b| b.remote_name() == remote);
+    let mut pending: Vec<&RemotePr> = open
+        .iter()
+        .filter(|pr| tracked_as(&pr.head).is_none())
+        .filter(|pr| !stack.branches.iter().any(|b| b.pr == Some(pr.number)))
+        .filter(|pr| {
+            tracked_as(&pr.base).is_some()
+                || open.iter().any(|other| other.head == pr.base)
+                || open.iter().any(|other| other.base == pr.head)
+        })
+        .collect();
+
+    let mut ordered = vec![];
+    while !pending.is_empty() {
+        let (ready, rest): (Vec<&RemotePr>, Vec<&RemotePr>) = pending
+            .iter()
+            .partition(|pr| !pending.iter().any(|other| other.head == pr.base));
+        if ready.is_empty() {
+            // Bases in a cycle can't be ordered; keep them as listed
+            ordered.extend(rest);
+            break;
+        }
+        ordered.extend(ready);
+        pending = rest;
+    }
+
+    ordered
+        .into_iter()
+        .map(|pr| Adoption {
+            pr: pr.clone(),
+            parent: tracked_as(&pr.base).map_or_else(|| pr.base.clone(), |b| b.name.to_string()),
+        })
+        .collect()
+}
+
+/// The PRs among `open` stacked on `base`: those based on it, then those
+/// based on them, and so on, parents first.
+#[must_use]
+pub fn stacked_on<'a>(base: &'a str, open: &'a [RemotePr]) -> Vec<&'a RemotePr> {
+    let mut found: Vec<&RemotePr> = vec![];
+    let mut seen = vec![base];
+    let mut next = 0;
+    while let Some(&base) = seen.get(next) {
+        next += 1;
+        for pr in open.iter().filter(|pr| pr.base == base) {
+            // A head seen already would close a cycle
+            if !seen.contains(&pr.head.as_str()) {
+                seen.push(&pr.head);
+                found.push(pr);
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::BranchName;
+    use crate::stack::StackBranch;
+
+    fn pr(number: u64, head: &str, base: &str, state: RemotePrState) -> RemotePr {
+        RemotePr {
+            number,
+            head: head.into(),
+            base: base.into(),
+            state,
+        }
+    }
+
+    fn stack() -> Stack {
+        let mut stack = Stack::new();
+        let mut a = StackBranch::try_new("a", Some("main")).unwrap();
+        a.pr = Some(1);
+        let mut b = StackBranch::try_new("b", Some("a")).unwrap();
+        b.pr = Some(2);
+        stack.add_branch(a);
+        stack.add_branch(b);
+        stack
+    }
+
+    #[test]
+    fn test_compare_in_sync() {
+        let prs = [
+            pr(1, "a", "main", RemotePrState::Open),
+            pr(2, "b", "a", RemotePrState::Open),
+        ];
+        assert!(compare(&stack(), &prs, &prs).is_empty());
+    }
+
+    #[test]
+    fn test_compare_tracked_prs() {
+        let mut stack = stack();
+        let mut c = StackBranch::try_new("c", Some("b")).unwrap();
+        c.pr = Some(3);
+        stack.add_branch(c);
+        let tracked = [
+            pr(1, "a", "main", RemotePrState::Merged),
+            pr(2, "b", "main", RemotePrState::Open),
+        ];
+
+        assert_eq!(
+            compare(&stack, &tracked[1..], &tracked),
+            vec![
+                Discrepancy::NotOpen {
+                    branch: "a".into(),
+                    pr: 1,
+                    state: RemotePrState::Merged,
+                },
+                Discrepancy::BaseMismatch {
+                    branch: "b".into(),
+                    pr: 2,
+                    expected: "a".into(),
+                    actual: "main".into(),
+                },
+                Discrepancy::Missing {
+                    branch: "c".into(),
+                    pr: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_untracked_prs() {
+        let tracked = [
+            pr(1, "a", "main", RemotePrState::Open),
+            pr(2, "b", "a", RemotePrState::Open),
+        ];
+        let mut open = tracked.to_vec();
+        open.extend([
+            // On top of the stack
+            pr(3, "c", "b", RemotePrState::Open),
+            // A separate stack of two
+            pr(4, "x", "main", RemotePrState::Open),
+            pr(5, "y", "x", RemotePrState::Open),
+            // A lone PR against main isn't part of any stack
+            pr(6, "lone", "main", RemotePrState::Open),
+        ]);
+
+        let untracked: Vec<u64> = compare(&stack(), &open, &tracked)
+            .iter()
+            .map(|d| match d {
+                Discrepancy::Untracked { pr, .. } => *pr,
+                other => panic!("unexpected discrepancy: {other:?}"),
+            })
+            .collect();
+        assert_eq!(untracked, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_adoptions_parents_first() {
+        let mut stack = stack();
+        stack.branches[1].remote = Some(BranchName::new("user/b").unwrap());
+        let open = [
+            pr(5, "z", "y", RemotePrState::Open),
+            pr(4, "y", "user/b", RemotePrState::Open),
+            pr(3, "x", "main", RemotePrState::Open),
+        ];
+
+        let adoptions = adoptions(&stack, &open);
+        let order: Vec<(&str, &str)> = adoptions
+            .iter()
+            .map(|a| (a.pr.head.as_str(), a.parent.as_str()))
+            .collect();
+        // x stands alone on main
+        assert_eq!(order, vec![("y", "b"), ("z", "y")]);
+    }
+
+    #[test]
+    fn test_stacked_on() {
+        let open = [
+            pr(4, "c", "b", RemotePrState::Open),
+            pr(1, "a", "main", RemotePrState::Open),
+            pr(2, "b", "a", RemotePrState::Open),
+            pr(3, "x", "main", RemotePrState::Open),
+            pr(5, "y", "release", RemotePrState::Open),
+            // Cycles through GitHub's API are unlikely, but mustn't hang
+            pr(6, "main", "c", RemotePrState::Open),
+        ];
+
+        let numbers: Vec<u64> = stacked_on("main", &open)
+            .iter()
+            .map(|pr| pr.number)
+            .collect();
+        assert_eq!(numbers, vec![1, 3, 2, 4]);
+        assert!(stacked_on("develop", &open).is_empty());
+    }
+
+    #[test]
+    fn test_compare_uses_remote_names() {
+        let mut stack = stack();
+        stack.branches[0].remote = Some(BranchName::new("user/a").unwrap());
+        let prs = [
+            pr(1, "user/a", "main", RemotePrState::Open),
+            pr(2, "b", "user/a", RemotePrState::Open),
+        ];
+        assert!(compare(&stack, &prs, &prs).is_empty());
+    }
+}
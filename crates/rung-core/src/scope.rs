@@ -0,0 +1,112 @@
+//! Path scopes for monorepos.
+//!
+//! Maps the files a branch changes onto configured packages and checks them
+//! against the branch's own path filters.
+
+use crate::config::PackageConfig;
+use crate::error::{Error, Result};
+
+/// Normalize a user-supplied directory to the form stored in the stack.
+///
+/// Strips leading `./` and trailing slashes. `"."` (the whole repository)
+/// normalizes to an empty string.
+///
+/// # Errors
+/// Returns `InvalidConfig` for absolute paths or paths containing `..`.
+pub fn normalize_path(path: &str) -> Result<String> {
+    let trimmed = path.trim().trim_start_matches("./").trim_end_matches('/');
+    if trimmed.starts_with('/') || trimmed.split('/').any(|part| part == "..") {
+        return Err(Error::InvalidConfig(format!(
+            "path '{path}' must be relative to the repository root"
+        )));
+    }
+    Ok(if trimmed == "." {
+        String::new()
+    } else {
+        trimmed.to_string()
+    })
+}
+
+/// Check whether `file` lives under directory `dir`.
+///
+/// An empty `dir` matches every file.
+#[must_use]
+pub fn path_in(file: &str, dir: &str) -> bool {
+    let dir = dir.trim_end_matches('/');
+    dir.is_empty() || file == dir || file.strip_prefix(dir).is_some_and(|r| r.starts_with('/'))
+}
+
+/// Packages containing at least one of `files`, in config order.
+#[must_use]
+pub fn touched_packages<'a, S: AsRef<str>>(
+    files: &[S],
+    packages: &'a [PackageConfig],
+) -> Vec<&'a PackageConfig> {
+    packages
+        .iter()
+        .filter(|pkg| files.iter().any(|f| path_in(f.as_ref(), &pkg.path)))
+        .collect()
+}
+
+/// Files that fall outside every path in `scope`.
+///
+/// An empty scope means the branch is unrestricted, so nothing is reported.
+#[must_use]
+pub fn outside_scope<'a, S: AsRef<str>>(files: &'a [S], scope: &[String]) -> Vec<&'a str> {
+    if scope.is_empty() {
+        return vec![];
+    }
+    files
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|f| !scope.iter().any(|dir| path_in(f, dir)))
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, path: &str) -> PackageConfig {
+        PackageConfig {
+            name: name.into(),
+            path: path.into(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("./services/api/").unwrap(), "services/api");
+        assert_eq!(normalize_path(".").unwrap(), "");
+        assert!(normalize_path("/etc").is_err());
+        assert!(normalize_path("services/../secrets").is_err());
+    }
+
+    #[test]
+    fn test_path_in() {
+        assert!(path_in("services/api/main.rs", "services/api"));
+        assert!(path_in("services/api/main.rs", "services/api/"));
+        assert!(!path_in("services/api-gateway/main.rs", "services/api"));
+        assert!(path_in("README.md", ""));
+    }
+
+    #[test]
+    fn test_touched_packages_and_scope() {
+        let packages = vec![package("api", "services/api"), package("web", "apps/web")];
+        let files = ["services/api/main.rs", "docs/intro.md"];
+
+        let touched: Vec<&str> = touched_packages(&files, &packages)
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(touched, vec!["api"]);
+
+        assert_eq!(
+            outside_scope(&files, &["services/api".to_string()]),
+            vec!["docs/intro.md"]
+        );
+        assert!(outside_scope(&files, &[]).is_empty());
+    }
+}
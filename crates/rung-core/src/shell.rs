@@ -0,0 +1,69 @@
+//! Running user-configured commands through the platform shell.
+//!
+//! Commands such as `sync --exec`, `rung foreach`, `[create] scaffold`, the
+//! editor and the pager are written for a shell: `sh -c` on Unix, and
+//! `cmd /C` on Windows, which has no `sh` unless Git for Windows put one on
+//! `PATH`.
+
+use std::ffi::OsStr;
+use std::process::Command;
+
+/// A command running `script` in the platform shell.
+#[must_use]
+pub fn command(script: &str) -> Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // Passed as is: quoting it would make cmd strip the wrong quotes
+        let mut command = Command::new("cmd");
+        command.arg("/C").raw_arg(script);
+        command
+    }
+    #[cfg(not(windows))]
+    {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+}
+
+/// A command running `script` followed by `arg`, such as an editor
+/// configured with flags (`code --wait`) and the file to edit. `arg` reaches
+/// the program as one argument, whatever characters it has.
+#[must_use]
+pub fn command_with_arg(script: &str, arg: impl AsRef<OsStr>) -> Command {
+    #[cfg(windows)]
+    {
+        let mut command = command(script);
+        command.arg(arg);
+        command
+    }
+    #[cfg(not(windows))]
+    {
+        let mut command = command(&format!("{script} \"$1\""));
+        command.arg("sh").arg(arg);
+        command
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_reports_exit_status() {
+        assert!(command("exit 0").status().unwrap().success());
+        assert_eq!(command("exit 3").status().unwrap().code(), Some(3));
+    }
+
+    #[test]
+    fn test_command_with_arg_keeps_it_whole() {
+        let output = command_with_arg("echo", "two words; exit 4")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("two words; exit 4"));
+    }
+}
@@ -0,0 +1,288 @@
+//! Named snapshots of stack branch tips.
+//!
+//! Snapshots are stored as lightweight git refs under `refs/rung/snapshots/<name>/`,
+//! one per branch, so they survive `git gc` and can be inspected with plain git.
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::stack::Stack;
+
+/// Ref namespace for snapshots.
+pub const SNAPSHOT_REF_PREFIX: &str = "refs/rung/snapshots/";
+
+/// How a branch changed since a snapshot was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum SnapshotChange {
+    /// Branch tip is identical to the snapshot.
+    Unchanged,
+
+    /// Branch tip moved.
+    Moved {
+        /// Commits on the branch that weren't in the snapshot.
+        commits_added: usize,
+        /// Commits in the snapshot that are no longer on the branch.
+        commits_removed: usize,
+    },
+
+    /// Branch existed in the snapshot but no longer exists locally.
+    Deleted,
+
+    /// Branch is in the stack but wasn't part of the snapshot.
+    Added,
+}
+
+/// Diff entry for a single branch.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotBranchDiff {
+    /// Branch name.
+    pub branch: String,
+
+    /// Tip recorded in the snapshot, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_commit: Option<String>,
+
+    /// Current tip, if the branch exists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_commit: Option<String>,
+
+    /// What changed.
+    #[serde(flatten)]
+    pub change: SnapshotChange,
+}
+
+fn snapshot_prefix(name: &str) -> String {
+    format!("{SNAPSHOT_REF_PREFIX}{name}/")
+}
+
+/// Record the tips of all stack branches under `refs/rung/snapshots/<name>/`.
+///
+/// Returns the number of branches captured.
+///
+/// # Errors
+/// Returns error if a snapshot with this name exists, no stack branch exists
+/// locally (a snapshot of nothing couldn't be read back), or ref creation
+/// fails.
+pub fn create_snapshot(repo: &rung_git::Repository, stack: &Stack, name: &str) -> Result<usize> {
+    crate::BranchName::new(name)?;
+    if name.contains('/') {
+        return Err(Error::InvalidBranchName {
+            name: name.to_string(),
+            reason: "snapshot names cannot contain '/'".to_string(),
+        });
+    }
+    let prefix = snapshot_prefix(name);
+    if !repo.references_with_prefix(&prefix)?.is_empty() {
+        return Err(Error::SnapshotExists(name.to_string()));
+    }
+
+    let mut captured = 0;
+    for branch in &stack.branches {
+        let Ok(commit) = repo.branch_commit(&branch.name) else {
+            continue;
+        };
+        repo.set_reference(
+            &format!("{prefix}{}", branch.name),
+            commit,
+            &format!("snapshot {name}"),
+        )?;
+        captured += 1;
+    }
+
+    if captured == 0 {
+        return Err(Error::NothingToSnapshot);
+    }
+    Ok(captured)
+}
+
+/// List snapshot names.
+///
+/// # Errors
+/// Returns error if refs can't be read.
+pub fn list_snapshots(repo: &rung_git::Repository) -> Result<Vec<String>> {
+    let mut names: Vec<String> = repo
+        .references_with_prefix(SNAPSHOT_REF_PREFIX)?
+        .into_iter()
+        .filter_map(|(r, _)| {
+            r.strip_prefix(SNAPSHOT_REF_PREFIX)
+                .and_then(|rest| rest.split_once('/'))
+                .map(|(name, _)| name.to_string())
+        })
+        .collect();
+    names.dedup();
+    Ok(names)
+}
+
+/// Load the branch tips recorded in a snapshot.
+///
+/// # Errors
+/// Returns error if the snapshot doesn't exist.
+pub fn load_snapshot(
+    repo: &rung_git::Repository,
+    name: &str,
+) -> Result<Vec<(String, rung_git::Oid)>> {
+    let prefix = snapshot_prefix(name);
+    let refs: Vec<(String, rung_git::Oid)> = repo
+        .references_with_prefix(&prefix)?
+        .into_iter()
+        .filter_map(|(r, oid)| r.strip_prefix(&prefix).map(|b| (b.to_string(), oid)))
+        .collect();
+
+    if refs.is_empty() {
+        return Err(Error::SnapshotNotFound(name.to_string()));
+    }
+    Ok(refs)
+}
+
+/// Compare the current stack against a snapshot.
+///
+/// Branches are reported in stack order, followed by snapshot branches that
+/// are no longer part of the stack.
+///
+/// # Errors
+/// Returns error if the snapshot doesn't exist or git operations fail.
+pub fn diff_snapshot(
+    repo: &rung_git::Repository,
+    stack: &Stack,
+    name: &str,
+) -> Result<Vec<SnapshotBranchDiff>> {
+    let recorded = load_snapshot(repo, name)?;
+    let mut diffs = Vec::new();
+
+    let names = stack.branches.iter().map(|b| b.name.to_string()).chain(
+        recorded
+            .iter()
+            .filter(|(b, _)| stack.find_branch(b).is_none())
+            .map(|(b, _)| b.clone()),
+    );
+
+    for branch in names {
+        let snapshot = recorded.iter().find(|(b, _)| *b == branch).map(|(_, o)| *o);
+        let current = repo.branch_commit(&branch).ok();
+
+        let change = match (snapshot, current) {
+            (Some(old), Some(new)) if old == new => SnapshotChange::Unchanged,
+            (Some(old), Some(new)) => SnapshotChange::Moved {
+                commits_added: repo.count_commits_between(old, new)?,
+                commits_removed: repo.count_commits_between(new, old)?,
+            },
+            (Some(_), None) => SnapshotChange::Deleted,
+            (None, Some(_)) => SnapshotChange::Added,
+            (None, None) => continue,
+        };
+
+        diffs.push(SnapshotBranchDiff {
+            branch,
+            snapshot_commit: snapshot.map(|o| o.to_string()),
+            current_commit: current.map(|o| o.to_string()),
+            change,
+        });
+    }
+
+    Ok(diffs)
+}
+
+/// Delete a snapshot and all of its refs.
+///
+/// # Errors
+/// Returns error if the snapshot doesn't exist or deletion fails.
+pub fn delete_snapshot(repo: &rung_git::Repository, name: &str) -> Result<()> {
+    let prefix = snapshot_prefix(name);
+    for (branch, _) in load_snapshot(repo, name)? {
+        repo.delete_reference(&format!("{prefix}{branch}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::BranchName;
+    use crate::stack::StackBranch;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit_file(temp: &TempDir, git_repo: &git2::Repository, filename: &str) {
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        fs::write(temp.path().join(filename), filename).unwrap();
+
+        let mut index = git_repo.index().unwrap();
+        index.add_path(std::path::Path::new(filename)).unwrap();
+        index.write().unwrap();
+
+        let tree_id = index.write_tree().unwrap();
+        let tree = git_repo.find_tree(tree_id).unwrap();
+        let parents: Vec<git2::Commit<'_>> = git_repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
+        git_repo
+            .commit(Some("HEAD"), &sig, &sig, filename, &tree, &parent_refs)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let temp = TempDir::new().unwrap();
+        let git_repo = git2::Repository::init(temp.path()).unwrap();
+        commit_file(&temp, &git_repo, "README.md");
+        let repo = rung_git::Repository::open(temp.path()).unwrap();
+
+        repo.create_branch(&BranchName::new("feature-a").unwrap())
+            .unwrap();
+        repo.create_branch(&BranchName::new("feature-b").unwrap())
+            .unwrap();
+
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("feature-a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("feature-b", Some("feature-a")).unwrap());
+
+        assert_eq!(create_snapshot(&repo, &stack, "before").unwrap(), 2);
+        assert!(create_snapshot(&repo, &stack, "before").is_err());
+        assert_eq!(list_snapshots(&repo).unwrap(), vec!["before"]);
+
+        // Move feature-a forward by one commit
+        repo.checkout("feature-a").unwrap();
+        commit_file(&temp, &git_repo, "a.txt");
+
+        let diffs = diff_snapshot(&repo, &stack, "before").unwrap();
+        assert_eq!(
+            diffs[0].change,
+            SnapshotChange::Moved {
+                commits_added: 1,
+                commits_removed: 0
+            }
+        );
+        assert_eq!(diffs[1].change, SnapshotChange::Unchanged);
+
+        delete_snapshot(&repo, "before").unwrap();
+        assert!(list_snapshots(&repo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_refuses_empty_stack() {
+        let temp = TempDir::new().unwrap();
+        let git_repo = git2::Repository::init(temp.path()).unwrap();
+        commit_file(&temp, &git_repo, "README.md");
+        let repo = rung_git::Repository::open(temp.path()).unwrap();
+
+        let mut stack = Stack::new();
+        assert!(matches!(
+            create_snapshot(&repo, &stack, "empty"),
+            Err(Error::NothingToSnapshot)
+        ));
+
+        // Branches missing locally leave nothing to capture either
+        stack.add_branch(StackBranch::try_new("gone", Some("main")).unwrap());
+        assert!(matches!(
+            create_snapshot(&repo, &stack, "empty"),
+            Err(Error::NothingToSnapshot)
+        ));
+        assert!(list_snapshots(&repo).unwrap().is_empty());
+    }
+}
@@ -1,9 +1,12 @@
 //! Stack data model representing a chain of dependent branches.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::BranchName;
+use crate::error::Error;
 
 /// A stack of dependent branches forming a PR chain.
 // TODO(long-term): For large stacks (>20 branches), consider adding a HashMap<String, usize>
@@ -32,6 +35,14 @@ impl Stack {
         self.branches.iter_mut().find(|b| b.name == name)
     }
 
+    /// Name on the remote for `name`. Untracked branches such as `main` are
+    /// assumed to share their local name.
+    #[must_use]
+    pub fn remote_name_of<'a>(&'a self, name: &'a str) -> &'a str {
+        self.find_branch(name)
+            .map_or(name, |b| b.remote_name().as_str())
+    }
+
     /// Add a new branch to the stack.
     pub fn add_branch(&mut self, branch: StackBranch) {
         self.branches.push(branch);
@@ -46,7 +57,56 @@ impl Stack {
         }
     }
 
-    /// Get all children of a branch.
+    /// Remove a branch from the stack, moving its children onto its parent,
+    /// as landing it or dropping an empty branch does.
+    pub fn splice_out(&mut self, name: &str) -> Option<StackBranch> {
+        let parent = self.find_branch(name)?.parent.clone();
+        for branch in &mut self.branches {
+            if branch.parent.as_ref().is_some_and(|p| p == name) {
+                branch.parent.clone_from(&parent);
+            }
+        }
+        self.remove_branch(name)
+    }
+
+    /// Rename a branch, keeping its children attached. A remote name equal to
+    /// the new name is dropped. Returns `false` if `old` isn't in the stack.
+    pub fn rename_branch(&mut self, old: &str, new: &BranchName) -> bool {
+        let Some(branch) = self.find_branch_mut(old) else {
+            return false;
+        };
+        branch.name = new.clone();
+        if branch.remote.as_ref() == Some(new) {
+            branch.remote = None;
+        }
+        for other in &mut self.branches {
+            if other.parent.as_ref().is_some_and(|parent| parent == old) {
+                other.parent = Some(new.clone());
+            }
+            for required in &mut other.requires {
+                if required == old {
+                    *required = new.clone();
+                }
+            }
+        }
+        true
+    }
+
+    /// Branches at the bottom of the stack: those with no parent, or whose
+    /// parent isn't tracked (typically `main`). In stack order.
+    #[must_use]
+    pub fn roots(&self) -> Vec<&StackBranch> {
+        self.branches
+            .iter()
+            .filter(|b| {
+                b.parent
+                    .as_deref()
+                    .is_none_or(|p| self.find_branch(p).is_none())
+            })
+            .collect()
+    }
+
+    /// Direct children of a branch, in stack order.
     #[must_use]
     pub fn children_of(&self, name: &str) -> Vec<&StackBranch> {
         self.branches
@@ -55,43 +115,213 @@ impl Stack {
             .collect()
     }
 
-    /// Get all descendants of a branch in topological order (parents before children).
+    /// Index the branches by name and parent, for walking the stack
+    /// repeatedly without rescanning it at every step.
+    #[must_use]
+    pub fn index(&self) -> StackIndex<'_> {
+        StackIndex::new(self)
+    }
+
+    /// Every branch with its depth below its root, depth-first in stack
+    /// order, for rendering the stack as an indented tree. Branches caught in
+    /// a parent cycle have no root and are left out.
+    #[must_use]
+    pub fn tree(&self) -> Vec<(usize, &StackBranch)> {
+        let index = self.index();
+        let mut rows = Vec::new();
+        let mut pending: Vec<(usize, &StackBranch)> =
+            self.roots().into_iter().rev().map(|b| (0, b)).collect();
+
+        while let Some((depth, branch)) = pending.pop() {
+            rows.push((depth, branch));
+            let children = index.children_of(&branch.name);
+            pending.extend(children.into_iter().rev().map(|c| (depth + 1, c)));
+        }
+        rows
+    }
+
+    /// Number of branches in the longest parent chain (0 for an empty stack).
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.tree()
+            .iter()
+            .map(|(depth, _)| depth + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// All descendants of a branch (children, grandchildren, ...), parents
+    /// before children. The branch itself is not included.
+    #[must_use]
+    pub fn descendants_of(&self, name: &str) -> Vec<&StackBranch> {
+        self.index().descendants_of(name)
+    }
+
+    /// The ancestry chain for a branch, from its root to the branch itself.
     ///
-    /// This includes children, grandchildren, etc. The branch itself is NOT included.
+    /// Empty if `name` isn't in the stack.
     #[must_use]
-    pub fn descendants(&self, name: &str) -> Vec<&StackBranch> {
-        let mut result = Vec::new();
-        let mut stack = vec![name];
+    pub fn ancestry(&self, name: &str) -> Vec<&StackBranch> {
+        self.index().ancestry(name)
+    }
+
+    /// Every branch in the same stack as `name`.
+    ///
+    /// Starts with `name`'s ancestry (root first), followed by the remaining
+    /// descendants of the root - including those on sibling branches - with
+    /// parents before children. Empty if `name` isn't in the stack.
+    #[must_use]
+    pub fn chain_through(&self, name: &str) -> Vec<&StackBranch> {
+        self.index().chain_through(name)
+    }
 
-        while let Some(current_parent) = stack.pop() {
-            for branch in &self.branches {
-                if branch.parent.as_deref() == Some(current_parent) {
-                    result.push(branch);
-                    stack.push(&branch.name);
+    /// Whether landing `name` first needs `other` to have landed: `other` is
+    /// an ancestor of `name`, or one it requires, directly or through other
+    /// branches.
+    #[must_use]
+    pub fn depends_on(&self, name: &str, other: &str) -> bool {
+        let mut seen = vec![name];
+        let mut pending = vec![name];
+        while let Some(current) = pending.pop() {
+            let Some(branch) = self.find_branch(current) else {
+                continue;
+            };
+            for next in branch.parent.iter().chain(&branch.requires) {
+                if next == other {
+                    return true;
+                }
+                if !seen.contains(&next.as_str()) {
+                    seen.push(next);
+                    pending.push(next);
                 }
             }
         }
-        result
+        false
     }
 
-    /// Get the ancestry chain for a branch (from root to the branch).
+    /// Requirements (see [`StackBranch::requires`]) that can't be met,
+    /// because the required branch can only land after the branch requiring
+    /// it - it's stacked on it, say. In stack order.
     #[must_use]
-    pub fn ancestry(&self, name: &str) -> Vec<&StackBranch> {
-        let mut chain = vec![];
-        let mut current = name;
+    pub fn requirement_conflicts(&self) -> Vec<RequirementConflict> {
+        self.branches
+            .iter()
+            .flat_map(|branch| {
+                branch
+                    .requires
+                    .iter()
+                    .filter(|required| {
+                        self.find_branch(required).is_some()
+                            && (*required == &branch.name
+                                || self.depends_on(required, &branch.name))
+                    })
+                    .map(|required| RequirementConflict {
+                        branch: branch.name.to_string(),
+                        requires: required.to_string(),
+                    })
+            })
+            .collect()
+    }
 
-        while let Some(branch) = self.find_branch(current) {
-            chain.push(branch);
-            match &branch.parent {
-                Some(parent) if self.find_branch(parent).is_some() => {
-                    current = parent;
+    /// The branches to land, in order, so that `name` lands last: each
+    /// branch comes after its parent and the branches it requires. Branches
+    /// caught in a conflicting requirement are listed once, where first
+    /// reached. Empty if `name` isn't in the stack.
+    #[must_use]
+    pub fn landing_order(&self, name: &str) -> Vec<&StackBranch> {
+        fn visit<'a>(
+            stack: &'a Stack,
+            name: &str,
+            seen: &mut Vec<&'a str>,
+            order: &mut Vec<&'a StackBranch>,
+        ) {
+            let Some(branch) = stack.find_branch(name) else {
+                return;
+            };
+            if seen.contains(&branch.name.as_str()) {
+                return;
+            }
+            seen.push(&branch.name);
+            for next in branch.parent.iter().chain(&branch.requires) {
+                visit(stack, next, seen, order);
+            }
+            order.push(branch);
+        }
+
+        let mut order = vec![];
+        visit(self, name, &mut vec![], &mut order);
+        order
+    }
+
+    /// Branches ordered so that every parent comes before its children.
+    ///
+    /// The order is stable: branches keep their current relative order
+    /// wherever the parent-first constraint allows. Parents outside the stack
+    /// (e.g. `main`) impose no constraint.
+    ///
+    /// # Errors
+    /// Returns `CyclicDependency` if following parents leads back to a branch.
+    pub fn topological_order(&self) -> crate::Result<Vec<&StackBranch>> {
+        Ok(self
+            .topological_indices()?
+            .into_iter()
+            .map(|i| &self.branches[i])
+            .collect())
+    }
+
+    /// Reorder `branches` in place so parents come before children.
+    ///
+    /// See [`Self::topological_order`]; on error the order is left unchanged.
+    ///
+    /// # Errors
+    /// Returns `CyclicDependency` if the parent links form a cycle.
+    pub fn sort_topologically(&mut self) -> crate::Result<()> {
+        let order = self.topological_indices()?;
+        let mut slots: Vec<Option<StackBranch>> = self.branches.drain(..).map(Some).collect();
+        self.branches = order.into_iter().filter_map(|i| slots[i].take()).collect();
+        Ok(())
+    }
+
+    fn topological_indices(&self) -> crate::Result<Vec<usize>> {
+        let index: HashMap<&str, usize> = self
+            .branches
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b.name.as_str(), i))
+            .collect();
+
+        let mut placed = vec![false; self.branches.len()];
+        let mut order = Vec::with_capacity(self.branches.len());
+
+        for start in 0..self.branches.len() {
+            // Walk up to the first placed (or external) ancestor, then place
+            // the walked chain root-first
+            let mut chain: Vec<usize> = vec![];
+            let mut current = Some(start);
+            while let Some(i) = current.filter(|&i| !placed[i]) {
+                if chain.contains(&i) {
+                    let mut names: Vec<&str> = chain
+                        .iter()
+                        .skip_while(|&&c| c != i)
+                        .map(|&c| self.branches[c].name.as_str())
+                        .collect();
+                    names.push(self.branches[i].name.as_str());
+                    return Err(Error::CyclicDependency(names.join(" → ")));
                 }
-                _ => break,
+                chain.push(i);
+                current = self.branches[i]
+                    .parent
+                    .as_ref()
+                    .and_then(|p| index.get(p.as_str()).copied());
+            }
+
+            for &i in chain.iter().rev() {
+                placed[i] = true;
+                order.push(i);
             }
         }
 
-        chain.reverse();
-        chain
+        Ok(order)
     }
 
     /// Check if the stack is empty.
@@ -105,6 +335,233 @@ impl Stack {
     pub fn len(&self) -> usize {
         self.branches.len()
     }
+
+    /// Compare this stack with a `newer` version of it.
+    ///
+    /// Entries follow the branch order of the stack they come from.
+    #[must_use]
+    pub fn diff(&self, newer: &Self) -> StackDiff {
+        let mut diff = StackDiff::default();
+        for old in &self.branches {
+            let Some(new) = newer.find_branch(&old.name) else {
+                diff.removed.push(old.name.to_string());
+                continue;
+            };
+            if old.parent != new.parent {
+                diff.reparented.push(ParentChange {
+                    branch: old.name.to_string(),
+                    old: old.parent.as_ref().map(ToString::to_string),
+                    new: new.parent.as_ref().map(ToString::to_string),
+                });
+            }
+            if old.pr != new.pr {
+                diff.pr_changes.push(PrChange {
+                    branch: old.name.to_string(),
+                    old: old.pr,
+                    new: new.pr,
+                });
+            }
+        }
+        diff.added = newer
+            .branches
+            .iter()
+            .filter(|b| self.find_branch(&b.name).is_none())
+            .map(|b| b.name.to_string())
+            .collect();
+        diff
+    }
+}
+
+/// Branches of a [`Stack`] indexed by name and by parent, built in one pass.
+///
+/// Walks cost time proportional to the branches they visit rather than a
+/// scan of the whole stack per step, which keeps work done for every branch
+/// (such as rendering each PR's stack comment) linear in the stack size.
+#[derive(Debug)]
+pub struct StackIndex<'a> {
+    stack: &'a Stack,
+    by_name: HashMap<&'a str, usize>,
+    /// Children of each parent name, as positions in stack order.
+    children_by_name: HashMap<&'a str, Vec<usize>>,
+    /// Position of each branch's parent, if that is in the stack.
+    parents: Vec<Option<usize>>,
+}
+
+impl<'a> StackIndex<'a> {
+    fn new(stack: &'a Stack) -> Self {
+        let mut by_name = HashMap::with_capacity(stack.branches.len());
+        let mut children_by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, branch) in stack.branches.iter().enumerate() {
+            by_name.entry(branch.name.as_str()).or_insert(i);
+            if let Some(parent) = &branch.parent {
+                children_by_name.entry(parent.as_str()).or_default().push(i);
+            }
+        }
+        let parents = stack
+            .branches
+            .iter()
+            .map(|b| b.parent.as_deref().and_then(|p| by_name.get(p).copied()))
+            .collect();
+        Self {
+            stack,
+            by_name,
+            children_by_name,
+            parents,
+        }
+    }
+
+    /// Find a branch by name.
+    #[must_use]
+    pub fn find_branch(&self, name: &str) -> Option<&'a StackBranch> {
+        self.by_name.get(name).map(|&i| &self.stack.branches[i])
+    }
+
+    /// Direct children of a branch, in stack order.
+    #[must_use]
+    pub fn children_of(&self, name: &str) -> Vec<&'a StackBranch> {
+        self.branches_at(self.child_positions(name))
+    }
+
+    /// See [`Stack::descendants_of`]. Each branch is listed once, even if the
+    /// parent links form a cycle.
+    #[must_use]
+    pub fn descendants_of(&self, name: &str) -> Vec<&'a StackBranch> {
+        self.branches_at(&self.descendant_positions(name))
+    }
+
+    /// See [`Stack::ancestry`]. A parent cycle ends the chain where it
+    /// would repeat.
+    #[must_use]
+    pub fn ancestry(&self, name: &str) -> Vec<&'a StackBranch> {
+        self.branches_at(&self.ancestry_positions(name))
+    }
+
+    /// See [`Stack::chain_through`].
+    #[must_use]
+    pub fn chain_through(&self, name: &str) -> Vec<&'a StackBranch> {
+        let mut chain = self.ancestry_positions(name);
+        let Some(&root) = chain.first() else {
+            return vec![];
+        };
+
+        let mut listed = vec![false; self.parents.len()];
+        for &i in &chain {
+            listed[i] = true;
+        }
+        let root_name = self.stack.branches[root].name.as_str();
+        chain.extend(
+            self.descendant_positions(root_name)
+                .into_iter()
+                .filter(|&i| !listed[i]),
+        );
+        self.branches_at(&chain)
+    }
+
+    fn branches_at(&self, positions: &[usize]) -> Vec<&'a StackBranch> {
+        positions.iter().map(|&i| &self.stack.branches[i]).collect()
+    }
+
+    fn child_positions(&self, name: &str) -> &[usize] {
+        self.children_by_name.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Root first, ending with `name`.
+    fn ancestry_positions(&self, name: &str) -> Vec<usize> {
+        let mut chain = vec![];
+        let mut current = self.by_name.get(name).copied();
+        while let Some(i) = current {
+            chain.push(i);
+            current = self.parents[i].filter(|_| chain.len() < self.parents.len());
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// In the order [`Stack::descendants_of`] documents.
+    fn descendant_positions(&self, name: &str) -> Vec<usize> {
+        let mut seen = vec![false; self.parents.len()];
+        let mut result = vec![];
+        let mut pending = vec![];
+        let mut children = self.child_positions(name);
+        loop {
+            for &child in children {
+                if !seen[child] {
+                    seen[child] = true;
+                    result.push(child);
+                    pending.push(child);
+                }
+            }
+            match pending.pop() {
+                Some(parent) => {
+                    children = self.child_positions(&self.stack.branches[parent].name);
+                }
+                None => return result,
+            }
+        }
+    }
+}
+
+/// A requirement that can't be met, from [`Stack::requirement_conflicts`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequirementConflict {
+    /// The branch declaring the requirement.
+    pub branch: String,
+
+    /// The branch it requires, which can't land before it.
+    pub requires: String,
+}
+
+/// Differences between two versions of a stack, from [`Stack::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StackDiff {
+    /// Branches only in the newer stack.
+    pub added: Vec<String>,
+
+    /// Branches only in the older stack.
+    pub removed: Vec<String>,
+
+    /// Branches whose parent changed.
+    pub reparented: Vec<ParentChange>,
+
+    /// Branches whose PR number changed.
+    pub pr_changes: Vec<PrChange>,
+}
+
+impl StackDiff {
+    /// Whether the two stacks have the same branches, parents and PRs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.reparented.is_empty()
+            && self.pr_changes.is_empty()
+    }
+}
+
+/// A branch moved to a different parent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParentChange {
+    /// Branch name.
+    pub branch: String,
+
+    /// Previous parent (None for a root branch).
+    pub old: Option<String>,
+
+    /// New parent (None for a root branch).
+    pub new: Option<String>,
+}
+
+/// A branch's PR number was set, cleared or replaced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrChange {
+    /// Branch name.
+    pub branch: String,
+
+    /// Previous PR number.
+    pub old: Option<u64>,
+
+    /// New PR number.
+    pub new: Option<u64>,
 }
 
 impl Default for Stack {
@@ -120,6 +577,7 @@ impl Default for Stack {
 /// - Shell metacharacters (`$`, `;`, `|`, etc.)
 /// - Invalid git branch name characters
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // Independent per-branch flags
 pub struct StackBranch {
     /// Branch name (validated).
     pub name: BranchName,
@@ -133,6 +591,64 @@ pub struct StackBranch {
 
     /// When this branch was added to the stack.
     pub created: DateTime<Utc>,
+
+    /// When this branch was last rebased by `rung sync`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_synced: Option<DateTime<Utc>>,
+
+    /// Directories this branch is expected to change (set with `rung scope`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+
+    /// Name of the branch on the remote, when it differs from the local name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<BranchName>,
+
+    /// Keep merge commits on this branch when syncing (set with `rung rebase-merges`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rebase_merges: bool,
+
+    /// Mark the branch's draft PR ready for review once its checks pass (set
+    /// by `rung submit --draft-until-green`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ready_when_green: bool,
+
+    /// Parent tip this branch was last based on, recorded when it is created
+    /// or rebased by rung. Used to spot a parent rewritten underneath it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub based_on: Option<String>,
+
+    /// GitHub login of whoever opened the branch's PR, recorded by
+    /// `rung submit` and `rung adopt`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    /// Branches this branch's commits were copied onto by `rung forward-port`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forward_ports: Vec<String>,
+
+    /// Stack branches outside the parent chain that must land before this
+    /// one, such as a branch adding a migration it uses (set with `rung
+    /// requires`). Those no longer in the stack count as landed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<BranchName>,
+
+    /// Someone else's PR branch, added as a parent by `rung rebase
+    /// --onto-pr`. rung refreshes it from the PR when syncing, and never
+    /// rebases, pushes or submits it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub frozen: bool,
+
+    /// The branch's parent landed without it being rebased (`rung merge
+    /// --no-rebase-descendants`), so it still carries the parent's commits.
+    /// The next sync replays only those after `based_on`, then clears this.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub needs_restack: bool,
+
+    /// Freeform notes on the branch, one line each (set with `rung note`),
+    /// such as what it's blocked on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
 }
 
 impl StackBranch {
@@ -144,6 +660,18 @@ impl StackBranch {
             parent,
             pr: None,
             created: Utc::now(),
+            last_synced: None,
+            paths: vec![],
+            remote: None,
+            rebase_merges: false,
+            ready_when_green: false,
+            based_on: None,
+            author: None,
+            forward_ports: vec![],
+            requires: vec![],
+            frozen: false,
+            needs_restack: false,
+            notes: vec![],
         }
     }
 
@@ -160,6 +688,57 @@ impl StackBranch {
         let parent = parent.map(BranchName::new).transpose()?;
         Ok(Self::new(name, parent))
     }
+
+    /// Name of the branch on the remote: the configured remote name, or the
+    /// local name when none is set.
+    #[must_use]
+    pub fn remote_name(&self) -> &BranchName {
+        self.remote.as_ref().unwrap_or(&self.name)
+    }
+
+    /// Name for a copy of this branch on `target`, as made by
+    /// `rung forward-port` and `rung copy`, e.g. `add-auth-to-main` (slashes
+    /// in `target` become dashes).
+    #[must_use]
+    pub fn forward_port_name(&self, target: &str) -> String {
+        format!("{}-to-{}", self.name, target.replace('/', "-"))
+    }
+
+    /// Whether this branch was already forward-ported onto `target`.
+    #[must_use]
+    pub fn is_forward_ported(&self, target: &str) -> bool {
+        self.forward_ports.iter().any(|t| t == target)
+    }
+
+    /// The most recent activity on this branch: rung creating or syncing it,
+    /// or `tip_time`, when its tip was committed (commits made with plain git
+    /// count too).
+    #[must_use]
+    pub fn last_activity(&self, tip_time: Option<DateTime<Utc>>) -> DateTime<Utc> {
+        let touched = self
+            .last_synced
+            .map_or(self.created, |synced| synced.max(self.created));
+        tip_time.map_or(touched, |tip| tip.max(touched))
+    }
+
+    /// Number of whole days since the branch's last activity.
+    #[must_use]
+    pub fn idle_days(&self, now: DateTime<Utc>, tip_time: Option<DateTime<Utc>>) -> i64 {
+        (now - self.last_activity(tip_time)).num_days().max(0)
+    }
+
+    /// Check if the branch has been idle for at least `threshold_days`.
+    ///
+    /// A threshold of 0 disables staleness tracking.
+    #[must_use]
+    pub fn is_stale(
+        &self,
+        threshold_days: u32,
+        now: DateTime<Utc>,
+        tip_time: Option<DateTime<Utc>>,
+    ) -> bool {
+        threshold_days > 0 && self.idle_days(now, tip_time) >= i64::from(threshold_days)
+    }
 }
 
 /// Synchronization state of a branch relative to its parent.
@@ -181,6 +760,10 @@ pub enum BranchState {
         files: Vec<String>,
     },
 
+    /// Parent was rewritten (e.g. amended) since the branch was based on it,
+    /// so the branch still carries the parent's old commits.
+    NeedsRestack,
+
     /// Parent branch was deleted or renamed.
     Detached,
 }
@@ -189,7 +772,7 @@ impl BranchState {
     /// Check if the branch needs syncing.
     #[must_use]
     pub const fn needs_sync(&self) -> bool {
-        matches!(self, Self::Diverged { .. })
+        matches!(self, Self::Diverged { .. } | Self::NeedsRestack)
     }
 
     /// Check if the branch has conflicts.
@@ -210,6 +793,42 @@ impl BranchState {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_stack_diff() {
+        let mut old = Stack::new();
+        old.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        old.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        old.add_branch(StackBranch::try_new("c", Some("b")).unwrap());
+        assert!(old.diff(&old.clone()).is_empty());
+
+        // "a" merged: "b" moves to main and gets a PR, "d" is new
+        let mut new = old.clone();
+        new.remove_branch("a");
+        new.find_branch_mut("b").unwrap().parent = Some(BranchName::new("main").unwrap());
+        new.find_branch_mut("b").unwrap().pr = Some(7);
+        new.add_branch(StackBranch::try_new("d", Some("c")).unwrap());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec!["d"]);
+        assert_eq!(diff.removed, vec!["a"]);
+        assert_eq!(
+            diff.reparented,
+            vec![ParentChange {
+                branch: "b".into(),
+                old: Some("a".into()),
+                new: Some("main".into()),
+            }]
+        );
+        assert_eq!(
+            diff.pr_changes,
+            vec![PrChange {
+                branch: "b".into(),
+                old: None,
+                new: Some(7),
+            }]
+        );
+    }
+
     #[test]
     fn test_stack_operations() {
         let mut stack = Stack::new();
@@ -251,7 +870,7 @@ mod tests {
         stack.add_branch(StackBranch::try_new("d", Some("a")).unwrap());
 
         // Descendants of "a" should be b, c, d (in some order based on traversal)
-        let descendants = stack.descendants("a");
+        let descendants = stack.descendants_of("a");
         assert_eq!(descendants.len(), 3);
         let names: Vec<&str> = descendants.iter().map(|b| b.name.as_str()).collect();
         assert!(names.contains(&"b"));
@@ -259,15 +878,366 @@ mod tests {
         assert!(names.contains(&"d"));
 
         // Descendants of "b" should only be c
-        let descendants = stack.descendants("b");
+        let descendants = stack.descendants_of("b");
         assert_eq!(descendants.len(), 1);
         assert_eq!(descendants[0].name, "c");
 
         // Descendants of "c" (leaf) should be empty
-        let descendants = stack.descendants("c");
+        let descendants = stack.descendants_of("c");
         assert!(descendants.is_empty());
     }
 
+    /// Small deterministic PRNG so the property tests need no extra crates.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            usize::try_from(self.0 % n as u64).unwrap()
+        }
+    }
+
+    /// A random forest of branches, shuffled so children often precede parents.
+    fn random_stack(rng: &mut XorShift, size: usize) -> Stack {
+        let mut branches: Vec<StackBranch> = (0..size)
+            .map(|i| {
+                // Parents only point at lower indices, so this is always a DAG
+                let parent = match rng.below(i + 1) {
+                    0 => "main".to_string(),
+                    p => format!("b{}", p - 1),
+                };
+                StackBranch::try_new(format!("b{i}"), Some(parent)).unwrap()
+            })
+            .collect();
+        for i in (1..branches.len()).rev() {
+            branches.swap(i, rng.below(i + 1));
+        }
+        Stack { branches }
+    }
+
+    fn position(order: &[&StackBranch], name: &str) -> Option<usize> {
+        order.iter().position(|b| b.name == name)
+    }
+
+    #[test]
+    fn test_topological_order_random_dags() {
+        for seed in 1..=200 {
+            let mut rng = XorShift(seed);
+            let size = rng.below(12) + 1;
+            let stack = random_stack(&mut rng, size);
+            let order = stack.topological_order().unwrap();
+
+            // Same branches, each exactly once
+            assert_eq!(order.len(), stack.len(), "seed {seed}");
+            for branch in &stack.branches {
+                assert!(position(&order, &branch.name).is_some(), "seed {seed}");
+            }
+
+            // Every in-stack parent precedes its child
+            for (i, branch) in order.iter().enumerate() {
+                if let Some(parent) = branch.parent.as_deref() {
+                    if let Some(p) = position(&order, parent) {
+                        assert!(p < i, "seed {seed}: {parent} after {}", branch.name);
+                    }
+                }
+            }
+
+            // Sorting is idempotent and leaves a sorted stack untouched
+            let mut sorted = stack.clone();
+            sorted.sort_topologically().unwrap();
+            let names: Vec<&str> = sorted.branches.iter().map(|b| b.name.as_str()).collect();
+            let expected: Vec<&str> = order.iter().map(|b| b.name.as_str()).collect();
+            assert_eq!(names, expected, "seed {seed}");
+            let again: Vec<String> = sorted
+                .topological_order()
+                .unwrap()
+                .iter()
+                .map(|b| b.name.to_string())
+                .collect();
+            assert_eq!(again, expected, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_topological_order_is_stable() {
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("x", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("y", Some("main")).unwrap());
+
+        stack.sort_topologically().unwrap();
+        let names: Vec<&str> = stack.branches.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["x", "a", "b", "y"]);
+    }
+
+    #[test]
+    fn test_topological_order_detects_cycles() {
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("root", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("a", Some("c")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("b")).unwrap());
+
+        let err = stack.sort_topologically().unwrap_err();
+        assert!(matches!(&err, Error::CyclicDependency(path) if path == "a → c → b → a"));
+        // Order is untouched on error
+        assert_eq!(stack.branches[0].name, "root");
+        assert_eq!(stack.branches[1].name, "a");
+
+        let mut self_parent = Stack::new();
+        self_parent.add_branch(StackBranch::try_new("loop", Some("loop")).unwrap());
+        assert!(self_parent.topological_order().is_err());
+    }
+
+    #[test]
+    fn test_remote_names() {
+        let mut stack = Stack::new();
+        let mut feature = StackBranch::try_new("me/feature", Some("main")).unwrap();
+        feature.remote = Some(BranchName::new("feature").unwrap());
+        stack.add_branch(feature);
+        stack.add_branch(StackBranch::try_new("me/child", Some("me/feature")).unwrap());
+
+        assert_eq!(stack.remote_name_of("me/feature"), "feature");
+        assert_eq!(stack.remote_name_of("me/child"), "me/child");
+        assert_eq!(stack.remote_name_of("main"), "main");
+
+        // Only written when set, and optional when reading older files
+        let json = serde_json::to_string(&stack).unwrap();
+        assert_eq!(json.matches("\"remote\"").count(), 1);
+        let old = r#"{"branches":[{"name":"a","parent":null,"created":"2024-01-01T00:00:00Z"}]}"#;
+        let parsed: Stack = serde_json::from_str(old).unwrap();
+        assert!(parsed.branches[0].remote.is_none());
+        assert!(parsed.branches[0].author.is_none());
+        assert_eq!(parsed.branches[0].remote_name(), "a");
+        assert!(!json.contains("\"author\""));
+    }
+
+    #[test]
+    fn test_splice_out() {
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("a")).unwrap());
+
+        assert!(stack.splice_out("a").is_some());
+        assert!(stack.splice_out("a").is_none());
+        let parents: Vec<_> = stack
+            .branches
+            .iter()
+            .map(|b| (b.name.as_str(), b.parent.as_deref()))
+            .collect();
+        assert_eq!(parents, [("b", Some("main")), ("c", Some("main"))]);
+    }
+
+    #[test]
+    fn test_rename_branch() {
+        let mut stack = Stack::new();
+        let mut feature = StackBranch::try_new("feature", Some("main")).unwrap();
+        feature.remote = Some(BranchName::new("renamed").unwrap());
+        stack.add_branch(feature);
+        stack.add_branch(StackBranch::try_new("child", Some("feature")).unwrap());
+        let mut other = StackBranch::try_new("other", Some("main")).unwrap();
+        other.requires = vec![BranchName::new("feature").unwrap()];
+        stack.add_branch(other);
+
+        let renamed = BranchName::new("renamed").unwrap();
+        assert!(stack.rename_branch("feature", &renamed));
+        assert!(stack.find_branch("feature").is_none());
+        assert!(stack.find_branch("renamed").unwrap().remote.is_none());
+        assert_eq!(
+            stack.find_branch("child").unwrap().parent.as_deref(),
+            Some("renamed")
+        );
+        assert_eq!(
+            stack.find_branch("other").unwrap().parent.as_deref(),
+            Some("main")
+        );
+        assert_eq!(
+            stack.find_branch("other").unwrap().requires,
+            vec![renamed.clone()]
+        );
+        assert!(!stack.rename_branch("missing", &renamed));
+    }
+
+    #[test]
+    fn test_requirements() {
+        // main ← migration ← backfill; main ← api, which requires backfill
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("api", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("migration", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("backfill", Some("migration")).unwrap());
+        stack.find_branch_mut("api").unwrap().requires = vec![BranchName::new("backfill").unwrap()];
+
+        assert!(stack.depends_on("api", "migration"));
+        assert!(stack.depends_on("backfill", "migration"));
+        assert!(!stack.depends_on("migration", "api"));
+        assert!(stack.requirement_conflicts().is_empty());
+        let order: Vec<&str> = stack
+            .landing_order("api")
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect();
+        assert_eq!(order, ["migration", "backfill", "api"]);
+        assert!(stack.landing_order("missing").is_empty());
+
+        // Requiring a descendant can't be met
+        stack.find_branch_mut("migration").unwrap().requires =
+            vec![BranchName::new("api").unwrap()];
+        assert_eq!(
+            stack.requirement_conflicts(),
+            [
+                RequirementConflict {
+                    branch: "api".into(),
+                    requires: "backfill".into(),
+                },
+                RequirementConflict {
+                    branch: "migration".into(),
+                    requires: "api".into(),
+                },
+            ]
+        );
+        assert_eq!(stack.landing_order("api").len(), 3);
+
+        // Requirements that landed are met
+        stack.find_branch_mut("migration").unwrap().requires =
+            vec![BranchName::new("merged").unwrap()];
+        assert!(stack.requirement_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_forward_ports() {
+        let mut branch = StackBranch::try_new("add-auth", Some("release/1.2")).unwrap();
+        assert_eq!(branch.forward_port_name("main"), "add-auth-to-main");
+        assert_eq!(
+            branch.forward_port_name("release/2.0"),
+            "add-auth-to-release-2.0"
+        );
+
+        assert!(!branch.is_forward_ported("main"));
+        branch.forward_ports.push("main".into());
+        assert!(branch.is_forward_ported("main"));
+        assert!(!branch.is_forward_ported("release/2.0"));
+    }
+
+    #[test]
+    fn test_roots() {
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("hotfix", None::<String>).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("develop")).unwrap());
+
+        let names: Vec<&str> = stack.roots().iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "hotfix", "c"]);
+        assert!(Stack::new().roots().is_empty());
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut stack = Stack::new();
+        // main → a → b
+        //          ↘ c
+        // main → other
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("other", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("a")).unwrap());
+
+        let rows: Vec<(usize, &str)> = stack
+            .tree()
+            .into_iter()
+            .map(|(depth, b)| (depth, b.name.as_str()))
+            .collect();
+        assert_eq!(rows, vec![(0, "a"), (1, "b"), (1, "c"), (0, "other")]);
+        assert_eq!(stack.depth(), 2);
+        assert_eq!(Stack::new().depth(), 0);
+    }
+
+    #[test]
+    fn test_chain_through() {
+        let mut stack = Stack::new();
+        // main → a → b → c
+        //          ↘ d
+        // main → other
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("b")).unwrap());
+        stack.add_branch(StackBranch::try_new("d", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("other", Some("main")).unwrap());
+
+        let names = |name: &str| -> Vec<String> {
+            stack
+                .chain_through(name)
+                .iter()
+                .map(|b| b.name.to_string())
+                .collect()
+        };
+
+        // Ancestry first, then the rest of the root's tree
+        let chain = names("b");
+        assert_eq!(&chain[..2], ["a", "b"]);
+        assert_eq!(chain.len(), 4);
+        assert!(chain.contains(&"c".to_string()) && chain.contains(&"d".to_string()));
+
+        assert_eq!(names("c")[..3], ["a", "b", "c"]);
+        assert_eq!(names("other"), vec!["other"]);
+        assert!(names("missing").is_empty());
+    }
+
+    #[test]
+    fn test_index_walks_terminate_on_cycles() {
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("a", Some("c")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("b")).unwrap());
+        let index = stack.index();
+
+        let names = |branches: Vec<&StackBranch>| -> Vec<String> {
+            branches.iter().map(|b| b.name.to_string()).collect()
+        };
+        assert_eq!(names(index.ancestry("c")), ["a", "b", "c"]);
+        assert_eq!(names(index.descendants_of("a")), ["b", "c", "a"]);
+        assert_eq!(index.chain_through("b").len(), 3);
+        assert_eq!(names(index.children_of("c")), ["a"]);
+    }
+
+    #[test]
+    fn test_traversals_ignore_untracked_parents() {
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+
+        assert!(stack.children_of("main").iter().any(|b| b.name == "a"));
+        assert_eq!(stack.ancestry("a").len(), 1);
+        assert!(stack.ancestry("main").is_empty());
+        assert!(stack.descendants_of("a").is_empty());
+    }
+
+    #[test]
+    fn test_staleness() {
+        let mut branch = StackBranch::try_new("feature/old", Some("main")).unwrap();
+        let now = branch.created + chrono::Duration::days(45);
+
+        assert_eq!(branch.idle_days(now, None), 45);
+        assert!(branch.is_stale(30, now, None));
+        assert!(!branch.is_stale(0, now, None));
+
+        // So does a recent commit, made with or without rung
+        let committed = Some(now - chrono::Duration::days(1));
+        assert_eq!(branch.idle_days(now, committed), 1);
+        assert!(!branch.is_stale(30, now, committed));
+        // An old tip doesn't make the branch look older than it is
+        let old_tip = Some(branch.created - chrono::Duration::days(90));
+        assert_eq!(branch.idle_days(now, old_tip), 45);
+
+        // A recent sync resets the idle clock
+        branch.last_synced = Some(now - chrono::Duration::days(2));
+        assert_eq!(branch.idle_days(now, None), 2);
+        assert!(!branch.is_stale(30, now, None));
+    }
+
     #[test]
     fn test_branch_state() {
         assert!(BranchState::Synced.is_healthy());
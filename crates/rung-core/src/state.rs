@@ -2,42 +2,90 @@
 
 use std::collections::VecDeque;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::archive::StackArchive;
+use crate::ci::CiCache;
+use crate::commit_index::CommitIndex;
+use crate::config::Config;
 use crate::error::{Error, Result};
-use crate::stack::Stack;
+use crate::landed::{Landing, Landings};
+use crate::operations::{Operation, SyncConflict};
+use crate::pr_cache::PrCache;
+use crate::stack::{Stack, StackBranch};
+use crate::status_cache::StatusCache;
+use crate::summary::Summary;
+use crate::sync::SyncMark;
 
 /// Manages the .git/rung/ directory state.
 #[derive(Debug)]
 pub struct State {
     /// Path to the .git/rung/ directory.
     rung_dir: PathBuf,
+    /// Path to the checked-in team config in this worktree.
+    team_config_path: PathBuf,
 }
 
 impl State {
     /// File names within .git/rung/
     const STACK_FILE: &'static str = "stack.json";
-    #[allow(dead_code)]
+    const STACK_BACKUP_FILE: &'static str = "stack.json.bak";
+    const STACK_CORRUPT_FILE: &'static str = "stack.json.corrupt";
+    /// Stack copy within a backup directory. Can't collide with a branch
+    /// file, as ref names never start with a dot.
+    const BACKUP_STACK_FILE: &'static str = ".stack.json";
+    /// Range-diffs of the branches a sync rebased, within its backup.
+    const BACKUP_RANGE_DIFF_FILE: &'static str = ".range-diff";
+    /// Name of the command that took a backup, within its directory.
+    const BACKUP_OPERATION_FILE: &'static str = ".operation";
+    /// Marks a backup whose branch files are named by [`escape_file_name`].
+    /// Older backups turned `/` into `-` instead.
+    const BACKUP_ESCAPED_FILE: &'static str = ".escaped";
     const CONFIG_FILE: &'static str = "config.toml";
     const SYNC_STATE_FILE: &'static str = "sync_state";
+    const CI_CACHE_FILE: &'static str = "ci.json";
+    const STATUS_CACHE_FILE: &'static str = "status.json";
+    const SYNC_MARK_FILE: &'static str = "last_sync.json";
+    const PR_CACHE_FILE: &'static str = "prs.json";
+    const COMMIT_INDEX_FILE: &'static str = "commits.json";
+    const LANDED_FILE: &'static str = "landed.json";
+    const SUMMARY_FILE: &'static str = "summary.json";
+    const OPERATIONS_FILE: &'static str = "operations.jsonl";
+    const ORIGIN_FILE: &'static str = "origin";
     const REFS_DIR: &'static str = "refs";
+    const ARCHIVES_DIR: &'static str = "archives";
 
     /// Create a new State instance for the given repository.
     ///
+    /// In a linked worktree (`git worktree add`), `.git` is a file naming the
+    /// worktree's own git directory. State lives in the main repository's
+    /// instead, so every worktree shares one stack; which branch is checked
+    /// out stays up to each worktree.
+    ///
     /// # Errors
-    /// Returns error if the path doesn't contain a .git directory.
+    /// Returns error if the path isn't in a git repository.
     pub fn new(repo_path: impl AsRef<Path>) -> Result<Self> {
-        let git_dir = repo_path.as_ref().join(".git");
-        if !git_dir.exists() {
-            return Err(Error::NotARepository);
-        }
+        let repo = rung_git::Repository::open(repo_path).map_err(|_| Error::NotARepository)?;
+        Ok(Self::for_repository(&repo))
+    }
 
-        Ok(Self {
-            rung_dir: git_dir.join("rung"),
-        })
+    /// Create a State for an open repository.
+    ///
+    /// Unlike [`Self::new`], this finds the git directory from the repository
+    /// itself, so it works for bare clones and for repositories opened
+    /// through `GIT_DIR` and `GIT_WORK_TREE`. Without a working tree there's
+    /// no checked-in team config.
+    #[must_use]
+    pub fn for_repository(repo: &rung_git::Repository) -> Self {
+        let git_dir = repo.git_dir();
+        Self {
+            rung_dir: repo.common_dir().join("rung"),
+            team_config_path: repo.workdir().unwrap_or(git_dir).join(Config::TEAM_PATH),
+        }
     }
 
     /// Initialize the .git/rung/ directory structure.
@@ -74,28 +122,121 @@ impl State {
         self.rung_dir.join(Self::STACK_FILE)
     }
 
+    fn stack_backup_path(&self) -> PathBuf {
+        self.rung_dir.join(Self::STACK_BACKUP_FILE)
+    }
+
     /// Load the stack from disk.
     ///
     /// # Errors
-    /// Returns error if file doesn't exist or can't be parsed.
+    /// Returns error if file doesn't exist, or `CorruptStack` if it can't be parsed.
     pub fn load_stack(&self) -> Result<Stack> {
         if !self.is_initialized() {
             return Err(Error::NotInitialized);
         }
 
-        let content = fs::read_to_string(self.stack_path())?;
-        let stack: Stack = serde_json::from_str(&content)?;
+        let path = self.stack_path();
+        let content = fs::read_to_string(&path)?;
+        let mut stack: Stack = serde_json::from_str(&content).map_err(|e| Error::CorruptStack {
+            file: path,
+            message: e.to_string(),
+        })?;
+
+        // Hand-edited or imported stacks may list children first. A cyclic
+        // stack is still returned so `rung doctor` can report it; anything
+        // relying on the order fails via `Stack::topological_order`.
+        let _ = stack.sort_topologically();
         Ok(stack)
     }
 
     /// Save the stack to disk.
     ///
+    /// Branches are written parents-first. The write is atomic (temp file +
+    /// rename), and the version being replaced is kept as `stack.json.bak`
+    /// for [`Self::restore_stack_backup`].
+    ///
     /// # Errors
-    /// Returns error if serialization or write fails.
+    /// Returns `CyclicDependency` if the parents form a cycle, or an error if
+    /// serialization or write fails.
     pub fn save_stack(&self, stack: &Stack) -> Result<()> {
-        let content = serde_json::to_string_pretty(stack)?;
-        fs::write(self.stack_path(), content)?;
-        Ok(())
+        let mut stack = stack.clone();
+        stack.sort_topologically()?;
+        let content = serde_json::to_string_pretty(&stack)?;
+        let path = self.stack_path();
+
+        // Never replace a good backup with a damaged file
+        if let Ok(previous) = fs::read_to_string(&path) {
+            if serde_json::from_str::<Stack>(&previous).is_ok() {
+                write_atomic(&self.stack_backup_path(), &previous)?;
+            }
+        }
+
+        write_atomic(&path, &content)?;
+        self.refresh_summary(Some(&stack))
+    }
+
+    /// Check whether a previous version of the stack is available.
+    #[must_use]
+    pub fn has_stack_backup(&self) -> bool {
+        self.stack_backup_path().exists()
+    }
+
+    /// Replace the stack file with the previous version kept by [`Self::save_stack`].
+    ///
+    /// The replaced file is moved to `stack.json.corrupt` for inspection.
+    ///
+    /// # Errors
+    /// Returns `NoBackupFound` without a backup, or an error if the backup
+    /// itself can't be parsed.
+    pub fn restore_stack_backup(&self) -> Result<Stack> {
+        let (stack, content) = read_stack_copy(&self.stack_backup_path())?;
+
+        let path = self.stack_path();
+        if path.exists() {
+            fs::rename(&path, self.rung_dir.join(Self::STACK_CORRUPT_FILE))?;
+        }
+        write_atomic(&path, &content)?;
+        self.refresh_summary(Some(&stack))?;
+        Ok(stack)
+    }
+
+    /// Load the previous version of the stack kept by [`Self::save_stack`],
+    /// without restoring it.
+    ///
+    /// # Errors
+    /// Returns `NoBackupFound` without a backup, or an error if it can't be parsed.
+    pub fn load_previous_stack(&self) -> Result<Stack> {
+        read_stack_copy(&self.stack_backup_path()).map(|(stack, _)| stack)
+    }
+
+    // === Config operations ===
+
+    /// Path of the repository config, `.git/rung/config.toml`.
+    #[must_use]
+    pub fn config_path(&self) -> PathBuf {
+        self.rung_dir.join(Self::CONFIG_FILE)
+    }
+
+    /// Path of the team config checked in to the worktree, `.rung/team.toml`.
+    #[must_use]
+    pub fn team_config_path(&self) -> &Path {
+        &self.team_config_path
+    }
+
+    /// Load the repository config, falling back to defaults if none exists.
+    ///
+    /// It's layered over the team config ([`Self::team_config_path`]), and
+    /// `[orgs.*]` sections from the user-wide config ([`Config::user_path`])
+    /// are added to the repository's own.
+    ///
+    /// # Errors
+    /// Returns error if any config file exists but can't be parsed.
+    pub fn load_config(&self) -> Result<Config> {
+        let mut config = Config::load_over_team(self.config_path(), &self.team_config_path)?;
+        if let Some(user_path) = Config::user_path() {
+            config.merge_user_orgs(Config::load(user_path)?);
+        }
+        Ok(config)
     }
 
     // === Sync state operations ===
@@ -130,8 +271,8 @@ impl State {
     /// Returns error if serialization or write fails.
     pub fn save_sync_state(&self, state: &SyncState) -> Result<()> {
         let content = serde_json::to_string_pretty(state)?;
-        fs::write(self.sync_state_path(), content)?;
-        Ok(())
+        write_atomic(&self.sync_state_path(), &content)?;
+        self.refresh_summary(None)
     }
 
     /// Clear sync state (called when sync completes or aborts).
@@ -143,7 +284,7 @@ impl State {
         if path.exists() {
             fs::remove_file(path)?;
         }
-        Ok(())
+        self.refresh_summary(None)
     }
 
     // === Backup operations ===
@@ -152,20 +293,31 @@ impl State {
         self.rung_dir.join(Self::REFS_DIR)
     }
 
-    /// Create a backup of branch refs.
+    /// Create a backup of branch refs before `operation` (the command's name,
+    /// such as `sync` or `reword`) rewrites them, along with a copy of the
+    /// current stack.
     ///
-    /// Returns the backup ID (timestamp).
+    /// Returns the backup ID (timestamp). A second backup within the same
+    /// second gets the next free one, so it never overwrites the first.
     ///
     /// # Errors
     /// Returns error if directory creation or file write fails.
-    pub fn create_backup(&self, branches: &[(&str, &str)]) -> Result<String> {
-        let backup_id = Utc::now().timestamp().to_string();
+    pub fn create_backup(&self, operation: &str, branches: &[(&str, &str)]) -> Result<String> {
+        let mut timestamp = Utc::now().timestamp();
+        while self.refs_dir().join(timestamp.to_string()).exists() {
+            timestamp += 1;
+        }
+        let backup_id = timestamp.to_string();
         let backup_dir = self.refs_dir().join(&backup_id);
         fs::create_dir_all(&backup_dir)?;
+        fs::write(backup_dir.join(Self::BACKUP_OPERATION_FILE), operation)?;
+        fs::write(backup_dir.join(Self::BACKUP_ESCAPED_FILE), "")?;
 
         for (branch_name, commit_sha) in branches {
-            let safe_name = branch_name.replace('/', "-");
-            fs::write(backup_dir.join(safe_name), commit_sha)?;
+            fs::write(backup_dir.join(escape_file_name(branch_name)), commit_sha)?;
+        }
+        if let Ok(stack) = fs::read_to_string(self.stack_path()) {
+            fs::write(backup_dir.join(Self::BACKUP_STACK_FILE), stack)?;
         }
 
         Ok(backup_id)
@@ -197,6 +349,32 @@ impl State {
             .ok_or(Error::NoBackupFound)
     }
 
+    /// IDs of the sync backups kept, oldest first.
+    #[must_use]
+    pub fn backup_ids(&self) -> Vec<String> {
+        let mut timestamps: Vec<i64> = fs::read_dir(self.refs_dir())
+            .into_iter()
+            .flatten()
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().to_str()?.parse::<i64>().ok())
+            .collect();
+        timestamps.sort_unstable();
+        timestamps.iter().map(ToString::to_string).collect()
+    }
+
+    /// The newest backup taken at or before `at`.
+    ///
+    /// # Errors
+    /// Returns `NoBackupFound` if there are no backups that old.
+    pub fn backup_at(&self, at: DateTime<Utc>) -> Result<String> {
+        self.backup_ids()
+            .into_iter()
+            .rev()
+            .find(|id| id.parse::<i64>().is_ok_and(|ts| ts <= at.timestamp()))
+            .ok_or(Error::NoBackupFound)
+    }
+
     /// Load a backup's branch refs.
     ///
     /// Returns a vec of (`branch_name`, `commit_sha`) pairs.
@@ -209,18 +387,21 @@ impl State {
             return Err(Error::NoBackupFound);
         }
 
+        let escaped = backup_dir.join(Self::BACKUP_ESCAPED_FILE).exists();
         let mut refs = vec![];
         for entry in fs::read_dir(&backup_dir)? {
             let entry = entry?;
-            if entry.path().is_file() {
+            // Branch files never start with a dot, unlike the backup's other files
+            let is_branch = !entry.file_name().to_string_lossy().starts_with('.');
+            if entry.path().is_file() && is_branch {
                 let name = entry
                     .file_name()
                     .to_str()
+                    .map(|file| backup_branch_name(file, escaped))
                     .ok_or_else(|| Error::StateParseError {
                         file: entry.path(),
                         message: "invalid filename".into(),
-                    })?
-                    .replace('-', "/");
+                    })?;
                 let sha = fs::read_to_string(entry.path())?.trim().to_string();
                 refs.push((name, sha));
             }
@@ -229,6 +410,79 @@ impl State {
         Ok(refs)
     }
 
+    /// Load the stack as it was when a backup was taken.
+    ///
+    /// # Errors
+    /// Returns `NoBackupFound` if the backup doesn't exist or predates stack
+    /// copies, or an error if the copy can't be parsed.
+    pub fn load_backup_stack(&self, backup_id: &str) -> Result<Stack> {
+        let path = self
+            .refs_dir()
+            .join(backup_id)
+            .join(Self::BACKUP_STACK_FILE);
+        read_stack_copy(&path).map(|(stack, _)| stack)
+    }
+
+    /// The command that took a backup, or `None` for backups from before
+    /// rung recorded it (those were all taken by `rung sync`).
+    #[must_use]
+    pub fn backup_operation(&self, backup_id: &str) -> Option<String> {
+        let path = self
+            .refs_dir()
+            .join(backup_id)
+            .join(Self::BACKUP_OPERATION_FILE);
+        fs::read_to_string(path)
+            .ok()
+            .map(|operation| operation.trim().to_string())
+    }
+
+    /// Drop a branch from a backup once it's been restored, deleting the
+    /// backup when no branches are left in it.
+    ///
+    /// # Errors
+    /// Returns error if the backup can't be read or changed.
+    pub fn remove_from_backup(&self, backup_id: &str, branch: &str) -> Result<()> {
+        let backup_dir = self.refs_dir().join(backup_id);
+        let escaped = backup_dir.join(Self::BACKUP_ESCAPED_FILE).exists();
+        let path = backup_dir.join(backup_file_name(branch, escaped));
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        if self.load_backup(backup_id)?.is_empty() {
+            self.delete_backup(backup_id)?;
+        }
+        Ok(())
+    }
+
+    /// Store the range-diffs of the branches a sync rebased with its backup.
+    ///
+    /// # Errors
+    /// Returns `NoBackupFound` if the backup doesn't exist, or an error if the
+    /// file can't be written.
+    pub fn save_range_diff(&self, backup_id: &str, range_diff: &str) -> Result<()> {
+        let backup_dir = self.refs_dir().join(backup_id);
+        if !backup_dir.exists() {
+            return Err(Error::NoBackupFound);
+        }
+        write_atomic(&backup_dir.join(Self::BACKUP_RANGE_DIFF_FILE), range_diff)
+    }
+
+    /// Load the range-diffs stored with a backup.
+    ///
+    /// # Errors
+    /// Returns `NoBackupFound` if the backup has none, or an error if the
+    /// file can't be read.
+    pub fn load_range_diff(&self, backup_id: &str) -> Result<String> {
+        let path = self
+            .refs_dir()
+            .join(backup_id)
+            .join(Self::BACKUP_RANGE_DIFF_FILE);
+        if !path.exists() {
+            return Err(Error::NoBackupFound);
+        }
+        Ok(fs::read_to_string(path)?)
+    }
+
     /// Delete a backup.
     ///
     /// # Errors
@@ -241,15 +495,33 @@ impl State {
         Ok(())
     }
 
-    /// Clean up old backups, keeping only the most recent N.
+    /// Number of sync backups kept.
+    #[must_use]
+    pub fn backup_count(&self) -> usize {
+        fs::read_dir(self.refs_dir())
+            .into_iter()
+            .flatten()
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.path().is_dir())
+            .filter(|e| {
+                e.file_name()
+                    .to_str()
+                    .is_some_and(|s| s.parse::<i64>().is_ok())
+            })
+            .count()
+    }
+
+    /// Clean up old backups, keeping only the most recent N, plus the one an
+    /// in-progress sync may need to abort to. Returns how many were removed.
     ///
     /// # Errors
     /// Returns error if cleanup fails.
-    pub fn cleanup_backups(&self, keep: usize) -> Result<()> {
+    pub fn cleanup_backups(&self, keep: usize) -> Result<usize> {
         let refs_dir = self.refs_dir();
         if !refs_dir.exists() {
-            return Ok(());
+            return Ok(0);
         }
+        let in_use = self.load_sync_state().ok().map(|s| s.backup_id);
 
         let mut backups: Vec<_> = fs::read_dir(&refs_dir)?
             .filter_map(std::result::Result::ok)
@@ -264,12 +536,564 @@ impl State {
 
         backups.sort_by_key(|(ts, _)| std::cmp::Reverse(*ts));
 
-        for (_, path) in backups.into_iter().skip(keep) {
-            fs::remove_dir_all(path)?;
+        let mut removed = 0;
+        for (ts, path) in backups.into_iter().skip(keep) {
+            if in_use.as_deref() != Some(ts.to_string().as_str()) {
+                fs::remove_dir_all(path)?;
+                removed += 1;
+            }
         }
 
+        Ok(removed)
+    }
+
+    // === Archive operations ===
+
+    fn archives_dir(&self) -> PathBuf {
+        self.rung_dir.join(Self::ARCHIVES_DIR)
+    }
+
+    fn archive_path(&self, name: &str) -> PathBuf {
+        self.archives_dir()
+            .join(format!("{}.json", escape_file_name(name)))
+    }
+
+    /// Where the archive named `name` is stored, if it exists. Archives from
+    /// before names were escaped turned `/` into `-`, so a file counts only if
+    /// the name recorded in it matches.
+    fn existing_archive_path(&self, name: &str) -> Option<PathBuf> {
+        let legacy = self
+            .archives_dir()
+            .join(format!("{}.json", name.replace('/', "-")));
+        [self.archive_path(name), legacy]
+            .into_iter()
+            .find(|path| archive_name_in(path).is_some_and(|stored| stored == name))
+    }
+
+    /// Load cached CI results, applying the `[cache]` settings.
+    ///
+    /// A missing or unreadable cache is treated as empty. Results past the
+    /// TTL are dropped from disk as well, and with caching disabled any
+    /// leftover cache file is removed.
+    #[must_use]
+    pub fn load_ci_cache(&self) -> CiCache {
+        let config = self.load_config().unwrap_or_default().cache;
+        let path = self.rung_dir.join(Self::CI_CACHE_FILE);
+        if !config.enabled {
+            let _ = fs::remove_file(&path);
+            return CiCache::default();
+        }
+
+        let mut cache: CiCache = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        if config.ttl_hours > 0 {
+            let cutoff = Utc::now() - chrono::Duration::hours(i64::from(config.ttl_hours));
+            if cache.expire_before(cutoff) {
+                let _ = self.save_ci_cache(&cache);
+            }
+        }
+        cache
+    }
+
+    /// Save cached CI results. Does nothing when caching is disabled.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn save_ci_cache(&self, cache: &CiCache) -> Result<()> {
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(cache)?;
+        write_atomic(&self.rung_dir.join(Self::CI_CACHE_FILE), &content)
+    }
+
+    /// Load cached branch states. A missing or unreadable cache is treated as
+    /// empty, and with caching disabled any leftover cache file is removed.
+    #[must_use]
+    pub fn load_status_cache(&self) -> StatusCache {
+        let path = self.rung_dir.join(Self::STATUS_CACHE_FILE);
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            let _ = fs::remove_file(&path);
+            return StatusCache::default();
+        }
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save cached branch states. Does nothing when caching is disabled.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn save_status_cache(&self, cache: &StatusCache) -> Result<()> {
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(cache)?;
+        write_atomic(&self.rung_dir.join(Self::STATUS_CACHE_FILE), &content)?;
+        self.refresh_summary(None)
+    }
+
+    // === Editor summary ===
+
+    /// Rewrite `summary.json` from the stack (`stack`, or else the saved
+    /// one), leaving the file and its mtime alone when nothing in it changed.
+    /// Branch states come from the status cache, where still current.
+    fn refresh_summary(&self, stack: Option<&Stack>) -> Result<()> {
+        let loaded;
+        let stack = match stack {
+            Some(stack) => stack,
+            None => match self.load_stack() {
+                Ok(stack) => {
+                    loaded = stack;
+                    &loaded
+                }
+                Err(_) => return Ok(()),
+            },
+        };
+        let repo = self
+            .rung_dir
+            .parent()
+            .and_then(|git_dir| rung_git::Repository::open(git_dir).ok());
+        let status = self.load_status_cache();
+        let state_of = |branch: &StackBranch| {
+            let key = StatusCache::key(repo.as_ref()?, stack, branch)?;
+            Some(status.get(&branch.name, &key)?.state.clone())
+        };
+        let summary = Summary::new(stack, state_of, self.is_sync_in_progress());
+
+        let content = serde_json::to_string_pretty(&summary)?;
+        let path = self.rung_dir.join(Self::SUMMARY_FILE);
+        if fs::read_to_string(&path).is_ok_and(|current| current == content) {
+            return Ok(());
+        }
+        write_atomic(&path, &content)
+    }
+
+    /// Load the last-known PR states. A missing or unreadable cache is
+    /// treated as empty, and with caching disabled any leftover cache file is
+    /// removed.
+    #[must_use]
+    pub fn load_pr_cache(&self) -> PrCache {
+        let path = self.rung_dir.join(Self::PR_CACHE_FILE);
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            let _ = fs::remove_file(&path);
+            return PrCache::default();
+        }
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the last-known PR states. Does nothing when caching is disabled.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn save_pr_cache(&self, cache: &PrCache) -> Result<()> {
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(cache)?;
+        write_atomic(&self.rung_dir.join(Self::PR_CACHE_FILE), &content)
+    }
+
+    /// Load the index of the stack branches' own commits. A missing or
+    /// unreadable index is treated as empty, and with caching disabled any
+    /// leftover index file is removed.
+    #[must_use]
+    pub fn load_commit_index(&self) -> CommitIndex {
+        let path = self.rung_dir.join(Self::COMMIT_INDEX_FILE);
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            let _ = fs::remove_file(&path);
+            return CommitIndex::default();
+        }
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the index of the stack branches' own commits. Does nothing when
+    /// caching is disabled.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn save_commit_index(&self, index: &CommitIndex) -> Result<()> {
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(index)?;
+        write_atomic(&self.rung_dir.join(Self::COMMIT_INDEX_FILE), &content)
+    }
+
+    /// The GitHub repository (`owner/repo`) the stack was set up for, if
+    /// recorded.
+    #[must_use]
+    pub fn load_origin(&self) -> Option<String> {
+        fs::read_to_string(self.rung_dir.join(Self::ORIGIN_FILE))
+            .ok()
+            .map(|origin| origin.trim().to_string())
+            .filter(|origin| !origin.is_empty())
+    }
+
+    /// Record the GitHub repository (`owner/repo`) the stack is for.
+    ///
+    /// # Errors
+    /// Returns error if the write fails.
+    pub fn save_origin(&self, origin: &str) -> Result<()> {
+        write_atomic(
+            &self.rung_dir.join(Self::ORIGIN_FILE),
+            &format!("{origin}\n"),
+        )
+    }
+
+    /// Load where merged branches landed. Empty if nothing was recorded or
+    /// the file can't be read.
+    #[must_use]
+    pub fn load_landings(&self) -> Landings {
+        fs::read_to_string(self.rung_dir.join(Self::LANDED_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Add a landing to those recorded.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn record_landing(&self, landing: Landing) -> Result<()> {
+        let mut landings = self.load_landings();
+        landings.record(landing);
+        let content = serde_json::to_string_pretty(&landings)?;
+        write_atomic(&self.rung_dir.join(Self::LANDED_FILE), &content)
+    }
+
+    /// Load the mark left by the last completed sync, if any. Always `None`
+    /// with caching disabled.
+    #[must_use]
+    pub fn load_sync_mark(&self) -> Option<SyncMark> {
+        let path = self.rung_dir.join(Self::SYNC_MARK_FILE);
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    /// Save the mark of a completed sync. Does nothing when caching is
+    /// disabled.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn save_sync_mark(&self, mark: &SyncMark) -> Result<()> {
+        if !self.load_config().unwrap_or_default().cache.enabled {
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(mark)?;
+        write_atomic(&self.rung_dir.join(Self::SYNC_MARK_FILE), &content)
+    }
+
+    /// Remove cached CI results, branch states, PR states, the commit index
+    /// and the last sync's mark. Returns whether there was anything to
+    /// remove.
+    ///
+    /// # Errors
+    /// Returns error if a cache file exists but can't be removed.
+    pub fn clear_cache(&self) -> Result<bool> {
+        let mut cleared = false;
+        for file in [
+            Self::CI_CACHE_FILE,
+            Self::STATUS_CACHE_FILE,
+            Self::SYNC_MARK_FILE,
+            Self::PR_CACHE_FILE,
+            Self::COMMIT_INDEX_FILE,
+        ] {
+            let path = self.rung_dir.join(file);
+            if path.exists() {
+                fs::remove_file(path)?;
+                cleared = true;
+            }
+        }
+        Ok(cleared)
+    }
+
+    /// Append an entry to the operations log.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn record_operation(&self, operation: &Operation) -> Result<()> {
+        let mut line = serde_json::to_string(operation)?;
+        line.push('\n');
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.rung_dir.join(Self::OPERATIONS_FILE))?;
+        file.write_all(line.as_bytes())?;
         Ok(())
     }
+
+    /// Load the operations log, oldest first.
+    ///
+    /// Lines that can't be parsed (e.g. written by a newer version) are skipped.
+    ///
+    /// # Errors
+    /// Returns error if the file exists but can't be read.
+    pub fn load_operations(&self) -> Result<Vec<Operation>> {
+        let path = self.rung_dir.join(Self::OPERATIONS_FILE);
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Drop operations log entries that started before `cutoff`, returning
+    /// how many were dropped. Lines that can't be parsed are kept, as they
+    /// may come from a newer version.
+    ///
+    /// # Errors
+    /// Returns error if the log can't be read or rewritten.
+    pub fn compact_operations(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let path = self.rung_dir.join(Self::OPERATIONS_FILE);
+        if !path.exists() {
+            return Ok(0);
+        }
+        let content = fs::read_to_string(&path)?;
+        let mut kept = String::new();
+        let mut dropped = 0;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let expired = serde_json::from_str::<Operation>(line)
+                .is_ok_and(|operation| operation.started_at < cutoff);
+            if expired {
+                dropped += 1;
+            } else {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+        if dropped > 0 {
+            write_atomic(&path, &kept)?;
+        }
+        Ok(dropped)
+    }
+
+    /// Tidy up `.git/rung`: prune backups beyond the newest `keep_backups`,
+    /// drop operations log entries from before `log_cutoff`, and remove temp
+    /// files left by interrupted writes.
+    ///
+    /// # Errors
+    /// Returns error if any file can't be read or removed.
+    pub fn gc(&self, keep_backups: usize, log_cutoff: Option<DateTime<Utc>>) -> Result<GcReport> {
+        let before = dir_size(&self.rung_dir);
+        let backups_removed = self.cleanup_backups(keep_backups)?;
+        let operations_removed = match log_cutoff {
+            Some(cutoff) => self.compact_operations(cutoff)?,
+            None => 0,
+        };
+        let temp_files_removed = remove_temp_files(&self.rung_dir)?;
+        Ok(GcReport {
+            backups_removed,
+            operations_removed,
+            temp_files_removed,
+            bytes_reclaimed: before.saturating_sub(dir_size(&self.rung_dir)),
+        })
+    }
+
+    /// Check if an archive with the given name exists.
+    #[must_use]
+    pub fn has_archive(&self, name: &str) -> bool {
+        self.existing_archive_path(name).is_some()
+    }
+
+    /// Save an archived stack.
+    ///
+    /// # Errors
+    /// Returns error if serialization or write fails.
+    pub fn save_archive(&self, archive: &StackArchive) -> Result<()> {
+        fs::create_dir_all(self.archives_dir())?;
+        let path = self.archive_path(&archive.name);
+        // An archive from before names were escaped can have this file name:
+        // move it to its own first
+        if let Some(other) = archive_name_in(&path).filter(|other| *other != archive.name) {
+            fs::rename(&path, self.archive_path(&other))?;
+        }
+        let content = serde_json::to_string_pretty(archive)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Load an archived stack by name.
+    ///
+    /// # Errors
+    /// Returns error if the archive doesn't exist or can't be parsed.
+    pub fn load_archive(&self, name: &str) -> Result<StackArchive> {
+        let path = self
+            .existing_archive_path(name)
+            .ok_or_else(|| Error::ArchiveNotFound(name.to_string()))?;
+
+        let content = fs::read_to_string(path)?;
+        let archive: StackArchive = serde_json::from_str(&content)?;
+        Ok(archive)
+    }
+
+    /// List all archived stacks, most recent first.
+    ///
+    /// # Errors
+    /// Returns error if the archives directory can't be read.
+    pub fn list_archives(&self) -> Result<Vec<StackArchive>> {
+        let dir = self.archives_dir();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut archives = vec![];
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let content = fs::read_to_string(&path)?;
+                let archive: StackArchive =
+                    serde_json::from_str(&content).map_err(|e| Error::StateParseError {
+                        file: path.clone(),
+                        message: e.to_string(),
+                    })?;
+                archives.push(archive);
+            }
+        }
+
+        archives.sort_by_key(|a| std::cmp::Reverse(a.archived_at));
+        Ok(archives)
+    }
+
+    /// Delete an archived stack.
+    ///
+    /// # Errors
+    /// Returns error if deletion fails.
+    pub fn delete_archive(&self, name: &str) -> Result<()> {
+        if let Some(path) = self.existing_archive_path(name) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// What [`State::gc`] cleaned up.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GcReport {
+    /// Sync backups pruned.
+    pub backups_removed: usize,
+    /// Operations log entries dropped.
+    pub operations_removed: usize,
+    /// Leftover temp files removed.
+    pub temp_files_removed: usize,
+    /// Bytes freed in `.git/rung`.
+    pub bytes_reclaimed: u64,
+}
+
+/// Total size of the files under `dir`.
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(std::result::Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Remove the temp files [`write_atomic`] leaves behind when interrupted,
+/// anywhere under `dir`. Returns how many were removed.
+fn remove_temp_files(dir: &Path) -> Result<usize> {
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            removed += remove_temp_files(&path)?;
+        } else if path.extension().is_some_and(|ext| ext == "tmp") {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Read a stack copy (a backup), returning it with its raw content.
+fn read_stack_copy(path: &Path) -> Result<(Stack, String)> {
+    if !path.exists() {
+        return Err(Error::NoBackupFound);
+    }
+    let content = fs::read_to_string(path)?;
+    let stack = serde_json::from_str(&content).map_err(|e| Error::StateParseError {
+        file: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    Ok((stack, content))
+}
+
+/// File name a branch (or archive) is stored under. `/` can't appear in a
+/// file name, so it's escaped (as is `%`, the escape character) in a way that
+/// keeps names like `feature/a` and `feature-a` apart.
+fn escape_file_name(name: &str) -> String {
+    name.replace('%', "%25").replace('/', "%2F")
+}
+
+/// File a branch's tip is backed up under: escaped, or as backups from
+/// before that named it, with `/` turned into `-`.
+fn backup_file_name(branch: &str, escaped: bool) -> String {
+    if escaped {
+        escape_file_name(branch)
+    } else {
+        branch.replace('/', "-")
+    }
+}
+
+/// The branch a backup file is for (see [`backup_file_name`]). Older backups
+/// can't tell `-` from `/`, and always read it as `/` as they did then.
+fn backup_branch_name(file: &str, escaped: bool) -> String {
+    if escaped {
+        unescape_file_name(file)
+    } else {
+        file.replace('-', "/")
+    }
+}
+
+/// Name recorded in the archive file at `path`, if it's readable.
+fn archive_name_in(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<StackArchive>(&content)
+        .ok()
+        .map(|archive| archive.name)
+}
+
+/// The name a file is stored for (see [`escape_file_name`]).
+fn unescape_file_name(file: &str) -> String {
+    file.replace("%2F", "/").replace("%25", "%")
+}
+
+/// Write a file so readers see either the old or the new content, never a
+/// partial write: write a sibling temp file, flush it, then rename over.
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp, path)?;
+    Ok(())
 }
 
 /// State tracked during an in-progress sync operation.
@@ -290,6 +1114,23 @@ pub struct SyncState {
     /// Branches remaining to be rebased.
     /// Uses `VecDeque` for O(1) `pop_front()` in `advance()`.
     pub remaining: VecDeque<String>,
+
+    /// Times this sync has stopped on a conflict so far.
+    #[serde(default)]
+    pub conflicts: usize,
+
+    /// Where each of those conflicts was.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicted: Vec<SyncConflict>,
+
+    /// Shell command run after each branch is rebased (`sync --exec`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+
+    /// Working tree the sync runs in. A rebase stopped on a conflict can
+    /// only be finished there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<PathBuf>,
 }
 
 impl SyncState {
@@ -305,9 +1146,30 @@ impl SyncState {
             current_branch: current,
             completed: vec![],
             remaining,
+            conflicts: 0,
+            conflicted: vec![],
+            exec: None,
+            worktree: None,
         }
     }
 
+    /// The worktree this sync runs in, if that isn't `workdir`.
+    #[must_use]
+    pub fn other_worktree(&self, workdir: &Path) -> Option<&Path> {
+        let worktree = self.worktree.as_deref()?;
+        let resolve = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        (resolve(worktree) != resolve(workdir)).then_some(worktree)
+    }
+
+    /// Count a conflict on `branch` in `files`.
+    pub fn record_conflict(&mut self, branch: &str, files: &[String]) {
+        self.conflicts += 1;
+        self.conflicted.push(SyncConflict {
+            branch: branch.to_string(),
+            files: files.to_vec(),
+        });
+    }
+
     /// Mark current branch as complete and move to next.
     pub fn advance(&mut self) {
         if !self.current_branch.is_empty() {
@@ -332,7 +1194,7 @@ mod tests {
 
     fn setup_test_repo() -> (TempDir, State) {
         let temp = TempDir::new().unwrap();
-        fs::create_dir(temp.path().join(".git")).unwrap();
+        git2::Repository::init(temp.path()).unwrap();
         let state = State::new(temp.path()).unwrap();
         (temp, state)
     }
@@ -362,20 +1224,557 @@ mod tests {
     }
 
     #[test]
-    fn test_backup_operations() {
+    fn test_summary_refreshed() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        let path = state.rung_dir().join(State::SUMMARY_FILE);
+        let read = || serde_json::from_str::<Summary>(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(read().branches.is_empty());
+
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("feature", Some("main")).unwrap());
+        state.save_stack(&stack).unwrap();
+        assert_eq!(read().branches[0].name, "feature");
+        assert!(!read().sync_in_progress);
+
+        // Saving the same stack leaves the file alone
+        let written = fs::metadata(&path).unwrap().modified().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        state.save_stack(&stack).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), written);
+
+        state
+            .save_sync_state(&SyncState::new("backup".into(), vec!["feature".into()]))
+            .unwrap();
+        assert!(read().sync_in_progress);
+        state.clear_sync_state().unwrap();
+        assert!(!read().sync_in_progress);
+    }
+
+    #[test]
+    fn test_stack_saved_parents_first() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        let mut stack = Stack::new();
+        stack.add_branch(crate::stack::StackBranch::try_new("child", Some("parent")).unwrap());
+        stack.add_branch(crate::stack::StackBranch::try_new("parent", Some("main")).unwrap());
+        state.save_stack(&stack).unwrap();
+        assert_eq!(state.load_stack().unwrap().branches[0].name, "parent");
+
+        // Cycles are refused rather than persisted
+        let mut cyclic = Stack::new();
+        cyclic.add_branch(crate::stack::StackBranch::try_new("a", Some("b")).unwrap());
+        cyclic.add_branch(crate::stack::StackBranch::try_new("b", Some("a")).unwrap());
+        assert!(matches!(
+            state.save_stack(&cyclic),
+            Err(Error::CyclicDependency(_))
+        ));
+    }
+
+    #[test]
+    fn test_stack_rejects_invalid_branch_names() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        // Hand-edited names are validated like any other BranchName
+        let stack_path = state.rung_dir().join("stack.json");
+        fs::write(
+            &stack_path,
+            r#"{"branches":[{"name":"--force","parent":"main","created":"2024-01-01T00:00:00Z"}]}"#,
+        )
+        .unwrap();
+        let err = state.load_stack().unwrap_err();
+        assert!(matches!(&err, Error::CorruptStack { message, .. } if message.contains("'-'")));
+    }
+
+    #[test]
+    fn test_stack_backup_and_restore() {
         let (_temp, state) = setup_test_repo();
         state.init().unwrap();
 
-        let branches = vec![("feature/a", "abc123"), ("feature/b", "def456")];
-        let backup_id = state.create_backup(&branches).unwrap();
+        let mut stack = Stack::new();
+        stack.add_branch(crate::stack::StackBranch::try_new("feature/a", Some("main")).unwrap());
+        state.save_stack(&stack).unwrap();
+        stack.add_branch(
+            crate::stack::StackBranch::try_new("feature/b", Some("feature/a")).unwrap(),
+        );
+        state.save_stack(&stack).unwrap();
+        assert!(state.has_stack_backup());
+
+        // Simulate a crash that truncated the file
+        let stack_path = state.rung_dir().join("stack.json");
+        let content = fs::read_to_string(&stack_path).unwrap();
+        fs::write(&stack_path, &content[..content.len() / 2]).unwrap();
+        assert!(matches!(
+            state.load_stack(),
+            Err(Error::CorruptStack { .. })
+        ));
+
+        assert_eq!(state.load_previous_stack().unwrap().len(), 1);
+        let restored = state.restore_stack_backup().unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(state.load_stack().unwrap().len(), 1);
+        assert!(state.rung_dir().join("stack.json.corrupt").exists());
+        assert!(!state.rung_dir().join("stack.json.tmp").exists());
+
+        // Saving over a damaged file must not clobber the good backup
+        fs::write(&stack_path, "{").unwrap();
+        state.save_stack(&Stack::new()).unwrap();
+        assert_eq!(state.restore_stack_backup().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_backup_operations() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
 
-        let loaded = state.load_backup(&backup_id).unwrap();
-        assert_eq!(loaded.len(), 2);
+        let branches = vec![
+            ("feature/a", "abc123"),
+            ("feature-a", "def456"),
+            ("fix/100%", "fed789"),
+        ];
+        let backup_id = state.create_backup("sync", &branches).unwrap();
+        assert_eq!(state.backup_operation(&backup_id).as_deref(), Some("sync"));
+        assert!(state.load_range_diff(&backup_id).is_err());
+        state
+            .save_range_diff(&backup_id, "1:  abc = 1:  fed Add a\n")
+            .unwrap();
+        assert_eq!(
+            state.load_range_diff(&backup_id).unwrap(),
+            "1:  abc = 1:  fed Add a\n"
+        );
+
+        // None of the backup's dot files is mistaken for a branch
+        let mut loaded = state.load_backup(&backup_id).unwrap();
+        loaded.sort();
+        let mut expected: Vec<(String, String)> = branches
+            .iter()
+            .map(|(b, c)| ((*b).to_string(), (*c).to_string()))
+            .collect();
+        expected.sort();
+        assert_eq!(loaded, expected);
+        assert!(state.load_backup_stack(&backup_id).unwrap().is_empty());
 
         let latest = state.latest_backup().unwrap();
         assert_eq!(latest, backup_id);
-
+        assert_eq!(state.backup_count(), 1);
+        assert_eq!(state.backup_ids(), [backup_id.as_str()]);
+        assert_eq!(state.backup_at(Utc::now()).unwrap(), backup_id);
+        assert!(
+            state
+                .backup_at(Utc::now() - chrono::Duration::hours(1))
+                .is_err()
+        );
+
+        state.remove_from_backup(&backup_id, "feature/a").unwrap();
+        assert_eq!(state.load_backup(&backup_id).unwrap().len(), 2);
         state.delete_backup(&backup_id).unwrap();
         assert!(state.latest_backup().is_err());
+        assert_eq!(state.backup_count(), 0);
+
+        // Removing the last branch removes the backup
+        let backup_id = state
+            .create_backup("edit", &[("feature/a", "abc123")])
+            .unwrap();
+        state.remove_from_backup(&backup_id, "feature/a").unwrap();
+        assert!(state.latest_backup().is_err());
+    }
+
+    #[test]
+    fn test_backups_in_the_same_second_stay_apart() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        let first = state.create_backup("sync", &[("a", "abc123")]).unwrap();
+        let second = state.create_backup("reword", &[("b", "def456")]).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(state.latest_backup().unwrap(), second);
+        assert_eq!(state.backup_operation(&second).as_deref(), Some("reword"));
+        assert_eq!(state.load_backup(&first).unwrap().len(), 1);
+
+        // Backups from before the operation was recorded have none
+        fs::remove_file(state.refs_dir().join(&first).join(".operation")).unwrap();
+        assert_eq!(state.backup_operation(&first), None);
+    }
+
+    #[test]
+    fn test_legacy_backup_names() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        // Taken before branch files were escaped: `/` became `-`
+        let backup_dir = state.refs_dir().join("1700000000");
+        fs::create_dir_all(&backup_dir).unwrap();
+        fs::write(backup_dir.join("feature-a"), "abc123\n").unwrap();
+        fs::write(backup_dir.join("fix-b"), "def456\n").unwrap();
+
+        let mut loaded = state.load_backup("1700000000").unwrap();
+        loaded.sort();
+        assert_eq!(
+            loaded,
+            [
+                ("feature/a".to_string(), "abc123".to_string()),
+                ("fix/b".to_string(), "def456".to_string()),
+            ]
+        );
+        state.remove_from_backup("1700000000", "feature/a").unwrap();
+        assert_eq!(state.load_backup("1700000000").unwrap().len(), 1);
+        state.remove_from_backup("1700000000", "fix/b").unwrap();
+        assert!(state.load_backup("1700000000").is_err());
+    }
+
+    #[test]
+    fn test_ci_cache_roundtrip() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        assert!(state.load_ci_cache().branches.is_empty());
+
+        let mut cache = CiCache::default();
+        cache.record("feature", "abc123", crate::ci::CiStatus::Pending);
+        state.save_ci_cache(&cache).unwrap();
+        assert_eq!(
+            state.load_ci_cache().status("feature", "abc123"),
+            Some(crate::ci::CiStatus::Pending)
+        );
+
+        // A damaged cache is just empty
+        fs::write(state.rung_dir().join("ci.json"), "{").unwrap();
+        assert!(state.load_ci_cache().branches.is_empty());
+    }
+
+    #[test]
+    fn test_ci_cache_settings() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        let config_path = state.rung_dir().join("config.toml");
+        let ci_path = state.rung_dir().join("ci.json");
+
+        let mut cache = CiCache::default();
+        cache.record("fresh", "abc123", crate::ci::CiStatus::Passing);
+        cache.record("expired", "def456", crate::ci::CiStatus::Failing);
+        cache.branches.get_mut("expired").unwrap().checked_at =
+            Utc::now() - chrono::Duration::hours(3);
+        state.save_ci_cache(&cache).unwrap();
+
+        // Expired results are dropped from disk, not just hidden
+        fs::write(&config_path, "[cache]\nttl_hours = 2\n").unwrap();
+        assert_eq!(state.load_ci_cache().branches.len(), 1);
+        assert!(!fs::read_to_string(&ci_path).unwrap().contains("expired"));
+
+        // Disabling the cache removes what's there and stops writes
+        fs::write(&config_path, "[cache]\nenabled = false\n").unwrap();
+        assert!(state.load_ci_cache().branches.is_empty());
+        assert!(!ci_path.exists());
+        state.save_ci_cache(&cache).unwrap();
+        assert!(!ci_path.exists());
+
+        fs::remove_file(&config_path).unwrap();
+        state.save_ci_cache(&cache).unwrap();
+        assert!(state.clear_cache().unwrap());
+        assert!(!state.clear_cache().unwrap());
+        assert!(state.load_ci_cache().branches.is_empty());
+    }
+
+    #[test]
+    fn test_status_cache_roundtrip() {
+        use crate::stack::BranchState;
+        use crate::status_cache::StatusEntry;
+
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        assert!(state.load_status_cache().branches.is_empty());
+
+        let mut cache = StatusCache::default();
+        cache.insert(
+            "feature",
+            StatusEntry {
+                key: "a:b:".into(),
+                state: BranchState::Synced,
+                files: Some(vec!["src/lib.rs".into()]),
+            },
+        );
+        state.save_status_cache(&cache).unwrap();
+        assert_eq!(state.load_status_cache(), cache);
+
+        assert!(state.clear_cache().unwrap());
+        assert!(state.load_status_cache().branches.is_empty());
+
+        let status_path = state.rung_dir().join("status.json");
+        fs::write(
+            state.rung_dir().join("config.toml"),
+            "[cache]\nenabled = false\n",
+        )
+        .unwrap();
+        state.save_status_cache(&cache).unwrap();
+        assert!(!status_path.exists());
+    }
+
+    #[test]
+    fn test_worktrees_share_state() {
+        let (temp, main) = setup_test_repo();
+        main.init().unwrap();
+
+        let repo = git2::Repository::open(temp.path()).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap();
+        let worktrees = TempDir::new().unwrap();
+        let worktree = worktrees.path().join("wt");
+        repo.worktree("wt", &worktree, None).unwrap();
+        let worktree_git_dir = temp.path().join(".git/worktrees/wt");
+
+        let linked = State::new(&worktree).unwrap();
+        assert!(linked.is_initialized());
+        let mut stack = Stack::new();
+        stack.add_branch(crate::stack::StackBranch::try_new("feature", Some("main")).unwrap());
+        linked.save_stack(&stack).unwrap();
+        assert_eq!(main.load_stack().unwrap().branches.len(), 1);
+        assert!(!worktree_git_dir.join("rung").exists());
+
+        // A sync remembers where it runs
+        let mut sync = SyncState::new("100".into(), vec!["feature".into()]);
+        assert!(sync.other_worktree(temp.path()).is_none());
+        sync.worktree = Some(temp.path().to_path_buf());
+        assert!(sync.other_worktree(temp.path()).is_none());
+        assert_eq!(sync.other_worktree(&worktree), Some(temp.path()));
+
+        assert!(matches!(
+            State::new(TempDir::new().unwrap().path()),
+            Err(Error::NotARepository)
+        ));
+    }
+
+    #[test]
+    fn test_origin_roundtrip() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        assert_eq!(state.load_origin(), None);
+
+        state.save_origin("acme/widgets").unwrap();
+        assert_eq!(state.load_origin().as_deref(), Some("acme/widgets"));
+        state.save_origin("acme/gadgets").unwrap();
+        assert_eq!(state.load_origin().as_deref(), Some("acme/gadgets"));
+    }
+
+    #[test]
+    fn test_sync_mark_roundtrip() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        assert!(state.load_sync_mark().is_none());
+
+        let mark = SyncMark {
+            base: "main".into(),
+            base_tip: "abc123".into(),
+            branches: [("feature".to_string(), "def456:main:7".to_string())].into(),
+        };
+        state.save_sync_mark(&mark).unwrap();
+        assert_eq!(state.load_sync_mark(), Some(mark.clone()));
+        assert!(state.clear_cache().unwrap());
+        assert!(state.load_sync_mark().is_none());
+
+        fs::write(
+            state.rung_dir().join("config.toml"),
+            "[cache]\nenabled = false\n",
+        )
+        .unwrap();
+        state.save_sync_mark(&mark).unwrap();
+        assert!(state.load_sync_mark().is_none());
+    }
+
+    #[test]
+    fn test_pr_cache_roundtrip() {
+        use crate::pr_cache::PrEntry;
+        use crate::remote_status::RemotePrState;
+
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        assert_eq!(state.load_pr_cache(), PrCache::default());
+
+        let mut cache = PrCache {
+            checked_at: Some(Utc::now()),
+            ..PrCache::default()
+        };
+        cache.record(
+            3,
+            PrEntry {
+                state: RemotePrState::Open,
+                base: "main".into(),
+                updated_at: Some("2024-06-01T12:00:00Z".into()),
+            },
+        );
+        state.save_pr_cache(&cache).unwrap();
+        assert_eq!(state.load_pr_cache(), cache);
+        assert!(state.clear_cache().unwrap());
+        assert_eq!(state.load_pr_cache(), PrCache::default());
+    }
+
+    #[test]
+    fn test_operations_log() {
+        use crate::operations::OperationKind;
+
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+        assert!(state.load_operations().unwrap().is_empty());
+
+        for pr_number in [1, 2] {
+            let op = Operation::finished(
+                Utc::now(),
+                OperationKind::Merge {
+                    branch: format!("feat-{pr_number}"),
+                    pr_number,
+                    merge_sha: "abc".into(),
+                    method: "squash".into(),
+                    into: "main".into(),
+                    reparented: vec![],
+                },
+            );
+            state.record_operation(&op).unwrap();
+        }
+
+        // Unknown entries are skipped, not fatal
+        let path = state.rung_dir().join("operations.jsonl");
+        let mut content = fs::read_to_string(&path).unwrap();
+        content.push_str("{\"op\":\"from_the_future\"}\n");
+        fs::write(&path, content).unwrap();
+
+        let ops = state.load_operations().unwrap();
+        assert_eq!(ops.len(), 2);
+        assert!(matches!(
+            ops[1].kind,
+            OperationKind::Merge { pr_number: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_gc() {
+        use crate::operations::OperationKind;
+
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        for id in ["100", "200", "300", "400"] {
+            let dir = state.refs_dir().join(id);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("feature"), "abc123").unwrap();
+        }
+        // The oldest backup belongs to a sync in progress
+        state
+            .save_sync_state(&SyncState::new("100".into(), vec!["feature".into()]))
+            .unwrap();
+
+        let now = Utc::now();
+        for days in [400, 10] {
+            let op = Operation::finished(
+                now - chrono::Duration::days(days),
+                OperationKind::Merge {
+                    branch: "feat".into(),
+                    pr_number: 1,
+                    merge_sha: "abc".into(),
+                    method: "squash".into(),
+                    into: "main".into(),
+                    reparented: vec![],
+                },
+            );
+            state.record_operation(&op).unwrap();
+        }
+        fs::write(state.rung_dir().join("stack.json.tmp"), "{").unwrap();
+
+        let report = state
+            .gc(2, Some(now - chrono::Duration::days(365)))
+            .unwrap();
+        assert_eq!(report.backups_removed, 1);
+        assert_eq!(report.operations_removed, 1);
+        assert_eq!(report.temp_files_removed, 1);
+        assert!(report.bytes_reclaimed > 0);
+
+        assert!(state.refs_dir().join("100").exists());
+        assert!(!state.refs_dir().join("200").exists());
+        assert_eq!(state.latest_backup().unwrap(), "400");
+        assert_eq!(state.load_operations().unwrap().len(), 1);
+        assert!(!state.rung_dir().join("stack.json.tmp").exists());
+
+        // Nothing left to do
+        assert_eq!(state.gc(2, Some(now)).unwrap().backups_removed, 0);
+    }
+
+    #[test]
+    fn test_archive_operations() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        let archive = StackArchive::new(
+            "feature/parked",
+            vec![crate::archive::ArchivedBranch {
+                branch: crate::stack::StackBranch::try_new("feature/parked", Some("main")).unwrap(),
+                commit: "abc123".into(),
+                base: Some("def456".into()),
+            }],
+        );
+        state.save_archive(&archive).unwrap();
+
+        assert!(state.has_archive("feature/parked"));
+        let loaded = state.load_archive("feature/parked").unwrap();
+        assert_eq!(loaded.branch_names(), vec!["feature/parked"]);
+        assert_eq!(state.list_archives().unwrap().len(), 1);
+
+        state.delete_archive("feature/parked").unwrap();
+        assert!(state.load_archive("feature/parked").is_err());
+        assert!(state.list_archives().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_archive_names_dont_collide() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        for name in ["feature/parked", "feature-parked"] {
+            state
+                .save_archive(&StackArchive::new(name, vec![]))
+                .unwrap();
+        }
+        assert_eq!(state.list_archives().unwrap().len(), 2);
+        assert_eq!(
+            state.load_archive("feature-parked").unwrap().name,
+            "feature-parked"
+        );
+        state.delete_archive("feature/parked").unwrap();
+        assert!(state.has_archive("feature-parked"));
+        assert!(!state.has_archive("feature/parked"));
+    }
+
+    #[test]
+    fn test_legacy_archive_names() {
+        let (_temp, state) = setup_test_repo();
+        state.init().unwrap();
+
+        // Written as `/` turned into `-`, before names were escaped
+        let legacy = StackArchive::new("feature/old", vec![]);
+        fs::create_dir_all(state.archives_dir()).unwrap();
+        fs::write(
+            state.archives_dir().join("feature-old.json"),
+            serde_json::to_string(&legacy).unwrap(),
+        )
+        .unwrap();
+
+        assert!(state.has_archive("feature/old"));
+        assert!(!state.has_archive("feature-old"));
+        assert_eq!(
+            state.load_archive("feature/old").unwrap().name,
+            "feature/old"
+        );
+
+        // Saving the name it looks like keeps both
+        state
+            .save_archive(&StackArchive::new("feature-old", vec![]))
+            .unwrap();
+        assert_eq!(state.list_archives().unwrap().len(), 2);
+        assert!(state.has_archive("feature/old"));
+        state.delete_archive("feature/old").unwrap();
+        assert!(state.has_archive("feature-old"));
+        assert_eq!(state.list_archives().unwrap().len(), 1);
     }
 }
@@ -0,0 +1,573 @@
+//! Workflow metrics for `rung stats`, and the recap `rung summary` prints.
+//!
+//! Everything is derived from the operations log, so the numbers only cover
+//! what rung itself did in this clone: branches added with `rung create`, PRs
+//! merged with `rung merge`, submits and completed syncs.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday};
+use serde::Serialize;
+
+use crate::operations::{Operation, OperationKind, SubmittedPr, SyncConflict};
+
+/// PRs merged in one calendar week.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WeekCount {
+    /// Monday the week starts on (UTC).
+    pub week_start: NaiveDate,
+    /// PRs merged that week.
+    pub merged: usize,
+}
+
+/// Metrics over a window of whole weeks ending with the current one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    /// Merges per week, oldest first, including weeks with none.
+    pub weeks: Vec<WeekCount>,
+    /// Total PRs merged in the window.
+    pub merged: usize,
+    /// Average PRs merged per week.
+    pub landed_per_week: f64,
+    /// Average longest parent chain seen by submits and syncs.
+    pub average_stack_depth: Option<f64>,
+    /// Average seconds from the submit that opened a PR to its merge.
+    /// Only PRs opened by `rung submit` count.
+    pub average_time_to_merge_secs: Option<i64>,
+    /// Completed syncs.
+    pub syncs: usize,
+    /// Conflicts hit by those syncs.
+    pub conflicts: usize,
+    /// Average conflicts per completed sync.
+    pub conflicts_per_sync: Option<f64>,
+}
+
+impl Stats {
+    /// Compute metrics for the `weeks` calendar weeks up to and including the
+    /// one containing `now`. Submits before the window still count towards
+    /// time-to-merge for PRs merged inside it.
+    #[must_use]
+    pub fn compute(operations: &[Operation], weeks: u32, now: DateTime<Utc>) -> Self {
+        let weeks = weeks.max(1);
+        let since = window_start(weeks, now);
+        let start = since.and_time(NaiveTime::MIN).and_utc();
+
+        // When each PR was first submitted
+        let mut submitted: HashMap<u64, DateTime<Utc>> = HashMap::new();
+        for operation in operations {
+            if let OperationKind::Submit { created, .. } = &operation.kind {
+                for pr in created {
+                    submitted
+                        .entry(pr.pr_number)
+                        .and_modify(|at| *at = (*at).min(operation.finished_at))
+                        .or_insert(operation.finished_at);
+                }
+            }
+        }
+
+        let mut counts = vec![0; weeks as usize];
+        let mut depths = vec![];
+        let mut times_to_merge = vec![];
+        let mut syncs = 0;
+        let mut conflicts = 0;
+
+        for operation in operations.iter().filter(|op| op.finished_at >= start) {
+            match &operation.kind {
+                OperationKind::Merge { pr_number, .. } => {
+                    let days = (operation.finished_at.date_naive() - since).num_days();
+                    if let Some(count) = usize::try_from(days / 7)
+                        .ok()
+                        .and_then(|week| counts.get_mut(week))
+                    {
+                        *count += 1;
+                    }
+                    if let Some(at) = submitted.get(pr_number) {
+                        times_to_merge.push((operation.finished_at - *at).num_seconds().max(0));
+                    }
+                }
+                OperationKind::Submit { stack_depth, .. } => depths.push(*stack_depth),
+                OperationKind::Sync {
+                    conflicts: hit,
+                    stack_depth,
+                    ..
+                } => {
+                    syncs += 1;
+                    conflicts += hit;
+                    depths.push(*stack_depth);
+                }
+                OperationKind::Create { .. } => {}
+            }
+        }
+
+        let merged = counts.iter().sum();
+        let average_time_to_merge_secs = i64::try_from(times_to_merge.len())
+            .ok()
+            .filter(|&n| n > 0)
+            .map(|n| times_to_merge.iter().sum::<i64>() / n);
+
+        Self {
+            weeks: counts
+                .into_iter()
+                .zip(0..)
+                .map(|(merged, week)| WeekCount {
+                    week_start: since + Days::new(7 * week),
+                    merged,
+                })
+                .collect(),
+            merged,
+            landed_per_week: ratio(merged, weeks as usize).unwrap_or(0.0),
+            average_stack_depth: ratio(depths.iter().sum(), depths.len()),
+            average_time_to_merge_secs,
+            syncs,
+            conflicts,
+            conflicts_per_sync: ratio(conflicts, syncs),
+        }
+    }
+}
+
+/// A completed sync that stopped on conflicts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConflictedSync {
+    /// When the sync finished.
+    pub finished_at: DateTime<Utc>,
+    /// Times it stopped on a conflict.
+    pub conflicts: usize,
+    /// Where, for syncs recorded since conflicts were.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicted: Vec<SyncConflict>,
+}
+
+/// How many conflicts a branch or file was in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConflictCount {
+    /// Branch name or file path.
+    pub name: String,
+    /// Conflicts it was in.
+    pub conflicts: usize,
+}
+
+/// Conflict metrics for `rung stats --conflicts`, over the same window of
+/// weeks as [`Stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConflictStats {
+    /// Completed syncs.
+    pub syncs: usize,
+    /// Conflicts hit by those syncs.
+    pub conflicts: usize,
+    /// The syncs that hit any, oldest first.
+    pub conflicted_syncs: Vec<ConflictedSync>,
+    /// Branches by how many conflicts they were rebased into, most first.
+    pub branches: Vec<ConflictCount>,
+    /// Files by how many conflicts they were in, most first.
+    pub files: Vec<ConflictCount>,
+}
+
+impl ConflictStats {
+    /// Compute conflict metrics for the `weeks` calendar weeks up to and
+    /// including the one containing `now`.
+    #[must_use]
+    pub fn compute(operations: &[Operation], weeks: u32, now: DateTime<Utc>) -> Self {
+        let start = window_start(weeks.max(1), now)
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        let mut syncs = 0;
+        let mut conflicted_syncs = vec![];
+        let mut branches: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut files: BTreeMap<&str, usize> = BTreeMap::new();
+
+        for operation in operations.iter().filter(|op| op.finished_at >= start) {
+            let OperationKind::Sync {
+                conflicts,
+                conflicted,
+                ..
+            } = &operation.kind
+            else {
+                continue;
+            };
+            syncs += 1;
+            if *conflicts == 0 {
+                continue;
+            }
+            for conflict in conflicted {
+                *branches.entry(&conflict.branch).or_default() += 1;
+                for file in &conflict.files {
+                    *files.entry(file).or_default() += 1;
+                }
+            }
+            conflicted_syncs.push(ConflictedSync {
+                finished_at: operation.finished_at,
+                conflicts: *conflicts,
+                conflicted: conflicted.clone(),
+            });
+        }
+        conflicted_syncs.sort_by_key(|sync| sync.finished_at);
+
+        Self {
+            syncs,
+            conflicts: conflicted_syncs.iter().map(|sync| sync.conflicts).sum(),
+            conflicted_syncs,
+            branches: ranked(branches),
+            files: ranked(files),
+        }
+    }
+}
+
+/// A PR merged with `rung merge`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergedPr {
+    /// The merged branch.
+    pub branch: String,
+    /// The merged PR.
+    pub pr_number: u64,
+    /// Branch the PR was merged into.
+    pub into: String,
+}
+
+/// What was done since a point in time, for `rung summary`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Recap {
+    /// Start of the period covered.
+    pub since: DateTime<Utc>,
+    /// Stacks started: branches created on something outside the stack.
+    pub stacks_created: usize,
+    /// Branches created, including the stacks' first ones.
+    pub branches_created: usize,
+    /// PRs opened by `rung submit`, oldest first.
+    pub prs_opened: Vec<SubmittedPr>,
+    /// Updates to existing PRs, counted once per submit.
+    pub prs_updated: usize,
+    /// PRs merged, oldest first.
+    pub prs_merged: Vec<MergedPr>,
+    /// Completed syncs.
+    pub syncs: usize,
+    /// Branches those syncs rebased.
+    pub rebased: usize,
+    /// Conflicts those syncs stopped on.
+    pub conflicts: usize,
+}
+
+impl Recap {
+    /// Sum up the operations that finished at or after `since`.
+    #[must_use]
+    pub fn compute(operations: &[Operation], since: DateTime<Utc>) -> Self {
+        let mut recap = Self {
+            since,
+            stacks_created: 0,
+            branches_created: 0,
+            prs_opened: vec![],
+            prs_updated: 0,
+            prs_merged: vec![],
+            syncs: 0,
+            rebased: 0,
+            conflicts: 0,
+        };
+        let mut operations: Vec<&Operation> = operations
+            .iter()
+            .filter(|op| op.finished_at >= since)
+            .collect();
+        operations.sort_by_key(|op| op.finished_at);
+
+        for operation in operations {
+            match &operation.kind {
+                OperationKind::Create { new_stack, .. } => {
+                    recap.branches_created += 1;
+                    recap.stacks_created += usize::from(*new_stack);
+                }
+                OperationKind::Submit {
+                    created, updated, ..
+                } => {
+                    recap.prs_opened.extend(created.iter().cloned());
+                    recap.prs_updated += updated;
+                }
+                OperationKind::Merge {
+                    branch,
+                    pr_number,
+                    into,
+                    ..
+                } => recap.prs_merged.push(MergedPr {
+                    branch: branch.clone(),
+                    pr_number: *pr_number,
+                    into: into.clone(),
+                }),
+                OperationKind::Sync {
+                    rebased, conflicts, ..
+                } => {
+                    recap.syncs += 1;
+                    recap.rebased += rebased;
+                    recap.conflicts += conflicts;
+                }
+            }
+        }
+        recap
+    }
+
+    /// Whether nothing was recorded in the period.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.branches_created == 0
+            && self.prs_opened.is_empty()
+            && self.prs_updated == 0
+            && self.prs_merged.is_empty()
+            && self.syncs == 0
+    }
+}
+
+/// Parse a period such as `12h`, `3d` or `2w` (hours, days or weeks).
+///
+/// # Errors
+/// Returns a message for `--since` if `value` isn't a whole number followed
+/// by one of those units.
+pub fn parse_period(value: &str) -> Result<TimeDelta, String> {
+    let invalid = || format!("invalid period '{value}' - use a number and h, d or w, like 1w");
+    let value = value.trim();
+    let split = value.len().saturating_sub(1);
+    let (count, unit) = (value.get(..split), value.get(split..));
+    let count: i64 = count
+        .and_then(|count| count.parse().ok())
+        .filter(|&count| count > 0)
+        .ok_or_else(invalid)?;
+    match unit {
+        Some("h") => TimeDelta::try_hours(count),
+        Some("d") => TimeDelta::try_days(count),
+        Some("w") => TimeDelta::try_weeks(count),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+/// Counts by name, most first and then by name.
+fn ranked(counts: BTreeMap<&str, usize>) -> Vec<ConflictCount> {
+    let mut ranked: Vec<ConflictCount> = counts
+        .into_iter()
+        .map(|(name, conflicts)| ConflictCount {
+            name: name.to_string(),
+            conflicts,
+        })
+        .collect();
+    // Stable, so ties stay in name order
+    ranked.sort_by_key(|count| std::cmp::Reverse(count.conflicts));
+    ranked
+}
+
+/// Monday of the first of the `weeks` calendar weeks ending with the one
+/// containing `now`.
+fn window_start(weeks: u32, now: DateTime<Utc>) -> NaiveDate {
+    let this_week = now.date_naive().week(Weekday::Mon).first_day();
+    this_week - Days::new(7 * u64::from(weeks - 1))
+}
+
+/// `total / count`, or `None` when there is nothing to average.
+#[allow(clippy::cast_precision_loss)] // counts stay far below 2^52
+fn ratio(total: usize, count: usize) -> Option<f64> {
+    (count > 0).then(|| total as f64 / count as f64)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> DateTime<Utc> {
+        format!("{date}T12:00:00Z").parse().unwrap()
+    }
+
+    fn op(date: &str, kind: OperationKind) -> Operation {
+        Operation {
+            started_at: at(date),
+            finished_at: at(date),
+            kind,
+        }
+    }
+
+    fn submit(date: &str, pr_number: u64, stack_depth: usize) -> Operation {
+        op(
+            date,
+            OperationKind::Submit {
+                created: vec![SubmittedPr {
+                    branch: format!("feat-{pr_number}"),
+                    pr_number,
+                }],
+                updated: 0,
+                stack_depth,
+            },
+        )
+    }
+
+    fn merge(date: &str, pr_number: u64) -> Operation {
+        op(
+            date,
+            OperationKind::Merge {
+                branch: format!("feat-{pr_number}"),
+                pr_number,
+                merge_sha: "abc1234".into(),
+                method: "squash".into(),
+                into: "main".into(),
+                reparented: vec![],
+            },
+        )
+    }
+
+    fn sync(date: &str, conflicts: usize, stack_depth: usize) -> Operation {
+        op(
+            date,
+            OperationKind::Sync {
+                rebased: 2,
+                conflicts,
+                conflicted: vec![],
+                stack_depth,
+            },
+        )
+    }
+
+    fn conflicted_sync(date: &str, conflicted: &[(&str, &[&str])]) -> Operation {
+        op(
+            date,
+            OperationKind::Sync {
+                rebased: 2,
+                conflicts: conflicted.len(),
+                conflicted: conflicted
+                    .iter()
+                    .map(|(branch, files)| SyncConflict {
+                        branch: (*branch).to_string(),
+                        files: files.iter().map(ToString::to_string).collect(),
+                    })
+                    .collect(),
+                stack_depth: 2,
+            },
+        )
+    }
+
+    #[test]
+    fn test_empty_log() {
+        let stats = Stats::compute(&[], 4, at("2024-03-14"));
+        assert_eq!(stats.weeks.len(), 4);
+        assert_eq!(stats.merged, 0);
+        assert!(stats.landed_per_week.abs() < f64::EPSILON);
+        assert_eq!(stats.average_stack_depth, None);
+        assert_eq!(stats.average_time_to_merge_secs, None);
+        assert_eq!(stats.conflicts_per_sync, None);
+    }
+
+    #[test]
+    fn test_compute() {
+        // 2024-03-14 is a Thursday; a two-week window starts Monday 2024-03-04
+        let operations = [
+            submit("2024-02-20", 1, 1),
+            merge("2024-02-28", 1), // before the window
+            submit("2024-03-01", 2, 2),
+            merge("2024-03-05", 2),
+            submit("2024-03-06", 3, 3),
+            merge("2024-03-13", 3),
+            merge("2024-03-14", 4), // not submitted by rung
+            sync("2024-03-07", 0, 3),
+            sync("2024-03-12", 3, 2),
+        ];
+        let stats = Stats::compute(&operations, 2, at("2024-03-14"));
+
+        assert_eq!(
+            stats.weeks,
+            vec![
+                WeekCount {
+                    week_start: "2024-03-04".parse().unwrap(),
+                    merged: 1,
+                },
+                WeekCount {
+                    week_start: "2024-03-11".parse().unwrap(),
+                    merged: 2,
+                },
+            ]
+        );
+        assert_eq!(stats.merged, 3);
+        assert!((stats.landed_per_week - 1.5).abs() < f64::EPSILON);
+        // Submit at depth 3, syncs at 3 and 2
+        assert_eq!(stats.average_stack_depth, Some(8.0 / 3.0));
+        // PR 2 took 4 days, PR 3 took 7 days
+        assert_eq!(stats.average_time_to_merge_secs, Some(11 * 86_400 / 2));
+        assert_eq!(stats.syncs, 2);
+        assert_eq!(stats.conflicts, 3);
+        assert_eq!(stats.conflicts_per_sync, Some(1.5));
+    }
+
+    #[test]
+    fn test_conflict_stats() {
+        let operations = [
+            conflicted_sync("2024-02-20", &[("feat-a", &["old.rs"])]), // before the window
+            conflicted_sync(
+                "2024-03-05",
+                &[
+                    ("feat-a", &["src/lib.rs", "Cargo.lock"]),
+                    ("feat-b", &["Cargo.lock"]),
+                ],
+            ),
+            sync("2024-03-07", 0, 2),
+            sync("2024-03-08", 2, 2), // recorded before conflicts were
+            conflicted_sync("2024-03-12", &[("feat-b", &["Cargo.lock"])]),
+        ];
+        let stats = ConflictStats::compute(&operations, 2, at("2024-03-14"));
+
+        assert_eq!(stats.syncs, 4);
+        assert_eq!(stats.conflicts, 5);
+        assert_eq!(stats.conflicted_syncs.len(), 3);
+        assert_eq!(stats.conflicted_syncs[1].conflicts, 2);
+        assert!(stats.conflicted_syncs[1].conflicted.is_empty());
+        let counts = |counts: &[ConflictCount]| {
+            counts
+                .iter()
+                .map(|c| (c.name.clone(), c.conflicts))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            counts(&stats.branches),
+            [("feat-b".to_string(), 2), ("feat-a".to_string(), 1)]
+        );
+        assert_eq!(
+            counts(&stats.files),
+            [("Cargo.lock".to_string(), 3), ("src/lib.rs".to_string(), 1)]
+        );
+    }
+
+    fn create(date: &str, branch: &str, new_stack: bool) -> Operation {
+        op(
+            date,
+            OperationKind::Create {
+                branch: branch.into(),
+                parent: "main".into(),
+                new_stack,
+            },
+        )
+    }
+
+    #[test]
+    fn test_recap() {
+        let operations = [
+            create("2024-03-01", "feat-0", true), // before the period
+            sync("2024-03-12", 1, 2),
+            merge("2024-03-13", 1),
+            create("2024-03-08", "feat-1", true),
+            create("2024-03-08", "feat-2", false),
+            submit("2024-03-09", 1, 2),
+            submit("2024-03-10", 2, 2),
+        ];
+        let recap = Recap::compute(&operations, at("2024-03-07"));
+
+        assert_eq!(recap.stacks_created, 1);
+        assert_eq!(recap.branches_created, 2);
+        let opened: Vec<u64> = recap.prs_opened.iter().map(|pr| pr.pr_number).collect();
+        assert_eq!(opened, [1, 2]);
+        assert_eq!(recap.prs_merged.len(), 1);
+        assert_eq!(recap.prs_merged[0].into, "main");
+        assert_eq!((recap.syncs, recap.rebased, recap.conflicts), (1, 2, 1));
+        assert!(!recap.is_empty());
+        assert!(Recap::compute(&operations, at("2024-03-14")).is_empty());
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("12h"), Ok(TimeDelta::hours(12)));
+        assert_eq!(parse_period("3d"), Ok(TimeDelta::days(3)));
+        assert_eq!(parse_period("1w"), Ok(TimeDelta::weeks(1)));
+        for invalid in ["", "w", "0d", "-1w", "1y", "1.5w", "weekly"] {
+            assert!(parse_period(invalid).is_err(), "{invalid} parsed");
+        }
+    }
+}
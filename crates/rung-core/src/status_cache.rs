@@ -0,0 +1,107 @@
+//! Cached branch states for `rung status`.
+//!
+//! Working out whether a branch is behind its parent needs a merge base and a
+//! commit count per branch, which adds up in deep stacks over large histories.
+//! Results are kept in `.git/rung/status.json`, keyed by everything they were
+//! computed from (the branch and parent tips, and the base recorded for the
+//! branch), so only branches whose refs moved are recomputed.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stack::{BranchState, Stack, StackBranch};
+
+/// A cached result for one branch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusEntry {
+    /// Ref state the result was computed from (see [`StatusCache::key`]).
+    pub key: String,
+
+    /// Sync state relative to the parent.
+    pub state: BranchState,
+
+    /// Files changed relative to the parent, if they were needed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<String>>,
+}
+
+/// Branch states by branch name, stored in `.git/rung/status.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusCache {
+    /// Entries keyed by local branch name.
+    #[serde(default)]
+    pub branches: BTreeMap<String, StatusEntry>,
+}
+
+impl StatusCache {
+    /// The ref state a branch's status depends on, or `None` if the branch or
+    /// its parent doesn't exist locally (those cases are cheap to work out).
+    #[must_use]
+    pub fn key(repo: &rung_git::Repository, stack: &Stack, branch: &StackBranch) -> Option<String> {
+        let tip = repo.branch_commit(&branch.name).ok()?;
+        let Some(parent) = branch.parent.as_deref() else {
+            return Some(tip.to_string());
+        };
+        let parent_tip = repo.branch_commit(parent).ok()?;
+        // The recorded base only matters for stack parents
+        let base = stack
+            .find_branch(parent)
+            .and(branch.based_on.as_deref())
+            .unwrap_or("");
+        Some(format!("{tip}:{parent_tip}:{base}"))
+    }
+
+    /// Cached entry for `branch`, if it was computed for `key`.
+    #[must_use]
+    pub fn get(&self, branch: &str, key: &str) -> Option<&StatusEntry> {
+        self.branches.get(branch).filter(|entry| entry.key == key)
+    }
+
+    /// Record the result for `branch`. Returns whether the cache changed.
+    pub fn insert(&mut self, branch: impl Into<String>, entry: StatusEntry) -> bool {
+        let branch = branch.into();
+        if self.branches.get(&branch) == Some(&entry) {
+            return false;
+        }
+        self.branches.insert(branch, entry);
+        true
+    }
+
+    /// Drop entries for branches not in `keep`. Returns whether any were dropped.
+    pub fn retain_branches(&mut self, keep: &[&str]) -> bool {
+        let before = self.branches.len();
+        self.branches
+            .retain(|name, _| keep.contains(&name.as_str()));
+        self.branches.len() != before
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str) -> StatusEntry {
+        StatusEntry {
+            key: key.into(),
+            state: BranchState::Diverged { commits_behind: 2 },
+            files: None,
+        }
+    }
+
+    #[test]
+    fn test_get_requires_matching_key() {
+        let mut cache = StatusCache::default();
+        assert!(cache.insert("feature", entry("a:b:")));
+        assert!(!cache.insert("feature", entry("a:b:")));
+        assert_eq!(cache.get("feature", "a:b:"), Some(&entry("a:b:")));
+        assert_eq!(cache.get("feature", "a:c:"), None);
+        assert_eq!(cache.get("other", "a:b:"), None);
+
+        assert!(cache.insert("other", entry("c:d:")));
+        assert!(cache.retain_branches(&["other"]));
+        assert!(!cache.retain_branches(&["other"]));
+        assert!(cache.get("feature", "a:b:").is_none());
+    }
+}
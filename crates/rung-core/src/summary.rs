@@ -0,0 +1,111 @@
+//! A compact view of the stack for editor plugins.
+//!
+//! Editors render stack UI as the user types, far too often to run `rung`
+//! each time. After every change to rung's state, `.git/rung/summary.json` is
+//! rewritten with the stack tree, each branch's PR and last-known state, so
+//! plugins can watch the file instead. It's only rewritten when what it shows
+//! changed, so its mtime is a reliable change signal.
+//!
+//! The schema is stable: fields are only ever added, and `version` changes if
+//! one is removed or changes meaning.
+
+use serde::{Deserialize, Serialize};
+
+use crate::stack::{BranchState, Stack, StackBranch};
+
+/// Current schema version of `summary.json`.
+pub const SUMMARY_VERSION: u32 = 1;
+
+/// The contents of `summary.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    /// Schema version ([`SUMMARY_VERSION`]).
+    pub version: u32,
+
+    /// Whether a sync is paused on a conflict.
+    pub sync_in_progress: bool,
+
+    /// The stack, depth-first: each branch follows its parent.
+    pub branches: Vec<SummaryBranch>,
+}
+
+/// One branch in `summary.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SummaryBranch {
+    /// Branch name.
+    pub name: String,
+
+    /// Parent branch (a trunk, for the stack's roots).
+    pub parent: Option<String>,
+
+    /// Depth below the branch's root (0 for roots).
+    pub depth: usize,
+
+    /// PR number, once submitted.
+    pub pr: Option<u64>,
+
+    /// Sync state relative to the parent, as `rung status` last worked it
+    /// out, or `None` when that's out of date (or caching is disabled).
+    pub state: Option<BranchState>,
+}
+
+impl Summary {
+    /// Summarize `stack`, with each branch's state as `state_of` knows it.
+    #[must_use]
+    pub fn new(
+        stack: &Stack,
+        state_of: impl Fn(&StackBranch) -> Option<BranchState>,
+        sync_in_progress: bool,
+    ) -> Self {
+        let branches = stack
+            .tree()
+            .into_iter()
+            .map(|(depth, branch)| SummaryBranch {
+                name: branch.name.to_string(),
+                parent: branch.parent.as_ref().map(ToString::to_string),
+                depth,
+                pr: branch.pr,
+                state: state_of(branch),
+            })
+            .collect();
+        Self {
+            version: SUMMARY_VERSION,
+            sync_in_progress,
+            branches,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let mut stack = Stack::default();
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.branches[1].pr = Some(7);
+        let state_of = |branch: &StackBranch| {
+            (branch.name == "b").then_some(BranchState::Diverged { commits_behind: 2 })
+        };
+
+        let summary = Summary::new(&stack, state_of, true);
+        assert!(summary.sync_in_progress);
+        let rows: Vec<_> = summary
+            .branches
+            .iter()
+            .map(|b| (b.name.as_str(), b.parent.as_deref(), b.depth, b.pr))
+            .collect();
+        assert_eq!(
+            rows,
+            [("a", Some("main"), 0, Some(7)), ("b", Some("a"), 1, None)]
+        );
+        assert_eq!(summary.branches[0].state, None);
+        assert_eq!(
+            summary.branches[1].state,
+            Some(BranchState::Diverged { commits_behind: 2 })
+        );
+    }
+}
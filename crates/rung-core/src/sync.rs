@@ -3,8 +3,14 @@
 //! This module contains the core logic for the `rung sync` command,
 //! which recursively rebases all branches in a stack when the base moves.
 
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::BranchName;
 use crate::error::Result;
-use crate::stack::Stack;
+use crate::operations::{Operation, OperationKind};
+use crate::stack::{BranchState, Stack};
 use crate::state::State;
 
 /// Result of a sync operation.
@@ -30,6 +36,30 @@ pub enum SyncResult {
         /// Backup ID for potential undo.
         backup_id: String,
     },
+
+    /// Sync paused because the `--exec` command failed on a rebased branch.
+    ///
+    /// The branch stays rebased; `continue_sync` runs the command again
+    /// before moving on.
+    ExecFailed {
+        /// Branch the command failed on.
+        at_branch: String,
+        /// The command that failed.
+        command: String,
+        /// Backup ID for potential undo.
+        backup_id: String,
+    },
+
+    /// Sync stopped early because an interrupt was requested.
+    ///
+    /// Any in-flight rebase was aborted; `continue_sync` resumes from
+    /// `at_branch`.
+    Interrupted {
+        /// Branch that will be rebased next on resume.
+        at_branch: String,
+        /// Backup ID for potential undo.
+        backup_id: String,
+    },
 }
 
 /// Plan for syncing a stack.
@@ -65,15 +95,68 @@ pub struct StaleBranches {
     pub removed: Vec<String>,
 }
 
+/// What the stack looked like after the last completed sync, stored in
+/// `.git/rung/last_sync.json`.
+///
+/// When the fetched base and every branch still match it, nothing a sync
+/// would act on has moved: a merged PR moves the base, and a new push, commit
+/// or PR changes a branch. Sync then returns without asking GitHub.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncMark {
+    /// Base branch the stack was synced onto.
+    pub base: String,
+    /// Tip of the base branch.
+    pub base_tip: String,
+    /// Each branch's tip, parent and PR, keyed by branch name.
+    pub branches: BTreeMap<String, String>,
+}
+
+impl SyncMark {
+    /// Capture the current state, or `None` if some branch is missing or
+    /// differs from what was pushed to origin (the next sync has work to do).
+    #[must_use]
+    pub fn capture(repo: &rung_git::Repository, stack: &Stack, base: &str) -> Option<Self> {
+        let has_origin = repo.origin_url().is_ok();
+        let base_tip = if has_origin {
+            repo.remote_branch_commit(base).ok()?
+        } else {
+            repo.branch_commit(base).ok()?
+        };
+        let branches = stack
+            .branches
+            .iter()
+            .map(|branch| {
+                let tip = repo.branch_commit(&branch.name).ok()?;
+                if has_origin && repo.remote_branch_commit(branch.remote_name()).ok()? != tip {
+                    return None;
+                }
+                let parent = branch.parent.as_deref().unwrap_or("");
+                let pr = branch.pr.map(|pr| pr.to_string()).unwrap_or_default();
+                Some((branch.name.to_string(), format!("{tip}:{parent}:{pr}")))
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            base: base.to_string(),
+            base_tip: base_tip.to_string(),
+            branches,
+        })
+    }
+}
+
 /// Result of reconciling merged PRs and validating PR bases.
 #[derive(Debug, Default)]
 pub struct ReconcileResult {
     /// Branches removed because their PRs merged.
     pub merged: Vec<MergedBranch>,
+    /// Branches removed because their PRs were closed and their remote
+    /// branches deleted.
+    pub closed: Vec<ClosedBranch>,
     /// Branches re-parented to new parents.
     pub reparented: Vec<ReparentedBranch>,
     /// PRs repaired due to ghost parent detection (base mismatch).
     pub repaired: Vec<ReparentedBranch>,
+    /// Branches whose PR head branch was renamed on GitHub.
+    pub renamed: Vec<RenamedBranch>,
 }
 
 /// A branch whose PR was merged.
@@ -87,6 +170,32 @@ pub struct MergedBranch {
     pub merged_into: String,
 }
 
+/// A branch whose PR was closed without merging and whose branch was deleted
+/// on the remote.
+#[derive(Debug)]
+pub struct ClosedBranch {
+    /// Branch name.
+    pub name: String,
+    /// PR number that was closed.
+    pub pr_number: u64,
+    /// Parent its children were moved onto.
+    pub parent: String,
+}
+
+/// A branch whose PR's head branch was renamed on the remote.
+#[derive(Debug)]
+pub struct RenamedBranch {
+    /// Local name before the rename.
+    pub name: BranchName,
+    /// Local name now: the new remote name, or the old name if that was taken
+    /// or the branch already pushed under a name of its own.
+    pub new_name: BranchName,
+    /// Name of the branch on the remote now.
+    pub remote: BranchName,
+    /// PR whose head branch was renamed.
+    pub pr_number: u64,
+}
+
 /// A branch that was re-parented due to its parent being merged.
 #[derive(Debug)]
 pub struct ReparentedBranch {
@@ -109,6 +218,8 @@ pub struct ExternalMergeInfo {
     pub pr_number: u64,
     /// Branch it was merged into.
     pub merged_into: String,
+    /// Commit that landed the PR, if GitHub reported it.
+    pub merge_commit: Option<String>,
 }
 
 /// Reconcile the stack after PRs were merged externally.
@@ -117,23 +228,24 @@ pub struct ExternalMergeInfo {
 /// 1. Re-parent its children to the merge target
 /// 2. Remove the merged branch from the stack
 ///
+/// A branch merged into another branch that was also merged (e.g. into its
+/// parent, just before the parent landed) counts as merged into wherever that
+/// one went, so children never end up on a removed branch.
+///
 /// This function does NOT call GitHub - the caller provides the list of
-/// merged PRs (obtained from GitHub API).
+/// merged PRs (obtained from GitHub API). Nor does it save `stack`, so a dry
+/// run can see the outcome without changing anything.
 ///
 /// # Errors
-/// Returns error if stack operations fail.
+/// Returns error if a merge target isn't a valid branch name.
 pub fn reconcile_merged(
-    state: &State,
+    stack: &mut Stack,
     merged_prs: &[ExternalMergeInfo],
 ) -> Result<ReconcileResult> {
-    if merged_prs.is_empty() {
-        return Ok(ReconcileResult::default());
-    }
-
-    let mut stack = state.load_stack()?;
     let mut result = ReconcileResult::default();
 
     for merge_info in merged_prs {
+        let target = merge_target(merged_prs, &merge_info.merged_into);
         // Find children of the merged branch (collect names first to avoid borrow issues)
         let children: Vec<String> = stack
             .children_of(&merge_info.branch_name)
@@ -150,16 +262,14 @@ pub fn reconcile_merged(
                     .map_or_else(String::new, ToString::to_string);
                 let pr_number = child.pr;
                 // Create validated BranchName for the new parent
-                let new_parent =
-                    crate::BranchName::new(merge_info.merged_into.clone()).map_err(|_| {
-                        crate::error::Error::BranchNotFound(merge_info.merged_into.clone())
-                    })?;
+                let new_parent = crate::BranchName::new(target)
+                    .map_err(|_| crate::error::Error::BranchNotFound(target.to_string()))?;
                 child.parent = Some(new_parent);
 
                 result.reparented.push(ReparentedBranch {
                     name: child_name,
                     old_parent,
-                    new_parent: merge_info.merged_into.clone(),
+                    new_parent: target.to_string(),
                     pr_number,
                 });
             }
@@ -175,26 +285,166 @@ pub fn reconcile_merged(
         });
     }
 
-    // Save updated stack
-    state.save_stack(&stack)?;
-
     Ok(result)
 }
 
+/// Information about a PR that was closed without merging and whose branch
+/// was deleted on the remote (e.g. a mid-stack PR abandoned on GitHub).
+#[derive(Debug)]
+pub struct ExternalCloseInfo {
+    /// Branch whose PR was closed.
+    pub branch_name: String,
+    /// PR number that was closed.
+    pub pr_number: u64,
+}
+
+/// Reconcile the stack after PRs were closed and their branches deleted.
+///
+/// Each closed branch leaves the stack (the local branch is kept), and its
+/// children move onto its parent, keeping the closed branch's commits. Added
+/// to `result` alongside any merges; a child moved twice (its grandparent
+/// was closed too) keeps one `reparented` entry, and base repairs for moved
+/// branches are dropped since the move sets their base.
+pub fn reconcile_closed(
+    stack: &mut Stack,
+    closed_prs: &[ExternalCloseInfo],
+    base_branch: &str,
+    result: &mut ReconcileResult,
+) {
+    for close_info in closed_prs {
+        let Some(parent) = stack
+            .find_branch(&close_info.branch_name)
+            .map(|b| b.parent.clone())
+        else {
+            continue;
+        };
+        let new_parent = parent.as_deref().unwrap_or(base_branch).to_string();
+        let children: Vec<String> = stack
+            .children_of(&close_info.branch_name)
+            .iter()
+            .map(|b| b.name.to_string())
+            .collect();
+
+        for child_name in children {
+            let Some(child) = stack.find_branch_mut(&child_name) else {
+                continue;
+            };
+            child.parent.clone_from(&parent);
+            let pr_number = child.pr;
+            result.repaired.retain(|r| r.name != child_name);
+            if let Some(moved) = result.reparented.iter_mut().find(|r| r.name == child_name) {
+                moved.new_parent.clone_from(&new_parent);
+            } else {
+                result.reparented.push(ReparentedBranch {
+                    name: child_name,
+                    old_parent: close_info.branch_name.clone(),
+                    new_parent: new_parent.clone(),
+                    pr_number,
+                });
+            }
+        }
+
+        stack.remove_branch(&close_info.branch_name);
+        result.repaired.retain(|r| r.name != close_info.branch_name);
+        result.closed.push(ClosedBranch {
+            name: close_info.branch_name.clone(),
+            pr_number: close_info.pr_number,
+            parent: new_parent,
+        });
+    }
+}
+
+/// Information about an open PR whose head branch on GitHub no longer matches
+/// the branch's remote name, because it was renamed in the GitHub UI.
+#[derive(Debug)]
+pub struct ExternalRenameInfo {
+    /// Branch whose PR's head was renamed.
+    pub branch_name: String,
+    /// PR number.
+    pub pr_number: u64,
+    /// The PR's head branch now.
+    pub new_head: String,
+}
+
+/// Reconcile the stack after PR head branches were renamed on GitHub.
+///
+/// A branch pushed under its own name takes the new name, unless `taken`
+/// says a local branch already has it; its children stay attached. Otherwise
+/// only the branch's remote name changes. GitHub retargets PRs based on a
+/// renamed branch, so base repairs that would undo that are dropped. Renames
+/// in the stack only: renaming the local git branches is left to the caller.
+pub fn reconcile_renamed(
+    stack: &mut Stack,
+    renamed_prs: &[ExternalRenameInfo],
+    taken: impl Fn(&str) -> bool,
+    result: &mut ReconcileResult,
+) {
+    for rename in renamed_prs {
+        let Ok(new_head) = BranchName::new(&rename.new_head) else {
+            continue;
+        };
+        let Some(branch) = stack.find_branch_mut(&rename.branch_name) else {
+            continue;
+        };
+        let old_remote = branch.remote_name().to_string();
+        if old_remote == rename.new_head {
+            continue;
+        }
+        let name = branch.name.clone();
+        let local = branch.remote.is_none() && !taken(&rename.new_head);
+        if local {
+            stack.rename_branch(&rename.branch_name, &new_head);
+            for moved in &mut result.reparented {
+                if moved.new_parent == rename.branch_name {
+                    moved.new_parent.clone_from(&rename.new_head);
+                }
+            }
+        } else {
+            branch.remote = (new_head != branch.name).then(|| new_head.clone());
+        }
+        result
+            .repaired
+            .retain(|r| !(r.old_parent == rename.new_head && r.new_parent == old_remote));
+        result.renamed.push(RenamedBranch {
+            new_name: if local {
+                new_head.clone()
+            } else {
+                name.clone()
+            },
+            name,
+            remote: new_head,
+            pr_number: rename.pr_number,
+        });
+    }
+}
+
+/// Where work merged into `branch` ended up, following merges of `branch`
+/// itself. Stops after one step per merge, in case the merges form a cycle.
+fn merge_target<'a>(merged_prs: &'a [ExternalMergeInfo], branch: &'a str) -> &'a str {
+    let mut target = branch;
+    for _ in 0..merged_prs.len() {
+        match merged_prs.iter().find(|m| m.branch_name == target) {
+            Some(merge) => target = &merge.merged_into,
+            None => break,
+        }
+    }
+    target
+}
+
 /// Create a sync plan for the given stack.
 ///
 /// Analyzes which branches need rebasing based on their parent's current position.
 /// Uses proactive cascade: when a branch needs rebasing, all its descendants are
 /// automatically included in the plan, ensuring one sync handles the entire stack.
 ///
-/// Branches are processed in stack order (parents before children) to ensure
-/// each branch is rebased onto the correct target.
+/// Branches are processed in topological order (parents before children) to
+/// ensure each branch is rebased onto the correct target.
 ///
 /// Stale branches (in stack but not in git) are detected and can be cleaned up
 /// by calling `remove_stale_branches`.
 ///
 /// # Errors
-/// Returns error if git operations fail.
+/// Returns error if git operations fail or the stack's parents form a cycle.
 pub fn create_sync_plan(
     repo: &rung_git::Repository,
     stack: &Stack,
@@ -205,14 +455,19 @@ pub fn create_sync_plan(
     // Track branches that need rebasing (including cascaded descendants)
     let mut needs_rebase: std::collections::HashSet<String> = std::collections::HashSet::new();
 
-    // Process branches in stack order (parents before children)
-    for branch in &stack.branches {
+    // Process parents before children, whatever order stack.branches is in
+    for branch in stack.topological_order()? {
         // Skip branches that don't exist locally (stale branches)
         // These will be handled separately by remove_stale_branches
         if !repo.branch_exists(&branch.name) {
             continue;
         }
 
+        // Frozen branches follow their PR; only their children are rebased
+        if branch.frozen {
+            continue;
+        }
+
         // Determine the parent branch name
         let parent_name = branch.parent.as_deref().unwrap_or(base_branch);
 
@@ -237,8 +492,11 @@ pub fn create_sync_plan(
 
         // Determine if this branch needs rebasing:
         // 1. Its merge_base differs from parent tip (direct divergence), OR
+        //    the parent was rewritten underneath it, OR
         // 2. It was marked for cascade rebase (parent was rebased)
-        let needs_direct_rebase = merge_base != parent_commit;
+        let needs_direct_rebase = branch.needs_restack
+            || merge_base != parent_commit
+            || parent_rewritten(repo, stack, branch);
         let needs_cascade_rebase = needs_rebase.contains(branch.name.as_str());
 
         if needs_direct_rebase || needs_cascade_rebase {
@@ -250,7 +508,7 @@ pub fn create_sync_plan(
 
             // Proactive cascade: mark all descendants as needing rebase
             // This ensures the entire sub-tree is synced in one pass
-            for descendant in stack.descendants(&branch.name) {
+            for descendant in stack.descendants_of(&branch.name) {
                 needs_rebase.insert(descendant.name.to_string());
             }
         }
@@ -259,17 +517,143 @@ pub fn create_sync_plan(
     Ok(SyncPlan { branches: actions })
 }
 
-/// Find and remove stale branches from the stack.
+/// Sync state of `branch` relative to its parent.
 ///
-/// A stale branch is one that exists in `stack.json` but not in the local git repository.
-/// This can happen if a branch was deleted externally or if the stack got out of sync.
+/// # Errors
+/// Returns error if the branch or its parent can't be read.
+pub fn branch_state(
+    repo: &rung_git::Repository,
+    stack: &Stack,
+    branch: &crate::stack::StackBranch,
+) -> Result<BranchState> {
+    if branch.needs_restack {
+        return Ok(BranchState::NeedsRestack);
+    }
+    let Some(parent_name) = &branch.parent else {
+        // Root branch, always synced
+        return Ok(BranchState::Synced);
+    };
+
+    // A missing parent (deleted from the stack, or an external branch like main)
+    if !repo.branch_exists(parent_name) {
+        return Ok(BranchState::Detached);
+    }
+
+    // A rewritten parent leaves its old commits under the branch, which can
+    // look synced (or merely behind) by merge base alone
+    if parent_rewritten(repo, stack, branch) {
+        return Ok(BranchState::NeedsRestack);
+    }
+
+    let branch_commit = repo.branch_commit(&branch.name)?;
+    let parent_commit = repo.branch_commit(parent_name)?;
+    let merge_base = repo.merge_base(branch_commit, parent_commit)?;
+
+    // If merge base is the parent commit, we're synced
+    if merge_base == parent_commit {
+        return Ok(BranchState::Synced);
+    }
+
+    let commits_behind = repo.count_commits_between(merge_base, parent_commit)?;
+    Ok(BranchState::Diverged { commits_behind })
+}
+
+/// Check whether `branch`'s parent was rewritten (amended, reset, rebased by
+/// hand) since the branch was last based on it.
 ///
-/// Returns information about the branches that were removed.
+/// Only stack parents are checked, against the base recorded in the stack: it
+/// counts as rewritten when that base is still under the branch but no longer
+/// in the parent's history. Branches without a recorded base are never flagged.
+#[must_use]
+pub fn parent_rewritten(
+    repo: &rung_git::Repository,
+    stack: &Stack,
+    branch: &crate::stack::StackBranch,
+) -> bool {
+    let Some(parent) = branch.parent.as_deref() else {
+        return false;
+    };
+    let Some(base) = branch.based_on.as_deref() else {
+        return false;
+    };
+    if stack.find_branch(parent).is_none() {
+        return false;
+    }
+    let (Ok(base), Ok(parent_tip), Ok(tip)) = (
+        rung_git::Oid::from_str(base),
+        repo.branch_commit(parent),
+        repo.branch_commit(&branch.name),
+    ) else {
+        return false;
+    };
+    let is_ancestor = |ancestor, of| repo.merge_base(ancestor, of).is_ok_and(|m| m == ancestor);
+
+    base != parent_tip && is_ancestor(base, tip) && !is_ancestor(base, parent_tip)
+}
+
+/// Record each branch's parent tip as the base it is now built on.
 ///
 /// # Errors
-/// Returns error if stack operations fail.
-pub fn remove_stale_branches(repo: &rung_git::Repository, state: &State) -> Result<StaleBranches> {
+/// Returns error if the stack can't be loaded or saved.
+pub fn record_bases<S: AsRef<str>>(
+    repo: &rung_git::Repository,
+    state: &State,
+    branches: &[S],
+) -> Result<()> {
+    if branches.is_empty() {
+        return Ok(());
+    }
     let mut stack = state.load_stack()?;
+    set_bases(repo, &mut stack, branches);
+    state.save_stack(&stack)
+}
+
+/// Set `base` on each of `branches` to its parent's current tip, which
+/// they've just been rebased onto.
+fn set_bases<S: AsRef<str>>(repo: &rung_git::Repository, stack: &mut Stack, branches: &[S]) {
+    for name in branches {
+        let Some(branch) = stack.find_branch_mut(name.as_ref()) else {
+            continue;
+        };
+        branch.needs_restack = false;
+        branch.based_on = branch
+            .parent
+            .as_deref()
+            .and_then(|parent| repo.branch_commit(parent).ok())
+            .map(|tip| tip.to_string());
+    }
+}
+
+/// Rebase the checked-out `branch` onto `target`.
+///
+/// If its parent was rewritten, or landed without the branch being rebased,
+/// only the branch's own commits (those after its recorded base) are
+/// replayed, so the parent's old commits are dropped.
+fn rebase_branch(
+    repo: &rung_git::Repository,
+    stack: &Stack,
+    branch: Option<&crate::stack::StackBranch>,
+    target: rung_git::Oid,
+    options: rung_git::RebaseOptions,
+) -> rung_git::Result<()> {
+    let old_base = branch
+        .filter(|b| b.needs_restack || parent_rewritten(repo, stack, b))
+        .and_then(|b| b.based_on.as_deref())
+        .and_then(|base| rung_git::Oid::from_str(base).ok());
+    old_base.map_or_else(
+        || repo.rebase_onto(target, options),
+        |old_base| repo.rebase_onto_from(target, old_base, options),
+    )
+}
+
+/// Find and remove stale branches from the stack.
+///
+/// A stale branch is one that exists in `stack.json` but not in the local git repository.
+/// This can happen if a branch was deleted externally or if the stack got out of sync.
+///
+/// Returns information about the branches that were removed. The caller is
+/// responsible for saving `stack`.
+pub fn remove_stale_branches(repo: &rung_git::Repository, stack: &mut Stack) -> StaleBranches {
     let mut removed = Vec::new();
 
     // Find branches that don't exist locally
@@ -281,7 +665,7 @@ pub fn remove_stale_branches(repo: &rung_git::Repository, state: &State) -> Resu
         .collect();
 
     if missing.is_empty() {
-        return Ok(StaleBranches::default());
+        return StaleBranches::default();
     }
 
     // For each stale branch, re-parent its children to its parent
@@ -304,15 +688,18 @@ pub fn remove_stale_branches(repo: &rung_git::Repository, state: &State) -> Resu
     stack
         .branches
         .retain(|b| !missing.contains(&b.name.to_string()));
-    state.save_stack(&stack)?;
 
-    Ok(StaleBranches { removed })
+    StaleBranches { removed }
 }
 
 /// Execute a sync operation.
 ///
-/// Rebases all branches in the plan onto their new bases. If a conflict occurs,
-/// the sync is paused and can be continued with `continue_sync` after resolution.
+/// Rebases all branches in the plan onto their new bases, running `exec` (if
+/// given) after each one. If a conflict occurs or `exec` fails, the sync is
+/// paused and can be continued with `continue_sync` after resolution.
+///
+/// The branches are backed up first, recorded as taken by `operation` (the
+/// command running the sync), so `rung undo` can restore them.
 ///
 /// # Errors
 /// Returns error if sync fails.
@@ -320,6 +707,8 @@ pub fn execute_sync(
     repo: &rung_git::Repository,
     state: &State,
     plan: SyncPlan,
+    exec: Option<&str>,
+    operation: &str,
 ) -> Result<SyncResult> {
     use crate::state::SyncState;
 
@@ -343,7 +732,7 @@ pub fn execute_sync(
         .map(|(b, c)| (b.as_str(), c.as_str()))
         .collect();
 
-    let backup_id = state.create_backup(&backup_refs)?;
+    let backup_id = state.create_backup(operation, &backup_refs)?;
 
     // Save original branch to restore later
     let original_branch = repo.current_branch().ok();
@@ -351,26 +740,50 @@ pub fn execute_sync(
     // Create sync state
     let branch_names: Vec<String> = plan.branches.iter().map(|a| a.branch.clone()).collect();
     let mut sync_state = SyncState::new(backup_id.clone(), branch_names);
+    sync_state.exec = exec.map(String::from);
+    sync_state.worktree = repo.workdir().map(std::path::Path::to_path_buf);
     state.save_sync_state(&sync_state)?;
 
+    let config = state.load_config()?;
+    let stack = state.load_stack()?;
+
     // Execute each rebase
     for action in plan.branches {
+        if crate::interrupt::is_requested() {
+            return stop_interrupted(repo, state, &sync_state, original_branch.as_deref());
+        }
+
         // Checkout the branch
         repo.checkout(&action.branch)?;
 
-        // Get target commit
-        let new_base = rung_git::Oid::from_str(&action.new_base)
-            .map_err(|e| crate::error::Error::RebaseFailed(action.branch.clone(), e.to_string()))?;
+        // Get target commit: a stack parent may have been rebased just now,
+        // so use its current tip rather than the one planned
+        let branch = stack.find_branch(&action.branch);
+        let new_base = match branch.and_then(|b| b.parent.as_deref()) {
+            Some(parent) if stack.find_branch(parent).is_some() => repo.branch_commit(parent)?,
+            _ => rung_git::Oid::from_str(&action.new_base).map_err(|e| {
+                crate::error::Error::RebaseFailed(action.branch.clone(), e.to_string())
+            })?,
+        };
 
         // Rebase onto new base
-        match repo.rebase_onto(new_base) {
+        let options = rebase_options(&config, branch);
+        match rebase_branch(repo, &stack, branch, new_base, options) {
+            // An interrupted git process looks like a failed rebase
+            Err(_) if crate::interrupt::is_requested() => {
+                return stop_interrupted(repo, state, &sync_state, original_branch.as_deref());
+            }
             Ok(()) => {
+                if let Some(paused) = run_exec(repo, state, &sync_state)? {
+                    return Ok(paused);
+                }
                 // Success - mark as complete and save state
                 sync_state.advance();
                 state.save_sync_state(&sync_state)?;
             }
             Err(rung_git::Error::RebaseConflict(files)) => {
                 // Conflict - save state and return Paused
+                sync_state.record_conflict(&action.branch, &files);
                 state.save_sync_state(&sync_state)?;
                 return Ok(SyncResult::Paused {
                     at_branch: action.branch,
@@ -389,6 +802,7 @@ pub fn execute_sync(
 
     // All done - clean up sync state
     state.clear_sync_state()?;
+    record_synced(repo, state, &sync_state)?;
 
     // Restore original branch if possible
     if let Some(branch) = original_branch {
@@ -401,9 +815,12 @@ pub fn execute_sync(
     })
 }
 
-/// Continue a paused sync after conflict resolution.
+/// Continue a paused or interrupted sync.
 ///
-/// User must have resolved conflicts and staged the changes before calling this.
+/// After a conflict, the user must have resolved and staged the changes
+/// before calling this. After an interrupt or a failed `--exec` command,
+/// nothing is in flight and the branch the sync stopped at is rebased onto
+/// its parent again (a no-op if it already is) and the command re-run.
 ///
 /// # Errors
 /// Returns error if no sync in progress or continuation fails.
@@ -412,33 +829,46 @@ pub fn continue_sync(repo: &rung_git::Repository, state: &State) -> Result<SyncR
     let mut sync_state = state.load_sync_state()?;
     let backup_id = sync_state.backup_id.clone();
 
-    // Continue the current rebase
-    match repo.rebase_continue() {
-        Ok(()) => {
-            // Success - mark current branch as complete
-            sync_state.advance();
-            state.save_sync_state(&sync_state)?;
-        }
-        Err(rung_git::Error::RebaseConflict(files)) => {
-            // More conflicts
-            return Ok(SyncResult::Paused {
-                at_branch: sync_state.current_branch.clone(),
-                conflict_files: files,
-                backup_id,
-            });
-        }
-        Err(e) => {
-            return Err(e.into());
+    // Continue the current rebase, if one was left paused on a conflict
+    if repo.is_rebasing() {
+        match repo.rebase_continue() {
+            Ok(()) => {
+                if let Some(paused) = run_exec(repo, state, &sync_state)? {
+                    return Ok(paused);
+                }
+                // Success - mark current branch as complete
+                sync_state.advance();
+                state.save_sync_state(&sync_state)?;
+            }
+            Err(rung_git::Error::RebaseConflict(files)) => {
+                // More conflicts
+                let branch = sync_state.current_branch.clone();
+                sync_state.record_conflict(&branch, &files);
+                state.save_sync_state(&sync_state)?;
+                return Ok(SyncResult::Paused {
+                    at_branch: sync_state.current_branch.clone(),
+                    conflict_files: files,
+                    backup_id,
+                });
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
         }
     }
 
-    // Process remaining branches
-    for branch_name in sync_state.remaining.clone() {
+    // Process remaining branches, starting with the current one
+    while !sync_state.current_branch.is_empty() {
+        if crate::interrupt::is_requested() {
+            return stop_interrupted(repo, state, &sync_state, None);
+        }
+
+        let branch_name = sync_state.current_branch.clone();
+
         // Checkout the branch
         repo.checkout(&branch_name)?;
 
-        // Get parent's current tip (we need to look this up from the stack)
-        // For now, we'll get the merge base and target from the previous branch
+        // Earlier branches may have moved, so rebase onto the parent's current tip
         let stack = state.load_stack()?;
         let branch = stack
             .find_branch(&branch_name)
@@ -448,12 +878,20 @@ pub fn continue_sync(repo: &rung_git::Repository, state: &State) -> Result<SyncR
         let parent_commit = repo.branch_commit(parent_name)?;
 
         // Rebase onto parent's tip
-        match repo.rebase_onto(parent_commit) {
+        let options = rebase_options(&state.load_config()?, Some(branch));
+        match rebase_branch(repo, &stack, Some(branch), parent_commit, options) {
+            Err(_) if crate::interrupt::is_requested() => {
+                return stop_interrupted(repo, state, &sync_state, None);
+            }
             Ok(()) => {
+                if let Some(paused) = run_exec(repo, state, &sync_state)? {
+                    return Ok(paused);
+                }
                 sync_state.advance();
                 state.save_sync_state(&sync_state)?;
             }
             Err(rung_git::Error::RebaseConflict(files)) => {
+                sync_state.record_conflict(&branch_name, &files);
                 state.save_sync_state(&sync_state)?;
                 return Ok(SyncResult::Paused {
                     at_branch: branch_name,
@@ -471,6 +909,7 @@ pub fn continue_sync(repo: &rung_git::Repository, state: &State) -> Result<SyncR
 
     // All done
     state.clear_sync_state()?;
+    record_synced(repo, state, &sync_state)?;
 
     Ok(SyncResult::Complete {
         branches_rebased: sync_state.completed.len(),
@@ -478,6 +917,113 @@ pub fn continue_sync(repo: &rung_git::Repository, state: &State) -> Result<SyncR
     })
 }
 
+/// How to rebase `branch`: the global `[sync]` settings, plus its own overrides.
+#[must_use]
+pub fn rebase_options(
+    config: &crate::config::Config,
+    branch: Option<&crate::stack::StackBranch>,
+) -> rung_git::RebaseOptions {
+    rung_git::RebaseOptions {
+        rebase_merges: config.sync.rebase_merges || branch.is_some_and(|b| b.rebase_merges),
+        committer_date_is_author_date: config.sync.committer_date_is_author_date,
+    }
+}
+
+/// Run the sync's `--exec` command on the branch just rebased.
+///
+/// Returns `Some(ExecFailed)` if the command failed, leaving the sync state
+/// at this branch so `continue_sync` retries it. The command's output goes
+/// to stderr so `--json` output stays parseable.
+fn run_exec(
+    repo: &rung_git::Repository,
+    state: &State,
+    sync_state: &crate::state::SyncState,
+) -> Result<Option<SyncResult>> {
+    let Some(command) = &sync_state.exec else {
+        return Ok(None);
+    };
+    let workdir = repo.workdir().ok_or(crate::error::Error::NotARepository)?;
+
+    let status = crate::shell::command(command)
+        .current_dir(workdir)
+        .stdout(std::io::stderr())
+        .status()?;
+    if status.success() {
+        return Ok(None);
+    }
+    // Ctrl-C reaches the command too; treat that as an interrupt, not a failure
+    if crate::interrupt::is_requested() {
+        return stop_interrupted(repo, state, sync_state, None).map(Some);
+    }
+
+    state.save_sync_state(sync_state)?;
+    Ok(Some(SyncResult::ExecFailed {
+        at_branch: sync_state.current_branch.clone(),
+        command: command.clone(),
+        backup_id: sync_state.backup_id.clone(),
+    }))
+}
+
+/// Stop a sync at the current branch so `continue_sync` can pick it up.
+///
+/// Aborts any half-applied rebase, which leaves the current branch as it was
+/// before this step, and keeps the sync state on disk.
+fn stop_interrupted(
+    repo: &rung_git::Repository,
+    state: &State,
+    sync_state: &crate::state::SyncState,
+    original_branch: Option<&str>,
+) -> Result<SyncResult> {
+    if repo.is_rebasing() {
+        repo.rebase_abort()?;
+    }
+    state.save_sync_state(sync_state)?;
+
+    if let Some(branch) = original_branch {
+        let _ = repo.checkout(branch); // Best effort
+    }
+
+    Ok(SyncResult::Interrupted {
+        at_branch: sync_state.current_branch.clone(),
+        backup_id: sync_state.backup_id.clone(),
+    })
+}
+
+/// Stamp `last_synced` and the new base on branches that were rebased by a
+/// completed sync, and add the sync to the operations log.
+fn record_synced(
+    repo: &rung_git::Repository,
+    state: &State,
+    sync_state: &crate::state::SyncState,
+) -> Result<()> {
+    let branches = &sync_state.completed;
+    if branches.is_empty() {
+        return Ok(());
+    }
+
+    let mut stack = state.load_stack()?;
+    let now = chrono::Utc::now();
+    for name in branches {
+        if let Some(branch) = stack.find_branch_mut(name) {
+            branch.last_synced = Some(now);
+        }
+    }
+    set_bases(repo, &mut stack, branches);
+    state.save_stack(&stack)?;
+
+    // The log only feeds `rung stats`, so failing to write it isn't fatal
+    let _ = state.record_operation(&Operation::finished(
+        sync_state.started_at,
+        OperationKind::Sync {
+            rebased: branches.len(),
+            conflicts: sync_state.conflicts,
+            conflicted: sync_state.conflicted.clone(),
+            stack_depth: stack.depth(),
+        },
+    ));
+    Ok(())
+}
+
 /// Abort a paused sync and restore from backup.
 ///
 /// # Errors
@@ -505,6 +1051,99 @@ pub fn abort_sync(repo: &rung_git::Repository, state: &State) -> Result<()> {
     Ok(())
 }
 
+/// Result of restacking a branch's descendants after it was rewritten.
+#[derive(Debug)]
+pub enum RestackResult {
+    /// All descendants were rebased onto their parents' new tips.
+    Complete {
+        /// Branches that were rebased, in order.
+        rebased: Vec<String>,
+    },
+
+    /// A descendant hit conflicts; the rebase is left in progress.
+    Conflict {
+        /// Branch where the conflict occurred.
+        at_branch: String,
+        /// Files with conflicts.
+        conflict_files: Vec<String>,
+        /// Branches rebased before the conflict.
+        rebased: Vec<String>,
+    },
+}
+
+/// Capture the current tips of the given branches.
+///
+/// Used before rewriting history so descendants can later be replayed with
+/// `rebase --onto <new> <old>`, bringing only their own commits.
+///
+/// # Errors
+/// Returns error if any branch doesn't exist.
+pub fn capture_tips<S: AsRef<str>>(
+    repo: &rung_git::Repository,
+    branches: &[S],
+) -> Result<std::collections::HashMap<String, rung_git::Oid>> {
+    branches
+        .iter()
+        .map(|b| {
+            let name = b.as_ref();
+            Ok((name.to_string(), repo.branch_commit(name)?))
+        })
+        .collect()
+}
+
+/// Rebase every descendant of `branch` onto its parent's current tip.
+///
+/// `old_tips` must contain the pre-rewrite tip of `branch` and of each
+/// descendant (see [`capture_tips`]). Descendants whose parent didn't move
+/// are skipped. Rebases use [`rebase_options`] for each descendant. The caller
+/// is responsible for restoring the checked-out branch.
+///
+/// # Errors
+/// Returns error if a tip is missing or a rebase fails for a reason other than conflicts.
+pub fn restack_descendants<H: std::hash::BuildHasher>(
+    repo: &rung_git::Repository,
+    stack: &Stack,
+    config: &crate::config::Config,
+    branch: &str,
+    old_tips: &std::collections::HashMap<String, rung_git::Oid, H>,
+) -> Result<RestackResult> {
+    let mut rebased = Vec::new();
+
+    for descendant in stack.descendants_of(branch) {
+        let Some(parent) = descendant.parent.as_deref() else {
+            continue;
+        };
+        let old_base = old_tips
+            .get(parent)
+            .copied()
+            .ok_or_else(|| crate::error::Error::BranchNotFound(parent.to_string()))?;
+        let new_base = repo.branch_commit(parent)?;
+
+        if old_base == new_base {
+            continue;
+        }
+
+        repo.checkout(&descendant.name)?;
+        let options = rebase_options(config, Some(descendant));
+        match repo.rebase_onto_from(new_base, old_base, options) {
+            Ok(()) => rebased.push(descendant.name.to_string()),
+            Err(rung_git::Error::RebaseConflict(files)) => {
+                return Ok(RestackResult::Conflict {
+                    at_branch: descendant.name.to_string(),
+                    conflict_files: files,
+                    rebased,
+                });
+            }
+            Err(e) => {
+                let _ = repo.rebase_abort(); // Best effort
+                return Err(e.into());
+            }
+        }
+    }
+
+    Ok(RestackResult::Complete { rebased })
+}
+
 /// Result of an undo operation.
 #[derive(Debug)]
 pub struct UndoResult {
@@ -521,24 +1160,50 @@ pub struct UndoResult {
 /// # Errors
 /// Returns error if no backup found or undo fails.
 pub fn undo_sync(repo: &rung_git::Repository, state: &State) -> Result<UndoResult> {
-    // Find latest backup
     let backup_id = state.latest_backup()?;
-    let refs = state.load_backup(&backup_id)?;
+    let branches: Vec<String> = state
+        .load_backup(&backup_id)?
+        .into_iter()
+        .map(|(branch, _)| branch)
+        .collect();
+    undo_sync_branches(repo, state, &branches)
+}
 
-    // Reset each branch to its saved SHA
-    for (branch_name, sha) in &refs {
+/// Undo the last backed-up operation for some of its branches.
+///
+/// Restores `branches` to their state before the most recent backup, taken
+/// by a sync or any other command that rewrites history. The
+/// others stay in the backup, so a later undo can still restore them; the
+/// backup is deleted once every branch has been restored.
+///
+/// # Errors
+/// Returns error if no backup found, a branch isn't in it, or undo fails.
+pub fn undo_sync_branches(
+    repo: &rung_git::Repository,
+    state: &State,
+    branches: &[String],
+) -> Result<UndoResult> {
+    let backup_id = state.latest_backup()?;
+    let refs = state.load_backup(&backup_id)?;
+    let mut restore = vec![];
+    for branch in branches {
+        let (_, sha) = refs
+            .iter()
+            .find(|(name, _)| name == branch)
+            .ok_or_else(|| crate::error::Error::BranchNotFound(branch.clone()))?;
         let oid = rung_git::Oid::from_str(sha)
-            .map_err(|e| crate::error::Error::RebaseFailed(branch_name.clone(), e.to_string()))?;
-        repo.reset_branch(branch_name, oid)?;
+            .map_err(|e| crate::error::Error::RebaseFailed(branch.clone(), e.to_string()))?;
+        restore.push((branch, oid));
     }
 
-    let branches_restored = refs.len();
-
-    // Delete the backup after successful restore
-    state.delete_backup(&backup_id)?;
+    // Reset each branch to its saved SHA, then drop it from the backup
+    for (branch, oid) in &restore {
+        repo.reset_branch(branch, *oid)?;
+        state.remove_from_backup(&backup_id, branch)?;
+    }
 
     Ok(UndoResult {
-        branches_restored,
+        branches_restored: restore.len(),
         backup_id,
     })
 }
@@ -593,6 +1258,280 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_restack_descendants_after_amend() {
+        let (temp, rung_repo, git_repo) = init_test_repo();
+        let main_branch = rung_repo.current_branch().unwrap();
+        // `git rebase` shells out and needs an identity to re-commit
+        let mut config = git_repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let force_checkout = |branch: &str| {
+            git_repo.set_head(&format!("refs/heads/{branch}")).unwrap();
+            git_repo
+                .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+                .unwrap();
+        };
+
+        // main → feature-a → feature-b, one commit each
+        rung_repo
+            .create_branch(&BranchName::new("feature-a").unwrap())
+            .unwrap();
+        force_checkout("feature-a");
+        add_commit(&temp, &git_repo, "a.txt", "A commit");
+        rung_repo
+            .create_branch(&BranchName::new("feature-b").unwrap())
+            .unwrap();
+        force_checkout("feature-b");
+        add_commit(&temp, &git_repo, "b.txt", "B commit");
+
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("feature-a", Some(main_branch)).unwrap());
+        stack.add_branch(StackBranch::try_new("feature-b", Some("feature-a")).unwrap());
+
+        let old_tips = capture_tips(&rung_repo, &["feature-a", "feature-b"]).unwrap();
+
+        // Rewrite feature-a by adding another commit
+        force_checkout("feature-a");
+        add_commit(&temp, &git_repo, "a2.txt", "A second commit");
+
+        let result = restack_descendants(
+            &rung_repo,
+            &stack,
+            &crate::config::Config::default(),
+            "feature-a",
+            &old_tips,
+        )
+        .unwrap();
+        let RestackResult::Complete { rebased } = result else {
+            panic!("expected restack to complete");
+        };
+        assert_eq!(rebased, vec!["feature-b".to_string()]);
+
+        let a_tip = rung_repo.branch_commit("feature-a").unwrap();
+        let b_tip = rung_repo.branch_commit("feature-b").unwrap();
+        assert_eq!(rung_repo.merge_base(a_tip, b_tip).unwrap(), a_tip);
+        assert_eq!(rung_repo.count_commits_between(a_tip, b_tip).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_exec_failure_pauses_and_continue_retries() {
+        let (temp, rung_repo, git_repo) = init_test_repo();
+        let main_branch = rung_repo.current_branch().unwrap();
+        let mut config = git_repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let force_checkout = |branch: &str| {
+            git_repo.set_head(&format!("refs/heads/{branch}")).unwrap();
+            git_repo
+                .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+                .unwrap();
+        };
+
+        // main → feature-a → feature-b, then main moves on
+        rung_repo
+            .create_branch(&BranchName::new("feature-a").unwrap())
+            .unwrap();
+        force_checkout("feature-a");
+        add_commit(&temp, &git_repo, "a.txt", "A commit");
+        rung_repo
+            .create_branch(&BranchName::new("feature-b").unwrap())
+            .unwrap();
+        force_checkout("feature-b");
+        add_commit(&temp, &git_repo, "b.txt", "B commit");
+        force_checkout(&main_branch);
+        add_commit(&temp, &git_repo, "main.txt", "Main moved");
+
+        let state = State::new(temp.path()).unwrap();
+        state.init().unwrap();
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("feature-a", Some(main_branch.clone())).unwrap());
+        stack.add_branch(StackBranch::try_new("feature-b", Some("feature-a")).unwrap());
+        state.save_stack(&stack).unwrap();
+
+        // The check fails on feature-b until it is "fixed"
+        let exec = "test ! -f b.txt || test -f fixed";
+        let plan = create_sync_plan(&rung_repo, &stack, &main_branch).unwrap();
+        let result = execute_sync(&rung_repo, &state, plan, Some(exec), "sync").unwrap();
+        assert!(matches!(
+            &result,
+            SyncResult::ExecFailed { at_branch, command, .. }
+                if at_branch == "feature-b" && command == exec
+        ));
+        let sync_state = state.load_sync_state().unwrap();
+        assert_eq!(sync_state.completed, vec!["feature-a".to_string()]);
+        assert_eq!(sync_state.exec.as_deref(), Some(exec));
+
+        // Still failing: stays paused
+        let result = continue_sync(&rung_repo, &state).unwrap();
+        assert!(matches!(result, SyncResult::ExecFailed { .. }));
+
+        std::fs::write(temp.path().join("fixed"), "").unwrap();
+        let result = continue_sync(&rung_repo, &state).unwrap();
+        assert!(matches!(
+            result,
+            SyncResult::Complete {
+                branches_rebased: 2,
+                ..
+            }
+        ));
+        assert!(!state.is_sync_in_progress());
+
+        let main_tip = rung_repo.branch_commit(&main_branch).unwrap();
+        let b_tip = rung_repo.branch_commit("feature-b").unwrap();
+        assert_eq!(rung_repo.count_commits_between(main_tip, b_tip).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_sync_restacks_branch_whose_parent_landed() {
+        let (temp, rung_repo, git_repo) = init_test_repo();
+        let main_branch = rung_repo.current_branch().unwrap();
+        let mut config = git_repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let force_checkout = |branch: &str| {
+            git_repo.set_head(&format!("refs/heads/{branch}")).unwrap();
+            git_repo
+                .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+                .unwrap();
+        };
+
+        // main → feature-a → feature-b, then feature-a lands squashed while
+        // feature-b is left as it was
+        rung_repo
+            .create_branch(&BranchName::new("feature-a").unwrap())
+            .unwrap();
+        force_checkout("feature-a");
+        add_commit(&temp, &git_repo, "a.txt", "A commit");
+        let a_tip = rung_repo.branch_commit("feature-a").unwrap();
+        rung_repo
+            .create_branch(&BranchName::new("feature-b").unwrap())
+            .unwrap();
+        force_checkout("feature-b");
+        add_commit(&temp, &git_repo, "b.txt", "B commit");
+        force_checkout(&main_branch);
+        add_commit(&temp, &git_repo, "squashed.txt", "Squashed A");
+
+        let state = State::new(temp.path()).unwrap();
+        state.init().unwrap();
+        let mut stack = Stack::new();
+        let mut b = StackBranch::try_new("feature-b", Some(main_branch.clone())).unwrap();
+        b.based_on = Some(a_tip.to_string());
+        b.needs_restack = true;
+        stack.add_branch(b);
+        state.save_stack(&stack).unwrap();
+        assert_eq!(
+            branch_state(&rung_repo, &stack, &stack.branches[0]).unwrap(),
+            BranchState::NeedsRestack
+        );
+
+        // Only feature-b's own commit is replayed, and the mark is cleared
+        let plan = create_sync_plan(&rung_repo, &stack, &main_branch).unwrap();
+        let result = execute_sync(&rung_repo, &state, plan, None, "sync").unwrap();
+        assert!(matches!(result, SyncResult::Complete { .. }));
+        let main_tip = rung_repo.branch_commit(&main_branch).unwrap();
+        let b_tip = rung_repo.branch_commit("feature-b").unwrap();
+        assert_eq!(rung_repo.merge_base(main_tip, b_tip).unwrap(), main_tip);
+        assert_eq!(rung_repo.count_commits_between(main_tip, b_tip).unwrap(), 1);
+        assert!(!state.load_stack().unwrap().branches[0].needs_restack);
+    }
+
+    #[test]
+    fn test_continue_sync_resumes_interrupted_sync() {
+        let (temp, rung_repo, git_repo) = init_test_repo();
+        let main_branch = rung_repo.current_branch().unwrap();
+        let mut config = git_repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let force_checkout = |branch: &str| {
+            git_repo.set_head(&format!("refs/heads/{branch}")).unwrap();
+            git_repo
+                .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+                .unwrap();
+        };
+
+        // main → feature-a → feature-b, then main moves on
+        rung_repo
+            .create_branch(&BranchName::new("feature-a").unwrap())
+            .unwrap();
+        force_checkout("feature-a");
+        add_commit(&temp, &git_repo, "a.txt", "A commit");
+        rung_repo
+            .create_branch(&BranchName::new("feature-b").unwrap())
+            .unwrap();
+        force_checkout("feature-b");
+        add_commit(&temp, &git_repo, "b.txt", "B commit");
+        force_checkout(&main_branch);
+        add_commit(&temp, &git_repo, "main.txt", "Main moved");
+
+        let state = State::new(temp.path()).unwrap();
+        state.init().unwrap();
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("feature-a", Some(main_branch.clone())).unwrap());
+        stack.add_branch(StackBranch::try_new("feature-b", Some("feature-a")).unwrap());
+        state.save_stack(&stack).unwrap();
+
+        // Sync state as left behind by an interrupt before the first rebase
+        let backup_id = state.create_backup("sync", &[]).unwrap();
+        let sync_state = crate::state::SyncState::new(
+            backup_id,
+            vec!["feature-a".to_string(), "feature-b".to_string()],
+        );
+        state.save_sync_state(&sync_state).unwrap();
+
+        let result = continue_sync(&rung_repo, &state).unwrap();
+        assert!(matches!(
+            result,
+            SyncResult::Complete {
+                branches_rebased: 2,
+                ..
+            }
+        ));
+        assert!(!state.is_sync_in_progress());
+
+        let main_tip = rung_repo.branch_commit(&main_branch).unwrap();
+        let b_tip = rung_repo.branch_commit("feature-b").unwrap();
+        assert_eq!(rung_repo.merge_base(main_tip, b_tip).unwrap(), main_tip);
+        assert_eq!(rung_repo.count_commits_between(main_tip, b_tip).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_undo_sync_branches() {
+        let (temp, rung_repo, git_repo) = init_test_repo();
+        let main_branch = rung_repo.current_branch().unwrap();
+        let old = rung_repo.branch_commit(&main_branch).unwrap();
+        rung_repo
+            .create_branch(&BranchName::new("feature-a").unwrap())
+            .unwrap();
+        rung_repo
+            .create_branch(&BranchName::new("feature-b").unwrap())
+            .unwrap();
+
+        let state = State::new(temp.path()).unwrap();
+        state.init().unwrap();
+        let sha = old.to_string();
+        state
+            .create_backup("sync", &[("feature-a", &sha), ("feature-b", &sha)])
+            .unwrap();
+        add_commit(&temp, &git_repo, "main.txt", "Main moved");
+        let new = rung_repo.branch_commit(&main_branch).unwrap();
+        rung_repo.reset_branch("feature-a", new).unwrap();
+        rung_repo.reset_branch("feature-b", new).unwrap();
+
+        assert!(undo_sync_branches(&rung_repo, &state, &["feature-c".into()]).is_err());
+
+        // Only feature-b is restored, and feature-a can still be
+        let result = undo_sync_branches(&rung_repo, &state, &["feature-b".into()]).unwrap();
+        assert_eq!(result.branches_restored, 1);
+        assert_eq!(rung_repo.branch_commit("feature-a").unwrap(), new);
+        assert_eq!(rung_repo.branch_commit("feature-b").unwrap(), old);
+
+        let result = undo_sync(&rung_repo, &state).unwrap();
+        assert_eq!(result.branches_restored, 1);
+        assert_eq!(rung_repo.branch_commit("feature-a").unwrap(), old);
+        assert!(state.latest_backup().is_err());
+    }
+
     #[test]
     fn test_sync_plan_empty_when_synced() {
         let (_temp, rung_repo, git_repo) = init_test_repo();
@@ -637,6 +1576,30 @@ mod tests {
         assert_eq!(plan.branches[0].branch, "feature-a");
     }
 
+    #[test]
+    fn test_branch_state() {
+        let (temp, rung_repo, git_repo) = init_test_repo();
+        let main_branch = rung_repo.current_branch().unwrap();
+        let head = git_repo.head().unwrap().peel_to_commit().unwrap();
+        git_repo.branch("feature-a", &head, false).unwrap();
+
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("feature-a", Some(main_branch)).unwrap());
+        stack.add_branch(StackBranch::try_new("orphan", Some("gone")).unwrap());
+        let state = |name: &str| {
+            branch_state(&rung_repo, &stack, stack.find_branch(name).unwrap()).unwrap()
+        };
+        assert_eq!(state("feature-a"), BranchState::Synced);
+        assert_eq!(state("orphan"), BranchState::Detached);
+
+        add_commit(&temp, &git_repo, "one.txt", "One");
+        add_commit(&temp, &git_repo, "two.txt", "Two");
+        assert_eq!(
+            state("feature-a"),
+            BranchState::Diverged { commits_behind: 2 }
+        );
+    }
+
     #[test]
     fn test_sync_plan_chain() {
         let (temp, rung_repo, git_repo) = init_test_repo();
@@ -678,6 +1641,18 @@ mod tests {
         assert_eq!(plan.branches.len(), 2);
         assert_eq!(plan.branches[0].branch, "feature-a");
         assert_eq!(plan.branches[1].branch, "feature-b");
+
+        // Child listed before its parent still plans the parent first
+        stack.branches.reverse();
+        let plan = create_sync_plan(&rung_repo, &stack, &main_branch).unwrap();
+        assert_eq!(plan.branches.len(), 2);
+        assert_eq!(plan.branches[0].branch, "feature-a");
+        assert_eq!(plan.branches[1].branch, "feature-b");
+
+        // A frozen parent is never rebased, and feature-b is already on it
+        stack.find_branch_mut("feature-a").unwrap().frozen = true;
+        let plan = create_sync_plan(&rung_repo, &stack, &main_branch).unwrap();
+        assert!(plan.is_empty());
     }
 
     #[test]
@@ -741,4 +1716,192 @@ mod tests {
         assert_eq!(plan.branches[2].branch, "feature-c");
         assert_eq!(plan.branches[3].branch, "feature-d");
     }
+
+    #[test]
+    fn test_reconcile_follows_merged_merge_target() {
+        let mut stack = Stack::default();
+        stack.add_branch(StackBranch::try_new("a", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("b", Some("a")).unwrap());
+        stack.add_branch(StackBranch::try_new("c", Some("b")).unwrap());
+
+        // b was merged into a just before a itself landed on main
+        let merged = |name: &str, pr, into: &str| ExternalMergeInfo {
+            branch_name: name.into(),
+            pr_number: pr,
+            merged_into: into.into(),
+            merge_commit: None,
+        };
+        let result =
+            reconcile_merged(&mut stack, &[merged("b", 2, "a"), merged("a", 1, "main")]).unwrap();
+
+        assert_eq!(stack.len(), 1);
+        assert_eq!(
+            stack.find_branch("c").unwrap().parent.as_deref(),
+            Some("main")
+        );
+        let reparented: Vec<(&str, &str)> = result
+            .reparented
+            .iter()
+            .map(|r| (r.name.as_str(), r.new_parent.as_str()))
+            .collect();
+        assert_eq!(reparented, [("c", "main")]);
+
+        // Merges that loop stop rather than spin
+        let mut stack = Stack::default();
+        stack.add_branch(StackBranch::try_new("x", Some("main")).unwrap());
+        stack.add_branch(StackBranch::try_new("y", Some("x")).unwrap());
+        reconcile_merged(&mut stack, &[merged("x", 1, "y"), merged("y", 2, "x")]).unwrap();
+    }
+
+    #[test]
+    fn test_reconcile_renamed() {
+        let mut stack = Stack::default();
+        let mut a = StackBranch::try_new("a", Some("main")).unwrap();
+        a.pr = Some(1);
+        let mut b = StackBranch::try_new("b", Some("a")).unwrap();
+        b.pr = Some(2);
+        let mut c = StackBranch::try_new("me/c", Some("b")).unwrap();
+        c.pr = Some(3);
+        c.remote = Some(BranchName::new("c").unwrap());
+        stack.add_branch(a);
+        stack.add_branch(b);
+        stack.add_branch(c);
+
+        // GitHub retargeted #2 onto the renamed branch; that's not a ghost parent
+        let mut result = ReconcileResult {
+            repaired: vec![ReparentedBranch {
+                name: "b".into(),
+                old_parent: "a-renamed".into(),
+                new_parent: "a".into(),
+                pr_number: Some(2),
+            }],
+            ..ReconcileResult::default()
+        };
+        let renamed = |name: &str, pr, head: &str| ExternalRenameInfo {
+            branch_name: name.into(),
+            pr_number: pr,
+            new_head: head.into(),
+        };
+        reconcile_renamed(
+            &mut stack,
+            &[
+                renamed("a", 1, "a-renamed"),
+                renamed("b", 2, "taken"),
+                renamed("me/c", 3, "c-renamed"),
+            ],
+            |name| name == "taken",
+            &mut result,
+        );
+
+        // Renamed locally, with its child still attached
+        assert!(stack.find_branch("a").is_none());
+        assert_eq!(
+            stack.find_branch("b").unwrap().parent.as_deref(),
+            Some("a-renamed")
+        );
+        // The new name is taken locally, so only the remote name changes
+        assert_eq!(stack.find_branch("b").unwrap().remote_name(), "taken");
+        // Already pushed under another name, so that name follows the rename
+        assert_eq!(
+            stack.find_branch("me/c").unwrap().remote_name(),
+            "c-renamed"
+        );
+
+        assert!(result.repaired.is_empty());
+        let names: Vec<(&str, &str, &str)> = result
+            .renamed
+            .iter()
+            .map(|r| (r.name.as_str(), r.new_name.as_str(), r.remote.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("a", "a-renamed", "a-renamed"),
+                ("b", "b", "taken"),
+                ("me/c", "me/c", "c-renamed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_closed() {
+        let mut stack = Stack::default();
+        let mut a = StackBranch::try_new("a", Some("main")).unwrap();
+        a.pr = Some(1);
+        let mut b = StackBranch::try_new("b", Some("a")).unwrap();
+        b.pr = Some(2);
+        let mut c = StackBranch::try_new("c", Some("b")).unwrap();
+        c.pr = Some(3);
+        stack.add_branch(a);
+        stack.add_branch(b);
+        stack.add_branch(c);
+
+        // GitHub moved #3 onto `a` itself; the repair back to `b` is dropped
+        let mut result = ReconcileResult {
+            repaired: vec![ReparentedBranch {
+                name: "c".into(),
+                old_parent: "a".into(),
+                new_parent: "b".into(),
+                pr_number: Some(3),
+            }],
+            ..ReconcileResult::default()
+        };
+        let closed = |name: &str, pr| ExternalCloseInfo {
+            branch_name: name.into(),
+            pr_number: pr,
+        };
+        reconcile_closed(
+            &mut stack,
+            &[closed("b", 2), closed("a", 1)],
+            "main",
+            &mut result,
+        );
+
+        assert!(stack.find_branch("a").is_none() && stack.find_branch("b").is_none());
+        assert_eq!(
+            stack.find_branch("c").unwrap().parent.as_deref(),
+            Some("main")
+        );
+        assert!(result.repaired.is_empty());
+        assert_eq!(result.reparented.len(), 1);
+        assert_eq!(result.reparented[0].old_parent, "b");
+        assert_eq!(result.reparented[0].new_parent, "main");
+        let closed: Vec<_> = result
+            .closed
+            .iter()
+            .map(|c| (c.name.as_str(), c.parent.as_str()))
+            .collect();
+        assert_eq!(closed, [("b", "a"), ("a", "main")]);
+    }
+
+    #[test]
+    fn test_sync_mark() {
+        let (temp, rung_repo, git_repo) = init_test_repo();
+        let main_branch = rung_repo.current_branch().unwrap();
+        let head = git_repo.head().unwrap().peel_to_commit().unwrap();
+        git_repo.branch("feature-a", &head, false).unwrap();
+
+        let mut stack = Stack::new();
+        stack.add_branch(StackBranch::try_new("feature-a", Some(main_branch.clone())).unwrap());
+        let mark = SyncMark::capture(&rung_repo, &stack, &main_branch).unwrap();
+        assert_eq!(mark.base_tip, head.id().to_string());
+        assert_eq!(
+            SyncMark::capture(&rung_repo, &stack, &main_branch),
+            Some(mark.clone())
+        );
+
+        // A PR or a moved base both change the mark
+        stack.branches[0].pr = Some(7);
+        let with_pr = SyncMark::capture(&rung_repo, &stack, &main_branch).unwrap();
+        assert_ne!(with_pr, mark);
+        add_commit(&temp, &git_repo, "main-update.txt", "Update main");
+        assert_ne!(
+            SyncMark::capture(&rung_repo, &stack, &main_branch),
+            Some(with_pr)
+        );
+
+        // A missing branch means there is no mark to take
+        stack.add_branch(StackBranch::try_new("gone", Some("feature-a")).unwrap());
+        assert!(SyncMark::capture(&rung_repo, &stack, &main_branch).is_none());
+    }
 }
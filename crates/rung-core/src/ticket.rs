@@ -0,0 +1,143 @@
+//! Ticket ID detection and tracker links.
+//!
+//! Finds identifiers like `ABC-123` in branch names and commit messages using
+//! the pattern from [`TicketConfig`], and builds tracker links from its URL template.
+
+use regex::{Regex, RegexBuilder};
+
+use crate::config::TicketConfig;
+use crate::error::{Error, Result};
+
+/// Placeholder replaced with the ticket ID in URL templates.
+const ID_PLACEHOLDER: &str = "{id}";
+
+/// Compiled ticket matching rules.
+#[derive(Debug, Clone)]
+pub struct TicketMatcher {
+    /// Case-sensitive pattern for commit messages.
+    pattern: Regex,
+    /// Case-insensitive pattern for branch names (`abc-123-fix-login`).
+    branch_pattern: Regex,
+    url: Option<String>,
+    prefix_title: bool,
+}
+
+impl TicketMatcher {
+    /// Compile the matcher from config.
+    ///
+    /// # Errors
+    /// Returns `InvalidConfig` if the pattern isn't a valid regex.
+    pub fn new(config: &TicketConfig) -> Result<Self> {
+        let invalid = |e: regex::Error| Error::InvalidConfig(format!("tickets.pattern: {e}"));
+        let bounded = format!(r"\b(?:{})\b", config.pattern);
+
+        Ok(Self {
+            pattern: Regex::new(&bounded).map_err(invalid)?,
+            branch_pattern: RegexBuilder::new(&bounded)
+                .case_insensitive(true)
+                .build()
+                .map_err(invalid)?,
+            url: config.url.clone(),
+            prefix_title: config.prefix_title,
+        })
+    }
+
+    /// Find the ticket for a branch.
+    ///
+    /// The branch name is checked first (upper-cased on match), then each
+    /// commit message in order.
+    #[must_use]
+    pub fn find<'a>(
+        &self,
+        branch: &str,
+        messages: impl IntoIterator<Item = &'a str>,
+    ) -> Option<String> {
+        self.branch_pattern
+            .find(branch)
+            .map(|m| m.as_str().to_uppercase())
+            .or_else(|| {
+                messages
+                    .into_iter()
+                    .find_map(|msg| self.pattern.find(msg).map(|m| m.as_str().to_string()))
+            })
+    }
+
+    /// Tracker URL for a ticket, if a URL template is configured.
+    #[must_use]
+    pub fn url(&self, id: &str) -> Option<String> {
+        self.url
+            .as_ref()
+            .map(|template| template.replace(ID_PLACEHOLDER, id))
+    }
+
+    /// Markdown link to the ticket, or the bare ID without a URL template.
+    #[must_use]
+    pub fn markdown_link(&self, id: &str) -> String {
+        self.url(id)
+            .map_or_else(|| id.to_string(), |url| format!("[{id}]({url})"))
+    }
+
+    /// Prefix a PR title with the ticket ID unless it already mentions it.
+    #[must_use]
+    pub fn apply_to_title(&self, title: &str, id: &str) -> String {
+        if !self.prefix_title || title.contains(id) {
+            return title.to_string();
+        }
+        format!("{id}: {title}")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn matcher(url: Option<&str>) -> TicketMatcher {
+        TicketMatcher::new(&TicketConfig {
+            url: url.map(String::from),
+            ..TicketConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_ticket() {
+        let m = matcher(None);
+        assert_eq!(m.find("abc-123-fix-login", []).as_deref(), Some("ABC-123"));
+        assert_eq!(
+            m.find("feature/login", ["Fix login\n\nRefs PROJ-42"])
+                .as_deref(),
+            Some("PROJ-42")
+        );
+        // Commit messages are matched case-sensitively
+        assert!(m.find("feature/login", ["Handle utf-8 input"]).is_none());
+    }
+
+    #[test]
+    fn test_links_and_titles() {
+        let m = matcher(Some("https://jira.example.com/browse/{id}"));
+        assert_eq!(
+            m.markdown_link("ABC-1"),
+            "[ABC-1](https://jira.example.com/browse/ABC-1)"
+        );
+        assert_eq!(matcher(None).markdown_link("ABC-1"), "ABC-1");
+
+        assert_eq!(m.apply_to_title("Fix login", "ABC-1"), "ABC-1: Fix login");
+        assert_eq!(
+            m.apply_to_title("ABC-1 Fix login", "ABC-1"),
+            "ABC-1 Fix login"
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let config = TicketConfig {
+            pattern: "[".into(),
+            ..TicketConfig::default()
+        };
+        assert!(matches!(
+            TicketMatcher::new(&config),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+}
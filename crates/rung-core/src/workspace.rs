@@ -0,0 +1,135 @@
+//! Workspaces: stacks in several repositories that make up one change.
+//!
+//! A workspace is a `rung-workspace.toml` file listing repository paths,
+//! relative to the directory it lives in:
+//!
+//! ```toml
+//! repos = ["api", "web-client"]
+//! ```
+//!
+//! It is found by walking up from the current directory, so it usually sits
+//! in the directory the repositories are cloned into.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// A group of repositories whose stacks are worked on together.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Directory containing the workspace file.
+    #[serde(skip)]
+    pub root: PathBuf,
+
+    /// Repository paths, relative to `root`.
+    #[serde(default)]
+    pub repos: Vec<String>,
+}
+
+impl Workspace {
+    /// Name of the workspace file.
+    pub const FILE_NAME: &'static str = "rung-workspace.toml";
+
+    /// An empty workspace rooted at `root`.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            repos: vec![],
+        }
+    }
+
+    /// Load the workspace file in `root`.
+    ///
+    /// # Errors
+    /// Returns error if the file can't be read or parsed.
+    pub fn load(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        let content = fs::read_to_string(root.join(Self::FILE_NAME))?;
+        let mut workspace: Self = toml::from_str(&content)?;
+        workspace.root = root;
+        Ok(workspace)
+    }
+
+    /// Find the workspace containing `start`, walking up its ancestors.
+    ///
+    /// # Errors
+    /// Returns error if a workspace file is found but can't be loaded.
+    pub fn find(start: &Path) -> Result<Option<Self>> {
+        start
+            .ancestors()
+            .find(|dir| dir.join(Self::FILE_NAME).is_file())
+            .map(Self::load)
+            .transpose()
+    }
+
+    /// Write the workspace file.
+    ///
+    /// # Errors
+    /// Returns error if serialization or writing fails.
+    pub fn save(&self) -> Result<()> {
+        let content =
+            toml::to_string_pretty(self).map_err(|e| std::io::Error::other(e.to_string()))?;
+        fs::write(self.root.join(Self::FILE_NAME), content)?;
+        Ok(())
+    }
+
+    /// Add a repository path (relative to `root`). Returns false if it's
+    /// already listed.
+    ///
+    /// # Errors
+    /// Returns `InvalidConfig` for an empty or absolute path.
+    pub fn add(&mut self, repo: &str) -> Result<bool> {
+        let repo = repo.trim_end_matches('/');
+        if repo.is_empty() || Path::new(repo).is_absolute() {
+            return Err(Error::InvalidConfig(format!(
+                "workspace repos must be relative paths, got '{repo}'"
+            )));
+        }
+        if self.repos.iter().any(|r| r == repo) {
+            return Ok(false);
+        }
+        self.repos.push(repo.to_string());
+        Ok(true)
+    }
+
+    /// Absolute paths of the repositories, in the order listed.
+    #[must_use]
+    pub fn repo_paths(&self) -> Vec<PathBuf> {
+        self.repos.iter().map(|repo| self.root.join(repo)).collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_workspace_roundtrip_and_find() {
+        let temp = TempDir::new().unwrap();
+        let mut workspace = Workspace::new(temp.path());
+        assert!(workspace.add("api/").unwrap());
+        assert!(workspace.add("client").unwrap());
+        assert!(!workspace.add("api").unwrap());
+        assert!(workspace.add("").is_err());
+        assert!(workspace.add("/srv/api").is_err());
+        workspace.save().unwrap();
+
+        let nested = temp.path().join("api/src");
+        fs::create_dir_all(&nested).unwrap();
+        let found = Workspace::find(&nested).unwrap().unwrap();
+        assert_eq!(found, workspace);
+        assert_eq!(
+            found.repo_paths(),
+            vec![temp.path().join("api"), temp.path().join("client")]
+        );
+
+        let outside = TempDir::new().unwrap();
+        assert!(Workspace::find(outside.path()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,232 @@
+//! Property tests for sync planning, merge reconciliation and undo.
+//!
+//! Stacks are random forests on `main`: branch `i` sits on `main` or on some
+//! branch `j < i`, and each adds one file. Events move some of the branches
+//! (or `main`) on by a commit, or merge some of the branches.
+
+#![allow(clippy::unwrap_used)]
+
+use std::collections::{HashMap, HashSet};
+
+use proptest::prelude::*;
+use rung_core::stack::StackBranch;
+use rung_core::sync::{self, ExternalMergeInfo, SyncResult};
+use rung_core::{Stack, State};
+use tempfile::TempDir;
+
+/// Largest stack generated.
+const MAX_BRANCHES: usize = 7;
+
+/// Parent of each branch: `None` for `main`, otherwise an earlier branch.
+fn forest() -> impl Strategy<Value = Vec<Option<usize>>> {
+    (1..=MAX_BRANCHES).prop_flat_map(|n| {
+        // Drawing `i` for branch `i` stands for `main`
+        (0..n)
+            .map(|i| (0..=i).prop_map(move |p| (p < i).then_some(p)))
+            .collect::<Vec<_>>()
+    })
+}
+
+/// A forest with, for each of `main` and the branches, whether it moves on.
+fn forest_with_moves() -> impl Strategy<Value = (Vec<Option<usize>>, Vec<bool>)> {
+    forest().prop_flat_map(|parents| {
+        let n = parents.len();
+        (Just(parents), prop::collection::vec(any::<bool>(), n + 1))
+    })
+}
+
+fn name(i: usize) -> String {
+    format!("feature-{i}")
+}
+
+fn parent_name(parent: Option<usize>) -> String {
+    parent.map_or_else(|| "main".to_string(), name)
+}
+
+/// The stack for `parents`, listing branches in `order`.
+fn build_stack(parents: &[Option<usize>], order: &[usize]) -> Stack {
+    let mut stack = Stack::new();
+    for &i in order {
+        stack.add_branch(StackBranch::try_new(name(i), Some(parent_name(parents[i]))).unwrap());
+    }
+    stack
+}
+
+/// A repository with the branches of `parents`, where `main` and every branch
+/// flagged in `moves` (`moves[0]` is `main`) got a commit after its children
+/// were created.
+struct Fixture {
+    temp: TempDir,
+    repo: rung_git::Repository,
+}
+
+impl Fixture {
+    fn new(parents: &[Option<usize>], moves: &[bool]) -> Self {
+        let temp = TempDir::new().unwrap();
+        let git = git2::Repository::init(temp.path()).unwrap();
+        let mut config = git.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+
+        let root = commit(&git, None, "README.md");
+        git.branch("main", &git.find_commit(root).unwrap(), true)
+            .unwrap();
+        for (i, parent) in parents.iter().enumerate() {
+            let base = git
+                .find_branch(&parent_name(*parent), git2::BranchType::Local)
+                .unwrap()
+                .get()
+                .target()
+                .unwrap();
+            let tip = commit(&git, Some(base), &format!("{}.txt", name(i)));
+            git.branch(&name(i), &git.find_commit(tip).unwrap(), false)
+                .unwrap();
+        }
+        for (i, _) in moves.iter().enumerate().filter(|(_, moved)| **moved) {
+            let branch = if i == 0 { "main".into() } else { name(i - 1) };
+            let tip = git
+                .find_branch(&branch, git2::BranchType::Local)
+                .unwrap()
+                .get()
+                .target()
+                .unwrap();
+            let next = commit(&git, Some(tip), &format!("{branch}-moved.txt"));
+            git.branch(&branch, &git.find_commit(next).unwrap(), true)
+                .unwrap();
+        }
+
+        git.set_head("refs/heads/main").unwrap();
+        git.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        let repo = rung_git::Repository::open(temp.path()).unwrap();
+        Self { temp, repo }
+    }
+
+    fn tips(&self, count: usize) -> HashMap<String, rung_git::Oid> {
+        let branches: Vec<String> = (0..count).map(name).collect();
+        sync::capture_tips(&self.repo, &branches).unwrap()
+    }
+}
+
+/// Commit `file` on top of `parent`, without touching any ref.
+fn commit(git: &git2::Repository, parent: Option<git2::Oid>, file: &str) -> git2::Oid {
+    let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+    let parent = parent.map(|oid| git.find_commit(oid).unwrap());
+    let base_tree = parent.as_ref().map(|c| c.tree().unwrap());
+    let mut builder = git.treebuilder(base_tree.as_ref()).unwrap();
+    let blob = git.blob(file.as_bytes()).unwrap();
+    builder.insert(file, blob, 0o100_644).unwrap();
+    let tree = git.find_tree(builder.write().unwrap()).unwrap();
+    let parents: Vec<&git2::Commit<'_>> = parent.iter().collect();
+    git.commit(None, &sig, &sig, file, &tree, &parents).unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    /// The plan rebases exactly the branches whose parent moved or is itself
+    /// rebased, and never a branch before its parent.
+    #[test]
+    fn plan_is_topologically_ordered(
+        (parents, moves) in forest_with_moves(),
+        seed in any::<u64>(),
+    ) {
+        let fixture = Fixture::new(&parents, &moves);
+        let mut order: Vec<usize> = (0..parents.len()).collect();
+        // Stacks aren't necessarily stored parents-first
+        order.sort_by_key(|&i| (i as u64).wrapping_mul(seed | 1).rotate_left(17));
+        let stack = build_stack(&parents, &order);
+
+        let plan = sync::create_sync_plan(&fixture.repo, &stack, "main").unwrap();
+        let position: HashMap<&str, usize> = plan
+            .branches
+            .iter()
+            .enumerate()
+            .map(|(pos, action)| (action.branch.as_str(), pos))
+            .collect();
+        prop_assert_eq!(position.len(), plan.branches.len(), "branch planned twice");
+
+        let mut expected = HashSet::new();
+        for (i, parent) in parents.iter().enumerate() {
+            let parent_moved = parent.map_or(moves[0], |p| moves[p + 1]);
+            if parent_moved || parent.is_some_and(|p| expected.contains(&p)) {
+                expected.insert(i);
+            }
+        }
+        let planned: HashSet<usize> = (0..parents.len())
+            .filter(|&i| position.contains_key(name(i).as_str()))
+            .collect();
+        prop_assert_eq!(&planned, &expected);
+
+        for &i in &planned {
+            if let Some(p) = parents[i].filter(|p| planned.contains(p)) {
+                prop_assert!(position[name(p).as_str()] < position[name(i).as_str()]);
+            }
+        }
+    }
+
+    /// After reconciling any set of merges, in any order, every remaining
+    /// branch sits on its nearest unmerged ancestor.
+    #[test]
+    fn reconcile_never_orphans(
+        (parents, merged, order) in forest().prop_flat_map(|parents| {
+            let n = parents.len();
+            let order = Just((0..n).collect::<Vec<_>>()).prop_shuffle();
+            (Just(parents), prop::collection::vec(any::<bool>(), n), order)
+        }),
+    ) {
+        let all: Vec<usize> = (0..parents.len()).collect();
+        let mut stack = build_stack(&parents, &all);
+        // Each PR was merged into the base it was opened against
+        let merges: Vec<ExternalMergeInfo> = order
+            .iter()
+            .filter(|&&i| merged[i])
+            .map(|&i| ExternalMergeInfo {
+                branch_name: name(i),
+                pr_number: i as u64 + 1,
+                merged_into: parent_name(parents[i]),
+                merge_commit: None,
+            })
+            .collect();
+
+        let result = sync::reconcile_merged(&mut stack, &merges).unwrap();
+        prop_assert_eq!(result.merged.len(), merges.len());
+
+        let remaining: Vec<usize> = all.iter().copied().filter(|&i| !merged[i]).collect();
+        prop_assert_eq!(stack.branches.len(), remaining.len());
+        for i in remaining {
+            let mut expected = parents[i];
+            while let Some(p) = expected.filter(|&p| merged[p]) {
+                expected = parents[p];
+            }
+            let branch = stack.find_branch(&name(i)).unwrap();
+            let expected = parent_name(expected);
+            prop_assert_eq!(branch.parent.as_deref(), Some(expected.as_str()));
+        }
+    }
+
+    /// Undoing a sync puts every branch back on the exact commit it had.
+    #[test]
+    fn undo_restores_exact_shas((parents, moves) in forest_with_moves()) {
+        let fixture = Fixture::new(&parents, &moves);
+        let state = State::new(fixture.temp.path()).unwrap();
+        state.init().unwrap();
+        let all: Vec<usize> = (0..parents.len()).collect();
+        let stack = build_stack(&parents, &all);
+        state.save_stack(&stack).unwrap();
+        let before = fixture.tips(parents.len());
+
+        let plan = sync::create_sync_plan(&fixture.repo, &stack, "main").unwrap();
+        let rebased = plan.branches.len();
+        match sync::execute_sync(&fixture.repo, &state, plan, None, "sync").unwrap() {
+            SyncResult::AlreadySynced => prop_assert_eq!(rebased, 0),
+            SyncResult::Complete { branches_rebased, .. } => {
+                prop_assert_eq!(branches_rebased, rebased);
+                let undo = sync::undo_sync(&fixture.repo, &state).unwrap();
+                prop_assert_eq!(undo.branches_restored, rebased);
+            }
+            other => prop_assert!(false, "unexpected sync result: {other:?}"),
+        }
+        prop_assert_eq!(fixture.tips(parents.len()), before);
+    }
+}
@@ -21,7 +21,7 @@ use crate::error::Error;
 /// # Examples
 ///
 /// ```
-/// use rung_core::BranchName;
+/// use rung_git::BranchName;
 ///
 /// // Valid branch names
 /// let name = BranchName::new("feature/auth").unwrap();
@@ -61,7 +61,7 @@ impl BranchName {
     /// # Examples
     ///
     /// ```
-    /// use rung_core::BranchName;
+    /// use rung_git::BranchName;
     ///
     /// let name = BranchName::from_message("feat: add authentication").unwrap();
     /// assert_eq!(name.as_str(), "feat-add-authentication");
@@ -167,11 +167,12 @@ fn validate_branch_name(name: &str) -> Result<(), Error> {
         });
     }
 
-    // Cannot start with a dot
-    if name.starts_with('.') {
+    // Cannot start with a dot, or a dash (git rejects it, and it would be
+    // read as an option by the git commands we shell out to)
+    if let Some(c @ ('.' | '-')) = name.chars().next() {
         return Err(Error::InvalidBranchName {
             name: name.to_string(),
-            reason: "branch name cannot start with '.'".to_string(),
+            reason: format!("branch name cannot start with '{c}'"),
         });
     }
 
@@ -201,7 +202,10 @@ fn validate_branch_name(name: &str) -> Result<(), Error> {
     }
 
     // Check for invalid patterns and characters
-    for (i, c) in name.chars().enumerate() {
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+
         // Control characters (0x00-0x1f, 0x7f)
         if c.is_ascii_control() {
             return Err(Error::InvalidBranchName {
@@ -243,7 +247,7 @@ fn validate_branch_name(name: &str) -> Result<(), Error> {
         }
 
         // Check for consecutive dots (..)
-        if c == '.' && name.chars().nth(i + 1) == Some('.') {
+        if c == '.' && next == Some('.') {
             return Err(Error::InvalidBranchName {
                 name: name.to_string(),
                 reason: "branch name cannot contain '..'".to_string(),
@@ -251,7 +255,7 @@ fn validate_branch_name(name: &str) -> Result<(), Error> {
         }
 
         // Check for consecutive slashes (//)
-        if c == '/' && name.chars().nth(i + 1) == Some('/') {
+        if c == '/' && next == Some('/') {
             return Err(Error::InvalidBranchName {
                 name: name.to_string(),
                 reason: "branch name cannot contain '//'".to_string(),
@@ -259,7 +263,7 @@ fn validate_branch_name(name: &str) -> Result<(), Error> {
         }
 
         // Check for @{ sequence
-        if c == '@' && name.chars().nth(i + 1) == Some('{') {
+        if c == '@' && next == Some('{') {
             return Err(Error::InvalidBranchName {
                 name: name.to_string(),
                 reason: "branch name cannot contain '@{'".to_string(),
@@ -267,7 +271,7 @@ fn validate_branch_name(name: &str) -> Result<(), Error> {
         }
 
         // Check for slash followed by dot (/.component)
-        if c == '/' && name.chars().nth(i + 1) == Some('.') {
+        if c == '/' && next == Some('.') {
             return Err(Error::InvalidBranchName {
                 name: name.to_string(),
                 reason: "branch name component cannot start with '.'".to_string(),
@@ -292,7 +296,7 @@ const MAX_BRANCH_NAME_LENGTH: usize = 50;
 /// # Examples
 ///
 /// ```
-/// use rung_core::slugify;
+/// use rung_git::slugify;
 ///
 /// assert_eq!(slugify("feat: add authentication"), "feat-add-authentication");
 /// assert_eq!(slugify("Fix login bug"), "fix-login-bug");
@@ -389,6 +393,14 @@ mod tests {
         assert!(matches!(err, Error::InvalidBranchName { .. }));
     }
 
+    #[test]
+    fn test_starts_with_dash() {
+        let err = BranchName::new("--upload-pack=evil").unwrap_err();
+        assert!(matches!(err, Error::InvalidBranchName { .. }));
+        assert!(BranchName::new("-f").is_err());
+        assert!(BranchName::new("fix-dash").is_ok());
+    }
+
     #[test]
     fn test_ends_with_dot() {
         let err = BranchName::new("branch.").unwrap_err();
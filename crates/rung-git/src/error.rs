@@ -14,6 +14,15 @@ pub enum Error {
     #[error("branch not found: {0}")]
     BranchNotFound(String),
 
+    /// Invalid branch name.
+    #[error("invalid branch name '{name}': {reason}")]
+    InvalidBranchName {
+        /// The invalid name.
+        name: String,
+        /// Why the name is invalid.
+        reason: String,
+    },
+
     /// Reference not found.
     #[error("reference not found: {0}")]
     RefNotFound(String),
@@ -22,6 +31,10 @@ pub enum Error {
     #[error("HEAD is detached - checkout a branch first")]
     DetachedHead,
 
+    /// HEAD is on a branch with no commits yet, as in a fresh repository.
+    #[error("'{0}' has no commits yet - make the first commit with `git commit`, then try again")]
+    UnbornHead(String),
+
     /// Rebase conflict.
     #[error("rebase conflict in: {0:?}")]
     RebaseConflict(Vec<String>),
@@ -30,6 +43,18 @@ pub enum Error {
     #[error("rebase failed: {0}")]
     RebaseFailed(String),
 
+    /// Revert conflict.
+    #[error("revert conflict in: {0:?}")]
+    RevertConflict(Vec<String>),
+
+    /// Revert failed.
+    #[error("revert failed: {0}")]
+    RevertFailed(String),
+
+    /// A git operation started outside rung is unfinished.
+    #[error("a git {} is in progress - finish or abort it first ({})", .0.command(), .0.resolution())]
+    OperationInProgress(crate::InProgress),
+
     /// Working directory is dirty.
     #[error("working directory has uncommitted changes")]
     DirtyWorkingDirectory,
@@ -46,10 +71,52 @@ pub enum Error {
     #[error("push failed: {0}")]
     PushFailed(String),
 
+    /// A push reported success, but origin's branch isn't at the pushed commit.
+    #[error(
+        "pushed {branch} at {expected}, but origin has {} - a proxy, mirror or another push got in the way",
+        .actual.as_deref().unwrap_or("no such branch")
+    )]
+    PushNotLanded {
+        /// Branch on origin.
+        branch: String,
+        /// Commit pushed.
+        expected: String,
+        /// Commit origin has instead, if it has the branch at all.
+        actual: Option<String>,
+    },
+
     /// Fetch failed.
     #[error("fetch failed: {0}")]
     FetchFailed(String),
 
+    /// Fast-forward failed.
+    #[error("fast-forward failed: {0}")]
+    FastForwardFailed(String),
+
+    /// `git range-diff` failed.
+    #[error("range-diff failed: {0}")]
+    RangeDiffFailed(String),
+
+    /// A commit would change nothing, and empty commits weren't allowed.
+    #[error("nothing to commit")]
+    NothingToCommit,
+
+    /// A commit author that isn't `Name <email>`.
+    #[error("invalid author '{0}' - expected 'Name <email>'")]
+    InvalidAuthor(String),
+
+    /// A commit message file couldn't be read.
+    #[error("cannot read commit message file {0}")]
+    MessageFile(String),
+
+    /// A git hook failed or rejected the operation.
+    #[error("git hook {0}")]
+    HookFailed(String),
+
+    /// The git binary is missing or unusable.
+    #[error("git binary unavailable: {0}")]
+    GitUnavailable(String),
+
     /// Underlying git2 error.
     #[error("git error: {0}")]
     Git2(#[from] git2::Error),
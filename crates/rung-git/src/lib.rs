@@ -4,9 +4,20 @@
 //! Provides high-level operations for branch management, rebasing,
 //! and repository state inspection.
 
+mod branch_name;
 mod error;
+mod reflog;
+mod remote;
 mod repository;
+mod version;
 
+pub use branch_name::{BranchName, slugify};
 pub use error::{Error, Result};
 pub use git2::Oid;
-pub use repository::Repository;
+pub use reflog::{ReflogEntry, set_reflog_command};
+pub use remote::{GitHubRemote, RemoteHosts};
+pub use repository::{
+    CommitInfo, CommitOptions, ConflictCommit, ConflictStages, InProgress, RebaseOptions,
+    Repository,
+};
+pub use version::GitVersion;
@@ -0,0 +1,75 @@
+//! Reflog messages that attribute ref updates to rung.
+//!
+//! Every ref rung moves gets a reflog entry starting `rung <command>:`, such
+//! as `rung sync: rebase feature-b onto 1a2b3c4d`, so `git reflog` shows
+//! which of a branch's moves were rung's and why. Updates made through git
+//! itself (rebases, fast-forwards) are attributed via `GIT_REFLOG_ACTION`.
+
+use std::sync::OnceLock;
+
+use git2::Oid;
+
+/// The rung subcommand running, such as `sync`.
+static COMMAND: OnceLock<String> = OnceLock::new();
+
+/// Name the rung subcommand whose ref updates follow. Call once at startup;
+/// without it, messages start `rung:`.
+pub fn set_reflog_command(command: &str) {
+    let _ = COMMAND.set(command.to_string());
+}
+
+/// The reflog message for `action`, e.g. `rung sync: rebase feature-b onto 1a2b3c4d`.
+pub fn message(action: &str) -> String {
+    COMMAND.get().map_or_else(
+        || format!("rung: {action}"),
+        |command| format!("rung {command}: {action}"),
+    )
+}
+
+/// The first 8 hex digits of `oid`, as reflog messages show commits.
+pub fn short(oid: Oid) -> String {
+    oid.to_string()[..8].to_string()
+}
+
+/// One move of a branch, from its reflog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflogEntry {
+    /// Where the branch pointed before (zero when it was created).
+    pub old: Oid,
+
+    /// Where the branch pointed after.
+    pub new: Oid,
+
+    /// Why it moved.
+    pub message: String,
+
+    /// When it moved, in seconds since the Unix epoch.
+    pub time: i64,
+}
+
+impl ReflogEntry {
+    /// Whether rung made the move.
+    #[must_use]
+    pub fn is_rung(&self) -> bool {
+        self.message.starts_with("rung:") || self.message.starts_with("rung ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_rung() {
+        let entry = |message: &str| ReflogEntry {
+            old: Oid::zero(),
+            new: Oid::zero(),
+            message: message.to_string(),
+            time: 0,
+        };
+        assert!(entry("rung sync: rebase a onto 1a2b3c4d").is_rung());
+        assert!(entry("rung: reset a to 1a2b3c4d").is_rung());
+        assert!(!entry("commit: rung the bell").is_rung());
+        assert!(!entry("rungs: x").is_rung());
+    }
+}
@@ -1,10 +1,174 @@
 //! Repository wrapper providing high-level git operations.
 
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 use git2::{BranchType, Oid, RepositoryState, Signature};
 
+use crate::BranchName;
 use crate::error::{Error, Result};
+use crate::reflog::{self, ReflogEntry};
+use crate::remote::{GitHubRemote, RemoteHosts};
+
+/// Options for [`Repository::rebase_onto`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RebaseOptions {
+    /// Keep merge commits on the branch instead of flattening them
+    /// (`git rebase --rebase-merges`).
+    pub rebase_merges: bool,
+
+    /// Keep each commit's committer date equal to its author date, so a
+    /// rebase doesn't make commits look new (`--committer-date-is-author-date`).
+    pub committer_date_is_author_date: bool,
+}
+
+impl RebaseOptions {
+    /// The `git rebase` flags for these options.
+    fn flags(self) -> Vec<&'static str> {
+        let mut flags = vec![];
+        if self.rebase_merges {
+            flags.push("--rebase-merges");
+        }
+        if self.committer_date_is_author_date {
+            flags.push("--committer-date-is-author-date");
+        }
+        flags
+    }
+}
+
+/// Options for [`Repository::create_commit`], named after the
+/// `git commit` flags they stand for.
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)] // One per `git commit` flag
+pub struct CommitOptions {
+    /// Run the `pre-commit` and `commit-msg` hooks first (off is `--no-verify`).
+    pub verify: bool,
+
+    /// Commit even if nothing changed (`--allow-empty`).
+    pub allow_empty: bool,
+
+    /// Replace HEAD rather than commit on top of it (`--amend`). The
+    /// replacement keeps HEAD's parents and, unless `author` is set, its author.
+    pub amend: bool,
+
+    /// Author to commit as, as `Name <email>` (`--author`), in place of the
+    /// configured identity. The committer is always the configured identity.
+    pub author: Option<String>,
+
+    /// Add a `Signed-off-by` trailer for the committer (`--signoff`).
+    pub signoff: bool,
+}
+
+/// A git operation left in progress, such as a `git merge` stopped on
+/// conflicts, that rung must not run into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InProgress {
+    /// `git merge`.
+    Merge,
+    /// `git rebase`, interactive or not.
+    Rebase,
+    /// `git cherry-pick`.
+    CherryPick,
+    /// `git revert`.
+    Revert,
+    /// `git bisect`.
+    Bisect,
+    /// `git am`.
+    ApplyMailbox,
+}
+
+impl InProgress {
+    /// The git subcommand that started the operation, e.g. `cherry-pick`.
+    #[must_use]
+    pub const fn command(self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Rebase => "rebase",
+            Self::CherryPick => "cherry-pick",
+            Self::Revert => "revert",
+            Self::Bisect => "bisect",
+            Self::ApplyMailbox => "am",
+        }
+    }
+
+    /// How to finish or abandon the operation.
+    #[must_use]
+    pub fn resolution(self) -> String {
+        let command = self.command();
+        match self {
+            Self::Bisect => "run `git bisect reset` when done".to_string(),
+            Self::Merge => format!("commit the merge, or run `git {command} --abort`"),
+            _ => format!("run `git {command} --continue` or `git {command} --abort`"),
+        }
+    }
+}
+
+/// A commit on the rebased-onto side that last touched a conflicting region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictCommit {
+    /// Commit ID.
+    pub id: Oid,
+    /// Author name.
+    pub author: String,
+    /// First line of the commit message.
+    pub summary: String,
+}
+
+/// The blobs staged for a conflicted file, by index stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictStages {
+    /// Path of the conflicted file.
+    pub path: String,
+    /// Blob in the common ancestor (stage 1), if the file existed there.
+    pub base: Option<Oid>,
+    /// Blob on the side being rebased onto (stage 2, "ours" in a rebase).
+    pub ours: Option<Oid>,
+    /// Blob from the commit being replayed (stage 3, "theirs" in a rebase).
+    pub theirs: Option<Oid>,
+}
+
+/// A commit with the details rung shows and builds PRs from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// Commit ID.
+    pub id: Oid,
+    /// First line of the message, trimmed.
+    pub summary: String,
+    /// The message after the summary and the blank lines following it.
+    pub body: String,
+    /// The full message as committed.
+    pub message: String,
+    /// Author name.
+    pub author_name: String,
+    /// Author email.
+    pub author_email: String,
+    /// `Key: value` trailers closing the message, in order.
+    pub trailers: Vec<(String, String)>,
+}
+
+impl CommitInfo {
+    /// The author as `Name <email>`, the form trailers use.
+    #[must_use]
+    pub fn author(&self) -> String {
+        format!("{} <{}>", self.author_name, self.author_email)
+    }
+
+    /// Whether the author signed the commit off with a `Signed-off-by`
+    /// trailer carrying their email, as DCO checks require.
+    #[must_use]
+    pub fn is_signed_off(&self) -> bool {
+        self.trailers.iter().any(|(key, value)| {
+            key.eq_ignore_ascii_case(SIGNED_OFF_BY)
+                && value
+                    .rsplit_once('<')
+                    .and_then(|(_, rest)| rest.strip_suffix('>'))
+                    .is_some_and(|email| email.trim().eq_ignore_ascii_case(&self.author_email))
+        })
+    }
+}
+
+/// Trailer key of a DCO sign-off.
+const SIGNED_OFF_BY: &str = "Signed-off-by";
 
 /// High-level wrapper around a git repository.
 pub struct Repository {
@@ -21,12 +185,18 @@ impl Repository {
         Ok(Self { inner })
     }
 
-    /// Open the repository containing the current directory.
+    /// Open the repository containing the current directory, or the one
+    /// `GIT_DIR` names, with `GIT_WORK_TREE` as its working tree if set, as
+    /// git does.
     ///
     /// # Errors
     /// Returns error if not inside a git repository.
     pub fn open_current() -> Result<Self> {
-        Self::open(".")
+        let inner = git2::Repository::open_from_env()?;
+        if let Some(work_tree) = std::env::var_os("GIT_WORK_TREE").filter(|dir| !dir.is_empty()) {
+            inner.set_workdir(Path::new(&work_tree), false)?;
+        }
+        Ok(Self { inner })
     }
 
     /// Get the path to the repository root (workdir).
@@ -35,12 +205,33 @@ impl Repository {
         self.inner.workdir()
     }
 
+    /// Whether the repository has no working tree.
+    #[must_use]
+    pub fn is_bare(&self) -> bool {
+        self.inner.is_bare()
+    }
+
     /// Get the path to the .git directory.
     #[must_use]
     pub fn git_dir(&self) -> &Path {
         self.inner.path()
     }
 
+    /// The git directory shared by all worktrees. A linked worktree names it
+    /// in a `commondir` file, relative to its own git directory.
+    #[must_use]
+    pub fn common_dir(&self) -> PathBuf {
+        let git_dir = self.git_dir();
+        std::fs::read_to_string(git_dir.join("commondir"))
+            .map_or_else(|_| git_dir.to_path_buf(), |dir| git_dir.join(dir.trim()))
+    }
+
+    /// A string value from git config, such as `core.pager`.
+    #[must_use]
+    pub fn config_string(&self, key: &str) -> Option<String> {
+        self.inner.config().ok()?.get_string(key).ok()
+    }
+
     /// Get the current repository state.
     #[must_use]
     pub fn state(&self) -> RepositoryState {
@@ -58,14 +249,52 @@ impl Repository {
         )
     }
 
+    /// The git operation in progress in the working tree, if any.
+    #[must_use]
+    pub fn in_progress(&self) -> Option<InProgress> {
+        match self.state() {
+            RepositoryState::Clean => None,
+            RepositoryState::Merge => Some(InProgress::Merge),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(InProgress::Rebase),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(InProgress::CherryPick)
+            }
+            RepositoryState::Revert | RepositoryState::RevertSequence => Some(InProgress::Revert),
+            RepositoryState::Bisect => Some(InProgress::Bisect),
+            RepositoryState::ApplyMailbox => Some(InProgress::ApplyMailbox),
+        }
+    }
+
+    /// Ensure no git operation is in progress, for commands that change
+    /// branches.
+    ///
+    /// # Errors
+    /// Returns `OperationInProgress` naming the operation and how to finish it.
+    pub fn require_no_operation(&self) -> Result<()> {
+        self.in_progress()
+            .map_or(Ok(()), |op| Err(Error::OperationInProgress(op)))
+    }
+
     // === Branch operations ===
 
     /// Get the name of the current branch.
     ///
     /// # Errors
-    /// Returns error if HEAD is detached.
+    /// Returns error if HEAD is detached, or `UnbornHead` if it's on a branch
+    /// with no commits yet.
     pub fn current_branch(&self) -> Result<String> {
-        let head = self.inner.head()?;
+        let head = match self.inner.head() {
+            Ok(head) => head,
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                return Err(self
+                    .unborn_branch()
+                    .map_or(Error::DetachedHead, Error::UnbornHead));
+            }
+            Err(e) => return Err(e.into()),
+        };
         if !head.is_branch() {
             return Err(Error::DetachedHead);
         }
@@ -75,15 +304,37 @@ impl Repository {
             .ok_or(Error::DetachedHead)
     }
 
+    /// The branch HEAD is on when it has no commits yet, as in a freshly
+    /// initialized repository.
+    #[must_use]
+    pub fn unborn_branch(&self) -> Option<String> {
+        match self.inner.head() {
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                let head = self.inner.find_reference("HEAD").ok()?;
+                head.symbolic_target()
+                    .and_then(|target| target.strip_prefix("refs/heads/"))
+                    .map(String::from)
+            }
+            _ => None,
+        }
+    }
+
     /// Get the commit SHA for a branch.
     ///
     /// # Errors
-    /// Returns error if branch doesn't exist.
+    /// Returns error if branch doesn't exist, or `UnbornHead` if it's the
+    /// branch HEAD is on and it has no commits yet.
     pub fn branch_commit(&self, branch_name: &str) -> Result<Oid> {
         let branch = self
             .inner
             .find_branch(branch_name, BranchType::Local)
-            .map_err(|_| Error::BranchNotFound(branch_name.into()))?;
+            .map_err(|_| {
+                if self.unborn_branch().as_deref() == Some(branch_name) {
+                    Error::UnbornHead(branch_name.into())
+                } else {
+                    Error::BranchNotFound(branch_name.into())
+                }
+            })?;
 
         branch
             .get()
@@ -111,18 +362,32 @@ impl Repository {
     ///
     /// # Errors
     /// Returns error if branch creation fails.
-    pub fn create_branch(&self, name: &str) -> Result<Oid> {
-        let head_commit = self.inner.head()?.peel_to_commit()?;
-        let branch = self.inner.branch(name, &head_commit, false)?;
+    pub fn create_branch(&self, name: &BranchName) -> Result<Oid> {
+        let head = self.inner.head()?.peel_to_commit()?.id();
+        self.create_branch_at(name, head)?;
+        Ok(head)
+    }
 
-        branch
-            .get()
-            .target()
-            .ok_or_else(|| Error::BranchNotFound(name.into()))
+    /// Create a new branch pointing at a specific commit.
+    ///
+    /// # Errors
+    /// Returns error if the commit doesn't exist or branch creation fails.
+    pub fn create_branch_at(&self, name: &BranchName, target: Oid) -> Result<()> {
+        self.inner.find_commit(target)?;
+        self.inner.reference(
+            &format!("refs/heads/{name}"),
+            target,
+            false,
+            &reflog::message(&format!("create {name} at {}", reflog::short(target))),
+        )?;
+        Ok(())
     }
 
     /// Checkout a branch.
     ///
+    /// In a sparse checkout git does it, since libgit2 would fill in the
+    /// paths outside the sparse patterns.
+    ///
     /// # Errors
     /// Returns error if checkout fails.
     pub fn checkout(&self, branch_name: &str) -> Result<()> {
@@ -130,6 +395,9 @@ impl Repository {
             .inner
             .find_branch(branch_name, BranchType::Local)
             .map_err(|_| Error::BranchNotFound(branch_name.into()))?;
+        if self.is_sparse() {
+            return self.git_worktree_command(&["checkout", "-q", branch_name]);
+        }
 
         let reference = branch.get();
         let object = reference.peel(git2::ObjectType::Commit)?;
@@ -140,6 +408,20 @@ impl Repository {
         Ok(())
     }
 
+    /// Check out a commit with a detached HEAD.
+    ///
+    /// # Errors
+    /// Returns error if the commit doesn't exist or checkout fails.
+    pub fn checkout_detached(&self, target: Oid) -> Result<()> {
+        let commit = self.inner.find_commit(target)?;
+        if self.is_sparse() {
+            return self.git_worktree_command(&["checkout", "-q", "--detach", &target.to_string()]);
+        }
+        self.inner.checkout_tree(commit.as_object(), None)?;
+        self.inner.set_head_detached(target)?;
+        Ok(())
+    }
+
     /// List all local branches.
     ///
     /// # Errors
@@ -155,6 +437,72 @@ impl Repository {
         Ok(names)
     }
 
+    /// List the branches on origin, as of the last fetch.
+    ///
+    /// # Errors
+    /// Returns error if branch listing fails.
+    pub fn list_remote_branches(&self) -> Result<Vec<String>> {
+        let branches = self.inner.branches(Some(BranchType::Remote))?;
+
+        let names: Vec<String> = branches
+            .filter_map(std::result::Result::ok)
+            .filter_map(|(b, _)| b.name().ok().flatten().map(String::from))
+            .filter_map(|name| name.strip_prefix("origin/").map(String::from))
+            .filter(|name| name != "HEAD")
+            .collect();
+
+        Ok(names)
+    }
+
+    /// Whether the worktree is a sparse checkout (`git sparse-checkout`).
+    ///
+    /// Asks git, since `git sparse-checkout` may set this in the worktree's
+    /// own config, which libgit2 doesn't read.
+    #[must_use]
+    pub fn is_sparse(&self) -> bool {
+        let Some(workdir) = self.workdir() else {
+            return false;
+        };
+        std::process::Command::new("git")
+            .args(["config", "--type=bool", "core.sparseCheckout"])
+            .current_dir(workdir)
+            .output()
+            .is_ok_and(|output| output.stdout.trim_ascii() == b"true")
+    }
+
+    /// Paths a sparse checkout leaves out of the worktree: those marked
+    /// skip-worktree in the index. Empty unless the checkout is sparse.
+    fn skip_worktree_paths(&self) -> Result<HashSet<String>> {
+        if !self.is_sparse() {
+            return Ok(HashSet::new());
+        }
+        let skip = git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+        let mut index = self.inner.index()?;
+        index.read(false)?;
+        Ok(index
+            .iter()
+            .filter(|entry| entry.flags_extended & skip != 0)
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .collect())
+    }
+
+    /// Run a git command that updates the worktree, such as `git checkout`.
+    fn git_worktree_command(&self, args: &[&str]) -> Result<()> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| Error::Git2(git2::Error::from_str(&e.to_string())))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::Git2(git2::Error::from_str(stderr.trim())))
+        }
+    }
+
     /// Check if a branch exists.
     #[must_use]
     pub fn branch_exists(&self, name: &str) -> bool {
@@ -171,16 +519,54 @@ impl Repository {
         Ok(())
     }
 
+    /// Rename a local branch, keeping its reflog and upstream settings. A
+    /// checked-out branch stays checked out under its new name.
+    ///
+    /// # Errors
+    /// Returns error if the branch doesn't exist or `new` is taken.
+    pub fn rename_branch(&self, old: &str, new: &BranchName) -> Result<()> {
+        let mut branch = self.inner.find_branch(old, BranchType::Local)?;
+        branch.rename(new, false)?;
+        Ok(())
+    }
+
+    /// The remote and remote branch a local branch tracks, from its
+    /// `branch.<name>.remote` and `branch.<name>.merge` settings.
+    #[must_use]
+    pub fn upstream_of(&self, branch: &str) -> Option<(String, String)> {
+        let remote = self.config_string(&format!("branch.{branch}.remote"))?;
+        let merge = self.config_string(&format!("branch.{branch}.merge"))?;
+        let merge = merge.strip_prefix("refs/heads/").unwrap_or(&merge);
+        Some((remote, merge.to_string()))
+    }
+
+    /// Make a local branch track `remote_branch` on origin, as
+    /// `git push -u` would.
+    ///
+    /// # Errors
+    /// Returns error if the config can't be written.
+    pub fn set_upstream(&self, branch: &BranchName, remote_branch: &BranchName) -> Result<()> {
+        let mut config = self.inner.config()?;
+        config.set_str(&format!("branch.{branch}.remote"), "origin")?;
+        config.set_str(
+            &format!("branch.{branch}.merge"),
+            &format!("refs/heads/{remote_branch}"),
+        )?;
+        Ok(())
+    }
+
     // === Working directory state ===
 
     /// Check if the working directory is clean (no modified or staged files).
     ///
     /// Untracked files are ignored - only tracked files that have been
-    /// modified or staged count as "dirty".
+    /// modified or staged count as "dirty". So are paths outside a sparse
+    /// checkout, which libgit2 would report as deleted.
     ///
     /// # Errors
     /// Returns error if status check fails.
     pub fn is_clean(&self) -> Result<bool> {
+        let skipped = self.skip_worktree_paths()?;
         let mut opts = git2::StatusOptions::new();
         opts.include_untracked(false)
             .include_ignored(false)
@@ -190,6 +576,9 @@ impl Repository {
 
         // Check if any status indicates modified/staged files
         for entry in statuses.iter() {
+            if entry.path().is_some_and(|path| skipped.contains(path)) {
+                continue;
+            }
             let status = entry.status();
             // These indicate actual changes to tracked files
             if status.intersects(
@@ -212,8 +601,10 @@ impl Repository {
     /// Ensure working directory is clean, returning error if not.
     ///
     /// # Errors
-    /// Returns `DirtyWorkingDirectory` if there are uncommitted changes.
+    /// Returns `OperationInProgress` if a git operation such as a merge is
+    /// unfinished, or `DirtyWorkingDirectory` if there are uncommitted changes.
     pub fn require_clean(&self) -> Result<()> {
+        self.require_no_operation()?;
         if self.is_clean()? {
             Ok(())
         } else {
@@ -230,10 +621,26 @@ impl Repository {
     /// # Errors
     /// Returns error if staging fails.
     pub fn stage_all(&self) -> Result<()> {
+        self.git_add("-A")
+    }
+
+    /// Stage modifications and deletions of tracked files, leaving untracked
+    /// files alone.
+    ///
+    /// Equivalent to `git add -u`.
+    ///
+    /// # Errors
+    /// Returns error if staging fails.
+    pub fn stage_tracked(&self) -> Result<()> {
+        self.git_add("-u")
+    }
+
+    /// Run `git add <mode>` over the whole worktree.
+    fn git_add(&self, mode: &str) -> Result<()> {
         let workdir = self.workdir().ok_or(Error::NotARepository)?;
 
         let output = std::process::Command::new("git")
-            .args(["add", "-A"])
+            .args(["add", mode])
             .current_dir(workdir)
             .output()
             .map_err(|e| Error::Git2(git2::Error::from_str(&e.to_string())))?;
@@ -272,34 +679,198 @@ impl Repository {
         Ok(false)
     }
 
-    /// Create a commit with the given message on HEAD.
+    /// Staged files whose content is larger than `limit` bytes, with their sizes.
+    ///
+    /// Sizes are of the blobs in the index, i.e. after clean filters such as
+    /// Git LFS have run, so LFS-tracked files are measured by their pointer.
+    ///
+    /// # Errors
+    /// Returns error if the index or its objects can't be read.
+    pub fn large_staged_files(&self, limit: u64) -> Result<Vec<(String, u64)>> {
+        let head = self.inner.head().and_then(|h| h.peel_to_tree()).ok();
+        let diff = self.inner.diff_tree_to_index(head.as_ref(), None, None)?;
+        let odb = self.inner.odb()?;
+
+        let mut large = vec![];
+        for delta in diff.deltas() {
+            let file = delta.new_file();
+            if delta.status() == git2::Delta::Deleted || file.id().is_zero() {
+                continue;
+            }
+            let (size, _) = odb.read_header(file.id())?;
+            let size = size as u64;
+            if size > limit {
+                if let Some(path) = file.path() {
+                    large.push((path.to_string_lossy().into_owned(), size));
+                }
+            }
+        }
+        Ok(large)
+    }
+
+    /// Commit the index on HEAD with the given message.
     ///
     /// Handles both normal commits (with parent) and initial commits (no parent).
+    /// With `options.verify`, the `pre-commit` and `commit-msg` hooks run first, as
+    /// with `git commit`; either can reject the commit, and `commit-msg` can edit
+    /// the message. See [`CommitOptions`] for the rest.
     ///
     /// # Errors
-    /// Returns error if commit creation fails.
-    pub fn create_commit(&self, message: &str) -> Result<Oid> {
-        let sig = self.signature()?;
+    /// Returns `HookFailed` if a hook rejects the commit, `NothingToCommit` if the
+    /// commit would change nothing and empty commits aren't allowed,
+    /// `InvalidAuthor` for an unparseable `author`, `RefNotFound` when amending
+    /// an unborn HEAD, or error if commit creation fails.
+    pub fn create_commit(&self, message: &str, options: &CommitOptions) -> Result<Oid> {
+        let mut message = if options.signoff {
+            self.sign_off(message)?
+        } else {
+            message.to_string()
+        };
+        if options.verify {
+            if let Some(hook) = self.hook("pre-commit") {
+                self.run_hook(&hook, &[])?;
+            }
+            if let Some(hook) = self.hook("commit-msg") {
+                let path = self.git_dir().join("COMMIT_EDITMSG");
+                let io_error = |e: std::io::Error| Error::HookFailed(format!("commit-msg: {e}"));
+                std::fs::write(&path, &message).map_err(io_error)?;
+                self.run_hook(&hook, &[&path])?;
+                message = std::fs::read_to_string(&path).map_err(io_error)?;
+            }
+        }
+
         let mut index = self.inner.index()?;
+        // Pick up anything the pre-commit hook staged
+        index.read(false)?;
         let tree_id = index.write_tree()?;
         let tree = self.inner.find_tree(tree_id)?;
+        let head = self.inner.head().and_then(|h| h.peel_to_commit()).ok();
+
+        // An amended commit replaces HEAD, so it's compared with HEAD's parent
+        let parent = if options.amend {
+            let head = head
+                .as_ref()
+                .ok_or_else(|| Error::RefNotFound("HEAD".into()))?;
+            head.parent(0).ok()
+        } else {
+            head.clone()
+        };
+        if !options.allow_empty {
+            let parent_tree = match &parent {
+                Some(parent) => parent.tree_id(),
+                None => self.inner.treebuilder(None)?.write()?,
+            };
+            if parent_tree == tree_id {
+                return Err(Error::NothingToCommit);
+            }
+        }
 
-        // Handle initial commit case (unborn HEAD)
-        let oid = match self.inner.head().and_then(|h| h.peel_to_commit()) {
-            Ok(parent) => {
-                self.inner
-                    .commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])?
+        let sig = self.signature()?;
+        let author = match (&options.author, &head) {
+            (Some(author), _) => parse_author(author)?,
+            (None, Some(head)) if options.amend => head.author().to_owned(),
+            (None, _) => self.author_signature()?,
+        };
+
+        let summary = message.lines().next().unwrap_or_default().to_string();
+        let (oid, action) = match head {
+            Some(head) if options.amend => {
+                let oid = head.amend(
+                    None,
+                    Some(&author),
+                    Some(&sig),
+                    None,
+                    Some(&message),
+                    Some(&tree),
+                )?;
+                (oid, format!("amend to {summary}"))
+            }
+            Some(head) => {
+                let oid = self
+                    .inner
+                    .commit(None, &author, &sig, &message, &tree, &[&head])?;
+                (oid, format!("commit {summary}"))
             }
-            Err(_) => {
-                // Initial commit - no parent
-                self.inner
-                    .commit(Some("HEAD"), &sig, &sig, message, &tree, &[])?
+            // Initial commit (unborn HEAD) - no parent
+            None => {
+                let oid = self
+                    .inner
+                    .commit(None, &author, &sig, &message, &tree, &[])?;
+                (oid, format!("initial commit {summary}"))
             }
         };
 
+        // Move the branch HEAD is on (or HEAD itself, when detached)
+        let head_ref = self.inner.find_reference("HEAD")?;
+        let target = head_ref.symbolic_target().unwrap_or("HEAD").to_string();
+        self.inner
+            .reference(&target, oid, true, &reflog::message(&action))?;
+
         Ok(oid)
     }
 
+    /// A commit message read from `path`, cleaned up as `git commit -F` does:
+    /// trailing whitespace and surrounding blank lines removed, and runs of
+    /// blank lines collapsed into one.
+    ///
+    /// # Errors
+    /// Returns `MessageFile` if the file can't be read.
+    pub fn read_message_file(path: &Path) -> Result<String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| Error::MessageFile(format!("{}: {e}", path.display())))?;
+        Ok(git2::message_prettify(raw, None)?)
+    }
+
+    /// The executable hook called `name`, from `core.hooksPath` or `.git/hooks`.
+    fn hook(&self, name: &str) -> Option<PathBuf> {
+        let configured = self
+            .inner
+            .config()
+            .ok()
+            .and_then(|config| config.get_path("core.hooksPath").ok());
+        let dir = match configured {
+            // Relative paths are relative to where git runs hooks: the worktree
+            Some(dir) if dir.is_relative() => {
+                self.workdir().unwrap_or_else(|| self.git_dir()).join(dir)
+            }
+            Some(dir) => dir,
+            // Linked worktrees share the main repository's hooks
+            None => self.common_dir().join("hooks"),
+        };
+        let path = dir.join(name);
+
+        #[cfg(unix)]
+        let executable = {
+            use std::os::unix::fs::PermissionsExt;
+            path.metadata()
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        };
+        #[cfg(not(unix))]
+        let executable = path.is_file();
+
+        executable.then_some(path)
+    }
+
+    /// Run a hook from the worktree, failing if it exits unsuccessfully.
+    fn run_hook(&self, hook: &Path, args: &[&Path]) -> Result<()> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+        let name = hook
+            .file_name()
+            .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+
+        let status = std::process::Command::new(hook)
+            .args(args)
+            .current_dir(workdir)
+            .status()
+            .map_err(|e| Error::HookFailed(format!("{name}: {e}")))?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(Error::HookFailed(format!("{name} rejected the commit")))
+        }
+    }
+
     // === Commit operations ===
 
     /// Get a commit by its SHA.
@@ -310,6 +881,153 @@ impl Repository {
         Ok(self.inner.find_commit(oid)?)
     }
 
+    /// Check if a commit exists in the object database.
+    #[must_use]
+    pub fn commit_exists(&self, oid: Oid) -> bool {
+        self.inner.find_commit(oid).is_ok()
+    }
+
+    /// Resolve a revision (SHA, short SHA, `HEAD~1`, ...) to a commit.
+    ///
+    /// # Errors
+    /// Returns `RefNotFound` if the revision doesn't resolve to a commit.
+    pub fn resolve_commit(&self, spec: &str) -> Result<Oid> {
+        self.inner
+            .revparse_single(spec)
+            .and_then(|obj| obj.peel_to_commit())
+            .map(|c| c.id())
+            .map_err(|_| Error::RefNotFound(spec.to_string()))
+    }
+
+    /// `message` with a `Signed-off-by` trailer for the committer identity
+    /// added to its trailer block, as `git commit --signoff` does. Messages
+    /// already signed off by them are returned as they are.
+    ///
+    /// # Errors
+    /// Returns error if git config doesn't have user.name/email.
+    pub fn sign_off(&self, message: &str) -> Result<String> {
+        let signer = self.signature()?;
+        let person = format!(
+            "{} <{}>",
+            signer.name().unwrap_or_default(),
+            signer.email().unwrap_or_default()
+        );
+        let trailers = git2::message_trailers_strs(message)?;
+        if trailers
+            .iter()
+            .any(|(key, value)| key.eq_ignore_ascii_case(SIGNED_OFF_BY) && value == person)
+        {
+            return Ok(message.to_string());
+        }
+
+        let separator = if trailers.len() == 0 { "\n\n" } else { "\n" };
+        let newline = if message.ends_with('\n') { "\n" } else { "" };
+        Ok(format!(
+            "{}{separator}{SIGNED_OFF_BY}: {person}{newline}",
+            message.trim_end()
+        ))
+    }
+
+    /// Replace the message of `target`, a commit on `branch_name`'s first-parent history.
+    ///
+    /// Every commit above `target` is recreated with the same tree, author and
+    /// message on top of the reworded commit, so the working tree is untouched.
+    /// Merge commits keep their other parents. Returns the new branch tip.
+    ///
+    /// # Errors
+    /// Returns `RefNotFound` if `target` is not on the branch, or an error if
+    /// commit creation fails.
+    pub fn reword_commit(&self, branch_name: &str, target: Oid, message: &str) -> Result<Oid> {
+        let tip = self.branch_commit(branch_name)?;
+
+        // Walk down from the tip, remembering the commits to replay
+        let mut above = Vec::new();
+        let mut cursor = self.inner.find_commit(tip)?;
+        while cursor.id() != target {
+            let parent = cursor
+                .parent(0)
+                .map_err(|_| Error::RefNotFound(target.to_string()))?;
+            above.push(cursor);
+            cursor = parent;
+        }
+
+        let parents: Vec<git2::Commit<'_>> = cursor.parents().collect();
+        let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
+        let mut new_tip = self.inner.commit(
+            None,
+            &cursor.author(),
+            &self.signature()?,
+            message,
+            &cursor.tree()?,
+            &parent_refs,
+        )?;
+
+        for commit in above.iter().rev() {
+            // Only the first parent, the one walked through, was rewritten
+            let mut parents = vec![self.inner.find_commit(new_tip)?];
+            parents.extend(commit.parents().skip(1));
+            let parent_refs: Vec<&git2::Commit<'_>> = parents.iter().collect();
+            new_tip = self.inner.commit(
+                None,
+                &commit.author(),
+                &commit.committer(),
+                commit.message().unwrap_or_default(),
+                &commit.tree()?,
+                &parent_refs,
+            )?;
+        }
+
+        self.inner.reference(
+            &format!("refs/heads/{branch_name}"),
+            new_tip,
+            true,
+            &reflog::message(&format!("reword {}", reflog::short(target))),
+        )?;
+
+        Ok(new_tip)
+    }
+
+    /// The ID `content` has as a blob, as `git hash-object` prints it.
+    ///
+    /// # Errors
+    /// Returns error if hashing fails.
+    pub fn hash_blob(content: &[u8]) -> Result<Oid> {
+        Ok(Oid::hash_object(git2::ObjectType::Blob, content)?)
+    }
+
+    /// Add `files` (name and content) to the top of `parent`'s tree, or an
+    /// empty one, as a new commit on top of it, without touching the index
+    /// or working tree. Returns `None` when `parent` already has them all.
+    ///
+    /// # Errors
+    /// Returns error if a name isn't a valid tree entry or commit creation fails.
+    pub fn commit_files(
+        &self,
+        parent: Option<Oid>,
+        files: &[(String, Vec<u8>)],
+        message: &str,
+    ) -> Result<Option<Oid>> {
+        let parent = parent.map(|oid| self.inner.find_commit(oid)).transpose()?;
+        let base = parent.as_ref().map(git2::Commit::tree).transpose()?;
+        let mut builder = self.inner.treebuilder(base.as_ref())?;
+        for (name, content) in files {
+            let blob = self.inner.blob(content)?;
+            builder.insert(name, blob, git2::FileMode::Blob.into())?;
+        }
+        let tree_id = builder.write()?;
+        if base.as_ref().is_some_and(|base| base.id() == tree_id) {
+            return Ok(None);
+        }
+
+        let sig = self.signature()?;
+        let tree = self.inner.find_tree(tree_id)?;
+        let parents: Vec<&git2::Commit<'_>> = parent.iter().collect();
+        let oid = self
+            .inner
+            .commit(None, &sig, &sig, message, &tree, &parents)?;
+        Ok(Some(oid))
+    }
+
     /// Get the commit message from a branch's tip commit.
     ///
     /// # Errors
@@ -343,6 +1061,48 @@ impl Repository {
         Ok(revwalk.count())
     }
 
+    /// Patch ID of the changes from `from` to `to`, as `git patch-id`
+    /// computes it: unchanged when the same changes are rebased elsewhere.
+    ///
+    /// # Errors
+    /// Returns error if either commit doesn't exist or the diff fails.
+    pub fn patch_id(&self, from: Oid, to: Oid) -> Result<Oid> {
+        let old_tree = self.inner.find_commit(from)?.tree()?;
+        let new_tree = self.inner.find_commit(to)?.tree()?;
+        let diff = self
+            .inner
+            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+        Ok(diff.patchid(None)?)
+    }
+
+    /// Compare two versions of a series of commits, `old_base..old` and
+    /// `new_base..new`, as `git range-diff` does (without color).
+    ///
+    /// # Errors
+    /// Returns error if git can't run or its range-diff fails (it needs git
+    /// 2.19 or later).
+    pub fn range_diff(&self, old_base: Oid, old: Oid, new_base: Oid, new: Oid) -> Result<String> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+
+        let output = std::process::Command::new("git")
+            .args([
+                "range-diff",
+                "--no-color",
+                &format!("{old_base}..{old}"),
+                &format!("{new_base}..{new}"),
+            ])
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| Error::RangeDiffFailed(e.to_string()))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::RangeDiffFailed(stderr.trim().to_string()))
+        }
+    }
+
     /// Get commits between two points.
     ///
     /// # Errors
@@ -361,7 +1121,169 @@ impl Repository {
         Ok(commits)
     }
 
-    // === Reset operations ===
+    /// Details of the commit `oid`.
+    ///
+    /// # Errors
+    /// Returns error if the commit doesn't exist.
+    pub fn commit_info(&self, oid: Oid) -> Result<CommitInfo> {
+        let commit = self.inner.find_commit(oid)?;
+        let message = commit.message().unwrap_or_default().to_string();
+        let mut lines = message.lines();
+        let summary = lines.next().unwrap_or_default().trim().to_string();
+        let body = lines
+            .skip_while(|line| line.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let trailers = git2::message_trailers_strs(&message)
+            .map(|trailers| {
+                trailers
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let author = commit.author();
+
+        Ok(CommitInfo {
+            id: oid,
+            summary,
+            body,
+            author_name: author.name().unwrap_or_default().to_string(),
+            author_email: author.email().unwrap_or_default().to_string(),
+            message,
+            trailers,
+        })
+    }
+
+    /// When the commit `oid` was committed, in seconds since the Unix epoch.
+    ///
+    /// # Errors
+    /// Returns error if the commit doesn't exist.
+    pub fn commit_time(&self, oid: Oid) -> Result<i64> {
+        Ok(self.inner.find_commit(oid)?.time().seconds())
+    }
+
+    /// Details of the commits reachable from `to` but not `from`, newest
+    /// first, as [`Repository::commits_between`] lists them.
+    ///
+    /// # Errors
+    /// Returns error if revwalk fails.
+    pub fn commit_infos_between(&self, from: Oid, to: Oid) -> Result<Vec<CommitInfo>> {
+        self.commits_between(from, to)?
+            .into_iter()
+            .map(|oid| self.commit_info(oid))
+            .collect()
+    }
+
+    /// The patch a commit introduces, against its first parent (or against
+    /// nothing, for a root commit), as `git show` prints it.
+    ///
+    /// # Errors
+    /// Returns error if the commit doesn't exist or the diff fails.
+    pub fn commit_patch(&self, oid: Oid) -> Result<String> {
+        let commit = self.inner.find_commit(oid)?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff =
+            self.inner
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+        let mut patch = String::new();
+        diff.print(git2::DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+        Ok(patch)
+    }
+
+    /// Blame `path` (relative to the repo root) as of `commit`: each line
+    /// of the file there, with the commit that last changed it.
+    ///
+    /// # Errors
+    /// Returns error if the file isn't in `commit` or can't be blamed.
+    pub fn blame_lines(&self, path: &str, commit: Oid) -> Result<Vec<(Oid, String)>> {
+        let entry = self
+            .inner
+            .find_commit(commit)?
+            .tree()?
+            .get_path(Path::new(path))?;
+        let blob = self.inner.find_blob(entry.id())?;
+        let content = String::from_utf8_lossy(blob.content());
+
+        let mut options = git2::BlameOptions::new();
+        options.newest_commit(commit);
+        let blame = self.inner.blame_file(Path::new(path), Some(&mut options))?;
+        Ok(content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let id = blame
+                    .get_line(i + 1)
+                    .map_or(commit, |hunk| hunk.final_commit_id());
+                (id, line.to_string())
+            })
+            .collect())
+    }
+
+    /// List files changed on `branch` since it diverged from `parent`.
+    ///
+    /// Compares the merge-base tree with the branch tip, so changes that only
+    /// exist on the parent are not included. Paths are relative to the repo root
+    /// and sorted; renamed files are reported under their new path.
+    ///
+    /// # Errors
+    /// Returns error if either branch doesn't exist or the diff fails.
+    pub fn changed_files(&self, branch: &str, parent: &str) -> Result<Vec<String>> {
+        let tip = self.branch_commit(branch)?;
+        let base = self.merge_base(tip, self.branch_commit(parent)?)?;
+
+        let old_tree = self.inner.find_commit(base)?.tree()?;
+        let new_tree = self.inner.find_commit(tip)?.tree()?;
+        let diff = self
+            .inner
+            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+
+        let mut files: Vec<String> = diff
+            .deltas()
+            .filter_map(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())
+                    .map(|p| p.to_string_lossy().into_owned())
+            })
+            .collect();
+        files.sort();
+        files.dedup();
+
+        Ok(files)
+    }
+
+    /// Count lines changed on `branch` since it diverged from `parent`,
+    /// added plus removed, comparing the same trees as [`Self::changed_files`].
+    ///
+    /// # Errors
+    /// Returns error if either branch doesn't exist or the diff fails.
+    pub fn changed_lines(&self, branch: &str, parent: &str) -> Result<usize> {
+        let tip = self.branch_commit(branch)?;
+        let base = self.merge_base(tip, self.branch_commit(parent)?)?;
+
+        let old_tree = self.inner.find_commit(base)?.tree()?;
+        let new_tree = self.inner.find_commit(tip)?.tree()?;
+        let stats = self
+            .inner
+            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?
+            .stats()?;
+
+        Ok(stats.insertions() + stats.deletions())
+    }
+
+    // === Reset operations ===
 
     /// Hard reset a branch to a specific commit.
     ///
@@ -375,11 +1297,14 @@ impl Repository {
             &reference_name,
             target,
             true, // force
-            &format!("rung: reset to {}", &target.to_string()[..8]),
+            &reflog::message(&format!("reset {branch_name} to {}", reflog::short(target))),
         )?;
 
         // If this is the current branch, also update working directory
         if self.current_branch().ok().as_deref() == Some(branch_name) {
+            if self.is_sparse() {
+                return self.git_worktree_command(&["reset", "-q", "--hard", "HEAD"]);
+            }
             self.inner
                 .reset(commit.as_object(), git2::ResetType::Hard, None)?;
         }
@@ -387,14 +1312,103 @@ impl Repository {
         Ok(())
     }
 
+    // === Reference operations ===
+
+    /// Create or overwrite a reference pointing at a commit, logging
+    /// `action` (such as `snapshot before-refactor`) as rung's.
+    ///
+    /// # Errors
+    /// Returns error if the reference name is invalid or the write fails.
+    pub fn set_reference(&self, ref_name: &str, target: Oid, action: &str) -> Result<()> {
+        self.inner
+            .reference(ref_name, target, true, &reflog::message(action))?;
+        Ok(())
+    }
+
+    /// How local branch `branch` moved, newest first.
+    ///
+    /// # Errors
+    /// Returns error if the reflog can't be read.
+    pub fn reflog(&self, branch: &str) -> Result<Vec<ReflogEntry>> {
+        let reflog = self.inner.reflog(&format!("refs/heads/{branch}"))?;
+        Ok(reflog
+            .iter()
+            .map(|entry| ReflogEntry {
+                old: entry.id_old(),
+                new: entry.id_new(),
+                message: entry.message().unwrap_or_default().to_string(),
+                time: entry.committer().when().seconds(),
+            })
+            .collect())
+    }
+
+    /// List references whose full name starts with `prefix`.
+    ///
+    /// Returns (`ref_name`, `target`) pairs sorted by name.
+    ///
+    /// # Errors
+    /// Returns error if reference iteration fails.
+    pub fn references_with_prefix(&self, prefix: &str) -> Result<Vec<(String, Oid)>> {
+        let mut refs: Vec<(String, Oid)> = self
+            .inner
+            .references_glob(&format!("{prefix}*"))?
+            .filter_map(std::result::Result::ok)
+            .filter_map(|r| Some((r.name()?.to_string(), r.target()?)))
+            .collect();
+        refs.sort();
+        Ok(refs)
+    }
+
+    /// Delete a reference by its full name.
+    ///
+    /// # Errors
+    /// Returns error if the reference doesn't exist or deletion fails.
+    pub fn delete_reference(&self, ref_name: &str) -> Result<()> {
+        let mut reference = self
+            .inner
+            .find_reference(ref_name)
+            .map_err(|_| Error::RefNotFound(ref_name.into()))?;
+        reference.delete()?;
+        Ok(())
+    }
+
     // === Signature ===
 
     /// Get the default signature for commits.
     ///
+    /// The identity is the one `git commit` would use as committer, so
+    /// conditional includes (`includeIf`) and `GIT_COMMITTER_*` variables
+    /// apply. Falls back to libgit2's lookup if `git` can't resolve it.
+    ///
     /// # Errors
     /// Returns error if git config doesn't have user.name/email.
-    pub fn signature(&self) -> Result<Signature<'_>> {
-        Ok(self.inner.signature()?)
+    pub fn signature(&self) -> Result<Signature<'static>> {
+        self.ident("GIT_COMMITTER_IDENT")
+    }
+
+    /// Get the author signature `git commit` would use, honoring
+    /// `includeIf` and `GIT_AUTHOR_*` variables like [`Self::signature`].
+    ///
+    /// # Errors
+    /// Returns error if git config doesn't have user.name/email.
+    pub fn author_signature(&self) -> Result<Signature<'static>> {
+        self.ident("GIT_AUTHOR_IDENT")
+    }
+
+    /// Resolve `var` (`GIT_AUTHOR_IDENT` or `GIT_COMMITTER_IDENT`) with `git var`.
+    fn ident(&self, var: &str) -> Result<Signature<'static>> {
+        let dir = self.workdir().unwrap_or_else(|| self.git_dir());
+        let resolved = std::process::Command::new("git")
+            .args(["var", var])
+            .current_dir(dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_ident(&String::from_utf8_lossy(&output.stdout)));
+        match resolved {
+            Some(signature) => Ok(signature),
+            None => Ok(self.inner.signature()?.to_owned()),
+        }
     }
 
     // === Rebase operations ===
@@ -405,11 +1419,14 @@ impl Repository {
     ///
     /// # Errors
     /// Returns error if rebase fails or conflicts occur.
-    pub fn rebase_onto(&self, target: Oid) -> Result<()> {
+    pub fn rebase_onto(&self, target: Oid, options: RebaseOptions) -> Result<()> {
         let workdir = self.workdir().ok_or(Error::NotARepository)?;
 
         let output = std::process::Command::new("git")
-            .args(["rebase", &target.to_string()])
+            .arg("rebase")
+            .args(options.flags())
+            .arg(target.to_string())
+            .env("GIT_REFLOG_ACTION", self.rebase_action(target))
             .current_dir(workdir)
             .output()
             .map_err(|e| Error::RebaseFailed(e.to_string()))?;
@@ -436,16 +1453,19 @@ impl Repository {
     ///
     /// # Errors
     /// Returns error if rebase fails or conflicts occur.
-    pub fn rebase_onto_from(&self, new_base: Oid, old_base: Oid) -> Result<()> {
+    pub fn rebase_onto_from(
+        &self,
+        new_base: Oid,
+        old_base: Oid,
+        options: RebaseOptions,
+    ) -> Result<()> {
         let workdir = self.workdir().ok_or(Error::NotARepository)?;
 
         let output = std::process::Command::new("git")
-            .args([
-                "rebase",
-                "--onto",
-                &new_base.to_string(),
-                &old_base.to_string(),
-            ])
+            .arg("rebase")
+            .args(options.flags())
+            .args(["--onto", &new_base.to_string(), &old_base.to_string()])
+            .env("GIT_REFLOG_ACTION", self.rebase_action(new_base))
             .current_dir(workdir)
             .output()
             .map_err(|e| Error::RebaseFailed(e.to_string()))?;
@@ -464,6 +1484,108 @@ impl Repository {
         Err(Error::RebaseFailed(stderr.to_string()))
     }
 
+    /// Create branch `name` holding the commits in `from..tip` replayed onto
+    /// `onto`, and check it out. The branch the commits came from is left
+    /// untouched.
+    ///
+    /// # Errors
+    /// Returns `RebaseConflict` if replaying stopped on conflicts (the rebase
+    /// is left paused on `name`), or an error if the branch can't be created.
+    pub fn copy_commits_onto(
+        &self,
+        name: &BranchName,
+        from: Oid,
+        tip: Oid,
+        onto: Oid,
+    ) -> Result<()> {
+        self.create_branch_at(name, tip)?;
+        self.checkout(name)?;
+        self.rebase_onto_from(onto, from, RebaseOptions::default())
+    }
+
+    /// Commit the inverse of `target` on the current branch, as
+    /// `git revert --no-edit` does, signed off with `signoff`. Merge commits
+    /// are reverted against their first parent.
+    ///
+    /// # Errors
+    /// Returns `RevertConflict` if the revert stopped on conflicts (it is left
+    /// in progress), or `RevertFailed` if git could not run it.
+    pub fn revert_commit(&self, target: Oid, signoff: bool) -> Result<Oid> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+        let mut args = vec!["revert", "--no-edit"];
+        if signoff {
+            args.push("--signoff");
+        }
+        if self.inner.find_commit(target)?.parent_count() > 1 {
+            args.extend(["-m", "1"]);
+        }
+
+        let output = std::process::Command::new("git")
+            .args(args)
+            .arg(target.to_string())
+            .env(
+                "GIT_REFLOG_ACTION",
+                reflog::message(&format!("revert {}", reflog::short(target))),
+            )
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| Error::RevertFailed(e.to_string()))?;
+
+        if output.status.success() {
+            return Ok(self.inner.head()?.peel_to_commit()?.id());
+        }
+
+        if matches!(
+            self.state(),
+            RepositoryState::Revert | RepositoryState::RevertSequence
+        ) {
+            let conflicts = self.conflicting_files()?;
+            return Err(Error::RevertConflict(conflicts));
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(Error::RevertFailed(stderr.trim().to_string()))
+    }
+
+    /// Run an interactive rebase of the current branch onto `onto`.
+    ///
+    /// The user's editor is attached to the terminal. Returns `Ok(())` when git
+    /// exits successfully, which includes rebases paused at an `edit` step -
+    /// callers should check [`Self::is_rebasing`] afterwards.
+    ///
+    /// # Errors
+    /// Returns `RebaseConflict` if the rebase stopped on conflicts, or
+    /// `RebaseFailed` if git could not run the rebase.
+    pub fn rebase_interactive(&self, onto: Oid) -> Result<()> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+
+        let status = std::process::Command::new("git")
+            .args(["rebase", "-i", &onto.to_string()])
+            .env("GIT_REFLOG_ACTION", self.rebase_action(onto))
+            .current_dir(workdir)
+            .status()
+            .map_err(|e| Error::RebaseFailed(e.to_string()))?;
+
+        if status.success() {
+            return Ok(());
+        }
+
+        if self.is_rebasing() {
+            let conflicts = self.conflicting_files()?;
+            return Err(Error::RebaseConflict(conflicts));
+        }
+
+        Err(Error::RebaseFailed(format!(
+            "interactive rebase exited with {status}"
+        )))
+    }
+
+    /// `GIT_REFLOG_ACTION` for rebasing the current branch onto `onto`.
+    fn rebase_action(&self, onto: Oid) -> String {
+        let branch = self.current_branch().unwrap_or_else(|_| "HEAD".into());
+        reflog::message(&format!("rebase {branch} onto {}", reflog::short(onto)))
+    }
+
     /// Get list of files with conflicts.
     ///
     /// # Errors
@@ -478,6 +1600,104 @@ impl Repository {
         Ok(conflicts)
     }
 
+    /// The staged blobs of each conflicted file, in index order.
+    ///
+    /// # Errors
+    /// Returns error if the index can't be read.
+    pub fn conflict_stages(&self) -> Result<Vec<ConflictStages>> {
+        let mut index = self.inner.index()?;
+        index.read(true)?;
+        let mut stages = vec![];
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let Some(path) = [&conflict.our, &conflict.their, &conflict.ancestor]
+                .into_iter()
+                .flatten()
+                .next()
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            else {
+                continue;
+            };
+            stages.push(ConflictStages {
+                path,
+                base: conflict.ancestor.map(|entry| entry.id),
+                ours: conflict.our.map(|entry| entry.id),
+                theirs: conflict.their.map(|entry| entry.id),
+            });
+        }
+        Ok(stages)
+    }
+
+    /// The commit a paused rebase stopped while replaying (`REBASE_HEAD`).
+    #[must_use]
+    pub fn replaying_commit(&self) -> Option<Oid> {
+        self.inner.refname_to_id("REBASE_HEAD").ok()
+    }
+
+    /// Find the commits that caused a paused rebase's conflicts in `path`.
+    ///
+    /// Blames the `HEAD` side of each conflict region over the commits since
+    /// the rebased commit's fork point, keeping those on the branch being
+    /// rebased onto. Empty when no rebase is paused or nothing matches, as
+    /// when `HEAD`'s side of every conflict is empty.
+    ///
+    /// # Errors
+    /// Returns error if the commits can't be read or blamed.
+    pub fn conflict_blame(&self, path: &str) -> Result<Vec<ConflictCommit>> {
+        let Some(picked) = self.replaying_commit() else {
+            return Ok(vec![]);
+        };
+        let head = self.inner.head()?.peel_to_commit()?;
+        let fork = self.inner.merge_base(head.id(), picked)?;
+        let onto = self.rebase_target().unwrap_or_else(|| head.id());
+
+        let Some(workdir) = self.workdir() else {
+            return Ok(vec![]);
+        };
+        let Ok(entry) = head.tree()?.get_path(Path::new(path)) else {
+            return Ok(vec![]); // Not in HEAD, e.g. a modify/delete conflict
+        };
+        let blob = self.inner.find_blob(entry.id())?;
+        let ours = String::from_utf8_lossy(blob.content());
+        let merged = std::fs::read_to_string(workdir.join(path)).unwrap_or_default();
+
+        let mut found: Vec<ConflictCommit> = vec![];
+        for (start, end) in locate_ours_regions(&merged, &ours) {
+            let mut options = git2::BlameOptions::new();
+            options
+                .newest_commit(head.id())
+                .oldest_commit(fork)
+                .min_line(start)
+                .max_line(end);
+            let blame = self.inner.blame_file(Path::new(path), Some(&mut options))?;
+            for hunk in blame.iter() {
+                let id = hunk.final_commit_id();
+                if hunk.is_boundary() || id == fork || found.iter().any(|c| c.id == id) {
+                    continue;
+                }
+                // Skip the branch's own commits replayed earlier in the rebase
+                if id != onto && !self.inner.graph_descendant_of(onto, id)? {
+                    continue;
+                }
+                let commit = self.inner.find_commit(id)?;
+                found.push(ConflictCommit {
+                    id,
+                    author: commit.author().name().unwrap_or("unknown").to_string(),
+                    summary: commit.summary().unwrap_or_default().to_string(),
+                });
+            }
+        }
+        Ok(found)
+    }
+
+    /// The commit a paused rebase is rebasing onto.
+    fn rebase_target(&self) -> Option<Oid> {
+        ["rebase-merge/onto", "rebase-apply/onto"]
+            .iter()
+            .find_map(|file| std::fs::read_to_string(self.git_dir().join(file)).ok())
+            .and_then(|onto| Oid::from_str(onto.trim()).ok())
+    }
+
     /// Abort an in-progress rebase.
     ///
     /// # Errors
@@ -487,11 +1707,15 @@ impl Repository {
 
         let output = std::process::Command::new("git")
             .args(["rebase", "--abort"])
+            .env("GIT_REFLOG_ACTION", reflog::message("abort rebase"))
             .current_dir(workdir)
             .output()
             .map_err(|e| Error::RebaseFailed(e.to_string()))?;
 
         if output.status.success() {
+            // Drop the conflicted index libgit2 cached, which git may have
+            // replaced within the same mtime tick
+            self.inner.index()?.read(true)?;
             Ok(())
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -508,6 +1732,7 @@ impl Repository {
 
         let output = std::process::Command::new("git")
             .args(["rebase", "--continue"])
+            .env("GIT_REFLOG_ACTION", reflog::message("continue rebase"))
             .current_dir(workdir)
             .output()
             .map_err(|e| Error::RebaseFailed(e.to_string()))?;
@@ -544,52 +1769,49 @@ impl Repository {
             .ok_or_else(|| Error::RemoteNotFound("origin".into()))
     }
 
-    /// Parse owner and repo name from a GitHub URL.
-    ///
-    /// Supports both HTTPS and SSH URLs:
-    /// - `https://github.com/owner/repo.git`
-    /// - `git@github.com:owner/repo.git`
+    /// The GitHub repository `origin` points at, on github.com or one of
+    /// `hosts` (see [`GitHubRemote::parse`]).
     ///
     /// # Errors
-    /// Returns error if URL cannot be parsed.
-    pub fn parse_github_remote(url: &str) -> Result<(String, String)> {
-        // SSH format: git@github.com:owner/repo.git
-        if let Some(rest) = url.strip_prefix("git@github.com:") {
-            let path = rest.strip_suffix(".git").unwrap_or(rest);
-            if let Some((owner, repo)) = path.split_once('/') {
-                return Ok((owner.to_string(), repo.to_string()));
-            }
-        }
-
-        // HTTPS format: https://github.com/owner/repo.git
-        if let Some(rest) = url
-            .strip_prefix("https://github.com/")
-            .or_else(|| url.strip_prefix("http://github.com/"))
-        {
-            let path = rest.strip_suffix(".git").unwrap_or(rest);
-            if let Some((owner, repo)) = path.split_once('/') {
-                return Ok((owner.to_string(), repo.to_string()));
-            }
-        }
-
-        Err(Error::InvalidRemoteUrl(url.to_string()))
+    /// Returns error if there's no origin or it isn't a GitHub repository.
+    pub fn github_remote(&self, hosts: &RemoteHosts) -> Result<GitHubRemote> {
+        GitHubRemote::parse(&self.origin_url()?, hosts)
     }
 
     /// Push a branch to the remote.
     ///
     /// # Errors
     /// Returns error if push fails.
-    pub fn push(&self, branch: &str, force: bool) -> Result<()> {
+    pub fn push(&self, branch: &BranchName, force: bool) -> Result<()> {
+        self.push_to(branch, branch, force)
+    }
+
+    /// Push a local branch to a differently named branch on the remote.
+    ///
+    /// # Errors
+    /// Returns error if the push fails.
+    pub fn push_to(
+        &self,
+        branch: &BranchName,
+        remote_branch: &BranchName,
+        force: bool,
+    ) -> Result<()> {
         let workdir = self.workdir().ok_or(Error::NotARepository)?;
 
-        let mut args = vec!["push", "-u", "origin", branch];
+        let refspec = format!("{branch}:refs/heads/{remote_branch}");
+        let mut args = vec!["push", "-u", "origin", refspec.as_str()];
         if force {
             args.insert(1, "--force-with-lease");
         }
 
-        let output = std::process::Command::new("git")
-            .args(&args)
-            .current_dir(workdir)
+        let mut command = std::process::Command::new("git");
+        command.args(&args).current_dir(workdir);
+        // Keep the push out of the terminal's process group so Ctrl-C lets it
+        // finish instead of leaving the remote half-updated
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+        let output = command
             .output()
             .map_err(|e| Error::PushFailed(e.to_string()))?;
 
@@ -605,7 +1827,7 @@ impl Repository {
     ///
     /// # Errors
     /// Returns error if fetch fails.
-    pub fn fetch(&self, branch: &str) -> Result<()> {
+    pub fn fetch(&self, branch: &BranchName) -> Result<()> {
         let workdir = self.workdir().ok_or(Error::NotARepository)?;
 
         // Use refspec to update both remote tracking branch and local branch
@@ -613,6 +1835,10 @@ impl Repository {
         let refspec = format!("{branch}:refs/heads/{branch}");
         let output = std::process::Command::new("git")
             .args(["fetch", "origin", &refspec])
+            .env(
+                "GIT_REFLOG_ACTION",
+                reflog::message(&format!("fetch {branch}")),
+            )
             .current_dir(workdir)
             .output()
             .map_err(|e| Error::FetchFailed(e.to_string()))?;
@@ -625,6 +1851,141 @@ impl Repository {
         }
     }
 
+    /// Fetch the head of pull request `number` from origin into the local
+    /// `branch`, replacing whatever it pointed at.
+    ///
+    /// # Errors
+    /// Returns error if fetch fails, e.g. when `branch` is checked out.
+    pub fn fetch_pull_head(&self, number: u64, branch: &BranchName) -> Result<Oid> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+
+        let refspec = format!("+refs/pull/{number}/head:refs/heads/{branch}");
+        let output = std::process::Command::new("git")
+            .args(["fetch", "origin", &refspec])
+            .env(
+                "GIT_REFLOG_ACTION",
+                reflog::message(&format!("fetch PR #{number}")),
+            )
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| Error::FetchFailed(e.to_string()))?;
+
+        if output.status.success() {
+            self.branch_commit(branch)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::FetchFailed(stderr.to_string()))
+        }
+    }
+
+    /// Fetch `branch` from origin into `origin/<branch>` only, leaving the
+    /// local branch alone.
+    ///
+    /// # Errors
+    /// Returns error if fetch fails.
+    pub fn fetch_remote_branch(&self, branch: &BranchName) -> Result<()> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+
+        let refspec = format!("refs/heads/{branch}:refs/remotes/origin/{branch}");
+        let output = std::process::Command::new("git")
+            .args(["fetch", "origin", &refspec])
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| Error::FetchFailed(e.to_string()))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::FetchFailed(stderr.to_string()))
+        }
+    }
+
+    /// Whether `branch` still exists on origin (`git ls-remote`).
+    ///
+    /// # Errors
+    /// Returns error if origin can't be reached.
+    pub fn remote_branch_exists(&self, branch: &BranchName) -> Result<bool> {
+        Ok(self.remote_branch_tip(branch)?.is_some())
+    }
+
+    /// Check that a push of `branch` landed: `remote_branch`, read back from
+    /// origin itself rather than the tracking ref the push updated, must be
+    /// at `branch`'s local tip.
+    ///
+    /// # Errors
+    /// Returns `PushNotLanded` if origin has the branch elsewhere or not at
+    /// all, or an error if origin can't be reached.
+    pub fn verify_pushed(&self, branch: &BranchName, remote_branch: &BranchName) -> Result<()> {
+        let expected = self.branch_commit(branch)?;
+        let actual = self.remote_branch_tip(remote_branch)?;
+        if actual == Some(expected) {
+            return Ok(());
+        }
+        Err(Error::PushNotLanded {
+            branch: remote_branch.to_string(),
+            expected: reflog::short(expected),
+            actual: actual.map(reflog::short),
+        })
+    }
+
+    /// The commit `branch` is at on origin (`git ls-remote`), or `None` if
+    /// origin doesn't have it.
+    ///
+    /// # Errors
+    /// Returns error if origin can't be reached.
+    pub fn remote_branch_tip(&self, branch: &BranchName) -> Result<Option<Oid>> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+
+        let output = std::process::Command::new("git")
+            .args([
+                "ls-remote",
+                "--heads",
+                "origin",
+                &format!("refs/heads/{branch}"),
+            ])
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| Error::FetchFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::FetchFailed(stderr.to_string()));
+        }
+        let wanted = format!("refs/heads/{branch}");
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .find(|(_, name)| *name == wanted)
+            .and_then(|(sha, _)| Oid::from_str(sha).ok()))
+    }
+
+    /// Fast-forward the current branch to `target`, keeping uncommitted
+    /// changes that don't overlap (`git merge --ff-only`).
+    ///
+    /// # Errors
+    /// Returns error if the branch can't be fast-forwarded.
+    pub fn fast_forward(&self, target: Oid) -> Result<()> {
+        let workdir = self.workdir().ok_or(Error::NotARepository)?;
+
+        let output = std::process::Command::new("git")
+            .args(["merge", "--ff-only", &target.to_string()])
+            .env(
+                "GIT_REFLOG_ACTION",
+                reflog::message(&format!("fast-forward to {}", reflog::short(target))),
+            )
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| Error::FastForwardFailed(e.to_string()))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::FastForwardFailed(stderr.trim().to_string()))
+        }
+    }
+
     /// Pull (fast-forward only) the current branch from origin.
     ///
     /// This fetches and merges `origin/<branch>` into the current branch,
@@ -637,6 +1998,10 @@ impl Repository {
 
         let output = std::process::Command::new("git")
             .args(["pull", "--ff-only"])
+            .env(
+                "GIT_REFLOG_ACTION",
+                reflog::message("fast-forward from origin"),
+            )
             .current_dir(workdir)
             .output()
             .map_err(|e| Error::FetchFailed(e.to_string()))?;
@@ -660,6 +2025,68 @@ impl Repository {
     }
 }
 
+/// Parse a `git var` identity such as `Ana <ana@example.com> 1700000000 +0200`.
+/// A signature for now from `Name <email>`, as `git commit --author` takes it.
+fn parse_author(author: &str) -> Result<Signature<'static>> {
+    let invalid = || Error::InvalidAuthor(author.to_string());
+    let (name, email) = author
+        .trim()
+        .strip_suffix('>')
+        .and_then(|person| person.rsplit_once('<'))
+        .ok_or_else(invalid)?;
+    let (name, email) = (name.trim(), email.trim());
+    if name.is_empty() || email.is_empty() {
+        return Err(invalid());
+    }
+    Signature::now(name, email).map_err(|_| invalid())
+}
+
+fn parse_ident(ident: &str) -> Option<Signature<'static>> {
+    let (rest, offset) = ident.trim().rsplit_once(' ')?;
+    let (person, seconds) = rest.rsplit_once(' ')?;
+    let (name, email) = person.strip_suffix('>')?.rsplit_once(" <")?;
+
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let digits = offset.get(1..)?;
+    let hours: i32 = digits.get(..2)?.parse().ok()?;
+    let minutes: i32 = digits.get(2..)?.parse().ok()?;
+    let time = git2::Time::new(seconds.parse().ok()?, sign * (hours * 60 + minutes));
+    Signature::new(name, email, &time).ok()
+}
+
+/// Line ranges (1-based, inclusive) in `ours` of the `HEAD` side of each
+/// conflict marked in `merged`. Sides that are empty or can't be found are
+/// left out.
+fn locate_ours_regions(merged: &str, ours: &str) -> Vec<(usize, usize)> {
+    let mut sides: Vec<Vec<&str>> = vec![];
+    // Lines of the HEAD side being read, between `<<<<<<<` and `=======`
+    // (or `|||||||` with diff3 markers)
+    let mut side: Option<Vec<&str>> = None;
+    for line in merged.lines() {
+        if line.starts_with("<<<<<<<") {
+            side = Some(vec![]);
+        } else if line.starts_with("|||||||") || line == "=======" {
+            sides.extend(side.take());
+        } else if let Some(side) = side.as_mut() {
+            side.push(line);
+        }
+    }
+
+    // Each side appears verbatim in HEAD's version, in order
+    let ours: Vec<&str> = ours.lines().collect();
+    let mut regions = vec![];
+    let mut from = 0;
+    for side in sides.iter().filter(|side| !side.is_empty()) {
+        let found = (from..ours.len().saturating_sub(side.len() - 1))
+            .find(|&i| ours[i..i + side.len()] == side[..]);
+        if let Some(i) = found {
+            regions.push((i + 1, i + side.len()));
+            from = i + side.len();
+        }
+    }
+    regions
+}
+
 impl std::fmt::Debug for Repository {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Repository")
@@ -675,6 +2102,10 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    fn branch(name: &str) -> BranchName {
+        BranchName::new(name).unwrap()
+    }
+
     fn init_test_repo() -> (TempDir, Repository) {
         let temp = TempDir::new().unwrap();
         let repo = git2::Repository::init(temp.path()).unwrap();
@@ -691,25 +2122,828 @@ mod tests {
         (temp, wrapped)
     }
 
+    #[test]
+    fn test_commit_files() {
+        let (_temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let head = repo.resolve_commit("HEAD").unwrap();
+        let files = vec![("a.png".to_string(), b"png".to_vec())];
+
+        // An orphan commit holds just the files
+        let orphan = repo.commit_files(None, &files, "Add a").unwrap().unwrap();
+        let commit = repo.find_commit(orphan).unwrap();
+        assert_eq!(commit.parent_count(), 0);
+        assert_eq!(commit.tree().unwrap().len(), 1);
+
+        let next = vec![("b.png".to_string(), b"more".to_vec())];
+        let oid = repo
+            .commit_files(Some(orphan), &next, "Add b")
+            .unwrap()
+            .unwrap();
+        let commit = repo.find_commit(oid).unwrap();
+        assert_eq!(commit.parent_id(0).unwrap(), orphan);
+        assert!(commit.tree().unwrap().get_name("a.png").is_some());
+        assert!(commit.tree().unwrap().get_name("b.png").is_some());
+
+        // Nothing new to add
+        assert_eq!(
+            repo.commit_files(Some(orphan), &files, "Again").unwrap(),
+            None
+        );
+        // The working tree's branch is left alone
+        assert_eq!(repo.resolve_commit("HEAD").unwrap(), head);
+    }
+
+    #[test]
+    fn test_sparse_checkout() {
+        let (temp, repo) = init_test_repo();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        for dir in ["app", "docs"] {
+            fs::create_dir(temp.path().join(dir)).unwrap();
+            fs::write(temp.path().join(dir).join("file.txt"), dir).unwrap();
+        }
+        git(&["add", "."]);
+        git(&["commit", "-qm", "Add app and docs"]);
+        let main = repo.current_branch().unwrap();
+        repo.create_branch(&branch("feature")).unwrap();
+        assert!(!repo.is_sparse());
+
+        git(&["sparse-checkout", "set", "--cone", "app"]);
+        assert!(repo.is_sparse());
+        assert!(!temp.path().join("docs").exists());
+        // Files outside the cone aren't missing changes
+        assert!(repo.is_clean().unwrap());
+
+        // Checkouts keep to the cone
+        repo.checkout("feature").unwrap();
+        assert!(!temp.path().join("docs").exists());
+        assert!(temp.path().join("app/file.txt").exists());
+        repo.checkout_detached(repo.branch_commit(&main).unwrap())
+            .unwrap();
+        assert!(!temp.path().join("docs").exists());
+        repo.checkout("feature").unwrap();
+        let base = repo.resolve_commit("HEAD~1").unwrap();
+        repo.reset_branch("feature", base).unwrap();
+        assert!(!temp.path().join("docs").exists());
+        assert!(repo.is_clean().unwrap());
+    }
+
     #[test]
     fn test_current_branch() {
         let (_temp, repo) = init_test_repo();
         // Default branch after init
         let branch = repo.current_branch().unwrap();
         assert!(branch == "main" || branch == "master");
+        assert_eq!(repo.unborn_branch(), None);
+    }
+
+    #[test]
+    fn test_unborn_head() {
+        let temp = TempDir::new().unwrap();
+        let repo = Repository {
+            inner: git2::Repository::init(temp.path()).unwrap(),
+        };
+        repo.inner.set_head("refs/heads/trunk").unwrap();
+
+        assert_eq!(repo.unborn_branch().as_deref(), Some("trunk"));
+        assert!(matches!(
+            repo.current_branch(),
+            Err(Error::UnbornHead(branch)) if branch == "trunk"
+        ));
+        assert!(matches!(
+            repo.branch_commit("trunk"),
+            Err(Error::UnbornHead(_))
+        ));
+        assert!(matches!(
+            repo.branch_commit("other"),
+            Err(Error::BranchNotFound(_))
+        ));
     }
 
     #[test]
     fn test_create_and_checkout_branch() {
         let (_temp, repo) = init_test_repo();
 
-        repo.create_branch("feature/test").unwrap();
+        repo.create_branch(&branch("feature/test")).unwrap();
         assert!(repo.branch_exists("feature/test"));
 
         repo.checkout("feature/test").unwrap();
         assert_eq!(repo.current_branch().unwrap(), "feature/test");
     }
 
+    #[test]
+    fn test_rejects_invalid_branch_names() {
+        // Names reach `create_branch`, `push`, `fetch` and the like only as a
+        // `BranchName`, so these never get as far as a git command line
+        for name in ["-f", "--exec=touch pwned", "a..b", "bad name"] {
+            assert!(
+                matches!(BranchName::new(name), Err(Error::InvalidBranchName { .. })),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_ident() {
+        let sig = parse_ident("Ana Lima <ana@example.com> 1700000000 -0230\n").unwrap();
+        assert_eq!(sig.name(), Some("Ana Lima"));
+        assert_eq!(sig.email(), Some("ana@example.com"));
+        assert_eq!(sig.when().seconds(), 1_700_000_000);
+        assert_eq!(sig.when().offset_minutes(), -150);
+        assert!(parse_ident("Ana 1700000000 +0000").is_none());
+    }
+
+    #[test]
+    fn test_sign_off() {
+        let (_temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Ana").unwrap();
+        config.set_str("user.email", "ana@example.com").unwrap();
+        let trailer = "Signed-off-by: Ana <ana@example.com>";
+
+        // A conventional subject isn't mistaken for a trailer block
+        assert_eq!(
+            repo.sign_off("fix: typo").unwrap(),
+            format!("fix: typo\n\n{trailer}")
+        );
+        assert_eq!(
+            repo.sign_off("Add x\n\nBody.\n\nFixes: #1\n").unwrap(),
+            format!("Add x\n\nBody.\n\nFixes: #1\n{trailer}\n")
+        );
+        let signed = format!("Add x\n\n{trailer}");
+        assert_eq!(repo.sign_off(&signed).unwrap(), signed);
+    }
+
+    #[test]
+    fn test_signature_honors_include_if() {
+        let (temp, repo) = init_test_repo();
+        let work = temp.path().join("work.gitconfig");
+        fs::write(&work, "[user]\n\tname = Ana\n\temail = ana@work.example\n").unwrap();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        config
+            .set_str("includeIf.onbranch:work/**.path", work.to_str().unwrap())
+            .unwrap();
+
+        assert_eq!(repo.signature().unwrap().email(), Some("test@example.com"));
+        repo.create_branch(&branch("work/report")).unwrap();
+        repo.checkout("work/report").unwrap();
+        assert_eq!(repo.signature().unwrap().email(), Some("ana@work.example"));
+        assert_eq!(repo.author_signature().unwrap().name(), Some("Ana"));
+    }
+
+    #[test]
+    fn test_locate_ours_regions() {
+        let ours = "a\nb\nc\nd\ne\n";
+        let merged = "a\n<<<<<<< HEAD\nb\nc\n=======\nB\n>>>>>>> pick\nd\n\
+                      <<<<<<< HEAD\n||||||| base\nx\n=======\ny\n>>>>>>> pick\n\
+                      <<<<<<< HEAD\ne\n||||||| base\nE\n=======\nf\n>>>>>>> pick\n";
+        // The empty side of the second conflict is left out
+        assert_eq!(locate_ours_regions(merged, ours), vec![(2, 3), (5, 5)]);
+        assert!(locate_ours_regions("a\nb\n", ours).is_empty());
+    }
+
+    #[test]
+    fn test_blame_lines() {
+        let (temp, repo) = init_test_repo();
+        let commit = |content: &str, message: &str| {
+            fs::write(temp.path().join("file.txt"), content).unwrap();
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(["commit", "-q", "-m", message, "--", "file.txt"])
+                .current_dir(temp.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        };
+        fs::write(temp.path().join("file.txt"), "").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "file.txt"])
+            .current_dir(temp.path())
+            .output()
+            .unwrap();
+        commit("one\ntwo\n", "Add lines");
+        let first = repo.resolve_commit("HEAD").unwrap();
+        commit("one\nTWO\nthree\n", "Shout");
+        let second = repo.resolve_commit("HEAD").unwrap();
+
+        let lines = repo.blame_lines("file.txt", second).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (first, "one".to_string()),
+                (second, "TWO".to_string()),
+                (second, "three".to_string()),
+            ]
+        );
+        assert_eq!(repo.blame_lines("file.txt", first).unwrap().len(), 2);
+        assert!(repo.blame_lines("missing.txt", second).is_err());
+    }
+
+    #[test]
+    fn test_conflict_blame() {
+        let (temp, repo) = init_test_repo();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+        let commit = |content: &str, message: &str| {
+            fs::write(temp.path().join("file.txt"), content).unwrap();
+            git(&["commit", "-qam", message]);
+        };
+        fs::write(temp.path().join("file.txt"), "one\ntwo\nthree\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "Add file"]);
+        let trunk = repo.current_branch().unwrap();
+        git(&["checkout", "-qb", "feature"]);
+        commit("one\nTWO\nthree\n", "Shout two");
+        git(&["checkout", "-q", &trunk]);
+        commit("one\n2\nthree\n", "Number two");
+        commit("1\n2\nthree\n", "Number one");
+        git(&["checkout", "-q", "feature"]);
+
+        assert!(repo.conflict_blame("file.txt").unwrap().is_empty());
+        let target = repo.branch_commit(&trunk).unwrap();
+        assert!(matches!(
+            repo.rebase_onto(target, RebaseOptions::default()),
+            Err(Error::RebaseConflict(_))
+        ));
+
+        let blamed = repo.conflict_blame("file.txt").unwrap();
+        let summaries: Vec<&str> = blamed.iter().map(|c| c.summary.as_str()).collect();
+        // Both trunk commits touched the conflicting lines
+        assert_eq!(summaries, vec!["Number one", "Number two"]);
+        assert_eq!(blamed[0].author, "Test");
+
+        // The feature commit is being replayed, against trunk's version
+        let feature = repo.branch_commit("feature").unwrap();
+        assert_eq!(repo.replaying_commit(), Some(feature));
+        let stages = repo.conflict_stages().unwrap();
+        assert_eq!(stages.len(), 1);
+        assert_eq!(stages[0].path, "file.txt");
+        let blob = |rev: &str| repo.inner.revparse_single(rev).unwrap().id();
+        assert_eq!(stages[0].base, Some(blob("feature~1:file.txt")));
+        assert_eq!(stages[0].ours, Some(blob(&format!("{trunk}:file.txt"))));
+        assert_eq!(stages[0].theirs, Some(blob("feature:file.txt")));
+
+        repo.rebase_abort().unwrap();
+        assert_eq!(repo.replaying_commit(), None);
+        assert!(repo.conflict_stages().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_in_progress() {
+        let (temp, repo) = init_test_repo();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap()
+        };
+        fs::write(temp.path().join("file.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "Add file"]);
+        let trunk = repo.current_branch().unwrap();
+        git(&["checkout", "-qb", "feature"]);
+        fs::write(temp.path().join("file.txt"), "feature\n").unwrap();
+        git(&["commit", "-qam", "Feature"]);
+        git(&["checkout", "-q", &trunk]);
+        fs::write(temp.path().join("file.txt"), "trunk\n").unwrap();
+        git(&["commit", "-qam", "Trunk"]);
+
+        assert_eq!(repo.in_progress(), None);
+        assert!(repo.require_no_operation().is_ok());
+
+        assert!(!git(&["cherry-pick", "feature"]).status.success());
+        assert_eq!(repo.in_progress(), Some(InProgress::CherryPick));
+        let err = repo.require_clean().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::OperationInProgress(InProgress::CherryPick)
+        ));
+        assert!(err.to_string().contains("`git cherry-pick --abort`"));
+
+        git(&["cherry-pick", "--abort"]);
+        assert_eq!(repo.in_progress(), None);
+        assert!(repo.require_clean().is_ok());
+    }
+
+    #[test]
+    fn test_patch_id_and_range_diff() {
+        let (temp, repo) = init_test_repo();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+        let commit = |file: &str, content: &str| {
+            fs::write(temp.path().join(file), content).unwrap();
+            git(&["add", "."]);
+            git(&["commit", "-qm", file]);
+            repo.resolve_commit("HEAD").unwrap()
+        };
+        let base = repo.resolve_commit("HEAD").unwrap();
+        let tip = commit("feature.txt", "feature\n");
+        git(&["checkout", "-q", "--detach", &base.to_string()]);
+        let moved = commit("trunk.txt", "trunk\n");
+        let rebased = commit("feature.txt", "feature\n");
+        let changed = commit("feature.txt", "changed\n");
+
+        let original = repo.patch_id(base, tip).unwrap();
+        assert_eq!(repo.patch_id(moved, rebased).unwrap(), original);
+        assert_ne!(repo.patch_id(moved, changed).unwrap(), original);
+
+        let range_diff = repo.range_diff(base, tip, moved, rebased).unwrap();
+        assert!(range_diff.starts_with("1:  "), "{range_diff}");
+        assert!(range_diff.contains(" = 1:  "), "{range_diff}");
+        let range_diff = repo.range_diff(base, tip, moved, changed).unwrap();
+        assert!(range_diff.contains("-:  "), "{range_diff}");
+    }
+
+    #[test]
+    fn test_checkout_detached() {
+        let (_temp, repo) = init_test_repo();
+        let head = repo.inner.head().unwrap().target().unwrap();
+
+        repo.checkout_detached(head).unwrap();
+        assert!(repo.current_branch().is_err());
+        assert_eq!(repo.resolve_commit("HEAD").unwrap(), head);
+    }
+
+    #[test]
+    fn test_create_branch_at() {
+        let (_temp, repo) = init_test_repo();
+        let head = repo.inner.head().unwrap().target().unwrap();
+
+        assert!(repo.commit_exists(head));
+        repo.create_branch_at(&branch("restored"), head).unwrap();
+        assert_eq!(repo.branch_commit("restored").unwrap(), head);
+    }
+
+    #[test]
+    fn test_rename_branch() {
+        let (_temp, repo) = init_test_repo();
+        let head = repo.inner.head().unwrap().target().unwrap();
+        repo.create_branch(&branch("old-name")).unwrap();
+        repo.create_branch(&branch("taken")).unwrap();
+        repo.checkout("old-name").unwrap();
+
+        assert!(repo.rename_branch("old-name", &branch("taken")).is_err());
+        repo.rename_branch("old-name", &branch("new-name")).unwrap();
+        assert!(!repo.branch_exists("old-name"));
+        assert_eq!(repo.branch_commit("new-name").unwrap(), head);
+        assert_eq!(repo.current_branch().unwrap(), "new-name");
+    }
+
+    #[test]
+    fn test_upstream() {
+        let (_temp, repo) = init_test_repo();
+        repo.create_branch(&branch("me/feature")).unwrap();
+
+        assert_eq!(repo.upstream_of("me/feature"), None);
+        repo.set_upstream(&branch("me/feature"), &branch("feature"))
+            .unwrap();
+        assert_eq!(
+            repo.upstream_of("me/feature"),
+            Some(("origin".into(), "feature".into()))
+        );
+    }
+
+    #[test]
+    fn test_reword_commit() {
+        let (temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let branch = repo.current_branch().unwrap();
+        let base = repo.branch_commit(&branch).unwrap();
+
+        let commit_file = |name: &str, message: &str| {
+            fs::write(temp.path().join(name), name).unwrap();
+            let mut index = repo.inner.index().unwrap();
+            index.add_path(std::path::Path::new(name)).unwrap();
+            index.write().unwrap();
+            let options = CommitOptions {
+                verify: true,
+                ..CommitOptions::default()
+            };
+            repo.create_commit(message, &options).unwrap()
+        };
+        let first = commit_file("a.txt", "First");
+        commit_file("b.txt", "Second");
+
+        let resolved = repo.resolve_commit(&first.to_string()[..7]).unwrap();
+        assert_eq!(resolved, first);
+
+        let new_tip = repo.reword_commit(&branch, first, "Reworded").unwrap();
+        assert_eq!(repo.branch_commit(&branch).unwrap(), new_tip);
+        assert_eq!(repo.branch_commit_message(&branch).unwrap(), "Second");
+
+        let commits = repo.commits_between(base, new_tip).unwrap();
+        assert_eq!(commits.len(), 2);
+        let reworded = repo.find_commit(commits[1]).unwrap();
+        assert_eq!(reworded.message(), Some("Reworded"));
+        assert!(repo.is_clean().unwrap());
+
+        // Commits not on the branch are rejected
+        assert!(repo.reword_commit(&branch, Oid::zero(), "x").is_err());
+    }
+
+    #[test]
+    fn test_reword_commit_keeps_merge_parents() {
+        let (temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let branch = repo.current_branch().unwrap();
+        let base = repo.branch_commit(&branch).unwrap();
+        let options = CommitOptions::default();
+
+        stage_file(&temp, &repo, "a.txt");
+        let first = repo.create_commit("First", &options).unwrap();
+        // A side commit off the base, merged in above `first`
+        let side = {
+            let sig = repo.signature().unwrap();
+            let parent = repo.inner.find_commit(base).unwrap();
+            let tree = parent.tree().unwrap();
+            repo.inner
+                .commit(None, &sig, &sig, "Side", &tree, &[&parent])
+                .unwrap()
+        };
+        let merge = {
+            let sig = repo.signature().unwrap();
+            let head = repo.inner.find_commit(first).unwrap();
+            let side = repo.inner.find_commit(side).unwrap();
+            let tree = head.tree().unwrap();
+            repo.inner
+                .commit(
+                    Some("HEAD"),
+                    &sig,
+                    &sig,
+                    "Merge side",
+                    &tree,
+                    &[&head, &side],
+                )
+                .unwrap()
+        };
+        stage_file(&temp, &repo, "b.txt");
+        repo.create_commit("After merge", &options).unwrap();
+
+        let new_tip = repo.reword_commit(&branch, first, "Reworded").unwrap();
+        let tip = repo.inner.find_commit(new_tip).unwrap();
+        let new_merge = tip.parent(0).unwrap();
+        assert_ne!(new_merge.id(), merge);
+        assert_eq!(new_merge.message(), Some("Merge side"));
+        let parents: Vec<Oid> = new_merge.parent_ids().collect();
+        assert_eq!(parents.len(), 2);
+        assert_eq!(parents[1], side);
+        let reworded = repo.inner.find_commit(parents[0]).unwrap();
+        assert_eq!(reworded.message(), Some("Reworded"));
+    }
+
+    /// Stage `name` with its own name as content.
+    fn stage_file(temp: &TempDir, repo: &Repository, name: &str) {
+        fs::write(temp.path().join(name), name).unwrap();
+        let mut index = repo.inner.index().unwrap();
+        index.add_path(std::path::Path::new(name)).unwrap();
+        index.write().unwrap();
+    }
+
+    #[test]
+    fn test_create_commit_empty() {
+        let (temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let head = repo.resolve_commit("HEAD").unwrap();
+
+        let options = CommitOptions::default();
+        assert!(matches!(
+            repo.create_commit("Nothing", &options),
+            Err(Error::NothingToCommit)
+        ));
+        assert_eq!(repo.resolve_commit("HEAD").unwrap(), head);
+
+        let empty = CommitOptions {
+            allow_empty: true,
+            ..CommitOptions::default()
+        };
+        let placeholder = repo.create_commit("Placeholder", &empty).unwrap();
+        let commit = repo.find_commit(placeholder).unwrap();
+        assert_eq!(commit.parent_id(0).unwrap(), head);
+        assert_eq!(commit.tree_id(), repo.find_commit(head).unwrap().tree_id());
+
+        stage_file(&temp, &repo, "a.txt");
+        assert!(repo.create_commit("Add a", &options).is_ok());
+    }
+
+    #[test]
+    fn test_create_commit_author() {
+        let (temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+
+        stage_file(&temp, &repo, "a.txt");
+        let options = CommitOptions {
+            author: Some("Release Bot <bot@example.com>".into()),
+            ..CommitOptions::default()
+        };
+        let oid = repo.create_commit("Release", &options).unwrap();
+        let commit = repo.find_commit(oid).unwrap();
+        assert_eq!(commit.author().name(), Some("Release Bot"));
+        assert_eq!(commit.author().email(), Some("bot@example.com"));
+        assert_eq!(commit.committer().name(), Some("Test"));
+
+        for invalid in ["Release Bot", "<bot@example.com>", "Bot <>"] {
+            let options = CommitOptions {
+                author: Some(invalid.into()),
+                allow_empty: true,
+                ..CommitOptions::default()
+            };
+            assert!(
+                matches!(
+                    repo.create_commit("x", &options),
+                    Err(Error::InvalidAuthor(_))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_create_commit_amend() {
+        let (temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let base = repo.resolve_commit("HEAD").unwrap();
+        stage_file(&temp, &repo, "a.txt");
+        let options = CommitOptions {
+            author: Some("Ana <ana@example.com>".into()),
+            ..CommitOptions::default()
+        };
+        let first = repo.create_commit("Add a", &options).unwrap();
+
+        stage_file(&temp, &repo, "b.txt");
+        let amend = CommitOptions {
+            amend: true,
+            ..CommitOptions::default()
+        };
+        let amended = repo.create_commit("Add a and b", &amend).unwrap();
+        assert_ne!(amended, first);
+        assert_eq!(repo.resolve_commit("HEAD").unwrap(), amended);
+
+        let commit = repo.find_commit(amended).unwrap();
+        assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [base]);
+        assert_eq!(commit.message(), Some("Add a and b"));
+        // The original author is kept
+        assert_eq!(commit.author().name(), Some("Ana"));
+        let tree = commit.tree().unwrap();
+        assert!(tree.get_name("a.txt").is_some() && tree.get_name("b.txt").is_some());
+
+        // Rewording alone still changes something relative to the parent
+        assert!(repo.create_commit("Reworded", &amend).is_ok());
+    }
+
+    #[test]
+    fn test_create_commit_signoff_and_hooks() {
+        let (temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let hook = temp.path().join(".git/hooks/pre-commit");
+            fs::create_dir_all(hook.parent().unwrap()).unwrap();
+            fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+            fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        stage_file(&temp, &repo, "a.txt");
+        let options = CommitOptions {
+            verify: true,
+            signoff: true,
+            ..CommitOptions::default()
+        };
+        #[cfg(unix)]
+        assert!(matches!(
+            repo.create_commit("Add a", &options),
+            Err(Error::HookFailed(_))
+        ));
+
+        let options = CommitOptions {
+            verify: false,
+            ..options
+        };
+        let oid = repo.create_commit("Add a", &options).unwrap();
+        assert_eq!(
+            repo.find_commit(oid).unwrap().message(),
+            Some("Add a\n\nSigned-off-by: Test <test@example.com>")
+        );
+    }
+
+    #[test]
+    fn test_reflog_messages() {
+        let (temp, repo) = init_test_repo();
+        let mut config = repo.inner.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let base = repo.resolve_commit("HEAD").unwrap();
+        let short = &base.to_string()[..8];
+
+        repo.create_branch(&branch("feature")).unwrap();
+        repo.checkout("feature").unwrap();
+        stage_file(&temp, &repo, "a.txt");
+        repo.create_commit("Add a\n\nDetails", &CommitOptions::default())
+            .unwrap();
+        repo.reset_branch("feature", base).unwrap();
+
+        let messages: Vec<String> = repo
+            .reflog("feature")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                format!("rung: reset feature to {short}"),
+                "rung: commit Add a".to_string(),
+                format!("rung: create feature at {short}"),
+            ]
+        );
+        assert!(repo.reflog("feature").unwrap()[2].old.is_zero());
+    }
+
+    #[test]
+    fn test_read_message_file() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("MSG");
+        fs::write(&path, "\n\nSubject  \n\n\n\nBody line\n\n").unwrap();
+        assert_eq!(
+            Repository::read_message_file(&path).unwrap(),
+            "Subject\n\nBody line\n"
+        );
+        assert!(matches!(
+            Repository::read_message_file(&temp.path().join("missing")),
+            Err(Error::MessageFile(_))
+        ));
+    }
+
+    #[test]
+    fn test_commit_infos_between() {
+        let (_temp, repo) = init_test_repo();
+        let base = repo.branch_commit(&repo.current_branch().unwrap()).unwrap();
+        let sig = git2::Signature::now("Ana", "ana@example.com").unwrap();
+        let commit = |message: &str| {
+            let parent = repo.inner.head().unwrap().peel_to_commit().unwrap();
+            let tree = parent.tree().unwrap();
+            repo.inner
+                .commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+                .unwrap()
+        };
+        commit("First");
+        let tip = commit(
+            "Second change\n\n\nWhy it matters.\n\nCo-authored-by: Bo <bo@example.com>\nRefs: #12\n",
+        );
+
+        let infos = repo.commit_infos_between(base, tip).unwrap();
+        assert_eq!(infos.len(), 2);
+        let second = &infos[0];
+        assert_eq!(second.id, tip);
+        assert_eq!(second.summary, "Second change");
+        assert_eq!(
+            second.body,
+            "Why it matters.\n\nCo-authored-by: Bo <bo@example.com>\nRefs: #12"
+        );
+        assert_eq!(second.author(), "Ana <ana@example.com>");
+        assert_eq!(
+            second.trailers,
+            vec![
+                (
+                    "Co-authored-by".to_string(),
+                    "Bo <bo@example.com>".to_string()
+                ),
+                ("Refs".to_string(), "#12".to_string()),
+            ]
+        );
+        assert_eq!(infos[1].summary, "First");
+        assert!(infos[1].body.is_empty());
+        assert!(infos[1].trailers.is_empty());
+    }
+
+    #[test]
+    fn test_commit_patch() {
+        let (temp, repo) = init_test_repo();
+        fs::write(temp.path().join("notes.txt"), "one\ntwo\n").unwrap();
+        let mut index = repo.inner.index().unwrap();
+        index.add_path(Path::new("notes.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.inner.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.inner.head().unwrap().peel_to_commit().unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let oid = repo
+            .inner
+            .commit(Some("HEAD"), &sig, &sig, "Add notes", &tree, &[&parent])
+            .unwrap();
+
+        let patch = repo.commit_patch(oid).unwrap();
+        assert!(patch.starts_with("diff --git a/notes.txt b/notes.txt\n"));
+        assert!(patch.contains("@@ -0,0 +1,2 @@"));
+        assert!(patch.ends_with("+one\n+two\n"));
+        assert!(repo.commit_patch(parent.id()).unwrap().is_empty());
+
+        repo.inner
+            .config()
+            .unwrap()
+            .set_str("core.pager", "less -S")
+            .unwrap();
+        assert_eq!(repo.config_string("core.pager").as_deref(), Some("less -S"));
+        assert!(repo.config_string("core.nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let (temp, repo) = init_test_repo();
+        let main = repo.current_branch().unwrap();
+        repo.create_branch(&branch("feature")).unwrap();
+        repo.checkout("feature").unwrap();
+
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        fs::create_dir_all(temp.path().join("pkg/api")).unwrap();
+        fs::write(temp.path().join("pkg/api/lib.rs"), "api").unwrap();
+        fs::write(temp.path().join("README.md"), "readme").unwrap();
+        let mut index = repo.inner.index().unwrap();
+        index
+            .add_path(std::path::Path::new("pkg/api/lib.rs"))
+            .unwrap();
+        index.add_path(std::path::Path::new("README.md")).unwrap();
+        index.write().unwrap();
+        let tree = repo.inner.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.inner.head().unwrap().peel_to_commit().unwrap();
+        repo.inner
+            .commit(Some("HEAD"), &sig, &sig, "Add files", &tree, &[&parent])
+            .unwrap();
+
+        assert_eq!(
+            repo.changed_files("feature", &main).unwrap(),
+            vec!["README.md", "pkg/api/lib.rs"]
+        );
+        assert!(repo.changed_files(&main, "feature").unwrap().is_empty());
+        assert_eq!(repo.changed_lines("feature", &main).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_reference_operations() {
+        let (_temp, repo) = init_test_repo();
+        let head = repo.inner.head().unwrap().target().unwrap();
+
+        repo.set_reference("refs/rung/test/a", head, "test")
+            .unwrap();
+        repo.set_reference("refs/rung/test/b/c", head, "test")
+            .unwrap();
+
+        let refs = repo.references_with_prefix("refs/rung/test/").unwrap();
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].0, "refs/rung/test/a");
+        assert_eq!(refs[1].0, "refs/rung/test/b/c");
+
+        repo.delete_reference("refs/rung/test/a").unwrap();
+        assert_eq!(
+            repo.references_with_prefix("refs/rung/test/")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(repo.delete_reference("refs/rung/test/a").is_err());
+    }
+
     #[test]
     fn test_is_clean() {
         let (temp, repo) = init_test_repo();
@@ -739,12 +2973,27 @@ mod tests {
         assert!(!repo.is_clean().unwrap());
     }
 
+    #[test]
+    fn test_large_staged_files() {
+        let (temp, repo) = init_test_repo();
+
+        fs::write(temp.path().join("small.txt"), "tiny").unwrap();
+        fs::write(temp.path().join("big.bin"), vec![0u8; 2048]).unwrap();
+        repo.stage_all().unwrap();
+
+        assert_eq!(
+            repo.large_staged_files(1024).unwrap(),
+            vec![("big.bin".to_string(), 2048)]
+        );
+        assert!(repo.large_staged_files(4096).unwrap().is_empty());
+    }
+
     #[test]
     fn test_list_branches() {
         let (_temp, repo) = init_test_repo();
 
-        repo.create_branch("feature/a").unwrap();
-        repo.create_branch("feature/b").unwrap();
+        repo.create_branch(&branch("feature/a")).unwrap();
+        repo.create_branch(&branch("feature/b")).unwrap();
 
         let branches = repo.list_branches().unwrap();
         assert!(branches.len() >= 3); // main/master + 2 features
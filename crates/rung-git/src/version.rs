@@ -0,0 +1,111 @@
+//! Version of the git binary that rung shells out to.
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+
+/// A git version, as reported by `git --version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GitVersion {
+    /// Major version.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl GitVersion {
+    /// Oldest git that supports every flag rung passes by default
+    /// (`push --force-with-lease`, `rebase --onto`, `--rebase-merges`).
+    pub const MINIMUM: Self = Self::new(2, 18, 0);
+
+    /// First git whose default rebase backend accepts
+    /// `--committer-date-is-author-date`, used by `[sync]` of the same name.
+    pub const COMMITTER_DATE_IS_AUTHOR_DATE: Self = Self::new(2, 29, 0);
+
+    /// Create a version from its parts.
+    #[must_use]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse `git --version` output, e.g. `git version 2.39.3 (Apple Git-146)`
+    /// or `git version 2.41.0.windows.1`.
+    #[must_use]
+    pub fn parse(output: &str) -> Option<Self> {
+        let version = output.trim().strip_prefix("git version ")?;
+        let mut parts = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(str::parse::<u32>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = parts.next().and_then(std::result::Result::ok).unwrap_or(0);
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// Version of the `git` binary on `PATH`.
+    ///
+    /// # Errors
+    /// Returns `GitUnavailable` if git can't be run or its output isn't recognised.
+    pub fn installed() -> Result<Self> {
+        let output = std::process::Command::new("git")
+            .arg("--version")
+            .output()
+            .map_err(|e| Error::GitUnavailable(e.to_string()))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Self::parse(&stdout).ok_or_else(|| {
+            Error::GitUnavailable(format!("unrecognised version '{}'", stdout.trim()))
+        })
+    }
+}
+
+impl fmt::Display for GitVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            GitVersion::parse("git version 2.43.0\n"),
+            Some(GitVersion::new(2, 43, 0))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.39.3 (Apple Git-146)"),
+            Some(GitVersion::new(2, 39, 3))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.41.0.windows.1"),
+            Some(GitVersion::new(2, 41, 0))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.45"),
+            Some(GitVersion::new(2, 45, 0))
+        );
+        assert_eq!(GitVersion::parse("hub version 2.14.2"), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(GitVersion::new(2, 17, 9) < GitVersion::MINIMUM);
+        assert!(GitVersion::new(2, 28, 0) < GitVersion::COMMITTER_DATE_IS_AUTHOR_DATE);
+        assert!(GitVersion::new(3, 0, 0) > GitVersion::COMMITTER_DATE_IS_AUTHOR_DATE);
+        assert_eq!(GitVersion::new(2, 18, 0).to_string(), "2.18.0");
+    }
+
+    #[test]
+    fn test_installed() {
+        assert!(GitVersion::installed().unwrap() >= GitVersion::new(1, 0, 0));
+    }
+}
@@ -3,7 +3,8 @@
 //! Tokens are stored using `SecretString` from the `secrecy` crate, which
 //! automatically zeroizes memory when dropped and prevents accidental logging.
 
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 #[cfg(test)]
 use secrecy::ExposeSecret;
@@ -14,9 +15,14 @@ use crate::error::{Error, Result};
 /// Authentication method for GitHub API.
 #[derive(Debug, Clone)]
 pub enum Auth {
-    /// Use token from gh CLI.
+    /// Use token from gh CLI, falling back to git's credential helper for
+    /// github.com.
     GhCli,
 
+    /// Ask git's configured credential helper for the password stored for
+    /// this https host (`git credential fill`).
+    GitCredential(String),
+
     /// Use token from environment variable.
     EnvVar(String),
 
@@ -27,7 +33,7 @@ pub enum Auth {
 impl Auth {
     /// Create auth from the first available method.
     ///
-    /// Tries in order: `GITHUB_TOKEN` env var, gh CLI.
+    /// Tries in order: `GITHUB_TOKEN` env var, gh CLI, git's credential helper.
     #[must_use]
     pub fn auto() -> Self {
         if std::env::var("GITHUB_TOKEN").is_ok() {
@@ -45,7 +51,12 @@ impl Auth {
     /// Returns error if token cannot be obtained.
     pub fn resolve(&self) -> Result<SecretString> {
         match self {
-            Self::GhCli => get_gh_token(),
+            Self::GhCli => get_gh_token().or_else(|e| {
+                Self::GitCredential(GITHUB_HOST.into())
+                    .resolve()
+                    .map_err(|_| e)
+            }),
+            Self::GitCredential(host) => get_git_credential(host),
             Self::EnvVar(var) => std::env::var(var)
                 .map(SecretString::from)
                 .map_err(|_| Error::NoToken),
@@ -77,6 +88,43 @@ fn get_gh_token() -> Result<SecretString> {
     Ok(SecretString::from(token))
 }
 
+/// Host queried in git's credential helper when gh has no token.
+const GITHUB_HOST: &str = "github.com";
+
+/// Get the password stored for `host` from git's credential helper.
+///
+/// Prompts are disabled, so a host with no stored credential fails instead
+/// of asking for a username and password.
+fn get_git_credential(host: &str) -> Result<SecretString> {
+    let mut child = Command::new("git")
+        .args(["credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_ASKPASS", "")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        write!(stdin, "protocol=https\nhost={host}\n\n")?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(Error::NoToken);
+    }
+    parse_credential_password(&String::from_utf8_lossy(&output.stdout)).ok_or(Error::NoToken)
+}
+
+/// Extract the password from `git credential fill` output.
+fn parse_credential_password(output: &str) -> Option<SecretString> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("password="))
+        .map(str::trim)
+        .filter(|password| !password.is_empty())
+        .map(SecretString::from)
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -93,4 +141,25 @@ mod tests {
         let auth = Auth::Token(SecretString::from("test_token"));
         assert_eq!(auth.resolve().unwrap().expose_secret(), "test_token");
     }
+
+    #[test]
+    fn test_git_credential_never_prompts() {
+        // No helper knows this host; without prompts this fails rather than hangs
+        assert!(
+            Auth::GitCredential("rung-test.invalid".into())
+                .resolve()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_credential_password() {
+        let output = "protocol=https\nhost=github.com\nusername=octocat\npassword=gho_abc123\n";
+        assert_eq!(
+            parse_credential_password(output).unwrap().expose_secret(),
+            "gho_abc123"
+        );
+        assert!(parse_credential_password("protocol=https\nhost=github.com\n").is_none());
+        assert!(parse_credential_password("password=\n").is_none());
+    }
 }
@@ -1,17 +1,102 @@
 //! GitHub API client.
 
-use reqwest::Client;
-use reqwest::header::{ACCEPT, AUTHORIZATION, HeaderMap, HeaderValue, USER_AGENT};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use reqwest::header::{
+    ACCEPT, AUTHORIZATION, ETAG, HeaderMap, HeaderValue, IF_NONE_MATCH, USER_AGENT,
+};
+use reqwest::{Client, StatusCode};
 use secrecy::{ExposeSecret, SecretString};
 use serde::de::DeserializeOwned;
 
 use crate::auth::Auth;
 use crate::error::{Error, Result};
 use crate::types::{
-    CheckRun, CreatePullRequest, MergePullRequest, MergeResult, PullRequest, PullRequestState,
+    ApiTime, CheckRun, CheckStatus, CreatePullRequest, HttpStatus, MergePullRequest, MergeResult,
+    MergeSettings, Milestone, PullRequest, PullRequestState, RateLimit, ReviewRequests,
     UpdatePullRequest,
 };
 
+/// How many PRs [`GitHubClient::list_recently_closed_prs`] returns at most.
+pub const RECENTLY_CLOSED_LIMIT: usize = 100;
+
+/// Idle connections kept open per host, enough for the concurrent comment
+/// updates in `rung submit`.
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+
+/// How long an idle pooled connection is kept before it's closed.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Interval between keep-alive probes on open connections.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Waits before retrying a PR whose head branch GitHub doesn't know yet, as
+/// right after the branch was pushed.
+const UNINDEXED_HEAD_RETRIES: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(4),
+];
+
+/// The HTTP client every [`GitHubClient`] in the process shares, so its
+/// connection pool outlives any one client and each host pays for the TCP
+/// and TLS handshakes once.
+static HTTP: OnceLock<Client> = OnceLock::new();
+
+/// Requests answered so far, across every client.
+static API_REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// Microseconds spent waiting for those responses.
+static API_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// Requests made by every [`GitHubClient`] in the process so far, and the
+/// time spent waiting for their responses.
+///
+/// Concurrent requests each count their own wait, so the total can exceed
+/// the wall-clock time.
+#[must_use]
+pub fn api_time() -> ApiTime {
+    ApiTime {
+        requests: API_REQUESTS.load(Ordering::Relaxed),
+        waited: Duration::from_micros(API_MICROS.load(Ordering::Relaxed)),
+    }
+}
+
+/// The shared HTTP client, built on first use.
+///
+/// Connections negotiate HTTP/2 where the server offers it, letting
+/// concurrent requests share one connection.
+fn http_client() -> Result<Client> {
+    if let Some(client) = HTTP.get() {
+        return Ok(client.clone());
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        ACCEPT,
+        HeaderValue::from_static("application/vnd.github+json"),
+    );
+    headers.insert(USER_AGENT, HeaderValue::from_static("rung-cli"));
+    headers.insert(
+        "X-GitHub-Api-Version",
+        HeaderValue::from_static("2022-11-28"),
+    );
+
+    let client = Client::builder()
+        .default_headers(headers)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_keepalive(KEEP_ALIVE_INTERVAL)
+        .http2_adaptive_window(true)
+        .http2_keep_alive_interval(KEEP_ALIVE_INTERVAL)
+        .http2_keep_alive_while_idle(true)
+        .build()?;
+    Ok(HTTP.get_or_init(|| client).clone())
+}
+
 // === Internal API response types (shared across methods) ===
 
 /// Internal representation of a PR from the GitHub API.
@@ -24,6 +109,12 @@ struct ApiPullRequest {
     /// Whether the PR was merged (GitHub returns state="closed" + merged=true for merged PRs).
     #[serde(default)]
     merged: bool,
+    /// When the PR was merged. List endpoints report this instead of `merged`.
+    #[serde(default)]
+    merged_at: Option<String>,
+    /// When the PR last changed.
+    #[serde(default)]
+    updated_at: Option<String>,
     draft: bool,
     html_url: String,
     head: ApiBranch,
@@ -32,6 +123,18 @@ struct ApiPullRequest {
     mergeable: Option<bool>,
     /// The mergeable state (e.g., "clean", "dirty", "blocked", "behind").
     mergeable_state: Option<String>,
+    /// The PR's author.
+    #[serde(default)]
+    user: Option<ApiUser>,
+    /// Commit that landed the PR (for open PRs, GitHub's test merge).
+    #[serde(default)]
+    merge_commit_sha: Option<String>,
+}
+
+/// Internal representation of a user from the GitHub API.
+#[derive(serde::Deserialize)]
+struct ApiUser {
+    login: String,
 }
 
 /// Internal representation of a branch ref from the GitHub API.
@@ -39,13 +142,14 @@ struct ApiPullRequest {
 struct ApiBranch {
     #[serde(rename = "ref")]
     ref_name: String,
+    sha: String,
 }
 
 impl ApiPullRequest {
     /// Convert API response to domain type, parsing state string.
     fn into_pull_request(self) -> PullRequest {
         // GitHub API returns state="closed" + merged=true for merged PRs
-        let state = if self.merged {
+        let state = if self.merged || self.merged_at.is_some() {
             PullRequestState::Merged
         } else {
             match self.state.as_str() {
@@ -61,10 +165,16 @@ impl ApiPullRequest {
             state,
             draft: self.draft,
             head_branch: self.head.ref_name,
+            head_sha: self.head.sha,
             base_branch: self.base.ref_name,
             html_url: self.html_url,
             mergeable: self.mergeable,
             mergeable_state: self.mergeable_state,
+            author: self.user.map(|user| user.login),
+            updated_at: self.updated_at,
+            merge_commit_sha: self
+                .merge_commit_sha
+                .filter(|_| state == PullRequestState::Merged),
         }
     }
 
@@ -77,10 +187,16 @@ impl ApiPullRequest {
             state,
             draft: self.draft,
             head_branch: self.head.ref_name,
+            head_sha: self.head.sha,
             base_branch: self.base.ref_name,
             html_url: self.html_url,
             mergeable: self.mergeable,
             mergeable_state: self.mergeable_state,
+            author: self.user.map(|user| user.login),
+            updated_at: self.updated_at,
+            merge_commit_sha: self
+                .merge_commit_sha
+                .filter(|_| state == PullRequestState::Merged),
         }
     }
 }
@@ -89,16 +205,9 @@ impl ApiPullRequest {
 
 /// GraphQL request wrapper.
 #[derive(serde::Serialize)]
-struct GraphQLRequest {
-    query: String,
-    variables: GraphQLVariables,
-}
-
-/// GraphQL variables for PR batch query.
-#[derive(serde::Serialize)]
-struct GraphQLVariables {
-    owner: String,
-    repo: String,
+struct GraphQLRequest<'a> {
+    query: &'a str,
+    variables: serde_json::Value,
 }
 
 /// GraphQL PR response (different field names than REST API).
@@ -106,12 +215,17 @@ struct GraphQLVariables {
 #[serde(rename_all = "camelCase")]
 struct GraphQLPullRequest {
     number: u64,
+    #[serde(default)]
+    title: String,
     state: String,
     merged: bool,
     is_draft: bool,
     head_ref_name: String,
+    head_ref_oid: String,
     base_ref_name: String,
     url: String,
+    #[serde(default)]
+    updated_at: Option<String>,
 }
 
 impl GraphQLPullRequest {
@@ -126,30 +240,29 @@ impl GraphQLPullRequest {
 
         PullRequest {
             number: self.number,
-            title: String::new(), // Not fetched in batch query
+            title: self.title,
             body: None,
             state,
             draft: self.is_draft,
             head_branch: self.head_ref_name,
+            head_sha: self.head_ref_oid,
             base_branch: self.base_ref_name,
             html_url: self.url,
             mergeable: None, // Not fetched in batch query
             mergeable_state: None,
+            author: None,
+            updated_at: self.updated_at,
+            merge_commit_sha: None,
         }
     }
 }
 
 #[derive(serde::Deserialize)]
 struct GraphQLResponse {
-    data: Option<GraphQLData>,
+    data: Option<serde_json::Value>,
     errors: Option<Vec<GraphQLError>>,
 }
 
-#[derive(serde::Deserialize)]
-struct GraphQLData {
-    repository: Option<serde_json::Value>,
-}
-
 #[derive(serde::Deserialize)]
 struct GraphQLError {
     message: String,
@@ -159,8 +272,16 @@ struct GraphQLError {
 pub struct GitHubClient {
     client: Client,
     base_url: String,
-    /// Token stored as `SecretString` for automatic zeroization on drop.
-    token: SecretString,
+    /// Token stored as `SecretString` for automatic zeroization on drop;
+    /// `None` for an anonymous client.
+    token: Option<SecretString>,
+    /// Status of every response so far, oldest first.
+    statuses: Mutex<Vec<HttpStatus>>,
+    /// Rate limit budget from the latest response that reported one.
+    rate_limit: Mutex<Option<RateLimit>>,
+    /// `ETag` and body of earlier GET responses, by URL, for conditional
+    /// requests.
+    etags: Mutex<HashMap<String, (String, Vec<u8>)>>,
 }
 
 impl GitHubClient {
@@ -177,45 +298,164 @@ impl GitHubClient {
 
     /// Create a new GitHub client with a custom API URL (for GitHub Enterprise).
     ///
+    /// Clients share one connection pool, so creating several in a command
+    /// doesn't reconnect.
+    ///
     /// # Errors
     /// Returns error if authentication fails.
     pub fn with_base_url(auth: &Auth, base_url: impl Into<String>) -> Result<Self> {
         let token = auth.resolve()?;
-
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            ACCEPT,
-            HeaderValue::from_static("application/vnd.github+json"),
-        );
-        headers.insert(USER_AGENT, HeaderValue::from_static("rung-cli"));
-        headers.insert(
-            "X-GitHub-Api-Version",
-            HeaderValue::from_static("2022-11-28"),
-        );
-
-        let client = Client::builder().default_headers(headers).build()?;
+        let client = http_client()?;
 
         Ok(Self {
             client,
             base_url: base_url.into(),
-            token,
+            token: Some(token),
+            statuses: Mutex::default(),
+            rate_limit: Mutex::default(),
+            etags: Mutex::default(),
         })
     }
 
+    /// Create a client that sends no token, for reading public repositories
+    /// without one. It can't write, or use GraphQL, and GitHub allows it far
+    /// fewer requests (60 an hour on github.com).
+    ///
+    /// # Errors
+    /// Returns error if the HTTP client can't be built.
+    pub fn anonymous(base_url: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            client: http_client()?,
+            base_url: base_url.into(),
+            token: None,
+            statuses: Mutex::default(),
+            rate_limit: Mutex::default(),
+            etags: Mutex::default(),
+        })
+    }
+
+    /// Whether the client sends no token (see [`Self::anonymous`]).
+    #[must_use]
+    pub const fn is_anonymous(&self) -> bool {
+        self.token.is_none()
+    }
+
+    /// Whether the latest response reported no requests left before the
+    /// rate limit resets.
+    #[must_use]
+    pub fn budget_spent(&self) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        self.rate_limit()
+            .is_some_and(|limit| limit.remaining == 0 && limit.reset > now)
+    }
+
+    /// The `Authorization` header value. Anonymous clients only read, so
+    /// anything else fails without a request.
+    fn bearer(&self) -> Result<String> {
+        let token = self.token.as_ref().ok_or(Error::NoToken)?;
+        Ok(format!("Bearer {}", token.expose_secret()))
+    }
+
+    /// The rate limit budget as of the latest response, if GitHub sent one.
+    #[must_use]
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit
+            .lock()
+            .ok()
+            .and_then(|rate_limit| *rate_limit)
+    }
+
+    /// The status of every response this client has received, oldest first.
+    #[must_use]
+    pub fn http_statuses(&self) -> Vec<HttpStatus> {
+        self.statuses
+            .lock()
+            .map(|statuses| statuses.clone())
+            .unwrap_or_default()
+    }
+
+    /// Note the status of a response to `method` `path` sent at `started`,
+    /// and the rate limit budget it reports.
+    fn record_status(
+        &self,
+        method: &str,
+        path: &str,
+        started: Instant,
+        response: &reqwest::Response,
+    ) {
+        let waited = u64::try_from(started.elapsed().as_micros()).unwrap_or(u64::MAX);
+        API_REQUESTS.fetch_add(1, Ordering::Relaxed);
+        API_MICROS.fetch_add(waited, Ordering::Relaxed);
+        if let Ok(mut statuses) = self.statuses.lock() {
+            statuses.push(HttpStatus {
+                method: method.to_string(),
+                path: path.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+
+        let header = |name: &str| -> Option<u64> {
+            response.headers().get(name)?.to_str().ok()?.parse().ok()
+        };
+        let reported = header("x-ratelimit-limit")
+            .zip(header("x-ratelimit-remaining"))
+            .zip(header("x-ratelimit-reset"))
+            .map(|((limit, remaining), reset)| RateLimit {
+                limit,
+                remaining,
+                reset,
+            });
+        if let (Some(reported), Ok(mut rate_limit)) = (reported, self.rate_limit.lock()) {
+            *rate_limit = Some(reported);
+        }
+    }
+
     /// Make a GET request.
+    ///
+    /// Repeats are conditional on the `ETag` of the last response, so an
+    /// unchanged resource costs nothing against the rate limit.
     async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
         let url = format!("{}{}", self.base_url, path);
-        let response = self
-            .client
-            .get(&url)
-            .header(
-                AUTHORIZATION,
-                format!("Bearer {}", self.token.expose_secret()),
-            )
-            .send()
-            .await?;
+        let cached = self
+            .etags
+            .lock()
+            .ok()
+            .and_then(|etags| etags.get(&url).cloned());
+        let mut request = self.client.get(&url);
+        if self.token.is_some() {
+            request = request.header(AUTHORIZATION, self.bearer()?);
+        } else if self.budget_spent() {
+            // Unauthenticated requests get a small budget: don't waste a
+            // request on a certain 403
+            return Err(Error::RateLimited);
+        }
+        if let Some((etag, _)) = &cached {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let started = Instant::now();
+        let response = request.send().await?;
+        self.record_status("GET", path, started, &response);
 
-        self.handle_response(response).await
+        match cached {
+            Some((_, body)) if response.status() == StatusCode::NOT_MODIFIED => {
+                return Ok(serde_json::from_slice(&body)?);
+            }
+            _ if !response.status().is_success() => return self.handle_response(response).await,
+            _ => {}
+        }
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(String::from);
+        let body = response.bytes().await?.to_vec();
+        let parsed = serde_json::from_slice(&body)?;
+        if let (Some(etag), Ok(mut etags)) = (etag, self.etags.lock()) {
+            etags.insert(url, (etag, body));
+        }
+        Ok(parsed)
     }
 
     /// Make a POST request.
@@ -225,16 +465,15 @@ impl GitHubClient {
         body: &B,
     ) -> Result<T> {
         let url = format!("{}{}", self.base_url, path);
+        let started = Instant::now();
         let response = self
             .client
             .post(&url)
-            .header(
-                AUTHORIZATION,
-                format!("Bearer {}", self.token.expose_secret()),
-            )
+            .header(AUTHORIZATION, self.bearer()?)
             .json(body)
             .send()
             .await?;
+        self.record_status("POST", path, started, &response);
 
         self.handle_response(response).await
     }
@@ -246,16 +485,15 @@ impl GitHubClient {
         body: &B,
     ) -> Result<T> {
         let url = format!("{}{}", self.base_url, path);
+        let started = Instant::now();
         let response = self
             .client
             .patch(&url)
-            .header(
-                AUTHORIZATION,
-                format!("Bearer {}", self.token.expose_secret()),
-            )
+            .header(AUTHORIZATION, self.bearer()?)
             .json(body)
             .send()
             .await?;
+        self.record_status("PATCH", path, started, &response);
 
         self.handle_response(response).await
     }
@@ -267,16 +505,15 @@ impl GitHubClient {
         body: &B,
     ) -> Result<T> {
         let url = format!("{}{}", self.base_url, path);
+        let started = Instant::now();
         let response = self
             .client
             .put(&url)
-            .header(
-                AUTHORIZATION,
-                format!("Bearer {}", self.token.expose_secret()),
-            )
+            .header(AUTHORIZATION, self.bearer()?)
             .json(body)
             .send()
             .await?;
+        self.record_status("PUT", path, started, &response);
 
         self.handle_response(response).await
     }
@@ -284,15 +521,14 @@ impl GitHubClient {
     /// Make a DELETE request.
     async fn delete(&self, path: &str) -> Result<()> {
         let url = format!("{}{}", self.base_url, path);
+        let started = Instant::now();
         let response = self
             .client
             .delete(&url)
-            .header(
-                AUTHORIZATION,
-                format!("Bearer {}", self.token.expose_secret()),
-            )
+            .header(AUTHORIZATION, self.bearer()?)
             .send()
             .await?;
+        self.record_status("DELETE", path, started, &response);
 
         let status = response.status();
         if status.is_success() || status.as_u16() == 204 {
@@ -364,6 +600,50 @@ impl GitHubClient {
         Ok(api_pr.into_pull_request())
     }
 
+    /// Get the reviews an open PR is still waiting for. GitHub drops a
+    /// reviewer from the request once they review, so whoever is left hasn't.
+    /// Closed and merged PRs wait for nobody.
+    ///
+    /// # Errors
+    /// Returns error if PR not found or API call fails.
+    pub async fn get_review_requests(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<ReviewRequests> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            state: String,
+            #[serde(default)]
+            requested_reviewers: Vec<ApiUser>,
+            #[serde(default)]
+            requested_teams: Vec<ApiTeam>,
+            created_at: Option<String>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct ApiTeam {
+            slug: String,
+        }
+
+        let pr: Response = self
+            .get(&format!("/repos/{owner}/{repo}/pulls/{number}"))
+            .await?;
+        if pr.state != "open" {
+            return Ok(ReviewRequests::default());
+        }
+        Ok(ReviewRequests {
+            users: pr
+                .requested_reviewers
+                .into_iter()
+                .map(|u| u.login)
+                .collect(),
+            teams: pr.requested_teams.into_iter().map(|t| t.slug).collect(),
+            opened_at: pr.created_at,
+        })
+    }
+
     /// Get multiple pull requests by number using GraphQL (single API call).
     ///
     /// This is more efficient than calling `get_pr` multiple times when fetching
@@ -385,25 +665,50 @@ impl GitHubClient {
         }
 
         let query = build_graphql_pr_query(numbers);
-        let request = GraphQLRequest {
-            query,
-            variables: GraphQLVariables {
-                owner: owner.to_string(),
-                repo: repo.to_string(),
-            },
-        };
+        let data = self
+            .graphql(&query, serde_json::json!({ "owner": owner, "repo": repo }))
+            .await?;
+
+        let mut result = std::collections::HashMap::new();
+
+        if let Some(repo_data) = data.get("repository") {
+            // Parse each pr0, pr1, pr2... field
+            for (i, &num) in numbers.iter().enumerate() {
+                let key = format!("pr{i}");
+                if let Some(pr_value) = repo_data.get(&key) {
+                    // Skip null values (PR doesn't exist)
+                    if !pr_value.is_null() {
+                        if let Ok(pr) =
+                            serde_json::from_value::<GraphQLPullRequest>(pr_value.clone())
+                        {
+                            result.insert(num, pr.into_pull_request());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Run a GraphQL query, returning its `data`.
+    async fn graphql(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let request = GraphQLRequest { query, variables };
         let url = format!("{}/graphql", self.base_url);
 
+        let started = Instant::now();
         let response = self
             .client
             .post(&url)
-            .header(
-                AUTHORIZATION,
-                format!("Bearer {}", self.token.expose_secret()),
-            )
+            .header(AUTHORIZATION, self.bearer()?)
             .json(&request)
             .send()
             .await?;
+        self.record_status("POST", "/graphql", started, &response);
 
         let status = response.status();
         if !status.is_success() {
@@ -440,28 +745,7 @@ impl GitHubClient {
             }
         }
 
-        let mut result = std::collections::HashMap::new();
-
-        if let Some(data) = graphql_response.data {
-            if let Some(repo_data) = data.repository {
-                // Parse each pr0, pr1, pr2... field
-                for (i, &num) in numbers.iter().enumerate() {
-                    let key = format!("pr{i}");
-                    if let Some(pr_value) = repo_data.get(&key) {
-                        // Skip null values (PR doesn't exist)
-                        if !pr_value.is_null() {
-                            if let Ok(pr) =
-                                serde_json::from_value::<GraphQLPullRequest>(pr_value.clone())
-                            {
-                                result.insert(num, pr.into_pull_request());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(result)
+        Ok(graphql_response.data.unwrap_or_default())
     }
 
     /// Find a PR for a branch.
@@ -487,7 +771,121 @@ impl GitHubClient {
             .map(|api_pr| api_pr.into_pull_request_with_state(PullRequestState::Open)))
     }
 
-    /// Create a pull request.
+    /// Find the most recently updated PR for a branch that was closed
+    /// without being merged.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn find_closed_pr_for_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> Result<Option<PullRequest>> {
+        let prs: Vec<ApiPullRequest> = self
+            .get(&format!(
+                "/repos/{owner}/{repo}/pulls?head={owner}:{branch}&state=closed&sort=updated&direction=desc"
+            ))
+            .await?;
+
+        Ok(prs
+            .into_iter()
+            .map(ApiPullRequest::into_pull_request)
+            .find(|pr| pr.state == PullRequestState::Closed))
+    }
+
+    /// Reopen a closed pull request.
+    ///
+    /// # Errors
+    /// Returns error if API call fails, as it does when the PR's branch was
+    /// deleted or force-pushed since it was closed.
+    pub async fn reopen_pr(&self, owner: &str, repo: &str, number: u64) -> Result<PullRequest> {
+        let api_pr: ApiPullRequest = self
+            .patch(
+                &format!("/repos/{owner}/{repo}/pulls/{number}"),
+                &serde_json::json!({ "state": "open" }),
+            )
+            .await?;
+
+        Ok(api_pr.into_pull_request())
+    }
+
+    /// List every open pull request.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn list_open_prs(&self, owner: &str, repo: &str) -> Result<Vec<PullRequest>> {
+        Ok(self
+            .list_open_api_prs(owner, repo)
+            .await?
+            .into_iter()
+            .map(|pr| pr.into_pull_request_with_state(PullRequestState::Open))
+            .collect())
+    }
+
+    /// List the most recently updated closed pull requests, whatever their
+    /// base, newest first: up to [`RECENTLY_CLOSED_LIMIT`] of them, in one
+    /// request.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn list_recently_closed_prs(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<PullRequest>> {
+        let prs: Vec<ApiPullRequest> = self
+            .get(&format!(
+                "/repos/{owner}/{repo}/pulls?state=closed&sort=updated&direction=desc&per_page={RECENTLY_CLOSED_LIMIT}"
+            ))
+            .await?;
+        Ok(prs
+            .into_iter()
+            .map(ApiPullRequest::into_pull_request)
+            .collect())
+    }
+
+    /// List the open pull requests opened by `author`.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn list_open_prs_by(
+        &self,
+        owner: &str,
+        repo: &str,
+        author: &str,
+    ) -> Result<Vec<PullRequest>> {
+        Ok(self
+            .list_open_api_prs(owner, repo)
+            .await?
+            .into_iter()
+            .filter(|pr| pr.user.as_ref().is_some_and(|user| user.login == author))
+            .map(|pr| pr.into_pull_request_with_state(PullRequestState::Open))
+            .collect())
+    }
+
+    /// All open PRs, a page at a time.
+    async fn list_open_api_prs(&self, owner: &str, repo: &str) -> Result<Vec<ApiPullRequest>> {
+        const PER_PAGE: usize = 100;
+        let mut prs = vec![];
+        for page in 1.. {
+            let batch: Vec<ApiPullRequest> = self
+                .get(&format!(
+                    "/repos/{owner}/{repo}/pulls?state=open&per_page={PER_PAGE}&page={page}"
+                ))
+                .await?;
+            let last = batch.len() < PER_PAGE;
+            prs.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(prs)
+    }
+
+    /// Create a pull request. A head branch pushed moments before may not
+    /// be indexed yet, so GitHub rejecting it is retried a few times over
+    /// several seconds.
     ///
     /// # Errors
     /// Returns error if PR creation fails.
@@ -497,12 +895,34 @@ impl GitHubClient {
         repo: &str,
         pr: CreatePullRequest,
     ) -> Result<PullRequest> {
-        // Newly created PRs are always open
-        let api_pr: ApiPullRequest = self
-            .post(&format!("/repos/{owner}/{repo}/pulls"), &pr)
-            .await?;
+        self.create_pr_retrying(owner, repo, &pr, &UNINDEXED_HEAD_RETRIES)
+            .await
+    }
 
-        Ok(api_pr.into_pull_request_with_state(PullRequestState::Open))
+    /// Create a pull request, waiting out each of `retries` in turn while
+    /// GitHub doesn't know its head branch.
+    async fn create_pr_retrying(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr: &CreatePullRequest,
+        retries: &[Duration],
+    ) -> Result<PullRequest> {
+        let path = format!("/repos/{owner}/{repo}/pulls");
+        let mut retries = retries.iter();
+        loop {
+            match self.post::<ApiPullRequest, _>(&path, pr).await {
+                // Newly created PRs are always open
+                Ok(api_pr) => {
+                    return Ok(api_pr.into_pull_request_with_state(PullRequestState::Open));
+                }
+                Err(e) if e.is_unindexed_head() => match retries.next() {
+                    Some(wait) => tokio::time::sleep(*wait).await,
+                    None => return Err(e),
+                },
+                Err(e) => return Err(e),
+            }
+        }
     }
 
     /// Update a pull request.
@@ -523,9 +943,37 @@ impl GitHubClient {
         Ok(api_pr.into_pull_request())
     }
 
+    /// Mark a draft pull request ready for review. REST can't do this, so it
+    /// goes through GraphQL.
+    ///
+    /// # Errors
+    /// Returns error if the PR isn't found or a request fails.
+    pub async fn mark_ready_for_review(&self, owner: &str, repo: &str, number: u64) -> Result<()> {
+        const ID: &str = r"query($owner: String!, $repo: String!, $pr: Int!) { repository(owner: $owner, name: $repo) { pullRequest(number: $pr) { id } } }";
+        const READY: &str = r"mutation($pr: ID!) { markPullRequestReadyForReview(input: { pullRequestId: $pr }) { pullRequest { isDraft } } }";
+
+        let data = self
+            .graphql(
+                ID,
+                serde_json::json!({ "owner": owner, "repo": repo, "pr": number }),
+            )
+            .await?;
+        let Some(id) = data["repository"]["pullRequest"]["id"].as_str() else {
+            return Err(Error::ApiError {
+                status: 404,
+                message: format!("PR #{number} not found"),
+            });
+        };
+
+        self.graphql(READY, serde_json::json!({ "pr": id })).await?;
+        Ok(())
+    }
+
     // === Check Runs ===
 
-    /// Get check runs for a commit.
+    /// Get check runs for a commit, along with any statuses reported through
+    /// the legacy commit status API by CI systems that don't use checks.
+    /// A status named like a check run is left out.
     ///
     /// # Errors
     /// Returns error if API call fails.
@@ -546,6 +994,20 @@ impl GitHubClient {
             status: String,
             conclusion: Option<String>,
             details_url: Option<String>,
+            started_at: Option<String>,
+            completed_at: Option<String>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CombinedStatus {
+            statuses: Vec<CommitStatus>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CommitStatus {
+            context: String,
+            state: String,
+            target_url: Option<String>,
         }
 
         let response: Response = self
@@ -553,8 +1015,13 @@ impl GitHubClient {
                 "/repos/{owner}/{repo}/commits/{commit_sha}/check-runs"
             ))
             .await?;
+        let combined: CombinedStatus = self
+            .get(&format!(
+                "/repos/{owner}/{repo}/commits/{commit_sha}/status"
+            ))
+            .await?;
 
-        Ok(response
+        let mut runs: Vec<CheckRun> = response
             .check_runs
             .into_iter()
             .map(|cr| CheckRun {
@@ -569,8 +1036,60 @@ impl GitHubClient {
                     _ => crate::types::CheckStatus::Failure,
                 },
                 details_url: cr.details_url,
+                started_at: cr.started_at,
+                completed_at: cr.completed_at,
             })
-            .collect())
+            .collect();
+        for status in combined.statuses {
+            if !runs.iter().any(|run| run.name == status.context) {
+                runs.push(CheckRun {
+                    status: CheckStatus::from_commit_status(&status.state),
+                    name: status.context,
+                    details_url: status.target_url,
+                    started_at: None,
+                    completed_at: None,
+                });
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Names of the status checks branch protection requires before a PR
+    /// into `branch` can merge. Empty when the branch isn't protected or
+    /// requires no checks.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn get_required_checks(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> Result<Vec<String>> {
+        #[derive(serde::Deserialize)]
+        struct Branch {
+            protection: Option<Protection>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Protection {
+            required_status_checks: Option<RequiredChecks>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct RequiredChecks {
+            #[serde(default)]
+            contexts: Vec<String>,
+        }
+
+        let branch: Branch = self
+            .get(&format!("/repos/{owner}/{repo}/branches/{branch}"))
+            .await?;
+        Ok(branch
+            .protection
+            .and_then(|protection| protection.required_status_checks)
+            .map(|checks| checks.contexts)
+            .unwrap_or_default())
     }
 
     // === Merge Operations ===
@@ -604,6 +1123,51 @@ impl GitHubClient {
             .await
     }
 
+    /// Check whether a branch exists in the repository.
+    ///
+    /// # Errors
+    /// Returns error if API call fails for a reason other than a missing branch.
+    pub async fn branch_exists(&self, owner: &str, repo: &str, branch: &str) -> Result<bool> {
+        match self
+            .get::<serde_json::Value>(&format!("/repos/{owner}/{repo}/branches/{branch}"))
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(Error::ApiError { status: 404, .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Login of the authenticated user.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn current_user(&self) -> Result<String> {
+        let user: ApiUser = self.get("/user").await?;
+        Ok(user.login)
+    }
+
+    /// Verified email addresses of the authenticated user.
+    ///
+    /// Needs a token allowed to read them (the `user:email` scope).
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn current_user_emails(&self) -> Result<Vec<String>> {
+        #[derive(serde::Deserialize)]
+        struct ApiEmail {
+            email: String,
+            verified: bool,
+        }
+
+        let emails: Vec<ApiEmail> = self.get("/user/emails").await?;
+        Ok(emails
+            .into_iter()
+            .filter(|e| e.verified)
+            .map(|e| e.email)
+            .collect())
+    }
+
     // === Repository Operations ===
 
     /// Get the repository's default branch name.
@@ -620,6 +1184,28 @@ impl GitHubClient {
         Ok(info.default_branch)
     }
 
+    /// Get the merge methods enabled for the repository.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn get_merge_settings(&self, owner: &str, repo: &str) -> Result<MergeSettings> {
+        self.get(&format!("/repos/{owner}/{repo}")).await
+    }
+
+    /// Have GitHub delete head branches once their PR is merged.
+    ///
+    /// # Errors
+    /// Returns error if API call fails (changing settings needs admin access).
+    pub async fn enable_delete_branch_on_merge(&self, owner: &str, repo: &str) -> Result<()> {
+        let _: serde_json::Value = self
+            .patch(
+                &format!("/repos/{owner}/{repo}"),
+                &serde_json::json!({ "delete_branch_on_merge": true }),
+            )
+            .await?;
+        Ok(())
+    }
+
     // === Comment Operations ===
 
     /// List comments on a pull request.
@@ -673,6 +1259,227 @@ impl GitHubClient {
         )
         .await
     }
+
+    // === Milestone and Project Operations ===
+
+    /// Find an open milestone by title, looking through every page of them.
+    ///
+    /// # Errors
+    /// Returns error if API call fails.
+    pub async fn find_milestone(
+        &self,
+        owner: &str,
+        repo: &str,
+        title: &str,
+    ) -> Result<Option<Milestone>> {
+        const PER_PAGE: usize = 100;
+        for page in 1.. {
+            let batch: Vec<Milestone> = self
+                .get(&format!(
+                    "/repos/{owner}/{repo}/milestones?state=open&per_page={PER_PAGE}&page={page}"
+                ))
+                .await?;
+            let last = batch.len() < PER_PAGE;
+            if let Some(milestone) = batch.into_iter().find(|m| m.title == title) {
+                return Ok(Some(milestone));
+            }
+            if last {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Put a pull request in a milestone.
+    ///
+    /// # Errors
+    /// Returns error if request fails.
+    pub async fn set_milestone(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        milestone: u64,
+    ) -> Result<()> {
+        let _: serde_json::Value = self
+            .patch(
+                &format!("/repos/{owner}/{repo}/issues/{pr_number}"),
+                &serde_json::json!({ "milestone": milestone }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Add a pull request to a project (Projects v2) owned by the repository's owner.
+    ///
+    /// # Errors
+    /// Returns error if the project isn't found or a request fails.
+    pub async fn add_to_project(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        project: u64,
+    ) -> Result<()> {
+        const IDS: &str = r"query($owner: String!, $repo: String!, $pr: Int!, $project: Int!) { repository(owner: $owner, name: $repo) { pullRequest(number: $pr) { id } owner { ... on ProjectV2Owner { projectV2(number: $project) { id } } } } }";
+        const ADD: &str = r"mutation($project: ID!, $content: ID!) { addProjectV2ItemById(input: { projectId: $project, contentId: $content }) { item { id } } }";
+
+        let data = self
+            .graphql(
+                IDS,
+                serde_json::json!({
+                    "owner": owner,
+                    "repo": repo,
+                    "pr": pr_number,
+                    "project": project,
+                }),
+            )
+            .await?;
+        let repository = &data["repository"];
+        let (Some(content), Some(project_id)) = (
+            repository["pullRequest"]["id"].as_str(),
+            repository["owner"]["projectV2"]["id"].as_str(),
+        ) else {
+            return Err(Error::ApiError {
+                status: 404,
+                message: format!("project #{project} not found for {owner}"),
+            });
+        };
+
+        self.graphql(
+            ADD,
+            serde_json::json!({ "project": project_id, "content": content }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    // === Label Operations ===
+
+    /// Add labels to a pull request, keeping any labels it already has.
+    ///
+    /// Returns the full set of labels on the PR after the update.
+    ///
+    /// # Errors
+    /// Returns error if request fails.
+    pub async fn add_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        labels: crate::types::AddLabels,
+    ) -> Result<Vec<crate::types::Label>> {
+        self.post(
+            &format!("/repos/{owner}/{repo}/issues/{pr_number}/labels"),
+            &labels,
+        )
+        .await
+    }
+
+    // === Review Operations ===
+
+    /// Request reviews on a pull request from users and teams.
+    ///
+    /// # Errors
+    /// Returns error if request fails, e.g. when a reviewer is the PR's author
+    /// or can't access the repository.
+    pub async fn request_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        request: crate::types::RequestReviewers,
+    ) -> Result<()> {
+        let _: serde_json::Value = self
+            .post(
+                &format!("/repos/{owner}/{repo}/pulls/{pr_number}/requested_reviewers"),
+                &request,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Reviews submitted on a PR, oldest first. Reviews by deleted users
+    /// are left out.
+    ///
+    /// # Errors
+    /// Returns error if PR not found or API call fails.
+    pub async fn get_reviews(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<Vec<crate::types::Review>> {
+        #[derive(serde::Deserialize)]
+        struct ApiReview {
+            user: Option<ApiUser>,
+            state: crate::types::ReviewState,
+        }
+
+        let reviews: Vec<ApiReview> = self
+            .get(&format!(
+                "/repos/{owner}/{repo}/pulls/{number}/reviews?per_page=100"
+            ))
+            .await?;
+        Ok(reviews
+            .into_iter()
+            .filter_map(|r| {
+                Some(crate::types::Review {
+                    author: r.user?.login,
+                    state: r.state,
+                })
+            })
+            .collect())
+    }
+
+    /// Everyone ever asked to review a PR, plus anyone who reviewed it
+    /// unasked: logins, and `owner/slug` for teams.
+    ///
+    /// Unlike [`Self::get_review_requests`], reviewers stay listed after
+    /// they respond.
+    ///
+    /// # Errors
+    /// Returns error if PR not found or API call fails.
+    pub async fn get_reviewers(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<String>> {
+        #[derive(serde::Deserialize)]
+        struct Event {
+            #[serde(rename = "event")]
+            kind: String,
+            requested_reviewer: Option<ApiUser>,
+            requested_team: Option<ApiTeam>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct ApiTeam {
+            slug: String,
+        }
+
+        let events: Vec<Event> = self
+            .get(&format!(
+                "/repos/{owner}/{repo}/issues/{number}/events?per_page=100"
+            ))
+            .await?;
+        let reviews = self.get_reviews(owner, repo, number).await?;
+
+        let mut reviewers: Vec<String> = vec![];
+        let requested = events
+            .into_iter()
+            .filter(|e| e.kind == "review_requested")
+            .flat_map(|e| {
+                let team = e.requested_team.map(|t| format!("{owner}/{}", t.slug));
+                e.requested_reviewer
+                    .map(|u| u.login)
+                    .into_iter()
+                    .chain(team)
+            });
+        let reviewed = reviews.into_iter().map(|r| r.author);
+        for name in requested.chain(reviewed) {
+            if !reviewers.contains(&name) {
+                reviewers.push(name);
+            }
+        }
+        Ok(reviewers)
+    }
 }
 
 impl std::fmt::Debug for GitHubClient {
@@ -686,7 +1493,8 @@ impl std::fmt::Debug for GitHubClient {
 
 /// Build a GraphQL query to fetch multiple PRs in a single request.
 fn build_graphql_pr_query(numbers: &[u64]) -> String {
-    const PR_FIELDS: &str = "number state merged isDraft headRefName baseRefName url";
+    const PR_FIELDS: &str =
+        "number title state merged isDraft headRefName headRefOid baseRefName url updatedAt";
 
     let pr_queries: Vec<String> = numbers
         .iter()
@@ -699,3 +1507,416 @@ fn build_graphql_pr_query(numbers: &[u64]) -> String {
         pr_queries = pr_queries.join(" ")
     )
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Serve `{"login":"octo"}` to every request, keeping connections open,
+    /// and count the connections accepted.
+    async fn keep_alive_server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0; 4096];
+                    while stream.read(&mut buf).await.is_ok_and(|n| n > 0) {
+                        let body = r#"{"login":"octo"}"#;
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                            body.len()
+                        );
+                        if stream.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (url, connections)
+    }
+
+    /// Answer every request with the last of a 60-request budget, logging in
+    /// as `octo` only with a token, and count the requests.
+    async fn last_request_server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let counted = Arc::clone(&counted);
+                tokio::spawn(async move {
+                    let mut buf = [0; 4096];
+                    while let Ok(n @ 1..) = stream.read(&mut buf).await {
+                        counted.fetch_add(1, Ordering::SeqCst);
+                        let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                        let login = if request.contains("authorization:") {
+                            "octo"
+                        } else {
+                            "anonymous"
+                        };
+                        let body = format!(r#"{{"login":"{login}"}}"#);
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                             x-ratelimit-limit: 60\r\nx-ratelimit-remaining: 0\r\n\
+                             x-ratelimit-reset: 9999999999\r\ncontent-length: {}\r\n\r\n{body}",
+                            body.len()
+                        );
+                        if stream.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (url, requests)
+    }
+
+    /// Reject the first `rejections` requests with the 422 GitHub gives for
+    /// an unknown head branch, then answer with PR #5. Counts the requests.
+    async fn unindexed_head_server(rejections: usize) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let counted = Arc::clone(&counted);
+                tokio::spawn(async move {
+                    let mut buf = [0; 4096];
+                    while let Ok(1..) = stream.read(&mut buf).await {
+                        let (status, body) = if counted.fetch_add(1, Ordering::SeqCst) < rejections
+                        {
+                            (
+                                "422 Unprocessable Entity",
+                                r#"{"message":"Validation Failed","errors":[{"resource":"PullRequest","field":"head","code":"invalid"}]}"#.to_string(),
+                            )
+                        } else {
+                            let branch = r#"{"ref":"feature","sha":"abc"}"#;
+                            (
+                                "201 Created",
+                                format!(
+                                    r#"{{"number":5,"title":"t","body":null,"state":"open","draft":false,"html_url":"u","head":{branch},"base":{branch},"mergeable":null,"mergeable_state":null}}"#
+                                ),
+                            )
+                        };
+                        let response = format!(
+                            "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                            body.len()
+                        );
+                        if stream.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (url, requests)
+    }
+
+    /// Answer each request with the body of the first route found in its
+    /// request line and body, or a 404, recording each request as both.
+    async fn routed_server(routes: Vec<(String, String)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&requests);
+        let routes = Arc::new(routes);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let recorded = Arc::clone(&recorded);
+                let routes = Arc::clone(&routes);
+                tokio::spawn(async move {
+                    let mut request = vec![];
+                    let mut buf = [0; 4096];
+                    let body_start = loop {
+                        let Ok(n @ 1..) = stream.read(&mut buf).await else {
+                            return;
+                        };
+                        request.extend_from_slice(&buf[..n]);
+                        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            break end + 4;
+                        }
+                    };
+                    let head = String::from_utf8_lossy(&request[..body_start]).to_string();
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse().ok())?
+                        })
+                        .unwrap_or(0);
+                    while request.len() < body_start + length {
+                        let Ok(n @ 1..) = stream.read(&mut buf).await else {
+                            break;
+                        };
+                        request.extend_from_slice(&buf[..n]);
+                    }
+                    let line = head.lines().next().unwrap_or_default();
+                    let request = format!(
+                        "{} {}",
+                        line.split(" HTTP/").next().unwrap_or_default(),
+                        String::from_utf8_lossy(&request[body_start..])
+                    );
+                    recorded.lock().unwrap().push(request.clone());
+
+                    let (status, body) = routes
+                        .iter()
+                        .find(|(pattern, _)| request.contains(pattern.as_str()))
+                        .map_or(
+                            ("404 Not Found", r#"{"message":"Not Found"}"#),
+                            |(_, body)| ("200 OK", body.as_str()),
+                        );
+                    let response = format!(
+                        "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).await.ok();
+                });
+            }
+        });
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn test_create_pr_retries_unindexed_head() {
+        let pr = CreatePullRequest {
+            title: "t".into(),
+            body: String::new(),
+            head: "feature".into(),
+            base: "main".into(),
+            draft: false,
+        };
+        let retries = [Duration::from_millis(1); 2];
+        let auth = Auth::Token(SecretString::from("test-token"));
+
+        let (url, requests) = unindexed_head_server(2).await;
+        let client = GitHubClient::with_base_url(&auth, &url).unwrap();
+        let created = client
+            .create_pr_retrying("o", "r", &pr, &retries)
+            .await
+            .unwrap();
+        assert_eq!(created.number, 5);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // Bounded: gives up once the retries run out
+        let (url, requests) = unindexed_head_server(usize::MAX).await;
+        let client = GitHubClient::with_base_url(&auth, &url).unwrap();
+        let result = client.create_pr_retrying("o", "r", &pr, &retries).await;
+        assert!(result.is_err_and(|e| e.is_unindexed_head()));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_is_unindexed_head() {
+        let error = |status, message: &str| Error::ApiError {
+            status,
+            message: message.to_string(),
+        };
+        assert!(
+            error(422, r#"{"errors":[{"field": "head", "code": "invalid"}]}"#).is_unindexed_head()
+        );
+        assert!(error(422, r#"{"message":"Head sha can't be blank"}"#).is_unindexed_head());
+        assert!(
+            !error(
+                422,
+                r#"{"errors":[{"message":"No commits between main and feature"}]}"#
+            )
+            .is_unindexed_head()
+        );
+        assert!(
+            !error(404, r#"{"errors":[{"field":"head","code":"invalid"}]}"#).is_unindexed_head()
+        );
+        assert!(!Error::RateLimited.is_unindexed_head());
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_client() {
+        let (url, requests) = last_request_server().await;
+        let client = GitHubClient::anonymous(&url).unwrap();
+        assert!(client.is_anonymous());
+
+        assert_eq!(client.current_user().await.unwrap(), "anonymous");
+        assert!(client.budget_spent());
+        // Spent: refused without asking
+        assert!(matches!(
+            client.current_user().await,
+            Err(Error::RateLimited)
+        ));
+        // Writes need a token
+        let comment = crate::types::CreateComment { body: "hi".into() };
+        assert!(matches!(
+            client.create_pr_comment("o", "r", 1, comment).await,
+            Err(Error::NoToken)
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let auth = Auth::Token(SecretString::from("test-token"));
+        let client = GitHubClient::with_base_url(&auth, &url).unwrap();
+        assert!(!client.is_anonymous());
+        assert_eq!(client.current_user().await.unwrap(), "octo");
+    }
+
+    #[tokio::test]
+    async fn test_clients_share_connections() {
+        let (url, connections) = keep_alive_server().await;
+        let auth = Auth::Token(SecretString::from("test-token"));
+
+        let first = GitHubClient::with_base_url(&auth, &url).unwrap();
+        assert_eq!(first.current_user().await.unwrap(), "octo");
+        assert_eq!(first.current_user().await.unwrap(), "octo");
+        let second = GitHubClient::with_base_url(&auth, &url).unwrap();
+        assert_eq!(second.current_user().await.unwrap(), "octo");
+
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_find_milestone_pages_through_all() {
+        let page = |range: std::ops::Range<u64>| {
+            serde_json::Value::from(
+                range
+                    .map(|n| serde_json::json!({ "number": n, "title": format!("v{n}") }))
+                    .collect::<Vec<_>>(),
+            )
+            .to_string()
+        };
+        let (url, requests) = routed_server(vec![
+            ("&page=1 ".into(), page(1..101)),
+            ("&page=2 ".into(), page(101..103)),
+        ])
+        .await;
+        let auth = Auth::Token(SecretString::from("test-token"));
+        let client = GitHubClient::with_base_url(&auth, &url).unwrap();
+
+        let found = client.find_milestone("o", "r", "v102").await.unwrap();
+        assert_eq!(found.map(|m| m.number), Some(102));
+        // Stops at the page that has it
+        let found = client.find_milestone("o", "r", "v3").await.unwrap();
+        assert_eq!(found.map(|m| m.number), Some(3));
+        // Stops after the last, short page
+        assert!(
+            client
+                .find_milestone("o", "r", "nope")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let requests = requests.lock().unwrap().clone();
+        let pages: Vec<_> = requests
+            .iter()
+            .map(|r| r.split("&page=").nth(1).unwrap_or_default().trim())
+            .collect();
+        assert_eq!(pages, ["1", "2", "1", "1", "2"]);
+        assert!(requests[0].starts_with("GET /repos/o/r/milestones?state=open"));
+    }
+
+    #[tokio::test]
+    async fn test_add_to_project() {
+        let ids = serde_json::json!({ "data": { "repository": {
+            "pullRequest": { "id": "PR_1" },
+            "owner": { "projectV2": { "id": "PVT_4" } },
+        } } });
+        let added =
+            serde_json::json!({ "data": { "addProjectV2ItemById": { "item": { "id": "I" } } } });
+        let (url, requests) = routed_server(vec![
+            ("addProjectV2ItemById".into(), added.to_string()),
+            ("POST /graphql".into(), ids.to_string()),
+        ])
+        .await;
+        let auth = Auth::Token(SecretString::from("test-token"));
+        let client = GitHubClient::with_base_url(&auth, &url).unwrap();
+        client.add_to_project("o", "r", 7, 4).await.unwrap();
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains(r#""pr":7"#) && requests[0].contains(r#""project":4"#));
+        assert!(requests[1].contains("addProjectV2ItemById"));
+        assert!(requests[1].contains(r#""project":"PVT_4""#));
+        assert!(requests[1].contains(r#""content":"PR_1""#));
+
+        // A project the owner doesn't have
+        let missing = serde_json::json!({ "data": { "repository": {
+            "pullRequest": { "id": "PR_1" },
+            "owner": { "projectV2": null },
+        } } });
+        let (url, requests) =
+            routed_server(vec![("POST /graphql".into(), missing.to_string())]).await;
+        let client = GitHubClient::with_base_url(&auth, &url).unwrap();
+        let error = client.add_to_project("o", "r", 7, 4).await.unwrap_err();
+        assert!(
+            error.to_string().contains("project #4 not found for o"),
+            "{error}"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_find_and_reopen_closed_pr() {
+        let pr = |number: u64, state: &str, merged_at: Option<&str>| {
+            serde_json::json!({
+                "number": number, "title": "t", "body": null, "state": state,
+                "merged_at": merged_at, "draft": false, "html_url": "u",
+                "head": { "ref": "feature", "sha": "abc" },
+                "base": { "ref": "main", "sha": "def" },
+                "mergeable": null, "mergeable_state": null,
+            })
+        };
+        // The most recently updated one was merged, so it's passed over
+        let closed = serde_json::json!([
+            pr(9, "closed", Some("2026-01-02T00:00:00Z")),
+            pr(7, "closed", None),
+        ]);
+        let (url, requests) = routed_server(vec![
+            ("head=o:feature&state=closed".into(), closed.to_string()),
+            (
+                "head=o:landed&state=closed".into(),
+                serde_json::json!([pr(9, "closed", Some("2026-01-02T00:00:00Z"))]).to_string(),
+            ),
+            (
+                "PATCH /repos/o/r/pulls/7 ".into(),
+                pr(7, "open", None).to_string(),
+            ),
+        ])
+        .await;
+        let auth = Auth::Token(SecretString::from("test-token"));
+        let client = GitHubClient::with_base_url(&auth, &url).unwrap();
+
+        let found = client
+            .find_closed_pr_for_branch("o", "r", "feature")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.number, 7);
+        assert_eq!(found.state, PullRequestState::Closed);
+        assert!(
+            client
+                .find_closed_pr_for_branch("o", "r", "landed")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let reopened = client.reopen_pr("o", "r", 7).await.unwrap();
+        assert_eq!(reopened.state, PullRequestState::Open);
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(
+            requests.last().map(String::as_str),
+            Some(r#"PATCH /repos/o/r/pulls/7 {"state":"open"}"#)
+        );
+    }
+}
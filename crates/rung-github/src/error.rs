@@ -11,7 +11,9 @@ pub enum Error {
     AuthenticationFailed,
 
     /// Token not found.
-    #[error("no GitHub token found - run `gh auth login` or set GITHUB_TOKEN")]
+    #[error(
+        "no GitHub token found - run `gh auth login`, set GITHUB_TOKEN, or store a token in git's credential helper"
+    )]
     NoToken,
 
     /// API rate limit exceeded.
@@ -38,7 +40,35 @@ pub enum Error {
     #[error("failed to parse GitHub response: {0}")]
     Parse(#[from] serde_json::Error),
 
+    /// Interrupted while waiting on GitHub.
+    #[error("cancelled while waiting on GitHub")]
+    Cancelled,
+
+    /// Gave up waiting on GitHub.
+    #[error("timed out after {}s waiting on GitHub", .0.as_secs())]
+    PollTimeout(std::time::Duration),
+
     /// IO error (e.g., reading gh CLI token).
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 }
+
+impl Error {
+    /// Whether GitHub rejected a PR because it doesn't know the head branch
+    /// or its commit, as happens for a branch pushed moments before that
+    /// isn't indexed yet.
+    #[must_use]
+    pub fn is_unindexed_head(&self) -> bool {
+        let Self::ApiError {
+            status: 422,
+            message,
+        } = self
+        else {
+            return false;
+        };
+        let message = message.to_lowercase().replace(' ', "");
+        message.contains(r#""field":"head","code":"invalid""#)
+            || message.contains("headsha")
+            || message.contains("notallrefsarereadable")
+    }
+}
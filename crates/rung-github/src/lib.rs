@@ -11,14 +11,18 @@
 mod auth;
 mod client;
 mod error;
+mod poll;
 mod types;
 
 pub use auth::Auth;
-pub use client::GitHubClient;
+pub use client::{GitHubClient, RECENTLY_CLOSED_LIMIT, api_time};
 pub use error::{Error, Result};
+pub use poll::{PollOutcome, Poller};
 // Re-export SecretString for constructing Auth::Token
 pub use secrecy::SecretString;
 pub use types::{
-    CheckRun, CheckStatus, CreateComment, CreatePullRequest, IssueComment, MergeMethod,
-    MergePullRequest, MergeResult, PullRequest, PullRequestState, UpdateComment, UpdatePullRequest,
+    AddLabels, ApiTime, CheckRun, CheckStatus, CreateComment, CreatePullRequest, HttpStatus,
+    IssueComment, Label, MergeMethod, MergePullRequest, MergeResult, MergeSettings, Milestone,
+    PullRequest, PullRequestState, RateLimit, RequestReviewers, Review, ReviewRequests,
+    ReviewState, UpdateComment, UpdatePullRequest,
 };
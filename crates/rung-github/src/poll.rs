@@ -0,0 +1,284 @@
+//! Waiting on GitHub state.
+//!
+//! [`Poller`] owns the sleep/retry logic for anything that has to wait for
+//! GitHub (a PR becoming mergeable, checks finishing, ...): a base interval
+//! with random jitter, exponential back-off when rate limited or on transient
+//! failures, an overall timeout, and cancellation via Ctrl-C. With
+//! [`Poller::run_paced`] it also slows down while the API budget runs low.
+
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::client::GitHubClient;
+use crate::error::{Error, Result};
+use crate::types::RateLimit;
+
+/// Result of a single poll check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PollOutcome<T> {
+    /// The awaited state was reached.
+    Ready(T),
+    /// Not there yet - check again after the next interval.
+    Pending,
+}
+
+/// Polling schedule shared by all wait loops.
+#[derive(Debug, Clone)]
+pub struct Poller {
+    /// Delay between checks while the state is pending.
+    pub interval: Duration,
+
+    /// Upper bound for the delay after repeated back-off.
+    pub max_interval: Duration,
+
+    /// Maximum random delay added to each sleep, so parallel waiters don't
+    /// hit the API in lockstep.
+    pub jitter: Duration,
+
+    /// Give up after this long. `None` waits indefinitely.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Poller {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            max_interval: Duration::from_secs(120),
+            jitter: Duration::from_secs(3),
+            timeout: Some(Duration::from_secs(60 * 60)),
+        }
+    }
+}
+
+impl Poller {
+    /// Create a poller with the given interval and default limits.
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            ..Self::default()
+        }
+    }
+
+    /// Run `check` until it reports [`PollOutcome::Ready`].
+    ///
+    /// Rate limiting, network errors and 5xx responses are retried with
+    /// exponential back-off (capped at `max_interval`); a successful pending
+    /// check resets the delay to `interval`.
+    ///
+    /// # Errors
+    /// Returns `Cancelled` on Ctrl-C, `PollTimeout` once the timeout elapses,
+    /// or the first non-retryable error returned by `check`.
+    pub async fn run<T, F, Fut>(&self, check: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<PollOutcome<T>>>,
+    {
+        self.run_with_budget(check, || None).await
+    }
+
+    /// Like [`run`](Self::run), but while `client`'s rate limit budget is
+    /// low, spread the checks left over the time until it resets rather than
+    /// running it dry.
+    ///
+    /// # Errors
+    /// As for [`run`](Self::run).
+    pub async fn run_paced<T, F, Fut>(&self, client: &GitHubClient, check: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<PollOutcome<T>>>,
+    {
+        self.run_with_budget(check, || client.rate_limit()).await
+    }
+
+    /// The poll loop, stretching each delay to suit the budget `budget`
+    /// reports.
+    async fn run_with_budget<T, F, Fut>(
+        &self,
+        mut check: F,
+        budget: impl Fn() -> Option<RateLimit>,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<PollOutcome<T>>>,
+    {
+        let start = Instant::now();
+        let mut delay = self.interval;
+
+        loop {
+            let outcome = tokio::select! {
+                outcome = check() => outcome,
+                _ = tokio::signal::ctrl_c() => return Err(Error::Cancelled),
+            };
+
+            delay = match outcome {
+                Ok(PollOutcome::Ready(value)) => return Ok(value),
+                Ok(PollOutcome::Pending) => self.interval,
+                Err(e) if is_retryable(&e) => self.back_off(delay),
+                Err(e) => return Err(e),
+            };
+
+            let paced = budget().map_or(delay, |rate_limit| {
+                rate_limit.paced_interval(delay, unix_now())
+            });
+            let mut sleep = paced + random_jitter(self.jitter);
+            if let Some(timeout) = self.timeout {
+                let remaining = timeout.saturating_sub(start.elapsed());
+                if remaining.is_zero() {
+                    return Err(Error::PollTimeout(timeout));
+                }
+                sleep = sleep.min(remaining);
+            }
+
+            tokio::select! {
+                () = tokio::time::sleep(sleep) => {}
+                _ = tokio::signal::ctrl_c() => return Err(Error::Cancelled),
+            }
+        }
+    }
+
+    /// Double the delay, capped at `max_interval`.
+    fn back_off(&self, delay: Duration) -> Duration {
+        (delay * 2).min(self.max_interval).max(self.interval)
+    }
+}
+
+/// Errors worth waiting out rather than surfacing.
+const fn is_retryable(error: &Error) -> bool {
+    match error {
+        Error::RateLimited | Error::Network(_) => true,
+        Error::ApiError { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+/// Seconds since the Unix epoch, as in GitHub's rate limit reset header.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+/// A random duration in `[0, max)`.
+fn random_jitter(max: Duration) -> Duration {
+    let max_ms = u64::try_from(max.as_millis()).unwrap_or(u64::MAX);
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    // RandomState is seeded per instance, which is plenty for spreading requests
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    Duration::from_millis(random % max_ms)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn fast_poller() -> Poller {
+        Poller {
+            interval: Duration::from_millis(1),
+            max_interval: Duration::from_millis(4),
+            jitter: Duration::ZERO,
+            timeout: Some(Duration::from_secs(5)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_polls_until_ready() {
+        let mut calls = 0;
+        let value = fast_poller()
+            .run(|| {
+                calls += 1;
+                let outcome = if calls < 3 {
+                    PollOutcome::Pending
+                } else {
+                    PollOutcome::Ready(calls)
+                };
+                async move { Ok(outcome) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limit_but_not_other_errors() {
+        let mut calls = 0;
+        let value = fast_poller()
+            .run(|| {
+                calls += 1;
+                let result = if calls == 1 {
+                    Err(Error::RateLimited)
+                } else {
+                    Ok(PollOutcome::Ready("done"))
+                };
+                async move { result }
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, "done");
+
+        let result: Result<()> = fast_poller()
+            .run(|| async { Err(Error::PrNotFound(7)) })
+            .await;
+        assert!(matches!(result, Err(Error::PrNotFound(7))));
+    }
+
+    #[tokio::test]
+    async fn test_times_out() {
+        let poller = Poller {
+            timeout: Some(Duration::from_millis(20)),
+            ..fast_poller()
+        };
+        let result: Result<()> = poller.run(|| async { Ok(PollOutcome::Pending) }).await;
+        assert!(matches!(result, Err(Error::PollTimeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_low_budget_stretches_interval() {
+        let poller = Poller {
+            timeout: Some(Duration::from_millis(50)),
+            ..fast_poller()
+        };
+        let low = RateLimit {
+            limit: 5000,
+            remaining: 1,
+            reset: unix_now() + 1000,
+        };
+        let mut calls = 0;
+        let result: Result<()> = poller
+            .run_with_budget(
+                || {
+                    calls += 1;
+                    async { Ok(PollOutcome::Pending) }
+                },
+                || Some(low),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::PollTimeout(_))));
+        // One wait for the reset, cut short by the timeout
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_back_off_and_jitter_bounds() {
+        let poller = fast_poller();
+        assert_eq!(
+            poller.back_off(Duration::from_millis(1)),
+            Duration::from_millis(2)
+        );
+        assert_eq!(
+            poller.back_off(Duration::from_millis(3)),
+            Duration::from_millis(4)
+        );
+
+        assert_eq!(random_jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..20 {
+            assert!(random_jitter(Duration::from_millis(10)) < Duration::from_millis(10));
+        }
+    }
+}
@@ -1,5 +1,8 @@
 //! GitHub API types.
 
+use std::fmt;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
 /// A GitHub Pull Request.
@@ -23,6 +26,9 @@ pub struct PullRequest {
     /// Head branch name.
     pub head_branch: String,
 
+    /// Commit SHA the head branch points to on GitHub.
+    pub head_sha: String,
+
     /// Base branch name.
     pub base_branch: String,
 
@@ -34,6 +40,19 @@ pub struct PullRequest {
 
     /// The mergeable state (e.g., "clean", "dirty", "blocked", "behind").
     pub mergeable_state: Option<String>,
+
+    /// Login of the PR's author (None if not fetched).
+    #[serde(default)]
+    pub author: Option<String>,
+
+    /// When the PR last changed, as an ISO 8601 timestamp (None if not fetched).
+    #[serde(default)]
+    pub updated_at: Option<String>,
+
+    /// Commit that landed a merged PR on its base: the squash or merge
+    /// commit, or the last commit rebased (None if not merged or not fetched).
+    #[serde(default)]
+    pub merge_commit_sha: Option<String>,
 }
 
 /// State of a pull request.
@@ -48,6 +67,53 @@ pub enum PullRequestState {
     Merged,
 }
 
+/// Reviews a PR is still waiting for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReviewRequests {
+    /// Logins of requested reviewers who haven't reviewed yet.
+    pub users: Vec<String>,
+
+    /// Slugs of requested teams whose review is still outstanding.
+    pub teams: Vec<String>,
+
+    /// When the PR was opened, as RFC 3339.
+    pub opened_at: Option<String>,
+}
+
+impl ReviewRequests {
+    /// Whether no review is outstanding.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.teams.is_empty()
+    }
+}
+
+/// A review submitted on a PR.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Review {
+    /// Login of the reviewer.
+    pub author: String,
+
+    /// What the review concluded.
+    pub state: ReviewState,
+}
+
+/// What a review concluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewState {
+    /// Approved the changes.
+    Approved,
+    /// Asked for changes before merging.
+    ChangesRequested,
+    /// Only left comments.
+    Commented,
+    /// An approval or change request since dismissed.
+    Dismissed,
+    /// Started but not yet submitted.
+    Pending,
+}
+
 /// A CI check run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckRun {
@@ -59,6 +125,13 @@ pub struct CheckRun {
 
     /// URL to view check details.
     pub details_url: Option<String>,
+
+    /// When the check started, as RFC 3339. Statuses reported through the
+    /// legacy commit status API have no timing.
+    pub started_at: Option<String>,
+
+    /// When the check completed, as RFC 3339.
+    pub completed_at: Option<String>,
 }
 
 /// Status of a CI check.
@@ -80,6 +153,17 @@ pub enum CheckStatus {
 }
 
 impl CheckStatus {
+    /// Map the `state` of a legacy commit status (`success`, `pending`,
+    /// `failure` or `error`).
+    #[must_use]
+    pub fn from_commit_status(state: &str) -> Self {
+        match state {
+            "success" => Self::Success,
+            "pending" => Self::InProgress,
+            _ => Self::Failure,
+        }
+    }
+
     /// Check if this status indicates success.
     #[must_use]
     pub const fn is_success(&self) -> bool {
@@ -147,6 +231,85 @@ pub enum MergeMethod {
     Rebase,
 }
 
+impl MergeMethod {
+    /// All methods, in the order rung prefers them.
+    pub const ALL: [Self; 3] = [Self::Squash, Self::Merge, Self::Rebase];
+
+    /// Name as used on the command line and by the API.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Squash => "squash",
+            Self::Rebase => "rebase",
+        }
+    }
+
+    /// Parse a method name, ignoring case.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|method| method.as_str().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for MergeMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Merge methods enabled in a repository's settings, and what happens to
+/// head branches after a merge.
+///
+/// GitHub only reports these to users who can push; when they're missing,
+/// every method is assumed to be allowed and the merge call has the final say.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // Mirrors GitHub's repository fields
+pub struct MergeSettings {
+    /// Squash merging is enabled.
+    #[serde(default = "default_true")]
+    pub allow_squash_merge: bool,
+
+    /// Merge commits are enabled.
+    #[serde(default = "default_true")]
+    pub allow_merge_commit: bool,
+
+    /// Rebase merging is enabled.
+    #[serde(default = "default_true")]
+    pub allow_rebase_merge: bool,
+
+    /// GitHub deletes head branches once their PR is merged.
+    #[serde(default)]
+    pub delete_branch_on_merge: bool,
+}
+
+impl MergeSettings {
+    /// Whether `method` is enabled.
+    #[must_use]
+    pub const fn allows(&self, method: MergeMethod) -> bool {
+        match method {
+            MergeMethod::Merge => self.allow_merge_commit,
+            MergeMethod::Squash => self.allow_squash_merge,
+            MergeMethod::Rebase => self.allow_rebase_merge,
+        }
+    }
+
+    /// Enabled methods, preferred first.
+    #[must_use]
+    pub fn allowed(&self) -> Vec<MergeMethod> {
+        MergeMethod::ALL
+            .into_iter()
+            .filter(|&method| self.allows(method))
+            .collect()
+    }
+}
+
+const fn default_true() -> bool {
+    true
+}
+
 /// Request to merge a pull request.
 #[derive(Debug, Serialize)]
 pub struct MergePullRequest {
@@ -198,3 +361,198 @@ pub struct UpdateComment {
     /// New comment body.
     pub body: String,
 }
+
+/// A label attached to an issue or pull request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Label {
+    /// Label name.
+    pub name: String,
+}
+
+/// The status GitHub answered one request with, kept for bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HttpStatus {
+    /// HTTP method.
+    pub method: String,
+    /// Path under the API base, e.g. `/repos/acme/widgets/pulls/1`.
+    pub path: String,
+    /// HTTP status code.
+    pub status: u16,
+}
+
+/// GitHub API requests made so far and the time spent waiting on them
+/// (see [`crate::api_time`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApiTime {
+    /// Requests answered.
+    pub requests: u64,
+    /// Total time from sending each request to receiving its response.
+    pub waited: Duration,
+}
+
+/// The API rate limit budget, as of the latest response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RateLimit {
+    /// Requests allowed per window.
+    pub limit: u64,
+    /// Requests left in the current window.
+    pub remaining: u64,
+    /// When the window resets, in seconds since the Unix epoch.
+    pub reset: u64,
+}
+
+impl RateLimit {
+    /// Below this share of the limit, in percent, the budget counts as low.
+    pub const LOW_PERCENT: u64 = 20;
+
+    /// Whether less than [`Self::LOW_PERCENT`] of the budget is left.
+    #[must_use]
+    pub const fn is_low(&self) -> bool {
+        self.remaining * 100 < self.limit * Self::LOW_PERCENT
+    }
+
+    /// The delay between polls that makes a low budget last until the reset:
+    /// the time left spread over the requests left, and never under
+    /// `interval`. `now` is in seconds since the Unix epoch.
+    #[must_use]
+    pub fn paced_interval(&self, interval: Duration, now: u64) -> Duration {
+        if !self.is_low() {
+            return interval;
+        }
+        let until_reset = Duration::from_secs(self.reset.saturating_sub(now));
+        let requests = u32::try_from(self.remaining.max(1)).unwrap_or(u32::MAX);
+        interval.max(until_reset / requests)
+    }
+}
+
+/// A repository milestone.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Milestone {
+    /// Milestone number (not its ID).
+    pub number: u64,
+
+    /// Milestone title.
+    pub title: String,
+}
+
+/// Request to add labels to an issue/PR.
+#[derive(Debug, Serialize)]
+pub struct AddLabels {
+    /// Label names to add. Labels that don't exist yet are created by GitHub.
+    pub labels: Vec<String>,
+}
+
+/// Request to ask users and teams to review a PR.
+#[derive(Debug, Serialize)]
+pub struct RequestReviewers {
+    /// Logins of users to request.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<String>,
+    /// Slugs of teams (in the repository's organization) to request.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub team_reviewers: Vec<String>,
+}
+
+impl RequestReviewers {
+    /// Split `names` into users and teams, where `org/team` names a team.
+    #[must_use]
+    pub fn from_names(names: &[String]) -> Self {
+        let (teams, users): (Vec<&String>, Vec<&String>) =
+            names.iter().partition(|name| name.contains('/'));
+        Self {
+            reviewers: users.into_iter().cloned().collect(),
+            team_reviewers: teams
+                .into_iter()
+                .filter_map(|team| team.split_once('/').map(|(_, slug)| slug.to_string()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_pacing() {
+        let interval = Duration::from_secs(15);
+        let plenty = RateLimit {
+            limit: 5000,
+            remaining: 4000,
+            reset: 3600,
+        };
+        assert!(!plenty.is_low());
+        assert_eq!(plenty.paced_interval(interval, 0), interval);
+
+        // 100 requests over the 50 minutes left: one every 30s
+        let low = RateLimit {
+            remaining: 100,
+            ..plenty
+        };
+        assert!(low.is_low());
+        assert_eq!(low.paced_interval(interval, 600), Duration::from_secs(30));
+        // Never faster than asked, and waits out an empty budget
+        assert_eq!(low.paced_interval(interval, 3590), interval);
+        let empty = RateLimit {
+            remaining: 0,
+            ..plenty
+        };
+        assert_eq!(
+            empty.paced_interval(interval, 600),
+            Duration::from_secs(3000)
+        );
+    }
+
+    #[test]
+    fn test_commit_status_mapping() {
+        assert_eq!(
+            CheckStatus::from_commit_status("success"),
+            CheckStatus::Success
+        );
+        assert!(CheckStatus::from_commit_status("pending").is_pending());
+        assert!(CheckStatus::from_commit_status("failure").is_failure());
+        assert!(CheckStatus::from_commit_status("error").is_failure());
+    }
+
+    #[test]
+    fn test_merge_method_names() {
+        for method in MergeMethod::ALL {
+            assert_eq!(MergeMethod::from_name(&method.to_string()), Some(method));
+        }
+        assert_eq!(MergeMethod::from_name("Squash"), Some(MergeMethod::Squash));
+        assert_eq!(MergeMethod::from_name("fast-forward"), None);
+    }
+
+    #[test]
+    fn test_merge_settings() {
+        let settings: MergeSettings = serde_json::from_str(
+            r#"{"allow_squash_merge": false, "allow_merge_commit": true, "allow_rebase_merge": true}"#,
+        )
+        .unwrap();
+        assert!(!settings.allows(MergeMethod::Squash));
+        assert_eq!(
+            settings.allowed(),
+            vec![MergeMethod::Merge, MergeMethod::Rebase]
+        );
+
+        // Not reported without push access
+        let settings: MergeSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.allowed(), MergeMethod::ALL.to_vec());
+        assert!(!settings.delete_branch_on_merge);
+    }
+
+    #[test]
+    fn test_request_reviewers_splits_teams() {
+        let request = RequestReviewers::from_names(&["octocat".into(), "acme/platform".into()]);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({"reviewers": ["octocat"], "team_reviewers": ["platform"]})
+        );
+        let users_only = RequestReviewers::from_names(&["octocat".into()]);
+        assert_eq!(
+            serde_json::to_value(&users_only).unwrap(),
+            serde_json::json!({"reviewers": ["octocat"]})
+        );
+    }
+}
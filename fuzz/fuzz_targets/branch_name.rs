@@ -0,0 +1,22 @@
+//! Branch name validation: names that pass must be safe to hand to git as
+//! command-line arguments and in refspecs.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rung_core::BranchName;
+
+fuzz_target!(|name: &str| {
+    if let Ok(branch) = BranchName::new(name) {
+        let name = branch.as_str();
+        assert!(!name.is_empty());
+        assert!(!name.starts_with(['-', '.', '/']) && !name.ends_with(['.', '/']));
+        assert!(!name.contains("..") && !name.contains("//") && !name.contains("@{"));
+        assert!(
+            !name
+                .chars()
+                .any(|c| c.is_ascii_control() || " ~^:?*[\\$;|&<>`\"'(){}!".contains(c)),
+            "{name:?}"
+        );
+    }
+});
@@ -0,0 +1,32 @@
+//! Remote URL parsing: whatever the URL and configured hosts, a parsed remote
+//! names an allowed host and an owner and repository that are safe to put in
+//! API paths.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rung_git::GitHubRemote;
+
+fuzz_target!(|input: (&str, Vec<String>)| {
+    let (url, hosts) = input;
+    let Ok(remote) = GitHubRemote::parse(url, &hosts) else {
+        return;
+    };
+    assert!(
+        remote.host == GitHubRemote::DEFAULT_HOST
+            || hosts.iter().any(|h| h.eq_ignore_ascii_case(&remote.host))
+    );
+    for part in [&remote.owner, &remote.repo] {
+        assert!(!part.is_empty() && part != "." && part != "..");
+        assert!(
+            part.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')),
+            "{part:?}"
+        );
+    }
+    assert_eq!(
+        remote.to_string(),
+        format!("{}/{}", remote.owner, remote.repo)
+    );
+    let _ = (remote.api_url(), remote.web_url());
+});
@@ -0,0 +1,15 @@
+//! Slugifying commit messages: the result is short and, when not empty, a
+//! valid branch name (`rung create -m` relies on this).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rung_core::{BranchName, slugify};
+
+fuzz_target!(|message: &str| {
+    let slug = slugify(message);
+    assert!(slug.chars().count() <= 50, "{slug:?}");
+    if !slug.is_empty() {
+        assert!(BranchName::new(&slug).is_ok(), "{slug:?}");
+    }
+});